@@ -0,0 +1,48 @@
+use crate::i18n::tr;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// 定义界面的外观：跟随系统的深色模式设置，或者固定使用浅色或深色。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Appearance {
+    #[default]
+    System, // 跟随系统的设置。
+    Light, // 总是使用浅色。
+    Dark,  // 总是使用深色。
+}
+
+impl Appearance {
+    // 所有可选的外观，用于设置界面中的选择列表。
+    pub const ALL: &'static [Self] = &[Self::System, Self::Light, Self::Dark];
+
+    // 判断是否使用深色外观，system_is_dark 为系统当前是否使用深色模式。
+    pub fn is_dark(self, system_is_dark: bool) -> bool {
+        match self {
+            Self::System => system_is_dark,
+            Self::Light => false,
+            Self::Dark => true,
+        }
+    }
+}
+
+impl fmt::Display for Appearance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(tr(match self {
+            Self::System => "Follow System",
+            Self::Light => "Light",
+            Self::Dark => "Dark",
+        }))
+    }
+}
+
+// 检测系统是否使用深色模式，无法确定时按深色处理，与之前默认的深色主题一致。
+pub fn system_is_dark() -> bool {
+    dark_light::detect() != dark_light::Mode::Light
+}
+
+// 在后台线程中检测系统是否使用深色模式。在某些桌面环境中需要查询系统设置，可能会阻塞一段时间。
+pub async fn detect() -> bool {
+    tokio::task::spawn_blocking(system_is_dark)
+        .await
+        .unwrap_or(true)
+}
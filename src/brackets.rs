@@ -0,0 +1,289 @@
+use crate::syntax;
+use crate::tab::Tab;
+use iced::advanced::text::highlighter::{self, Format};
+use iced::widget::text_editor::{self, Action, Edit, Motion};
+use iced::{Color, Font, Theme};
+use std::ops::Range;
+use std::sync::Arc;
+
+// 查找匹配括号时最多扫描的行数，避免在大文件中卡顿。
+const SCAN_LIMIT: usize = 2000;
+
+// 匹配括号的高亮颜色。
+const MATCH_COLOR: Color = Color::from_rgb(1.0, 0.75, 0.0);
+
+// 返回开括号或引号对应的闭合字符。
+pub fn closing(c: char) -> Option<char> {
+    match c {
+        '(' => Some(')'),
+        '[' => Some(']'),
+        '{' => Some('}'),
+        '"' | '\'' | '`' => Some(c),
+        _ => None,
+    }
+}
+
+// 返回闭括号对应的开括号。
+fn opening(c: char) -> Option<char> {
+    match c {
+        ')' => Some('('),
+        ']' => Some('['),
+        '}' => Some('{'),
+        _ => None,
+    }
+}
+
+// 判断字符是否可以在输入时被“跳过”，即光标后已经有自动插入的同一个字符。
+fn is_closer(c: char) -> bool {
+    matches!(c, ')' | ']' | '}' | '"' | '\'' | '`')
+}
+
+// 在标签页中执行一个编辑器动作，启用自动闭合时处理括号和引号的成对输入与删除。
+pub fn edit(tab: &mut Tab, action: Action, auto_close: bool) {
+    if !auto_close || tab.is_read_only || tab.context.selection().is_some() {
+        tab.edit(action);
+        return;
+    }
+
+    let before = char_before_cursor(&tab.context);
+    let after = char_after_cursor(&tab.context);
+
+    match action {
+        // 输入的字符与光标后自动插入的闭合字符相同时，直接跳过它。
+        Action::Edit(Edit::Insert(c)) if is_closer(c) && after == Some(c) => {
+            tab.edit(Action::Move(Motion::Right));
+        }
+        Action::Edit(Edit::Insert(c)) => {
+            let Some(close) = closing(c) else {
+                tab.edit(action);
+                return;
+            };
+
+            // 只在光标后为空白或闭括号时自动闭合，引号紧跟在单词之后（如 it's）时不闭合。
+            let is_quote = c == close;
+            let is_free = match after {
+                Some(c) => c.is_whitespace() || opening(c).is_some(),
+                None => true,
+            };
+            let follows_word = before.is_some_and(|c| c.is_alphanumeric() || c == '_');
+
+            if is_free && !(is_quote && follows_word) {
+                tab.edit(Action::Edit(Edit::Paste(Arc::new(format!("{c}{close}")))));
+                tab.edit(Action::Move(Motion::Left));
+            } else {
+                tab.edit(action);
+            }
+        }
+        // 在一对空括号之间退格时同时删除闭合字符。
+        Action::Edit(Edit::Backspace) if after.is_some() && before.and_then(closing) == after => {
+            tab.edit(Action::Edit(Edit::Delete));
+            tab.edit(action);
+        }
+        _ => tab.edit(action),
+    }
+}
+
+// 返回光标前的一个字符。
+fn char_before_cursor(content: &text_editor::Content) -> Option<char> {
+    let (line, column) = content.cursor_position();
+    let text = content.line(line)?;
+
+    text.get(..column)?.chars().next_back()
+}
+
+// 返回光标后的一个字符。
+fn char_after_cursor(content: &text_editor::Content) -> Option<char> {
+    let (line, column) = content.cursor_position();
+    let text = content.line(line)?;
+
+    text.get(column..)?.chars().next()
+}
+
+// 查找光标处的括号及与之匹配的括号，返回两者的行号和字节位置。
+//
+// 优先检查光标后的字符，其次是光标前的字符。匹配时不区分字符串和注释中的括号。
+pub fn find_match(content: &text_editor::Content) -> Option<[(usize, usize); 2]> {
+    let (line, column) = content.cursor_position();
+    let text = content.line(line)?;
+
+    let candidates = [
+        text.get(column..)
+            .and_then(|rest| rest.chars().next())
+            .map(|c| (column, c)),
+        text.get(..column)
+            .and_then(|head| head.char_indices().next_back()),
+    ];
+
+    candidates.into_iter().flatten().find_map(|(index, c)| {
+        let other = if let Some(close) = closing(c).filter(|close| *close != c) {
+            scan_forward(content, (line, index), c, close)?
+        } else {
+            scan_backward(content, (line, index), opening(c)?, c)?
+        };
+
+        Some([(line, index), other])
+    })
+}
+
+// 从开括号向后查找与之匹配的闭括号。
+fn scan_forward(
+    content: &text_editor::Content,
+    (line, column): (usize, usize),
+    open: char,
+    close: char,
+) -> Option<(usize, usize)> {
+    let mut depth = 0;
+    let last = content.line_count().min(line + SCAN_LIMIT);
+
+    for index in line..last {
+        let text = content.line(index)?;
+        let start = if index == line { column } else { 0 };
+
+        for (offset, c) in text.get(start..)?.char_indices() {
+            if c == open {
+                depth += 1;
+            } else if c == close {
+                depth -= 1;
+
+                if depth == 0 {
+                    return Some((index, start + offset));
+                }
+            }
+        }
+    }
+
+    None
+}
+
+// 从闭括号向前查找与之匹配的开括号。
+fn scan_backward(
+    content: &text_editor::Content,
+    (line, column): (usize, usize),
+    open: char,
+    close: char,
+) -> Option<(usize, usize)> {
+    let mut depth = 0;
+    let first = line.saturating_sub(SCAN_LIMIT);
+
+    for index in (first..=line).rev() {
+        let text = content.line(index)?;
+        let end = if index == line {
+            column + 1
+        } else {
+            text.len()
+        };
+
+        for (offset, c) in text.get(..end)?.char_indices().rev() {
+            if c == close {
+                depth += 1;
+            } else if c == open {
+                depth -= 1;
+
+                if depth == 0 {
+                    return Some((index, offset));
+                }
+            }
+        }
+    }
+
+    None
+}
+
+// 定义高亮器的设置：语法高亮设置及需要标记的一对括号。
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settings {
+    pub syntax: syntax::Settings,
+    pub brackets: Option<[(usize, usize); 2]>,
+}
+
+// 定义高亮的类型。
+pub enum Highlight {
+    Syntax(syntax::Highlight),
+    Bracket,
+}
+
+// 在语法高亮的基础上标记匹配的括号。
+//
+// 编辑器只能通过高亮器改变文字样式，因此把括号位置作为高亮设置的一部分；
+// 只有括号位置变化时，从受影响的最上面一行开始重新高亮，而不是整个文档。
+pub struct Highlighter {
+    syntax: syntax::Highlighter,
+    settings: syntax::Settings,
+    brackets: Option<[(usize, usize); 2]>,
+}
+
+impl highlighter::Highlighter for Highlighter {
+    type Settings = Settings;
+    type Highlight = Highlight;
+    type Iterator<'a> = std::vec::IntoIter<(Range<usize>, Highlight)>;
+
+    fn new(settings: &Self::Settings) -> Self {
+        Self {
+            syntax: syntax::Highlighter::new(&settings.syntax),
+            settings: settings.syntax.clone(),
+            brackets: settings.brackets,
+        }
+    }
+
+    fn update(&mut self, new_settings: &Self::Settings) {
+        // 语法高亮的设置变化时会从头重新高亮。
+        if self.settings != new_settings.syntax {
+            self.syntax.update(&new_settings.syntax);
+            self.settings = new_settings.syntax.clone();
+        }
+
+        let changed = [self.brackets, new_settings.brackets]
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|(line, _)| line)
+            .min();
+
+        if let Some(line) = changed {
+            self.syntax
+                .change_line(line.min(self.syntax.current_line()));
+        }
+
+        self.brackets = new_settings.brackets;
+    }
+
+    fn change_line(&mut self, line: usize) {
+        self.syntax.change_line(line);
+    }
+
+    fn highlight_line(&mut self, line: &str) -> Self::Iterator<'_> {
+        let index = self.syntax.current_line();
+
+        let mut highlights: Vec<_> = self
+            .syntax
+            .highlight_line(line)
+            .map(|(range, highlight)| (range, Highlight::Syntax(highlight)))
+            .collect();
+
+        // 括号的高亮放在最后，覆盖语法高亮的颜色。
+        highlights.extend(
+            self.brackets
+                .into_iter()
+                .flatten()
+                .filter(|(line, _)| *line == index)
+                .map(|(_, column)| (column..column + 1, Highlight::Bracket)),
+        );
+
+        highlights.into_iter()
+    }
+
+    fn current_line(&self) -> usize {
+        self.syntax.current_line()
+    }
+}
+
+// 将高亮转换为编辑器使用的文字格式。
+pub fn format(highlight: &Highlight, _theme: &Theme) -> Format<Font> {
+    match highlight {
+        Highlight::Syntax(highlight) => highlight.to_format(),
+        Highlight::Bracket => Format {
+            color: Some(MATCH_COLOR),
+            font: None,
+        },
+    }
+}
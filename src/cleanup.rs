@@ -0,0 +1,62 @@
+use crate::indent;
+use crate::preferences::Preferences;
+
+// 判断偏好设置是否启用了任何一种清理。
+pub fn is_enabled(preferences: &Preferences) -> bool {
+    preferences.normalize_indentation
+        || preferences.trim_trailing_whitespace
+        || preferences.single_final_newline
+}
+
+// 按照偏好设置清理保存前的文本：去掉行尾空白、统一缩进，并只保留一个结尾换行。
+//
+// 文本中的各行以 \n 分隔；没有启用任何清理时原样返回。
+pub fn apply(text: &str, preferences: &Preferences, rules: indent::Rules) -> String {
+    let mut lines: Vec<String> = text
+        .split('\n')
+        .map(|line| {
+            let line = if preferences.normalize_indentation {
+                normalize_indentation(line, rules)
+            } else {
+                line.to_string()
+            };
+
+            if preferences.trim_trailing_whitespace {
+                line.trim_end_matches([' ', '\t']).to_string()
+            } else {
+                line
+            }
+        })
+        .collect();
+
+    // 保存时总会在末尾补上一个换行，这里去掉多余的空行。
+    if preferences.single_final_newline {
+        while lines.last().is_some_and(|line| line.trim().is_empty()) {
+            let _ = lines.pop();
+        }
+
+        lines.push(String::new());
+    }
+
+    lines.join("\n")
+}
+
+// 按照语言的缩进单位重写一行开头的缩进：使用空格缩进的语言把制表符换成空格，
+// 使用制表符缩进的语言把成组的空格换成制表符。
+fn normalize_indentation(line: &str, rules: indent::Rules) -> String {
+    let content = line.trim_start_matches([' ', '\t']);
+    let width = rules.width(&line[..line.len() - content.len()]);
+    let tab_width = rules.indentation.tab_width;
+
+    let indentation = if rules.indentation.use_tabs {
+        format!(
+            "{}{}",
+            "\t".repeat(width / tab_width),
+            " ".repeat(width % tab_width)
+        )
+    } else {
+        " ".repeat(width)
+    };
+
+    format!("{indentation}{content}")
+}
@@ -0,0 +1,96 @@
+use crate::palette;
+use crate::Message;
+use std::collections::VecDeque;
+
+// 列表中每一项预览显示的最大字符数。
+const PREVIEW_LENGTH: usize = 60;
+
+// 定义最近复制或剪切的文本，最近的在前。
+#[derive(Debug, Clone, Default)]
+pub struct ClipboardHistory {
+    entries: VecDeque<String>, // 记录的文本，不包含重复的项。
+}
+
+impl ClipboardHistory {
+    // 使用上一次会话中保存的文本创建历史记录。
+    pub fn new(entries: Vec<String>, capacity: usize) -> Self {
+        let mut history = Self {
+            entries: entries.into(),
+        };
+        history.truncate(capacity);
+        history
+    }
+
+    // 记录一段文本。已经记录过的文本移到最前面，超出容量时丢弃最早的项。
+    pub fn push(&mut self, text: String, capacity: usize) {
+        if text.is_empty() {
+            return;
+        }
+
+        self.entries.retain(|entry| *entry != text);
+        self.entries.push_front(text);
+        self.truncate(capacity);
+    }
+
+    // 返回指定位置的文本，并将它移到最前面。
+    pub fn take(&mut self, index: usize) -> Option<String> {
+        let text = self.entries.remove(index)?;
+        self.entries.push_front(text.clone());
+        Some(text)
+    }
+
+    // 只保留最近的 capacity 项。
+    pub fn truncate(&mut self, capacity: usize) {
+        self.entries.truncate(capacity);
+    }
+
+    // 清空历史记录。
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    // 返回记录的所有文本，用于保存会话。
+    pub fn entries(&self) -> Vec<String> {
+        self.entries.iter().cloned().collect()
+    }
+
+    // 是否没有记录任何文本。
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    // 返回在命令面板中选择粘贴哪一项时的各条命令。
+    pub fn commands(&self) -> Vec<palette::Entry> {
+        self.entries
+            .iter()
+            .enumerate()
+            .map(|(index, text)| palette::Entry {
+                label: preview(text),
+                shortcut: None,
+                message: Message::PasteFromHistory(index),
+            })
+            .collect()
+    }
+}
+
+// 返回一段文本的单行预览：第一个非空行，过长时截断，多行文本注明其余的行数。
+fn preview(text: &str) -> String {
+    let lines = text.lines().count();
+    let first = text
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or_default();
+
+    let mut preview: String = first.chars().take(PREVIEW_LENGTH).collect();
+
+    if first.chars().count() > PREVIEW_LENGTH {
+        preview.push('…');
+    }
+
+    if lines > 1 {
+        preview.push_str(&format!("  (+{} lines)", lines - 1));
+    }
+
+    preview
+}
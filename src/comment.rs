@@ -0,0 +1,97 @@
+// 定义一种语言的注释语法。
+#[derive(Debug, Clone, Copy)]
+pub enum Syntax {
+    Line(&'static str),                // 行注释，例如 //。
+    Block(&'static str, &'static str), // 块注释，例如 <!-- -->。
+}
+
+impl Syntax {
+    // 根据文件扩展名返回注释语法。
+    pub fn for_extension(extension: &str) -> Option<Self> {
+        Some(match extension {
+            "rs" | "c" | "h" | "cpp" | "hpp" | "cc" | "cs" | "java" | "js" | "ts" | "jsx"
+            | "tsx" | "go" | "swift" | "kt" | "scala" | "dart" | "zig" => Self::Line("//"),
+            "py" | "pyw" | "sh" | "bash" | "zsh" | "rb" | "pl" | "toml" | "yaml" | "yml" | "r"
+            | "mk" | "cmake" | "conf" | "ini" => Self::Line("#"),
+            "sql" | "lua" | "hs" | "elm" | "ada" => Self::Line("--"),
+            "html" | "htm" | "xml" | "svg" | "md" | "markdown" | "vue" => {
+                Self::Block("<!--", "-->")
+            }
+            "css" | "scss" | "less" => Self::Block("/*", "*/"),
+            _ => return None,
+        })
+    }
+
+    // 判断一行是否已经被注释。
+    fn is_commented(self, line: &str) -> bool {
+        let line = line.trim();
+
+        match self {
+            Self::Line(token) => line.starts_with(token),
+            Self::Block(open, close) => line.starts_with(open) && line.ends_with(close),
+        }
+    }
+
+    // 去掉一行的注释，保留原有的缩进。
+    fn uncomment(self, line: &str) -> String {
+        let indentation = &line[..line.len() - line.trim_start().len()];
+        let content = line.trim_start();
+
+        let content = match self {
+            Self::Line(token) => {
+                let rest = &content[token.len()..];
+                rest.strip_prefix(' ').unwrap_or(rest).to_string()
+            }
+            Self::Block(open, close) => {
+                let content = content.trim_end();
+                let inner = &content[open.len()..content.len() - close.len()];
+                let inner = inner.strip_prefix(' ').unwrap_or(inner);
+                inner.strip_suffix(' ').unwrap_or(inner).to_string()
+            }
+        };
+
+        format!("{indentation}{content}")
+    }
+
+    // 在指定的缩进位置注释一行。
+    fn comment(self, line: &str, indentation: usize) -> String {
+        let (prefix, content) = line.split_at(indentation.min(line.len()));
+
+        match self {
+            Self::Line(token) => format!("{prefix}{token} {content}"),
+            Self::Block(open, close) => format!("{prefix}{open} {content} {close}"),
+        }
+    }
+}
+
+// 切换多行文本的注释状态：所有非空行都已注释时取消注释，否则注释所有非空行。
+pub fn toggle(lines: &[String], syntax: Syntax) -> String {
+    let is_blank = |line: &String| line.trim().is_empty();
+
+    let uncomment = lines
+        .iter()
+        .filter(|line| !is_blank(line))
+        .all(|line| syntax.is_commented(line));
+
+    // 注释符号放在所有行共同的最小缩进处，保持代码对齐。
+    let indentation = lines
+        .iter()
+        .filter(|line| !is_blank(line))
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+
+    lines
+        .iter()
+        .map(|line| {
+            if is_blank(line) {
+                line.clone()
+            } else if uncomment {
+                syntax.uncomment(line)
+            } else {
+                syntax.comment(line, indentation)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
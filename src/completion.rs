@@ -0,0 +1,173 @@
+use crate::tab::Tab;
+use crate::Message;
+use iced::widget::text_editor::{Action, Edit};
+use iced::widget::{button, column, container, text};
+use iced::{theme, Element, Length};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+// 列表中最多显示的候选项数量。
+const MAX_RESULTS: usize = 10;
+
+// 输入多少个字符之后开始提示文档中的单词。
+pub const MIN_PREFIX: usize = 2;
+
+// 定义补全弹窗的状态。
+#[derive(Debug)]
+pub struct Completion {
+    pub tab: usize,      // 弹窗所属的标签页标识。
+    pub line: usize,     // 开始补全时光标所在的行。
+    pub column: usize,   // 开始补全时光标前的单词的起始位置。
+    pub selected: usize, // 选中的候选项在匹配结果中的位置。
+    items: Vec<String>,  // 所有的候选项。
+}
+
+impl Completion {
+    // 在标签页的光标处创建补全弹窗。
+    pub fn new(tab: &Tab, items: Vec<String>) -> Self {
+        let (line, column) = tab.context.cursor_position();
+
+        Self {
+            tab: tab.id,
+            line,
+            column: column - word_before_cursor(tab).len(),
+            selected: 0,
+            items,
+        }
+    }
+
+    // 判断光标是否仍在补全的单词中，移动到其他行或单词之前时应当关闭弹窗。
+    pub fn is_active(&self, tab: &Tab) -> bool {
+        let (line, column) = tab.context.cursor_position();
+
+        tab.id == self.tab && line == self.line && column >= self.column
+    }
+
+    // 返回以光标前的单词开头的候选项，不区分大小写；与单词一样长的候选项不再提示。
+    pub fn matches(&self, prefix: &str) -> Vec<&str> {
+        let lowercase = prefix.to_lowercase();
+
+        self.items
+            .iter()
+            .map(String::as_str)
+            .filter(|item| item.len() > prefix.len() && item.to_lowercase().starts_with(&lowercase))
+            .take(MAX_RESULTS)
+            .collect()
+    }
+
+    // 返回当前选中的候选项。继续输入后匹配的候选项变少时选中最后一项。
+    pub fn selection(&self, prefix: &str) -> Option<String> {
+        let matches = self.matches(prefix);
+
+        matches
+            .get(self.selected.min(matches.len().saturating_sub(1)))
+            .map(|item| item.to_string())
+    }
+
+    // 上下移动选中的候选项，到达两端时循环。
+    pub fn move_selection(&mut self, prefix: &str, offset: isize) {
+        let count = self.matches(prefix).len();
+
+        if count > 0 {
+            self.selected = (self.selected as isize + offset).rem_euclid(count as isize) as usize;
+        }
+    }
+
+    // 创建补全弹窗的视图。
+    pub fn view(&self, prefix: &str) -> Option<Element<'_, Message>> {
+        let matches = self.matches(prefix);
+
+        if matches.is_empty() {
+            return None;
+        }
+
+        let selected = self.selected.min(matches.len() - 1);
+
+        let entries = matches
+            .into_iter()
+            .enumerate()
+            .map(|(index, item)| {
+                let style = if index == selected {
+                    theme::Button::Primary
+                } else {
+                    theme::Button::Text
+                };
+
+                button(text(item).size(14))
+                    .on_press(Message::CompletionAccepted(item.to_string()))
+                    .width(Length::Fill)
+                    .padding([2, 5])
+                    .style(style)
+                    .into()
+            })
+            .collect();
+
+        Some(
+            container(column(entries))
+                .width(300)
+                .padding(5)
+                .style(theme::Container::Box)
+                .into(),
+        )
+    }
+}
+
+// 收集文档中的单词作为候选项，出现次数多的排在前面。
+//
+// 光标前正在输入的单词本身不算在内，除非它在文档的其他位置也出现过。
+pub fn index(tab: &Tab, prefix: &str) -> Vec<String> {
+    let text = tab.context.text();
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+
+    for word in text.split(|c: char| !is_word_char(c)) {
+        // 以数字开头的不是标识符。
+        if word.chars().next().is_some_and(|c| !c.is_numeric()) {
+            *counts.entry(word).or_default() += 1;
+        }
+    }
+
+    if let Some(count) = counts.get_mut(prefix) {
+        *count -= 1;
+    }
+
+    let mut words: Vec<_> = counts.into_iter().filter(|(_, count)| *count > 0).collect();
+    words.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then(a.cmp(b)));
+
+    words
+        .into_iter()
+        .map(|(word, _)| word.to_string())
+        .collect()
+}
+
+// 返回光标前由字母、数字和下划线组成的单词。
+pub fn word_before_cursor(tab: &Tab) -> String {
+    let (line, column) = tab.context.cursor_position();
+
+    let Some(text) = tab.context.line(line) else {
+        return String::new();
+    };
+
+    let before = text.get(..column).unwrap_or_default();
+    let start = before
+        .char_indices()
+        .rev()
+        .take_while(|(_, c)| is_word_char(*c))
+        .last()
+        .map_or(before.len(), |(index, _)| index);
+
+    before[start..].to_string()
+}
+
+// 判断字符是否可以组成标识符。
+pub fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+// 用选中的候选项替换光标前的单词。
+pub fn accept(tab: &mut Tab, item: &str) {
+    for _ in word_before_cursor(tab).chars() {
+        tab.edit(Action::Edit(Edit::Backspace));
+    }
+
+    tab.edit(Action::Edit(Edit::Paste(Arc::new(item.to_string()))));
+}
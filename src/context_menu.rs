@@ -0,0 +1,321 @@
+use crate::spelling::Misspelling;
+use crate::Message;
+use iced::advanced::layout::{self, Layout};
+use iced::advanced::renderer;
+use iced::advanced::widget::{Operation, Tree, Widget};
+use iced::advanced::{overlay, Clipboard, Shell};
+use iced::event::{self, Event};
+use iced::widget::{button, column, container, horizontal_rule, text};
+use iced::{keyboard, mouse};
+use iced::{theme, Element, Length, Point, Rectangle, Renderer, Size, Theme, Vector};
+
+// 菜单的宽度。
+const WIDTH: f32 = 220.0;
+
+// 定义右键点击位置的内容，决定菜单中除编辑操作之外的其他项。
+//
+// 以后需要根据点击位置提供的操作（例如跳转到定义）在这里添加新的类型。
+#[derive(Debug, Clone, PartialEq)]
+pub enum Target {
+    Misspelling(Misspelling), // 拼写错误的单词。
+}
+
+// 定义菜单中的一项。
+#[derive(Debug, Clone)]
+pub enum Item {
+    Action(String, Option<Message>), // 点击时发送消息的菜单项，没有消息时不可用。
+    Separator,                       // 分隔线。
+}
+
+// 定义打开的右键菜单。
+#[derive(Debug, Clone)]
+pub struct Menu {
+    pub tab: usize,       // 菜单所属的标签页标识。
+    pub position: Point,  // 菜单左上角相对于编辑器的位置，即点击的位置。
+    pub items: Vec<Item>, // 菜单中的各项。
+}
+
+impl Menu {
+    // 创建菜单的视图，点击菜单项时关闭菜单并执行对应的操作。
+    pub fn view(&self) -> Element<'_, Message> {
+        let items = self.items.iter().map(|item| match item {
+            Item::Action(label, message) => button(text(label).size(14))
+                .on_press_maybe(
+                    message
+                        .clone()
+                        .map(|message| Message::MenuItemSelected(Box::new(message))),
+                )
+                .width(Length::Fill)
+                .padding([2, 5])
+                .style(theme::Button::Text)
+                .into(),
+            Item::Separator => horizontal_rule(1).into(),
+        });
+
+        container(column(items.collect()))
+            .width(WIDTH)
+            .padding(5)
+            .style(theme::Container::Box)
+            .into()
+    }
+}
+
+// 为内部的编辑器添加右键菜单。
+//
+// 右键点击时发送 on_open 消息，附带点击位置和该位置的内容；targets 为可以点击的内容所在的区域。
+// 打开的菜单作为浮层显示在点击的位置，不会被编辑器的边界裁剪。
+pub fn context_menu<'a>(
+    content: impl Into<Element<'a, Message>>,
+    targets: Vec<(Rectangle, Target)>,
+    menu: Option<&'a Menu>,
+    on_open: fn(Point, Option<Target>) -> Message,
+) -> Element<'a, Message> {
+    Element::new(ContextMenu {
+        content: content.into(),
+        targets,
+        menu: menu.map(|menu| (menu.position, menu.view())),
+        on_open,
+    })
+}
+
+// 显示右键菜单的控件，其余的行为都交给内部的编辑器处理。
+struct ContextMenu<'a> {
+    content: Element<'a, Message>,                 // 被添加菜单的编辑器。
+    targets: Vec<(Rectangle, Target)>,             // 可以点击的内容及其区域。
+    menu: Option<(Point, Element<'a, Message>)>,   // 打开的菜单及其位置。
+    on_open: fn(Point, Option<Target>) -> Message, // 右键点击时发送的消息。
+}
+
+impl Widget<Message, Renderer> for ContextMenu<'_> {
+    fn children(&self) -> Vec<Tree> {
+        let mut children = vec![Tree::new(&self.content)];
+        children.extend(self.menu.as_ref().map(|(_, menu)| Tree::new(menu)));
+        children
+    }
+
+    fn diff(&self, tree: &mut Tree) {
+        match &self.menu {
+            Some((_, menu)) => tree.diff_children(&[&self.content, menu]),
+            None => tree.diff_children(std::slice::from_ref(&self.content)),
+        }
+    }
+
+    fn width(&self) -> Length {
+        self.content.as_widget().width()
+    }
+
+    fn height(&self) -> Length {
+        self.content.as_widget().height()
+    }
+
+    fn layout(
+        &self,
+        tree: &mut Tree,
+        renderer: &Renderer,
+        limits: &layout::Limits,
+    ) -> layout::Node {
+        self.content
+            .as_widget()
+            .layout(&mut tree.children[0], renderer, limits)
+    }
+
+    fn operate(
+        &self,
+        tree: &mut Tree,
+        layout: Layout<'_>,
+        renderer: &Renderer,
+        operation: &mut dyn Operation<Message>,
+    ) {
+        self.content
+            .as_widget()
+            .operate(&mut tree.children[0], layout, renderer, operation);
+    }
+
+    fn on_event(
+        &mut self,
+        tree: &mut Tree,
+        event: Event,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        renderer: &Renderer,
+        clipboard: &mut dyn Clipboard,
+        shell: &mut Shell<'_, Message>,
+        viewport: &Rectangle,
+    ) -> event::Status {
+        // 编辑器不处理右键，右键点击时打开菜单。
+        if let Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Right)) = event {
+            if let Some(position) = cursor.position_in(layout.bounds()) {
+                let target = self
+                    .targets
+                    .iter()
+                    .find(|(bounds, _)| bounds.contains(position))
+                    .map(|(_, target)| target.clone());
+
+                shell.publish((self.on_open)(position, target));
+                return event::Status::Captured;
+            }
+        }
+
+        // 编辑器会把 Ctrl+Shift+V 当作粘贴处理，留给快捷键打开剪贴板历史。
+        if let Event::Keyboard(keyboard::Event::KeyPressed {
+            key_code: keyboard::KeyCode::V,
+            modifiers,
+        }) = event
+        {
+            if modifiers.command() && modifiers.shift() {
+                return event::Status::Ignored;
+            }
+        }
+
+        self.content.as_widget_mut().on_event(
+            &mut tree.children[0],
+            event,
+            layout,
+            cursor,
+            renderer,
+            clipboard,
+            shell,
+            viewport,
+        )
+    }
+
+    fn mouse_interaction(
+        &self,
+        tree: &Tree,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        viewport: &Rectangle,
+        renderer: &Renderer,
+    ) -> mouse::Interaction {
+        self.content.as_widget().mouse_interaction(
+            &tree.children[0],
+            layout,
+            cursor,
+            viewport,
+            renderer,
+        )
+    }
+
+    fn draw(
+        &self,
+        tree: &Tree,
+        renderer: &mut Renderer,
+        theme: &Theme,
+        style: &renderer::Style,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        viewport: &Rectangle,
+    ) {
+        self.content.as_widget().draw(
+            &tree.children[0],
+            renderer,
+            theme,
+            style,
+            layout,
+            cursor,
+            viewport,
+        );
+    }
+
+    fn overlay<'b>(
+        &'b mut self,
+        tree: &'b mut Tree,
+        layout: Layout<'_>,
+        renderer: &Renderer,
+    ) -> Option<overlay::Element<'b, Message, Renderer>> {
+        let Some((position, menu)) = &mut self.menu else {
+            return self
+                .content
+                .as_widget_mut()
+                .overlay(&mut tree.children[0], layout, renderer);
+        };
+
+        Some(overlay::Element::new(
+            layout.position() + Vector::new(position.x, position.y),
+            Box::new(Overlay {
+                menu,
+                tree: &mut tree.children[1],
+            }),
+        ))
+    }
+}
+
+// 显示在编辑器之上的菜单浮层。
+struct Overlay<'a, 'b> {
+    menu: &'b mut Element<'a, Message>, // 菜单的内容。
+    tree: &'b mut Tree,                 // 菜单的状态。
+}
+
+impl overlay::Overlay<Message, Renderer> for Overlay<'_, '_> {
+    // 菜单放在点击的位置，超出窗口时向左或向上移动。
+    fn layout(&mut self, renderer: &Renderer, bounds: Size, position: Point) -> layout::Node {
+        let limits = layout::Limits::new(Size::ZERO, bounds);
+        let node = self.menu.as_widget().layout(self.tree, renderer, &limits);
+        let size = node.size();
+
+        node.move_to(Point::new(
+            position.x.min(bounds.width - size.width).max(0.0),
+            position.y.min(bounds.height - size.height).max(0.0),
+        ))
+    }
+
+    fn on_event(
+        &mut self,
+        event: Event,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        renderer: &Renderer,
+        clipboard: &mut dyn Clipboard,
+        shell: &mut Shell<'_, Message>,
+    ) -> event::Status {
+        // 点击菜单以外的位置时关闭菜单，点击照常交给下面的控件处理。
+        if let Event::Mouse(mouse::Event::ButtonPressed(_)) = event {
+            if !cursor.is_over(layout.bounds()) {
+                shell.publish(Message::ContextMenuClosed);
+                return event::Status::Ignored;
+            }
+        }
+
+        self.menu.as_widget_mut().on_event(
+            self.tree,
+            event,
+            layout,
+            cursor,
+            renderer,
+            clipboard,
+            shell,
+            &layout.bounds(),
+        )
+    }
+
+    fn mouse_interaction(
+        &self,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        viewport: &Rectangle,
+        renderer: &Renderer,
+    ) -> mouse::Interaction {
+        self.menu
+            .as_widget()
+            .mouse_interaction(self.tree, layout, cursor, viewport, renderer)
+    }
+
+    fn draw(
+        &self,
+        renderer: &mut Renderer,
+        theme: &Theme,
+        style: &renderer::Style,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+    ) {
+        self.menu.as_widget().draw(
+            self.tree,
+            renderer,
+            theme,
+            style,
+            layout,
+            cursor,
+            &layout.bounds(),
+        );
+    }
+}
@@ -0,0 +1,505 @@
+use crate::diagnostics;
+use crate::navigation;
+use crate::tab::Tab;
+use iced::advanced::text::highlighter::{self, Format};
+use iced::widget::text_editor::{Action, Edit, Motion};
+use iced::{Font, Theme};
+use std::ops::Range;
+use std::sync::Arc;
+
+// 定义一个光标，位置为文档中的字符偏移量。没有选区时锚点与光标位置相同。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Caret {
+    anchor: usize, // 选区开始的位置。
+    head: usize,   // 光标所在的位置。
+}
+
+impl Caret {
+    // 创建一个没有选区的光标。
+    fn at(offset: usize) -> Self {
+        Self {
+            anchor: offset,
+            head: offset,
+        }
+    }
+
+    // 返回选区的范围。
+    fn range(self) -> Range<usize> {
+        self.anchor.min(self.head)..self.anchor.max(self.head)
+    }
+}
+
+// 定义标签页中除编辑器自身的光标之外的其他光标。
+//
+// 文本编辑器只支持一个光标，其他光标保存在这里。执行动作时依次把编辑器的光标移到每个位置上，
+// 执行同一个动作后再记录新的位置，最后恢复编辑器自身的光标。
+#[derive(Debug)]
+pub struct Cursors {
+    pub tab: usize,                 // 光标所属的标签页标识。
+    carets: Vec<Caret>,             // 其他光标。
+    origin: Option<(usize, usize)>, // 列选择开始的行和字符列。
+}
+
+impl Cursors {
+    // 在标签页中创建一组空的光标。
+    pub fn new(tab: &Tab) -> Self {
+        Self {
+            tab: tab.id,
+            carets: Vec::new(),
+            origin: None,
+        }
+    }
+
+    // 返回光标的总数，包括编辑器自身的光标。
+    pub fn count(&self) -> usize {
+        self.carets.len() + 1
+    }
+
+    // 判断是否还有其他光标。
+    pub fn is_empty(&self) -> bool {
+        self.carets.is_empty()
+    }
+
+    // 将编辑器当前的光标保留为其他光标，之后编辑器的光标可以移到新的位置。
+    pub fn add(&mut self, tab: &Tab) {
+        self.origin = None;
+        self.carets.push(primary(tab));
+    }
+
+    // 移除与编辑器自身的光标重合的其他光标，再次点击已有的光标时即可移除它。
+    pub fn remove_duplicates(&mut self, tab: &Tab) {
+        let current = primary(tab);
+
+        self.carets.retain(|caret| caret.head != current.head);
+    }
+
+    // 在所有光标处执行同一个动作。perform 负责在编辑器的光标处执行修改内容的动作。
+    pub fn apply(
+        &mut self,
+        tab: &mut Tab,
+        action: Action,
+        mut perform: impl FnMut(&mut Tab, Action),
+    ) {
+        let mut carets = self.all(tab);
+
+        if action.is_edit() {
+            let actions = vec![action; carets.len()];
+            edit_each(tab, &mut carets, actions, &mut perform);
+        } else {
+            for caret in &mut carets {
+                place(tab, *caret);
+                tab.edit(action.clone());
+
+                // 扩展选区时保留原来的锚点。
+                let moved = primary(tab);
+                *caret = match action {
+                    Action::Select(_) => Caret {
+                        anchor: caret.anchor,
+                        head: moved.head,
+                    },
+                    _ => moved,
+                };
+            }
+        }
+
+        self.merge(tab, carets);
+    }
+
+    // 按列粘贴：文本的行数与光标数相同时，按光标在文档中的先后顺序在每个光标处粘贴一行。
+    //
+    // 返回是否已经粘贴，行数不同时不做任何事，由调用者在每个光标处粘贴整段文本。
+    pub fn paste_lines(&mut self, tab: &mut Tab, text: &str) -> bool {
+        let mut carets = self.all(tab);
+        let lines: Vec<&str> = text.lines().collect();
+
+        if lines.len() != carets.len() {
+            return false;
+        }
+
+        let mut order: Vec<usize> = (0..carets.len()).collect();
+        order.sort_by_key(|&index| carets[index].range().start);
+
+        let mut actions = vec![Action::Edit(Edit::Paste(Arc::default())); carets.len()];
+        for (line, index) in lines.into_iter().zip(order) {
+            actions[index] = Action::Edit(Edit::Paste(Arc::new(line.to_string())));
+        }
+
+        edit_each(tab, &mut carets, actions, &mut |tab: &mut Tab, action| {
+            tab.edit(action)
+        });
+        self.merge(tab, carets);
+        true
+    }
+
+    // 返回所有光标选中的文本，按在文档中的先后顺序每个选区一行，用于复制列选择。
+    //
+    // 没有其他光标或者所有光标都没有选区时返回 None。
+    pub fn selections(&self, tab: &Tab) -> Option<String> {
+        let mut carets = self.all(tab);
+        carets.sort_by_key(|caret| caret.range().start);
+
+        if self.carets.is_empty() || carets.iter().all(|caret| caret.range().is_empty()) {
+            return None;
+        }
+
+        let rope = tab.context.rope();
+        let length = rope.len_chars();
+        let texts: Vec<String> = carets
+            .into_iter()
+            .map(|caret| {
+                let range = caret.range();
+                rope.slice(range.start.min(length)..range.end.min(length))
+                    .to_string()
+            })
+            .collect();
+
+        Some(texts.join("\n"))
+    }
+
+    // 返回所有光标，编辑器自身的光标在最前面。
+    fn all(&self, tab: &Tab) -> Vec<Caret> {
+        let mut carets = vec![primary(tab)];
+        carets.extend(self.carets.iter().copied());
+        carets
+    }
+
+    // 合并移动到同一位置的光标，第一个光标作为编辑器自身的光标。
+    fn merge(&mut self, tab: &mut Tab, carets: Vec<Caret>) {
+        let mut unique: Vec<Caret> = Vec::new();
+        for caret in carets {
+            if !unique.iter().any(|other| other.head == caret.head) {
+                unique.push(caret);
+            }
+        }
+
+        place(tab, unique[0]);
+        self.carets = unique.split_off(1);
+    }
+
+    // 选中光标处的单词；已经有选区时，在最后一个光标之后查找选中文本的下一处出现的位置并添加光标。
+    pub fn add_next_occurrence(&mut self, tab: &mut Tab) {
+        let current = primary(tab);
+
+        let Some(selection) = tab
+            .context
+            .selection()
+            .filter(|_| current.anchor != current.head)
+        else {
+            navigation::select_word(tab);
+            return;
+        };
+
+        let rope = tab.context.rope();
+        let text = rope.to_string();
+        let length = selection.chars().count();
+
+        let from = self
+            .carets
+            .iter()
+            .chain([&current])
+            .map(|caret| caret.range().end)
+            .max()
+            .unwrap_or(0);
+        let from = rope.char_to_byte(from);
+
+        // 到达文档末尾后从头开始查找。
+        let found = text[from..]
+            .find(&selection)
+            .map(|index| from + index)
+            .or_else(|| text.find(&selection));
+
+        let Some(start) = found.map(|byte| rope.byte_to_char(byte)) else {
+            return;
+        };
+
+        let next = Caret {
+            anchor: start,
+            head: start + length,
+        };
+
+        // 所有出现的位置都已经有光标了。
+        if next == current || self.carets.contains(&next) {
+            return;
+        }
+
+        self.carets.push(current);
+        place(tab, next);
+    }
+
+    // 从编辑器的光标处开始列选择。
+    pub fn start_column(&mut self, tab: &Tab) {
+        self.carets.clear();
+        self.origin = Some(char_position(tab));
+    }
+
+    // 拖动到编辑器的光标处时，在起点和终点围成的矩形中的每一行放置一个光标。
+    pub fn drag_column(&mut self, tab: &mut Tab) {
+        let Some((origin_line, origin_column)) = self.origin else {
+            return;
+        };
+
+        let (line, column) = char_position(tab);
+        let rope = tab.context.rope();
+
+        let carets: Vec<Caret> = (origin_line.min(line)..=origin_line.max(line))
+            .map(|index| {
+                let start = rope.line_to_char(index);
+                let length = line_length(tab, index);
+
+                Caret {
+                    anchor: start + origin_column.min(length),
+                    head: start + column.min(length),
+                }
+            })
+            .collect();
+
+        // 拖动到达的一行由编辑器自身的光标表示。
+        let current = line - origin_line.min(line);
+
+        place(tab, carets[current]);
+        self.carets = carets
+            .into_iter()
+            .enumerate()
+            .filter(|(index, _)| *index != current)
+            .map(|(_, caret)| caret)
+            .collect();
+    }
+
+    // 判断是否正在进行列选择。
+    pub fn is_selecting_columns(&self) -> bool {
+        self.origin.is_some()
+    }
+
+    // 返回其他光标在每一行中需要标记的字符范围，没有选区的光标标记它后面的一个字符。
+    pub fn marks(&self, tab: &Tab) -> Vec<(usize, Range<usize>)> {
+        let rope = tab.context.rope();
+
+        self.carets
+            .iter()
+            .filter(|caret| caret.head <= rope.len_chars())
+            .map(|caret| {
+                let range = caret.range();
+                let line = rope.char_to_line(range.start);
+                let start = range.start - rope.line_to_char(line);
+
+                (line, start..start + range.len().max(1))
+            })
+            .collect()
+    }
+}
+
+// 在每个光标处执行对应的修改内容的动作，之后把每个光标移到它修改的位置之后。
+//
+// 从文档末尾向前依次编辑，前面的编辑不会改变后面已经编辑过的光标到文档末尾的距离。
+fn edit_each(
+    tab: &mut Tab,
+    carets: &mut [Caret],
+    actions: Vec<Action>,
+    perform: &mut impl FnMut(&mut Tab, Action),
+) {
+    let mut order: Vec<usize> = (0..carets.len()).collect();
+    order.sort_by_key(|&index| std::cmp::Reverse(carets[index].range().start));
+
+    let mut distances = vec![0; carets.len()];
+
+    for index in order {
+        place(tab, carets[index]);
+        perform(tab, actions[index].clone());
+
+        distances[index] = tab.context.rope().len_chars() - primary(tab).head;
+    }
+
+    let length = tab.context.rope().len_chars();
+
+    for (caret, distance) in carets.iter_mut().zip(distances) {
+        *caret = Caret::at(length.saturating_sub(distance));
+    }
+}
+
+// 返回编辑器自身的光标。
+//
+// 编辑器只提供选中的文本，不提供选区的方向：如果光标前的文本与选中的文本相同，
+// 认为锚点在光标之前，否则在光标之后。
+fn primary(tab: &Tab) -> Caret {
+    let (line, column) = char_position(tab);
+    let head = tab.context.rope().line_to_char(line) + column;
+
+    let Some(selection) = tab.context.selection() else {
+        return Caret::at(head);
+    };
+
+    let rope = tab.context.rope();
+    let length = selection.chars().count();
+
+    let is_before = head >= length && rope.slice(head - length..head) == selection.as_str();
+
+    Caret {
+        anchor: if is_before {
+            head - length
+        } else {
+            head + length
+        },
+        head,
+    }
+}
+
+// 返回编辑器的光标的选区在文档中的字符范围，没有选区时为光标所在的位置。
+pub fn selected_range(tab: &Tab) -> Range<usize> {
+    primary(tab).range()
+}
+
+// 选中文档中指定的字符范围，光标位于范围的末尾。
+pub fn select_range(tab: &mut Tab, range: Range<usize>) {
+    place(
+        tab,
+        Caret {
+            anchor: range.start,
+            head: range.end,
+        },
+    );
+}
+
+// 将编辑器的光标移到指定的位置，并选中从锚点到光标的范围。
+fn place(tab: &mut Tab, caret: Caret) {
+    let rope = tab.context.rope();
+    let anchor = caret.anchor.min(rope.len_chars());
+    let line = rope.char_to_line(anchor);
+    let column = anchor - rope.line_to_char(line);
+
+    tab.move_cursor_to(line, column);
+
+    let (motion, count) = if caret.head >= anchor {
+        (Motion::Right, caret.head - anchor)
+    } else {
+        (Motion::Left, anchor - caret.head)
+    };
+
+    for _ in 0..count {
+        tab.edit(Action::Select(motion));
+    }
+}
+
+// 返回编辑器的光标所在的行和字符列。
+fn char_position(tab: &Tab) -> (usize, usize) {
+    let (line, column) = tab.context.cursor_position();
+
+    let column = tab.context.line(line).map_or(0, |text| {
+        text.get(..column).unwrap_or(&text).chars().count()
+    });
+
+    (line, column)
+}
+
+// 返回指定行的字符数。
+fn line_length(tab: &Tab, line: usize) -> usize {
+    tab.context
+        .line(line)
+        .map_or(0, |text| text.chars().count())
+}
+
+// 定义高亮器的设置。
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settings {
+    pub diagnostics: diagnostics::Settings, // 语法、括号和诊断高亮的设置。
+    pub marks: Vec<(usize, Range<usize>)>,  // 其他光标在每一行中的字符范围。
+}
+
+// 定义高亮的类型。
+pub enum Highlight {
+    Diagnostics(diagnostics::Highlight),
+    Caret,
+}
+
+// 在诊断高亮的基础上标记其他光标的位置和选区。
+//
+// 编辑器只会绘制自身的光标，其他光标所在的字符用粗体和主题的主色显示。
+pub struct Highlighter {
+    diagnostics: diagnostics::Highlighter,
+    marks: Vec<(usize, Range<usize>)>,
+}
+
+impl highlighter::Highlighter for Highlighter {
+    type Settings = Settings;
+    type Highlight = Highlight;
+    type Iterator<'a> = std::vec::IntoIter<(Range<usize>, Highlight)>;
+
+    fn new(settings: &Self::Settings) -> Self {
+        Self {
+            diagnostics: diagnostics::Highlighter::new(&settings.diagnostics),
+            marks: settings.marks.clone(),
+        }
+    }
+
+    fn update(&mut self, new_settings: &Self::Settings) {
+        self.diagnostics.update(&new_settings.diagnostics);
+
+        // 光标变化时从受影响的最上面一行开始重新高亮。
+        if self.marks != new_settings.marks {
+            let changed = self
+                .marks
+                .iter()
+                .chain(&new_settings.marks)
+                .map(|(line, _)| *line)
+                .min();
+
+            if let Some(line) = changed {
+                self.diagnostics
+                    .change_line(line.min(self.diagnostics.current_line()));
+            }
+
+            self.marks = new_settings.marks.clone();
+        }
+    }
+
+    fn change_line(&mut self, line: usize) {
+        self.diagnostics.change_line(line);
+    }
+
+    fn highlight_line(&mut self, line: &str) -> Self::Iterator<'_> {
+        let index = self.diagnostics.current_line();
+
+        let mut highlights: Vec<_> = self
+            .diagnostics
+            .highlight_line(line)
+            .map(|(range, highlight)| (range, Highlight::Diagnostics(highlight)))
+            .collect();
+
+        highlights.extend(
+            self.marks
+                .iter()
+                .filter(|(mark, _)| *mark == index)
+                .map(|(_, columns)| (byte_range(line, columns), Highlight::Caret)),
+        );
+
+        highlights.into_iter()
+    }
+
+    fn current_line(&self) -> usize {
+        self.diagnostics.current_line()
+    }
+}
+
+// 将高亮转换为编辑器使用的文字格式。
+pub fn format(highlight: &Highlight, theme: &Theme) -> Format<Font> {
+    match highlight {
+        Highlight::Diagnostics(highlight) => diagnostics::format(highlight, theme),
+        Highlight::Caret => Format {
+            color: Some(theme.palette().primary),
+            font: Some(Font {
+                weight: iced::font::Weight::Bold,
+                ..Font::MONOSPACE
+            }),
+        },
+    }
+}
+
+// 将行中的字符范围转换为字节范围，超出行尾时截断。
+fn byte_range(line: &str, columns: &Range<usize>) -> Range<usize> {
+    let offset = |column: usize| {
+        line.char_indices()
+            .nth(column)
+            .map_or(line.len(), |(index, _)| index)
+    };
+
+    offset(columns.start)..offset(columns.end)
+}
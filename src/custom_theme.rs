@@ -0,0 +1,241 @@
+use crate::diagnostics::Severity;
+use crate::diff::Background;
+use crate::i18n::tr;
+use crate::Message;
+use iced::theme::{self, Palette};
+use iced::widget::{button, column, container, pick_list, row, text, text_input};
+use iced::{Color, Element, Length, Theme};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// 定义用户保存的自定义应用程序主题，颜色以 #rrggbb 的形式保存在偏好设置中。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CustomTheme {
+    pub name: String,       // 主题的名称。
+    pub background: String, // 背景颜色。
+    pub text: String,       // 文字颜色。
+    pub primary: String,    // 主要按钮和选中项的颜色。
+    pub danger: String,     // 危险操作和错误的颜色。
+}
+
+// 定义自定义主题中可以调整的颜色。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Background, // 背景颜色。
+    Text,       // 文字颜色。
+    Primary,    // 主要颜色。
+    Danger,     // 危险颜色。
+}
+
+impl Field {
+    pub const ALL: &'static [Self] = &[Self::Background, Self::Text, Self::Primary, Self::Danger];
+}
+
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(tr(match self {
+            Self::Background => "Background",
+            Self::Text => "Text",
+            Self::Primary => "Primary",
+            Self::Danger => "Danger",
+        }))
+    }
+}
+
+impl CustomTheme {
+    // 以现有的调色板为基础创建主题。
+    pub fn from_palette(name: String, palette: Palette) -> Self {
+        Self {
+            name,
+            background: to_hex(palette.background),
+            text: to_hex(palette.text),
+            primary: to_hex(palette.primary),
+            danger: to_hex(palette.danger),
+        }
+    }
+
+    // 返回指定颜色的文本。
+    pub fn color(&self, field: Field) -> &str {
+        match field {
+            Field::Background => &self.background,
+            Field::Text => &self.text,
+            Field::Primary => &self.primary,
+            Field::Danger => &self.danger,
+        }
+    }
+
+    // 返回指定颜色的文本的可变引用。
+    pub fn color_mut(&mut self, field: Field) -> &mut String {
+        match field {
+            Field::Background => &mut self.background,
+            Field::Text => &mut self.text,
+            Field::Primary => &mut self.primary,
+            Field::Danger => &mut self.danger,
+        }
+    }
+
+    // 返回主题的调色板，有颜色无效时返回 None。成功的颜色不能调整，按背景的深浅沿用内置主题的颜色。
+    pub fn palette(&self) -> Option<Palette> {
+        let background = parse_color(&self.background)?;
+        let base = if is_dark(background) {
+            Palette::DARK
+        } else {
+            Palette::LIGHT
+        };
+
+        Some(Palette {
+            background,
+            text: parse_color(&self.text)?,
+            primary: parse_color(&self.primary)?,
+            success: base.success,
+            danger: parse_color(&self.danger)?,
+        })
+    }
+
+    // 返回应用程序使用的主题，有颜色无效时返回 None。
+    pub fn theme(&self) -> Option<Theme> {
+        self.palette().map(Theme::custom)
+    }
+}
+
+// 定义正在编辑的自定义主题。
+#[derive(Debug, Clone)]
+pub struct ThemeEditor {
+    pub draft: CustomTheme,       // 正在编辑的主题，界面会实时预览其中的颜色。
+    pub original: Option<String>, // 编辑已有主题时为原来的名称，保存时替换原来的主题。
+}
+
+impl ThemeEditor {
+    // 判断主题是否可以保存：名称不能为空，所有颜色都必须有效。
+    pub fn can_save(&self) -> bool {
+        !self.draft.name.trim().is_empty() && self.draft.palette().is_some()
+    }
+}
+
+// 解析 #rrggbb 形式的颜色。
+pub fn parse_color(hex: &str) -> Option<Color> {
+    let hex = hex.trim().strip_prefix('#')?;
+
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    let value = u32::from_str_radix(hex, 16).ok()?;
+
+    Some(Color::from_rgb8(
+        (value >> 16) as u8,
+        (value >> 8) as u8,
+        value as u8,
+    ))
+}
+
+// 把颜色转换为 #rrggbb 形式的文本。
+fn to_hex(color: Color) -> String {
+    let [r, g, b, _] = color.into_rgba8();
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+// 按亮度判断背景颜色是否为深色。
+pub fn is_dark(color: Color) -> bool {
+    0.299 * color.r + 0.587 * color.g + 0.114 * color.b < 0.5
+}
+
+// 创建设置界面中的自定义主题部分：选择保存的主题，以及新建、编辑和删除主题。
+pub fn view<'a>(
+    themes: &'a [CustomTheme],
+    selected: Option<&'a str>,
+    editor: Option<&'a ThemeEditor>,
+) -> Element<'a, Message> {
+    let names: Vec<String> = themes.iter().map(|theme| theme.name.clone()).collect();
+
+    let choose = row![
+        text(tr("Application theme")),
+        pick_list(
+            names,
+            selected.map(str::to_string),
+            Message::CustomThemeSelected
+        ),
+        button(text(tr("Use Appearance")))
+            .on_press_maybe(selected.is_some().then_some(Message::CustomThemeCleared)),
+        button(text(tr("New"))).on_press_maybe(editor.is_none().then_some(Message::NewCustomTheme)),
+        button(text(tr("Edit"))).on_press_maybe(
+            (editor.is_none() && selected.is_some()).then_some(Message::EditCustomTheme)
+        ),
+        button(text(tr("Delete")))
+            .on_press_maybe(
+                (editor.is_none() && selected.is_some()).then_some(Message::DeleteCustomTheme)
+            )
+            .style(theme::Button::Destructive)
+    ]
+    .spacing(10);
+
+    let Some(editor) = editor else {
+        return column![text(tr("Custom Themes")).size(20), choose]
+            .spacing(10)
+            .into();
+    };
+
+    let name = row![
+        text(tr("Name")).width(100),
+        text_input(tr("Theme name"), &editor.draft.name)
+            .on_input(Message::CustomThemeNameChanged)
+            .padding(5)
+            .width(240)
+    ]
+    .spacing(10);
+
+    let colors = Field::ALL.iter().map(|&field| {
+        let value = editor.draft.color(field);
+
+        let swatch: Element<_> = match parse_color(value) {
+            Some(color) => container(text(""))
+                .width(24)
+                .height(24)
+                .style(theme::Container::Custom(Box::new(Background(color))))
+                .into(),
+            None => text(tr("Use #rrggbb"))
+                .style(Severity::Error.color())
+                .into(),
+        };
+
+        row![
+            text(field.to_string()).width(100),
+            text_input("#rrggbb", value)
+                .on_input(move |value| Message::CustomThemeColorChanged(field, value))
+                .padding(5)
+                .width(120),
+            swatch
+        ]
+        .spacing(10)
+        .into()
+    });
+
+    let actions = row![
+        button(text(tr("Save")))
+            .on_press_maybe(editor.can_save().then_some(Message::SaveCustomTheme))
+            .style(theme::Button::Primary),
+        button(text(tr("Cancel")))
+            .on_press(Message::CancelCustomTheme)
+            .style(theme::Button::Secondary)
+    ]
+    .spacing(10);
+
+    column![
+        text(tr("Custom Themes")).size(20),
+        choose,
+        container(
+            column![
+                text(tr("The window previews the colors while you edit them.")).size(14),
+                name,
+                column(colors.collect()).spacing(5),
+                actions
+            ]
+            .spacing(10)
+        )
+        .padding(10)
+        .width(Length::Fill)
+        .style(theme::Container::Box)
+    ]
+    .spacing(10)
+    .into()
+}
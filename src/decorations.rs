@@ -0,0 +1,365 @@
+use crate::whitespace::{self, Kind};
+use crate::{Message, CHARACTER_WIDTH, EDITOR_PADDING};
+use iced::advanced::layout::{self, Layout};
+use iced::advanced::renderer::{self, Renderer as _};
+use iced::advanced::widget::{Operation, Tree, Widget};
+use iced::advanced::{overlay, Clipboard, Shell};
+use iced::event::{self, Event};
+use iced::mouse;
+use iced::{Color, Element, Length, Rectangle, Renderer, Theme};
+
+// 当前行背景和列标尺的不透明度，颜色取自主题的文字颜色，因此在深色和浅色主题中都不会太显眼。
+const CURRENT_LINE_ALPHA: f32 = 0.06;
+const RULER_ALPHA: f32 = 0.15;
+
+// 列标尺和缩进参考线的宽度。
+const RULER_WIDTH: f32 = 1.0;
+
+// 缩进参考线的不透明度，颜色取自高亮主题的文字颜色，因此与代码的配色一致。
+const GUIDE_ALPHA: f32 = 0.12;
+
+// 空白字符标记的不透明度，颜色同样取自高亮主题的文字颜色。
+const WHITESPACE_ALPHA: f32 = 0.35;
+
+// 表示空格的点和箭头线条的粗细。
+const DOT_SIZE: f32 = 2.0;
+const STROKE_WIDTH: f32 = 1.0;
+
+// 箭头头部的长度。
+const ARROW_HEAD: f32 = 3.0;
+
+// 拼写错误的单词下方下划线的颜色和粗细。
+const UNDERLINE: Color = Color::from_rgb(0.9, 0.3, 0.3);
+const UNDERLINE_WIDTH: f32 = 1.5;
+
+// 定义可见的各行的缩进参考线。
+#[derive(Debug, Clone, Default)]
+pub struct Guides {
+    pub lines: Vec<(f32, f32, usize)>, // 各行相对于编辑器顶部的位置、高度和缩进的宽度（列数）。
+    pub step: usize,                   // 相邻两条参考线之间的列数，即制表符的宽度。
+    pub color: Color,                  // 参考线的颜色。
+}
+
+// 在编辑器的文字之下绘制当前行的背景、缩进参考线、空白字符、列标尺和下划线。
+//
+// current_line 为光标所在行相对于编辑器顶部的位置和高度，ruler 为列标尺所在的列。
+// underlines 为需要加下划线的文字相对于编辑器的区域。
+pub fn decorations<'a>(
+    content: impl Into<Element<'a, Message>>,
+    current_line: Option<(f32, f32)>,
+    guides: Option<Guides>,
+    whitespace: Option<whitespace::Marks>,
+    ruler: Option<usize>,
+    underlines: Vec<Rectangle>,
+) -> Element<'a, Message> {
+    Element::new(Decorations {
+        content: content.into(),
+        current_line,
+        guides,
+        whitespace,
+        ruler,
+        underlines,
+    })
+}
+
+// 为内部的编辑器绘制装饰的控件，其余的行为都交给内部的编辑器处理。
+struct Decorations<'a> {
+    content: Element<'a, Message>,         // 被装饰的编辑器。
+    current_line: Option<(f32, f32)>,      // 光标所在行的纵向位置和高度。
+    guides: Option<Guides>,                // 缩进参考线。
+    whitespace: Option<whitespace::Marks>, // 需要显示的空白字符。
+    ruler: Option<usize>,                  // 列标尺所在的列。
+    underlines: Vec<Rectangle>,            // 加下划线的文字区域。
+}
+
+impl Widget<Message, Renderer> for Decorations<'_> {
+    fn children(&self) -> Vec<Tree> {
+        vec![Tree::new(&self.content)]
+    }
+
+    fn diff(&self, tree: &mut Tree) {
+        tree.diff_children(std::slice::from_ref(&self.content));
+    }
+
+    fn width(&self) -> Length {
+        self.content.as_widget().width()
+    }
+
+    fn height(&self) -> Length {
+        self.content.as_widget().height()
+    }
+
+    fn layout(
+        &self,
+        tree: &mut Tree,
+        renderer: &Renderer,
+        limits: &layout::Limits,
+    ) -> layout::Node {
+        self.content
+            .as_widget()
+            .layout(&mut tree.children[0], renderer, limits)
+    }
+
+    fn operate(
+        &self,
+        tree: &mut Tree,
+        layout: Layout<'_>,
+        renderer: &Renderer,
+        operation: &mut dyn Operation<Message>,
+    ) {
+        self.content
+            .as_widget()
+            .operate(&mut tree.children[0], layout, renderer, operation);
+    }
+
+    fn on_event(
+        &mut self,
+        tree: &mut Tree,
+        event: Event,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        renderer: &Renderer,
+        clipboard: &mut dyn Clipboard,
+        shell: &mut Shell<'_, Message>,
+        viewport: &Rectangle,
+    ) -> event::Status {
+        self.content.as_widget_mut().on_event(
+            &mut tree.children[0],
+            event,
+            layout,
+            cursor,
+            renderer,
+            clipboard,
+            shell,
+            viewport,
+        )
+    }
+
+    fn mouse_interaction(
+        &self,
+        tree: &Tree,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        viewport: &Rectangle,
+        renderer: &Renderer,
+    ) -> mouse::Interaction {
+        self.content.as_widget().mouse_interaction(
+            &tree.children[0],
+            layout,
+            cursor,
+            viewport,
+            renderer,
+        )
+    }
+
+    fn draw(
+        &self,
+        tree: &Tree,
+        renderer: &mut Renderer,
+        theme: &Theme,
+        style: &renderer::Style,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        viewport: &Rectangle,
+    ) {
+        self.content.as_widget().draw(
+            &tree.children[0],
+            renderer,
+            theme,
+            style,
+            layout,
+            cursor,
+            viewport,
+        );
+
+        // 同一层中的文字总是绘制在矩形之上，因此装饰在编辑器的背景之上、文字之下。
+        let bounds = layout.bounds();
+        let text = theme.palette().text;
+
+        if let Some((y, height)) = self.current_line {
+            let line = Rectangle {
+                x: bounds.x,
+                y: bounds.y + y,
+                width: bounds.width,
+                height,
+            };
+
+            if let Some(line) = line.intersection(&bounds) {
+                fill(
+                    renderer,
+                    line,
+                    Color {
+                        a: CURRENT_LINE_ALPHA,
+                        ..text
+                    },
+                );
+            }
+        }
+
+        if let Some(guides) = &self.guides {
+            let step = guides.step.max(1);
+            let color = Color {
+                a: GUIDE_ALPHA,
+                ..guides.color
+            };
+
+            for &(y, height, width) in &guides.lines {
+                for column in (0..width).step_by(step) {
+                    let guide = Rectangle {
+                        x: bounds.x + EDITOR_PADDING + column as f32 * CHARACTER_WIDTH,
+                        y: bounds.y + y,
+                        width: RULER_WIDTH,
+                        height,
+                    };
+
+                    if let Some(guide) = guide.intersection(&bounds) {
+                        fill(renderer, guide, color);
+                    }
+                }
+            }
+        }
+
+        if let Some(whitespace) = &self.whitespace {
+            let color = Color {
+                a: WHITESPACE_ALPHA,
+                ..whitespace.color
+            };
+
+            for mark in &whitespace.marks {
+                let cell = Rectangle {
+                    x: bounds.x + mark.bounds.x,
+                    y: bounds.y + mark.bounds.y,
+                    ..mark.bounds
+                };
+
+                if cell.intersection(&bounds).is_some() {
+                    draw_whitespace(renderer, mark.kind, cell, color);
+                }
+            }
+        }
+
+        if let Some(column) = self.ruler {
+            let x = bounds.x + EDITOR_PADDING + column as f32 * CHARACTER_WIDTH;
+
+            if x < bounds.x + bounds.width {
+                let ruler = Rectangle {
+                    x,
+                    width: RULER_WIDTH,
+                    ..bounds
+                };
+
+                fill(
+                    renderer,
+                    ruler,
+                    Color {
+                        a: RULER_ALPHA,
+                        ..text
+                    },
+                );
+            }
+        }
+
+        for word in &self.underlines {
+            let underline = Rectangle {
+                x: bounds.x + word.x,
+                y: bounds.y + word.y + word.height - UNDERLINE_WIDTH,
+                width: word.width,
+                height: UNDERLINE_WIDTH,
+            };
+
+            if let Some(underline) = underline.intersection(&bounds) {
+                fill(renderer, underline, UNDERLINE);
+            }
+        }
+    }
+
+    fn overlay<'b>(
+        &'b mut self,
+        tree: &'b mut Tree,
+        layout: Layout<'_>,
+        renderer: &Renderer,
+    ) -> Option<overlay::Element<'b, Message, Renderer>> {
+        self.content
+            .as_widget_mut()
+            .overlay(&mut tree.children[0], layout, renderer)
+    }
+}
+
+// 在空白字符占据的区域中绘制它的标记：空格为居中的点，制表符为指向右侧的箭头，
+// 换行符为先向下再指向左侧的回车符号。
+fn draw_whitespace(renderer: &mut Renderer, kind: Kind, cell: Rectangle, color: Color) {
+    let center = cell.center();
+
+    match kind {
+        Kind::Space => {
+            let dot = Rectangle {
+                x: center.x - DOT_SIZE / 2.0,
+                y: center.y - DOT_SIZE / 2.0,
+                width: DOT_SIZE,
+                height: DOT_SIZE,
+            };
+
+            fill(renderer, dot, color);
+        }
+        Kind::Tab => {
+            let start = cell.x + CHARACTER_WIDTH / 4.0;
+            let end = cell.x + cell.width - CHARACTER_WIDTH / 4.0;
+
+            arrow(renderer, start, end, center.y, color);
+        }
+        Kind::LineEnding => {
+            let start = cell.x + cell.width - CHARACTER_WIDTH / 4.0;
+            let end = cell.x + CHARACTER_WIDTH / 4.0;
+            let top = cell.y + cell.height / 4.0;
+
+            let stem = Rectangle {
+                x: start - STROKE_WIDTH,
+                y: top,
+                width: STROKE_WIDTH,
+                height: center.y - top + STROKE_WIDTH,
+            };
+
+            fill(renderer, stem, color);
+            arrow(renderer, start, end, center.y, color);
+        }
+    }
+}
+
+// 绘制一条从 start 指向 end 的水平箭头，箭头部分由逐渐变短的竖线拼成。
+fn arrow(renderer: &mut Renderer, start: f32, end: f32, y: f32, color: Color) {
+    let direction = if end > start { -1.0 } else { 1.0 };
+
+    let shaft = Rectangle {
+        x: start.min(end),
+        y: y - STROKE_WIDTH / 2.0,
+        width: (end - start).abs(),
+        height: STROKE_WIDTH,
+    };
+
+    fill(renderer, shaft, color);
+
+    for step in 1..=ARROW_HEAD as usize {
+        let offset = step as f32;
+        let head = Rectangle {
+            x: end + direction * offset - STROKE_WIDTH / 2.0,
+            y: y - offset,
+            width: STROKE_WIDTH,
+            height: offset * 2.0,
+        };
+
+        fill(renderer, head, color);
+    }
+}
+
+// 用纯色填充矩形。
+fn fill(renderer: &mut Renderer, bounds: Rectangle, color: Color) {
+    renderer.fill_quad(
+        renderer::Quad {
+            bounds,
+            border_radius: 0.0.into(),
+            border_width: 0.0,
+            border_color: Color::TRANSPARENT,
+        },
+        color,
+    );
+}
@@ -0,0 +1,147 @@
+use crate::brackets;
+use iced::advanced::text::highlighter::{self, Format};
+use iced::{Color, Font, Theme};
+use std::ops::Range;
+
+// 定义诊断的严重程度。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Error,       // 错误。
+    Warning,     // 警告。
+    Information, // 提示信息。
+}
+
+impl Severity {
+    // 返回标记诊断范围的颜色。
+    pub fn color(self) -> Color {
+        match self {
+            Severity::Error => Color::from_rgb8(0xf1, 0x4c, 0x4c),
+            Severity::Warning => Color::from_rgb8(0xe5, 0xb5, 0x3a),
+            Severity::Information => Color::from_rgb8(0x4f, 0xa3, 0xe0),
+        }
+    }
+}
+
+// 定义编辑器中的一条诊断，例如编译错误或警告。
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub line: usize,           // 所在的行，从 0 开始。
+    pub columns: Range<usize>, // 在行中的字符范围，跨越多行时到行尾为止。
+    pub severity: Severity,    // 严重程度。
+    pub message: String,       // 诊断信息。
+}
+
+// 定义高亮器的设置。
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settings {
+    pub brackets: brackets::Settings, // 语法和括号高亮的设置。
+    pub diagnostics: Vec<Diagnostic>, // 需要标记的诊断。
+}
+
+// 定义高亮的类型。
+pub enum Highlight {
+    Brackets(brackets::Highlight),
+    Diagnostic(Severity),
+}
+
+// 在括号高亮的基础上，用诊断的颜色标记对应的文本。
+//
+// 编辑器的文字格式只支持颜色和字体，无法画下划线，因此直接改变诊断范围内文字的颜色。
+pub struct Highlighter {
+    brackets: brackets::Highlighter,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl highlighter::Highlighter for Highlighter {
+    type Settings = Settings;
+    type Highlight = Highlight;
+    type Iterator<'a> = std::vec::IntoIter<(Range<usize>, Highlight)>;
+
+    fn new(settings: &Self::Settings) -> Self {
+        Self {
+            brackets: brackets::Highlighter::new(&settings.brackets),
+            diagnostics: settings.diagnostics.clone(),
+        }
+    }
+
+    fn update(&mut self, new_settings: &Self::Settings) {
+        self.brackets.update(&new_settings.brackets);
+
+        // 诊断变化时从受影响的最上面一行开始重新高亮。
+        if self.diagnostics != new_settings.diagnostics {
+            let changed = self
+                .diagnostics
+                .iter()
+                .chain(&new_settings.diagnostics)
+                .map(|diagnostic| diagnostic.line)
+                .min();
+
+            if let Some(line) = changed {
+                self.brackets
+                    .change_line(line.min(self.brackets.current_line()));
+            }
+
+            self.diagnostics = new_settings.diagnostics.clone();
+        }
+    }
+
+    fn change_line(&mut self, line: usize) {
+        self.brackets.change_line(line);
+    }
+
+    fn highlight_line(&mut self, line: &str) -> Self::Iterator<'_> {
+        let index = self.brackets.current_line();
+
+        let mut highlights: Vec<_> = self
+            .brackets
+            .highlight_line(line)
+            .map(|(range, highlight)| (range, Highlight::Brackets(highlight)))
+            .collect();
+
+        // 诊断的高亮放在最后，覆盖其他的颜色；严重的诊断排在后面，重叠时优先显示。
+        let mut diagnostics: Vec<_> = self
+            .diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.line == index)
+            .collect();
+        diagnostics.sort_by(|a, b| b.severity.cmp(&a.severity));
+
+        highlights.extend(diagnostics.into_iter().map(|diagnostic| {
+            (
+                byte_range(line, &diagnostic.columns),
+                Highlight::Diagnostic(diagnostic.severity),
+            )
+        }));
+
+        highlights.into_iter()
+    }
+
+    fn current_line(&self) -> usize {
+        self.brackets.current_line()
+    }
+}
+
+// 将高亮转换为编辑器使用的文字格式。
+pub fn format(highlight: &Highlight, theme: &Theme) -> Format<Font> {
+    match highlight {
+        Highlight::Brackets(highlight) => brackets::format(highlight, theme),
+        Highlight::Diagnostic(severity) => Format {
+            color: Some(severity.color()),
+            font: None,
+        },
+    }
+}
+
+// 将行中的字符范围转换为字节范围。范围为空时至少标记一个字符，超出行尾时截断。
+fn byte_range(line: &str, columns: &Range<usize>) -> Range<usize> {
+    let offset = |column: usize| {
+        line.char_indices()
+            .nth(column)
+            .map_or(line.len(), |(index, _)| index)
+    };
+
+    let start = offset(columns.start);
+    let end = offset(columns.end.max(columns.start + 1));
+
+    start..end
+}
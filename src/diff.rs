@@ -0,0 +1,449 @@
+use crate::encoding::Encoding;
+use crate::i18n::{tr, trf};
+use crate::line_ending::LineEnding;
+use crate::{Error, FileError, Message, Operation, LINE_HEIGHT};
+use iced::widget::{button, column, container, horizontal_space, row, scrollable, text};
+use iced::{theme, Color, Command, Element, Font, Length, Theme};
+use similar::{ChangeTag, TextDiff};
+use std::fmt;
+use std::ops::Range;
+use std::path::PathBuf;
+
+// 每处修改前后显示的未修改的行数。
+const CONTEXT: usize = 3;
+
+// 比较两个文件时，每处修改上方标题栏的高度，用于计算跳转到某处修改时的滚动位置。
+const HUNK_HEADER_HEIGHT: f32 = 32.0;
+
+// 修改行的背景颜色，半透明，在深色和浅色主题中都能看清文字。
+const ADDED: Color = Color::from_rgba(0.35, 0.65, 0.29, 0.25);
+const REMOVED: Color = Color::from_rgba(0.82, 0.29, 0.25, 0.25);
+
+// 比较两个文件时，选中的修改上方标题栏的背景颜色。
+const CURRENT: Color = Color::from_rgba(0.36, 0.5, 0.9, 0.3);
+
+// 定义显示差异的方式。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layout {
+    SideBySide, // 左右并排显示修改前后的内容。
+    Inline,     // 在同一列中依次显示删除和新增的行。
+}
+
+impl Layout {
+    // 所有可选的显示方式。
+    pub const ALL: &[Self] = &[Self::SideBySide, Self::Inline];
+}
+
+impl fmt::Display for Layout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(tr(match self {
+            Self::SideBySide => "Side by Side",
+            Self::Inline => "Inline",
+        }))
+    }
+}
+
+// 定义差异中一行的类型。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Equal,   // 未修改的行。
+    Added,   // 新增的行。
+    Removed, // 删除的行。
+}
+
+// 定义差异中的一行。
+#[derive(Debug, Clone, PartialEq)]
+pub struct Line {
+    pub kind: Kind,         // 行的类型。
+    pub old: Option<usize>, // 在修改前的文本中的行号，新增的行为 None。
+    pub new: Option<usize>, // 在修改后的文本中的行号，删除的行为 None。
+    pub text: String,       // 行的内容，不包括换行符。
+}
+
+// 定义一处修改及其前后的几行上下文。
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hunk {
+    pub old: Range<usize>, // 在修改前的文本中覆盖的行。
+    pub new: Range<usize>, // 在修改后的文本中覆盖的行。
+    pub lines: Vec<Line>,  // 包括上下文在内的各行。
+}
+
+// 定义两段文本之间的差异。
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Diff {
+    pub hunks: Vec<Hunk>, // 所有修改，按出现的顺序排列。
+}
+
+impl Diff {
+    // 逐行比较两段文本，各行以 \n 分隔。
+    pub fn compute(old: &str, new: &str) -> Self {
+        let diff = TextDiff::from_lines(old, new);
+
+        let hunks = diff
+            .grouped_ops(CONTEXT)
+            .iter()
+            .filter_map(|group| {
+                let (first, last) = (group.first()?, group.last()?);
+
+                let lines = group
+                    .iter()
+                    .flat_map(|op| diff.iter_changes(op))
+                    .map(|change| Line {
+                        kind: match change.tag() {
+                            ChangeTag::Equal => Kind::Equal,
+                            ChangeTag::Insert => Kind::Added,
+                            ChangeTag::Delete => Kind::Removed,
+                        },
+                        old: change.old_index(),
+                        new: change.new_index(),
+                        text: change.value().trim_end_matches(['\n', '\r']).to_string(),
+                    })
+                    .collect();
+
+                Some(Hunk {
+                    old: first.old_range().start..last.old_range().end,
+                    new: first.new_range().start..last.new_range().end,
+                    lines,
+                })
+            })
+            .collect();
+
+        Self { hunks }
+    }
+
+    // 两段文本是否完全相同。
+    pub fn is_empty(&self) -> bool {
+        self.hunks.is_empty()
+    }
+
+    // 统计新增和删除的行数。
+    pub fn statistics(&self) -> (usize, usize) {
+        let lines = self.hunks.iter().flat_map(|hunk| &hunk.lines);
+
+        lines.fold((0, 0), |(added, removed), line| match line.kind {
+            Kind::Added => (added + 1, removed),
+            Kind::Removed => (added, removed + 1),
+            Kind::Equal => (added, removed),
+        })
+    }
+
+    // 创建显示差异的视图，两次修改之间省略的未修改的行用分隔线表示。
+    pub fn view(&self, layout: Layout) -> Element<'_, Message> {
+        if self.is_empty() {
+            return container(text(tr("No changes")))
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .center_x()
+                .center_y()
+                .into();
+        }
+
+        let mut rows = Vec::new();
+
+        for (index, hunk) in self.hunks.iter().enumerate() {
+            if index > 0 {
+                rows.push(separator(hunk.old.start - self.hunks[index - 1].old.end));
+            }
+
+            match layout {
+                Layout::Inline => rows.extend(hunk.lines.iter().map(inline)),
+                Layout::SideBySide => rows.extend(side_by_side(&hunk.lines)),
+            }
+        }
+
+        scrollable(column(rows)).height(Length::Fill).into()
+    }
+}
+
+// 定义复制一处修改的目标。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Left,  // 复制到左侧的文档。
+    Right, // 复制到右侧的文档。
+}
+
+// 定义两个标签页之间的比较，左侧为发起比较的标签页，右侧为选择的文件。
+#[derive(Debug, Clone)]
+pub struct Comparison {
+    pub left: usize,               // 左侧标签页的标识。
+    pub right: usize,              // 右侧标签页的标识。
+    pub diff: Diff,                // 两个文档之间的差异。
+    pub current: usize,            // 当前选中的修改的序号。
+    pub revisions: (usize, usize), // 计算差异时两个文档的版本号。
+}
+
+impl Comparison {
+    // 比较两段文本，left 和 right 为各自所属的标签页标识和版本号。
+    pub fn new(left: (usize, usize), right: (usize, usize), old: &str, new: &str) -> Self {
+        Self {
+            left: left.0,
+            right: right.0,
+            diff: Diff::compute(old, new),
+            current: 0,
+            revisions: (left.1, right.1),
+        }
+    }
+
+    // 判断比较是否涉及指定的标签页。
+    pub fn involves(&self, id: usize) -> bool {
+        self.left == id || self.right == id
+    }
+
+    // 文档被修改后重新比较，选中的修改保持在有效的范围内。
+    pub fn update(&mut self, revisions: (usize, usize), old: &str, new: &str) {
+        self.diff = Diff::compute(old, new);
+        self.revisions = revisions;
+        self.current = self.current.min(self.diff.hunks.len().saturating_sub(1));
+    }
+
+    // 选中下一处（forward 为 true）或上一处修改，到达两端时循环，并滚动到该处修改。
+    pub fn step(&mut self, forward: bool) -> Command<Message> {
+        let count = self.diff.hunks.len();
+
+        if count == 0 {
+            return Command::none();
+        }
+
+        self.current = if forward {
+            (self.current + 1) % count
+        } else {
+            (self.current + count - 1) % count
+        };
+
+        self.reveal()
+    }
+
+    // 滚动到选中的修改。
+    //
+    // 每一行的高度按编辑器的行高估算，很长的行在窄窗口中换行时位置会略有偏差。
+    pub fn reveal(&self) -> Command<Message> {
+        let y: f32 = self.diff.hunks[..self.current.min(self.diff.hunks.len())]
+            .iter()
+            .map(|hunk| HUNK_HEADER_HEIGHT + pairs(&hunk.lines).len() as f32 * LINE_HEIGHT)
+            .sum();
+
+        scrollable::scroll_to(
+            Self::scrollable_id(),
+            scrollable::AbsoluteOffset { x: 0.0, y },
+        )
+    }
+
+    // 返回差异区域的标识，用于在切换修改时滚动。
+    fn scrollable_id() -> scrollable::Id {
+        scrollable::Id::new("comparison")
+    }
+
+    // 创建左右并排显示两个文档差异的视图，两侧的行在同一个滚动区域中，总是同步滚动。
+    //
+    // 每处修改上方显示所在的行和复制按钮，选中的修改用不同的背景标出。
+    pub fn view(&self) -> Element<'_, Message> {
+        if self.diff.is_empty() {
+            return container(text(tr("The files are identical")))
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .center_x()
+                .center_y()
+                .into();
+        }
+
+        let mut rows = Vec::new();
+
+        for (index, hunk) in self.diff.hunks.iter().enumerate() {
+            rows.push(hunk_header(index, hunk, index == self.current));
+            rows.extend(side_by_side(&hunk.lines));
+        }
+
+        scrollable(column(rows))
+            .id(Self::scrollable_id())
+            .height(Length::Fill)
+            .into()
+    }
+}
+
+// 定义一个异步函数，读取磁盘上的文件并与当前的文本比较。
+//
+// 文件使用标签页的字符编码解码，并统一换行符，避免只因换行符不同而显示整个文件被修改。
+pub async fn with_saved(path: PathBuf, encoding: Encoding, current: String) -> Result<Diff, Error> {
+    let bytes = tokio::fs::read(&path)
+        .await
+        .map_err(|error| Error::FileFailed(FileError::new(Operation::Read, &path, &error)))?;
+
+    let mut saved = LineEnding::normalize(&encoding.decode(&bytes).text);
+
+    if !saved.ends_with('\n') {
+        saved.push('\n');
+    }
+
+    Ok(Diff::compute(&saved, &current))
+}
+
+// 创建单列显示的一行：两侧的行号、修改标记和内容。
+fn inline(line: &Line) -> Element<'_, Message> {
+    let sign = match line.kind {
+        Kind::Equal => ' ',
+        Kind::Added => '+',
+        Kind::Removed => '-',
+    };
+
+    let content = row![
+        number(line.old),
+        number(line.new),
+        text(format!("{sign} {}", line.text)).font(Font::MONOSPACE)
+    ]
+    .spacing(10);
+
+    highlight(content, line.kind)
+}
+
+// 创建左右并排显示的各行。
+fn side_by_side(lines: &[Line]) -> Vec<Element<'_, Message>> {
+    pairs(lines)
+        .into_iter()
+        .map(|(left, right)| pair(left, right))
+        .collect()
+}
+
+// 将一处修改中的各行排列成左右并排的行：连续删除和新增的行两两配对，分别显示在左右两侧。
+fn pairs(lines: &[Line]) -> Vec<(Option<&Line>, Option<&Line>)> {
+    let mut pairs = Vec::new();
+    let mut index = 0;
+
+    while index < lines.len() {
+        if lines[index].kind == Kind::Equal {
+            pairs.push((Some(&lines[index]), Some(&lines[index])));
+            index += 1;
+            continue;
+        }
+
+        let removed: Vec<_> = lines[index..]
+            .iter()
+            .take_while(|line| line.kind == Kind::Removed)
+            .collect();
+        let added: Vec<_> = lines[index + removed.len()..]
+            .iter()
+            .take_while(|line| line.kind == Kind::Added)
+            .collect();
+
+        for row in 0..removed.len().max(added.len()) {
+            pairs.push((removed.get(row).copied(), added.get(row).copied()));
+        }
+
+        index += removed.len() + added.len();
+    }
+
+    pairs
+}
+
+// 创建左右并排的一行，left 为修改前的行，right 为修改后的行，没有对应的行时留空。
+fn pair<'a>(left: Option<&'a Line>, right: Option<&'a Line>) -> Element<'a, Message> {
+    let side = |line: Option<&'a Line>, is_left: bool| -> Element<'a, Message> {
+        let Some(line) = line else {
+            return container(text("")).width(Length::FillPortion(1)).into();
+        };
+
+        let content = row![
+            number(if is_left { line.old } else { line.new }),
+            text(&line.text).font(Font::MONOSPACE)
+        ]
+        .spacing(10);
+
+        container(highlight(content, line.kind))
+            .width(Length::FillPortion(1))
+            .into()
+    };
+
+    row![side(left, true), side(right, false)]
+        .spacing(10)
+        .into()
+}
+
+// 创建一处修改上方的标题栏：两侧覆盖的行，以及将这处修改复制到另一侧的按钮。
+fn hunk_header<'a>(index: usize, hunk: &Hunk, is_current: bool) -> Element<'a, Message> {
+    let copy = |label, side| {
+        button(text(label).size(14))
+            .on_press(Message::CopyHunk(index, side))
+            .padding([2, 8])
+            .style(theme::Button::Secondary)
+    };
+
+    let content = row![
+        text(trf(
+            "Lines {old} ↔ {new}",
+            &[("old", &range(&hunk.old)), ("new", &range(&hunk.new))],
+        ))
+        .size(14),
+        horizontal_space(Length::Fill),
+        copy(tr("Copy to Right →"), Side::Right),
+        copy(tr("← Copy to Left"), Side::Left)
+    ]
+    .spacing(10);
+
+    container(content)
+        .width(Length::Fill)
+        .height(HUNK_HEADER_HEIGHT)
+        .padding([4, 10])
+        .center_y()
+        .style(if is_current {
+            theme::Container::Custom(Box::new(Background(CURRENT)))
+        } else {
+            theme::Container::Box
+        })
+        .into()
+}
+
+// 将从 0 开始的行范围格式化为从 1 开始的“起始–结束”。
+fn range(lines: &Range<usize>) -> String {
+    if lines.is_empty() {
+        format!("{}", lines.start + 1)
+    } else {
+        format!("{}–{}", lines.start + 1, lines.end)
+    }
+}
+
+// 创建右对齐的行号，没有行号时留空。
+fn number<'a>(line: Option<usize>) -> Element<'a, Message> {
+    let label = line.map_or_else(String::new, |line| (line + 1).to_string());
+
+    text(format!("{label:>5}"))
+        .font(Font::MONOSPACE)
+        .style(Color::from_rgb(0.5, 0.5, 0.5))
+        .into()
+}
+
+// 创建表示省略了若干未修改的行的分隔线。
+fn separator<'a>(skipped: usize) -> Element<'a, Message> {
+    container(text(format!("⋯ {skipped} unchanged lines")).size(14))
+        .width(Length::Fill)
+        .padding([4, 10])
+        .style(theme::Container::Box)
+        .into()
+}
+
+// 按行的类型为一行设置背景颜色。
+fn highlight<'a>(content: impl Into<Element<'a, Message>>, kind: Kind) -> Element<'a, Message> {
+    let content = container(content).width(Length::Fill);
+
+    match kind {
+        Kind::Equal => content.into(),
+        Kind::Added => content
+            .style(theme::Container::Custom(Box::new(Background(ADDED))))
+            .into(),
+        Kind::Removed => content
+            .style(theme::Container::Custom(Box::new(Background(REMOVED))))
+            .into(),
+    }
+}
+
+// 使用纯色背景的容器样式。
+pub struct Background(pub Color);
+
+impl container::StyleSheet for Background {
+    type Style = Theme;
+
+    fn appearance(&self, _style: &Self::Style) -> container::Appearance {
+        container::Appearance {
+            background: Some(self.0.into()),
+            ..container::Appearance::default()
+        }
+    }
+}
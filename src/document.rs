@@ -0,0 +1,671 @@
+use crate::gutter::{Metrics, Viewport};
+use iced::widget::text_editor::{self, Action, Edit, Motion};
+use ropey::{Rope, RopeBuilder};
+use std::cell::Cell;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::ops::{Deref, Range};
+use std::sync::Arc;
+
+// 定义编辑器中的文档：文本编辑器的内容加上一份与之同步的 rope。
+//
+// 文本编辑器只能把整个文档复制成 String，rope 则可以廉价地克隆，
+// 用于撤销快照和保存，而不必每次都复制整个文档。
+// 读取光标、选区和行等信息时可以直接当作 text_editor::Content 使用。
+//
+// 文本编辑器不能隐藏行，折叠的区域中被折叠的行从内容中移除，保存在 folds 中，
+// 因此内容和 rope 中的行号都不包括被折叠的行，完整的文本由 text 和 full_rope 返回。
+pub struct Document {
+    content: text_editor::Content,  // 文本编辑器显示和编辑的内容。
+    rope: Rope,                     // 与内容同步的文本，各行之间以 \n 分隔。
+    viewport: Viewport,             // 估算的编辑器滚动位置，用于让标记栏与编辑器对齐。
+    metrics: Cell<Metrics>,         // 视图在最近一次布局时估算的编辑器尺寸。
+    folds: BTreeMap<usize, String>, // 折叠的区域的开始行及被折叠的文本，文本以换行符开头。
+}
+
+impl Document {
+    // 创建一个空白的文档。
+    pub fn new() -> Self {
+        Self::with_text("")
+    }
+
+    // 使用指定的文本创建文档。
+    pub fn with_text(text: &str) -> Self {
+        let content = text_editor::Content::with(text);
+        let rope = rope_of(&content);
+
+        Self {
+            content,
+            rope,
+            viewport: Viewport::default(),
+            metrics: Cell::default(),
+            folds: BTreeMap::new(),
+        }
+    }
+
+    // 使用 rope 创建文档，例如恢复撤销快照时。
+    pub fn with_rope(rope: Rope) -> Self {
+        Self {
+            content: text_editor::Content::with(&rope.to_string()),
+            rope,
+            viewport: Viewport::default(),
+            metrics: Cell::default(),
+            folds: BTreeMap::new(),
+        }
+    }
+
+    // 返回与内容同步的 rope。
+    pub fn rope(&self) -> &Rope {
+        &self.rope
+    }
+
+    // 返回包括被折叠的行在内的完整文本。
+    pub fn full_rope(&self) -> Rope {
+        let mut rope = self.rope.clone();
+
+        for (&line, hidden) in self.folds.iter().rev() {
+            rope.insert(line_end(&rope, line), hidden);
+        }
+
+        rope
+    }
+
+    // 返回文档的完整文本，包括被折叠的行，没有折叠时与 text_editor::Content::text 的结果相同，以换行符结尾。
+    pub fn text(&self) -> String {
+        let mut text = self.full_rope().to_string();
+
+        if !text.ends_with('\n') {
+            text.push('\n');
+        }
+
+        text
+    }
+
+    // 判断文档中是否有折叠的区域。
+    pub fn has_folds(&self) -> bool {
+        !self.folds.is_empty()
+    }
+
+    // 判断从指定的行开始的区域是否已经折叠。
+    pub fn is_folded(&self, line: usize) -> bool {
+        self.folds.contains_key(&line)
+    }
+
+    // 返回从指定的行开始的区域中被折叠的文本，以换行符开头，没有折叠时返回 None。
+    pub fn hidden(&self, line: usize) -> Option<&str> {
+        self.folds.get(&line).map(String::as_str)
+    }
+
+    // 返回各个折叠的区域的开始行及被折叠的行数，按开始行排序。
+    pub fn folds(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.folds
+            .iter()
+            .map(|(line, hidden)| (*line, hidden.matches('\n').count()))
+    }
+
+    // 把内容中的行号转换为完整文本中的行号。
+    pub fn full_line(&self, line: usize) -> usize {
+        line + self
+            .folds()
+            .filter(|(start, _)| *start < line)
+            .map(|(_, count)| count)
+            .sum::<usize>()
+    }
+
+    // 返回光标在完整文本中的行号和按字符计的列号。
+    //
+    // 文本编辑器返回的是显示的行号和行中的字节数，重新创建文档或在其他窗口中恢复光标时需要转换。
+    pub fn cursor(&self) -> (usize, usize) {
+        let (line, column) = self.content.cursor_position();
+        let column = self.content.line(line).map_or(0, |text| {
+            text.get(..column).unwrap_or_default().chars().count()
+        });
+
+        (self.full_line(line), column)
+    }
+
+    // 把完整文本中的行号转换为内容中的行号，被折叠的行对应折叠区域的开始行。
+    pub fn display_line(&self, line: usize) -> usize {
+        let mut hidden = 0;
+
+        for (start, count) in self.folds() {
+            if start + hidden >= line {
+                break;
+            }
+
+            if line <= start + hidden + count {
+                return start;
+            }
+
+            hidden += count;
+        }
+
+        line - hidden
+    }
+
+    // 折叠 start 行之后直到 end 行（包含）的各行，返回从内容中移除的行数。
+    //
+    // 区域中已经折叠的区域合并进新的区域。内容重新生成后光标回到文档开头，由调用者恢复。
+    pub fn fold(&mut self, start: usize, end: usize) -> usize {
+        let end = end.min(self.rope.len_lines().saturating_sub(1));
+
+        if end <= start {
+            return 0;
+        }
+
+        let from = line_end(&self.rope, start);
+        let to = line_end(&self.rope, end);
+        let mut hidden = Rope::from(self.rope.slice(from..to));
+
+        // 被折叠的文本的第 0 行是开始行的末尾，内容中的第 line 行对应其中的第 line - start 行。
+        // 开始行本身已经折叠时，原来被折叠的文本位于最前面。
+        let inner: Vec<usize> = self
+            .folds
+            .range(start..=end)
+            .map(|(line, _)| *line)
+            .collect();
+
+        for line in inner.into_iter().rev() {
+            let text = self.folds.remove(&line).unwrap_or_default();
+            hidden.insert(line_end(&hidden, line - start), &text);
+        }
+
+        self.rope.remove(from..to);
+        self.shift_folds(end + 1, -((end - start) as isize));
+        self.folds.insert(start, hidden.to_string());
+        self.reload();
+
+        end - start
+    }
+
+    // 展开从指定的行开始的区域，返回放回内容中的行数，这一行没有折叠时返回 0。
+    pub fn unfold(&mut self, line: usize) -> usize {
+        let Some(hidden) = self.folds.remove(&line) else {
+            return 0;
+        };
+
+        let count = hidden.matches('\n').count();
+
+        self.rope.insert(line_end(&self.rope, line), &hidden);
+        self.shift_folds(line + 1, count as isize);
+        self.reload();
+
+        count
+    }
+
+    // 行数改变了 delta 后移动折叠的区域：从 line 行（包含）开始的区域随之移动。
+    pub fn shift_folds(&mut self, line: usize, delta: isize) {
+        if delta == 0 || self.folds.is_empty() {
+            return;
+        }
+
+        self.folds = std::mem::take(&mut self.folds)
+            .into_iter()
+            .map(|(start, hidden)| {
+                if start >= line {
+                    (start.saturating_add_signed(delta), hidden)
+                } else {
+                    (start, hidden)
+                }
+            })
+            .collect();
+    }
+
+    // 使用 rope 重新生成文本编辑器的内容，滚动位置回到文档开头。
+    fn reload(&mut self) {
+        self.content = text_editor::Content::with(&self.rope.to_string());
+        self.viewport = Viewport::default();
+    }
+
+    // 返回估算的编辑器滚动位置。
+    pub fn viewport(&self) -> Viewport {
+        self.viewport
+    }
+
+    // 返回第一个可见的行在完整文本中的行号，用于保存会话。
+    pub fn first_visible_line(&self) -> usize {
+        self.full_line(self.viewport.line)
+    }
+
+    // 滚动到第一个可见的行为完整文本中的 line 行的位置，这一行被折叠时滚动到折叠区域的开始行。
+    pub fn scroll_to(&mut self, line: usize) {
+        let target = Viewport {
+            line: self
+                .display_line(line)
+                .min(self.rope.len_lines().saturating_sub(1)),
+            row: 0,
+        };
+
+        let lines = self
+            .viewport
+            .rows_to(&self.rope, self.metrics.get(), target);
+
+        if lines != 0 {
+            self.edit(Action::Scroll { lines });
+        }
+    }
+
+    // 记录视图估算的编辑器尺寸，用于之后跟踪滚动位置。
+    //
+    // 编辑器的尺寸只有在布局时才知道，因此由视图写入。
+    pub fn set_metrics(&self, metrics: Metrics) {
+        self.metrics.set(metrics);
+    }
+
+    // 把光标移到指定的行和列（按字符计），超出范围时停在文档或行的末尾。
+    //
+    // 从文档开头、光标所在的行和文档末尾中离目标行最近的位置出发。
+    pub fn move_to(&mut self, line: usize, column: usize) {
+        let last = self.content.line_count().saturating_sub(1);
+        let line = line.min(last);
+        let (current, _) = self.content.cursor_position();
+
+        // 有选区时第一次移动可能只是取消选区，不从光标所在的行出发。
+        let distance = match self.content.selection() {
+            Some(_) => usize::MAX,
+            None => current.abs_diff(line),
+        };
+
+        if line < distance && line <= last - line {
+            self.content.edit(Action::Move(Motion::DocumentStart));
+        } else if last - line < distance {
+            self.content.edit(Action::Move(Motion::DocumentEnd));
+        }
+
+        self.go_to(line, column, Action::Move);
+    }
+
+    // 把选区从锚点扩展到指定的行和列（按字符计），超出范围时停在文档或行的末尾。
+    pub fn select_to(&mut self, line: usize, column: usize) {
+        let line = line.min(self.content.line_count().saturating_sub(1));
+
+        self.go_to(line, column, Action::Select);
+    }
+
+    // 把光标逐行移到目标行，再从行首移到目标列，action 决定是否扩展选区。
+    //
+    // 文本编辑器只能按显示的行上下移动，自动换行时一行文本会显示为多行，
+    // 因此按光标实际所在的行判断是否到达，而不是数移动的次数。
+    fn go_to(&mut self, line: usize, column: usize, action: fn(Motion) -> Action) {
+        loop {
+            let position = self.content.cursor_position();
+            let motion = match position.0.cmp(&line) {
+                Ordering::Less => Motion::Down,
+                Ordering::Greater => Motion::Up,
+                Ordering::Equal => break,
+            };
+
+            self.content.edit(action(motion));
+
+            // 光标没有移动，已经到了文档的一端。
+            if self.content.cursor_position() == position {
+                break;
+            }
+        }
+
+        // 自动换行时 Home 只回到所在的显示行的开头，继续向左移到这一行的开头。
+        self.content.edit(action(Motion::Home));
+        while self.content.cursor_position().1 > 0 {
+            self.content.edit(action(Motion::Left));
+        }
+
+        let column = self
+            .content
+            .line(line)
+            .map_or(0, |text| text.chars().count())
+            .min(column);
+
+        for _ in 0..column {
+            self.content.edit(action(Motion::Right));
+        }
+
+        self.viewport = self
+            .viewport
+            .reveal(&self.rope, self.metrics.get(), line, column);
+    }
+
+    // 把另一个视图中的修改同步到这个文档，例如分屏中的两个视图。
+    //
+    // 只替换两者不同的部分，光标、选区和滚动位置保持不变；
+    // 修改涉及这个文档中被折叠的文本时重新生成内容，只保留光标所在的行和列。
+    pub fn sync(&mut self, source: &Document) {
+        let new = source.full_rope();
+        let Some((range, text)) = difference(&self.full_rope(), &new) else {
+            return;
+        };
+
+        if let Some(range) = self.content_range(range) {
+            self.replace(range, &text);
+            return;
+        }
+
+        let (line, column) = line_column(&self.rope, self.caret().1);
+
+        *self = Self::with_rope(new);
+        self.move_to(line, column);
+    }
+
+    // 把内容中 range 范围内的字符替换为 text，光标和选区随之移动，滚动位置保持不变。
+    fn replace(&mut self, range: Range<usize>, text: &str) {
+        let (anchor, head) = self.caret();
+        let viewport = self.viewport;
+        let first = self.rope.char_to_line(range.start);
+        let last = self.rope.char_to_line(range.end);
+        let delta = text.matches('\n').count() as isize - (last - first) as isize;
+
+        self.place(range.start, range.end);
+
+        if text.is_empty() {
+            self.content.edit(Action::Edit(Edit::Delete));
+        } else {
+            self.content
+                .edit(Action::Edit(Edit::Paste(Arc::new(text.to_string()))));
+        }
+
+        self.shift_folds(last, delta);
+        self.rope.remove(range.clone());
+        self.rope.insert(range.start, text);
+
+        // 替换的范围之前的位置不变，之后的位置随替换的文本的长度移动，之中的位置移到替换的文本之后。
+        let length = text.chars().count();
+        let moved = |position: usize| {
+            if position <= range.start {
+                position
+            } else if position >= range.end {
+                position - range.len() + length
+            } else {
+                range.start + length
+            }
+        };
+
+        self.place(moved(anchor), moved(head));
+
+        // 修改位于第一个可见的行之前时，可见的文本随之上下移动。
+        let target = if first < viewport.line {
+            Viewport {
+                line: viewport.line.saturating_add_signed(delta).max(first),
+                ..viewport
+            }
+        } else {
+            viewport
+        };
+
+        let lines = self
+            .viewport
+            .rows_to(&self.rope, self.metrics.get(), target);
+
+        if lines != 0 {
+            self.edit(Action::Scroll { lines });
+        }
+    }
+
+    // 返回选区的锚点和光标的位置（按字符计），没有选区时两者相同。
+    fn caret(&self) -> (usize, usize) {
+        let (line, column) = self.content.cursor_position();
+        let head = self.rope.line_to_char(line)
+            + self.content.line(line).map_or(0, |text| {
+                text.get(..column).unwrap_or_default().chars().count()
+            });
+
+        let Some(selection) = self.content.selection() else {
+            return (head, head);
+        };
+
+        // 文本编辑器不提供选区的另一端，按选中的文本判断它在光标之前还是之后。
+        let length = selection.chars().count();
+
+        if head >= length && self.rope.slice(head - length..head) == selection.as_str() {
+            (head - length, head)
+        } else {
+            ((head + length).min(self.rope.len_chars()), head)
+        }
+    }
+
+    // 把光标移到 head（按字符计），与 anchor 不同时选中两者之间的文本。
+    fn place(&mut self, anchor: usize, head: usize) {
+        let (line, column) = line_column(&self.rope, anchor);
+        self.move_to(line, column);
+
+        if head != anchor {
+            let (line, column) = line_column(&self.rope, head);
+            self.select_to(line, column);
+        }
+    }
+
+    // 把完整文本中的字符范围转换为内容中的范围，范围包含被折叠的文本时返回 None。
+    fn content_range(&self, range: Range<usize>) -> Option<Range<usize>> {
+        let mut hidden = 0;
+
+        for (&line, text) in &self.folds {
+            let start = line_end(&self.rope, line) + hidden;
+            let length = text.chars().count();
+
+            if range.end <= start {
+                break;
+            }
+
+            if range.start < start + length {
+                return None;
+            }
+
+            hidden += length;
+        }
+
+        Some(range.start - hidden..range.end - hidden)
+    }
+
+    // 执行一个编辑器动作，并跟踪编辑器的滚动位置：滚动时移动相应的行数，光标移动后滚动到光标可见的位置。
+    pub fn edit(&mut self, action: Action) {
+        let scroll = match &action {
+            Action::Scroll { lines } => Some(*lines),
+            _ => None,
+        };
+        let cursor = self.content.cursor_position();
+
+        self.apply(action);
+
+        let metrics = self.metrics.get();
+
+        if let Some(lines) = scroll {
+            self.viewport = self.viewport.scroll(&self.rope, metrics, lines);
+            return;
+        }
+
+        let (line, column) = self.content.cursor_position();
+
+        if (line, column) != cursor {
+            let column = self.content.line(line).map_or(0, |text| {
+                text.get(..column).unwrap_or_default().chars().count()
+            });
+
+            self.viewport = self.viewport.reveal(&self.rope, metrics, line, column);
+        }
+    }
+
+    // 执行替换选区的编辑，在 rope 中替换相应的范围。
+    //
+    // 选区的另一端是推测出来的，编辑后的光标位置或行数与推测的结果不符时重新生成整个 rope。
+    fn replace_selection(&mut self, action: Action) {
+        let (anchor, head) = self.caret();
+        let start = anchor.min(head);
+        let end = anchor.max(head);
+
+        let text = match &action {
+            Action::Edit(Edit::Insert(character)) => character.to_string(),
+            Action::Edit(Edit::Paste(text)) => text.to_string(),
+            Action::Edit(Edit::Enter) => String::from("\n"),
+            _ => String::new(),
+        };
+
+        self.content.edit(action);
+        self.rope.remove(start..end);
+        self.rope.insert(start, &text);
+
+        if self.rope.len_lines() != self.content.line_count()
+            || self.caret().1 != start + text.chars().count()
+        {
+            self.rope = rope_of(&self.content);
+        }
+    }
+
+    // 执行一个编辑器动作，并增量更新 rope。
+    //
+    // 没有选区时，编辑只影响光标所在的一行及因换行或合并而增减的行；
+    // 有选区时，编辑把选中的范围替换为输入的文本。
+    fn apply(&mut self, action: Action) {
+        if !action.is_edit() {
+            self.content.edit(action);
+            return;
+        }
+
+        if self.content.selection().is_some() {
+            self.replace_selection(action);
+            return;
+        }
+
+        let line_count = self.content.line_count();
+        let (line, _) = self.content.cursor_position();
+
+        self.content.edit(action);
+
+        let new_line_count = self.content.line_count();
+        let (new_line, _) = self.content.cursor_position();
+
+        // 受影响的第一行，以及编辑前后受影响的行数。
+        let first = line.min(new_line);
+        let removed = 1 + line_count.saturating_sub(new_line_count);
+        let added = 1 + new_line_count.saturating_sub(line_count);
+
+        let start = self.rope.line_to_char(first);
+        let end = if first + removed < line_count {
+            self.rope.line_to_char(first + removed)
+        } else {
+            self.rope.len_chars()
+        };
+
+        let mut replacement = (first..first + added)
+            .filter_map(|index| self.content.line(index).map(|line| line.to_string()))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        if first + added < new_line_count {
+            replacement.push('\n');
+        }
+
+        self.rope.remove(start..end);
+        self.rope.insert(start, &replacement);
+    }
+}
+
+impl Default for Document {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Deref for Document {
+    type Target = text_editor::Content;
+
+    fn deref(&self) -> &Self::Target {
+        &self.content
+    }
+}
+
+// 根据文本编辑器的内容生成 rope。
+fn rope_of(content: &text_editor::Content) -> Rope {
+    let mut builder = RopeBuilder::new();
+
+    for (index, line) in content.lines().enumerate() {
+        if index > 0 {
+            builder.append("\n");
+        }
+
+        builder.append(&line);
+    }
+
+    builder.finish()
+}
+
+// 比较两段文本，返回 old 中需要替换的字符范围及替换成的文本，两者相同时返回 None。
+//
+// 从两端开始跳过相同的部分，不需要复制整个文档。
+fn difference(old: &Rope, new: &Rope) -> Option<(Range<usize>, String)> {
+    let prefix = old
+        .bytes()
+        .zip(new.bytes())
+        .take_while(|(old, new)| old == new)
+        .count();
+
+    if prefix == old.len_bytes() && prefix == new.len_bytes() {
+        return None;
+    }
+
+    let limit = old.len_bytes().min(new.len_bytes()) - prefix;
+    let mut old_bytes = old.bytes_at(old.len_bytes());
+    let mut new_bytes = new.bytes_at(new.len_bytes());
+    let mut suffix = 0;
+
+    while suffix < limit
+        && old_bytes
+            .prev()
+            .is_some_and(|byte| new_bytes.prev() == Some(byte))
+    {
+        suffix += 1;
+    }
+
+    // 相同的部分可能在一个多字节字符的中间结束，把这个字符算作修改的一部分。
+    let start = old.byte_to_char(prefix);
+    let old_end = char_after(old, old.len_bytes() - suffix);
+    let new_end = char_after(new, new.len_bytes() - suffix);
+
+    Some((start..old_end, new.slice(start..new_end).to_string()))
+}
+
+// 返回从指定的字节位置开始的第一个完整字符的位置。
+fn char_after(rope: &Rope, byte: usize) -> usize {
+    let index = rope.byte_to_char(byte);
+
+    if rope.char_to_byte(index) < byte {
+        index + 1
+    } else {
+        index
+    }
+}
+
+// 把字符位置转换为行和列（按字符计）。
+fn line_column(rope: &Rope, index: usize) -> (usize, usize) {
+    let line = rope.char_to_line(index);
+
+    (line, index - rope.line_to_char(line))
+}
+
+// 返回一行末尾（换行符之前）的字符位置。
+fn line_end(rope: &Rope, line: usize) -> usize {
+    if line + 1 < rope.len_lines() {
+        rope.line_to_char(line + 1) - 1
+    } else {
+        rope.len_chars()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_the_cursor_in_characters() {
+        let mut document = Document::with_text("第一行\n中文 text");
+        document.move_to(1, 4);
+
+        assert_eq!(document.cursor(), (1, 4));
+    }
+
+    #[test]
+    fn reports_the_cursor_line_in_the_full_text() {
+        let mut document = Document::with_text("fn main() {\n    a();\n}\n中文");
+        document.fold(0, 2);
+        document.move_to(1, 1);
+
+        assert_eq!(document.cursor(), (3, 1));
+    }
+}
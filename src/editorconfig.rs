@@ -0,0 +1,313 @@
+use crate::encoding::Encoding;
+use crate::indent;
+use crate::line_ending::LineEnding;
+use std::path::{Path, PathBuf};
+
+// 定义 .editorconfig 中适用于某个文件的属性，没有设置的属性为 None。
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Properties {
+    pub use_tabs: Option<bool>,          // indent_style：是否使用制表符缩进。
+    pub indent_size: Option<usize>,      // indent_size：一级缩进的空格数。
+    pub tab_width: Option<usize>,        // tab_width：制表符的显示宽度。
+    pub end_of_line: Option<LineEnding>, // end_of_line：保存时使用的换行符。
+    pub charset: Option<(Encoding, bool)>, // charset：保存时使用的字符编码，以及是否写入 BOM。
+    pub trim_trailing_whitespace: Option<bool>, // trim_trailing_whitespace：保存时是否去掉行尾空白。
+}
+
+impl Properties {
+    // 返回缩进相关的属性，作为缩进规则的覆盖项。
+    //
+    // indent_size 为 tab 时与制表符宽度相同；没有设置 tab_width 时使用 indent_size。
+    pub fn indentation(&self) -> indent::Override {
+        indent::Override {
+            use_tabs: self.use_tabs,
+            size: self.indent_size.or(self.tab_width),
+            tab_width: self.tab_width.or(self.indent_size),
+        }
+    }
+
+    // 设置一个属性，值为 unset 时清除之前设置的值，无法识别的属性和值会被忽略。
+    fn set(&mut self, key: &str, value: &str) {
+        let unset = value == "unset";
+        let number = || value.parse().ok().filter(|size| *size > 0);
+        let boolean = || match value {
+            "true" => Some(true),
+            "false" => Some(false),
+            _ => None,
+        };
+
+        match key {
+            "indent_style" => {
+                self.use_tabs = match value {
+                    "tab" => Some(true),
+                    "space" => Some(false),
+                    _ if unset => None,
+                    _ => self.use_tabs,
+                }
+            }
+            "indent_size" => {
+                self.indent_size = match value {
+                    // 与制表符宽度相同，在 indentation 中处理。
+                    "tab" => None,
+                    _ if unset => None,
+                    _ => number().or(self.indent_size),
+                }
+            }
+            "tab_width" => {
+                self.tab_width = if unset {
+                    None
+                } else {
+                    number().or(self.tab_width)
+                }
+            }
+            "end_of_line" => {
+                self.end_of_line = match value {
+                    "lf" => Some(LineEnding::Lf),
+                    "crlf" => Some(LineEnding::CrLf),
+                    "cr" => Some(LineEnding::Cr),
+                    _ if unset => None,
+                    _ => self.end_of_line,
+                }
+            }
+            "charset" => {
+                self.charset = if unset {
+                    None
+                } else {
+                    Encoding::for_label(value).or(self.charset)
+                }
+            }
+            "trim_trailing_whitespace" => {
+                self.trim_trailing_whitespace = if unset {
+                    None
+                } else {
+                    boolean().or(self.trim_trailing_whitespace)
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+// 定义一个异步函数，从文件所在目录向上查找 .editorconfig，返回适用于该文件的属性。
+//
+// 查找到 root = true 的文件或者文件系统的根目录为止，离文件越近的配置优先级越高。
+// 无法读取的配置文件会被跳过。
+pub async fn load(path: PathBuf) -> Properties {
+    let mut files = Vec::new();
+
+    for directory in path.ancestors().skip(1) {
+        let Ok(contents) = tokio::fs::read_to_string(directory.join(".editorconfig")).await else {
+            continue;
+        };
+
+        let config = parse(&contents);
+        let is_root = config.is_root;
+        files.push((directory.to_path_buf(), config));
+
+        if is_root {
+            break;
+        }
+    }
+
+    let mut properties = Properties::default();
+
+    for (directory, config) in files.iter().rev() {
+        let Some(relative) = relative_path(directory, &path) else {
+            continue;
+        };
+
+        for (pattern, pairs) in &config.sections {
+            if section_matches(pattern, &relative) {
+                for (key, value) in pairs {
+                    properties.set(key, value);
+                }
+            }
+        }
+    }
+
+    properties
+}
+
+// 定义解析后的 .editorconfig 文件。
+#[derive(Debug, Default)]
+struct Config {
+    is_root: bool,                                  // 是否设置了 root = true。
+    sections: Vec<(String, Vec<(String, String)>)>, // 每一节的文件匹配模式和其中的属性。
+}
+
+// 解析 .editorconfig 文件。属性名和属性值不区分大小写，统一转换为小写。
+fn parse(contents: &str) -> Config {
+    let mut config = Config::default();
+
+    for line in contents.lines() {
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with(['#', ';']) {
+            continue;
+        }
+
+        if let Some(pattern) = line
+            .strip_prefix('[')
+            .and_then(|line| line.strip_suffix(']'))
+        {
+            config.sections.push((pattern.to_string(), Vec::new()));
+            continue;
+        }
+
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+
+        let key = key.trim().to_lowercase();
+        let value = value.trim().to_lowercase();
+
+        match config.sections.last_mut() {
+            Some((_, pairs)) => pairs.push((key, value)),
+            None if key == "root" => config.is_root = value == "true",
+            None => {}
+        }
+    }
+
+    config
+}
+
+// 返回文件相对于配置文件所在目录的路径，使用 / 分隔。
+fn relative_path(directory: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(directory).ok()?;
+
+    let parts: Vec<_> = relative
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect();
+
+    Some(parts.join("/"))
+}
+
+// 判断一节的模式是否匹配文件。包含 / 的模式相对于配置文件所在的目录，否则匹配任意目录中的文件名。
+fn section_matches(pattern: &str, relative: &str) -> bool {
+    let pattern = if pattern.contains('/') {
+        pattern.trim_start_matches('/').to_string()
+    } else {
+        format!("**/{pattern}")
+    };
+
+    expand(&pattern).iter().any(|pattern| {
+        let pattern: Vec<char> = pattern.chars().collect();
+        let path: Vec<char> = relative.chars().collect();
+
+        // 不在子目录中的文件也能匹配以 **/ 开头的模式。
+        glob(&pattern, &path)
+            || (pattern.starts_with(&['*', '*', '/']) && glob(&pattern[3..], &path))
+    })
+}
+
+// 展开模式中的 {a,b} 和 {1..3}，返回不含大括号的所有模式。
+fn expand(pattern: &str) -> Vec<String> {
+    let Some(start) = pattern.find('{') else {
+        return vec![pattern.to_string()];
+    };
+
+    // 找到与之匹配的右大括号，并按顶层的逗号拆分。
+    let mut depth = 0;
+    let mut end = None;
+    let mut splits = Vec::new();
+
+    for (index, c) in pattern[start..].char_indices() {
+        let index = start + index;
+
+        match c {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    end = Some(index);
+                    break;
+                }
+            }
+            ',' if depth == 1 => splits.push(index),
+            _ => {}
+        }
+    }
+
+    let Some(end) = end else {
+        return vec![pattern.to_string()];
+    };
+
+    let inner = &pattern[start + 1..end];
+    let (prefix, suffix) = (&pattern[..start], &pattern[end + 1..]);
+
+    let alternatives: Vec<String> = if splits.is_empty() {
+        match inner
+            .split_once("..")
+            .and_then(|(from, to)| Some((from.parse::<i64>().ok()?, to.parse::<i64>().ok()?)))
+        {
+            Some((from, to)) => (from.min(to)..=from.max(to))
+                .map(|number| number.to_string())
+                .collect(),
+            // 没有逗号的大括号按字面意思匹配。
+            None => vec![format!("{{{inner}}}")],
+        }
+    } else {
+        let mut alternatives = Vec::new();
+        let mut previous = start + 1;
+
+        for split in splits.into_iter().chain([end]) {
+            alternatives.push(pattern[previous..split].to_string());
+            previous = split + 1;
+        }
+
+        alternatives
+    };
+
+    alternatives
+        .into_iter()
+        .flat_map(|alternative| {
+            let rest = expand(&format!("{alternative}{suffix}"));
+            rest.into_iter().map(move |rest| format!("{prefix}{rest}"))
+        })
+        .collect()
+}
+
+// 匹配通配符：** 匹配任意字符，* 和 ? 不匹配路径分隔符，[...] 匹配字符集合。
+fn glob(pattern: &[char], path: &[char]) -> bool {
+    match pattern {
+        [] => path.is_empty(),
+        ['*', '*', rest @ ..] => (0..=path.len()).any(|skip| glob(rest, &path[skip..])),
+        ['*', rest @ ..] => {
+            let segment = path.iter().position(|c| *c == '/').unwrap_or(path.len());
+            (0..=segment).any(|skip| glob(rest, &path[skip..]))
+        }
+        ['?', rest @ ..] => path.first().is_some_and(|c| *c != '/') && glob(rest, &path[1..]),
+        ['[', rest @ ..] => {
+            let Some(close) = rest.iter().position(|c| *c == ']') else {
+                return path.first() == Some(&'[') && glob(rest, &path[1..]);
+            };
+
+            let Some(&c) = path.first() else {
+                return false;
+            };
+
+            let (negated, set) = match &rest[..close] {
+                ['!', set @ ..] => (true, set),
+                set => (false, set),
+            };
+
+            let mut found = false;
+            let mut index = 0;
+
+            while index < set.len() {
+                if index + 2 < set.len() && set[index + 1] == '-' {
+                    found |= (set[index]..=set[index + 2]).contains(&c);
+                    index += 3;
+                } else {
+                    found |= set[index] == c;
+                    index += 1;
+                }
+            }
+
+            found != negated && c != '/' && glob(&rest[close + 1..], &path[1..])
+        }
+        ['\\', escaped, rest @ ..] => path.first() == Some(escaped) && glob(rest, &path[1..]),
+        [literal, rest @ ..] => path.first() == Some(literal) && glob(rest, &path[1..]),
+    }
+}
@@ -0,0 +1,246 @@
+use chardetng::EncodingDetector;
+use encoding_rs::EncoderResult;
+use std::fmt;
+use std::sync::Arc;
+
+// 定义文件的字符编码。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Encoding(&'static encoding_rs::Encoding);
+
+// 定义解码后的文件内容。
+#[derive(Debug, Clone)]
+pub struct Decoded {
+    pub text: Arc<String>,  // 解码后的文本。
+    pub encoding: Encoding, // 使用的编码。
+    pub is_uncertain: bool, // 编码是否为不确定的猜测结果。
+    pub is_read_only: bool, // 文件是否没有写入权限，由加载文件时检查。
+    pub has_bom: bool,      // 文件开头是否有 BOM。
+}
+
+impl Encoding {
+    pub const UTF_8: Self = Self(&encoding_rs::UTF_8_INIT);
+
+    // 返回状态栏中可供手动选择的编码。
+    pub fn all() -> Vec<Self> {
+        [
+            encoding_rs::UTF_8,
+            encoding_rs::UTF_16LE,
+            encoding_rs::UTF_16BE,
+            encoding_rs::GBK,
+            encoding_rs::GB18030,
+            encoding_rs::BIG5,
+            encoding_rs::SHIFT_JIS,
+            encoding_rs::EUC_JP,
+            encoding_rs::EUC_KR,
+            encoding_rs::WINDOWS_1251,
+            encoding_rs::WINDOWS_1252,
+            encoding_rs::KOI8_R,
+        ]
+        .into_iter()
+        .map(Self)
+        .collect()
+    }
+
+    // 根据编码名称查找编码以及保存时是否写入 BOM，例如 .editorconfig 中的 charset，
+    // 只有 utf-8-bom 需要写入 BOM，不支持的名称返回 None。
+    pub fn for_label(label: &str) -> Option<(Self, bool)> {
+        match label {
+            "latin1" => Some((Self(encoding_rs::WINDOWS_1252), false)),
+            "utf-8-bom" => Some((Self::UTF_8, true)),
+            _ => encoding_rs::Encoding::for_label(label.as_bytes())
+                .map(|encoding| (Self(encoding), false)),
+        }
+    }
+
+    // 检测字节内容的编码并解码：依次检查 BOM、UTF-8 有效性，最后使用启发式猜测。
+    pub fn detect(bytes: &[u8]) -> Decoded {
+        if let Some((encoding, _)) = encoding_rs::Encoding::for_bom(bytes) {
+            return Self(encoding).decode(bytes);
+        }
+
+        if let Ok(text) = std::str::from_utf8(bytes) {
+            return Decoded {
+                text: Arc::new(text.to_owned()),
+                encoding: Self::UTF_8,
+                is_uncertain: false,
+                is_read_only: false,
+                has_bom: false,
+            };
+        }
+
+        let mut detector = EncodingDetector::new();
+        let _ = detector.feed(bytes, true);
+        let (encoding, is_confident) = detector.guess_assess(None, true);
+
+        let mut decoded = Self(encoding).decode(bytes);
+        decoded.is_uncertain |= !is_confident;
+        decoded
+    }
+
+    // 使用当前编码解码字节内容，无法解码的字节会被替换字符代替。
+    //
+    // 内容以 BOM 开头时按照 BOM 选择编码并去掉 BOM，记录下来以便保存时写回。
+    pub fn decode(self, bytes: &[u8]) -> Decoded {
+        let (text, encoding, had_errors) = self.0.decode(bytes);
+
+        Decoded {
+            text: Arc::new(text.into_owned()),
+            encoding: Self(encoding),
+            is_uncertain: had_errors,
+            is_read_only: false,
+            has_bom: encoding_rs::Encoding::for_bom(bytes).is_some(),
+        }
+    }
+
+    // 将依次排列的文本片段编码为当前编码的字节内容，has_bom 为 true 时在 UTF-8 内容的开头写入 BOM。
+    //
+    // 逐段编码，不需要先把整个文档拼接成一个字符串。
+    // 文本中有当前编码无法表示的字符时返回第一个这样的字符，不输出用数字实体代替的内容。
+    pub fn encode<T: AsRef<str>>(
+        self,
+        chunks: impl IntoIterator<Item = T>,
+        has_bom: bool,
+    ) -> Result<Vec<u8>, char> {
+        let mut bytes = Vec::new();
+
+        // encoding_rs 不支持输出 UTF-16，需要手动编码，并且总是写入 BOM。
+        if self.0 == encoding_rs::UTF_16LE {
+            bytes.extend([0xFF, 0xFE]);
+            for chunk in chunks {
+                bytes.extend(chunk.as_ref().encode_utf16().flat_map(u16::to_le_bytes));
+            }
+            return Ok(bytes);
+        }
+
+        if self.0 == encoding_rs::UTF_16BE {
+            bytes.extend([0xFE, 0xFF]);
+            for chunk in chunks {
+                bytes.extend(chunk.as_ref().encode_utf16().flat_map(u16::to_be_bytes));
+            }
+            return Ok(bytes);
+        }
+
+        if self.0 == encoding_rs::UTF_8 {
+            if has_bom {
+                bytes.extend([0xEF, 0xBB, 0xBF]);
+            }
+            for chunk in chunks {
+                bytes.extend_from_slice(chunk.as_ref().as_bytes());
+            }
+            return Ok(bytes);
+        }
+
+        let mut encoder = self.0.new_encoder();
+        let mut encode = |mut text: &str, last: bool| loop {
+            let needed = encoder
+                .max_buffer_length_from_utf8_without_replacement(text.len())
+                .unwrap_or(text.len() * 4);
+            bytes.reserve(needed);
+
+            let (result, read) =
+                encoder.encode_from_utf8_to_vec_without_replacement(text, &mut bytes, last);
+
+            match result {
+                EncoderResult::InputEmpty => return Ok(()),
+                EncoderResult::OutputFull => text = &text[read..],
+                EncoderResult::Unmappable(character) => return Err(character),
+            }
+        };
+
+        for chunk in chunks {
+            encode(chunk.as_ref(), false)?;
+        }
+
+        // 最后用一个空的片段结束编码，有状态的编码（例如 ISO-2022-JP）需要在末尾恢复初始状态。
+        encode("", true)?;
+
+        Ok(bytes)
+    }
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_through_legacy_encodings() {
+        let text = "编辑器 Editor";
+
+        for encoding in [encoding_rs::GBK, encoding_rs::GB18030, encoding_rs::BIG5] {
+            let encoding = Encoding(encoding);
+            let bytes = encoding.encode([text], false).unwrap();
+            let decoded = encoding.decode(&bytes);
+
+            assert_eq!(decoded.text.as_str(), text);
+            assert!(!decoded.is_uncertain);
+        }
+    }
+
+    #[test]
+    fn encodes_chunks_like_a_single_string() {
+        let encoding = Encoding(encoding_rs::SHIFT_JIS);
+        let chunks = encoding.encode(["日本", "語\n", "テキスト"], false);
+
+        assert_eq!(chunks, encoding.encode(["日本語\nテキスト"], false));
+    }
+
+    #[test]
+    fn refuses_unmappable_characters() {
+        let encoding = Encoding(encoding_rs::WINDOWS_1252);
+
+        assert_eq!(encoding.encode(["price: 10€ or 10円"], false), Err('円'));
+    }
+
+    #[test]
+    fn keeps_the_utf8_bom() {
+        let bytes = Encoding::UTF_8.encode(["text"], true).unwrap();
+        assert_eq!(bytes, b"\xEF\xBB\xBFtext");
+
+        let decoded = Encoding::detect(&bytes);
+        assert_eq!(decoded.encoding, Encoding::UTF_8);
+        assert_eq!(decoded.text.as_str(), "text");
+        assert!(decoded.has_bom);
+    }
+
+    #[test]
+    fn detects_utf16_by_bom() {
+        let encoding = Encoding(encoding_rs::UTF_16LE);
+        let bytes = encoding.encode(["文本"], false).unwrap();
+        let decoded = Encoding::detect(&bytes);
+
+        assert_eq!(decoded.encoding, encoding);
+        assert_eq!(decoded.text.as_str(), "文本");
+    }
+
+    #[test]
+    fn maps_editorconfig_charsets() {
+        assert_eq!(
+            Encoding::for_label("latin1"),
+            Some((Encoding(encoding_rs::WINDOWS_1252), false))
+        );
+        assert_eq!(Encoding::for_label("utf-8"), Some((Encoding::UTF_8, false)));
+        assert_eq!(
+            Encoding::for_label("utf-8-bom"),
+            Some((Encoding::UTF_8, true))
+        );
+        assert_eq!(Encoding::for_label("unknown"), None);
+    }
+
+    #[test]
+    fn round_trips_the_utf8_bom_label() {
+        let (encoding, has_bom) = Encoding::for_label("utf-8-bom").unwrap();
+        let bytes = encoding.encode(["text"], has_bom).unwrap();
+        let decoded = Encoding::detect(&bytes);
+
+        assert_eq!(bytes, b"\xEF\xBB\xBFtext");
+        assert_eq!(decoded.encoding, Encoding::UTF_8);
+        assert_eq!(decoded.text.as_str(), "text");
+        assert!(decoded.has_bom);
+    }
+}
@@ -0,0 +1,209 @@
+use crate::i18n::tr;
+use crate::{Error, FileError, Message, Operation};
+use iced::widget::{button, column, container, row, scrollable, text, Space};
+use iced::{theme, Element, Length};
+use std::path::{Path, PathBuf};
+
+// 定义目录中的一个条目。
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub path: PathBuf, // 条目的完整路径。
+    pub is_dir: bool,  // 是否为目录。
+}
+
+// 定义文件树中的一个节点。
+#[derive(Debug)]
+struct Node {
+    path: PathBuf,               // 节点的完整路径。
+    name: String,                // 显示的名称。
+    is_dir: bool,                // 是否为目录。
+    expanded: bool,              // 目录是否已展开。
+    children: Option<Vec<Node>>, // 子节点，尚未加载时为 None。
+}
+
+// 定义侧边栏中的项目文件树，工作区有多个根目录时依次显示。
+#[derive(Debug)]
+pub struct FileTree {
+    roots: Vec<Node>, // 根目录节点，至少有一个。
+}
+
+impl Node {
+    // 根据目录条目创建节点。
+    fn new(path: PathBuf, is_dir: bool) -> Self {
+        let name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .map_or_else(|| path.display().to_string(), str::to_string);
+
+        Self {
+            path,
+            name,
+            is_dir,
+            expanded: false,
+            children: None,
+        }
+    }
+
+    // 查找指定路径对应的节点。
+    fn find_mut(&mut self, path: &Path) -> Option<&mut Node> {
+        if self.path == path {
+            return Some(self);
+        }
+
+        if !path.starts_with(&self.path) {
+            return None;
+        }
+
+        self.children
+            .as_mut()?
+            .iter_mut()
+            .find_map(|child| child.find_mut(path))
+    }
+
+    // 将节点及展开的子节点渲染为按缩进排列的行。
+    fn view<'a>(&self, depth: u16, rows: &mut Vec<Element<'a, Message>>) {
+        let label = if self.is_dir {
+            format!("{} {}", if self.expanded { "▾" } else { "▸" }, self.name)
+        } else {
+            format!("  {}", self.name)
+        };
+
+        let on_press = if self.is_dir {
+            Message::DirectoryToggled(self.path.clone())
+        } else {
+            Message::FileClicked(self.path.clone())
+        };
+
+        rows.push(
+            row![
+                Space::with_width(depth * 12),
+                button(text(label).size(14))
+                    .on_press(on_press)
+                    .padding([2, 5])
+                    .style(theme::Button::Text)
+            ]
+            .into(),
+        );
+
+        if self.expanded {
+            for child in self.children.iter().flatten() {
+                child.view(depth + 1, rows);
+            }
+        }
+    }
+}
+
+impl FileTree {
+    // 以指定目录为根创建文件树，根目录默认展开。
+    pub fn new(root: PathBuf) -> Self {
+        Self::with_roots(vec![root])
+    }
+
+    // 以工作区的多个目录为根创建文件树，roots 不能为空。
+    pub fn with_roots(roots: Vec<PathBuf>) -> Self {
+        let roots = roots
+            .into_iter()
+            .map(|root| {
+                let mut root = Node::new(root, true);
+                root.expanded = true;
+                root
+            })
+            .collect();
+
+        Self { roots }
+    }
+
+    // 返回文件树的第一个根目录。
+    pub fn root(&self) -> &Path {
+        &self.roots[0].path
+    }
+
+    // 返回文件树的所有根目录。
+    pub fn roots(&self) -> Vec<PathBuf> {
+        self.roots.iter().map(|root| root.path.clone()).collect()
+    }
+
+    // 查找指定路径对应的节点。
+    fn find_mut(&mut self, path: &Path) -> Option<&mut Node> {
+        self.roots.iter_mut().find_map(|root| root.find_mut(path))
+    }
+
+    // 切换目录的展开状态，如果目录内容尚未加载则返回 true。
+    pub fn toggle(&mut self, path: &Path) -> bool {
+        let Some(node) = self.find_mut(path) else {
+            return false;
+        };
+
+        node.expanded = !node.expanded;
+        node.expanded && node.children.is_none()
+    }
+
+    // 使用加载完成的目录条目填充对应的节点。
+    pub fn set_entries(&mut self, path: &Path, entries: Vec<Entry>) {
+        if let Some(node) = self.find_mut(path) {
+            node.children = Some(
+                entries
+                    .into_iter()
+                    .map(|entry| Node::new(entry.path, entry.is_dir))
+                    .collect(),
+            );
+        }
+    }
+
+    // 创建文件树的视图。
+    pub fn view(&self) -> Element<'_, Message> {
+        let mut rows = Vec::new();
+
+        for root in &self.roots {
+            root.view(0, &mut rows);
+        }
+
+        container(scrollable(column(rows)))
+            .width(220)
+            .height(Length::Fill)
+            .style(theme::Container::Box)
+            .into()
+    }
+}
+
+// 定义一个异步函数来读取目录中的条目，目录排在文件之前并按名称排序。
+pub async fn list_directory(path: PathBuf) -> Result<(PathBuf, Vec<Entry>), Error> {
+    let mut directory = tokio::fs::read_dir(&path)
+        .await
+        .map_err(|error| Error::FileFailed(FileError::new(Operation::ListFolder, &path, &error)))?;
+
+    let mut entries = Vec::new();
+
+    while let Some(entry) = directory
+        .next_entry()
+        .await
+        .map_err(|error| Error::FileFailed(FileError::new(Operation::ListFolder, &path, &error)))?
+    {
+        let is_dir = entry
+            .file_type()
+            .await
+            .map_err(|error| {
+                Error::FileFailed(FileError::new(Operation::ListFolder, &path, &error))
+            })?
+            .is_dir();
+
+        entries.push(Entry {
+            path: entry.path(),
+            is_dir,
+        });
+    }
+
+    entries.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.path.cmp(&b.path)));
+
+    Ok((path, entries))
+}
+
+// 定义一个异步函数来打开目录选择对话框。
+pub async fn pick_folder() -> Result<PathBuf, Error> {
+    rfd::AsyncFileDialog::new()
+        .set_title(tr("Choose a folder"))
+        .pick_folder()
+        .await
+        .map(|handle| handle.path().to_owned())
+        .ok_or(Error::DialogClosed)
+}
@@ -0,0 +1,249 @@
+use crate::syntax;
+use ropey::Rope;
+use std::borrow::Cow;
+use std::path::Path;
+
+// 检查模式行时读取的开头和结尾的行数，与 vim 的默认设置相同。
+const MODELINES: usize = 5;
+
+// 检查每一行时最多读取的字符数，避免在很长的行中查找。
+const MAX_LINE: usize = 200;
+
+// 查找前置元数据的结束标记时最多读取的行数。
+const MAX_FRONT_MATTER: usize = 100;
+
+// 检查 JSON 结构时最多读取的字符数。
+const MAX_PREFIX: usize = 1000;
+
+// 依次尝试的检测方法，前面的方法优先：明确写在文件中的模式行最可靠，其次是解释器，最后是内容的格式。
+//
+// 以后支持新的检测方法时在这里添加。
+const DETECTORS: &[fn(&Rope) -> Option<&'static str>] =
+    &[modeline, shebang, prolog, front_matter, json];
+
+// vim 的文件类型名称中与高亮器的语法名称不同的部分。
+const FILETYPES: &[(&str, &str)] = &[
+    ("make", "mk"),
+    ("text", "txt"),
+    ("javascriptreact", "jsx"),
+    ("dosbatch", "bat"),
+    ("tex", "tex"),
+];
+
+// 定义解释器与高亮器使用的扩展名的对应关系，解释器的名称已经去掉了版本号。
+const INTERPRETERS: &[(&str, &str)] = &[
+    ("python", "py"),
+    ("pypy", "py"),
+    ("sh", "sh"),
+    ("bash", "sh"),
+    ("zsh", "sh"),
+    ("dash", "sh"),
+    ("ksh", "sh"),
+    ("ash", "sh"),
+    ("node", "js"),
+    ("nodejs", "js"),
+    ("deno", "js"),
+    ("bun", "js"),
+    ("ruby", "rb"),
+    ("perl", "pl"),
+    ("php", "php"),
+    ("lua", "lua"),
+    ("luajit", "lua"),
+    ("tclsh", "tcl"),
+    ("Rscript", "r"),
+    ("awk", "awk"),
+    ("gawk", "awk"),
+    ("make", "mk"),
+    ("rust-script", "rs"),
+];
+
+// 根据文档的内容推断语言，返回高亮器使用的扩展名，用于没有扩展名的文件。
+pub fn detect(rope: &Rope) -> Option<&'static str> {
+    DETECTORS.iter().find_map(|detect| detect(rope))
+}
+
+// 返回文档中的一行，去掉行尾的换行符，过长时只保留开头的部分。
+fn line(rope: &Rope, index: usize) -> Cow<'_, str> {
+    let line = rope.line(index);
+    let line: Cow<str> = line.slice(..line.len_chars().min(MAX_LINE)).into();
+
+    match line {
+        Cow::Borrowed(line) => Cow::Borrowed(line.trim_end()),
+        Cow::Owned(line) => Cow::Owned(line.trim_end().to_string()),
+    }
+}
+
+// 根据开头或结尾几行中的 vim 模式行选择语言，例如 # vim: ft=python 或 /* vim: set filetype=sh: */。
+fn modeline(rope: &Rope) -> Option<&'static str> {
+    let lines = rope.len_lines();
+    let head = 0..lines.min(MODELINES);
+    let tail = lines.saturating_sub(MODELINES).max(head.end)..lines;
+
+    head.chain(tail).find_map(|index| {
+        let line = line(rope, index);
+
+        let (_, options) = ["vim:", "vi:", "ex:"]
+            .iter()
+            .find_map(|marker| line.split_once(marker))?;
+
+        let filetype = options.split([' ', '\t', ':']).find_map(|option| {
+            option
+                .strip_prefix("ft=")
+                .or_else(|| option.strip_prefix("filetype="))
+        })?;
+
+        FILETYPES
+            .iter()
+            .find(|(name, _)| *name == filetype)
+            .map(|(_, extension)| *extension)
+            .or_else(|| syntax::find_extension(filetype))
+    })
+}
+
+// 根据第一行的 #! 找出解释器，例如 #!/usr/bin/env python3 或 #!/bin/bash -e。
+fn shebang(rope: &Rope) -> Option<&'static str> {
+    let line = line(rope, 0);
+    let mut words = line.strip_prefix("#!")?.split_whitespace();
+    let mut interpreter = program(words.next()?);
+
+    // 通过 env 启动时解释器是第一个既不是选项也不是环境变量的参数。
+    if interpreter == "env" {
+        interpreter = program(words.find(|word| !word.starts_with('-') && !word.contains('='))?);
+    }
+
+    // 去掉版本号，例如 python3.11 和 perl5。
+    let name = interpreter.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+
+    INTERPRETERS
+        .iter()
+        .find(|(program, _)| *program == name)
+        .map(|(_, extension)| *extension)
+}
+
+// 返回路径中的程序名称。
+fn program(path: &str) -> &str {
+    Path::new(path)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(path)
+}
+
+// 根据第一行的 XML 声明或 HTML 文档类型识别标记语言。
+fn prolog(rope: &Rope) -> Option<&'static str> {
+    let line = line(rope, 0);
+    let line = line.trim_start_matches('\u{feff}').trim_start();
+    let lowercase = line.to_lowercase();
+
+    if line.starts_with("<?xml") {
+        Some("xml")
+    } else if lowercase.starts_with("<!doctype html") || lowercase.starts_with("<html") {
+        Some("html")
+    } else {
+        None
+    }
+}
+
+// 根据开头的 --- 识别 YAML：之后还有正文时是带有 YAML 前置元数据的 Markdown。
+fn front_matter(rope: &Rope) -> Option<&'static str> {
+    let first = line(rope, 0);
+
+    if first.starts_with("%YAML") {
+        return Some("yaml");
+    }
+
+    if first != "---" {
+        return None;
+    }
+
+    let lines = rope.len_lines();
+    let end = (1..lines.min(MAX_FRONT_MATTER))
+        .find(|&index| matches!(&*line(rope, index), "---" | "..."));
+
+    let has_body = end.is_some_and(|end| {
+        (end + 1..lines.min(end + 1 + MAX_FRONT_MATTER))
+            .any(|index| !line(rope, index).trim().is_empty())
+    });
+
+    Some(if has_body { "md" } else { "yaml" })
+}
+
+// 根据开头的括号识别 JSON：对象以 { 和字符串键开头，数组以 [ 和一个值开头。
+//
+// 只有 [ 开头的文件也可能是 INI 或 TOML 的节，因此数组要求第一个值是对象、数组、字符串或数字。
+fn json(rope: &Rope) -> Option<&'static str> {
+    let prefix: Cow<str> = rope.slice(..rope.len_chars().min(MAX_PREFIX)).into();
+    let mut chars = prefix
+        .trim_start_matches('\u{feff}')
+        .chars()
+        .filter(|c| !c.is_whitespace());
+
+    let is_json = match (chars.next()?, chars.next()?) {
+        ('{', '"' | '}') => true,
+        ('[', '{' | '[' | '"' | ']' | '-' | '0'..='9') => true,
+        _ => false,
+    };
+
+    is_json.then_some("json")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detect(text: &str) -> Option<&'static str> {
+        super::detect(&Rope::from_str(text))
+    }
+
+    #[test]
+    fn detects_interpreters_from_the_shebang() {
+        assert_eq!(detect("#!/usr/bin/python3.11\nprint(1)\n"), Some("py"));
+        assert_eq!(detect("#!/bin/bash -e\necho\n"), Some("sh"));
+        assert_eq!(detect("#!/usr/bin/env node\n"), Some("js"));
+        assert_eq!(
+            detect("#!/usr/bin/env -S NODE_ENV=test deno run\n"),
+            Some("js")
+        );
+        assert_eq!(detect("#!/usr/bin/perl5\n"), Some("pl"));
+    }
+
+    #[test]
+    fn ignores_unknown_interpreters() {
+        assert_eq!(detect("#!/usr/bin/unknown\n"), None);
+        assert_eq!(detect("#!\n"), None);
+        assert_eq!(detect("plain text\n"), None);
+    }
+
+    #[test]
+    fn detects_modelines_at_either_end() {
+        assert_eq!(detect("# vim: ft=make\nall:\n"), Some("mk"));
+        assert_eq!(
+            detect("a\nb\nc\nd\ne\nf\ng\n/* vim: set filetype=text: */\n"),
+            Some("txt")
+        );
+    }
+
+    #[test]
+    fn prefers_modelines_over_the_shebang() {
+        assert_eq!(detect("#!/bin/sh\n# vim: ft=make\n"), Some("mk"));
+    }
+
+    #[test]
+    fn detects_markup_prologs() {
+        assert_eq!(detect("<?xml version=\"1.0\"?>\n<a/>\n"), Some("xml"));
+        assert_eq!(detect("\u{feff}<!DOCTYPE html>\n"), Some("html"));
+    }
+
+    #[test]
+    fn distinguishes_front_matter_from_yaml() {
+        assert_eq!(detect("---\ntitle: Notes\n---\n\n# Notes\n"), Some("md"));
+        assert_eq!(detect("---\nkey: value\n"), Some("yaml"));
+        assert_eq!(detect("%YAML 1.2\n---\n"), Some("yaml"));
+    }
+
+    #[test]
+    fn detects_json_structure() {
+        assert_eq!(detect("{\n  \"key\": 1\n}\n"), Some("json"));
+        assert_eq!(detect("[1, 2]"), Some("json"));
+        assert_eq!(detect("[section]\nkey = 1\n"), None);
+    }
+}
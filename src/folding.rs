@@ -0,0 +1,112 @@
+use ropey::{Rope, RopeSlice};
+use std::cmp::Reverse;
+
+// 定义可以折叠的区域：开始行保持可见，折叠其后直到结束行（包含）的各行。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Region {
+    pub start: usize, // 区域的开始行，例如以左花括号结尾的行。
+    pub end: usize,   // 最后一个被折叠的行，花括号的区域不包括右花括号所在的行。
+}
+
+impl Region {
+    // 判断区域是否包含指定的行。
+    pub fn contains(&self, line: usize) -> bool {
+        self.start <= line && line <= self.end
+    }
+}
+
+// 判断文件是否按缩进划分代码块。
+fn uses_indentation(extension: &str) -> bool {
+    matches!(extension, "py" | "pyw" | "yaml" | "yml")
+}
+
+// 返回文档中可以折叠的区域，按开始行排序，从同一行开始的多个区域只保留最大的一个。
+pub fn regions(rope: &Rope, extension: &str) -> Vec<Region> {
+    let mut regions = if uses_indentation(extension) {
+        by_indentation(rope)
+    } else {
+        by_braces(rope)
+    };
+
+    regions.sort_by_key(|region| (region.start, Reverse(region.end)));
+    regions.dedup_by_key(|region| region.start);
+    regions
+}
+
+// 按花括号和方括号划分区域，跳过字符串和行注释中的括号。
+fn by_braces(rope: &Rope) -> Vec<Region> {
+    let mut regions = Vec::new();
+    let mut open = Vec::new(); // 尚未闭合的括号所在的行。
+
+    for (number, line) in rope.lines().enumerate() {
+        let mut chars = line.chars().peekable();
+        let mut in_string = false;
+
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' if in_string => {
+                    chars.next();
+                }
+                '"' => in_string = !in_string,
+                _ if in_string => {}
+                '/' if chars.peek() == Some(&'/') => break,
+                '{' | '[' => open.push(number),
+                '}' | ']' => {
+                    // 右括号所在的行保持可见，区域至少要折叠一行。
+                    if let Some(start) = open.pop().filter(|start| number > start + 1) {
+                        regions.push(Region {
+                            start,
+                            end: number - 1,
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    regions
+}
+
+// 按缩进划分区域：一行之后缩进更深的各行（中间可以有空行）属于这一行的区域。
+fn by_indentation(rope: &Rope) -> Vec<Region> {
+    let indents: Vec<Option<usize>> = rope.lines().map(indentation).collect();
+    let mut regions = Vec::new();
+
+    for (start, indent) in indents.iter().enumerate() {
+        let Some(indent) = indent else {
+            continue;
+        };
+
+        let mut end = start;
+
+        for (line, deeper) in indents.iter().enumerate().skip(start + 1) {
+            match deeper {
+                Some(deeper) if deeper > indent => end = line,
+                Some(_) => break,
+                None => {}
+            }
+        }
+
+        if end > start {
+            regions.push(Region { start, end });
+        }
+    }
+
+    regions
+}
+
+// 返回一行的缩进宽度，空行返回 None。
+fn indentation(line: RopeSlice<'_>) -> Option<usize> {
+    let mut width = 0;
+
+    for c in line.chars() {
+        match c {
+            ' ' | '\t' => width += 1,
+            '\n' | '\r' => return None,
+            _ => return Some(width),
+        }
+    }
+
+    None
+}
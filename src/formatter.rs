@@ -0,0 +1,130 @@
+use crate::{Error, FileError, Operation};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+// 定义一个外部格式化工具，它从标准输入读取代码，并把结果写到标准输出。
+struct Formatter {
+    extensions: &'static [&'static str], // 适用的文件扩展名。
+    program: &'static str,               // 程序名称。
+    arguments: &'static [&'static str], // 参数，{file} 替换为文件路径，部分工具据此查找配置和判断语言。
+}
+
+// 各种语言使用的格式化工具。
+const FORMATTERS: &[Formatter] = &[
+    Formatter {
+        extensions: &["rs"],
+        program: "rustfmt",
+        arguments: &["--edition", "2021"],
+    },
+    Formatter {
+        extensions: &["py", "pyw"],
+        program: "black",
+        arguments: &["--quiet", "--stdin-filename", "{file}", "-"],
+    },
+    Formatter {
+        extensions: &[
+            "js", "jsx", "mjs", "ts", "tsx", "json", "css", "scss", "less", "html", "vue", "md",
+            "yaml", "yml",
+        ],
+        program: "prettier",
+        arguments: &["--stdin-filepath", "{file}"],
+    },
+    Formatter {
+        extensions: &["go"],
+        program: "gofmt",
+        arguments: &[],
+    },
+    Formatter {
+        extensions: &["c", "h", "cpp", "hpp", "cc"],
+        program: "clang-format",
+        arguments: &["--assume-filename", "{file}"],
+    },
+];
+
+// 查找扩展名对应的格式化工具。
+fn find(extension: &str) -> Option<&'static Formatter> {
+    FORMATTERS
+        .iter()
+        .find(|formatter| formatter.extensions.contains(&extension))
+}
+
+// 判断是否有适用于扩展名的格式化工具。
+pub fn is_supported(extension: &str) -> bool {
+    find(extension).is_some()
+}
+
+// 定义一个异步函数，用外部格式化工具格式化文本，返回格式化后的文本。
+//
+// 工具在文件所在的目录中运行，以便读取项目的配置文件。工具失败时返回它输出的错误，不改动原文。
+pub async fn format(
+    path: Option<PathBuf>,
+    extension: String,
+    text: String,
+) -> Result<String, Error> {
+    let Some(Formatter {
+        program, arguments, ..
+    }) = find(&extension)
+    else {
+        return Err(Error::FormatFailed(format!(
+            "No formatter for .{extension} files"
+        )));
+    };
+
+    let file = path
+        .as_deref()
+        .map(Path::to_string_lossy)
+        .unwrap_or_else(|| format!("untitled.{extension}").into());
+
+    let mut command = Command::new(program);
+    command
+        .args(
+            arguments
+                .iter()
+                .map(|argument| argument.replace("{file}", &file)),
+        )
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+
+    if let Some(directory) = path.as_deref().and_then(Path::parent) {
+        command.current_dir(directory);
+    }
+
+    let mut child = command.spawn().map_err(|error| {
+        if error.kind() == std::io::ErrorKind::NotFound {
+            Error::FormatFailed(format!("{program} is not installed"))
+        } else {
+            Error::FileFailed(FileError::new(Operation::Run, Path::new(program), &error))
+        }
+    })?;
+
+    // 在后台写入标准输入，避免输出较多时双方互相等待。
+    if let Some(mut stdin) = child.stdin.take() {
+        tokio::spawn(async move {
+            let _ = stdin.write_all(text.as_bytes()).await;
+        });
+    }
+
+    let output = child.wait_with_output().await.map_err(|error| {
+        Error::FileFailed(FileError::new(Operation::Run, Path::new(program), &error))
+    })?;
+
+    if output.status.success() {
+        return String::from_utf8(output.stdout)
+            .map_err(|_| Error::FormatFailed(format!("{program} returned invalid UTF-8")));
+    }
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let message = stderr
+        .lines()
+        .find(|line| !line.trim().is_empty())
+        .unwrap_or("failed");
+
+    Err(Error::FormatFailed(format!(
+        "{program}: {}",
+        message.trim()
+    )))
+}
@@ -0,0 +1,271 @@
+use crate::line_ending::LineEnding;
+use crate::{Error, FileError, Message, Operation};
+use iced::mouse;
+use iced::widget::canvas::{self, Canvas, Frame, Geometry};
+use iced::{event, Color, Element, Length, Point, Rectangle, Renderer, Size, Theme};
+use similar::{DiffOp, TextDiff};
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::process::Command;
+
+// 标记栏的宽度。
+const GUTTER_WIDTH: f32 = 6.0;
+
+// 删除标记的高度，删除的行在当前文档中不占位置，只画一条细线。
+const REMOVED_HEIGHT: f32 = 2.0;
+
+// 定义一行相对于 HEAD 的修改类型。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Added,    // 新增的行。
+    Modified, // 修改过的行。
+    Removed,  // 在这一行之前删除了若干行。
+}
+
+impl Kind {
+    // 返回标记的颜色。
+    fn color(self) -> Color {
+        match self {
+            Kind::Added => Color::from_rgb8(0x58, 0xa6, 0x4b),
+            Kind::Modified => Color::from_rgb8(0x3b, 0x82, 0xc4),
+            Kind::Removed => Color::from_rgb8(0xd0, 0x4a, 0x3f),
+        }
+    }
+}
+
+// 定义文档中一行的修改。
+#[derive(Debug, Clone, Copy)]
+pub struct Change {
+    pub line: usize, // 所在的行，从 0 开始。
+    pub kind: Kind,  // 修改的类型。
+}
+
+// 定义工作区中一个修改过的文件。
+#[derive(Debug, Clone)]
+pub struct FileStatus {
+    pub path: PathBuf,  // 文件的完整路径。
+    pub staged: char,   // 暂存区中的状态，例如 M、A、D，未暂存时为空格。
+    pub unstaged: char, // 工作区中的状态，未跟踪的文件为 ?。
+}
+
+// 定义一个异步函数，在指定目录中运行 git 命令并返回标准输出。
+//
+// 命令执行失败时返回 git 输出的错误信息。
+async fn run(directory: &Path, args: &[&OsStr]) -> Result<String, Error> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(directory)
+        .args(args)
+        .output()
+        .await
+        .map_err(|error| {
+            Error::FileFailed(FileError::new(Operation::Run, Path::new("git"), &error))
+        })?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else {
+        Err(Error::GitFailed(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ))
+    }
+}
+
+// 定义一个异步函数，读取文件在 HEAD 中的内容。
+//
+// 文件不在 git 仓库中、尚未提交或者没有安装 git 时返回 None，此时不显示标记。
+pub async fn head(path: PathBuf) -> Option<String> {
+    let directory = path.parent()?;
+    let name = path.file_name()?.to_str()?;
+    let object = format!("HEAD:./{name}");
+
+    run(directory, &[OsStr::new("show"), OsStr::new(&object)])
+        .await
+        .ok()
+}
+
+// 定义一个异步函数，查找目录所在仓库的根目录，并列出仓库中所有修改过的文件。
+pub async fn status(directory: PathBuf) -> Result<(PathBuf, Vec<FileStatus>), Error> {
+    let root = run(
+        &directory,
+        &[OsStr::new("rev-parse"), OsStr::new("--show-toplevel")],
+    )
+    .await?;
+    let root = PathBuf::from(root.trim_end());
+
+    let output = run(
+        &root,
+        &[
+            OsStr::new("status"),
+            OsStr::new("--porcelain=v1"),
+            OsStr::new("-z"),
+        ],
+    )
+    .await?;
+
+    // 每一项的格式为 "XY 路径"，以 \0 分隔；重命名和复制之后还有一项原来的路径。
+    let mut files = Vec::new();
+    let mut entries = output.split('\0').filter(|entry| !entry.is_empty());
+
+    while let Some(entry) = entries.next() {
+        let mut chars = entry.chars();
+        let (Some(staged), Some(unstaged)) = (chars.next(), chars.next()) else {
+            continue;
+        };
+
+        if matches!(staged, 'R' | 'C') {
+            let _ = entries.next();
+        }
+
+        files.push(FileStatus {
+            path: root.join(entry.get(3..).unwrap_or_default()),
+            staged,
+            unstaged,
+        });
+    }
+
+    Ok((root, files))
+}
+
+// 定义一个异步函数，将文件加入暂存区。
+pub async fn stage(root: PathBuf, path: PathBuf) -> Result<(), Error> {
+    run(
+        &root,
+        &[OsStr::new("add"), OsStr::new("--"), path.as_os_str()],
+    )
+    .await?;
+
+    Ok(())
+}
+
+// 定义一个异步函数，使用指定的提交信息提交暂存区中的修改。
+pub async fn commit(root: PathBuf, message: String) -> Result<(), Error> {
+    run(
+        &root,
+        &[OsStr::new("commit"), OsStr::new("-m"), OsStr::new(&message)],
+    )
+    .await?;
+
+    Ok(())
+}
+
+// 定义一个异步函数，按行比较 HEAD 中的内容和当前文档，返回每一行的修改。
+//
+// 两段文本都以换行符结尾后再比较，避免最后一行只因缺少换行符而被标记为修改。
+pub async fn diff(head: Arc<String>, mut current: String) -> Vec<Change> {
+    let mut head = LineEnding::normalize(&head);
+
+    for text in [&mut head, &mut current] {
+        if !text.ends_with('\n') {
+            text.push('\n');
+        }
+    }
+
+    let diff = TextDiff::from_lines(&head, &current);
+    let mut changes = Vec::new();
+
+    for op in diff.ops() {
+        let (lines, kind) = match *op {
+            DiffOp::Equal { .. } => continue,
+            DiffOp::Delete { new_index, .. } => (new_index..new_index + 1, Kind::Removed),
+            DiffOp::Insert {
+                new_index, new_len, ..
+            } => (new_index..new_index + new_len, Kind::Added),
+            DiffOp::Replace {
+                new_index, new_len, ..
+            } => (new_index..new_index + new_len, Kind::Modified),
+        };
+
+        changes.extend(lines.map(|line| Change { line, kind }));
+    }
+
+    changes
+}
+
+// 创建显示修改标记的标记栏，放在编辑器旁边。
+//
+// 文本编辑器不提供滚动位置，标记按行号在整个文档中的比例排列，点击标记跳转到对应的行。
+pub fn gutter(changes: &[Change], line_count: usize) -> Element<'_, Message> {
+    Canvas::new(Gutter {
+        changes,
+        line_count,
+    })
+    .width(GUTTER_WIDTH)
+    .height(Length::Fill)
+    .into()
+}
+
+// 绘制修改标记的画布。
+struct Gutter<'a> {
+    changes: &'a [Change], // 需要绘制的修改。
+    line_count: usize,     // 文档的总行数。
+}
+
+impl Gutter<'_> {
+    // 返回每一行在标记栏中占据的高度。
+    fn line_height(&self, bounds: Rectangle) -> f32 {
+        bounds.height / self.line_count.max(1) as f32
+    }
+}
+
+impl canvas::Program<Message> for Gutter<'_> {
+    type State = ();
+
+    fn update(
+        &self,
+        _state: &mut Self::State,
+        event: canvas::Event,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> (event::Status, Option<Message>) {
+        let canvas::Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) = event else {
+            return (event::Status::Ignored, None);
+        };
+
+        let Some(position) = cursor.position_in(bounds) else {
+            return (event::Status::Ignored, None);
+        };
+
+        // 跳转到点击位置附近的第一个修改，附近没有修改时直接跳转到点击的行。
+        let line_height = self.line_height(bounds);
+        let line = (position.y / line_height) as usize;
+        let tolerance = (REMOVED_HEIGHT / line_height).ceil() as usize;
+
+        let target = self
+            .changes
+            .iter()
+            .map(|change| change.line)
+            .find(|changed| changed.abs_diff(line) <= tolerance)
+            .unwrap_or(line);
+
+        (event::Status::Captured, Some(Message::JumpToLine(target)))
+    }
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &Renderer,
+        _theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+        let line_height = self.line_height(bounds);
+
+        for change in self.changes {
+            let height = match change.kind {
+                Kind::Removed => REMOVED_HEIGHT,
+                Kind::Added | Kind::Modified => line_height.max(REMOVED_HEIGHT),
+            };
+
+            frame.fill_rectangle(
+                Point::new(0.0, change.line as f32 * line_height),
+                Size::new(bounds.width, height),
+                change.kind.color(),
+            );
+        }
+
+        vec![frame.into_geometry()]
+    }
+}
@@ -0,0 +1,81 @@
+use crate::i18n::{tr, trf};
+use crate::Message;
+use iced::widget::{container, row, text, text_input};
+use iced::{theme, Element};
+
+// 定义“跳转到行”对话框的状态。
+#[derive(Debug, Default)]
+pub struct GoToLine {
+    pub input: String, // 用户输入的行号，可选地带有以冒号分隔的列号。
+}
+
+impl GoToLine {
+    // 返回输入框的标识，用于在打开对话框时获取焦点。
+    pub fn id() -> text_input::Id {
+        text_input::Id::new("go-to-line")
+    }
+
+    // 解析用户输入的“行[:列]”，返回从 0 开始的行号和列号。
+    pub fn target(&self) -> Option<(usize, usize)> {
+        let mut parts = self.input.trim().splitn(2, ':');
+
+        let line = parts.next()?.trim().parse::<usize>().ok()?;
+        let column = match parts.next() {
+            Some(column) => column.trim().parse::<usize>().ok()?,
+            None => 1,
+        };
+
+        Some((line.saturating_sub(1), column.saturating_sub(1)))
+    }
+
+    // 创建对话框的视图。
+    pub fn view(&self, line_count: usize) -> Element<'_, Message> {
+        let input = text_input(
+            &trf("Line[:Column] (1-{count})", &[("count", &line_count)]),
+            &self.input,
+        )
+        .id(Self::id())
+        .on_input(Message::GoToLineChanged)
+        .on_submit(Message::GoToLineSubmitted)
+        .padding(5);
+
+        container(row![text(tr("Go to line:")), input].spacing(10))
+            .padding(10)
+            .style(theme::Container::Box)
+            .into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target(input: &str) -> Option<(usize, usize)> {
+        GoToLine {
+            input: input.to_string(),
+        }
+        .target()
+    }
+
+    #[test]
+    fn parses_line_and_column() {
+        assert_eq!(target("12"), Some((11, 0)));
+        assert_eq!(target("12:5"), Some((11, 4)));
+        assert_eq!(target(" 12 : 5 "), Some((11, 4)));
+    }
+
+    #[test]
+    fn clamps_zero_to_the_first_line() {
+        assert_eq!(target("0"), Some((0, 0)));
+        assert_eq!(target("1:0"), Some((0, 0)));
+    }
+
+    #[test]
+    fn rejects_invalid_input() {
+        assert_eq!(target(""), None);
+        assert_eq!(target("abc"), None);
+        assert_eq!(target("-3"), None);
+        assert_eq!(target("3:x"), None);
+        assert_eq!(target("3:4:5"), None);
+    }
+}
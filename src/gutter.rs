@@ -0,0 +1,423 @@
+use crate::{Message, CHARACTER_WIDTH, EDITOR_PADDING, LINE_HEIGHT};
+use iced::alignment;
+use iced::mouse;
+use iced::widget::canvas::{self, Canvas, Frame, Geometry};
+use iced::{event, Color, Element, Font, Length, Point, Rectangle, Renderer, Size, Theme};
+use ropey::Rope;
+use std::collections::BTreeSet;
+
+// 行号的字号，与文本编辑器中文字的字号相同，使行号与文本对齐。
+const TEXT_SIZE: f32 = 16.0;
+
+// 定义估算的编辑器尺寸：可见的显示行数，以及自动换行时每个显示行的字符数。
+//
+// 文本编辑器不提供自身的尺寸，由视图在布局时根据可用空间估算。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Metrics {
+    pub rows: usize,            // 可见的显示行数。
+    pub columns: Option<usize>, // 每个显示行的字符数，关闭自动换行时为 None。
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self {
+            rows: 40,
+            columns: None,
+        }
+    }
+}
+
+impl Metrics {
+    // 返回文档中的一行换行后占据的显示行数。
+    fn rows_of(&self, rope: &Rope, line: usize) -> usize {
+        let Some(columns) = self.columns.filter(|columns| *columns > 0) else {
+            return 1;
+        };
+
+        line_length(rope, line).div_ceil(columns).max(1)
+    }
+}
+
+// 定义估算的编辑器滚动位置：第一个可见的文本行，以及该行换行后从第几个显示行开始可见。
+//
+// 文本编辑器不提供滚动位置，这里按照编辑器的行为跟踪：
+// 滚动鼠标滚轮时移动相应的显示行数，移动光标后滚动到光标可见的位置。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Viewport {
+    pub line: usize, // 第一个可见的文本行。
+    pub row: usize,  // 该行换行后第一个可见的显示行。
+}
+
+impl Viewport {
+    // 向下（lines 为正）或向上滚动若干显示行，最多滚动到最后一行位于可见区域的底部。
+    pub fn scroll(self, rope: &Rope, metrics: Metrics, lines: i32) -> Self {
+        let distance = lines.unsigned_abs() as usize;
+
+        if lines < 0 {
+            return self.retreat(rope, metrics, distance);
+        }
+
+        let last_line = rope.len_lines().saturating_sub(1);
+        let end = Self {
+            line: last_line,
+            row: metrics.rows_of(rope, last_line) - 1,
+        };
+        let limit = end.retreat(rope, metrics, metrics.rows.saturating_sub(2));
+
+        self.advance(rope, metrics, distance).min(limit.max(self))
+    }
+
+    // 光标移动到指定的行和列（按字符计）后，滚动到光标可见的最近位置。
+    pub fn reveal(self, rope: &Rope, metrics: Metrics, line: usize, column: usize) -> Self {
+        let row = match metrics.columns.filter(|columns| *columns > 0) {
+            Some(columns) => (column / columns).min(metrics.rows_of(rope, line) - 1),
+            None => 0,
+        };
+        let cursor = Self { line, row };
+
+        if cursor < self {
+            return cursor;
+        }
+
+        self.max(cursor.retreat(rope, metrics, metrics.rows.saturating_sub(1)))
+    }
+
+    // 返回从当前位置滚动到 target 需要滚动的显示行数，向上滚动时为负数。
+    pub fn rows_to(self, rope: &Rope, metrics: Metrics, target: Self) -> i32 {
+        let (mut position, end) = (self.min(target), self.max(target));
+        let mut rows = 0;
+
+        while position.line < end.line {
+            rows += metrics.rows_of(rope, position.line) - position.row;
+            position = Self {
+                line: position.line + 1,
+                row: 0,
+            };
+        }
+
+        rows += end.row - position.row;
+
+        if target < self {
+            -(rows as i32)
+        } else {
+            rows as i32
+        }
+    }
+
+    // 返回当前位置之后第 distance 个显示行，不超过文档的最后一个显示行。
+    fn advance(mut self, rope: &Rope, metrics: Metrics, mut distance: usize) -> Self {
+        let line_count = rope.len_lines();
+
+        loop {
+            let rows = metrics.rows_of(rope, self.line);
+
+            if self.row + distance < rows {
+                self.row += distance;
+                return self;
+            }
+
+            if self.line + 1 >= line_count {
+                self.row = rows - 1;
+                return self;
+            }
+
+            distance -= rows - self.row;
+            self.line += 1;
+            self.row = 0;
+        }
+    }
+
+    // 返回当前位置之前第 distance 个显示行，不超过文档的开头。
+    fn retreat(mut self, rope: &Rope, metrics: Metrics, mut distance: usize) -> Self {
+        loop {
+            if distance <= self.row {
+                self.row -= distance;
+                return self;
+            }
+
+            if self.line == 0 {
+                self.row = 0;
+                return self;
+            }
+
+            distance -= self.row + 1;
+            self.line -= 1;
+            self.row = metrics.rows_of(rope, self.line) - 1;
+        }
+    }
+
+    // 返回可见区域中的各行及其在标记栏中的位置，height 为可见区域的高度。
+    pub fn lines(self, rope: &Rope, metrics: Metrics, height: f32) -> Vec<Line> {
+        let line_count = rope.len_lines();
+
+        let mut lines = Vec::new();
+        let mut y = EDITOR_PADDING - self.row as f32 * LINE_HEIGHT;
+
+        for number in self.line..line_count {
+            if y >= height {
+                break;
+            }
+
+            let height = metrics.rows_of(rope, number) as f32 * LINE_HEIGHT;
+            lines.push(Line { number, y, height });
+            y += height;
+        }
+
+        lines
+    }
+}
+
+// 定义标记栏中可见的一个文本行。
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Line {
+    pub number: usize, // 文档中的行号，从 0 开始。
+    pub y: f32,        // 该行顶部在标记栏中的位置，第一行可能只有一部分可见。
+    pub height: f32,   // 该行换行后占据的高度。
+}
+
+// 定义标记栏中的一栏，例如行号、git 修改或诊断标记。
+//
+// 多栏并排显示在编辑器左侧，共用同一组可见的行，因此总是与编辑器的滚动位置一致。
+pub trait Lane {
+    // 返回这一栏的宽度。
+    fn width(&self) -> f32;
+
+    // 在 bounds 中绘制一行的内容，bounds 的高度为该行换行后占据的高度。
+    fn draw(&self, frame: &mut Frame, theme: &Theme, line: usize, bounds: Rectangle);
+
+    // 返回点击某一行时发送的消息。
+    fn on_click(&self, _line: usize) -> Option<Message> {
+        None
+    }
+}
+
+// 创建并排显示多栏标记的标记栏，lines 为可见的各行。
+pub fn view<'a>(lines: Vec<Line>, lanes: Vec<Box<dyn Lane + 'a>>) -> Element<'a, Message> {
+    let width: f32 = lanes.iter().map(|lane| lane.width()).sum();
+
+    Canvas::new(Gutter { lines, lanes })
+        .width(width)
+        .height(Length::Fill)
+        .into()
+}
+
+// 绘制标记栏的画布。
+struct Gutter<'a> {
+    lines: Vec<Line>,               // 可见的各行。
+    lanes: Vec<Box<dyn Lane + 'a>>, // 从左到右排列的各栏。
+}
+
+impl canvas::Program<Message> for Gutter<'_> {
+    type State = ();
+
+    fn update(
+        &self,
+        _state: &mut Self::State,
+        event: canvas::Event,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> (event::Status, Option<Message>) {
+        let canvas::Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) = event else {
+            return (event::Status::Ignored, None);
+        };
+
+        let Some(position) = cursor.position_in(bounds) else {
+            return (event::Status::Ignored, None);
+        };
+
+        let Some(line) = self
+            .lines
+            .iter()
+            .find(|line| position.y >= line.y && position.y < line.y + line.height)
+        else {
+            return (event::Status::Ignored, None);
+        };
+
+        // 找到点击位置所在的栏。
+        let mut x = 0.0;
+
+        for lane in &self.lanes {
+            x += lane.width();
+
+            if position.x < x {
+                return match lane.on_click(line.number) {
+                    Some(message) => (event::Status::Captured, Some(message)),
+                    None => (event::Status::Ignored, None),
+                };
+            }
+        }
+
+        (event::Status::Ignored, None)
+    }
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &Renderer,
+        theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+        let mut x = 0.0;
+
+        for lane in &self.lanes {
+            let width = lane.width();
+
+            for line in &self.lines {
+                let bounds = Rectangle::new(Point::new(x, line.y), Size::new(width, line.height));
+                lane.draw(&mut frame, theme, line.number, bounds);
+            }
+
+            x += width;
+        }
+
+        vec![frame.into_geometry()]
+    }
+}
+
+// 显示行号的一栏，高亮光标所在的行。
+pub struct LineNumbers {
+    pub line_count: usize,          // 文档的总行数，决定这一栏的宽度。
+    pub current: usize,             // 光标所在的行。
+    pub folds: Vec<(usize, usize)>, // 折叠的区域的开始行及被折叠的行数，显示的行号跳过被折叠的行。
+}
+
+impl Lane for LineNumbers {
+    fn width(&self) -> f32 {
+        let digits = self.line_count.max(1).to_string().len().max(2);
+
+        (digits + 2) as f32 * CHARACTER_WIDTH
+    }
+
+    fn draw(&self, frame: &mut Frame, theme: &Theme, line: usize, bounds: Rectangle) {
+        let text = theme.palette().text;
+        let is_current = line == self.current;
+
+        if is_current {
+            frame.fill_rectangle(
+                bounds.position(),
+                Size::new(bounds.width, LINE_HEIGHT),
+                Color { a: 0.08, ..text },
+            );
+        }
+
+        let folded: usize = self
+            .folds
+            .iter()
+            .filter(|(start, _)| *start < line)
+            .map(|(_, count)| count)
+            .sum();
+
+        frame.fill_text(canvas::Text {
+            content: (line + folded + 1).to_string(),
+            position: Point::new(bounds.x + bounds.width - CHARACTER_WIDTH, bounds.y),
+            color: if is_current {
+                text
+            } else {
+                Color { a: 0.4, ..text }
+            },
+            size: TEXT_SIZE.into(),
+            font: Font::MONOSPACE,
+            horizontal_alignment: alignment::Horizontal::Right,
+            vertical_alignment: alignment::Vertical::Top,
+            ..canvas::Text::default()
+        });
+    }
+
+    // 点击行号时将光标移动到该行。
+    fn on_click(&self, line: usize) -> Option<Message> {
+        Some(Message::JumpToLine(line))
+    }
+}
+
+// 显示书签的一栏，点击时添加或移除书签。
+pub struct Bookmarks {
+    pub lines: BTreeSet<usize>, // 添加了书签的行，被折叠的书签显示在折叠区域的开始行。
+}
+
+impl Lane for Bookmarks {
+    fn width(&self) -> f32 {
+        CHARACTER_WIDTH * 1.5
+    }
+
+    fn draw(&self, frame: &mut Frame, theme: &Theme, line: usize, bounds: Rectangle) {
+        if !self.lines.contains(&line) {
+            return;
+        }
+
+        let size = CHARACTER_WIDTH * 0.8;
+
+        frame.fill(
+            &canvas::Path::circle(
+                Point::new(bounds.center_x(), bounds.y + LINE_HEIGHT / 2.0),
+                size / 2.0,
+            ),
+            theme.palette().primary,
+        );
+    }
+
+    fn on_click(&self, line: usize) -> Option<Message> {
+        Some(Message::BookmarkClicked(line))
+    }
+}
+
+// 显示折叠标记的一栏，点击时折叠或展开区域。
+pub struct Folds {
+    pub regions: BTreeSet<usize>, // 可以折叠的区域的开始行。
+    pub folded: BTreeSet<usize>,  // 已经折叠的区域的开始行。
+}
+
+impl Lane for Folds {
+    fn width(&self) -> f32 {
+        CHARACTER_WIDTH * 1.5
+    }
+
+    fn draw(&self, frame: &mut Frame, theme: &Theme, line: usize, bounds: Rectangle) {
+        let palette = theme.palette();
+
+        let (marker, color) = if self.folded.contains(&line) {
+            ("▸", palette.primary)
+        } else if self.regions.contains(&line) {
+            (
+                "▾",
+                Color {
+                    a: 0.4,
+                    ..palette.text
+                },
+            )
+        } else {
+            return;
+        };
+
+        frame.fill_text(canvas::Text {
+            content: marker.to_string(),
+            position: Point::new(bounds.center_x(), bounds.y),
+            color,
+            size: TEXT_SIZE.into(),
+            horizontal_alignment: alignment::Horizontal::Center,
+            vertical_alignment: alignment::Vertical::Top,
+            ..canvas::Text::default()
+        });
+    }
+
+    fn on_click(&self, line: usize) -> Option<Message> {
+        (self.folded.contains(&line) || self.regions.contains(&line))
+            .then_some(Message::FoldClicked(line))
+    }
+}
+
+// 返回文档中一行的字符数，不包括换行符。
+fn line_length(rope: &Rope, line: usize) -> usize {
+    let Some(slice) = rope.get_line(line) else {
+        return 0;
+    };
+
+    let length = slice.len_chars();
+
+    if length > 0 && slice.char(length - 1) == '\n' {
+        length - 1
+    } else {
+        length
+    }
+}
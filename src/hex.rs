@@ -0,0 +1,152 @@
+use crate::i18n::{tr, trf};
+use crate::Message;
+use iced::widget::{button, column, container, horizontal_space, row, text};
+use iced::{theme, Element, Length};
+
+// 每行显示的字节数。
+pub const BYTES_PER_ROW: usize = 16;
+
+// 一页显示的行数。
+const ROWS_PER_PAGE: usize = 32;
+
+// 判断文件内容是否为二进制：与 git 相同，开头的 8000 个字节中出现 NUL 时认为是二进制文件。
+pub fn is_binary(bytes: &[u8]) -> bool {
+    bytes[..bytes.len().min(8000)].contains(&0)
+}
+
+// 定义十六进制编辑器的状态，按偏移、十六进制和 ASCII 三栏显示二进制文件的内容。
+#[derive(Debug, Clone)]
+pub struct HexEditor {
+    pub bytes: Vec<u8>, // 文件内容。
+    cursor: usize,      // 光标所在的字节。
+    low_nibble: bool,   // 下一个输入的十六进制数字是否修改光标所在字节的低四位。
+    top: usize,         // 当前页的第一行。
+}
+
+impl HexEditor {
+    // 创建十六进制编辑器，光标位于文件开头。
+    pub fn new(bytes: Vec<u8>) -> Self {
+        Self {
+            bytes,
+            cursor: 0,
+            low_nibble: false,
+            top: 0,
+        }
+    }
+
+    // 把光标移动到指定的字节，并滚动到光标所在的页。
+    pub fn select(&mut self, offset: usize) {
+        self.cursor = offset.min(self.bytes.len().saturating_sub(1));
+        self.low_nibble = false;
+
+        let row = self.cursor / BYTES_PER_ROW;
+
+        if row < self.top {
+            self.top = row;
+        } else if row >= self.top + ROWS_PER_PAGE {
+            self.top = row + 1 - ROWS_PER_PAGE;
+        }
+    }
+
+    // 按字节数移动光标，负数向前移动。
+    pub fn move_cursor(&mut self, delta: isize) {
+        self.select(self.cursor.saturating_add_signed(delta));
+    }
+
+    // 按页移动光标。
+    pub fn move_page(&mut self, pages: isize) {
+        self.move_cursor(pages * (ROWS_PER_PAGE * BYTES_PER_ROW) as isize);
+    }
+
+    // 在光标处输入一个十六进制数字，先修改高四位，再修改低四位并移动到下一个字节。
+    //
+    // 修改了内容时返回 true。
+    pub fn input(&mut self, digit: u8) -> bool {
+        let Some(byte) = self.bytes.get_mut(self.cursor) else {
+            return false;
+        };
+
+        if self.low_nibble {
+            *byte = (*byte & 0xf0) | digit;
+            self.move_cursor(1);
+        } else {
+            *byte = (*byte & 0x0f) | (digit << 4);
+            self.low_nibble = true;
+        }
+
+        true
+    }
+
+    // 创建编辑器的视图，每次只显示一页，点击字节可以移动光标。
+    pub fn view(&self, is_read_only: bool) -> Element<'_, Message> {
+        let rows = self.bytes.len().div_ceil(BYTES_PER_ROW);
+        let last = rows.min(self.top + ROWS_PER_PAGE);
+
+        let header = row![
+            text(trf(
+                if is_read_only {
+                    "Offset {offset} of {length} (read-only)"
+                } else {
+                    "Offset {offset} of {length}"
+                },
+                &[
+                    ("offset", &format!("{:08X}", self.cursor)),
+                    ("length", &format!("{:08X}", self.bytes.len())),
+                ],
+            ))
+            .size(14),
+            horizontal_space(Length::Fill),
+            button(text(tr("Previous Page")).size(14))
+                .on_press_maybe((self.top > 0).then_some(Message::HexPage(-1)))
+                .padding([2, 5]),
+            button(text(tr("Next Page")).size(14))
+                .on_press_maybe((last < rows).then_some(Message::HexPage(1)))
+                .padding([2, 5])
+        ]
+        .spacing(10);
+
+        let lines = (self.top..last).map(|row| {
+            let start = row * BYTES_PER_ROW;
+            let end = self.bytes.len().min(start + BYTES_PER_ROW);
+
+            let hex = (start..end).map(|offset| {
+                let style = if offset == self.cursor {
+                    theme::Button::Primary
+                } else {
+                    theme::Button::Text
+                };
+
+                button(text(format!("{:02X}", self.bytes[offset])))
+                    .on_press(Message::HexByteClicked(offset))
+                    .padding([0, 2])
+                    .style(style)
+                    .into()
+            });
+
+            let ascii: String = self.bytes[start..end]
+                .iter()
+                .map(|&byte| {
+                    if byte.is_ascii_graphic() || byte == b' ' {
+                        byte as char
+                    } else {
+                        '.'
+                    }
+                })
+                .collect();
+
+            row![
+                text(format!("{start:08X}")),
+                row(hex.collect()).width(BYTES_PER_ROW as f32 * 26.0),
+                text(ascii)
+            ]
+            .spacing(20)
+            .into()
+        });
+
+        container(column![header, column(lines.collect())].spacing(10))
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .padding(10)
+            .into()
+    }
+}
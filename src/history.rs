@@ -0,0 +1,102 @@
+use iced::widget::text_editor::{Action, Edit};
+use ropey::Rope;
+
+// 每个标签页最多保留的撤销步数。
+const LIMIT: usize = 100;
+
+// 定义文档在某一时刻的快照。
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub text: Rope,             // 文档的完整内容，克隆时共享未修改的部分。
+    pub cursor: (usize, usize), // 光标在完整文本中的行和按字符计的列。
+}
+
+// 定义编辑动作的类型，连续的同类动作会合并为一个撤销步骤。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Insert, // 输入普通字符。
+    Delete, // 删除字符。
+    Other,  // 换行、粘贴等其他编辑。
+}
+
+// 定义撤销/重做历史记录。
+#[derive(Debug, Default)]
+pub struct History {
+    undo: Vec<Snapshot>, // 可撤销的快照。
+    redo: Vec<Snapshot>, // 可重做的快照。
+    last: Option<Kind>,  // 上一次编辑动作的类型。
+}
+
+impl History {
+    // 在执行编辑动作之前调用，必要时记录编辑前的快照。
+    pub fn record(&mut self, action: &Action, snapshot: impl FnOnce() -> Snapshot) {
+        let kind = match action {
+            Action::Edit(Edit::Insert(c)) if !c.is_whitespace() => Kind::Insert,
+            Action::Edit(Edit::Backspace | Edit::Delete) => Kind::Delete,
+            Action::Edit(_) => Kind::Other,
+            _ => {
+                // 移动光标会打断连续输入的合并。
+                self.last = None;
+                return;
+            }
+        };
+
+        if kind == Kind::Other || self.last != Some(kind) {
+            self.push(snapshot());
+        }
+
+        self.last = Some(kind);
+        self.redo.clear();
+    }
+
+    // 直接记录一次快照，用于整体替换文档内容的操作。
+    pub fn push(&mut self, snapshot: Snapshot) {
+        if self.undo.len() == LIMIT {
+            let _ = self.undo.remove(0);
+        }
+
+        self.undo.push(snapshot);
+        self.redo.clear();
+        self.last = None;
+    }
+
+    // 撤销一步，返回需要恢复的快照。
+    pub fn undo(&mut self, current: Snapshot) -> Option<Snapshot> {
+        let previous = self.undo.pop()?;
+        self.redo.push(current);
+        self.last = None;
+        Some(previous)
+    }
+
+    // 重做一步，返回需要恢复的快照。
+    pub fn redo(&mut self, current: Snapshot) -> Option<Snapshot> {
+        let next = self.redo.pop()?;
+        self.undo.push(current);
+        self.last = None;
+        Some(next)
+    }
+
+    // 使用已有的快照创建历史记录，用于接收从其他窗口移过来的标签页。
+    pub fn with_snapshots(undo: Vec<Snapshot>, redo: Vec<Snapshot>) -> Self {
+        Self {
+            undo,
+            redo,
+            last: None,
+        }
+    }
+
+    // 返回可撤销和可重做的快照，最近的在最后。
+    pub fn snapshots(&self) -> (&[Snapshot], &[Snapshot]) {
+        (&self.undo, &self.redo)
+    }
+
+    // 判断是否可以撤销。
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    // 判断是否可以重做。
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+}
@@ -0,0 +1,97 @@
+use crate::i18n::tr;
+use crate::syntax::{self, Theme};
+use crate::{Error, FileError, Operation};
+use iced::Color;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+// 定义一个异步函数，将文档按当前的高亮主题导出为独立的 HTML 文件，并保存到用户选择的位置。
+pub async fn export(
+    title: String,
+    text: String,
+    theme: Theme,
+    extension: String,
+) -> Result<PathBuf, Error> {
+    let name = Path::new(&title)
+        .file_stem()
+        .map_or_else(|| title.clone(), |stem| stem.to_string_lossy().into_owned());
+
+    let path = rfd::AsyncFileDialog::new()
+        .set_title(tr("Export as HTML"))
+        .set_file_name(format!("{name}.html"))
+        .add_filter("HTML", &["html", "htm"])
+        .save_file()
+        .await
+        .ok_or(Error::DialogClosed)?
+        .path()
+        .to_owned();
+
+    let lines = syntax::highlight_document(&text, theme, &extension);
+
+    tokio::fs::write(&path, render(&title, &lines, theme))
+        .await
+        .map_err(|error| Error::FileFailed(FileError::new(Operation::Write, &path, &error)))?;
+
+    Ok(path)
+}
+
+// 生成 HTML 文档。所有样式都写在元素的 style 属性中，复制到博客或邮件中时颜色不会丢失。
+fn render(title: &str, lines: &[syntax::Line], theme: Theme) -> String {
+    let mut code = String::new();
+
+    for (index, line) in lines.iter().enumerate() {
+        if index > 0 {
+            code.push('\n');
+        }
+
+        for (color, text) in line {
+            let _ = write!(
+                code,
+                "<span style=\"color: {}\">{}</span>",
+                css(*color),
+                escape(text)
+            );
+        }
+    }
+
+    format!(
+        "<!DOCTYPE html>\n\
+         <html>\n\
+         <head>\n\
+         <meta charset=\"utf-8\">\n\
+         <title>{title}</title>\n\
+         </head>\n\
+         <body>\n\
+         <pre style=\"background-color: {background}; color: {foreground}; padding: 1em; \
+         font-family: monospace; tab-size: 4; overflow-x: auto\"><code>{code}</code></pre>\n\
+         </body>\n\
+         </html>\n",
+        title = escape(title),
+        background = css(theme.background()),
+        foreground = css(theme.foreground()),
+    )
+}
+
+// 将颜色转换为 CSS 的十六进制表示。
+fn css(color: Color) -> String {
+    let [r, g, b, _] = color.into_rgba8();
+
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+// 转义 HTML 中的特殊字符。
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+
+    escaped
+}
@@ -0,0 +1,78 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::OnceLock;
+
+// 界面当前使用的语言，保存为 Language 的序号。
+//
+// 界面文字分散在各个模块的视图函数中，用全局状态代替逐层传递语言设置。
+static LANGUAGE: AtomicU8 = AtomicU8::new(0);
+
+// 中文翻译，以英文原文为键，与 gettext 一样缺少的翻译直接显示原文。
+const CHINESE: &str = include_str!("../locales/zh-CN.toml");
+
+// 定义界面可以使用的语言。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Language {
+    #[default]
+    English, // 英文，即源代码中的原文。
+    Chinese, // 简体中文。
+}
+
+impl Language {
+    pub const ALL: &'static [Self] = &[Self::English, Self::Chinese];
+
+    // 返回语言的翻译表，英文不需要翻译。
+    fn bundle(self) -> Option<&'static HashMap<String, String>> {
+        static CHINESE_BUNDLE: OnceLock<HashMap<String, String>> = OnceLock::new();
+
+        match self {
+            Self::English => None,
+            Self::Chinese => {
+                Some(CHINESE_BUNDLE.get_or_init(|| toml::from_str(CHINESE).unwrap_or_default()))
+            }
+        }
+    }
+}
+
+impl fmt::Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // 语言的名称总是用这种语言本身显示，以便切换错误时也能找到原来的语言。
+        f.write_str(match self {
+            Self::English => "English",
+            Self::Chinese => "简体中文",
+        })
+    }
+}
+
+// 设置界面使用的语言，之后创建的界面都会使用新的语言。
+pub fn set_language(language: Language) {
+    LANGUAGE.store(language as u8, Ordering::Relaxed);
+}
+
+// 返回界面当前使用的语言。
+pub fn language() -> Language {
+    match LANGUAGE.load(Ordering::Relaxed) {
+        1 => Language::Chinese,
+        _ => Language::English,
+    }
+}
+
+// 把英文原文翻译为当前的语言，没有翻译时返回原文。
+pub fn tr(message: &'static str) -> &'static str {
+    language()
+        .bundle()
+        .and_then(|bundle| bundle.get(message))
+        .map_or(message, String::as_str)
+}
+
+// 翻译带有占位符的文本，再把 {name} 形式的占位符替换为对应的参数。
+//
+// 不同语言中参数的顺序可能不同，因此使用命名的占位符而不是位置参数。
+pub fn trf(message: &'static str, args: &[(&str, &dyn fmt::Display)]) -> String {
+    args.iter()
+        .fold(tr(message).to_string(), |text, (name, value)| {
+            text.replace(&format!("{{{name}}}"), &value.to_string())
+        })
+}
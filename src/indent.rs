@@ -0,0 +1,169 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// 定义缩进的方式。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Indentation {
+    pub use_tabs: bool,   // 是否使用制表符缩进。
+    pub size: usize,      // 使用空格缩进时一级缩进的空格数。
+    pub tab_width: usize, // 制表符的显示宽度。
+}
+
+// 定义偏好设置中某种语言的缩进覆盖项，没有设置的项使用语言的默认值。
+//
+// 在 preferences.toml 中按文件扩展名配置，例如：
+//
+// [indentation.yaml]
+// use_tabs = false
+// size = 2
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Override {
+    pub use_tabs: Option<bool>,   // 是否使用制表符缩进。
+    pub size: Option<usize>,      // 一级缩进的空格数。
+    pub tab_width: Option<usize>, // 制表符的显示宽度。
+}
+
+// 定义某种语言的缩进规则。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rules {
+    pub indentation: Indentation, // 缩进的方式。
+    pub openers: &'static [char], // 行尾出现这些字符时，下一行增加一级缩进。
+}
+
+impl Rules {
+    // 根据文件扩展名返回默认的缩进规则。
+    pub fn for_extension(extension: &str) -> Self {
+        let (use_tabs, size, openers): (bool, usize, &'static [char]) = match extension {
+            "py" | "pyw" => (false, 4, &[':', '(', '[', '{']),
+            "yaml" | "yml" => (false, 2, &[':']),
+            "go" | "mk" => (true, 4, &['{', '(', '[']),
+            "html" | "xml" | "js" | "ts" | "json" | "css" | "lua" | "rb" => {
+                (false, 2, &['{', '(', '['])
+            }
+            _ => (false, 4, &['{', '(', '[']),
+        };
+
+        Self {
+            indentation: Indentation {
+                use_tabs,
+                size,
+                tab_width: 4,
+            },
+            openers,
+        }
+    }
+
+    // 根据偏好设置返回缩进规则：先使用设置中的制表符宽度，再应用该语言的覆盖项。
+    pub fn with_preferences(
+        extension: &str,
+        tab_width: usize,
+        overrides: &BTreeMap<String, Override>,
+    ) -> Self {
+        let mut rules = Self::for_extension(extension);
+        rules.indentation.tab_width = tab_width.max(1);
+
+        if let Some(language) = overrides.get(extension) {
+            rules.apply(*language);
+        }
+
+        rules
+    }
+
+    // 应用覆盖项中设置了的部分。
+    pub fn apply(&mut self, language: Override) {
+        let indentation = &mut self.indentation;
+
+        indentation.use_tabs = language.use_tabs.unwrap_or(indentation.use_tabs);
+        indentation.size = language.size.unwrap_or(indentation.size).max(1);
+        indentation.tab_width = language.tab_width.unwrap_or(indentation.tab_width).max(1);
+    }
+
+    // 返回一级缩进使用的文本。
+    pub fn unit(&self) -> String {
+        if self.indentation.use_tabs {
+            String::from("\t")
+        } else {
+            " ".repeat(self.indentation.size)
+        }
+    }
+
+    // 计算缩进文本的显示宽度，制表符按制表符宽度计算。
+    pub fn width(&self, indentation: &str) -> usize {
+        indentation
+            .chars()
+            .map(|c| {
+                if c == '\t' {
+                    self.indentation.tab_width
+                } else {
+                    1
+                }
+            })
+            .sum()
+    }
+
+    // 计算一行开头缩进的显示宽度，空白行没有缩进，返回 None。
+    pub fn leading_width(&self, line: &str) -> Option<usize> {
+        let content = line.trim_start_matches([' ', '\t']);
+
+        if content.trim().is_empty() {
+            None
+        } else {
+            Some(self.width(&line[..line.len() - content.len()]))
+        }
+    }
+
+    // 计算在给定文本之后换行时新行的缩进：继承当前行的缩进，行尾为开括号时再增加一级。
+    pub fn indentation_after(&self, before: &str) -> String {
+        let mut indentation: String = before
+            .chars()
+            .take_while(|c| *c == ' ' || *c == '\t')
+            .collect();
+
+        if before.trim_end().ends_with(self.openers) {
+            indentation.push_str(&self.unit());
+        }
+
+        indentation
+    }
+
+    // 在只有缩进的行中输入闭括号时，返回需要删除的字符数，使闭括号回退一级缩进。
+    pub fn dedent(&self, before: &str, c: char) -> usize {
+        let is_closer = matches!(c, '}' | ')' | ']');
+        let is_blank = before.chars().all(|c| c == ' ' || c == '\t');
+        let unit = self.unit();
+
+        if is_closer && is_blank && before.ends_with(&unit) {
+            unit.chars().count()
+        } else {
+            0
+        }
+    }
+
+    // 使用空格缩进时，返回按下 Tab 键需要插入的空格，补齐到下一个缩进位置；使用制表符时返回 None。
+    pub fn tab(&self, before: &str) -> Option<String> {
+        if self.indentation.use_tabs {
+            return None;
+        }
+
+        let size = self.indentation.size;
+        let column = self.width(before);
+
+        Some(" ".repeat(size - column % size))
+    }
+
+    // 使用空格缩进时，在只有空格的缩进中退格回到上一个缩进位置，返回需要删除的空格数。
+    pub fn backspace(&self, before: &str) -> usize {
+        if self.indentation.use_tabs || before.is_empty() || !before.chars().all(|c| c == ' ') {
+            return 1;
+        }
+
+        let size = self.indentation.size;
+        let column = before.len();
+
+        match column % size {
+            0 => size,
+            remainder => remainder,
+        }
+    }
+}
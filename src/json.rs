@@ -0,0 +1,169 @@
+use serde::de::IgnoredAny;
+
+// 定义 JSON 解析错误的位置和原因。
+#[derive(Debug, Clone)]
+pub struct Invalid {
+    pub line: usize,     // 出错的行，从 0 开始。
+    pub column: usize,   // 出错的列，按字符计算，从 0 开始。
+    pub message: String, // 错误的描述。
+}
+
+impl Invalid {
+    fn new(text: &str, error: serde_json::Error) -> Self {
+        // serde_json 的行号和列号从 1 开始，列号是行中的字节数，列号为 0 表示错误发生在行首之前。
+        let line = error.line().saturating_sub(1);
+        let byte = error.column().saturating_sub(1);
+        let column = text.split('\n').nth(line).map_or(0, |line| {
+            line.char_indices()
+                .take_while(|(index, _)| *index < byte)
+                .count()
+        });
+
+        Self {
+            line,
+            column,
+            message: error.to_string(),
+        }
+    }
+}
+
+// 判断是否为 JSON 文件。
+pub fn is_json(extension: &str) -> bool {
+    matches!(extension, "json" | "geojson")
+}
+
+// 检查文本是否为有效的 JSON，只检查语法，不解析出具体的值。
+pub fn validate(text: &str) -> Result<(), Invalid> {
+    serde_json::from_str::<IgnoredAny>(text)
+        .map(|_| ())
+        .map_err(|error| Invalid::new(text, error))
+}
+
+// 使用指定的缩进格式化 JSON 文本，原文以换行符结尾时保留。
+//
+// 只调整字符串以外的空白，数字、字符串和重复的键都原样保留，不经过解析后的值再序列化。
+pub fn format(text: &str, indentation: &str) -> Result<String, Invalid> {
+    validate(text)?;
+
+    let mut formatted = String::with_capacity(text.len());
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut is_escaped = false;
+
+    let newline = |formatted: &mut String, depth: usize| {
+        formatted.push('\n');
+        for _ in 0..depth {
+            formatted.push_str(indentation);
+        }
+    };
+
+    let mut source = text.chars();
+
+    while let Some(c) = source.next() {
+        if in_string {
+            formatted.push(c);
+
+            if is_escaped {
+                is_escaped = false;
+            } else if c == '\\' {
+                is_escaped = true;
+            } else if c == '"' {
+                in_string = false;
+            }
+
+            continue;
+        }
+
+        match c {
+            // 字符串以外的空白都重新生成。
+            c if c.is_whitespace() => {}
+            '"' => {
+                formatted.push(c);
+                in_string = true;
+            }
+            '{' | '[' => {
+                formatted.push(c);
+
+                // 空的对象和数组写在同一行。
+                let rest = source.as_str().trim_start();
+                let close = if c == '{' { '}' } else { ']' };
+
+                if rest.starts_with(close) {
+                    formatted.push(close);
+                    source = rest[close.len_utf8()..].chars();
+                } else {
+                    depth += 1;
+                    newline(&mut formatted, depth);
+                }
+            }
+            '}' | ']' => {
+                depth -= 1;
+                newline(&mut formatted, depth);
+                formatted.push(c);
+            }
+            ',' => {
+                formatted.push(c);
+                newline(&mut formatted, depth);
+            }
+            ':' => formatted.push_str(": "),
+            _ => formatted.push(c),
+        }
+    }
+
+    if text.ends_with('\n') {
+        formatted.push('\n');
+    }
+
+    Ok(formatted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_error_positions_from_zero() {
+        let error = validate("[1,]").unwrap_err();
+        assert_eq!((error.line, error.column), (0, 3));
+
+        let error = validate("{\n  \"a\": 1,\n}").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 0));
+        assert!(error.message.contains("trailing comma"));
+    }
+
+    #[test]
+    fn accepts_valid_json() {
+        assert!(validate("{\"a\": [1, 2, null]}").is_ok());
+    }
+
+    #[test]
+    fn formats_with_indentation_and_keeps_key_order() {
+        let formatted = format("{\"b\":1,\"a\":[true]}\n", "  ").unwrap();
+
+        assert_eq!(formatted, "{\n  \"b\": 1,\n  \"a\": [\n    true\n  ]\n}\n");
+    }
+
+    #[test]
+    fn reports_columns_in_characters() {
+        let error = validate("{\"名字\": 1,}").unwrap_err();
+
+        assert_eq!((error.line, error.column), (0, 9));
+    }
+
+    #[test]
+    fn keeps_numbers_and_duplicate_keys() {
+        let formatted = format("{\"a\":12345678901234567890123,\"a\":1e3}", "  ").unwrap();
+
+        assert_eq!(
+            formatted,
+            "{\n  \"a\": 12345678901234567890123,\n  \"a\": 1e3\n}"
+        );
+    }
+
+    #[test]
+    fn keeps_strings_and_empty_containers() {
+        let formatted = format("[\"a ,{ \\\" b\", { }, [ ]]", "\t").unwrap();
+
+        assert_eq!(formatted, "[\n\t\"a ,{ \\\" b\",\n\t{},\n\t[]\n]");
+    }
+}
@@ -0,0 +1,1079 @@
+use crate::i18n::{tr, trf};
+use crate::{Error, FileError, Message, Operation};
+use iced::keyboard::{KeyCode, Modifiers};
+use iced::widget::text_editor::{self, Motion};
+use iced::widget::{button, column, horizontal_space, pane_grid, pick_list, row, text};
+use iced::{theme, Element, Length};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::PathBuf;
+
+// 定义可以绑定快捷键的编辑器操作。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    New,              // 新建文件。
+    Open,             // 打开文件。
+    Save,             // 保存文件。
+    SaveAs,           // 另存为新文件。
+    Undo,             // 撤销。
+    Redo,             // 重做。
+    NextTab,          // 切换到下一个标签页。
+    CloseTab,         // 关闭当前标签页。
+    GoToLine,         // 跳转到行。
+    ToggleSidebar,    // 显示或隐藏文件树侧边栏。
+    ToggleSettings,   // 打开或关闭设置界面。
+    Dismiss,          // 关闭打开的对话框。
+    SplitVertical,    // 左右分屏。
+    SplitHorizontal,  // 上下分屏。
+    CloseSplit,       // 关闭分屏。
+    ToggleComment,    // 注释或取消注释选中的行。
+    FindInFiles,      // 在目录中搜索。
+    ToggleWordWrap,   // 切换自动换行。
+    LineNumbers,      // 显示或隐藏行号。
+    Minimap,          // 显示或隐藏缩略图。
+    ZoomIn,           // 放大。
+    ZoomOut,          // 缩小。
+    ResetZoom,        // 恢复默认的缩放比例。
+    CommandPalette,   // 打开命令面板。
+    SourceControl,    // 打开或关闭源代码管理面板。
+    Terminal,         // 打开或关闭终端面板。
+    RunFile,          // 运行当前文件。
+    Complete,         // 请求光标处的补全。
+    Hover,            // 显示光标处的悬停信息。
+    Problems,         // 打开或关闭问题面板。
+    NextOccurrence,   // 为选中文本的下一处出现的位置添加光标。
+    DuplicateLines,   // 复制当前行或选中的行。
+    MoveLinesUp,      // 上移当前行或选中的行。
+    MoveLinesDown,    // 下移当前行或选中的行。
+    FormatDocument,   // 格式化当前文档。
+    Print,            // 将当前文档打印为 PDF。
+    ExportHtml,       // 将当前文档导出为 HTML。
+    ShowChanges,      // 显示当前文档与磁盘上的文件之间的差异。
+    Compare,          // 选择另一个文件与当前文档比较。
+    LineStart,        // 移到行首。
+    LineEnd,          // 移到行尾。
+    KillLine,         // 剪切从光标到行尾的文本。
+    Yank,             // 粘贴剪贴板中的文本。
+    PasteHistory,     // 选择剪贴板历史中的一项粘贴。
+    OpenRecent,       // 打开当前工作区中最近打开的文件。
+    SwitchWorkspace,  // 切换到另一个工作区。
+    QuickOpen,        // 按名称快速打开文件。
+    Outline,          // 打开或关闭大纲面板。
+    LocalHistory,     // 打开或关闭当前文档的本地历史面板。
+    OpenUrl,          // 从网址打开文件。
+    OpenRemote,       // 通过 SFTP 打开远程文件。
+    TableView,        // 在表格和文本之间切换 CSV 或 TSV 文件的视图。
+    FormatJson,       // 格式化 JSON 文档。
+    ValidateJson,     // 检查 JSON 文档是否有效。
+    FindReplace,      // 在当前文档中查找和替换。
+    ToggleBookmark,   // 在光标所在的行上添加或移除书签。
+    NextBookmark,     // 跳转到下一个书签。
+    PreviousBookmark, // 跳转到上一个书签。
+    Fold,             // 折叠光标所在的区域。
+    Unfold,           // 展开光标所在行开始的折叠区域。
+    RecordMacro,      // 开始或停止录制键盘宏。
+    PlayMacro,        // 重放录制的键盘宏。
+    Console,          // 显示或隐藏脚本控制台。
+    RevealFile,       // 在系统的文件管理器中显示当前文件。
+    CopyPath,         // 复制当前文件的绝对路径。
+    CopyFileName,     // 复制当前文件的文件名。
+    RevertFile,       // 放弃未保存的修改，从磁盘重新读取当前文件。
+    Whitespace,       // 切换显示空白字符的范围。
+    SelectLine,       // 选中光标所在的行。
+    ExpandSelection,  // 把选区扩大到包含它的下一级范围。
+    NewWindow,        // 打开一个新的编辑器窗口。
+    NewScratch,       // 新建一个临时缓冲区。
+}
+
+impl Action {
+    // 所有可以绑定快捷键的操作。
+    pub const ALL: &[Self] = &[
+        Self::New,
+        Self::Open,
+        Self::Save,
+        Self::SaveAs,
+        Self::Undo,
+        Self::Redo,
+        Self::NextTab,
+        Self::CloseTab,
+        Self::GoToLine,
+        Self::ToggleSidebar,
+        Self::ToggleSettings,
+        Self::Dismiss,
+        Self::SplitVertical,
+        Self::SplitHorizontal,
+        Self::CloseSplit,
+        Self::ToggleComment,
+        Self::FindInFiles,
+        Self::ToggleWordWrap,
+        Self::LineNumbers,
+        Self::Minimap,
+        Self::ZoomIn,
+        Self::ZoomOut,
+        Self::ResetZoom,
+        Self::CommandPalette,
+        Self::SourceControl,
+        Self::Terminal,
+        Self::RunFile,
+        Self::Complete,
+        Self::Hover,
+        Self::Problems,
+        Self::NextOccurrence,
+        Self::DuplicateLines,
+        Self::MoveLinesUp,
+        Self::MoveLinesDown,
+        Self::FormatDocument,
+        Self::Print,
+        Self::ExportHtml,
+        Self::ShowChanges,
+        Self::Compare,
+        Self::LineStart,
+        Self::LineEnd,
+        Self::KillLine,
+        Self::Yank,
+        Self::PasteHistory,
+        Self::OpenRecent,
+        Self::SwitchWorkspace,
+        Self::QuickOpen,
+        Self::Outline,
+        Self::LocalHistory,
+        Self::OpenUrl,
+        Self::OpenRemote,
+        Self::TableView,
+        Self::FormatJson,
+        Self::ValidateJson,
+        Self::FindReplace,
+        Self::ToggleBookmark,
+        Self::NextBookmark,
+        Self::PreviousBookmark,
+        Self::Fold,
+        Self::Unfold,
+        Self::RecordMacro,
+        Self::PlayMacro,
+        Self::Console,
+        Self::RevealFile,
+        Self::CopyPath,
+        Self::CopyFileName,
+        Self::RevertFile,
+        Self::Whitespace,
+        Self::SelectLine,
+        Self::ExpandSelection,
+        Self::NewWindow,
+        Self::NewScratch,
+    ];
+
+    // 返回配置文件中使用的名称。
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::New => "new",
+            Self::Open => "open",
+            Self::Save => "save",
+            Self::SaveAs => "save_as",
+            Self::Undo => "undo",
+            Self::Redo => "redo",
+            Self::NextTab => "next_tab",
+            Self::CloseTab => "close_tab",
+            Self::GoToLine => "go_to_line",
+            Self::ToggleSidebar => "toggle_sidebar",
+            Self::ToggleSettings => "toggle_settings",
+            Self::Dismiss => "dismiss",
+            Self::SplitVertical => "split_vertical",
+            Self::SplitHorizontal => "split_horizontal",
+            Self::CloseSplit => "close_split",
+            Self::ToggleComment => "toggle_comment",
+            Self::FindInFiles => "find_in_files",
+            Self::ToggleWordWrap => "toggle_word_wrap",
+            Self::LineNumbers => "toggle_line_numbers",
+            Self::Minimap => "toggle_minimap",
+            Self::ZoomIn => "zoom_in",
+            Self::ZoomOut => "zoom_out",
+            Self::ResetZoom => "reset_zoom",
+            Self::CommandPalette => "command_palette",
+            Self::SourceControl => "source_control",
+            Self::Terminal => "terminal",
+            Self::RunFile => "run_file",
+            Self::Complete => "complete",
+            Self::Hover => "hover",
+            Self::Problems => "problems",
+            Self::NextOccurrence => "add_next_occurrence",
+            Self::DuplicateLines => "duplicate_lines",
+            Self::MoveLinesUp => "move_lines_up",
+            Self::MoveLinesDown => "move_lines_down",
+            Self::FormatDocument => "format_document",
+            Self::Print => "print",
+            Self::ExportHtml => "export_html",
+            Self::ShowChanges => "show_changes",
+            Self::Compare => "compare_with_file",
+            Self::LineStart => "line_start",
+            Self::LineEnd => "line_end",
+            Self::KillLine => "kill_line",
+            Self::Yank => "yank",
+            Self::PasteHistory => "paste_from_history",
+            Self::OpenRecent => "open_recent",
+            Self::SwitchWorkspace => "switch_workspace",
+            Self::QuickOpen => "quick_open",
+            Self::Outline => "toggle_outline",
+            Self::LocalHistory => "local_history",
+            Self::OpenUrl => "open_url",
+            Self::OpenRemote => "open_remote",
+            Self::TableView => "table_view",
+            Self::FormatJson => "format_json",
+            Self::ValidateJson => "validate_json",
+            Self::FindReplace => "find_replace",
+            Self::ToggleBookmark => "toggle_bookmark",
+            Self::NextBookmark => "next_bookmark",
+            Self::PreviousBookmark => "previous_bookmark",
+            Self::Fold => "fold",
+            Self::Unfold => "unfold",
+            Self::RecordMacro => "record_macro",
+            Self::PlayMacro => "play_macro",
+            Self::Console => "console",
+            Self::RevealFile => "reveal_file",
+            Self::CopyPath => "copy_path",
+            Self::CopyFileName => "copy_file_name",
+            Self::RevertFile => "revert_file",
+            Self::Whitespace => "toggle_whitespace",
+            Self::SelectLine => "select_line",
+            Self::ExpandSelection => "expand_selection",
+            Self::NewWindow => "new_window",
+            Self::NewScratch => "new_scratch",
+        }
+    }
+
+    // 返回执行该操作时发送的消息。
+    pub fn message(self) -> Message {
+        match self {
+            Self::New => Message::New,
+            Self::Open => Message::Open,
+            Self::Save => Message::Save,
+            Self::SaveAs => Message::SaveAs,
+            Self::Undo => Message::Undo,
+            Self::Redo => Message::Redo,
+            Self::NextTab => Message::NextTab,
+            Self::CloseTab => Message::CloseActiveTab,
+            Self::GoToLine => Message::GoToLine,
+            Self::ToggleSidebar => Message::ToggleSidebar,
+            Self::ToggleSettings => Message::ToggleSettings,
+            Self::Dismiss => Message::Dismiss,
+            Self::SplitVertical => Message::Split(pane_grid::Axis::Vertical),
+            Self::SplitHorizontal => Message::Split(pane_grid::Axis::Horizontal),
+            Self::CloseSplit => Message::CloseSplit,
+            Self::ToggleComment => Message::ToggleComment,
+            Self::FindInFiles => Message::FindInFiles,
+            Self::ToggleWordWrap => Message::ToggleWordWrap,
+            Self::LineNumbers => Message::ToggleLineNumbers,
+            Self::Minimap => Message::ToggleMinimap,
+            Self::ZoomIn => Message::ZoomIn,
+            Self::ZoomOut => Message::ZoomOut,
+            Self::ResetZoom => Message::ResetZoom,
+            Self::CommandPalette => Message::CommandPalette,
+            Self::SourceControl => Message::ToggleSourceControl,
+            Self::Terminal => Message::ToggleTerminal,
+            Self::RunFile => Message::RunFile,
+            Self::Complete => Message::TriggerCompletion,
+            Self::Hover => Message::ShowHover,
+            Self::Problems => Message::ToggleProblems,
+            Self::NextOccurrence => Message::AddNextOccurrence,
+            Self::DuplicateLines => Message::DuplicateLines,
+            Self::MoveLinesUp => Message::MoveLinesUp,
+            Self::MoveLinesDown => Message::MoveLinesDown,
+            Self::FormatDocument => Message::FormatDocument,
+            Self::Print => Message::Print,
+            Self::ExportHtml => Message::ExportHtml,
+            Self::ShowChanges => Message::ShowChanges,
+            Self::Compare => Message::Compare,
+            Self::LineStart => Message::Edit(text_editor::Action::Move(Motion::Home)),
+            Self::LineEnd => Message::Edit(text_editor::Action::Move(Motion::End)),
+            Self::KillLine => Message::KillLine,
+            Self::Yank => Message::Yank,
+            Self::PasteHistory => Message::PasteHistory,
+            Self::OpenRecent => Message::OpenRecent,
+            Self::SwitchWorkspace => Message::SwitchWorkspace,
+            Self::QuickOpen => Message::QuickOpen,
+            Self::Outline => Message::ToggleOutline,
+            Self::LocalHistory => Message::ToggleLocalHistory,
+            Self::OpenUrl => Message::OpenUrl,
+            Self::OpenRemote => Message::OpenRemote,
+            Self::TableView => Message::ToggleTable,
+            Self::FormatJson => Message::FormatJson,
+            Self::ValidateJson => Message::ValidateJson,
+            Self::FindReplace => Message::FindReplace,
+            Self::ToggleBookmark => Message::ToggleBookmark,
+            Self::NextBookmark => Message::NextBookmark,
+            Self::PreviousBookmark => Message::PreviousBookmark,
+            Self::Fold => Message::Fold,
+            Self::Unfold => Message::Unfold,
+            Self::RecordMacro => Message::ToggleMacroRecording,
+            Self::PlayMacro => Message::PlayMacro,
+            Self::Console => Message::ToggleConsole,
+            Self::RevealFile => Message::RevealFile,
+            Self::CopyPath => Message::CopyPath,
+            Self::CopyFileName => Message::CopyFileName,
+            Self::RevertFile => Message::RevertFile,
+            Self::Whitespace => Message::ToggleWhitespace,
+            Self::SelectLine => Message::SelectLine,
+            Self::ExpandSelection => Message::ExpandSelection,
+            Self::NewWindow => Message::NewWindow,
+            Self::NewScratch => Message::NewScratch,
+        }
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(tr(match self {
+            Self::New => "New File",
+            Self::Open => "Open File",
+            Self::Save => "Save File",
+            Self::SaveAs => "Save File As",
+            Self::Undo => "Undo",
+            Self::Redo => "Redo",
+            Self::NextTab => "Next Tab",
+            Self::CloseTab => "Close Tab",
+            Self::GoToLine => "Go to Line",
+            Self::ToggleSidebar => "Toggle Sidebar",
+            Self::ToggleSettings => "Toggle Settings",
+            Self::Dismiss => "Close Dialog",
+            Self::SplitVertical => "Split Vertically",
+            Self::SplitHorizontal => "Split Horizontally",
+            Self::CloseSplit => "Close Split",
+            Self::ToggleComment => "Toggle Line Comment",
+            Self::FindInFiles => "Find in Files",
+            Self::ToggleWordWrap => "Toggle Word Wrap",
+            Self::LineNumbers => "Toggle Line Numbers",
+            Self::Minimap => "Toggle Minimap",
+            Self::ZoomIn => "Zoom In",
+            Self::ZoomOut => "Zoom Out",
+            Self::ResetZoom => "Reset Zoom",
+            Self::CommandPalette => "Command Palette",
+            Self::SourceControl => "Toggle Source Control",
+            Self::Terminal => "Toggle Terminal",
+            Self::RunFile => "Run File",
+            Self::Complete => "Trigger Completion",
+            Self::Hover => "Show Hover",
+            Self::Problems => "Toggle Problems",
+            Self::NextOccurrence => "Add Next Occurrence",
+            Self::DuplicateLines => "Duplicate Lines",
+            Self::MoveLinesUp => "Move Lines Up",
+            Self::MoveLinesDown => "Move Lines Down",
+            Self::FormatDocument => "Format Document",
+            Self::Print => "Print to PDF",
+            Self::ExportHtml => "Export as HTML",
+            Self::ShowChanges => "Show Changes",
+            Self::Compare => "Compare with File",
+            Self::LineStart => "Move to Line Start",
+            Self::LineEnd => "Move to Line End",
+            Self::KillLine => "Kill to End of Line",
+            Self::Yank => "Yank",
+            Self::PasteHistory => "Paste from History",
+            Self::OpenRecent => "Open Recent File",
+            Self::SwitchWorkspace => "Switch Workspace",
+            Self::QuickOpen => "Go to File",
+            Self::Outline => "Toggle Outline",
+            Self::LocalHistory => "Local History",
+            Self::OpenUrl => "Open URL",
+            Self::OpenRemote => "Open Remote File",
+            Self::TableView => "Toggle Table View",
+            Self::FormatJson => "Format JSON",
+            Self::ValidateJson => "Validate JSON",
+            Self::FindReplace => "Find and Replace",
+            Self::ToggleBookmark => "Toggle Bookmark",
+            Self::NextBookmark => "Next Bookmark",
+            Self::PreviousBookmark => "Previous Bookmark",
+            Self::Fold => "Fold",
+            Self::Unfold => "Unfold",
+            Self::RecordMacro => "Start or Stop Macro Recording",
+            Self::PlayMacro => "Play Macro",
+            Self::Console => "Toggle Script Console",
+            Self::RevealFile => "Reveal in File Manager",
+            Self::CopyPath => "Copy Path",
+            Self::CopyFileName => "Copy File Name",
+            Self::RevertFile => "Revert File",
+            Self::Whitespace => "Toggle Whitespace",
+            Self::SelectLine => "Select Line",
+            Self::ExpandSelection => "Expand Selection",
+            Self::NewWindow => "New Window",
+            Self::NewScratch => "New Scratch Buffer",
+        }))
+    }
+}
+
+// 定义一个按键组合。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Key {
+    pub code: KeyCode,        // 按下的键。
+    pub modifiers: Modifiers, // 同时按下的修饰键。
+}
+
+// 定义配置文件中可以使用的按键名称。
+const KEYS: &[(&str, KeyCode)] = &[
+    ("A", KeyCode::A),
+    ("B", KeyCode::B),
+    ("C", KeyCode::C),
+    ("D", KeyCode::D),
+    ("E", KeyCode::E),
+    ("F", KeyCode::F),
+    ("G", KeyCode::G),
+    ("H", KeyCode::H),
+    ("I", KeyCode::I),
+    ("J", KeyCode::J),
+    ("K", KeyCode::K),
+    ("L", KeyCode::L),
+    ("M", KeyCode::M),
+    ("N", KeyCode::N),
+    ("O", KeyCode::O),
+    ("P", KeyCode::P),
+    ("Q", KeyCode::Q),
+    ("R", KeyCode::R),
+    ("S", KeyCode::S),
+    ("T", KeyCode::T),
+    ("U", KeyCode::U),
+    ("V", KeyCode::V),
+    ("W", KeyCode::W),
+    ("X", KeyCode::X),
+    ("Y", KeyCode::Y),
+    ("Z", KeyCode::Z),
+    ("0", KeyCode::Key0),
+    ("1", KeyCode::Key1),
+    ("2", KeyCode::Key2),
+    ("3", KeyCode::Key3),
+    ("4", KeyCode::Key4),
+    ("5", KeyCode::Key5),
+    ("6", KeyCode::Key6),
+    ("7", KeyCode::Key7),
+    ("8", KeyCode::Key8),
+    ("9", KeyCode::Key9),
+    ("F1", KeyCode::F1),
+    ("F2", KeyCode::F2),
+    ("F3", KeyCode::F3),
+    ("F4", KeyCode::F4),
+    ("F5", KeyCode::F5),
+    ("F6", KeyCode::F6),
+    ("F7", KeyCode::F7),
+    ("F8", KeyCode::F8),
+    ("F9", KeyCode::F9),
+    ("F10", KeyCode::F10),
+    ("F11", KeyCode::F11),
+    ("F12", KeyCode::F12),
+    ("Tab", KeyCode::Tab),
+    ("Escape", KeyCode::Escape),
+    ("Enter", KeyCode::Enter),
+    ("Space", KeyCode::Space),
+    ("Backspace", KeyCode::Backspace),
+    ("Delete", KeyCode::Delete),
+    ("Home", KeyCode::Home),
+    ("End", KeyCode::End),
+    ("PageUp", KeyCode::PageUp),
+    ("PageDown", KeyCode::PageDown),
+    ("Left", KeyCode::Left),
+    ("Right", KeyCode::Right),
+    ("Up", KeyCode::Up),
+    ("Down", KeyCode::Down),
+    ("Plus", KeyCode::Plus),
+    ("NumpadAdd", KeyCode::NumpadAdd),
+    ("Minus", KeyCode::Minus),
+    ("Equals", KeyCode::Equals),
+    ("Slash", KeyCode::Slash),
+    ("Comma", KeyCode::Comma),
+    ("Period", KeyCode::Period),
+    ("Backslash", KeyCode::Backslash),
+    ("LBracket", KeyCode::LBracket),
+    ("RBracket", KeyCode::RBracket),
+    ("Grave", KeyCode::Grave),
+];
+
+impl Key {
+    // 创建一个按键组合，只保留 Shift、Ctrl、Alt 和 Logo 修饰键。
+    pub fn new(code: KeyCode, modifiers: Modifiers) -> Self {
+        Self {
+            code,
+            modifiers: modifiers
+                & (Modifiers::SHIFT | Modifiers::CTRL | Modifiers::ALT | Modifiers::LOGO),
+        }
+    }
+
+    // 判断按下的是否只是修饰键本身。
+    pub fn is_modifier(self) -> bool {
+        matches!(
+            self.code,
+            KeyCode::LShift
+                | KeyCode::RShift
+                | KeyCode::LControl
+                | KeyCode::RControl
+                | KeyCode::LAlt
+                | KeyCode::RAlt
+                | KeyCode::LWin
+                | KeyCode::RWin
+        )
+    }
+
+    // 解析形如 “Ctrl+Shift+S” 的按键组合。
+    pub fn parse(key: &str) -> Option<Self> {
+        let mut modifiers = Modifiers::empty();
+        let mut code = None;
+
+        for part in key.split('+').map(str::trim) {
+            match part.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => modifiers |= Modifiers::CTRL,
+                "shift" => modifiers |= Modifiers::SHIFT,
+                "alt" | "option" => modifiers |= Modifiers::ALT,
+                "cmd" | "super" | "logo" => modifiers |= Modifiers::LOGO,
+                _ => {
+                    code = KEYS
+                        .iter()
+                        .find(|(name, _)| name.eq_ignore_ascii_case(part))
+                        .map(|(_, code)| *code);
+                }
+            }
+        }
+
+        Some(Self::new(code?, modifiers))
+    }
+}
+
+// 定义一个快捷键：一个按键组合，或者像 Emacs 的 Ctrl+X Ctrl+S 那样先按前缀再按第二个组合。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Shortcut {
+    pub prefix: Option<Key>, // 需要先按下的前缀组合。
+    pub key: Key,            // 最后按下的组合。
+}
+
+impl Shortcut {
+    // 解析以空格分隔的一个或两个按键组合，例如 “Ctrl+X Ctrl+S”。
+    pub fn parse(shortcut: &str) -> Option<Self> {
+        let keys: Vec<&str> = shortcut.split_whitespace().collect();
+
+        match keys[..] {
+            [key] => Some(Self::from(Key::parse(key)?)),
+            [prefix, key] => Some(Self {
+                prefix: Some(Key::parse(prefix)?),
+                key: Key::parse(key)?,
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl From<Key> for Shortcut {
+    fn from(key: Key) -> Self {
+        Self { prefix: None, key }
+    }
+}
+
+impl fmt::Display for Shortcut {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.prefix {
+            Some(prefix) => write!(f, "{prefix} {}", self.key),
+            None => write!(f, "{}", self.key),
+        }
+    }
+}
+
+// 定义可以在设置界面中选择的一组预设快捷键。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Preset {
+    Default, // 编辑器默认的快捷键。
+    Emacs,   // 在默认快捷键的基础上使用 Emacs 风格的按键。
+}
+
+impl Preset {
+    // 所有的预设。
+    pub const ALL: &[Self] = &[Self::Default, Self::Emacs];
+
+    // 返回配置文件中使用的名称。
+    fn name(self) -> &'static str {
+        match self {
+            Self::Default => "default",
+            Self::Emacs => "emacs",
+        }
+    }
+
+    // 返回预设的快捷键映射。
+    //
+    // 两个组合组成的快捷键中，第二个组合也带有 Ctrl：单独的字母键会同时被输入到编辑器中。
+    pub fn keymap(self) -> Keymap {
+        let mut keymap = Keymap::default();
+
+        if self == Self::Emacs {
+            let ctrl = |code| Key::new(code, Modifiers::CTRL);
+            let prefix = ctrl(KeyCode::X);
+
+            let bindings = [
+                (Action::LineStart, Shortcut::from(ctrl(KeyCode::A))),
+                (Action::LineEnd, Shortcut::from(ctrl(KeyCode::E))),
+                (Action::KillLine, Shortcut::from(ctrl(KeyCode::K))),
+                (Action::Yank, Shortcut::from(ctrl(KeyCode::Y))),
+                (Action::Undo, Shortcut::from(ctrl(KeyCode::Slash))),
+                (Action::GoToLine, Shortcut::from(ctrl(KeyCode::L))),
+            ];
+
+            let sequences = [
+                (Action::Save, KeyCode::S),
+                (Action::Open, KeyCode::F),
+                (Action::SaveAs, KeyCode::W),
+                (Action::CloseTab, KeyCode::K),
+            ];
+
+            let sequences = sequences.into_iter().map(|(action, code)| {
+                (
+                    action,
+                    Shortcut {
+                        prefix: Some(prefix),
+                        key: ctrl(code),
+                    },
+                )
+            });
+
+            for (action, shortcut) in bindings.into_iter().chain(sequences) {
+                keymap.bind(action, shortcut);
+            }
+        }
+
+        keymap.preset = self;
+        keymap
+    }
+}
+
+impl fmt::Display for Preset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Default => tr("Default"),
+            Self::Emacs => "Emacs",
+        })
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (modifier, name) in [
+            (Modifiers::CTRL, "Ctrl+"),
+            (Modifiers::LOGO, "Cmd+"),
+            (Modifiers::ALT, "Alt+"),
+            (Modifiers::SHIFT, "Shift+"),
+        ] {
+            if self.modifiers.contains(modifier) {
+                f.write_str(name)?;
+            }
+        }
+
+        match KEYS.iter().find(|(_, code)| *code == self.code) {
+            Some((name, _)) => f.write_str(name),
+            None => write!(f, "{:?}", self.code),
+        }
+    }
+}
+
+// 放大时除了绑定的快捷键之外也接受的按键：单独的加号键和小键盘的加号键，
+// 与 Ctrl 或 Ctrl+Shift 一起按下时放大。
+const ZOOM_IN_KEYS: &[KeyCode] = &[KeyCode::Plus, KeyCode::NumpadAdd];
+
+// 配置文件中脚本快捷键的名称前缀。
+const SCRIPT_PREFIX: &str = "script:";
+
+// 定义按键组合到编辑器操作的映射。
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    preset: Preset,                    // 快捷键所基于的预设。
+    bindings: Vec<(Action, Shortcut)>, // 每个操作绑定的快捷键。
+    scripts: Vec<(String, Shortcut)>,  // 每个脚本绑定的快捷键，附带脚本的名称。
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let command = Modifiers::COMMAND;
+        let shifted = Modifiers::COMMAND | Modifiers::SHIFT;
+
+        let bindings = vec![
+            (Action::New, Key::new(KeyCode::N, command)),
+            (Action::NewWindow, Key::new(KeyCode::N, shifted)),
+            (Action::Open, Key::new(KeyCode::O, command)),
+            (Action::Save, Key::new(KeyCode::S, command)),
+            (Action::SaveAs, Key::new(KeyCode::S, shifted)),
+            (Action::Undo, Key::new(KeyCode::Z, command)),
+            (Action::Redo, Key::new(KeyCode::Z, shifted)),
+            (Action::NextTab, Key::new(KeyCode::Tab, Modifiers::CTRL)),
+            (Action::CloseTab, Key::new(KeyCode::W, command)),
+            (Action::GoToLine, Key::new(KeyCode::G, command)),
+            (Action::ToggleSidebar, Key::new(KeyCode::B, command)),
+            (Action::ToggleSettings, Key::new(KeyCode::Comma, command)),
+            (
+                Action::Dismiss,
+                Key::new(KeyCode::Escape, Modifiers::empty()),
+            ),
+            (Action::SplitVertical, Key::new(KeyCode::Backslash, command)),
+            (Action::ToggleComment, Key::new(KeyCode::Slash, command)),
+            (Action::FindInFiles, Key::new(KeyCode::F, shifted)),
+            (Action::ToggleWordWrap, Key::new(KeyCode::Z, Modifiers::ALT)),
+            // 大部分键盘上加号与等号在同一个键上。
+            (Action::ZoomIn, Key::new(KeyCode::Equals, command)),
+            (Action::ZoomOut, Key::new(KeyCode::Minus, command)),
+            (Action::ResetZoom, Key::new(KeyCode::Key0, command)),
+            (Action::CommandPalette, Key::new(KeyCode::P, shifted)),
+            (Action::SourceControl, Key::new(KeyCode::G, shifted)),
+            (Action::Terminal, Key::new(KeyCode::Grave, Modifiers::CTRL)),
+            (Action::RunFile, Key::new(KeyCode::F5, Modifiers::empty())),
+            (Action::Complete, Key::new(KeyCode::Space, Modifiers::CTRL)),
+            (Action::Hover, Key::new(KeyCode::I, command)),
+            (Action::Problems, Key::new(KeyCode::M, shifted)),
+            (Action::NextOccurrence, Key::new(KeyCode::D, command)),
+            (Action::DuplicateLines, Key::new(KeyCode::D, shifted)),
+            (Action::MoveLinesUp, Key::new(KeyCode::Up, Modifiers::ALT)),
+            (
+                Action::MoveLinesDown,
+                Key::new(KeyCode::Down, Modifiers::ALT),
+            ),
+            (
+                Action::FormatDocument,
+                Key::new(KeyCode::F, Modifiers::SHIFT | Modifiers::ALT),
+            ),
+            (
+                Action::Print,
+                Key::new(KeyCode::P, command | Modifiers::ALT),
+            ),
+            (Action::PasteHistory, Key::new(KeyCode::V, shifted)),
+            (Action::OpenRecent, Key::new(KeyCode::R, command)),
+            (Action::QuickOpen, Key::new(KeyCode::P, command)),
+            (Action::Outline, Key::new(KeyCode::O, shifted)),
+            (Action::FindReplace, Key::new(KeyCode::H, command)),
+            (
+                Action::ToggleBookmark,
+                Key::new(KeyCode::F2, Modifiers::CTRL),
+            ),
+            (
+                Action::NextBookmark,
+                Key::new(KeyCode::F2, Modifiers::empty()),
+            ),
+            (
+                Action::PreviousBookmark,
+                Key::new(KeyCode::F2, Modifiers::SHIFT),
+            ),
+            (Action::Fold, Key::new(KeyCode::LBracket, shifted)),
+            (Action::Unfold, Key::new(KeyCode::RBracket, shifted)),
+            (Action::RecordMacro, Key::new(KeyCode::R, shifted)),
+            (Action::PlayMacro, Key::new(KeyCode::E, shifted)),
+            (
+                Action::Console,
+                Key::new(KeyCode::Grave, Modifiers::CTRL | Modifiers::SHIFT),
+            ),
+            (Action::SelectLine, Key::new(KeyCode::L, command)),
+            (
+                Action::ExpandSelection,
+                Key::new(KeyCode::Right, Modifiers::SHIFT | Modifiers::ALT),
+            ),
+        ];
+
+        Self {
+            preset: Preset::Default,
+            bindings: bindings
+                .into_iter()
+                .map(|(action, key)| (action, Shortcut::from(key)))
+                .collect(),
+            scripts: Vec::new(),
+        }
+    }
+}
+
+impl Keymap {
+    // 在预设快捷键的基础上应用 TOML 配置文件中的覆盖项，preset 指定使用的预设。
+    pub fn from_toml(contents: &str) -> Result<Self, Error> {
+        let mut overrides: BTreeMap<String, String> = toml::from_str(contents)
+            .map_err(|error| Error::InvalidConfig(error.message().to_string()))?;
+
+        let preset = match overrides.remove("preset") {
+            Some(name) => Preset::ALL
+                .iter()
+                .copied()
+                .find(|preset| preset.name() == name)
+                .ok_or_else(|| {
+                    Error::InvalidConfig(trf("Unknown preset: {name}", &[("name", &name)]))
+                })?,
+            None => Preset::Default,
+        };
+
+        let mut keymap = preset.keymap();
+
+        for (name, key) in overrides {
+            let shortcut = Shortcut::parse(&key).ok_or_else(|| {
+                Error::InvalidConfig(trf("Invalid key binding: {key}", &[("key", &key)]))
+            })?;
+
+            // 以 script: 开头的名称为脚本目录中的脚本，例如 "script:rename" = "Ctrl+Alt+R"。
+            if let Some(script) = name.strip_prefix(SCRIPT_PREFIX) {
+                keymap.bind_script(script.to_string(), shortcut);
+                continue;
+            }
+
+            let action = Action::ALL
+                .iter()
+                .copied()
+                .find(|action| action.name() == name)
+                .ok_or_else(|| {
+                    Error::InvalidConfig(trf("Unknown action: {name}", &[("name", &name)]))
+                })?;
+
+            keymap.bind(action, shortcut);
+        }
+
+        Ok(keymap)
+    }
+
+    // 将所有快捷键序列化为 TOML 配置文件的内容。
+    pub fn to_toml(&self) -> String {
+        let mut bindings: BTreeMap<String, String> = self
+            .bindings
+            .iter()
+            .map(|(action, key)| (action.name().to_string(), key.to_string()))
+            .chain(
+                self.scripts
+                    .iter()
+                    .map(|(script, key)| (format!("{SCRIPT_PREFIX}{script}"), key.to_string())),
+            )
+            .collect();
+
+        bindings.insert(String::from("preset"), self.preset.name().to_string());
+
+        toml::to_string(&bindings).unwrap_or_default()
+    }
+
+    // 查找按键组合对应的操作，prefix 为之前按下的前缀组合。
+    pub fn resolve(&self, prefix: Option<Key>, key: Key) -> Option<Action> {
+        let shortcut = Shortcut { prefix, key };
+
+        self.bindings
+            .iter()
+            .find(|(_, binding)| *binding == shortcut)
+            .map(|(action, _)| *action)
+            .or_else(|| {
+                let is_zoom_in = prefix.is_none()
+                    && ZOOM_IN_KEYS.contains(&key.code)
+                    && (key.modifiers - Modifiers::SHIFT) == Modifiers::COMMAND;
+
+                is_zoom_in.then_some(Action::ZoomIn)
+            })
+    }
+
+    // 查找按键组合对应的脚本名称，prefix 为之前按下的前缀组合。
+    pub fn resolve_script(&self, prefix: Option<Key>, key: Key) -> Option<&str> {
+        let shortcut = Shortcut { prefix, key };
+
+        self.scripts
+            .iter()
+            .find(|(_, binding)| *binding == shortcut)
+            .map(|(script, _)| script.as_str())
+    }
+
+    // 判断按键组合是否为某个快捷键的前缀，按下后需要等待第二个组合。
+    pub fn is_prefix(&self, key: Key) -> bool {
+        self.bindings
+            .iter()
+            .map(|(_, binding)| binding)
+            .chain(self.scripts.iter().map(|(_, binding)| binding))
+            .any(|binding| binding.prefix == Some(key))
+    }
+
+    // 判断按键组合是否需要在获得焦点的编辑器之前处理：带 Ctrl 的前缀、快捷键或脚本快捷键。
+    pub fn intercepts(&self, prefix: Option<Key>, key: Key) -> bool {
+        key.modifiers.control()
+            && ((prefix.is_none() && self.is_prefix(key))
+                || self.resolve(prefix, key).is_some()
+                || self.resolve_script(prefix, key).is_some())
+    }
+
+    // 返回脚本绑定的快捷键。
+    pub fn script_binding(&self, script: &str) -> Option<Shortcut> {
+        self.scripts
+            .iter()
+            .find(|(bound, _)| bound == script)
+            .map(|(_, key)| *key)
+    }
+
+    // 为脚本绑定快捷键。脚本的快捷键优先级低于操作，与操作的快捷键相同时不会被触发。
+    pub fn bind_script(&mut self, script: String, shortcut: Shortcut) {
+        self.scripts
+            .retain(|(bound, binding)| *bound != script && *binding != shortcut);
+        self.scripts.push((script, shortcut));
+    }
+
+    // 返回操作绑定的快捷键。
+    pub fn binding(&self, action: Action) -> Option<Shortcut> {
+        self.bindings
+            .iter()
+            .find(|(bound, _)| *bound == action)
+            .map(|(_, key)| *key)
+    }
+
+    // 为操作绑定新的快捷键，并移除其他操作上相同的绑定。
+    //
+    // 单个组合与其他快捷键的前缀相同时，第二个组合永远无法按下，因此也移除这样的绑定。
+    pub fn bind(&mut self, action: Action, shortcut: impl Into<Shortcut>) {
+        let shortcut = shortcut.into();
+
+        self.bindings.retain(|(bound, binding)| {
+            *bound != action
+                && *binding != shortcut
+                && binding
+                    .prefix
+                    .map_or(true, |prefix| Shortcut::from(prefix) != shortcut)
+                && shortcut
+                    .prefix
+                    .map_or(true, |prefix| Shortcut::from(prefix) != *binding)
+        });
+        self.bindings.push((action, shortcut));
+    }
+
+    // 创建设置界面中的快捷键列表，正在录制的操作会提示用户按下新的按键。
+    pub fn view(&self, recording: Option<Action>) -> Element<'_, Message> {
+        let bindings = Action::ALL.iter().map(|&action| {
+            let key = if recording == Some(action) {
+                String::from(tr("Press a key..."))
+            } else {
+                self.binding(action)
+                    .map_or_else(|| String::from(tr("Unbound")), |key| key.to_string())
+            };
+
+            row![
+                text(action.to_string()).width(200),
+                text(key),
+                horizontal_space(Length::Fill),
+                button(text(tr("Change")))
+                    .on_press(Message::RecordBinding(action))
+                    .style(theme::Button::Secondary),
+            ]
+            .spacing(10)
+            .into()
+        });
+
+        column![
+            row![
+                text(tr("Key Bindings")).size(20),
+                horizontal_space(Length::Fill),
+                pick_list(Preset::ALL, Some(self.preset), Message::PresetSelected),
+                button(text(tr("Reset to Defaults")))
+                    .on_press(Message::ResetKeymap)
+                    .style(theme::Button::Destructive),
+            ]
+            .spacing(10),
+            column(bindings.collect()).spacing(5),
+        ]
+        .spacing(10)
+        .into()
+    }
+}
+
+// 定义一个异步函数来加载快捷键配置，配置文件不存在时使用默认快捷键。
+pub async fn load(path: Option<PathBuf>) -> Result<Keymap, Error> {
+    let Some(path) = path else {
+        return Ok(Keymap::default());
+    };
+
+    match tokio::fs::read_to_string(&path).await {
+        Ok(contents) => Keymap::from_toml(&contents),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(Keymap::default()),
+        Err(error) => Err(Error::FileFailed(FileError::new(
+            Operation::Read,
+            &path,
+            &error,
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_key_combinations() {
+        assert_eq!(
+            Key::parse("Ctrl+Shift+S"),
+            Some(Key::new(KeyCode::S, Modifiers::CTRL | Modifiers::SHIFT))
+        );
+        assert_eq!(
+            Key::parse("alt + f4"),
+            Some(Key::new(KeyCode::F4, Modifiers::ALT))
+        );
+        assert_eq!(Key::parse("Ctrl"), None);
+        assert_eq!(Key::parse("Ctrl+Unknown"), None);
+    }
+
+    #[test]
+    fn parses_prefixed_shortcuts() {
+        let shortcut = Shortcut::parse("Ctrl+X Ctrl+S").unwrap();
+
+        assert_eq!(shortcut.prefix, Some(Key::new(KeyCode::X, Modifiers::CTRL)));
+        assert_eq!(shortcut.key, Key::new(KeyCode::S, Modifiers::CTRL));
+        assert_eq!(shortcut.to_string(), "Ctrl+X Ctrl+S");
+        assert_eq!(Shortcut::parse("Ctrl+X Ctrl+C Ctrl+S"), None);
+    }
+
+    #[test]
+    fn applies_overrides_from_toml() {
+        let keymap = Keymap::from_toml(
+            r#"
+            save = "Ctrl+Alt+S"
+            "script:rename" = "Ctrl+Alt+R"
+            "#,
+        )
+        .unwrap();
+
+        let save = Key::new(KeyCode::S, Modifiers::CTRL | Modifiers::ALT);
+        let rename = Key::new(KeyCode::R, Modifiers::CTRL | Modifiers::ALT);
+
+        assert_eq!(keymap.resolve(None, save), Some(Action::Save));
+        assert_eq!(keymap.binding(Action::Save), Some(Shortcut::from(save)));
+        assert_eq!(keymap.resolve_script(None, rename), Some("rename"));
+    }
+
+    #[test]
+    fn zooms_in_with_the_plus_keys() {
+        let keymap = Keymap::default();
+
+        for key in [
+            Key::new(KeyCode::Equals, Modifiers::COMMAND),
+            Key::new(KeyCode::Plus, Modifiers::COMMAND),
+            Key::new(KeyCode::Plus, Modifiers::COMMAND | Modifiers::SHIFT),
+            Key::new(KeyCode::NumpadAdd, Modifiers::COMMAND),
+        ] {
+            assert_eq!(keymap.resolve(None, key), Some(Action::ZoomIn));
+        }
+
+        assert_eq!(
+            keymap.resolve(None, Key::new(KeyCode::Plus, Modifiers::empty())),
+            None
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_configuration() {
+        assert!(Keymap::from_toml(r#"save = "Ctrl+Nothing""#).is_err());
+        assert!(Keymap::from_toml(r#"no_such_action = "Ctrl+S""#).is_err());
+        assert!(Keymap::from_toml(r#"preset = "vim""#).is_err());
+    }
+
+    #[test]
+    fn round_trips_through_toml() {
+        let keymap = Preset::Emacs.keymap();
+        let parsed = Keymap::from_toml(&keymap.to_toml()).unwrap();
+
+        for action in Action::ALL.iter().copied() {
+            assert_eq!(parsed.binding(action), keymap.binding(action));
+        }
+    }
+
+    #[test]
+    fn binding_a_prefix_removes_conflicting_shortcuts() {
+        let mut keymap = Keymap::default();
+        let prefix = Key::new(KeyCode::X, Modifiers::CTRL);
+
+        keymap.bind(Action::Save, prefix);
+        keymap.bind(
+            Action::SaveAs,
+            Shortcut {
+                prefix: Some(prefix),
+                key: Key::new(KeyCode::W, Modifiers::CTRL),
+            },
+        );
+
+        assert_eq!(keymap.binding(Action::Save), None);
+        assert!(keymap.is_prefix(prefix));
+    }
+}
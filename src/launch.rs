@@ -0,0 +1,308 @@
+use crate::encoding::Encoding;
+use crate::history::{History, Snapshot};
+use crate::line_ending::LineEnding;
+use crate::tab::Tab;
+use crate::{Error, FileError, Operation};
+use ropey::Rope;
+use serde::{Deserialize, Serialize};
+use std::ffi::OsString;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tokio::process::Command;
+
+// 标记由其他窗口启动的编辑器进程的命令行参数。
+const NEW_WINDOW: &str = "--new-window";
+
+// 之后的参数都是文件路径的命令行参数。
+const END_OF_OPTIONS: &str = "--";
+
+// 以便签窗口启动的命令行参数。
+const SCRATCHPAD: &str = "--scratchpad";
+
+// 编辑器写入便签窗口的标准输入，要求它保存后关闭的命令。
+pub const CLOSE_SCRATCHPAD: &str = "close";
+
+// 要求便签窗口关闭后最多等待多久，超时后直接结束进程。
+const SCRATCHPAD_TIMEOUT: Duration = Duration::from_secs(2);
+
+// 启动时把剪贴板的内容作为新文档打开的命令行参数。
+const FROM_CLIPBOARD: &str = "--from-clipboard";
+
+// 接收从其他窗口移过来的标签页的命令行参数，后面跟着保存标签页状态的文件。
+const TRANSFER: &str = "--transfer";
+
+// 定义启动编辑器时的命令行参数。
+#[derive(Debug, Clone, Default)]
+pub struct Flags {
+    pub path: Option<PathBuf>,     // 需要打开的文件路径。
+    pub is_secondary: bool,        // 是否是从其他窗口打开的新窗口。
+    pub is_scratchpad: bool,       // 是否作为便签窗口启动。
+    pub from_clipboard: bool,      // 是否把剪贴板的内容作为新文档打开。
+    pub transfer: Option<PathBuf>, // 从其他窗口移过来的标签页的状态文件。
+}
+
+// 定义移到新窗口的标签页，写入临时文件后由新窗口的进程读取，未保存的修改和撤销历史都随之移动。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Transfer {
+    path: Option<PathBuf>,    // 文件路径，新建的文档为 None。
+    url: Option<String>,      // 从网址打开的文档的来源。
+    scratch: Option<usize>,   // 临时缓冲区的编号。
+    text: String,             // 文档的完整内容，包括折叠的部分。
+    cursor: (usize, usize),   // 光标在完整文本中的行和按字符计的列。
+    is_dirty: bool,           // 是否有未保存的修改。
+    is_read_only: bool,       // 文件是否只读。
+    encoding: String,         // 字符编码的名称。
+    has_bom: bool,            // 保存时是否写入 BOM。
+    line_ending: LineEnding,  // 换行符。
+    disk: Option<u64>,        // 最近一次读取或保存时磁盘上文件内容的指纹。
+    language: Option<String>, // 手动选择的语言。
+    bookmarks: Vec<usize>,    // 添加了书签的行。
+    undo: Vec<Step>,          // 可撤销的快照，最近的在最后。
+    redo: Vec<Step>,          // 可重做的快照，最近的在最后。
+}
+
+// 定义撤销历史中的一个快照。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Step {
+    text: String,           // 文档的完整内容。
+    cursor: (usize, usize), // 光标所在的行和列。
+}
+
+impl Transfer {
+    // 记录标签页的状态。
+    pub fn new(tab: &Tab) -> Self {
+        let (undo, redo) = tab.history.snapshots();
+        let steps = |snapshots: &[Snapshot]| {
+            snapshots
+                .iter()
+                .map(|snapshot| Step {
+                    text: snapshot.text.to_string(),
+                    cursor: snapshot.cursor,
+                })
+                .collect()
+        };
+
+        Self {
+            path: tab.path.clone(),
+            url: tab.url.clone(),
+            scratch: tab.scratch,
+            text: tab.context.full_rope().to_string(),
+            cursor: tab.context.cursor(),
+            is_dirty: tab.is_dirty,
+            is_read_only: tab.is_read_only,
+            encoding: tab.encoding.to_string(),
+            has_bom: tab.has_bom,
+            line_ending: tab.line_ending,
+            disk: tab.disk,
+            language: tab.language.clone(),
+            bookmarks: tab.bookmarks.iter().copied().collect(),
+            undo: steps(undo),
+            redo: steps(redo),
+        }
+    }
+
+    // 使用记录的状态创建标签页。
+    pub fn into_tab(self, id: usize) -> Tab {
+        let snapshots = |steps: Vec<Step>| {
+            steps
+                .into_iter()
+                .map(|step| Snapshot {
+                    text: Rope::from(step.text),
+                    cursor: step.cursor,
+                })
+                .collect()
+        };
+
+        let mut tab = Tab::with_text(id, &self.text);
+        tab.path = self.path;
+        tab.url = self.url;
+        tab.scratch = self.scratch;
+        tab.is_dirty = self.is_dirty;
+        tab.is_read_only = self.is_read_only;
+        tab.encoding =
+            Encoding::for_label(&self.encoding).map_or(Encoding::UTF_8, |(encoding, _)| encoding);
+        tab.has_bom = self.has_bom;
+        tab.line_ending = self.line_ending;
+        tab.disk = self.disk;
+        tab.language = self.language;
+        tab.bookmarks = self.bookmarks.into_iter().collect();
+        tab.history = History::with_snapshots(snapshots(self.undo), snapshots(self.redo));
+        tab.rules = crate::indent::Rules::for_extension(&tab.extension());
+        tab.move_cursor_to(self.cursor.0, self.cursor.1);
+        tab
+    }
+}
+
+// 解析命令行参数，不包括程序名称。第一个不是选项的参数为需要打开的文件路径。
+//
+// “--” 之后的参数都当作路径，以便打开以 “--” 开头的文件；其他不认识的选项被忽略。
+pub fn parse(mut args: impl Iterator<Item = OsString>) -> Flags {
+    let mut flags = Flags::default();
+    let mut is_path = false;
+
+    while let Some(arg) = args.next() {
+        if is_path {
+            if flags.path.is_none() {
+                flags.path = Some(PathBuf::from(arg));
+            }
+        } else if arg == END_OF_OPTIONS {
+            is_path = true;
+        } else if arg == NEW_WINDOW {
+            flags.is_secondary = true;
+        } else if arg == SCRATCHPAD {
+            flags.is_scratchpad = true;
+        } else if arg == FROM_CLIPBOARD {
+            flags.from_clipboard = true;
+        } else if arg == TRANSFER {
+            flags.transfer = args.next().map(PathBuf::from);
+        } else if arg.to_string_lossy().starts_with("--") {
+            // 不认识的选项不当作文件路径。
+        } else if flags.path.is_none() {
+            flags.path = Some(PathBuf::from(arg));
+        }
+    }
+
+    flags
+}
+
+// 定义一个异步函数，启动一个新的编辑器进程作为空白的新窗口。
+//
+// 这个版本的 iced 不支持多窗口，每个窗口是一个独立的进程。只等待进程启动，不等待它结束。
+pub async fn open_window() -> Result<(), Error> {
+    let program = program()?;
+
+    Command::new(&program)
+        .arg(NEW_WINDOW)
+        .spawn()
+        .map(|_| ())
+        .map_err(|error| Error::FileFailed(FileError::new(Operation::Run, &program, &error)))
+}
+
+// 定义一个异步函数，启动一个新的编辑器进程并把标签页移到其中。
+//
+// 标签页的状态先写入数据目录中的临时文件，新窗口读取后删除它。
+pub async fn move_tab(transfer: Transfer) -> Result<(), Error> {
+    let folder = dirs::data_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("iced-editor");
+
+    tokio::fs::create_dir_all(&folder).await.map_err(|error| {
+        Error::FileFailed(FileError::new(Operation::CreateFolder, &folder, &error))
+    })?;
+
+    let millis = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    let file = folder.join(format!("transfer-{}-{millis}.json", std::process::id()));
+    let contents = serde_json::to_vec(&transfer).unwrap_or_default();
+
+    tokio::fs::write(&file, contents)
+        .await
+        .map_err(|error| Error::FileFailed(FileError::new(Operation::Write, &file, &error)))?;
+
+    let program = program()?;
+    let spawned = Command::new(&program)
+        .arg(NEW_WINDOW)
+        .arg(TRANSFER)
+        .arg(&file)
+        .spawn();
+
+    if let Err(error) = spawned {
+        let _ = tokio::fs::remove_file(&file).await;
+        return Err(Error::FileFailed(FileError::new(
+            Operation::Run,
+            &program,
+            &error,
+        )));
+    }
+
+    Ok(())
+}
+
+// 定义一个异步函数，读取从其他窗口移过来的标签页，读取后删除临时文件。
+pub async fn receive(file: PathBuf) -> Result<Transfer, Error> {
+    let contents = tokio::fs::read(&file)
+        .await
+        .map_err(|error| Error::FileFailed(FileError::new(Operation::Read, &file, &error)))?;
+
+    let _ = tokio::fs::remove_file(&file).await;
+
+    serde_json::from_slice(&contents).map_err(|error| {
+        let error = std::io::Error::new(std::io::ErrorKind::InvalidData, error);
+        Error::FileFailed(FileError::new(Operation::Read, &file, &error))
+    })
+}
+
+// 启动一个便签窗口的进程，返回进程以便之后关闭它。
+//
+// 进程的标准输入连接到编辑器，用来要求它关闭。
+pub fn open_scratchpad() -> Result<std::process::Child, Error> {
+    let program = program()?;
+
+    std::process::Command::new(&program)
+        .arg(SCRATCHPAD)
+        .stdin(std::process::Stdio::piped())
+        .spawn()
+        .map_err(|error| Error::FileFailed(FileError::new(Operation::Run, &program, &error)))
+}
+
+// 定义一个异步函数，关闭便签窗口的进程。
+//
+// 先通过标准输入要求便签窗口保存还没有写入的内容再退出，超时仍然没有退出时才结束进程。
+pub async fn close_scratchpad(mut scratchpad: std::process::Child) {
+    let _ = tokio::task::spawn_blocking(move || {
+        if let Some(mut stdin) = scratchpad.stdin.take() {
+            let _ = writeln!(stdin, "{CLOSE_SCRATCHPAD}");
+        }
+
+        let start = Instant::now();
+
+        while let Ok(None) = scratchpad.try_wait() {
+            if start.elapsed() >= SCRATCHPAD_TIMEOUT {
+                let _ = scratchpad.kill();
+                let _ = scratchpad.wait();
+                return;
+            }
+
+            std::thread::sleep(Duration::from_millis(50));
+        }
+    })
+    .await;
+}
+
+// 返回当前运行的编辑器程序的路径，用于启动新的编辑器进程。
+fn program() -> Result<PathBuf, Error> {
+    std::env::current_exe().map_err(|error| {
+        Error::FileFailed(FileError::new(
+            Operation::Run,
+            Path::new(env!("CARGO_PKG_NAME")),
+            &error,
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flags(args: &[&str]) -> Flags {
+        parse(args.iter().map(OsString::from))
+    }
+
+    #[test]
+    fn ignores_unknown_options() {
+        let flags = flags(&["--unknown", "notes.md"]);
+
+        assert_eq!(flags.path, Some(PathBuf::from("notes.md")));
+    }
+
+    #[test]
+    fn treats_arguments_after_separator_as_paths() {
+        let flags = flags(&["--", "--scratchpad"]);
+
+        assert!(!flags.is_scratchpad);
+        assert_eq!(flags.path, Some(PathBuf::from("--scratchpad")));
+    }
+}
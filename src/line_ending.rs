@@ -0,0 +1,95 @@
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::fmt;
+
+// 定义文件使用的换行符。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LineEnding {
+    Lf,   // Unix 风格的 \n。
+    CrLf, // Windows 风格的 \r\n。
+    Cr,   // 旧版 Mac 风格的 \r。
+}
+
+impl LineEnding {
+    // 所有可选的换行符，用于状态栏中的选择列表。
+    pub const ALL: &[Self] = &[Self::Lf, Self::CrLf, Self::Cr];
+
+    // 根据文件中出现次数最多的换行符检测文件的换行风格，没有换行时默认为 LF。
+    pub fn detect(text: &str) -> Self {
+        let crlf = text.matches("\r\n").count();
+        let cr = text.matches('\r').count() - crlf;
+        let lf = text.matches('\n').count() - crlf;
+
+        if crlf > lf && crlf >= cr {
+            Self::CrLf
+        } else if cr > lf && cr > crlf {
+            Self::Cr
+        } else {
+            Self::Lf
+        }
+    }
+
+    // 将任意换行符统一转换为编辑器内部使用的 \n。
+    pub fn normalize(text: &str) -> String {
+        text.replace("\r\n", "\n").replace('\r', "\n")
+    }
+
+    // 返回换行符对应的字符串。
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Lf => "\n",
+            Self::CrLf => "\r\n",
+            Self::Cr => "\r",
+        }
+    }
+
+    // 将编辑器内部使用 \n 的文本转换为当前的换行风格，使用 \n 时不复制文本。
+    pub fn apply(self, text: &str) -> Cow<'_, str> {
+        match self {
+            Self::Lf => Cow::Borrowed(text),
+            _ => Cow::Owned(text.replace('\n', self.as_str())),
+        }
+    }
+}
+
+impl fmt::Display for LineEnding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Lf => "LF",
+            Self::CrLf => "CRLF",
+            Self::Cr => "CR",
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_the_most_common_line_ending() {
+        assert_eq!(LineEnding::detect("a\nb\nc"), LineEnding::Lf);
+        assert_eq!(LineEnding::detect("a\r\nb\r\nc\n"), LineEnding::CrLf);
+        assert_eq!(LineEnding::detect("a\rb\rc"), LineEnding::Cr);
+    }
+
+    #[test]
+    fn defaults_to_lf_without_line_breaks() {
+        assert_eq!(LineEnding::detect(""), LineEnding::Lf);
+        assert_eq!(LineEnding::detect("single line"), LineEnding::Lf);
+    }
+
+    #[test]
+    fn normalizes_mixed_line_endings() {
+        assert_eq!(LineEnding::normalize("a\r\nb\rc\nd"), "a\nb\nc\nd");
+    }
+
+    #[test]
+    fn applies_line_ending_after_normalizing() {
+        let text = LineEnding::normalize("a\r\nb\r\n");
+
+        assert_eq!(LineEnding::CrLf.apply(&text), "a\r\nb\r\n");
+        assert_eq!(LineEnding::Cr.apply(&text), "a\rb\r");
+        assert!(matches!(LineEnding::Lf.apply(&text), Cow::Borrowed(_)));
+    }
+}
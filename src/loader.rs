@@ -0,0 +1,133 @@
+use crate::encoding::{Decoded, Encoding};
+use crate::hex;
+use crate::{Error, FileError, Message, Operation};
+use iced::futures::channel::mpsc;
+use iced::futures::SinkExt;
+use iced::{subscription, Subscription};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::io::AsyncReadExt;
+
+// 超过这个大小的文件以只读方式打开，避免每次编辑都为撤销历史复制整个文档。
+const READ_ONLY_THRESHOLD: u64 = 64 * 1024 * 1024;
+
+// 每次读取的字节数。
+const CHUNK_SIZE: usize = 1024 * 1024;
+
+// 定义正在分块加载的文件。
+//
+// 加载在订阅中进行，从正在加载的列表中移除后订阅随之停止，用于取消缓慢的加载。
+#[derive(Debug, Clone)]
+pub struct Loading {
+    pub id: usize,          // 加载任务的唯一标识。
+    pub path: PathBuf,      // 文件的路径。
+    pub read: u64,          // 已经读取的字节数。
+    pub total: Option<u64>, // 文件的总字节数，打开文件之前未知。
+}
+
+impl Loading {
+    // 创建尚未开始的加载任务。
+    pub fn new(id: usize, path: PathBuf) -> Self {
+        Self {
+            id,
+            path,
+            read: 0,
+            total: None,
+        }
+    }
+
+    // 返回加载的进度，范围为 0 到 1，尚未打开文件时返回 None。
+    pub fn progress(&self) -> Option<f32> {
+        match self.total? {
+            0 => Some(1.0),
+            total => Some(self.read as f32 / total as f32),
+        }
+    }
+}
+
+// 定义加载过程中发送的进度。
+#[derive(Debug, Clone)]
+pub enum Progress {
+    Started(u64),                                // 打开文件后得到的总字节数。
+    Advanced(u64),                               // 已经读取的字节数。
+    Finished(Result<(PathBuf, Decoded), Error>), // 加载完成，附带解码后的内容。
+    Binary(PathBuf, Arc<Vec<u8>>, bool), // 加载完成但文件是二进制的，附带原始字节及文件是否只读。
+}
+
+// 创建一个订阅来分块读取文件，每读取一块发送一次进度，读取完成后发送解码后的内容。
+//
+// 二进制文件不解码为文本，以原始字节发送，由编辑器用十六进制编辑器打开。
+pub fn load(loading: &Loading) -> Subscription<Message> {
+    let id = loading.id;
+    let path = loading.path.clone();
+
+    subscription::channel(id, 100, move |mut output| async move {
+        let progress = match read(id, &path, &mut output).await {
+            Ok(bytes) if hex::is_binary(&bytes) => {
+                let is_read_only = crate::is_read_only(&path).await;
+                Progress::Binary(path.clone(), Arc::new(bytes), is_read_only)
+            }
+            Ok(bytes) => {
+                let mut decoded = Encoding::detect(&bytes);
+                decoded.is_read_only = crate::is_read_only(&path).await
+                    || decoded.text.len() as u64 > READ_ONLY_THRESHOLD;
+
+                Progress::Finished(Ok((path.clone(), decoded)))
+            }
+            Err(error) => Progress::Finished(Err(error)),
+        };
+
+        let _ = output.send(Message::LoadProgress(id, progress)).await;
+
+        // 加载完成后订阅会被移除，在此之前保持空闲。
+        loop {
+            std::future::pending::<()>().await;
+        }
+    })
+}
+
+// 分块读取文件内容。
+async fn read(
+    id: usize,
+    path: &Path,
+    output: &mut mpsc::Sender<Message>,
+) -> Result<Vec<u8>, Error> {
+    let mut file = tokio::fs::File::open(path)
+        .await
+        .map_err(|error| Error::FileFailed(FileError::new(Operation::Open, path, &error)))?;
+
+    let total = file
+        .metadata()
+        .await
+        .map_err(|error| Error::FileFailed(FileError::new(Operation::Open, path, &error)))?
+        .len();
+
+    let _ = output
+        .send(Message::LoadProgress(id, Progress::Started(total)))
+        .await;
+
+    let mut bytes = Vec::with_capacity(total as usize);
+    let mut chunk = vec![0; CHUNK_SIZE];
+
+    loop {
+        let read = file
+            .read(&mut chunk)
+            .await
+            .map_err(|error| Error::FileFailed(FileError::new(Operation::Open, path, &error)))?;
+
+        if read == 0 {
+            break;
+        }
+
+        bytes.extend_from_slice(&chunk[..read]);
+
+        let _ = output
+            .send(Message::LoadProgress(
+                id,
+                Progress::Advanced(bytes.len() as u64),
+            ))
+            .await;
+    }
+
+    Ok(bytes)
+}
@@ -0,0 +1,233 @@
+use crate::diff::{self, Diff};
+use crate::i18n::{tr, trf};
+use crate::{Error, FileError, Message, Operation};
+use iced::widget::{button, column, container, horizontal_space, row, scrollable, text};
+use iced::{theme, Element, Length};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// 每个文件最多保留的版本数量，超过时删除最早的版本。
+const MAX_VERSIONS: usize = 50;
+
+// 版本列表的宽度。
+const LIST_WIDTH: f32 = 200.0;
+
+// 定义文件保存过的一个版本。
+#[derive(Debug, Clone)]
+pub struct Version {
+    time: SystemTime, // 保存的时间。
+    file: PathBuf,    // 保存这个版本内容的快照文件。
+}
+
+impl Version {
+    // 返回保存时间距离现在多久，例如 "5 minutes ago"。
+    fn age(&self) -> String {
+        let seconds = self.time.elapsed().unwrap_or_default().as_secs();
+
+        let (count, one, many) = match seconds {
+            0..=59 => return String::from(tr("Just now")),
+            60..=3599 => (seconds / 60, "1 minute ago", "{count} minutes ago"),
+            3600..=86399 => (seconds / 3600, "1 hour ago", "{count} hours ago"),
+            _ => (seconds / 86400, "1 day ago", "{count} days ago"),
+        };
+
+        if count == 1 {
+            String::from(tr(one))
+        } else {
+            trf(many, &[("count", &count)])
+        }
+    }
+}
+
+// 定义本地历史面板的状态。
+#[derive(Debug)]
+pub struct LocalHistory {
+    pub tab: usize,              // 查看历史的标签页标识。
+    pub versions: Vec<Version>,  // 文件保存过的版本，最近的在前。
+    pub selected: Option<usize>, // 选中的版本。
+    pub text: Option<String>,    // 选中的版本的内容，尚未读取完成时为 None。
+    pub diff: Option<Diff>,      // 选中的版本与当前内容之间的差异。
+}
+
+impl LocalHistory {
+    // 创建标签页的本地历史面板，版本列表需要在后台读取。
+    pub fn new(tab: usize) -> Self {
+        Self {
+            tab,
+            versions: Vec::new(),
+            selected: None,
+            text: None,
+            diff: None,
+        }
+    }
+
+    // 返回选中的版本。
+    pub fn selection(&self) -> Option<&Version> {
+        self.versions.get(self.selected?)
+    }
+
+    // 创建面板的视图：左侧为版本列表，右侧为选中的版本到当前内容的差异。
+    pub fn view(&self, title: String, layout: diff::Layout) -> Element<'_, Message> {
+        let header = row![
+            text(trf("Local History of {title}", &[("title", &title)])).size(18),
+            horizontal_space(Length::Fill),
+            button(text(tr("Restore")))
+                .on_press_maybe(self.text.is_some().then_some(Message::RestoreLocalVersion))
+                .style(theme::Button::Primary),
+            button(text("×"))
+                .on_press(Message::ToggleLocalHistory)
+                .style(theme::Button::Text)
+        ]
+        .spacing(10);
+
+        let versions: Element<_> = if self.versions.is_empty() {
+            text(tr("No saved versions yet")).size(14).into()
+        } else {
+            column(
+                self.versions
+                    .iter()
+                    .enumerate()
+                    .map(|(index, version)| {
+                        let style = if Some(index) == self.selected {
+                            theme::Button::Primary
+                        } else {
+                            theme::Button::Text
+                        };
+
+                        button(text(version.age()).size(14))
+                            .on_press(Message::LocalVersionSelected(index))
+                            .width(Length::Fill)
+                            .padding([2, 5])
+                            .style(style)
+                            .into()
+                    })
+                    .collect(),
+            )
+            .into()
+        };
+
+        let diff: Element<_> = match &self.diff {
+            Some(diff) => diff.view(layout),
+            None => container(text(tr(
+                "Select a version to compare it with the current content",
+            )))
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .center_x()
+            .center_y()
+            .into(),
+        };
+
+        column![
+            header,
+            row![scrollable(versions).width(LIST_WIDTH), diff].spacing(10)
+        ]
+        .spacing(10)
+        .into()
+    }
+}
+
+// 返回保存文件历史版本的目录，每个文件使用以其路径的哈希值命名的子目录。
+fn folder(path: &Path) -> Option<PathBuf> {
+    let mut hasher = DefaultHasher::new();
+    path.hash(&mut hasher);
+
+    dirs::data_dir().map(|dir| {
+        dir.join("iced-editor")
+            .join("history")
+            .join(format!("{:016x}", hasher.finish()))
+    })
+}
+
+// 定义一个异步函数来列出文件保存过的版本，最近的在前。
+pub async fn list(path: PathBuf) -> Result<Vec<Version>, Error> {
+    let Some(folder) = folder(&path) else {
+        return Ok(Vec::new());
+    };
+
+    let mut entries = match tokio::fs::read_dir(&folder).await {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => {
+            return Err(Error::FileFailed(FileError::new(
+                Operation::ListFolder,
+                &folder,
+                &error,
+            )))
+        }
+    };
+
+    let mut versions = Vec::new();
+
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let file = entry.path();
+
+        // 快照文件以保存时的毫秒时间戳命名。
+        let millis = file
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .and_then(|stem| stem.parse().ok());
+
+        if let Some(millis) = millis {
+            versions.push(Version {
+                time: UNIX_EPOCH + Duration::from_millis(millis),
+                file,
+            });
+        }
+    }
+
+    versions.sort_by(|a, b| b.time.cmp(&a.time));
+
+    Ok(versions)
+}
+
+// 定义一个异步函数来读取一个版本的内容。
+pub async fn read(version: Version) -> Result<String, Error> {
+    tokio::fs::read_to_string(&version.file)
+        .await
+        .map_err(|error| Error::FileFailed(FileError::new(Operation::Read, &version.file, &error)))
+}
+
+// 定义一个异步函数，把刚刚保存的内容记录为文件的一个新版本。
+//
+// 内容与最近的版本相同时不重复记录，版本过多时删除最早的版本。
+pub async fn record(path: PathBuf, contents: String) -> Result<(), Error> {
+    let Some(folder) = folder(&path) else {
+        return Ok(());
+    };
+
+    let versions = list(path).await?;
+
+    if let Some(latest) = versions.first() {
+        if tokio::fs::read_to_string(&latest.file)
+            .await
+            .is_ok_and(|latest| latest == contents)
+        {
+            return Ok(());
+        }
+    }
+
+    tokio::fs::create_dir_all(&folder).await.map_err(|error| {
+        Error::FileFailed(FileError::new(Operation::CreateFolder, &folder, &error))
+    })?;
+
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+
+    let file = folder.join(format!("{millis}.txt"));
+
+    tokio::fs::write(&file, contents)
+        .await
+        .map_err(|error| Error::FileFailed(FileError::new(Operation::Write, &file, &error)))?;
+
+    // 加上新记录的版本后，只保留最近的 MAX_VERSIONS 个。
+    for version in versions.iter().skip(MAX_VERSIONS - 1) {
+        let _ = tokio::fs::remove_file(&version.file).await;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,774 @@
+use crate::diagnostics::{Diagnostic, Severity};
+use crate::Message;
+use iced::futures::channel::mpsc;
+use iced::futures::{stream, SinkExt, StreamExt};
+use iced::{subscription, Subscription};
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{ChildStdin, ChildStdout, Command};
+
+// 补全列表中最多保留的候选项数量。
+const MAX_COMPLETIONS: usize = 200;
+
+// 定义一个语言服务器。
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct Server {
+    pub name: &'static str,              // 服务器的可执行文件名。
+    args: &'static [&'static str],       // 启动参数。
+    extensions: &'static [&'static str], // 支持的文件扩展名。
+    markers: &'static [&'static str],    // 用于确定项目根目录的文件。
+}
+
+// 支持的语言服务器，需要用户自行安装并放在 PATH 中。
+const SERVERS: &[Server] = &[
+    Server {
+        name: "rust-analyzer",
+        args: &[],
+        extensions: &["rs"],
+        markers: &["Cargo.toml"],
+    },
+    Server {
+        name: "pylsp",
+        args: &[],
+        extensions: &["py"],
+        markers: &["pyproject.toml", "setup.py"],
+    },
+    Server {
+        name: "typescript-language-server",
+        args: &["--stdio"],
+        extensions: &["js", "jsx", "ts", "tsx"],
+        markers: &["package.json", "tsconfig.json"],
+    },
+    Server {
+        name: "gopls",
+        args: &[],
+        extensions: &["go"],
+        markers: &["go.mod"],
+    },
+    Server {
+        name: "clangd",
+        args: &[],
+        extensions: &["c", "h", "cpp", "hpp", "cc"],
+        markers: &["compile_commands.json", "CMakeLists.txt"],
+    },
+];
+
+// 定义一个工作区：在项目根目录中运行的一个语言服务器。
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Workspace {
+    pub server: &'static Server, // 使用的语言服务器。
+    pub root: PathBuf,           // 项目的根目录。
+}
+
+impl Workspace {
+    // 返回文件所属的工作区，没有对应的语言服务器时返回 None。
+    //
+    // 从文件所在的目录向上查找项目文件，找不到时使用文件所在的目录。
+    pub fn of(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?;
+        let server = SERVERS
+            .iter()
+            .find(|server| server.extensions.contains(&extension))?;
+
+        let directory = path.parent()?;
+        let root = directory
+            .ancestors()
+            .find(|ancestor| {
+                server
+                    .markers
+                    .iter()
+                    .any(|marker| ancestor.join(marker).is_file())
+            })
+            .unwrap_or(directory);
+
+        Some(Self {
+            server,
+            root: root.to_path_buf(),
+        })
+    }
+}
+
+// 定义发送给语言服务器的请求。
+#[derive(Debug, Clone)]
+enum Request {
+    Open(PathBuf, String),         // 打开文档，附带文档的内容。
+    Change(PathBuf, i32, String),  // 文档的内容发生变化，附带新的版本号和内容。
+    Save(PathBuf),                 // 文档已保存。
+    Close(PathBuf),                // 文档已关闭。
+    Completion(PathBuf, Position), // 请求光标处的补全。
+    Hover(PathBuf, Position),      // 请求光标处的悬停信息。
+}
+
+// 定义文档中的位置，列使用 UTF-16 编码单元计数。
+#[derive(Debug, Clone, Copy)]
+pub struct Position {
+    pub line: usize,      // 所在的行，从 0 开始。
+    pub character: usize, // 在行中的位置。
+}
+
+impl Position {
+    // 根据行的文本和光标的字节位置创建位置。
+    pub fn new(line: usize, text: &str, column: usize) -> Self {
+        Self {
+            line,
+            character: text.get(..column).unwrap_or(text).encode_utf16().count(),
+        }
+    }
+}
+
+// 定义与语言服务器的连接，可以在应用程序中克隆并发送请求。
+#[derive(Debug, Clone)]
+pub struct Connection(mpsc::Sender<Request>);
+
+impl Connection {
+    // 发送请求，服务器已经退出时忽略。
+    fn send(&mut self, request: Request) {
+        let _ = self.0.try_send(request);
+    }
+}
+
+// 定义语言服务器发送给应用程序的事件。
+#[derive(Debug, Clone)]
+pub enum Event {
+    Connected(Workspace, Connection), // 服务器已启动，可以发送请求。
+    Diagnostics(PathBuf, Vec<Diagnostic>), // 文件的诊断，列使用 UTF-16 编码单元计数。
+    Completions(Vec<String>),         // 补全的候选项。
+    Hover(String),                    // 悬停信息。
+    Disconnected(Workspace, Option<String>), // 服务器已退出；没有安装服务器时不附带原因。
+}
+
+// 定义一个打开的文档。
+#[derive(Debug)]
+struct Document {
+    workspace: Workspace, // 文档所属的工作区。
+    version: i32,         // 最近一次发送给服务器的版本号。
+    revision: usize,      // 最近一次发送给服务器时标签页内容的版本号。
+    is_open: bool,        // 是否已经通知服务器打开了文档。
+}
+
+// 定义所有语言服务器的状态。
+#[derive(Debug, Default)]
+pub struct State {
+    connections: HashMap<Workspace, Connection>, // 已经启动的服务器。
+    failed: HashSet<Workspace>,                  // 已经退出或无法启动的服务器，不再重新启动。
+    documents: HashMap<PathBuf, Document>,       // 打开的文档。
+    diagnostics: HashMap<PathBuf, Vec<Diagnostic>>, // 每个文件最近一次收到的诊断。
+}
+
+impl State {
+    // 通知服务器打开了文档或者文档的内容已经改变，服务器还没有启动时在连接后再通知。
+    pub fn open(&mut self, path: &Path, text: impl FnOnce() -> String, revision: usize) {
+        if let Some(document) = self.documents.get_mut(path) {
+            if document.is_open {
+                if document.revision != revision {
+                    document.version += 1;
+                    document.revision = revision;
+
+                    let request = Request::Change(path.to_path_buf(), document.version, text());
+                    send(&mut self.connections, &document.workspace, request);
+                }
+                return;
+            }
+        }
+
+        let Some(workspace) = Workspace::of(path) else {
+            return;
+        };
+
+        let connection = self.connections.get_mut(&workspace);
+        let is_open = connection.is_some();
+
+        if let Some(connection) = connection {
+            connection.send(Request::Open(path.to_path_buf(), text()));
+        }
+
+        let _ = self.documents.insert(
+            path.to_path_buf(),
+            Document {
+                workspace,
+                version: 1,
+                revision,
+                is_open,
+            },
+        );
+    }
+
+    // 判断文档是否有尚未发送给服务器的修改。
+    pub fn is_stale(&self, path: &Path, revision: usize) -> bool {
+        self.documents
+            .get(path)
+            .is_some_and(|document| document.is_open && document.revision != revision)
+    }
+
+    // 通知服务器文档已保存。
+    pub fn save(&mut self, path: &Path) {
+        if let Some(document) = self.documents.get(path).filter(|document| document.is_open) {
+            send(
+                &mut self.connections,
+                &document.workspace,
+                Request::Save(path.to_path_buf()),
+            );
+        }
+    }
+
+    // 通知服务器文档已关闭，并清除它的诊断。
+    pub fn close(&mut self, path: &Path) {
+        if let Some(document) = self.documents.remove(path) {
+            if document.is_open {
+                send(
+                    &mut self.connections,
+                    &document.workspace,
+                    Request::Close(path.to_path_buf()),
+                );
+            }
+        }
+
+        let _ = self.diagnostics.remove(path);
+    }
+
+    // 请求光标处的补全，文档没有对应的服务器时返回 false。
+    pub fn request_completion(&mut self, path: &Path, position: Position) -> bool {
+        self.request(path, Request::Completion(path.to_path_buf(), position))
+    }
+
+    // 请求光标处的悬停信息，文档没有对应的服务器时返回 false。
+    pub fn request_hover(&mut self, path: &Path, position: Position) -> bool {
+        self.request(path, Request::Hover(path.to_path_buf(), position))
+    }
+
+    fn request(&mut self, path: &Path, request: Request) -> bool {
+        match self.documents.get(path).filter(|document| document.is_open) {
+            Some(document) => send(&mut self.connections, &document.workspace, request),
+            None => false,
+        }
+    }
+
+    // 记录已经启动的服务器，返回需要通知服务器打开的文档。
+    pub fn connected(&mut self, workspace: Workspace, connection: Connection) -> Vec<PathBuf> {
+        let _ = self.connections.insert(workspace.clone(), connection);
+
+        self.documents
+            .iter()
+            .filter(|(_, document)| document.workspace == workspace)
+            .map(|(path, _)| path.clone())
+            .collect()
+    }
+
+    // 记录已经退出的服务器，不再重新启动，并清除相关文档的诊断。
+    pub fn disconnected(&mut self, workspace: Workspace) {
+        let _ = self.connections.remove(&workspace);
+
+        for (path, document) in &mut self.documents {
+            if document.workspace == workspace {
+                document.is_open = false;
+                let _ = self.diagnostics.remove(path);
+            }
+        }
+
+        let _ = self.failed.insert(workspace);
+    }
+
+    // 记录文件的诊断。
+    pub fn set_diagnostics(&mut self, path: PathBuf, diagnostics: Vec<Diagnostic>) {
+        let _ = self.diagnostics.insert(path, diagnostics);
+    }
+
+    // 返回文件的诊断。
+    pub fn diagnostics(&self, path: &Path) -> &[Diagnostic] {
+        self.diagnostics.get(path).map_or(&[], Vec::as_slice)
+    }
+
+    // 为打开的文档所属的每个工作区启动一个语言服务器。
+    pub fn subscription(&self) -> Subscription<Message> {
+        let workspaces: HashSet<_> = self
+            .documents
+            .values()
+            .map(|document| &document.workspace)
+            .filter(|workspace| !self.failed.contains(*workspace))
+            .collect();
+
+        Subscription::batch(workspaces.into_iter().cloned().map(connect))
+    }
+}
+
+// 通过工作区的连接发送请求，服务器还没有启动时返回 false。
+fn send(
+    connections: &mut HashMap<Workspace, Connection>,
+    workspace: &Workspace,
+    request: Request,
+) -> bool {
+    match connections.get_mut(workspace) {
+        Some(connection) => {
+            connection.send(request);
+            true
+        }
+        None => false,
+    }
+}
+
+// 将 UTF-16 编码单元计数的列转换为字符位置。
+pub fn char_column(text: &str, character: usize) -> usize {
+    let mut units = 0;
+
+    for (index, c) in text.chars().enumerate() {
+        if units >= character {
+            return index;
+        }
+
+        units += c.len_utf16();
+    }
+
+    text.chars().count()
+}
+
+// 创建一个订阅，在工作区中启动语言服务器，并将服务器的消息转换为应用程序的事件。
+fn connect(workspace: Workspace) -> Subscription<Message> {
+    subscription::channel(workspace.clone(), 100, move |mut output| async move {
+        let name = workspace.server.name;
+
+        let reason = match run(&workspace, &mut output).await {
+            Ok(()) => Some(format!("{name} exited")),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => None,
+            Err(error) => Some(format!("{name}: {error}")),
+        };
+
+        let _ = output
+            .send(Message::Lsp(Event::Disconnected(workspace, reason)))
+            .await;
+
+        // 服务器退出后订阅会被移除，在此之前保持空闲。
+        loop {
+            std::future::pending::<()>().await;
+        }
+    })
+}
+
+// 定义会话中收到的输入。
+enum Input {
+    Client(Request), // 应用程序发送的请求。
+    Server(Value),   // 服务器发送的消息。
+    Exited,          // 服务器关闭了输出。
+}
+
+// 定义等待服务器响应的请求。
+enum Pending {
+    Completion,
+    Hover,
+}
+
+// 启动语言服务器并处理整个会话，服务器退出时返回。
+async fn run(workspace: &Workspace, output: &mut mpsc::Sender<Message>) -> io::Result<()> {
+    let mut child = Command::new(workspace.server.name)
+        .args(workspace.server.args)
+        .current_dir(&workspace.root)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()?;
+
+    let (Some(mut stdin), Some(stdout)) = (child.stdin.take(), child.stdout.take()) else {
+        return Err(io::ErrorKind::BrokenPipe.into());
+    };
+
+    // 在单独的任务中读取服务器的消息，避免读取到一半时被其他输入打断。
+    let (messages, incoming) = mpsc::channel(100);
+    let _ = tokio::spawn(read_messages(stdout, messages));
+
+    let (sender, requests) = mpsc::channel(100);
+    let _ = output
+        .send(Message::Lsp(Event::Connected(
+            workspace.clone(),
+            Connection(sender),
+        )))
+        .await;
+
+    let root = uri(&workspace.root);
+    let initialize = json!({
+        "processId": std::process::id(),
+        "rootUri": root,
+        "workspaceFolders": [{ "uri": root, "name": workspace.root.display().to_string() }],
+        "capabilities": {
+            "textDocument": {
+                "synchronization": { "didSave": true },
+                "publishDiagnostics": {},
+                "completion": { "completionItem": { "snippetSupport": false } },
+                "hover": { "contentFormat": ["plaintext", "markdown"] }
+            }
+        }
+    });
+    write(&mut stdin, &request(0, "initialize", initialize)).await?;
+
+    let mut inputs = stream::select(
+        incoming
+            .map(Input::Server)
+            .chain(stream::once(async { Input::Exited })),
+        requests.map(Input::Client),
+    );
+
+    // 收到 initialize 的响应之前，应用程序的请求先排队。
+    let mut queue = Some(Vec::new());
+    let mut pending = HashMap::new();
+    let mut next_id = 1;
+
+    while let Some(input) = inputs.next().await {
+        match input {
+            Input::Client(request) => match queue.as_mut() {
+                Some(queue) => queue.push(request),
+                None => {
+                    let message = encode(request, &mut next_id, &mut pending);
+                    write(&mut stdin, &message).await?;
+                }
+            },
+            Input::Server(message) => {
+                let method = message.get("method").and_then(Value::as_str);
+                let id = message.get("id").cloned();
+
+                match (method, id) {
+                    // 服务器发来的请求：不支持的请求一律返回空结果。
+                    (Some(method), Some(id)) => {
+                        let result = match method {
+                            "workspace/configuration" => {
+                                let count =
+                                    message["params"]["items"].as_array().map_or(0, Vec::len);
+                                Value::Array(vec![Value::Null; count])
+                            }
+                            _ => Value::Null,
+                        };
+
+                        write(
+                            &mut stdin,
+                            &json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+                        )
+                        .await?;
+                    }
+                    (Some("textDocument/publishDiagnostics"), None) => {
+                        if let Some((path, diagnostics)) = parse_diagnostics(&message["params"]) {
+                            let event = Event::Diagnostics(path, diagnostics);
+                            let _ = output.send(Message::Lsp(event)).await;
+                        }
+                    }
+                    (Some(_), None) => {}
+                    (None, Some(id)) if id == 0 => {
+                        write(&mut stdin, &notification("initialized", json!({}))).await?;
+
+                        for request in queue.take().unwrap_or_default() {
+                            let message = encode(request, &mut next_id, &mut pending);
+                            write(&mut stdin, &message).await?;
+                        }
+                    }
+                    (None, Some(id)) => {
+                        let result = &message["result"];
+
+                        let event = match id.as_i64().and_then(|id| pending.remove(&id)) {
+                            Some(Pending::Completion) => {
+                                Some(Event::Completions(parse_completions(result)))
+                            }
+                            Some(Pending::Hover) => parse_hover(result).map(Event::Hover),
+                            None => None,
+                        };
+
+                        if let Some(event) = event {
+                            let _ = output.send(Message::Lsp(event)).await;
+                        }
+                    }
+                    (None, None) => {}
+                }
+            }
+            Input::Exited => break,
+        }
+    }
+
+    Ok(())
+}
+
+// 将应用程序的请求编码为协议中的消息，需要响应的请求会记录在 pending 中。
+fn encode(outgoing: Request, next_id: &mut i64, pending: &mut HashMap<i64, Pending>) -> Value {
+    let document = |path: &Path| json!({ "uri": uri(path) });
+
+    let mut ask = |kind: Pending, method: &str, path: &Path, position: Position| {
+        let id = *next_id;
+        *next_id += 1;
+        let _ = pending.insert(id, kind);
+
+        request(
+            id,
+            method,
+            json!({
+                "textDocument": document(path),
+                "position": { "line": position.line, "character": position.character }
+            }),
+        )
+    };
+
+    match outgoing {
+        Request::Open(path, text) => notification(
+            "textDocument/didOpen",
+            json!({
+                "textDocument": {
+                    "uri": uri(&path),
+                    "languageId": language_id(&path),
+                    "version": 1,
+                    "text": text
+                }
+            }),
+        ),
+        Request::Change(path, version, text) => notification(
+            "textDocument/didChange",
+            json!({
+                "textDocument": { "uri": uri(&path), "version": version },
+                "contentChanges": [{ "text": text }]
+            }),
+        ),
+        Request::Save(path) => notification(
+            "textDocument/didSave",
+            json!({ "textDocument": document(&path) }),
+        ),
+        Request::Close(path) => notification(
+            "textDocument/didClose",
+            json!({ "textDocument": document(&path) }),
+        ),
+        Request::Completion(path, position) => ask(
+            Pending::Completion,
+            "textDocument/completion",
+            &path,
+            position,
+        ),
+        Request::Hover(path, position) => {
+            ask(Pending::Hover, "textDocument/hover", &path, position)
+        }
+    }
+}
+
+// 创建一个需要响应的请求。
+fn request(id: i64, method: &str, params: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params })
+}
+
+// 创建一个不需要响应的通知。
+fn notification(method: &str, params: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "method": method, "params": params })
+}
+
+// 向服务器写入一条消息，消息之前是 Content-Length 头。
+async fn write(stdin: &mut ChildStdin, message: &Value) -> io::Result<()> {
+    let body = message.to_string();
+
+    stdin
+        .write_all(format!("Content-Length: {}\r\n\r\n{body}", body.len()).as_bytes())
+        .await?;
+    stdin.flush().await
+}
+
+// 不断读取服务器发送的消息，服务器关闭输出或应用程序不再接收时结束。
+async fn read_messages(stdout: ChildStdout, mut messages: mpsc::Sender<Value>) {
+    let mut reader = BufReader::new(stdout);
+
+    loop {
+        let mut length = None;
+
+        // 读取消息头，直到遇到空行。
+        loop {
+            let mut header = String::new();
+
+            if reader.read_line(&mut header).await.unwrap_or(0) == 0 {
+                return;
+            }
+
+            let header = header.trim_end();
+
+            if header.is_empty() {
+                break;
+            }
+
+            if let Some(value) = header.strip_prefix("Content-Length:") {
+                length = value.trim().parse::<usize>().ok();
+            }
+        }
+
+        let Some(length) = length else {
+            continue;
+        };
+
+        let mut body = vec![0; length];
+
+        if reader.read_exact(&mut body).await.is_err() {
+            return;
+        }
+
+        if let Ok(message) = serde_json::from_slice(&body) {
+            if messages.send(message).await.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+// 解析 textDocument/publishDiagnostics 通知。
+fn parse_diagnostics(params: &Value) -> Option<(PathBuf, Vec<Diagnostic>)> {
+    let path = path_of(params["uri"].as_str()?)?;
+
+    let diagnostics = params["diagnostics"]
+        .as_array()?
+        .iter()
+        .filter_map(|diagnostic| {
+            let start = &diagnostic["range"]["start"];
+            let end = &diagnostic["range"]["end"];
+
+            let line = start["line"].as_u64()? as usize;
+            let first = start["character"].as_u64()? as usize;
+
+            // 跨越多行的诊断只标记第一行，到行尾为止。
+            let last = if end["line"].as_u64()? as usize == line {
+                end["character"].as_u64()? as usize
+            } else {
+                usize::MAX
+            };
+
+            let severity = match diagnostic["severity"].as_u64() {
+                Some(2) => Severity::Warning,
+                Some(3 | 4) => Severity::Information,
+                _ => Severity::Error,
+            };
+
+            Some(Diagnostic {
+                line,
+                columns: first..last,
+                severity,
+                message: diagnostic["message"].as_str()?.to_string(),
+            })
+        })
+        .collect();
+
+    Some((path, diagnostics))
+}
+
+// 解析补全请求的结果，结果可能是候选项的数组或者包含候选项的列表。
+fn parse_completions(result: &Value) -> Vec<String> {
+    let items = result
+        .get("items")
+        .unwrap_or(result)
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+
+    let mut completions: Vec<String> = Vec::new();
+
+    for item in items {
+        let text = item["textEdit"]["newText"]
+            .as_str()
+            .or_else(|| item["insertText"].as_str())
+            .or_else(|| item["label"].as_str());
+
+        if let Some(text) = text {
+            if !completions.iter().any(|existing| existing == text) {
+                completions.push(text.to_string());
+            }
+        }
+
+        if completions.len() >= MAX_COMPLETIONS {
+            break;
+        }
+    }
+
+    completions
+}
+
+// 解析悬停请求的结果，去掉 Markdown 代码块的标记，没有内容时返回 None。
+fn parse_hover(result: &Value) -> Option<String> {
+    let marked = |value: &Value| {
+        value
+            .as_str()
+            .or_else(|| value["value"].as_str())
+            .map(str::to_string)
+    };
+
+    let contents = result.get("contents")?;
+
+    let text = match contents {
+        Value::Array(items) => items
+            .iter()
+            .filter_map(marked)
+            .collect::<Vec<_>>()
+            .join("\n\n"),
+        _ => marked(contents)?,
+    };
+
+    let text = text
+        .lines()
+        .filter(|line| !line.trim_start().starts_with("```"))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    (!text.trim().is_empty()).then(|| text.trim().to_string())
+}
+
+// 返回文件对应的语言标识。
+fn language_id(path: &Path) -> &'static str {
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("rs") => "rust",
+        Some("py") => "python",
+        Some("js") => "javascript",
+        Some("jsx") => "javascriptreact",
+        Some("ts") => "typescript",
+        Some("tsx") => "typescriptreact",
+        Some("go") => "go",
+        Some("c" | "h") => "c",
+        Some("cpp" | "hpp" | "cc") => "cpp",
+        _ => "plaintext",
+    }
+}
+
+// 将文件路径转换为 file:// URI。
+fn uri(path: &Path) -> String {
+    let path = path.to_string_lossy().replace('\\', "/");
+    let mut uri = String::from("file://");
+
+    // Windows 的路径以盘符开头，需要补上开头的斜杠。
+    if !path.starts_with('/') {
+        uri.push('/');
+    }
+
+    for byte in path.bytes() {
+        if byte.is_ascii_alphanumeric() || b"/-_.~:".contains(&byte) {
+            uri.push(char::from(byte));
+        } else {
+            let _ = write!(uri, "%{byte:02X}");
+        }
+    }
+
+    uri
+}
+
+// 将 file:// URI 转换为文件路径，无法识别时返回 None。
+fn path_of(uri: &str) -> Option<PathBuf> {
+    let encoded = uri.strip_prefix("file://")?;
+    let mut bytes = Vec::with_capacity(encoded.len());
+    let mut rest = encoded.as_bytes();
+
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' && tail.len() >= 2 {
+            let hex = std::str::from_utf8(&tail[..2]).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+
+    let path = String::from_utf8(bytes).ok()?;
+
+    // Windows 的路径形如 /C:/...，去掉开头的斜杠。
+    let path = match path.as_bytes() {
+        [b'/', _, b':', ..] => path[1..].to_string(),
+        _ => path,
+    };
+
+    Some(PathBuf::from(path))
+}
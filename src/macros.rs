@@ -0,0 +1,100 @@
+use crate::i18n::{tr, trf};
+use crate::Message;
+use iced::widget::text_editor::Action;
+use iced::widget::{container, row, text, text_input};
+use iced::{theme, Element};
+
+// 定义键盘宏：录制文本编辑器的动作，之后按原来的顺序重放。
+#[derive(Debug, Default)]
+pub struct Macros {
+    recording: Option<Vec<Action>>, // 正在录制的动作，没有录制时为 None。
+    last: Vec<Action>,              // 最近一次录制完成的宏。
+}
+
+impl Macros {
+    // 判断是否正在录制。
+    pub fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+
+    // 开始录制，或者停止录制并保存录制的动作。没有录制到任何动作时保留原来的宏。
+    pub fn toggle(&mut self) {
+        match self.recording.take() {
+            Some(actions) if !actions.is_empty() => self.last = actions,
+            Some(_) => {}
+            None => self.recording = Some(Vec::new()),
+        }
+    }
+
+    // 录制一个动作。点击、拖动和滚动取决于屏幕上的位置，重放时没有意义，不录制。
+    pub fn record(&mut self, action: &Action) {
+        let Some(actions) = self.recording.as_mut() else {
+            return;
+        };
+
+        if !matches!(
+            action,
+            Action::Click(_) | Action::Drag(_) | Action::Scroll { .. }
+        ) {
+            actions.push(action.clone());
+        }
+    }
+
+    // 返回最近一次录制完成的宏。
+    pub fn actions(&self) -> &[Action] {
+        &self.last
+    }
+}
+
+// 定义“重放宏”对话框的状态。
+#[derive(Debug)]
+pub struct PlayMacro {
+    pub input: String, // 用户输入的重放次数。
+}
+
+impl Default for PlayMacro {
+    fn default() -> Self {
+        Self {
+            input: String::from("1"),
+        }
+    }
+}
+
+impl PlayMacro {
+    // 返回输入框的标识，用于在打开对话框时获取焦点。
+    pub fn id() -> text_input::Id {
+        text_input::Id::new("play-macro")
+    }
+
+    // 解析用户输入的重放次数，至少为 1。
+    pub fn count(&self) -> Option<usize> {
+        self.input
+            .trim()
+            .parse::<usize>()
+            .ok()
+            .filter(|count| *count > 0)
+    }
+
+    // 创建对话框的视图，actions 为宏中的动作数。
+    pub fn view(&self, actions: usize) -> Element<'_, Message> {
+        let input = text_input(tr("Times"), &self.input)
+            .id(Self::id())
+            .on_input(Message::PlayMacroChanged)
+            .on_submit(Message::PlayMacroSubmitted)
+            .padding(5);
+
+        container(
+            row![
+                text(trf(
+                    "Play macro ({actions} actions) times:",
+                    &[("actions", &actions)]
+                )),
+                input
+            ]
+            .spacing(10),
+        )
+        .padding(10)
+        .style(theme::Container::Box)
+        .into()
+    }
+}
@@ -1,192 +1,6089 @@
-use iced::highlighter::{self, Highlighter};
 use iced::widget::{
-    button, column, container, horizontal_space, pick_list, row, text, text_editor, tooltip,
+    button, column, container, horizontal_space, pane_grid, pick_list, progress_bar, responsive,
+    row, scrollable, text, text_editor, text_input, tooltip, Space,
 };
-use iced::{executor, keyboard, theme, window, Font, Subscription};
-use iced::{Application, Command, Element, Length, Settings, Theme};
+use iced::{
+    clipboard, executor, keyboard, subscription, theme, time, window, Event, Font, Subscription,
+};
+use iced::{Application, Command, Element, Length, Point, Rectangle, Settings, Theme};
+use ropey::Rope;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet};
 use std::io;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::AsyncWriteExt;
+
+mod appearance;
+mod brackets;
+mod cleanup;
+mod clipboard_history;
+mod comment;
+mod completion;
+mod context_menu;
+mod cursors;
+mod custom_theme;
+mod decorations;
+mod diagnostics;
+mod diff;
+mod document;
+mod editorconfig;
+mod encoding;
+mod file_tree;
+mod filetype;
+mod folding;
+mod formatter;
+mod git;
+mod goto_line;
+mod gutter;
+mod hex;
+mod history;
+mod html;
+mod i18n;
+mod indent;
+mod json;
+mod keymap;
+mod launch;
+mod line_ending;
+mod loader;
+mod local_history;
+mod lsp;
+mod macros;
+mod markdown;
+mod minimap;
+mod navigation;
+mod notifications;
+mod open_url;
+mod palette;
+mod plugins;
+mod preferences;
+mod print;
+mod problems;
+mod quick_open;
+mod recovery;
+mod remote;
+mod replace;
+mod reveal;
+mod runner;
+mod scratchpad;
+mod scripting;
+mod search;
+mod selection;
+mod session;
+mod shortcuts;
+mod snippets;
+mod source_control;
+mod spelling;
+mod statistics;
+mod symbols;
+mod syntax;
+mod syntax_tree;
+mod tab;
+mod tab_bar;
+mod table;
+mod terminal;
+mod transform;
+mod vim;
+mod watcher;
+mod whitespace;
+mod workspace;
+
+use appearance::Appearance;
+use clipboard_history::ClipboardHistory;
+use custom_theme::{CustomTheme, ThemeEditor};
+use document::Document;
+use encoding::{Decoded, Encoding};
+use file_tree::FileTree;
+use goto_line::GoToLine;
+use gutter::Lane;
+use i18n::{tr, trf};
+use keymap::Keymap;
+use line_ending::LineEnding;
+use local_history::LocalHistory;
+use macros::PlayMacro;
+use notifications::Notifications;
+use open_url::OpenUrl;
+use preferences::Preferences;
+use problems::Problems;
+use remote::OpenRemote;
+use replace::Replace;
+use search::Search;
+use source_control::SourceControl;
+use tab::Tab;
+use terminal::Terminal;
+
+// 主函数，程序的入口点。
+fn main() -> iced::Result {
+    // 命令行参数中包括需要打开的文件路径。
+    let flags = launch::parse(std::env::args_os().skip(1));
+
+    if flags.is_scratchpad {
+        return scratchpad::run();
+    }
+
+    // 运行 Editor 应用程序。
+    Editor::run(Settings {
+        flags,
+        // 嵌入字体文件，用于文本编辑器中的字体图标。
+        fonts: vec![include_bytes!("../fonts/editor-icons.ttf")
+            .as_slice()
+            .into()],
+
+        window: window::Settings {
+            ..Default::default()
+        },
+        default_font: Font::MONOSPACE,
+        // 关闭窗口时先检查是否有未保存的修改。
+        exit_on_close_request: false,
+        ..Default::default()
+    })
+}
+
+// 定义文本编辑器应用程序的状态。
+struct Editor {
+    tabs: Vec<Tab>,                             // 所有打开的标签页。
+    active: usize,                              // 当前激活的标签页下标。
+    next_tab_id: usize,                         // 下一个标签页的唯一标识。
+    notifications: Notifications,               // 通知中心：短暂显示的通知及其历史。
+    theme: syntax::Theme,                       // 代码高亮主题。
+    system_is_dark: bool,                       // 系统当前是否使用深色模式。
+    pending: Option<Pending>,                   // 等待用户确认的操作。
+    after_save: Option<Pending>,                // 保存完成后需要继续执行的操作。
+    run_after_save: Option<usize>,              // 保存完成后需要运行的标签页标识。
+    saving: Vec<usize>,                         // 正在检查或保存的标签页标识，可能重复。
+    preferences: Preferences,                   // 用户偏好设置。
+    screen: Screen,                             // 当前显示的界面。
+    show_sidebar: bool,                         // 是否显示文件树侧边栏。
+    file_tree: Option<FileTree>,                // 侧边栏中的项目文件树。
+    goto_line: Option<GoToLine>,                // 打开的“跳转到行”对话框。
+    search: Option<Search>,                     // 打开的目录搜索面板。
+    source_control: Option<SourceControl>,      // 打开的源代码管理面板。
+    terminal: Terminal,                         // 底部的终端面板。
+    show_terminal: bool,                        // 是否显示终端面板。
+    palette: Option<palette::Palette>,          // 打开的命令面板。
+    quick_open: Option<quick_open::QuickOpen>,  // 打开的快速打开文件面板。
+    completion: Option<completion::Completion>, // 打开的补全弹窗。
+    hover: Option<(usize, String)>,             // 显示的悬停信息，附带所属的标签页标识。
+    lsp: lsp::State,                            // 语言服务器的状态。
+    problems: Problems,                         // cargo check 发现的问题。
+    snippets: snippets::Snippets,               // 用户定义的代码片段。
+    expansion: Option<snippets::Expansion>,     // 正在进行的代码片段展开。
+    cursors: Option<cursors::Cursors>,          // 编辑器光标之外的其他光标。
+    modifiers: keyboard::Modifiers,             // 当前按下的修饰键，用于区分 Ctrl 和 Alt 点击。
+    vim: vim::Vim,                              // Vim 按键模式的状态。
+    show_problems: bool,                        // 是否显示问题面板。
+    loading: Vec<loader::Loading>,              // 正在后台加载的文件。
+    next_load_id: usize,                        // 下一个加载任务的唯一标识。
+    keymap: Keymap,                             // 快捷键映射。
+    recording: Option<keymap::Action>,          // 正在等待用户按下新快捷键的操作。
+    prefix: Option<keymap::Key>,                // 已经按下的快捷键前缀，等待第二个组合。
+    kill: Option<(usize, usize, String)>,       // 最近一次剪切到行尾的标签页标识、版本号和文本。
+    preview: Option<markdown::Preview>,         // 当前 Markdown 文件的预览。
+    last_edit: Instant,                         // 最近一次编辑的时间，用于延迟刷新预览。
+    panes: pane_grid::State<Pane>,              // 编辑区域的分屏布局。
+    diff: Option<(usize, diff::Diff)>,          // 与磁盘上的文件之间的差异及所属的标签页。
+    diff_layout: diff::Layout,                  // 差异的显示方式。
+    comparison: Option<diff::Comparison>,       // 正在比较的两个标签页。
+    spelling: spelling::Spelling,               // 拼写检查的词典和个人词典。
+    context_menu: Option<context_menu::Menu>,   // 打开的右键菜单。
+    clipboard_history: ClipboardHistory,        // 最近复制或剪切的文本。
+    block: Option<String>,                      // 最近复制或剪切的列选择，粘贴它时按列粘贴。
+    column_cut: Option<(usize, usize, String)>, // 删除列选择后的标签页标识、版本号和删除的文本。
+    workspaces: workspace::Workspaces,          // 记住的工作区及当前打开的工作区。
+    show_outline: bool,                         // 是否显示当前文档的大纲面板。
+    journaled: Vec<(usize, usize)>,             // 最近一次写入恢复日志的标签页标识及其版本号。
+    local_history: Option<LocalHistory>,        // 打开的本地历史面板。
+    open_url: Option<OpenUrl>,                  // 打开的“打开网址”对话框。
+    open_remote: Option<OpenRemote>,            // 打开的“打开远程文件”对话框。
+    connection: Option<remote::Status>,         // 最近一次使用的远程连接的状态。
+    table: Option<table::Table>,                // 以表格显示的 CSV 或 TSV 文件。
+    valid_json: Option<(usize, usize)>,         // 验证通过的 JSON 文档的标签页标识及版本号。
+    replace: Option<Replace>,                   // 打开的“查找和替换”面板。
+    macros: macros::Macros,                     // 录制的键盘宏。
+    play_macro: Option<PlayMacro>,              // 打开的“重放宏”对话框。
+    theme_editor: Option<ThemeEditor>,          // 设置界面中正在编辑的自定义主题。
+    plugins: Vec<plugins::Plugin>,              // 插件目录中加载的插件。
+    console: scripting::Console,                // 脚本控制台面板的状态。
+    show_console: bool,                         // 是否显示脚本控制台面板。
+    bookmarks: BTreeMap<PathBuf, Vec<usize>>,   // 已经关闭的文件中添加了书签的行，重新打开时恢复。
+    recovery: Vec<recovery::Entry>,             // 上一次没有正常退出时留下的未保存修改，等待确认。
+    is_secondary: bool,                         // 是否是从其他窗口打开的窗口。
+    scratchpad: Option<std::process::Child>,    // 打开的便签窗口的进程。
+}
+
+// 定义分屏中的视图，两个视图显示同一个文档。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Pane {
+    Primary,   // 主视图。
+    Secondary, // 分屏后新增的视图，拥有独立的光标。
+}
+
+// 定义应用程序可以显示的界面。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Screen {
+    Editor,   // 文本编辑界面。
+    Settings, // 设置界面。
+}
+
+// 定义因存在未保存的修改而需要用户确认的操作。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Pending {
+    CloseTab(usize),         // 关闭指定标识的标签页。
+    Exit,                    // 退出应用程序。
+    Reload(usize),           // 文件在磁盘上被修改，重新加载指定标识的标签页。
+    Revert(usize),           // 放弃指定标识的标签页中未保存的修改，恢复为磁盘上的版本。
+    Overwrite(usize),        // 文件在读取后被其他程序修改，确认是否覆盖指定标识的标签页对应的文件。
+    Unmappable(usize, char), // 文本中有所选编码无法表示的字符，确认是否改用 UTF-8 保存指定标识的标签页。
+    Recover,                 // 恢复上一次没有正常退出时未保存的修改。
+}
+
+// 定义保存标签页的方式。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SaveMode {
+    Save,     // 保存到文件原来的位置，没有路径时弹出保存对话框。
+    SaveAs,   // 总是弹出保存对话框。
+    Autosave, // 自动保存到文件原来的位置，不修改编辑器中的内容。
+}
+
+// 定义一次保存开始时标签页的状态，保存完成后用于更新标签页。
+#[derive(Debug, Clone)]
+struct Saved {
+    id: usize,       // 标签页标识。
+    revision: usize, // 开始保存时文档内容的版本号。
+    text: Rope,      // 写入的文本，转换换行符和编码之前。
+    disk: u64,       // 写入的内容的指纹。
+}
+
+// 定义确认对话框中用户的选择。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Choice {
+    Save,      // 保存后继续。
+    Discard,   // 放弃修改并继续。
+    Cancel,    // 取消操作。
+    Reload,    // 从磁盘重新加载文件。
+    Restore,   // 恢复未保存的修改。
+    Overwrite, // 覆盖被其他程序修改的文件。
+    Compare,   // 比较磁盘上的文件与编辑器中的内容。
+    SaveAs,    // 另存为其他文件。
+    Utf8,      // 改用 UTF-8 编码保存。
+}
+
+// 定义应用程序可能接收的消息类型。
+#[derive(Debug, Clone)]
+enum Message {
+    Edit(text_editor::Action),                     // 文本编辑器的动作。
+    New,                                           // 新建文件。
+    NewScratch,                                    // 新建一个临时缓冲区。
+    Open,                                          // 打开文件。
+    Save,                                          // 保存文件。
+    SaveAs,                                        // 另存为新文件。
+    FileSaved(Saved, Result<PathBuf, Error>),      // 文件保存结果，附带开始保存时标签页的状态。
+    ThemeSelected(syntax::Theme),                  // 选择的高亮主题。
+    DefaultThemeSelected,                          // 使用与界面外观相配的默认高亮主题。
+    LanguageSelected(i18n::Language),              // 选择的界面语言。
+    AppearanceSelected(Appearance),                // 选择的界面外观。
+    AppearanceTick,                                // 定时检查系统的深色模式设置。
+    SystemAppearanceDetected(bool),                // 检测到系统是否使用深色模式。
+    TabSelected(usize),                            // 切换到指定的标签页。
+    TabClosed(usize),                              // 关闭指定的标签页。
+    TabMoved(usize, usize),                        // 把标签页从一个位置拖到另一个位置。
+    TabTornOff(usize),                             // 把标签页拖出标签栏，在新窗口中打开。
+    NewWindow,                                     // 打开一个新的编辑器窗口。
+    WindowOpened(Result<(), Error>),               // 打开新窗口的结果。
+    TabMovedOut(usize, Result<(), Error>),         // 把标签页移到新窗口的结果，附带标签页标识。
+    TabReceived(Result<launch::Transfer, Error>),  // 从其他窗口移过来的标签页。
+    ToggleScratchpad,                              // 打开或关闭便签窗口。
+    ScratchpadClosed,                              // 便签窗口的进程已经退出。
+    NextTab,                                       // 切换到下一个标签页。
+    CloseActiveTab,                                // 关闭当前标签页。
+    Undo,                                          // 撤销最近一次编辑。
+    Redo,                                          // 重做最近一次撤销的编辑。
+    CloseRequested,                                // 用户请求关闭窗口。
+    Confirm(Choice),                               // 用户在确认对话框中的选择。
+    ToggleSettings,                                // 打开或关闭设置界面。
+    AutosaveToggled(bool),                         // 启用或停用自动保存。
+    AutosaveIntervalChanged(u32),                  // 修改自动保存的间隔。
+    AutoCloseBracketsToggled(bool),                // 启用或停用括号自动闭合。
+    MatchBracketsToggled(bool),                    // 启用或停用匹配括号的高亮。
+    TrimWhitespaceToggled(bool),                   // 启用或停用保存时去掉行尾空白。
+    NormalizeIndentationToggled(bool),             // 启用或停用保存时统一缩进。
+    FinalNewlineToggled(bool),                     // 启用或停用保存时去掉末尾多余的空行。
+    BackupToggled(bool),                           // 启用或停用保存时保留原文件的 .bak 副本。
+    ToggleWordWrap,                                // 切换自动换行。
+    ToggleLineNumbers,                             // 显示或隐藏行号。
+    ToggleMinimap,                                 // 显示或隐藏缩略图。
+    ToggleWhitespace,                              // 切换显示空白字符的范围。
+    ZoomIn,                                        // 放大。
+    ZoomOut,                                       // 缩小。
+    ResetZoom,                                     // 恢复默认的缩放比例。
+    CommandPalette,                                // 打开命令面板。
+    PaletteChanged(String),                        // 修改命令面板中的搜索文本。
+    PaletteSubmitted,                              // 执行命令面板中选中的命令。
+    RunCommand(Box<Message>),                      // 关闭命令面板并执行命令。
+    FilePicked(Result<PathBuf, Error>),            // 文件选择结果。
+    LoadProgress(usize, loader::Progress),         // 文件的加载进度，附带加载任务的标识。
+    PreferencesLoaded(Result<Preferences, Error>), // 偏好设置加载结果。
+    PreferencesSaved(Result<(), Error>),           // 偏好设置保存结果。
+    PreferencesChanged,                            // 偏好设置文件在磁盘上被修改。
+    PreferencesRead(Result<Preferences, Error>),   // 重新读取的偏好设置。
+    Autosave,                                      // 自动保存的定时器触发。
+    LineEndingSelected(LineEnding),                // 选择当前文件的换行符。
+    EncodingSelected(Encoding),                    // 选择当前文件的字符编码。
+    FileReloaded(usize, Result<(PathBuf, Decoded), Error>), // 重新加载文件的结果。
+    ToggleSidebar,                                 // 显示或隐藏文件树侧边栏。
+    OpenFolder,                                    // 选择一个目录作为文件树的根。
+    FolderOpened(Result<PathBuf, Error>),          // 目录选择结果。
+    DirectoryLoaded(Result<(PathBuf, Vec<file_tree::Entry>), Error>), // 目录内容加载结果。
+    DirectoryToggled(PathBuf),                     // 展开或折叠目录。
+    FileClicked(PathBuf),                          // 在文件树中点击了文件。
+    GoToLine,                                      // 打开“跳转到行”对话框。
+    GoToLineChanged(String),                       // 修改“跳转到行”对话框中的输入。
+    GoToLineSubmitted,                             // 确认跳转到输入的行。
+    Dismiss,                                       // 关闭打开的对话框。
+    KeyPressed(keyboard::KeyCode, keyboard::Modifiers), // 键盘按键事件。
+    KeymapLoaded(Result<Keymap, Error>),           // 快捷键配置加载结果。
+    RecordBinding(keymap::Action),                 // 开始为操作录制新的快捷键。
+    ResetKeymap,                                   // 恢复默认快捷键。
+    KeymapSaved(Result<(), Error>),                // 快捷键配置保存结果。
+    PreviewTick,                                   // 检查是否需要刷新 Markdown 预览。
+    SessionRestored(session::Restored, launch::Flags), // 会话恢复结果，附带命令行参数。
+    LaunchClipboardRead(Option<String>),           // 启动时读取到的剪贴板内容。
+    FileDropped(PathBuf),                          // 文件被拖放到窗口上。
+    FileChanged(PathBuf),                          // 打开的文件在磁盘上被修改。
+    FileChangedOnDisk(usize, Option<u64>),         // 被修改的文件的标签页标识和磁盘上内容的指纹。
+    Split(pane_grid::Axis),                        // 将编辑区域分为两个视图。
+    CloseSplit,                                    // 关闭分屏。
+    SplitEdit(text_editor::Action),                // 分屏中第二个视图的动作。
+    PaneResized(pane_grid::ResizeEvent),           // 调整分屏的大小。
+    ToggleComment,                                 // 注释或取消注释选中的行。
+    FindInFiles,                                   // 打开目录搜索面板。
+    CloseSearch,                                   // 关闭目录搜索面板。
+    ChooseSearchFolder,                            // 选择搜索的目录。
+    SearchFolderChosen(Result<PathBuf, Error>),    // 搜索目录的选择结果。
+    SearchQueryChanged(String),                    // 修改搜索的文本。
+    SearchSubmitted,                               // 开始搜索。
+    SearchCompleted(Result<(String, Vec<search::FileMatches>), Error>), // 搜索结果。
+    SearchResultClicked(PathBuf, usize, usize),    // 点击了搜索结果，附带行号和列号。
+    SearchResultOpened(Result<(PathBuf, Decoded), Error>, usize, usize), // 打开搜索结果所在文件的结果。
+    HeadLoaded(usize, Option<String>), // 文件在 git HEAD 中的内容，附带标签页标识。
+    ChangesTick,                       // 检查是否需要重新比较 git 修改。
+    ChangesComputed(usize, usize, Vec<git::Change>), // git 修改的比较结果，附带标签页标识和版本号。
+    JumpToLine(usize),                 // 将光标移动到指定行的开头。
+    ToggleSourceControl,               // 打开或关闭源代码管理面板。
+    RefreshSourceControl,              // 重新读取仓库中修改过的文件。
+    GitStatusLoaded(Result<(PathBuf, Vec<git::FileStatus>), Error>), // 仓库根目录和修改过的文件。
+    StageFile,                         // 暂存当前标签页的文件。
+    FileStaged(Result<(), Error>),     // 暂存文件的结果。
+    CommitMessageChanged(String),      // 修改提交信息。
+    Commit,                            // 提交暂存的修改。
+    Committed(Result<(), Error>),      // 提交的结果。
+    ToggleTerminal,                    // 显示或隐藏终端面板。
+    TerminalInputChanged(String),      // 修改终端中输入的命令。
+    TerminalSubmitted,                 // 运行终端中输入的命令。
+    TerminalKill,                      // 结束终端中正在运行的命令。
+    TerminalClear,                     // 清空终端的输出。
+    TerminalOutput(usize, terminal::Event), // 终端中运行的命令发送的事件，附带命令的标识。
+    RunFile,                           // 在终端面板中运行当前文件。
+    Lsp(lsp::Event),                   // 语言服务器发送的事件。
+    LspTick,                           // 检查是否需要将修改同步给语言服务器。
+    TriggerCompletion,                 // 请求光标处的补全。
+    CompletionAccepted(String),        // 接受补全的候选项。
+    ShowHover,                         // 显示光标处的悬停信息。
+    ToggleProblems,                    // 打开或关闭问题面板。
+    CheckProject,                      // 对当前文件所属的 Cargo 项目运行 cargo check。
+    ProjectChecked(Result<(PathBuf, Vec<problems::Problem>), Error>), // 工作空间根目录和发现的问题。
+    SnippetsLoaded(Result<snippets::Snippets, Error>),                // 代码片段配置加载结果。
+    ModifiersChanged(keyboard::Modifiers),                            // 按下或松开了修饰键。
+    EditorConfigLoaded(usize, editorconfig::Properties), // 文件适用的 .editorconfig 属性。
+    FormatDocument,                                      // 用外部格式化工具格式化当前文档。
+    DocumentFormatted(usize, usize, Result<String, Error>), // 格式化的结果，附带标签页标识和版本号。
+    DuplicateLines,                                         // 复制光标所在的行或选中的行。
+    MoveLinesUp,                                            // 将光标所在的行或选中的行上移一行。
+    MoveLinesDown,                                          // 将光标所在的行或选中的行下移一行。
+    AddNextOccurrence, // 选中光标处的单词，或者为选中文本的下一处出现的位置添加光标。
+    Print,             // 将当前文档打印为 PDF。
+    Printed(Result<PathBuf, Error>), // PDF 文件的保存结果。
+    PrintLineNumbersToggled(bool), // 启用或停用打印时的行号。
+    ExportHtml,        // 将当前文档导出为带有代码高亮的 HTML。
+    Exported(Result<PathBuf, Error>), // HTML 文件的保存结果。
+    VimModeToggled(bool), // 启用或停用 Vim 按键模式。
+    PresetSelected(keymap::Preset), // 选择预设快捷键。
+    FontSelected(String), // 选择编辑器字体。
+    TabWidthChanged(usize), // 修改制表符的宽度。
+    IndentationChanged(String, indent::Override), // 修改指定扩展名的缩进设置。
+    CurrentLineHighlightToggled(bool), // 启用或停用当前行的背景高亮。
+    IndentGuidesToggled(bool), // 显示或隐藏缩进参考线。
+    WhitespaceModeSelected(whitespace::Mode), // 选择显示空白字符的范围。
+    LineEndingMarksToggled(bool), // 显示或隐藏行尾的换行符标记。
+    RulerChanged(Option<usize>), // 修改列标尺所在的列，None 表示不显示。
+    KillLine,          // 剪切从光标到行尾的文本。
+    Yank,              // 粘贴剪贴板中的文本。
+    Yanked(Option<String>), // 读取到的剪贴板内容。
+    ShowChanges,       // 显示或关闭当前文档与磁盘上的文件之间的差异。
+    SavedDiffComputed(usize, Result<diff::Diff, Error>), // 与磁盘上的文件比较的结果，附带标签页标识。
+    DiffLayoutSelected(diff::Layout),                    // 选择差异的显示方式。
+    Compare,                                             // 选择另一个文件与当前文档比较。
+    CompareFileOpened(usize, Result<(PathBuf, Decoded), Error>), // 用于比较的文件。
+    NextHunk,                                            // 跳转到下一处修改。
+    PreviousHunk,                                        // 跳转到上一处修改。
+    CopyHunk(usize, diff::Side),                         // 将一处修改复制到另一侧。
+    CloseComparison,                                     // 结束两个文件的比较。
+    SpellCheckToggled(bool),                             // 启用或停用拼写检查。
+    DictionarySelected(String),                          // 选择拼写检查的词典。
+    DictionariesFound(Vec<String>),                      // 找到的可用词典。
+    DictionaryLoaded(Result<Option<spelling::Dictionary>, Error>), // 词典加载结果。
+    WordsLoaded(Result<BTreeSet<String>, Error>),        // 个人词典加载结果。
+    WordsSaved(Result<(), Error>),                       // 个人词典保存结果。
+    SuggestionAccepted(spelling::Misspelling, String),   // 用选中的建议替换拼写错误的单词。
+    AddToDictionary(String),                             // 将单词添加到个人词典。
+    ContextMenu(Point, Option<context_menu::Target>),    // 在编辑器中右键点击的位置和内容。
+    ContextMenuClosed,                                   // 关闭右键菜单。
+    MenuItemSelected(Box<Message>),                      // 选择右键菜单中的一项。
+    Cut,                                                 // 剪切选中的文本。
+    Copy,                                                // 复制选中的文本。
+    Paste,                                               // 粘贴剪贴板中的文本。
+    SelectAll,                                           // 选中整个文档。
+    SelectLine,                                          // 选中光标所在的行或多选中下一行。
+    ExpandSelection,                                     // 把选区扩大到包含它的下一级范围。
+    PasteHistory,                                        // 选择剪贴板历史中的一项粘贴。
+    PasteFromHistory(usize),                             // 粘贴剪贴板历史中的一项。
+    ClipboardCopied(bool),                               // 编辑器复制或剪切（true）了选区。
+    ClipboardRead(Option<String>),                       // 复制后读取到的剪贴板内容。
+    ClipboardHistorySizeChanged(usize),                  // 修改剪贴板历史的容量。
+    ClearClipboardHistory,                               // 清空剪贴板历史。
+    OpenWorkspace,                                       // 选择一个目录作为工作区打开。
+    WorkspaceFolderChosen(Result<PathBuf, Error>),       // 工作区目录选择结果。
+    AddFolderToWorkspace,                                // 选择一个目录添加到当前工作区。
+    WorkspaceRootChosen(Result<PathBuf, Error>),         // 添加到工作区的目录选择结果。
+    CloseWorkspace,                                      // 关闭当前工作区。
+    SwitchWorkspace,                                     // 选择要切换到的工作区。
+    WorkspaceSelected(String),                           // 切换到指定名称的工作区。
+    OpenRecent,                                          // 选择当前工作区中最近打开的文件。
+    WorkspacesLoaded(Result<workspace::Workspaces, Error>), // 工作区清单加载结果。
+    WorkspacesSaved(Result<(), Error>),                  // 工作区清单保存结果。
+    QuickOpen,                                           // 打开快速打开文件面板。
+    FilesIndexed(Result<(Vec<PathBuf>, Vec<PathBuf>), Error>), // 根目录及其中所有文件的索引结果。
+    QuickOpenChanged(String),                            // 修改快速打开面板中的搜索文本。
+    QuickOpenSubmitted,                                  // 打开快速打开面板中选中的文件。
+    QuickOpenSelected(PathBuf),                          // 关闭快速打开面板并打开文件。
+    BreadcrumbClicked(PathBuf),                          // 以路径导航栏中的目录为根打开文件树。
+    ToggleOutline,                                       // 打开或关闭大纲面板。
+    SymbolClicked(usize),                                // 跳转到大纲中的符号所在的行。
+    HighlighterSelected(String, syntax::Backend),        // 选择指定扩展名的代码高亮方式。
+    CancelLoading(usize),                                // 取消加载指定标识的文件。
+    JournalTick,                                         // 定时将未保存的修改写入恢复日志。
+    JournalWritten(Result<(), Error>),                   // 恢复日志的写入结果。
+    RecoveryLoaded(Vec<recovery::Entry>),                // 启动时读取到的恢复日志。
+    Recovered(Vec<Result<recovery::Recovered, Error>>),  // 恢复未保存修改的结果。
+    ToggleLocalHistory,                                  // 打开或关闭当前文档的本地历史面板。
+    LocalHistoryListed(usize, Result<Vec<local_history::Version>, Error>), // 读取到的历史版本，附带标签页标识。
+    LocalVersionSelected(usize), // 选择本地历史中的一个版本。
+    LocalVersionLoaded(usize, Result<String, Error>), // 读取到的版本内容，附带版本的下标。
+    RestoreLocalVersion,         // 用选中的版本替换当前内容。
+    LocalHistoryRecorded(usize, Result<(), Error>), // 记录历史版本的结果，附带标签页标识。
+    OpenUrl,                     // 打开“打开网址”对话框。
+    OpenUrlChanged(String),      // 修改“打开网址”对话框中的输入。
+    OpenUrlReadOnlyToggled(bool), // 切换是否以只读方式打开下载的内容。
+    OpenUrlSubmitted,            // 确认下载输入的网址。
+    UrlFetched(Result<(String, Decoded), Error>), // 网址内容的下载结果。
+    OpenRemote,                  // 打开“打开远程文件”对话框。
+    OpenRemoteChanged(String),   // 修改远程文件的网址。
+    RemotePasswordChanged(String), // 修改远程服务器的密码。
+    RemoteRememberToggled(bool), // 切换是否把密码保存到系统钥匙串中。
+    OpenRemoteSubmitted,         // 确认打开输入的远程文件。
+    TrustHostKey,                // 信任“打开远程文件”对话框中显示的主机密钥。
+    HostKeyTrusted(Result<(), Error>), // 主机密钥的记录结果。
+    RemoteOpened(Result<(remote::Location, Decoded), Error>), // 远程文件的读取结果。
+    RemoteSaved(Saved, Result<(), Error>), // 远程文件的保存结果，附带开始保存时标签页的状态。
+    Disconnect,                  // 断开所有远程连接。
+    HexByteClicked(usize),       // 在十六进制编辑器中点击了一个字节。
+    HexPage(isize),              // 在十六进制编辑器中向前或向后翻页。
+    ToggleTable,                 // 在表格和文本之间切换 CSV 或 TSV 文件的视图。
+    TableSorted(usize),          // 点击表格的列标题排序。
+    CellEdited(usize, usize, String), // 修改表格中的单元格，附带记录和列的下标。
+    FormatJson,                  // 使用当前的缩进格式化 JSON 文档。
+    ValidateJson,                // 检查当前文档是否为有效的 JSON。
+    Transform(transform::Transform), // 对选中的文本应用变换。
+    FindReplace,                 // 打开“查找和替换”面板。
+    FindChanged(String),         // 修改查找的文本。
+    ReplacementChanged(String),  // 修改替换的文本。
+    RegexToggled(bool),          // 切换是否使用正则表达式查找。
+    ReplaceInSelectionToggled(bool), // 切换是否只在选中的行中替换。
+    FoundClicked(usize, usize),  // 点击了替换预览中的一处匹配，附带完整文本中的行号和列号。
+    ReplaceOne(usize),           // 只替换预览中的第几处匹配。
+    ReplaceAll,                  // 替换所有匹配。
+    ToggleBookmark,              // 在光标所在的行上添加或移除书签。
+    BookmarkClicked(usize),      // 点击了标记栏中的书签栏，附带行号。
+    NextBookmark,                // 跳转到下一个书签。
+    PreviousBookmark,            // 跳转到上一个书签。
+    Fold,                        // 折叠光标所在的区域。
+    Unfold,                      // 展开光标所在行开始的折叠区域。
+    FoldClicked(usize),          // 点击了标记栏中的折叠标记，附带行号。
+    ToggleMacroRecording,        // 开始或停止录制键盘宏。
+    PlayMacro,                   // 打开“重放宏”对话框。
+    PlayMacroChanged(String),    // 修改“重放宏”对话框中的重放次数。
+    PlayMacroSubmitted,          // 按输入的次数重放宏。
+    CustomThemeSelected(String), // 选择的自定义应用程序主题。
+    CustomThemeCleared,          // 不使用自定义主题，按界面外观使用内置主题。
+    NewCustomTheme,              // 以当前主题的颜色为基础新建自定义主题。
+    EditCustomTheme,             // 编辑选择的自定义主题。
+    DeleteCustomTheme,           // 删除选择的自定义主题。
+    CustomThemeNameChanged(String), // 修改正在编辑的主题的名称。
+    CustomThemeColorChanged(custom_theme::Field, String), // 修改正在编辑的主题的颜色。
+    SaveCustomTheme,             // 保存正在编辑的主题并使用它。
+    CancelCustomTheme,           // 放弃正在编辑的主题。
+    PluginsLoaded((Vec<plugins::Plugin>, Vec<Error>)), // 加载的插件和无法加载的插件的错误。
+    ReloadPlugins,               // 重新加载插件目录中的插件。
+    PluginToggled(String, bool), // 启用或停用指定标识的插件。
+    RunPluginCommand(String, String), // 运行插件提供的命令，附带插件标识和导出的函数名称。
+    PluginCommandFinished(usize, usize, Result<plugins::Outcome, Error>), // 插件命令的运行结果。
+    RunPluginTransform(String, String), // 用插件提供的变换处理选中的文本。
+    TransformFinished(usize, usize, String, Result<plugins::Outcome, Error>), // 插件变换的结果。
+    ToggleConsole,               // 显示或隐藏脚本控制台。
+    ConsoleInputChanged(String), // 修改控制台输入框中的脚本。
+    ConsoleSubmitted,            // 运行控制台输入框中的脚本。
+    ConsoleClear,                // 清空控制台的输出。
+    RunScript(String),           // 运行脚本目录中指定名称的脚本。
+    ScriptFinished(usize, usize, Option<String>, String, scripting::Outcome), // 脚本的运行结果。
+    ScriptsLoaded(Result<Vec<scripting::Script>, Error>), // 脚本目录的加载结果。
+    NotificationTick,            // 定时隐藏到期的通知。
+    DismissNotification(usize),  // 关闭正在显示的通知。
+    RemoveNotification(usize),   // 从通知历史中删除通知。
+    ToggleNotifications,         // 打开或关闭通知历史。
+    ClearNotifications,          // 清空通知历史。
+    NotificationAction(usize),   // 点击了通知中的操作按钮，例如重试。
+    RetrySave(usize),            // 重新保存指定标识的标签页。
+    LanguageOverridden(syntax::Language), // 为当前文档手动选择语言。
+    LanguageOverrideCleared,     // 恢复由文件名推断的语言。
+    RevealFile,                  // 在系统的文件管理器中显示当前文件。
+    FileRevealed(Result<(), Error>), // 在文件管理器中显示文件的结果。
+    CopyPath,                    // 复制当前文件的绝对路径。
+    CopyFileName,                // 复制当前文件的文件名。
+    PathCopied(String),          // 解析出绝对路径后写入剪贴板。
+    RevertFile,                  // 放弃未保存的修改，从磁盘重新读取当前文件。
+    SaveChecked(usize, SaveMode, bool), // 保存前检查的结果：文件是否在读取后被其他程序修改。
+}
+
+// 为 Editor 结构体实现 iced 的 Application trait。
+impl Application for Editor {
+    type Message = Message;
+    type Executor = executor::Default;
+    type Theme = Theme;
+    type Flags = launch::Flags;
+    // 创建一个新的 Editor 实例，先恢复上一次的会话，再打开命令行指定的文件或剪贴板中的内容。
+    fn new(flags: Self::Flags) -> (Self, Command<Message>) {
+        let is_secondary = flags.is_secondary;
+        let system_is_dark = appearance::system_is_dark();
+
+        (
+            Self {
+                tabs: vec![Tab::new(0)],
+                active: 0,
+                next_tab_id: 1,
+                notifications: Notifications::default(),
+                theme: syntax::Theme::default_theme(system_is_dark),
+                system_is_dark,
+                pending: None,
+                after_save: None,
+                run_after_save: None,
+                saving: Vec::new(),
+                preferences: Preferences::default(),
+                screen: Screen::Editor,
+                show_sidebar: false,
+                file_tree: None,
+                goto_line: None,
+                search: None,
+                source_control: None,
+                terminal: Terminal::default(),
+                show_terminal: false,
+                palette: None,
+                quick_open: None,
+                completion: None,
+                hover: None,
+                lsp: lsp::State::default(),
+                problems: Problems::default(),
+                show_problems: false,
+                snippets: snippets::Snippets::default(),
+                expansion: None,
+                cursors: None,
+                modifiers: keyboard::Modifiers::default(),
+                vim: vim::Vim::default(),
+                loading: Vec::new(),
+                next_load_id: 0,
+                keymap: Keymap::default(),
+                recording: None,
+                prefix: None,
+                kill: None,
+                preview: None,
+                last_edit: Instant::now(),
+                panes: pane_grid::State::new(Pane::Primary).0,
+                diff: None,
+                diff_layout: diff::Layout::SideBySide,
+                comparison: None,
+                spelling: spelling::Spelling::default(),
+                context_menu: None,
+                clipboard_history: ClipboardHistory::default(),
+                block: None,
+                column_cut: None,
+                workspaces: workspace::Workspaces::default(),
+                show_outline: false,
+                journaled: Vec::new(),
+                local_history: None,
+                open_url: None,
+                open_remote: None,
+                connection: None,
+                table: None,
+                valid_json: None,
+                replace: None,
+                macros: macros::Macros::default(),
+                play_macro: None,
+                bookmarks: BTreeMap::new(),
+                theme_editor: None,
+                plugins: Vec::new(),
+                console: scripting::Console::default(),
+                show_console: false,
+                recovery: Vec::new(),
+                is_secondary,
+                scratchpad: None,
+            },
+            Command::batch(vec![
+                // 新窗口从空白的文档开始，不恢复上一次的会话。
+                Command::perform(
+                    async move {
+                        if is_secondary {
+                            session::Restored::default()
+                        } else {
+                            session::restore().await
+                        }
+                    },
+                    move |restored| Message::SessionRestored(restored, flags),
+                ),
+                Command::perform(
+                    keymap::load(config_file("keymap.toml")),
+                    Message::KeymapLoaded,
+                ),
+                Command::perform(
+                    preferences::load(config_file("preferences.toml")),
+                    Message::PreferencesLoaded,
+                ),
+                Command::perform(
+                    snippets::load(config_file("snippets.toml")),
+                    Message::SnippetsLoaded,
+                ),
+                Command::perform(
+                    workspace::load(config_file("workspaces.toml")),
+                    Message::WorkspacesLoaded,
+                ),
+                if is_secondary {
+                    Command::none()
+                } else {
+                    Command::perform(recovery::load(), Message::RecoveryLoaded)
+                },
+                Command::perform(
+                    plugins::load(config_file("plugins")),
+                    Message::PluginsLoaded,
+                ),
+                Command::perform(
+                    scripting::load(config_file("scripts")),
+                    Message::ScriptsLoaded,
+                ),
+                Command::perform(spelling::available(), Message::DictionariesFound),
+                Command::perform(
+                    spelling::load_words(config_file("dictionary.txt")),
+                    Message::WordsLoaded,
+                ),
+            ]),
+        )
+    }
+    // 返回应用程序的标题：当前文件名、未保存的标记和所在的目录。
+    //
+    // iced 在每次更新后都会重新读取标题，因此切换标签页或修改文件时标题会随之变化。
+    fn title(&self) -> String {
+        let tab = self.active_tab();
+        let scratch = tab.scratch_name();
+
+        let name = tab
+            .path
+            .as_deref()
+            .and_then(Path::file_name)
+            .and_then(|name| name.to_str())
+            .or(scratch.as_deref())
+            .unwrap_or(tr("New File"));
+
+        let marker = if tab.has_unsaved_changes() {
+            " •"
+        } else {
+            ""
+        };
+
+        match tab.path.as_deref().and_then(Path::parent) {
+            Some(folder) => format!("{name}{marker} — {} — A cool Editor !", folder.display()),
+            None => format!("{name}{marker} — A cool Editor !"),
+        }
+    }
+    // 根据接收到的消息更新应用程序的状态。
+    fn update(&mut self, message: Self::Message) -> Command<Message> {
+        match message {
+            Message::Edit(action) => {
+                // 编辑或滚动后菜单的位置不再对应原来的文字。
+                self.context_menu = None;
+                self.macros.record(&action);
+
+                // 启用 Vim 按键模式时，除插入模式外的按键作为 Vim 命令处理。
+                if self.preferences.vim_mode {
+                    let revision = self.tabs[self.active].revision;
+                    let outcome = self.vim.handle(&mut self.tabs[self.active], action.clone());
+
+                    if self.tabs[self.active].revision != revision {
+                        self.last_edit = Instant::now();
+                    }
+
+                    match outcome {
+                        vim::Outcome::Ignored => {}
+                        vim::Outcome::Handled => {
+                            self.completion = None;
+                            return Command::none();
+                        }
+                        vim::Outcome::Request(vim::Request::Write) => {
+                            return self.check_and_save(self.active, SaveMode::Save);
+                        }
+                        vim::Outcome::Request(vim::Request::Quit) => {
+                            self.request_close_tab(self.active);
+                            return Command::none();
+                        }
+                        vim::Outcome::Request(vim::Request::ForceQuit) => {
+                            self.close_tab(self.active);
+                            return Command::none();
+                        }
+                        vim::Outcome::Request(vim::Request::WriteQuit) => {
+                            self.after_save = Some(Pending::CloseTab(self.active_tab().id));
+                            return self.check_and_save(self.active, SaveMode::Save);
+                        }
+                    }
+                }
+
+                // 补全弹窗显示时，回车或 Tab 接受选中的候选项，上下方向键选择候选项。
+                let prefix = completion::word_before_cursor(&self.tabs[self.active]);
+
+                if let Some(completion) = self
+                    .completion
+                    .as_mut()
+                    .filter(|completion| !completion.matches(&prefix).is_empty())
+                {
+                    match action {
+                        text_editor::Action::Edit(
+                            text_editor::Edit::Enter | text_editor::Edit::Insert('\t'),
+                        ) => {
+                            if let Some(item) = completion.selection(&prefix) {
+                                return self.update(Message::CompletionAccepted(item));
+                            }
+                        }
+                        text_editor::Action::Move(text_editor::Motion::Up) => {
+                            completion.move_selection(&prefix, -1);
+                            return Command::none();
+                        }
+                        text_editor::Action::Move(text_editor::Motion::Down) => {
+                            completion.move_selection(&prefix, 1);
+                            return Command::none();
+                        }
+                        _ => {}
+                    }
+                }
+
+                // Tab 跳到代码片段的下一个占位符，或者展开光标前的代码片段。
+                if let text_editor::Action::Edit(text_editor::Edit::Insert('\t')) = action {
+                    let tab = &mut self.tabs[self.active];
+
+                    if let Some(expansion) = self
+                        .expansion
+                        .as_mut()
+                        .filter(|expansion| expansion.tab == tab.id)
+                    {
+                        expansion.next(tab);
+
+                        if expansion.is_finished() {
+                            self.expansion = None;
+                        }
+                        return Command::none();
+                    }
+
+                    if !tab.is_read_only {
+                        if let Some(expansion) = self.snippets.expand(tab) {
+                            self.last_edit = Instant::now();
+                            self.completion = None;
+                            self.expansion = (!expansion.is_finished()).then_some(expansion);
+                            return Command::none();
+                        }
+                    }
+                }
+
+                // 方向键与修饰键的组合绑定了快捷键（例如 Alt+↑ 上移行）时，
+                // 由快捷键处理，忽略编辑器自身的光标移动。
+                let motion = match action {
+                    text_editor::Action::Move(motion) | text_editor::Action::Select(motion) => {
+                        Some(motion)
+                    }
+                    _ => None,
+                };
+                let key_code = match motion {
+                    Some(text_editor::Motion::Up) => Some(keyboard::KeyCode::Up),
+                    Some(text_editor::Motion::Down) => Some(keyboard::KeyCode::Down),
+                    Some(text_editor::Motion::Left | text_editor::Motion::WordLeft) => {
+                        Some(keyboard::KeyCode::Left)
+                    }
+                    Some(text_editor::Motion::Right | text_editor::Motion::WordRight) => {
+                        Some(keyboard::KeyCode::Right)
+                    }
+                    _ => None,
+                };
+
+                if key_code.is_some_and(|key_code| {
+                    !self.modifiers.is_empty()
+                        && self
+                            .keymap
+                            .resolve(None, keymap::Key::new(key_code, self.modifiers))
+                            .is_some()
+                }) {
+                    return Command::none();
+                }
+
+                // 按住 Ctrl 点击时添加光标，按住 Alt 拖动时进行列选择，普通的点击和拖动取消其他光标。
+                let tab = &mut self.tabs[self.active];
+
+                match action {
+                    text_editor::Action::Click(_) if self.modifiers.command() => {
+                        let cursors = match self.cursors.take() {
+                            Some(cursors) if cursors.tab == tab.id => cursors,
+                            _ => cursors::Cursors::new(tab),
+                        };
+                        let cursors = self.cursors.insert(cursors);
+
+                        cursors.add(tab);
+                        tab.edit(action);
+                        cursors.remove_duplicates(tab);
+                        return Command::none();
+                    }
+                    text_editor::Action::Click(_) if self.modifiers.alt() => {
+                        tab.edit(action);
+
+                        let mut cursors = cursors::Cursors::new(tab);
+                        cursors.start_column(tab);
+                        self.cursors = Some(cursors);
+                        return Command::none();
+                    }
+                    text_editor::Action::Drag(_) => {
+                        if let Some(cursors) = self.cursors.as_mut().filter(|cursors| {
+                            cursors.tab == tab.id && cursors.is_selecting_columns()
+                        }) {
+                            tab.edit(action);
+                            cursors.drag_column(tab);
+                            return Command::none();
+                        }
+
+                        self.cursors = None;
+                    }
+                    text_editor::Action::Click(_)
+                    | text_editor::Action::SelectWord
+                    | text_editor::Action::SelectLine => self.cursors = None,
+                    _ => {}
+                }
+
+                // 有其他光标时在所有光标处执行同一个动作。
+                if let Some(cursors) = self
+                    .cursors
+                    .as_mut()
+                    .filter(|cursors| cursors.tab == tab.id && !cursors.is_empty())
+                {
+                    if !matches!(action, text_editor::Action::Scroll { .. }) {
+                        if action.is_edit() {
+                            self.last_edit = Instant::now();
+                        }
+
+                        // 粘贴的行数与光标数相同时每个光标粘贴一行。
+                        if let text_editor::Action::Edit(text_editor::Edit::Paste(text)) = &action {
+                            if cursors.paste_lines(tab, text) {
+                                self.completion = None;
+                                self.expansion = None;
+                                return Command::none();
+                            }
+                        }
+
+                        // 剪切列选择时编辑器只复制了自身光标的选区，先记下所有选区的文本。
+                        let deleted = match action {
+                            text_editor::Action::Edit(text_editor::Edit::Delete) => {
+                                cursors.selections(tab)
+                            }
+                            _ => None,
+                        };
+
+                        let auto_close = self.preferences.auto_close_brackets;
+                        cursors.apply(tab, action, |tab, action| {
+                            brackets::edit(tab, action, auto_close)
+                        });
+
+                        self.column_cut = deleted.map(|text| (tab.id, tab.revision, text));
+
+                        self.completion = None;
+                        self.expansion = None;
+                        return Command::none();
+                    }
+                }
+
+                if action.is_edit() {
+                    self.last_edit = Instant::now();
+                    self.hover = None;
+                }
+
+                // 粘贴复制的列选择时按列粘贴到光标所在的列。
+                if let text_editor::Action::Edit(text_editor::Edit::Paste(text)) = &action {
+                    if self.block.as_deref() == Some(text.as_str()) {
+                        self.active_tab_mut().paste_block(text);
+                        self.completion = None;
+                        self.expansion = None;
+                        return Command::none();
+                    }
+                }
+
+                // 行首和按单词的移动与删除由编辑器之前的一层处理。
+                if navigation::handle(self.active_tab_mut(), &action, self.modifiers) {
+                    self.completion = None;
+                    self.expansion = None;
+                    return Command::none();
+                }
+
+                // 记录编辑前占位符所在行的长度，用于更新后面的占位符的位置。
+                let before_edit = self
+                    .expansion
+                    .as_ref()
+                    .filter(|expansion| action.is_edit() && expansion.tab == self.active_tab().id)
+                    .map(|expansion| {
+                        let tab = self.active_tab();
+
+                        (
+                            snippets::line_length(tab, expansion.line()),
+                            tab.context.line_count(),
+                        )
+                    });
+
+                let (ends_word, continues_word) = match action {
+                    text_editor::Action::Edit(text_editor::Edit::Insert(c)) => {
+                        let is_word_char = completion::is_word_char(c);
+                        (!is_word_char, is_word_char)
+                    }
+                    _ => (false, false),
+                };
+
+                let auto_close = self.preferences.auto_close_brackets;
+                brackets::edit(self.active_tab_mut(), action, auto_close);
+
+                let tab = &self.tabs[self.active];
+
+                if let Some((length, line_count)) = before_edit {
+                    if !self
+                        .expansion
+                        .as_mut()
+                        .is_some_and(|expansion| expansion.edited(tab, length, line_count))
+                    {
+                        self.expansion = None;
+                    }
+                }
+
+                // 输入单词以外的字符或者光标离开单词时关闭补全弹窗。
+                if ends_word
+                    || !self
+                        .completion
+                        .as_ref()
+                        .is_some_and(|completion| completion.is_active(tab))
+                {
+                    self.completion = None;
+                }
+
+                // 输入单词时提示文档中以它开头的其他单词。
+                if continues_word && self.completion.is_none() {
+                    let prefix = completion::word_before_cursor(tab);
+
+                    if prefix.chars().count() >= completion::MIN_PREFIX {
+                        let words = completion::index(tab, &prefix);
+                        self.completion = Some(completion::Completion::new(tab, words));
+                    }
+                }
+                Command::none()
+            }
+            Message::New => {
+                self.open_tab(Tab::new);
+                Command::none()
+            }
+            Message::NewScratch => {
+                // 新窗口不保存会话，临时缓冲区的内容无处保存，按普通的新文件处理。
+                if self.is_secondary {
+                    return self.update(Message::New);
+                }
+
+                let number = self.next_scratch_number();
+                self.open_tab(|id| Tab::with_scratch(id, number, ""));
+                Command::none()
+            }
+            Message::Open => Command::perform(pick_file(), Message::FilePicked),
+            Message::FilePicked(Ok(path)) => self.open_path(path),
+            Message::FilePicked(Err(error)) => {
+                self.notifications.error(error);
+                Command::none()
+            }
+            Message::LoadProgress(id, loader::Progress::Started(total)) => {
+                if let Some(loading) = self.loading.iter_mut().find(|loading| loading.id == id) {
+                    loading.total = Some(total);
+                }
+                Command::none()
+            }
+            Message::LoadProgress(id, loader::Progress::Advanced(read)) => {
+                if let Some(loading) = self.loading.iter_mut().find(|loading| loading.id == id) {
+                    loading.read = read;
+                }
+                Command::none()
+            }
+            Message::JournalTick => {
+                let dirty = self.unsaved_revisions();
+
+                if dirty == self.journaled {
+                    return Command::none();
+                }
+
+                let buffers = self
+                    .tabs
+                    .iter()
+                    .filter(|tab| tab.has_unsaved_changes())
+                    .map(|tab| recovery::Buffer {
+                        path: tab.path.clone(),
+                        encoding: tab.encoding,
+                        text: tab.context.full_rope(),
+                    })
+                    .collect();
+
+                self.journaled = dirty;
+                Command::perform(recovery::write(buffers), Message::JournalWritten)
+            }
+            Message::JournalWritten(Ok(())) => Command::none(),
+            Message::JournalWritten(Err(error)) => {
+                // 写入失败时下一次定时再重试。
+                self.journaled.clear();
+                self.notifications.error(error);
+                Command::none()
+            }
+            Message::RecoveryLoaded(entries) => {
+                if !entries.is_empty() && self.pending.is_none() {
+                    self.recovery = entries;
+                    self.pending = Some(Pending::Recover);
+                }
+                Command::none()
+            }
+            Message::Recovered(results) => {
+                let mut commands = Vec::new();
+
+                for result in results {
+                    let recovered = match result {
+                        Ok(recovered) => recovered,
+                        Err(error) => {
+                            self.notifications.error(error);
+                            continue;
+                        }
+                    };
+
+                    let open = recovered.path.as_ref().and_then(|path| {
+                        self.tabs
+                            .iter()
+                            .position(|tab| tab.path.as_ref() == Some(path))
+                    });
+
+                    match (open, recovered.path, recovered.decoded) {
+                        (Some(index), _, _) => self.active = index,
+                        (None, Some(path), Some(decoded)) => {
+                            commands.push(self.open_file(path, &decoded));
+                        }
+                        _ => self.open_tab(Tab::new),
+                    }
+
+                    self.active_tab_mut().replace_text(recovered.text);
+                    self.sync_document(self.active);
+                }
+
+                // 恢复的修改需要重新写入日志。
+                self.journaled.clear();
+                Command::batch(commands)
+            }
+            Message::ToggleLocalHistory => {
+                let tab = self.active_tab();
+
+                if self
+                    .local_history
+                    .as_ref()
+                    .is_some_and(|history| history.tab == tab.id)
+                {
+                    self.local_history = None;
+                    return Command::none();
+                }
+
+                // 还没有保存过的文档没有历史版本。
+                let Some(path) = tab.path.clone() else {
+                    return Command::none();
+                };
+
+                let id = tab.id;
+                self.local_history = Some(LocalHistory::new(id));
+
+                Command::perform(local_history::list(path), move |result| {
+                    Message::LocalHistoryListed(id, result)
+                })
+            }
+            Message::LocalHistoryListed(id, Ok(versions)) => {
+                if let Some(history) = self
+                    .local_history
+                    .as_mut()
+                    .filter(|history| history.tab == id)
+                {
+                    history.versions = versions;
+                }
+                Command::none()
+            }
+            Message::LocalVersionSelected(index) => {
+                let Some(history) = self.local_history.as_mut() else {
+                    return Command::none();
+                };
+
+                history.selected = Some(index);
+                history.text = None;
+                history.diff = None;
+
+                match history.selection() {
+                    Some(version) => {
+                        Command::perform(local_history::read(version.clone()), move |result| {
+                            Message::LocalVersionLoaded(index, result)
+                        })
+                    }
+                    None => Command::none(),
+                }
+            }
+            Message::LocalVersionLoaded(index, Ok(text)) => {
+                // 读取期间又选择了其他版本时丢弃结果。
+                let Some(history) = self
+                    .local_history
+                    .as_mut()
+                    .filter(|history| history.selected == Some(index))
+                else {
+                    return Command::none();
+                };
+
+                if let Some(tab) = self.tabs.iter().find(|tab| tab.id == history.tab) {
+                    history.diff = Some(diff::Diff::compute(&text, &tab.context.text()));
+                    history.text = Some(text);
+                }
+                Command::none()
+            }
+            Message::RestoreLocalVersion => {
+                let Some(history) = self.local_history.as_mut() else {
+                    return Command::none();
+                };
+
+                let Some(text) = history.text.clone() else {
+                    return Command::none();
+                };
+
+                // 作为一次可撤销的编辑替换整个文档。
+                if let Some(tab) = self.tabs.iter_mut().find(|tab| tab.id == history.tab) {
+                    tab.replace_text(text.clone());
+                    history.diff = Some(diff::Diff::compute(&text, &tab.context.text()));
+                    self.last_edit = Instant::now();
+                }
+                Command::none()
+            }
+            Message::LocalHistoryRecorded(id, result) => {
+                if let Err(error) = result {
+                    self.notifications.error(error);
+                }
+
+                // 记录完成后继续执行保存之后等待中的操作。
+                self.continue_after_save(id).unwrap_or_else(Command::none)
+            }
+            Message::LocalHistoryListed(_, Err(error))
+            | Message::LocalVersionLoaded(_, Err(error)) => {
+                self.notifications.error(error);
+                Command::none()
+            }
+            Message::OpenUrl => {
+                self.open_url = Some(OpenUrl::default());
+                text_input::focus(OpenUrl::id())
+            }
+            Message::OpenUrlChanged(input) => {
+                if let Some(open_url) = self.open_url.as_mut() {
+                    open_url.input = input;
+                }
+                Command::none()
+            }
+            Message::OpenUrlReadOnlyToggled(read_only) => {
+                if let Some(open_url) = self.open_url.as_mut() {
+                    open_url.read_only = read_only;
+                }
+                Command::none()
+            }
+            Message::OpenUrlSubmitted => {
+                let Some(open_url) = self.open_url.as_ref() else {
+                    return Command::none();
+                };
+
+                match open_url.url() {
+                    Some(url) => {
+                        let read_only = open_url.read_only;
+                        self.open_url = None;
+
+                        Command::perform(open_url::fetch(url, read_only), Message::UrlFetched)
+                    }
+                    None => {
+                        self.notifications
+                            .error(Error::DownloadFailed(String::from(tr(
+                                "Only http:// and https:// URLs can be opened",
+                            ))));
+                        Command::none()
+                    }
+                }
+            }
+            Message::UrlFetched(Ok((url, decoded))) => {
+                // 下载的内容没有本地路径，保存时使用“另存为”。
+                if self.active_tab().is_blank() {
+                    let id = self.active_tab().id;
+                    *self.active_tab_mut() = Tab::with_download(id, url, &decoded);
+                    self.apply_indentation(self.active);
+                } else {
+                    self.open_tab(|id| Tab::with_download(id, url, &decoded));
+                }
+
+                self.sync_document(self.active);
+                self.notifications.dismiss_errors();
+                Command::none()
+            }
+            Message::UrlFetched(Err(error)) => {
+                self.notifications.error(error);
+                Command::none()
+            }
+            Message::OpenRemote => {
+                self.open_remote = Some(OpenRemote::default());
+                text_input::focus(OpenRemote::id())
+            }
+            Message::OpenRemoteChanged(input) => {
+                if let Some(open_remote) = self.open_remote.as_mut() {
+                    open_remote.input = input;
+                }
+                Command::none()
+            }
+            Message::RemotePasswordChanged(password) => {
+                if let Some(open_remote) = self.open_remote.as_mut() {
+                    open_remote.password = password;
+                }
+                Command::none()
+            }
+            Message::RemoteRememberToggled(remember) => {
+                if let Some(open_remote) = self.open_remote.as_mut() {
+                    open_remote.remember = remember;
+                }
+                Command::none()
+            }
+            Message::OpenRemoteSubmitted => {
+                let Some(open_remote) = self.open_remote.as_ref() else {
+                    return Command::none();
+                };
+
+                let Some(location) = remote::Location::parse(&open_remote.input) else {
+                    self.notifications
+                        .error(Error::RemoteFailed(String::from(tr(
+                            "Expected a URL like sftp://user@host/path/to/file",
+                        ))));
+                    return Command::none();
+                };
+
+                let password =
+                    Some(open_remote.password.clone()).filter(|password| !password.is_empty());
+                let remember = open_remote.remember;
+
+                // 连接成功后再关闭对话框，遇到未知的主机密钥时在对话框中确认。
+                if let Some(open_remote) = self.open_remote.as_mut() {
+                    open_remote.host_key = None;
+                }
+                self.connection = Some(remote::Status::Connecting(location.server()));
+
+                Command::perform(
+                    remote::open(location, password, remember),
+                    Message::RemoteOpened,
+                )
+            }
+            Message::TrustHostKey => {
+                let Some(host_key) = self
+                    .open_remote
+                    .as_mut()
+                    .and_then(|open_remote| open_remote.host_key.take())
+                else {
+                    return Command::none();
+                };
+
+                Command::perform(remote::trust(host_key), Message::HostKeyTrusted)
+            }
+            Message::HostKeyTrusted(Ok(())) => self.update(Message::OpenRemoteSubmitted),
+            Message::HostKeyTrusted(Err(error)) => {
+                self.notifications.error(error);
+                Command::none()
+            }
+            Message::RemoteOpened(Ok((location, decoded))) => {
+                self.open_remote = None;
+                self.connection = Some(remote::Status::Connected(location.server()));
+
+                // 文件已经打开时切换到对应的标签页。
+                if let Some(index) = self
+                    .tabs
+                    .iter()
+                    .position(|tab| tab.remote.as_ref() == Some(&location))
+                {
+                    self.active = index;
+                    return Command::none();
+                }
+
+                if self.active_tab().is_blank() {
+                    let id = self.active_tab().id;
+                    *self.active_tab_mut() = Tab::with_remote(id, location, &decoded);
+                    self.apply_indentation(self.active);
+                } else {
+                    self.open_tab(|id| Tab::with_remote(id, location, &decoded));
+                }
+
+                self.sync_document(self.active);
+                self.notifications.dismiss_errors();
+                Command::none()
+            }
+            Message::RemoteSaved(saved, Ok(())) => {
+                self.finish_save(saved.id);
+
+                if let Some(tab) = self.tabs.iter_mut().find(|tab| tab.id == saved.id) {
+                    // 保存期间又修改过的内容还没有写入服务器，仍然需要保存。
+                    if tab.revision == saved.revision {
+                        tab.is_dirty = false;
+                    }
+
+                    if let Some(location) = &tab.remote {
+                        self.connection = Some(remote::Status::Connected(location.server()));
+                    }
+                }
+
+                // 保存完成后继续执行等待中的操作。
+                self.continue_after_save(saved.id)
+                    .unwrap_or_else(Command::none)
+            }
+            Message::RemoteSaved(saved, Err(error)) => {
+                self.finish_save(saved.id);
+                self.update(Message::RemoteOpened(Err(error)))
+            }
+            Message::RemoteOpened(Err(error)) => {
+                if let Some(
+                    remote::Status::Connecting(server) | remote::Status::Connected(server),
+                ) = self.connection.take()
+                {
+                    self.connection = Some(remote::Status::Disconnected(server));
+                }
+
+                // 第一次连接的服务器在对话框中显示指纹，由用户确认后重新连接。
+                if let (Error::UnknownHost(key), Some(open_remote)) =
+                    (&error, self.open_remote.as_mut())
+                {
+                    open_remote.host_key = Some(key.clone());
+                    return Command::none();
+                }
+
+                self.notifications.error(error);
+                self.after_save = None;
+                Command::none()
+            }
+            Message::Disconnect => {
+                remote::disconnect();
+
+                if let Some(
+                    remote::Status::Connecting(server) | remote::Status::Connected(server),
+                ) = self.connection.take()
+                {
+                    self.connection = Some(remote::Status::Disconnected(server));
+                }
+                Command::none()
+            }
+            Message::CancelLoading(id) => {
+                // 移除加载任务后对应的订阅随之停止，未完成的读取被丢弃。
+                self.loading.retain(|loading| loading.id != id);
+                Command::none()
+            }
+            Message::LoadProgress(id, loader::Progress::Finished(result)) => {
+                self.loading.retain(|loading| loading.id != id);
+
+                match result {
+                    Ok((path, decoded)) => self.open_file(path, &decoded),
+                    // 打开文件失败时可以重试，例如在授予权限或挂载磁盘之后。
+                    Err(Error::FileFailed(error)) => {
+                        let retry = Message::FilePicked(Ok(error.path.clone()));
+                        self.notifications.retry(Error::FileFailed(error), retry);
+                        Command::none()
+                    }
+                    Err(error) => {
+                        self.notifications.error(error);
+                        Command::none()
+                    }
+                }
+            }
+            Message::LoadProgress(id, loader::Progress::Binary(path, bytes, is_read_only)) => {
+                self.loading.retain(|loading| loading.id != id);
+
+                // 二进制文件不能作为文本编辑，用十六进制编辑器打开。
+                if let Some(index) = self
+                    .tabs
+                    .iter()
+                    .position(|tab| tab.path.as_ref() == Some(&path))
+                {
+                    self.active = index;
+                    return Command::none();
+                }
+
+                let bytes = Arc::unwrap_or_clone(bytes);
+
+                if self.active_tab().is_blank() {
+                    let id = self.active_tab().id;
+                    *self.active_tab_mut() = Tab::with_binary(id, path, bytes, is_read_only);
+                } else {
+                    self.open_tab(|id| Tab::with_binary(id, path, bytes, is_read_only));
+                }
+                Command::none()
+            }
+            Message::HexByteClicked(offset) => {
+                if let Some(hex) = self.active_tab_mut().hex.as_mut() {
+                    hex.select(offset);
+                }
+                Command::none()
+            }
+            Message::ToggleTable => {
+                let tab = self.active_tab();
+
+                if self.table.as_ref().is_some_and(|table| table.tab == tab.id) {
+                    self.table = None;
+                } else if table::delimiter(&tab.extension()).is_some() {
+                    self.table = Some(table::Table::new(tab.id));
+                }
+                Command::none()
+            }
+            Message::TableSorted(column) => {
+                if let Some(table) = self.table.as_mut() {
+                    table.toggle_sort(column);
+                }
+                Command::none()
+            }
+            Message::CellEdited(index, column, value) => {
+                let tab = &mut self.tabs[self.active];
+
+                let (Some(table), Some(delimiter)) = (
+                    self.table.as_ref().filter(|table| table.tab == tab.id),
+                    table::delimiter(&tab.extension()),
+                ) else {
+                    return Command::none();
+                };
+
+                // 只替换被修改的记录所在的行，作为一次可撤销的编辑。
+                if let Some(record) = table.record(tab, delimiter, index) {
+                    let mut fields = record.fields;
+
+                    if fields.len() <= column {
+                        fields.resize(column + 1, String::new());
+                    }
+                    fields[column] = value;
+
+                    tab.replace_lines(
+                        record.line,
+                        record.line + record.lines - 1,
+                        table::serialize(&fields, delimiter),
+                    );
+                    self.last_edit = Instant::now();
+                }
+                Command::none()
+            }
+            Message::HexPage(pages) => {
+                if let Some(hex) = self.active_tab_mut().hex.as_mut() {
+                    hex.move_page(pages);
+                }
+                Command::none()
+            }
+            Message::Save => self.check_and_save(self.active, SaveMode::Save),
+            Message::SaveChecked(id, mode, changed) => {
+                self.finish_save(id);
+
+                let Some(index) = self.tabs.iter().position(|tab| tab.id == id) else {
+                    return Command::none();
+                };
+
+                if !changed {
+                    return self.save_tab(index, mode);
+                }
+
+                // 自动保存时不弹出对话框，跳过这个标签页，等用户决定如何处理磁盘上的修改。
+                // 正在等待其他确认（例如重新加载）时也不保存，交给那个对话框处理。
+                if mode == SaveMode::Save && self.pending.is_none() {
+                    self.active = index;
+                    self.pending = Some(Pending::Overwrite(id));
+                }
+                Command::none()
+            }
+            // 总是弹出保存对话框；保存后新的扩展名会用于代码高亮。
+            Message::SaveAs => self.save_tab(self.active, SaveMode::SaveAs),
+            Message::FileSaved(saved, Ok(path)) => {
+                let id = saved.id;
+                let mut checks = Vec::new();
+                let mut history = None;
+
+                self.finish_save(id);
+
+                self.notifications.info(trf(
+                    "Saved {name}",
+                    &[(
+                        "name",
+                        &path.file_name().unwrap_or_default().to_string_lossy(),
+                    )],
+                ));
+
+                // 保存后文档与磁盘上的文件相同，关闭已经过时的差异。
+                if self.diff.as_ref().is_some_and(|(tab, _)| *tab == id) {
+                    self.diff = None;
+                }
+
+                // 保存后重新读取 HEAD 中的内容，文件可能是另存到了其他仓库中，或者刚刚提交过。
+                let head = match self.tabs.iter().position(|tab| tab.id == id) {
+                    Some(index) => {
+                        let tab = &mut self.tabs[index];
+                        tab.path = Some(path.clone());
+                        tab.is_read_only = false;
+                        tab.scratch = None;
+
+                        // 保存期间又修改过的内容还没有写入文件，仍然需要保存。
+                        if tab.revision == saved.revision {
+                            tab.is_dirty = false;
+                        }
+
+                        // 使用写入的内容的指纹，保存期间的修改和自动保存时的清理不会写入文件。
+                        tab.disk = Some(saved.disk);
+
+                        // 另存为本地文件后不再与网址或远程文件关联。
+                        tab.url = None;
+                        tab.remote = None;
+
+                        // 另存为其他扩展名后使用新语言的缩进规则。
+                        self.apply_indentation(index);
+
+                        self.sync_document(index);
+                        self.lsp.save(&path);
+
+                        // 把保存的内容记录到本地历史中。
+                        history = Some(Command::perform(
+                            local_history::record(path.clone(), saved.text.to_string()),
+                            move |result| Message::LocalHistoryRecorded(id, result),
+                        ));
+
+                        // 保存 Rust 文件后检查所属的 Cargo 项目。
+                        if let Some(project) = problems::project(&path) {
+                            checks.push(self.check_project(project));
+                        }
+
+                        load_file_settings(&self.tabs[index])
+                    }
+                    None => Command::none(),
+                };
+
+                let run = if self.run_after_save == Some(id) {
+                    self.run_after_save = None;
+                    self.run_file(id)
+                } else {
+                    Command::none()
+                };
+
+                // 保存完成后继续执行等待中的操作。记录本地历史时等记录完成后再继续，
+                // 保存后关闭标签页或退出时也不会丢失这次保存的版本。
+                if let Some(history) = history {
+                    checks.extend([head, run, self.refresh_source_control(), history]);
+                    return Command::batch(checks);
+                }
+
+                match self.continue_after_save(id) {
+                    Some(command) => command,
+                    None => {
+                        checks.extend([head, run, self.refresh_source_control()]);
+                        Command::batch(checks)
+                    }
+                }
+            }
+            // 保存失败或取消保存对话框时标签页仍然是未保存的，关闭和退出时照常提示。
+            Message::FileSaved(saved, Err(error)) => {
+                self.finish_save(saved.id);
+
+                match error {
+                    Error::FileFailed(_) => self
+                        .notifications
+                        .retry(error, Message::RetrySave(saved.id)),
+                    error => self.notifications.error(error),
+                }
+                self.after_save = None;
+                self.run_after_save = None;
+                Command::none()
+            }
+            Message::ThemeSelected(theme) => {
+                self.theme = theme;
+                self.preferences.theme = Some(theme.to_string());
+                self.save_preferences()
+            }
+            Message::DefaultThemeSelected => {
+                self.preferences.theme = None;
+                self.apply_theme();
+                self.save_preferences()
+            }
+            Message::LanguageSelected(language) => {
+                self.preferences.language = language;
+                i18n::set_language(language);
+                self.save_preferences()
+            }
+            Message::AppearanceSelected(appearance) => {
+                self.preferences.appearance = appearance;
+                self.apply_theme();
+                self.save_preferences()
+            }
+            Message::AppearanceTick => {
+                Command::perform(appearance::detect(), Message::SystemAppearanceDetected)
+            }
+            Message::SystemAppearanceDetected(is_dark) => {
+                if self.system_is_dark != is_dark {
+                    self.system_is_dark = is_dark;
+                    self.apply_theme();
+                }
+                Command::none()
+            }
+            Message::TabSelected(index) => {
+                if index < self.tabs.len() {
+                    self.active = index;
+                    self.context_menu = None;
+                    self.refresh_comparison();
+                }
+                Command::none()
+            }
+            Message::TabClosed(index) => {
+                self.request_close_tab(index);
+                Command::none()
+            }
+            Message::TabMoved(from, to) => {
+                if from < self.tabs.len() && to < self.tabs.len() {
+                    let active = self.active_tab().id;
+                    let tab = self.tabs.remove(from);
+                    self.tabs.insert(to, tab);
+
+                    self.active = self
+                        .tabs
+                        .iter()
+                        .position(|tab| tab.id == active)
+                        .unwrap_or(to);
+                }
+                Command::none()
+            }
+            Message::TabTornOff(index) => {
+                let Some(tab) = self.tabs.get(index) else {
+                    return Command::none();
+                };
+
+                // 新窗口是另一个编辑器进程：远程连接和十六进制编辑器无法交给它，
+                // 临时缓冲区保存在主窗口的会话中，也不移动。
+                if tab.remote.is_some() || tab.hex.is_some() || tab.scratch.is_some() {
+                    self.notifications
+                        .info(tr("This tab can't be moved to a new window"));
+                    return Command::none();
+                }
+
+                // 内容、未保存的修改和撤销历史都随标签页移到新窗口。
+                let id = tab.id;
+                Command::perform(
+                    launch::move_tab(launch::Transfer::new(tab)),
+                    move |result| Message::TabMovedOut(id, result),
+                )
+            }
+            Message::NewWindow => Command::perform(launch::open_window(), Message::WindowOpened),
+            Message::TabMovedOut(id, Ok(())) => {
+                self.close_tab_by_id(id);
+                Command::none()
+            }
+            Message::ToggleScratchpad => {
+                // 便签窗口是另一个进程，关闭时要求它先保存还没有写入的内容。
+                if let Some(mut scratchpad) = self.scratchpad.take() {
+                    if let Ok(None) = scratchpad.try_wait() {
+                        return Command::perform(launch::close_scratchpad(scratchpad), |_| {
+                            Message::ScratchpadClosed
+                        });
+                    }
+                }
+
+                match launch::open_scratchpad() {
+                    Ok(scratchpad) => self.scratchpad = Some(scratchpad),
+                    Err(error) => self.notifications.error(error),
+                }
+                Command::none()
+            }
+            Message::TabReceived(Ok(transfer)) => {
+                let tab = |id| transfer.into_tab(id);
+
+                if self.active_tab().is_blank() {
+                    let id = self.active_tab().id;
+                    *self.active_tab_mut() = tab(id);
+                    self.apply_indentation(self.active);
+                } else {
+                    self.open_tab(tab);
+                }
+
+                self.sync_document(self.active);
+                load_file_settings(self.active_tab())
+            }
+            Message::WindowOpened(Ok(())) | Message::ScratchpadClosed => Command::none(),
+            Message::WindowOpened(Err(error))
+            | Message::TabMovedOut(_, Err(error))
+            | Message::TabReceived(Err(error)) => {
+                self.notifications.error(error);
+                Command::none()
+            }
+            Message::NextTab => {
+                self.active = (self.active + 1) % self.tabs.len();
+                Command::none()
+            }
+            Message::CloseActiveTab => {
+                self.request_close_tab(self.active);
+                Command::none()
+            }
+            Message::Undo => {
+                self.cursors = None;
+                self.active_tab_mut().undo();
+                self.refresh_comparison();
+                Command::none()
+            }
+            Message::Redo => {
+                self.cursors = None;
+                self.active_tab_mut().redo();
+                self.refresh_comparison();
+                Command::none()
+            }
+            Message::ModifiersChanged(modifiers) => {
+                self.modifiers = modifiers;
+                Command::none()
+            }
+            Message::AddNextOccurrence => {
+                let tab = &mut self.tabs[self.active];
+
+                let cursors = match self.cursors.take() {
+                    Some(cursors) if cursors.tab == tab.id => cursors,
+                    _ => cursors::Cursors::new(tab),
+                };
+
+                self.cursors.insert(cursors).add_next_occurrence(tab);
+                Command::none()
+            }
+            Message::CloseRequested => {
+                if self.has_unsaved_changes() {
+                    self.pending = Some(Pending::Exit);
+                    Command::none()
+                } else {
+                    self.exit()
+                }
+            }
+            Message::Confirm(choice) => {
+                let Some(pending) = self.pending.take() else {
+                    return Command::none();
+                };
+
+                match (choice, pending) {
+                    // 不保存时也不再继续等待中的关闭或退出。
+                    (Choice::Cancel, Pending::Overwrite(_) | Pending::Unmappable(..)) => {
+                        self.after_save = None;
+                        self.run_after_save = None;
+                        Command::none()
+                    }
+                    (Choice::Cancel, Pending::Reload(id)) => {
+                        // 保留编辑器中的版本，它与磁盘上的文件已经不同，之后保存时直接覆盖。
+                        if let Some(tab) = self.tabs.iter_mut().find(|tab| tab.id == id) {
+                            tab.is_dirty = true;
+                            tab.disk = None;
+                        }
+                        Command::none()
+                    }
+                    (Choice::Cancel, _) => Command::none(),
+                    (Choice::Reload, Pending::Reload(id) | Pending::Revert(id)) => {
+                        self.reload_tab(id)
+                    }
+                    (Choice::Overwrite, Pending::Overwrite(id)) => {
+                        match self.tabs.iter().position(|tab| tab.id == id) {
+                            Some(index) => self.save_tab(index, SaveMode::Save),
+                            None => Command::none(),
+                        }
+                    }
+                    // 显示磁盘上的文件与编辑器中内容的差异，合并之后再保存。
+                    (Choice::Compare, Pending::Overwrite(_)) => {
+                        self.diff = None;
+                        self.update(Message::ShowChanges)
+                    }
+                    (Choice::SaveAs, Pending::Overwrite(_)) => self.update(Message::SaveAs),
+                    (Choice::Utf8, Pending::Unmappable(id, _)) => {
+                        match self.tabs.iter().position(|tab| tab.id == id) {
+                            Some(index) => {
+                                self.tabs[index].encoding = Encoding::UTF_8;
+                                self.tabs[index].encoding_is_uncertain = false;
+                                self.save_tab(index, SaveMode::Save)
+                            }
+                            None => Command::none(),
+                        }
+                    }
+                    (Choice::Discard, Pending::CloseTab(id)) => {
+                        self.close_tab_by_id(id);
+                        Command::none()
+                    }
+                    (Choice::Discard, Pending::Exit) => self.exit(),
+                    (Choice::Restore, Pending::Recover) => {
+                        let entries = std::mem::take(&mut self.recovery);
+                        Command::perform(recovery::restore(entries), Message::Recovered)
+                    }
+                    (Choice::Discard, Pending::Recover) => {
+                        self.recovery.clear();
+                        self.journaled.clear();
+                        Command::perform(recovery::write(Vec::new()), Message::JournalWritten)
+                    }
+                    (Choice::Save, Pending::CloseTab(id)) => {
+                        self.after_save = Some(pending);
+
+                        match self.tabs.iter().position(|tab| tab.id == id) {
+                            Some(index) => self.check_and_save(index, SaveMode::Save),
+                            None => Command::none(),
+                        }
+                    }
+                    (Choice::Save, Pending::Exit) => {
+                        self.after_save = Some(pending);
+
+                        let saves: Vec<_> = (0..self.tabs.len())
+                            .filter(|&index| self.tabs[index].has_unsaved_changes())
+                            .collect();
+
+                        Command::batch(
+                            saves
+                                .into_iter()
+                                .map(|index| self.check_and_save(index, SaveMode::Save)),
+                        )
+                    }
+                    _ => Command::none(),
+                }
+            }
+            Message::ToggleSettings => {
+                self.screen = match self.screen {
+                    Screen::Editor => Screen::Settings,
+                    Screen::Settings => Screen::Editor,
+                };
+                Command::none()
+            }
+            Message::AutosaveToggled(autosave) => {
+                self.preferences.autosave = autosave;
+                self.save_preferences()
+            }
+            Message::AutosaveIntervalChanged(interval) => {
+                self.preferences.autosave_interval = interval;
+                self.save_preferences()
+            }
+            Message::AutoCloseBracketsToggled(auto_close) => {
+                self.preferences.auto_close_brackets = auto_close;
+                self.save_preferences()
+            }
+            Message::MatchBracketsToggled(match_brackets) => {
+                self.preferences.match_brackets = match_brackets;
+                self.save_preferences()
+            }
+            Message::TrimWhitespaceToggled(trim) => {
+                self.preferences.trim_trailing_whitespace = trim;
+                self.save_preferences()
+            }
+            Message::BackupToggled(backup) => {
+                self.preferences.backup_on_save = backup;
+                self.save_preferences()
+            }
+            Message::NormalizeIndentationToggled(normalize) => {
+                self.preferences.normalize_indentation = normalize;
+                self.save_preferences()
+            }
+            Message::FinalNewlineToggled(single) => {
+                self.preferences.single_final_newline = single;
+                self.save_preferences()
+            }
+            Message::VimModeToggled(vim_mode) => {
+                self.preferences.vim_mode = vim_mode;
+                self.vim = vim::Vim::default();
+                self.save_preferences()
+            }
+            Message::FontSelected(font) => {
+                self.preferences.font = font;
+                self.save_preferences()
+            }
+            Message::TabWidthChanged(width) => {
+                self.preferences.tab_width = width;
+                self.apply_indentation_to_all();
+                self.save_preferences()
+            }
+            Message::IndentationChanged(extension, changed) => {
+                let language = self.preferences.indentation.entry(extension).or_default();
+
+                language.use_tabs = changed.use_tabs.or(language.use_tabs);
+                language.size = changed.size.or(language.size);
+                language.tab_width = changed.tab_width.or(language.tab_width);
+
+                self.apply_indentation_to_all();
+                self.save_preferences()
+            }
+            Message::HighlighterSelected(extension, backend) => {
+                self.preferences.highlighters.insert(extension, backend);
+                self.save_preferences()
+            }
+            Message::PrintLineNumbersToggled(line_numbers) => {
+                self.preferences.print_line_numbers = line_numbers;
+                self.save_preferences()
+            }
+            Message::ToggleWordWrap => {
+                self.preferences.word_wrap = !self.preferences.word_wrap;
+                self.save_preferences()
+            }
+            Message::CurrentLineHighlightToggled(highlight) => {
+                self.preferences.highlight_current_line = highlight;
+                self.save_preferences()
+            }
+            Message::IndentGuidesToggled(show) => {
+                self.preferences.indent_guides = show;
+                self.save_preferences()
+            }
+            Message::WhitespaceModeSelected(mode) => {
+                self.preferences.render_whitespace = mode;
+                self.save_preferences()
+            }
+            Message::LineEndingMarksToggled(show) => {
+                self.preferences.line_ending_marks = show;
+                self.save_preferences()
+            }
+            Message::RulerChanged(ruler) => {
+                self.preferences.ruler = ruler;
+                self.save_preferences()
+            }
+            Message::ToggleLineNumbers => {
+                self.preferences.line_numbers = !self.preferences.line_numbers;
+                self.save_preferences()
+            }
+            Message::ToggleMinimap => {
+                self.preferences.minimap = !self.preferences.minimap;
+                self.save_preferences()
+            }
+            Message::ToggleWhitespace => {
+                self.preferences.render_whitespace = self.preferences.render_whitespace.next();
+                self.save_preferences()
+            }
+            Message::ZoomIn => self.zoom(self.preferences.zoom + ZOOM_STEP),
+            Message::ZoomOut => self.zoom(self.preferences.zoom.saturating_sub(ZOOM_STEP)),
+            Message::ResetZoom => self.zoom(100),
+            Message::PreferencesLoaded(Ok(preferences)) => self.apply_preferences(preferences),
+            Message::PreferencesLoaded(Err(error)) | Message::PreferencesSaved(Err(error)) => {
+                self.notifications.error(error);
+                Command::none()
+            }
+            Message::PreferencesSaved(Ok(())) => Command::none(),
+            Message::PreferencesChanged => Command::perform(
+                preferences::load(config_file("preferences.toml")),
+                Message::PreferencesRead,
+            ),
+            Message::PreferencesRead(Ok(preferences)) => {
+                // 自己保存偏好设置时也会收到通知，内容没有变化时不重新应用。
+                if preferences.to_toml() == self.preferences.to_toml() {
+                    return Command::none();
+                }
+
+                self.apply_preferences(preferences)
+            }
+            // 其他窗口可能正在写入文件，读取失败时保留当前的设置，等下一次修改。
+            Message::PreferencesRead(Err(_)) => Command::none(),
+            Message::Autosave => {
+                // 只自动保存已经有路径的文件，避免弹出保存对话框。正在保存的文件留到下一次。
+                let saves: Vec<_> = (0..self.tabs.len())
+                    .filter(|&index| {
+                        let tab = &self.tabs[index];
+                        tab.path.is_some()
+                            && !tab.is_read_only
+                            && tab.has_unsaved_changes()
+                            && !self.saving.contains(&tab.id)
+                    })
+                    .collect();
+
+                Command::batch(
+                    saves
+                        .into_iter()
+                        .map(|index| self.check_and_save(index, SaveMode::Autosave)),
+                )
+            }
+            Message::EncodingSelected(encoding) => {
+                let tab = self.active_tab_mut();
+                tab.encoding_is_uncertain = false;
+
+                // 没有未保存的修改时，使用新的编码重新读取文件；否则只在保存时使用新的编码。
+                match tab.path.clone() {
+                    Some(path) if !tab.is_dirty => {
+                        let id = tab.id;
+
+                        Command::perform(load_file_with(path, encoding), move |result| {
+                            Message::FileReloaded(id, result)
+                        })
+                    }
+                    _ => {
+                        if tab.encoding != encoding {
+                            tab.encoding = encoding;
+                            tab.is_dirty = true;
+                        }
+                        Command::none()
+                    }
+                }
+            }
+            Message::FileReloaded(id, Ok((path, decoded))) => {
+                match self.tabs.iter().position(|tab| tab.id == id) {
+                    Some(index) => {
+                        // 重新加载后尽量保留光标的位置，编辑器会随之滚动到光标所在的行。
+                        let (line, column) = self.tabs[index].context.cursor();
+
+                        // 重新打开文档，让语言服务器使用磁盘上的内容。
+                        self.lsp.close(&path);
+                        self.notifications.info(trf(
+                            "Reloaded {name} from disk",
+                            &[(
+                                "name",
+                                &path.file_name().unwrap_or_default().to_string_lossy(),
+                            )],
+                        ));
+                        self.tabs[index] = Tab::with_file(id, path, &decoded);
+                        self.tabs[index].move_cursor_to(line, column);
+                        self.apply_indentation(index);
+                        self.sync_document(index);
+
+                        load_file_settings(&self.tabs[index])
+                    }
+                    None => Command::none(),
+                }
+            }
+            Message::FileReloaded(_, Err(error)) => {
+                self.notifications.error(error);
+                Command::none()
+            }
+            Message::ToggleSidebar => {
+                self.show_sidebar = !self.show_sidebar;
+
+                // 第一次打开侧边栏时，使用当前文件所在的目录作为根目录。
+                if self.show_sidebar && self.file_tree.is_none() {
+                    if let Some(folder) = self
+                        .active_tab()
+                        .path
+                        .as_deref()
+                        .and_then(Path::parent)
+                        .map(Path::to_path_buf)
+                    {
+                        return self.open_folder(folder);
+                    }
+                }
+                Command::none()
+            }
+            Message::OpenFolder => {
+                Command::perform(file_tree::pick_folder(), Message::FolderOpened)
+            }
+            Message::FolderOpened(Ok(folder)) => {
+                self.show_sidebar = true;
+                self.open_folder(folder)
+            }
+            Message::DirectoryLoaded(Ok((path, entries))) => {
+                if let Some(file_tree) = self.file_tree.as_mut() {
+                    file_tree.set_entries(&path, entries);
+                }
+                Command::none()
+            }
+            Message::FolderOpened(Err(error)) | Message::DirectoryLoaded(Err(error)) => {
+                self.notifications.error(error);
+                Command::none()
+            }
+            Message::DirectoryToggled(path) => {
+                let needs_loading = self
+                    .file_tree
+                    .as_mut()
+                    .is_some_and(|file_tree| file_tree.toggle(&path));
+
+                if needs_loading {
+                    Command::perform(file_tree::list_directory(path), Message::DirectoryLoaded)
+                } else {
+                    Command::none()
+                }
+            }
+            Message::FileClicked(path) => self.open_path(path),
+            Message::OpenWorkspace => {
+                Command::perform(file_tree::pick_folder(), Message::WorkspaceFolderChosen)
+            }
+            Message::WorkspaceFolderChosen(Ok(folder)) => {
+                self.workspaces.open(folder);
+                self.show_sidebar = true;
+                Command::batch([self.open_workspace(), self.save_workspaces()])
+            }
+            Message::AddFolderToWorkspace => {
+                Command::perform(file_tree::pick_folder(), Message::WorkspaceRootChosen)
+            }
+            Message::WorkspaceRootChosen(Ok(folder)) => {
+                self.workspaces.add_root(folder);
+                self.show_sidebar = true;
+                Command::batch([self.open_workspace(), self.save_workspaces()])
+            }
+            // 关闭目录选择对话框时不做任何事。
+            Message::WorkspaceFolderChosen(Err(_)) | Message::WorkspaceRootChosen(Err(_)) => {
+                Command::none()
+            }
+            Message::CloseWorkspace => {
+                self.workspaces.close();
+                self.file_tree = None;
+                self.save_workspaces()
+            }
+            Message::SwitchWorkspace => {
+                self.palette = Some(palette::Palette::with_placeholder(
+                    self.workspaces.commands(),
+                    tr("Switch to workspace"),
+                ));
+                text_input::focus(palette::Palette::id())
+            }
+            Message::WorkspaceSelected(name) => {
+                if !self.workspaces.switch(&name) {
+                    return Command::none();
+                }
+
+                self.show_sidebar = true;
+                Command::batch([self.open_workspace(), self.save_workspaces()])
+            }
+            Message::OpenRecent => {
+                self.palette = Some(palette::Palette::with_placeholder(
+                    self.workspaces.recent_commands(),
+                    tr("Open a recent file in this workspace"),
+                ));
+                text_input::focus(palette::Palette::id())
+            }
+            Message::WorkspacesLoaded(Ok(workspaces)) => {
+                self.workspaces = workspaces;
+                self.open_workspace()
+            }
+            Message::WorkspacesLoaded(Err(error)) | Message::WorkspacesSaved(Err(error)) => {
+                self.notifications.error(error);
+                Command::none()
+            }
+            Message::WorkspacesSaved(Ok(())) => Command::none(),
+            Message::QuickOpen => {
+                // 在文件树的所有根目录中查找，没有打开目录时使用运行命令时的工作目录。
+                let roots = match &self.file_tree {
+                    Some(file_tree) => file_tree.roots(),
+                    None => vec![self.working_directory()],
+                };
+
+                self.palette = None;
+                self.quick_open = Some(quick_open::QuickOpen::new(roots.clone()));
+
+                Command::batch([
+                    Command::perform(quick_open::index(roots), Message::FilesIndexed),
+                    text_input::focus(quick_open::QuickOpen::id()),
+                ])
+            }
+            Message::FilesIndexed(Ok((roots, files))) => {
+                // 忽略面板已经关闭或重新打开后过期的索引。
+                if let Some(quick_open) = self
+                    .quick_open
+                    .as_mut()
+                    .filter(|quick_open| quick_open.roots() == roots)
+                {
+                    quick_open.set_files(files);
+                }
+                Command::none()
+            }
+            Message::FilesIndexed(Err(error)) => {
+                self.quick_open = None;
+                self.notifications.error(error);
+                Command::none()
+            }
+            Message::QuickOpenChanged(query) => {
+                if let Some(quick_open) = self.quick_open.as_mut() {
+                    quick_open.set_query(query);
+                }
+                Command::none()
+            }
+            Message::QuickOpenSubmitted => {
+                let path = self
+                    .quick_open
+                    .as_ref()
+                    .and_then(quick_open::QuickOpen::selection)
+                    .map(Path::to_path_buf);
+
+                match path {
+                    Some(path) => self.update(Message::QuickOpenSelected(path)),
+                    None => Command::none(),
+                }
+            }
+            Message::QuickOpenSelected(path) => {
+                self.quick_open = None;
+                self.open_path(path)
+            }
+            Message::BreadcrumbClicked(folder) => {
+                self.show_sidebar = true;
+                self.open_folder(folder)
+            }
+            Message::ToggleOutline => {
+                self.show_outline = !self.show_outline;
+                Command::none()
+            }
+            Message::SymbolClicked(line) => {
+                self.active_tab_mut().move_cursor_to(line, 0);
+                Command::none()
+            }
+            Message::FileChanged(path) => {
+                let Some(tab) = self
+                    .tabs
+                    .iter()
+                    .find(|tab| tab.path.as_ref() == Some(&path))
+                else {
+                    return Command::none();
+                };
+
+                let id = tab.id;
+
+                Command::perform(disk_fingerprint(path, tab.encoding), move |fingerprint| {
+                    Message::FileChangedOnDisk(id, fingerprint)
+                })
+            }
+            // 文件被删除或暂时无法读取时忽略这次变化。
+            Message::FileChangedOnDisk(_, None) => Command::none(),
+            Message::FileChangedOnDisk(id, Some(fingerprint)) => {
+                let Some(index) = self.tabs.iter().position(|tab| tab.id == id) else {
+                    return Command::none();
+                };
+
+                // 与最近一次读取或保存的内容相同时（例如刚刚由编辑器自己保存）不需要提示，
+                // 与编辑器中还没有保存的修改无关。正在保存时变化来自编辑器自己，保存完成后会更新指纹。
+                let tab = &self.tabs[index];
+                let is_same = tab.disk == Some(fingerprint) || self.saving.contains(&id);
+
+                if !is_same && self.pending.is_none() {
+                    self.pending = Some(Pending::Reload(id));
+                    self.active = index;
+                }
+                Command::none()
+            }
+            Message::Split(axis) => {
+                if self.panes.len() == 1 {
+                    if let Some(pane) = self.panes.iter().next().map(|(pane, _)| *pane) {
+                        let _ = self.panes.split(axis, &pane, Pane::Secondary);
+                    }
+                }
+                Command::none()
+            }
+            Message::CloseSplit => {
+                self.panes = pane_grid::State::new(Pane::Primary).0;
+
+                for tab in &mut self.tabs {
+                    tab.split = None;
+                }
+                Command::none()
+            }
+            Message::SplitEdit(action) => {
+                if action.is_edit() {
+                    self.last_edit = Instant::now();
+                }
+
+                let auto_close = self.preferences.auto_close_brackets;
+                self.active_tab_mut()
+                    .with_split(|tab| brackets::edit(tab, action, auto_close));
+                Command::none()
+            }
+            Message::PaneResized(pane_grid::ResizeEvent { split, ratio }) => {
+                self.panes.resize(&split, ratio);
+                Command::none()
+            }
+            Message::ToggleComment => {
+                self.last_edit = Instant::now();
+                self.active_tab_mut().toggle_comment();
+                Command::none()
+            }
+            Message::FormatDocument => {
+                let tab = self.active_tab();
+
+                // 没有格式化工具的语言直接提示，不启动进程。
+                if !formatter::is_supported(&tab.extension()) {
+                    self.notifications.error(Error::FormatFailed(trf(
+                        "No formatter for .{extension} files",
+                        &[("extension", &tab.extension())],
+                    )));
+                    return Command::none();
+                }
+
+                let (id, revision) = (tab.id, tab.revision);
+
+                Command::perform(
+                    formatter::format(tab.path.clone(), tab.extension(), tab.context.text()),
+                    move |result| Message::DocumentFormatted(id, revision, result),
+                )
+            }
+            Message::DocumentFormatted(id, revision, Ok(text)) => {
+                // 格式化期间文档又被修改时丢弃结果，以免覆盖新的输入。
+                if let Some(tab) = self.tabs.iter_mut().find(|tab| tab.id == id) {
+                    if tab.revision != revision {
+                        self.notifications
+                            .error(Error::FormatFailed(String::from(tr(
+                                "The document changed while formatting",
+                            ))));
+                    } else if tab.context.text() != text {
+                        tab.replace_text(text);
+                        self.last_edit = Instant::now();
+                    }
+                }
+                Command::none()
+            }
+            Message::FormatJson => {
+                let tab = self.active_tab();
+
+                if !json::is_json(&tab.extension()) {
+                    return self.not_json();
+                }
+
+                let text = tab.context.text();
+                let result = json::format(&text, &tab.rules.unit()).map(|formatted| {
+                    if formatted != text {
+                        self.active_tab_mut().replace_text(formatted);
+                        self.last_edit = Instant::now();
+                    }
+                });
+
+                self.report_json(result);
+                Command::none()
+            }
+            Message::Transform(transform) => {
+                if let Err(error) = self.active_tab_mut().transform(transform) {
+                    self.notifications.error(Error::TransformFailed(error));
+                }
+                self.last_edit = Instant::now();
+                Command::none()
+            }
+            Message::FindReplace => {
+                self.replace = Some(Replace::default());
+                text_input::focus(Replace::id())
+            }
+            Message::FindChanged(find) => {
+                if let Some(replace) = self.replace.as_mut() {
+                    replace.set_find(find);
+                }
+                Command::none()
+            }
+            Message::ReplacementChanged(replacement) => {
+                if let Some(replace) = self.replace.as_mut() {
+                    replace.set_replacement(replacement);
+                }
+                Command::none()
+            }
+            Message::RegexToggled(is_regex) => {
+                if let Some(replace) = self.replace.as_mut() {
+                    replace.set_regex(is_regex);
+                }
+                Command::none()
+            }
+            Message::ReplaceInSelectionToggled(in_selection) => {
+                // 打开选项时记录当前选中的行，之后移动光标不会改变替换的范围。
+                let lines = self.active_tab().selected_lines();
+
+                if let Some(replace) = self.replace.as_mut() {
+                    replace.scope = in_selection.then_some(lines);
+                }
+                Command::none()
+            }
+            Message::FoundClicked(line, column) => {
+                self.active_tab_mut().reveal(line, column);
+                Command::none()
+            }
+            Message::ReplaceOne(index) => self.replace_matches(Some(index)),
+            Message::ReplaceAll => self.replace_matches(None),
+            Message::ValidateJson => {
+                let tab = self.active_tab();
+
+                if !json::is_json(&tab.extension()) {
+                    return self.not_json();
+                }
+
+                let result = json::validate(&tab.context.text());
+
+                self.report_json(result);
+                Command::none()
+            }
+            Message::DocumentFormatted(_, _, Err(error)) => {
+                self.notifications.error(error);
+                Command::none()
+            }
+            Message::Print => {
+                let tab = self.active_tab();
+
+                Command::perform(
+                    print::export(
+                        tab.full_title(),
+                        tab.context.text(),
+                        self.theme,
+                        tab.extension(),
+                        self.preferences.print_line_numbers,
+                        self.preferences.tab_width,
+                    ),
+                    Message::Printed,
+                )
+            }
+            Message::ExportHtml => {
+                let tab = self.active_tab();
+
+                Command::perform(
+                    html::export(
+                        tab.full_title(),
+                        tab.context.text(),
+                        self.theme,
+                        tab.extension(),
+                    ),
+                    Message::Exported,
+                )
+            }
+            Message::Printed(Ok(_)) | Message::Exported(Ok(_)) => Command::none(),
+            Message::Printed(Err(Error::DialogClosed))
+            | Message::Exported(Err(Error::DialogClosed)) => Command::none(),
+            Message::Printed(Err(error)) | Message::Exported(Err(error)) => {
+                self.notifications.error(error);
+                Command::none()
+            }
+            Message::ShowChanges => {
+                let tab = self.active_tab();
+
+                if self.diff.as_ref().is_some_and(|(id, _)| *id == tab.id) {
+                    self.diff = None;
+                    return Command::none();
+                }
+
+                // 还没有保存过的文档没有可以比较的文件。
+                let Some(path) = tab.path.clone() else {
+                    return Command::none();
+                };
+
+                let id = tab.id;
+
+                Command::perform(
+                    diff::with_saved(path, tab.encoding, tab.context.text()),
+                    move |result| Message::SavedDiffComputed(id, result),
+                )
+            }
+            Message::SavedDiffComputed(id, Ok(diff)) => {
+                self.diff = Some((id, diff));
+                Command::none()
+            }
+            Message::SavedDiffComputed(_, Err(error)) => {
+                self.notifications.error(error);
+                Command::none()
+            }
+            Message::DiffLayoutSelected(layout) => {
+                self.diff_layout = layout;
+                Command::none()
+            }
+            Message::Compare => {
+                let tab = self.active_tab();
+
+                // 空白的新文档没有可以比较的内容，打开的文件也会直接替换它。
+                if tab.is_blank() {
+                    return Command::none();
+                }
+
+                let id = tab.id;
+
+                Command::perform(
+                    async { load_file(pick_file().await?).await },
+                    move |result| Message::CompareFileOpened(id, result),
+                )
+            }
+            Message::CompareFileOpened(id, Ok((path, decoded))) => {
+                let Some(left) = self.tabs.iter().position(|tab| tab.id == id) else {
+                    return Command::none();
+                };
+
+                // 选择的文件在新的标签页中打开，可以直接编辑和保存，比较结束后仍然保留。
+                let command = self.open_file(path, &decoded);
+                let (left, right) = (&self.tabs[left], self.active_tab());
+
+                self.comparison = Some(diff::Comparison::new(
+                    (left.id, left.revision),
+                    (right.id, right.revision),
+                    &left.context.text(),
+                    &right.context.text(),
+                ));
+                self.active = self.tabs.iter().position(|tab| tab.id == id).unwrap_or(0);
+
+                command
+            }
+            Message::CompareFileOpened(_, Err(Error::DialogClosed)) => Command::none(),
+            Message::CompareFileOpened(_, Err(error)) => {
+                self.notifications.error(error);
+                Command::none()
+            }
+            Message::NextHunk => match &mut self.comparison {
+                Some(comparison) => comparison.step(true),
+                None => Command::none(),
+            },
+            Message::PreviousHunk => match &mut self.comparison {
+                Some(comparison) => comparison.step(false),
+                None => Command::none(),
+            },
+            Message::CopyHunk(index, side) => {
+                self.copy_hunk(index, side);
+                self.refresh_comparison();
+                Command::none()
+            }
+            Message::CloseComparison => {
+                self.comparison = None;
+                Command::none()
+            }
+            Message::SpellCheckToggled(enabled) => {
+                self.preferences.spell_check = enabled;
+
+                let load = if self.spelling.dictionary.is_none() {
+                    self.load_dictionary()
+                } else {
+                    Command::none()
+                };
+
+                Command::batch([load, self.save_preferences()])
+            }
+            Message::DictionarySelected(language) => {
+                self.preferences.dictionary = language;
+                self.spelling.dictionary = None;
+
+                Command::batch([self.load_dictionary(), self.save_preferences()])
+            }
+            Message::DictionariesFound(available) => {
+                self.spelling.available = available;
+                Command::none()
+            }
+            Message::DictionaryLoaded(Ok(dictionary)) => {
+                self.spelling.dictionary = dictionary;
+                Command::none()
+            }
+            Message::WordsLoaded(Ok(words)) => {
+                self.spelling.words = words;
+                Command::none()
+            }
+            Message::WordsSaved(Ok(())) => Command::none(),
+            Message::DictionaryLoaded(Err(error))
+            | Message::WordsLoaded(Err(error))
+            | Message::WordsSaved(Err(error)) => {
+                self.notifications.error(error);
+                Command::none()
+            }
+            Message::SuggestionAccepted(misspelling, suggestion) => {
+                // 打开菜单之后文档可能被修改过，单词已经不在原来的位置时不做替换。
+                let tab = &mut self.tabs[self.active];
+                let current: Option<String> = tab.context.line(misspelling.line).map(|line| {
+                    line.chars()
+                        .skip(misspelling.columns.start)
+                        .take(misspelling.columns.len())
+                        .collect()
+                });
+
+                if current.as_deref() == Some(misspelling.word.as_str()) {
+                    self.last_edit = Instant::now();
+                    tab.replace_columns(misspelling.line, misspelling.columns, suggestion);
+                }
+
+                Command::none()
+            }
+            Message::AddToDictionary(word) => {
+                self.spelling.words.insert(word);
+
+                let contents: String = self
+                    .spelling
+                    .words
+                    .iter()
+                    .map(|word| format!("{word}\n"))
+                    .collect();
+
+                Command::perform(
+                    write_config(config_file("dictionary.txt"), contents),
+                    Message::WordsSaved,
+                )
+            }
+            Message::ContextMenu(position, target) => {
+                self.context_menu = Some(context_menu::Menu {
+                    tab: self.active_tab().id,
+                    position,
+                    items: self.context_menu_items(target),
+                });
+                Command::none()
+            }
+            Message::ContextMenuClosed => {
+                self.context_menu = None;
+                Command::none()
+            }
+            Message::MenuItemSelected(message) => {
+                self.context_menu = None;
+                self.update(*message)
+            }
+            Message::Cut => {
+                if let Some(block) = self.column_selection() {
+                    return Command::batch([
+                        self.copy_block(block),
+                        self.update(Message::Edit(text_editor::Action::Edit(
+                            text_editor::Edit::Delete,
+                        ))),
+                    ]);
+                }
+
+                let Some(text) = self.active_tab().context.selection() else {
+                    return Command::none();
+                };
+
+                self.clipboard_history
+                    .push(text.clone(), self.preferences.clipboard_history);
+
+                Command::batch([
+                    clipboard::write(text),
+                    self.update(Message::Edit(text_editor::Action::Edit(
+                        text_editor::Edit::Delete,
+                    ))),
+                ])
+            }
+            Message::Copy => {
+                if let Some(block) = self.column_selection() {
+                    return self.copy_block(block);
+                }
+
+                match self.active_tab().context.selection() {
+                    Some(text) => {
+                        self.clipboard_history
+                            .push(text.clone(), self.preferences.clipboard_history);
+                        clipboard::write(text)
+                    }
+                    None => Command::none(),
+                }
+            }
+            Message::Paste => clipboard::read(Message::Yanked),
+            Message::SelectAll => {
+                self.active_tab_mut().select_all();
+                Command::none()
+            }
+            Message::SelectLine => {
+                self.cursors = None;
+                selection::select_line(self.active_tab_mut());
+                Command::none()
+            }
+            Message::ExpandSelection => {
+                let tab = self.active_tab();
+                let tree = tab.syntax_tree();
+                let extension = tab.extension();
+
+                self.cursors = None;
+                selection::expand(self.active_tab_mut(), tree, &extension);
+                Command::none()
+            }
+            Message::PasteHistory => {
+                self.palette = Some(palette::Palette::with_placeholder(
+                    self.clipboard_history.commands(),
+                    tr("Paste from clipboard history"),
+                ));
+                text_input::focus(palette::Palette::id())
+            }
+            Message::PasteFromHistory(index) => match self.clipboard_history.take(index) {
+                Some(text) => self.update(Message::Edit(text_editor::Action::Edit(
+                    text_editor::Edit::Paste(Arc::new(text)),
+                ))),
+                None => Command::none(),
+            },
+            // 编辑器自己处理复制和剪切的快捷键，之后从剪贴板读取复制的内容。
+            Message::ClipboardCopied(is_cut) => {
+                // 按键被当作快捷键拦截时，编辑器没有复制或剪切。
+                let key_code = if is_cut {
+                    keyboard::KeyCode::X
+                } else {
+                    keyboard::KeyCode::C
+                };
+                if self
+                    .keymap
+                    .intercepts(None, keymap::Key::new(key_code, self.modifiers))
+                {
+                    return Command::none();
+                }
+
+                // 编辑器只会复制自身光标的选区，列选择中每一行的选区由这里按行复制。
+                // 剪切时各个选区在这之前就已经删除了，使用删除时记下的文本。
+                let tab = self.active_tab();
+                let (id, revision) = (tab.id, tab.revision);
+
+                let block = if is_cut {
+                    self.column_cut
+                        .take()
+                        .filter(|(tab, after, _)| *tab == id && *after == revision)
+                        .map(|(_, _, text)| text)
+                } else {
+                    self.column_selection()
+                };
+
+                match block {
+                    Some(text) => self.copy_block(text),
+                    None => clipboard::read(Message::ClipboardRead),
+                }
+            }
+            Message::ClipboardRead(Some(text)) => {
+                self.clipboard_history
+                    .push(text, self.preferences.clipboard_history);
+                Command::none()
+            }
+            Message::ClipboardRead(None) => Command::none(),
+            Message::ClipboardHistorySizeChanged(size) => {
+                self.preferences.clipboard_history = size;
+                self.clipboard_history.truncate(size);
+                self.save_preferences()
+            }
+            Message::ClearClipboardHistory => {
+                self.clipboard_history.clear();
+                Command::none()
+            }
+            Message::KillLine => {
+                let tab = &mut self.tabs[self.active];
+                let revision = tab.revision;
+
+                let Some(killed) = tab.kill_line() else {
+                    return Command::none();
+                };
+                self.last_edit = Instant::now();
+
+                // 与 Emacs 一样，连续剪切时追加到上一次剪切的文本后面，可以一次粘贴多行。
+                let text = match self.kill.take() {
+                    Some((id, after, previous)) if id == tab.id && after == revision => {
+                        previous + &killed
+                    }
+                    _ => killed,
+                };
+
+                self.kill = Some((tab.id, tab.revision, text.clone()));
+                self.clipboard_history
+                    .push(text.clone(), self.preferences.clipboard_history);
+                clipboard::write(text)
+            }
+            Message::Yank => clipboard::read(Message::Yanked),
+            Message::Yanked(Some(text)) => self.update(Message::Edit(text_editor::Action::Edit(
+                text_editor::Edit::Paste(Arc::new(text)),
+            ))),
+            Message::Yanked(None) => Command::none(),
+            Message::DuplicateLines => {
+                self.last_edit = Instant::now();
+                self.active_tab_mut().duplicate_lines();
+                Command::none()
+            }
+            Message::MoveLinesUp => {
+                self.last_edit = Instant::now();
+                self.active_tab_mut().move_lines(true);
+                Command::none()
+            }
+            Message::MoveLinesDown => {
+                self.last_edit = Instant::now();
+                self.active_tab_mut().move_lines(false);
+                Command::none()
+            }
+            Message::FindInFiles => {
+                // 默认在文件树的根目录中搜索，没有打开目录时使用当前文件所在的目录。
+                if self.search.is_none() {
+                    let folders = match &self.file_tree {
+                        Some(file_tree) => file_tree.roots(),
+                        None => self
+                            .active_tab()
+                            .path
+                            .as_deref()
+                            .and_then(Path::parent)
+                            .map(Path::to_path_buf)
+                            .into_iter()
+                            .collect(),
+                    };
+
+                    self.search = Some(Search::new(folders));
+                }
+
+                text_input::focus(Search::id())
+            }
+            Message::CloseSearch => {
+                self.search = None;
+                Command::none()
+            }
+            Message::ChooseSearchFolder => {
+                Command::perform(file_tree::pick_folder(), Message::SearchFolderChosen)
+            }
+            Message::SearchFolderChosen(Ok(folder)) => {
+                if let Some(search) = self.search.as_mut() {
+                    search.folders = vec![folder];
+                    search.results = None;
+                }
+                Command::none()
+            }
+            // 关闭目录选择对话框时保留原来的目录。
+            Message::SearchFolderChosen(Err(_)) => Command::none(),
+            Message::SearchQueryChanged(query) => {
+                // 修改搜索文本后，尚未完成的搜索结果会被丢弃。
+                if let Some(search) = self.search.as_mut() {
+                    search.query = query;
+                    search.is_searching = false;
+                }
+                Command::none()
+            }
+            Message::SearchSubmitted => {
+                let Some(search) = self.search.as_mut() else {
+                    return Command::none();
+                };
+
+                if search.folders.is_empty() {
+                    return Command::perform(file_tree::pick_folder(), Message::SearchFolderChosen);
+                }
+
+                if search.query.is_empty() {
+                    return Command::none();
+                }
+
+                search.is_searching = true;
+                Command::perform(
+                    search::search(search.folders.clone(), search.query.clone()),
+                    Message::SearchCompleted,
+                )
+            }
+            Message::SearchCompleted(Ok((query, results))) => {
+                // 忽略搜索文本已经改变的过期结果。
+                if let Some(search) = self.search.as_mut().filter(|search| search.query == query) {
+                    search.results = Some(results);
+                    search.is_searching = false;
+                }
+                Command::none()
+            }
+            Message::SearchCompleted(Err(error)) => {
+                if let Some(search) = self.search.as_mut() {
+                    search.is_searching = false;
+                }
+                self.notifications.error(error);
+                Command::none()
+            }
+            Message::SearchResultClicked(path, line, column) => {
+                Command::perform(load_file(path), move |result| {
+                    Message::SearchResultOpened(result, line, column)
+                })
+            }
+            Message::SearchResultOpened(Ok((path, decoded)), line, column) => {
+                let command = self.open_file(path, &decoded);
+                self.active_tab_mut().move_cursor_to(line, column);
+                command
+            }
+            Message::SearchResultOpened(Err(error), _, _) => {
+                self.notifications.error(error);
+                Command::none()
+            }
+            Message::FileDropped(path) => {
+                // 拖放目录时在侧边栏中打开该目录，拖放文件时加载文件。
+                if path.is_dir() {
+                    self.show_sidebar = true;
+                    self.open_folder(path)
+                } else {
+                    self.open_path(path)
+                }
+            }
+            Message::GoToLine => {
+                self.goto_line = Some(GoToLine::default());
+                text_input::focus(GoToLine::id())
+            }
+            Message::GoToLineChanged(input) => {
+                if let Some(goto_line) = self.goto_line.as_mut() {
+                    goto_line.input = input;
+                }
+                Command::none()
+            }
+            Message::GoToLineSubmitted => {
+                if let Some((line, column)) = self.goto_line.take().and_then(|goto| goto.target()) {
+                    self.active_tab_mut().move_cursor_to(line, column);
+                }
+                Command::none()
+            }
+            Message::CommandPalette => {
+                self.quick_open = None;
+                let mut commands = palette::commands(&self.keymap);
+                commands.extend(plugins::commands(
+                    &self.plugins,
+                    &self.preferences.disabled_plugins,
+                ));
+                commands.extend(self.console.scripts.iter().map(|script| {
+                    palette::Entry {
+                        label: trf("Script: {name}", &[("name", &script.name)]),
+                        shortcut: self
+                            .keymap
+                            .script_binding(&script.name)
+                            .map(|key| key.to_string()),
+                        message: Message::RunScript(script.name.clone()),
+                    }
+                }));
+
+                self.palette = Some(palette::Palette::new(commands));
+                text_input::focus(palette::Palette::id())
+            }
+            Message::PaletteChanged(query) => {
+                if let Some(palette) = self.palette.as_mut() {
+                    palette.query = query;
+                    palette.selected = 0;
+                }
+                Command::none()
+            }
+            Message::PaletteSubmitted => {
+                let message = self
+                    .palette
+                    .as_ref()
+                    .and_then(palette::Palette::selection)
+                    .map(|entry| entry.message.clone());
+
+                match message {
+                    Some(message) => self.update(Message::RunCommand(Box::new(message))),
+                    None => Command::none(),
+                }
+            }
+            Message::RunCommand(message) => {
+                self.palette = None;
+                self.update(*message)
+            }
+            Message::Dismiss => {
+                self.notifications.show_history = false;
+                self.goto_line = None;
+                self.play_macro = None;
+                self.replace = None;
+                self.open_url = None;
+                self.open_remote = None;
+                self.diff = None;
+                self.comparison = None;
+                self.context_menu = None;
+                self.palette = None;
+                self.quick_open = None;
+                self.completion = None;
+                self.hover = None;
+                self.expansion = None;
+                self.cursors = None;
+                self.recording = None;
+
+                if self.preferences.vim_mode {
+                    self.vim.escape(&mut self.tabs[self.active]);
+                }
+                Command::none()
+            }
+            Message::KeyPressed(key_code, modifiers) => {
+                let key = keymap::Key::new(key_code, modifiers);
+
+                // 命令面板或快速打开面板打开时，上下方向键用于选择命令或文件。
+                let offset = match key_code {
+                    keyboard::KeyCode::Up => Some(-1),
+                    keyboard::KeyCode::Down => Some(1),
+                    _ => None,
+                };
+
+                if let Some(offset) = offset {
+                    if let Some(palette) = self.palette.as_mut() {
+                        palette.move_selection(offset);
+                        return Command::none();
+                    }
+
+                    if let Some(quick_open) = self.quick_open.as_mut() {
+                        quick_open.move_selection(offset);
+                        return Command::none();
+                    }
+                }
+
+                // 正在录制快捷键时，把按下的组合绑定到对应的操作上，Esc 取消录制。
+                if let Some(action) = self.recording {
+                    if key.is_modifier() {
+                        return Command::none();
+                    }
+
+                    self.recording = None;
+
+                    if key_code == keyboard::KeyCode::Escape {
+                        return Command::none();
+                    }
+
+                    self.keymap.bind(action, key);
+                    return self.save_keymap();
+                }
+
+                // 按下前缀组合后，下一个组合与它一起查找快捷键。
+                if key.is_modifier() {
+                    return Command::none();
+                }
+
+                // 十六进制编辑器使用方向键和翻页键移动光标，输入十六进制数字修改字节。
+                if modifiers.is_empty()
+                    && self.prefix.is_none()
+                    && !self.has_dialog()
+                    && self.hex_key_pressed(key_code)
+                {
+                    return Command::none();
+                }
+
+                let prefix = self.prefix.take();
+
+                if prefix.is_none() && self.keymap.is_prefix(key) {
+                    self.prefix = Some(key);
+                    return Command::none();
+                }
+
+                if let Some(action) = self.keymap.resolve(prefix, key) {
+                    return self.update(action.message());
+                }
+
+                match self.keymap.resolve_script(prefix, key) {
+                    Some(script) => self.update(Message::RunScript(script.to_string())),
+                    None => Command::none(),
+                }
+            }
+            Message::SnippetsLoaded(Ok(snippets)) => {
+                self.snippets = snippets;
+                Command::none()
+            }
+            Message::SnippetsLoaded(Err(error)) => {
+                self.notifications.error(error);
+                Command::none()
+            }
+            Message::KeymapLoaded(Ok(keymap)) => {
+                self.keymap = keymap;
+                Command::none()
+            }
+            Message::KeymapLoaded(Err(error)) | Message::KeymapSaved(Err(error)) => {
+                self.notifications.error(error);
+                Command::none()
+            }
+            Message::RecordBinding(action) => {
+                self.recording = Some(action);
+                Command::none()
+            }
+            Message::PresetSelected(preset) => {
+                self.keymap = preset.keymap();
+                self.recording = None;
+                self.save_keymap()
+            }
+            Message::ResetKeymap => {
+                self.keymap = Keymap::default();
+                self.recording = None;
+                self.save_keymap()
+            }
+            Message::KeymapSaved(Ok(())) => Command::none(),
+            Message::SessionRestored(restored, flags) => {
+                self.clipboard_history =
+                    ClipboardHistory::new(restored.clipboard, self.preferences.clipboard_history);
+
+                self.bookmarks = restored.bookmarks;
+
+                // 先恢复光标的位置，再滚动到上一次第一个可见的行。
+                let mut commands = Vec::new();
+
+                for (file, decoded) in restored.files {
+                    let (line, column) = file.cursor;
+                    commands.push(self.open_file(file.path, &decoded));
+                    self.active_tab_mut().move_cursor_to(line, column);
+                    self.active_tab_mut().context.scroll_to(file.scroll);
+                }
+
+                for scratch in restored.scratch {
+                    let (line, column) = scratch.cursor;
+                    let tab = |id| Tab::with_scratch(id, scratch.number, &scratch.text);
+
+                    if self.active_tab().is_blank() {
+                        let id = self.active_tab().id;
+                        *self.active_tab_mut() = tab(id);
+                        self.apply_indentation(self.active);
+                    } else {
+                        self.open_tab(tab);
+                    }
+
+                    self.active_tab_mut().move_cursor_to(line, column);
+                    self.active_tab_mut().context.scroll_to(scratch.scroll);
+                }
+
+                if let Some(index) = self
+                    .tabs
+                    .iter()
+                    .position(|tab| tab.path.is_some() && tab.path == restored.active)
+                {
+                    self.active = index;
+                }
+
+                if let Some(path) = flags.path {
+                    commands.push(self.open_path(path));
+                }
+
+                if let Some(file) = flags.transfer {
+                    commands.push(Command::perform(
+                        launch::receive(file),
+                        Message::TabReceived,
+                    ));
+                }
+
+                // 恢复会话之后再读取剪贴板，使剪贴板的内容成为当前的标签页。
+                if flags.from_clipboard {
+                    commands.push(clipboard::read(Message::LaunchClipboardRead));
+                }
+
+                Command::batch(commands)
+            }
+            Message::LaunchClipboardRead(text) => {
+                match text.filter(|text| !text.is_empty()) {
+                    Some(text) if self.active_tab().is_blank() => {
+                        let id = self.active_tab().id;
+                        *self.active_tab_mut() = Tab::with_text(id, &text);
+                        self.apply_indentation(self.active);
+                    }
+                    Some(text) => self.open_tab(|id| Tab::with_text(id, &text)),
+                    None => self.notifications.info(tr("The clipboard is empty")),
+                }
+                Command::none()
+            }
+            Message::PreviewTick => {
+                // 停止输入一段时间后才重新解析，避免每次按键都刷新预览。
+                if self.preview_is_stale() && self.last_edit.elapsed() >= PREVIEW_DELAY {
+                    let tab = self.active_tab();
+                    self.preview = Some(markdown::Preview::new(
+                        tab.id,
+                        tab.revision,
+                        &tab.context.text(),
+                    ));
+                }
+                Command::none()
+            }
+            Message::HeadLoaded(id, head) => {
+                let Some(index) = self.tabs.iter().position(|tab| tab.id == id) else {
+                    return Command::none();
+                };
+
+                let tab = &mut self.tabs[index];
+                tab.head = head.map(Arc::new);
+
+                if tab.head.is_none() {
+                    tab.changes.clear();
+                }
+
+                self.compare_with_head(index)
+            }
+            Message::EditorConfigLoaded(id, properties) => {
+                let Some(index) = self.tabs.iter().position(|tab| tab.id == id) else {
+                    return Command::none();
+                };
+
+                let tab = &mut self.tabs[index];
+                let previous = std::mem::replace(&mut tab.editorconfig, properties);
+
+                // 只在属性变化时覆盖换行符和编码，保留用户在状态栏中手动做出的选择。
+                if let Some(line_ending) = properties.end_of_line {
+                    if previous.end_of_line != Some(line_ending) {
+                        tab.line_ending = line_ending;
+                    }
+                }
+
+                if let Some((encoding, has_bom)) = properties.charset {
+                    if previous.charset != properties.charset {
+                        tab.encoding = encoding;
+                        tab.has_bom = has_bom;
+                        tab.encoding_is_uncertain = false;
+                    }
+                }
+
+                self.apply_indentation(index);
+                Command::none()
+            }
+            Message::ChangesTick => {
+                // 与预览相同，停止输入一段时间后才重新比较。
+                if self.changes_are_stale() && self.last_edit.elapsed() >= CHANGES_DELAY {
+                    self.compare_with_head(self.active)
+                } else {
+                    Command::none()
+                }
+            }
+            Message::ChangesComputed(id, revision, changes) => {
+                // 比较期间文档又被修改时丢弃过期的结果，等待下一次比较。
+                if let Some(tab) = self.tabs.iter_mut().find(|tab| tab.id == id) {
+                    if tab.revision == revision {
+                        tab.changes = changes;
+                    }
+                }
+                Command::none()
+            }
+            Message::ToggleBookmark => {
+                let tab = self.active_tab_mut();
+                let (line, _) = tab.context.cursor_position();
+                tab.toggle_bookmark(line);
+                Command::none()
+            }
+            Message::BookmarkClicked(line) => {
+                self.active_tab_mut().toggle_bookmark(line);
+                Command::none()
+            }
+            Message::NextBookmark | Message::PreviousBookmark => {
+                let forward = matches!(message, Message::NextBookmark);
+                let tab = self.active_tab_mut();
+
+                if let Some(line) = tab.next_bookmark(forward) {
+                    tab.move_cursor_to(line, 0);
+                }
+                Command::none()
+            }
+            Message::Fold | Message::Unfold => {
+                let tab = self.active_tab_mut();
+                let (line, _) = tab.context.cursor_position();
+
+                if matches!(message, Message::Fold) {
+                    tab.fold(line);
+                } else {
+                    tab.unfold(line);
+                }
+                Command::none()
+            }
+            Message::FoldClicked(line) => {
+                self.active_tab_mut().toggle_fold(line);
+                Command::none()
+            }
+            Message::ToggleMacroRecording => {
+                self.macros.toggle();
+                Command::none()
+            }
+            Message::PlayMacro => {
+                // 录制时重放会把宏本身录进去，没有录制过宏时也没有可以重放的动作。
+                if self.macros.is_recording() || self.macros.actions().is_empty() {
+                    return Command::none();
+                }
+
+                self.play_macro = Some(PlayMacro::default());
+                text_input::focus(PlayMacro::id())
+            }
+            Message::PlayMacroChanged(input) => {
+                if let Some(play_macro) = self.play_macro.as_mut() {
+                    play_macro.input = input;
+                }
+                Command::none()
+            }
+            Message::PlayMacroSubmitted => {
+                let Some(count) = self.play_macro.take().and_then(|play| play.count()) else {
+                    return Command::none();
+                };
+
+                // 与按键时一样逐个处理录制的动作，补全、代码片段和 Vim 模式都按原来的方式响应。
+                let actions = self.macros.actions().to_vec();
+                let commands: Vec<_> = (0..count)
+                    .flat_map(|_| actions.iter().cloned())
+                    .map(|action| self.update(Message::Edit(action)))
+                    .collect();
+
+                Command::batch(commands)
+            }
+            Message::CustomThemeSelected(name) => {
+                self.preferences.custom_theme = Some(name);
+                self.apply_theme();
+                self.save_preferences()
+            }
+            Message::CustomThemeCleared => {
+                self.preferences.custom_theme = None;
+                self.apply_theme();
+                self.save_preferences()
+            }
+            Message::NewCustomTheme => {
+                self.theme_editor = Some(ThemeEditor {
+                    draft: CustomTheme::from_palette(String::new(), self.theme().palette()),
+                    original: None,
+                });
+                Command::none()
+            }
+            Message::EditCustomTheme => {
+                self.theme_editor = self.preferences.custom_theme().map(|theme| ThemeEditor {
+                    draft: theme.clone(),
+                    original: Some(theme.name.clone()),
+                });
+                Command::none()
+            }
+            Message::DeleteCustomTheme => {
+                let Some(name) = self.preferences.custom_theme.take() else {
+                    return Command::none();
+                };
+
+                self.preferences
+                    .custom_themes
+                    .retain(|theme| theme.name != name);
+                self.apply_theme();
+                self.save_preferences()
+            }
+            Message::CustomThemeNameChanged(name) => {
+                if let Some(editor) = self.theme_editor.as_mut() {
+                    editor.draft.name = name;
+                }
+                Command::none()
+            }
+            Message::CustomThemeColorChanged(field, value) => {
+                if let Some(editor) = self.theme_editor.as_mut() {
+                    *editor.draft.color_mut(field) = value;
+                }
+                Command::none()
+            }
+            Message::SaveCustomTheme => {
+                let Some(editor) = self.theme_editor.take().filter(ThemeEditor::can_save) else {
+                    return Command::none();
+                };
+
+                let mut theme = editor.draft;
+                theme.name = theme.name.trim().to_string();
+
+                // 同名的主题被覆盖，重命名已有的主题时去掉原来的主题。
+                self.preferences.custom_themes.retain(|saved| {
+                    saved.name != theme.name && Some(&saved.name) != editor.original.as_ref()
+                });
+                self.preferences.custom_theme = Some(theme.name.clone());
+                self.preferences.custom_themes.push(theme);
+                self.preferences
+                    .custom_themes
+                    .sort_by(|a, b| a.name.cmp(&b.name));
+                self.apply_theme();
+                self.save_preferences()
+            }
+            Message::CancelCustomTheme => {
+                self.theme_editor = None;
+                Command::none()
+            }
+            Message::PluginsLoaded((plugins, errors)) => {
+                self.plugins = plugins;
+
+                for error in errors {
+                    self.notifications.error(error);
+                }
+                Command::none()
+            }
+            Message::ReloadPlugins => Command::perform(
+                plugins::load(config_file("plugins")),
+                Message::PluginsLoaded,
+            ),
+            Message::PluginToggled(id, enabled) => {
+                if enabled {
+                    self.preferences.disabled_plugins.remove(&id);
+                } else {
+                    self.preferences.disabled_plugins.insert(id);
+                }
+                self.save_preferences()
+            }
+            Message::RunPluginCommand(id, function) => {
+                let Some(plugin) = self.plugin(&id) else {
+                    return Command::none();
+                };
+
+                let tab = self.active_tab();
+                let (id, revision) = (tab.id, tab.revision);
+
+                Command::perform(
+                    plugins::run_command(plugin, function, tab.context.text()),
+                    move |result| Message::PluginCommandFinished(id, revision, result),
+                )
+            }
+            Message::PluginCommandFinished(id, revision, Ok(outcome)) => {
+                self.notify(outcome.notifications);
+
+                // 插件运行期间文档又被修改时丢弃结果，以免覆盖新的输入。
+                if let Some(tab) = self.tabs.iter_mut().find(|tab| tab.id == id) {
+                    if tab.revision != revision {
+                        self.notifications
+                            .error(Error::PluginFailed(String::from(tr(
+                                "The document changed while the plugin was running",
+                            ))));
+                    } else if !tab.is_read_only && tab.context.text() != outcome.text {
+                        tab.replace_text(outcome.text);
+                        self.last_edit = Instant::now();
+                    }
+                }
+                Command::none()
+            }
+            Message::PluginCommandFinished(_, _, Err(error)) => {
+                self.notifications.error(error);
+                Command::none()
+            }
+            Message::ToggleConsole => {
+                self.show_console = !self.show_console;
+
+                // 打开控制台时重新加载脚本目录，以便使用刚刚修改的脚本。
+                if self.show_console {
+                    Command::batch([
+                        text_input::focus(scripting::Console::input_id()),
+                        Command::perform(
+                            scripting::load(config_file("scripts")),
+                            Message::ScriptsLoaded,
+                        ),
+                    ])
+                } else {
+                    Command::none()
+                }
+            }
+            Message::ConsoleInputChanged(input) => {
+                self.console.input = input;
+                Command::none()
+            }
+            Message::ConsoleSubmitted => {
+                if self.console.input.trim().is_empty() {
+                    return Command::none();
+                }
+
+                let source = std::mem::take(&mut self.console.input);
+                self.run_script(source.clone(), source)
+            }
+            Message::ConsoleClear => {
+                self.console.clear();
+                Command::none()
+            }
+            Message::RunScript(name) => {
+                let Some(script) = self.console.script(&name).cloned() else {
+                    return Command::none();
+                };
+
+                self.run_script(script.source, format!("run {name}"))
+            }
+            Message::ScriptFinished(id, revision, selection, command, outcome) => {
+                self.finish_script(id, revision, selection, command, outcome)
+            }
+            Message::ScriptsLoaded(Ok(scripts)) => {
+                self.console.scripts = scripts;
+                Command::none()
+            }
+            Message::ScriptsLoaded(Err(error)) => {
+                self.notifications.error(error);
+                Command::none()
+            }
+            Message::NotificationTick => {
+                self.notifications.tick(Instant::now());
+                Command::none()
+            }
+            Message::DismissNotification(id) => {
+                self.notifications.dismiss(id);
+                Command::none()
+            }
+            Message::RemoveNotification(id) => {
+                self.notifications.remove(id);
+                Command::none()
+            }
+            Message::ToggleNotifications => {
+                self.notifications.show_history = !self.notifications.show_history;
+                Command::none()
+            }
+            Message::ClearNotifications => {
+                self.notifications.clear();
+                Command::none()
+            }
+            Message::NotificationAction(id) => match self.notifications.take_action(id) {
+                Some(action) => self.update(action),
+                None => Command::none(),
+            },
+            Message::RetrySave(id) => match self.tabs.iter().position(|tab| tab.id == id) {
+                Some(index) => self.check_and_save(index, SaveMode::Save),
+                None => Command::none(),
+            },
+            Message::LanguageOverridden(language) => {
+                self.active_tab_mut().language = Some(language.extension.to_string());
+                self.apply_indentation(self.active);
+                Command::none()
+            }
+            Message::LanguageOverrideCleared => {
+                self.active_tab_mut().language = None;
+                self.apply_indentation(self.active);
+                Command::none()
+            }
+            Message::RevealFile => match self.active_tab().path.clone() {
+                Some(path) => Command::perform(reveal::reveal(path), Message::FileRevealed),
+                None => Command::none(),
+            },
+            Message::FileRevealed(Ok(())) => Command::none(),
+            Message::FileRevealed(Err(error)) => {
+                self.notifications.error(error);
+                Command::none()
+            }
+            Message::CopyPath => match self.active_tab().path.clone() {
+                Some(path) => Command::perform(
+                    async move { reveal::absolute(&path).await.display().to_string() },
+                    Message::PathCopied,
+                ),
+                None => Command::none(),
+            },
+            Message::CopyFileName => {
+                let Some(name) = self
+                    .active_tab()
+                    .path
+                    .as_deref()
+                    .and_then(Path::file_name)
+                    .map(|name| name.to_string_lossy().into_owned())
+                else {
+                    return Command::none();
+                };
+
+                self.update(Message::PathCopied(name))
+            }
+            Message::RevertFile => {
+                let tab = self.active_tab();
+                let id = tab.id;
+
+                if tab.path.is_none() {
+                    return Command::none();
+                }
+
+                if tab.has_unsaved_changes() {
+                    self.pending = Some(Pending::Revert(id));
+                    Command::none()
+                } else {
+                    self.reload_tab(id)
+                }
+            }
+            Message::PathCopied(text) => {
+                self.notifications
+                    .info(trf("Copied \"{text}\"", &[("text", &text)]));
+                clipboard::write(text)
+            }
+            Message::RunPluginTransform(id, function) => {
+                let Some(plugin) = self.plugin(&id) else {
+                    return Command::none();
+                };
+
+                let tab = self.active_tab();
+                let (id, revision, input) = (tab.id, tab.revision, tab.selection_or_line());
+
+                Command::perform(
+                    plugins::run_transform(plugin, function, input.clone()),
+                    move |result| Message::TransformFinished(id, revision, input, result),
+                )
+            }
+            Message::TransformFinished(id, revision, input, Ok(outcome)) => {
+                self.notify(outcome.notifications);
+
+                // 插件运行期间文档或选区变化时丢弃结果，以免替换错误的文本。
+                if let Some(tab) = self.tabs.iter_mut().find(|tab| tab.id == id) {
+                    if tab.revision != revision || tab.selection_or_line() != input {
+                        self.notifications
+                            .error(Error::PluginFailed(String::from(tr(
+                                "The document changed while the plugin was running",
+                            ))));
+                    } else if tab.replace_selection(|_| Ok(outcome.text)).is_ok() {
+                        self.last_edit = Instant::now();
+                    }
+                }
+                Command::none()
+            }
+            Message::TransformFinished(_, _, _, Err(error)) => {
+                self.notifications.error(error);
+                Command::none()
+            }
+            Message::JumpToLine(line) => {
+                self.active_tab_mut().move_cursor_to(line, 0);
+                Command::none()
+            }
+            Message::ToggleProblems => {
+                self.show_problems = !self.show_problems;
+
+                // 第一次打开面板时检查当前文件所属的项目。
+                if self.show_problems && self.problems.root.is_none() {
+                    return self.update(Message::CheckProject);
+                }
+                Command::none()
+            }
+            Message::CheckProject => {
+                let project = self
+                    .active_tab()
+                    .path
+                    .as_deref()
+                    .and_then(problems::project)
+                    .or_else(|| self.problems.root.clone());
+
+                match project {
+                    Some(project) => self.check_project(project),
+                    None => Command::none(),
+                }
+            }
+            Message::ProjectChecked(result) => {
+                self.problems.is_checking = false;
+
+                match result {
+                    Ok((root, problems)) => {
+                        self.problems.root = Some(root);
+                        self.problems.problems = problems;
+                    }
+                    Err(error) => self.notifications.error(error),
+                }
+
+                // 检查期间又保存了文件时重新检查。
+                match self.problems.root.clone() {
+                    Some(root) if self.problems.is_stale => self.check_project(root),
+                    _ => Command::none(),
+                }
+            }
+            Message::ToggleSourceControl => {
+                if self.source_control.take().is_some() {
+                    return Command::none();
+                }
+
+                self.source_control = Some(SourceControl::default());
+                Command::batch(vec![
+                    self.refresh_source_control(),
+                    text_input::focus(SourceControl::id()),
+                ])
+            }
+            Message::RefreshSourceControl => self.refresh_source_control(),
+            Message::GitStatusLoaded(result) => {
+                let Some(source_control) = self.source_control.as_mut() else {
+                    return Command::none();
+                };
+
+                source_control.is_busy = false;
+
+                match result {
+                    Ok((root, files)) => {
+                        source_control.root = Some(root);
+                        source_control.files = files;
+                    }
+                    Err(error) => {
+                        source_control.root = None;
+                        source_control.files.clear();
+                        self.notifications.error(error);
+                    }
+                }
+                Command::none()
+            }
+            Message::StageFile => {
+                let path = self.active_tab().path.clone();
+
+                match (self.source_control.as_mut(), path) {
+                    (Some(source_control), Some(path)) if !source_control.is_busy => {
+                        let Some(root) = source_control.root.clone() else {
+                            return Command::none();
+                        };
+
+                        source_control.is_busy = true;
+                        Command::perform(git::stage(root, path), Message::FileStaged)
+                    }
+                    _ => Command::none(),
+                }
+            }
+            Message::CommitMessageChanged(message) => {
+                if let Some(source_control) = self.source_control.as_mut() {
+                    source_control.message = message;
+                }
+                Command::none()
+            }
+            Message::Commit => {
+                let Some(source_control) = self.source_control.as_mut() else {
+                    return Command::none();
+                };
+
+                let message = source_control.message.trim().to_string();
+
+                match source_control.root.clone() {
+                    Some(root) if !source_control.is_busy && !message.is_empty() => {
+                        source_control.is_busy = true;
+                        Command::perform(git::commit(root, message), Message::Committed)
+                    }
+                    _ => Command::none(),
+                }
+            }
+            Message::FileStaged(Ok(())) => self.refresh_source_control(),
+            Message::Committed(Ok(())) => {
+                if let Some(source_control) = self.source_control.as_mut() {
+                    source_control.message.clear();
+                }
+
+                // 提交后 HEAD 发生变化，重新读取所有标签页的修改标记。
+                let mut commands = vec![self.refresh_source_control()];
+                commands.extend(self.tabs.iter().map(load_head));
+
+                Command::batch(commands)
+            }
+            Message::FileStaged(Err(error)) | Message::Committed(Err(error)) => {
+                if let Some(source_control) = self.source_control.as_mut() {
+                    source_control.is_busy = false;
+                }
+
+                self.notifications.error(error);
+                Command::none()
+            }
+            Message::ToggleTerminal => {
+                self.show_terminal = !self.show_terminal;
+
+                if self.show_terminal {
+                    text_input::focus(Terminal::input_id())
+                } else {
+                    Command::none()
+                }
+            }
+            Message::TerminalInputChanged(input) => {
+                self.terminal.input = input;
+                Command::none()
+            }
+            Message::TerminalSubmitted => {
+                let directory = self.working_directory();
+                self.terminal.submit(directory);
+                Command::none()
+            }
+            Message::TerminalKill => {
+                self.terminal.kill();
+                Command::none()
+            }
+            Message::TerminalClear => {
+                self.terminal.clear();
+                Command::none()
+            }
+            Message::TerminalOutput(id, event) => {
+                self.terminal.update(id, event);
+
+                // 输出新内容后滚动到底部。
+                scrollable::snap_to(Terminal::output_id(), scrollable::RelativeOffset::END)
+            }
+            Message::RunFile => {
+                let tab = self.active_tab();
+
+                // 运行磁盘上的文件，有未保存的修改时先保存。
+                if tab.is_dirty && tab.path.is_some() && !tab.is_read_only {
+                    self.run_after_save = Some(tab.id);
+                    self.check_and_save(self.active, SaveMode::Save)
+                } else {
+                    self.run_file(tab.id)
+                }
+            }
+            Message::Lsp(lsp::Event::Connected(workspace, connection)) => {
+                for path in self.lsp.connected(workspace, connection) {
+                    if let Some(index) = self
+                        .tabs
+                        .iter()
+                        .position(|tab| tab.path.as_ref() == Some(&path))
+                    {
+                        self.sync_document(index);
+                    }
+                }
+                Command::none()
+            }
+            Message::Lsp(lsp::Event::Diagnostics(path, mut diagnostics)) => {
+                // 服务器使用 UTF-16 编码单元计数列，按文档当前的内容转换为字符位置。
+                if let Some(tab) = self
+                    .tabs
+                    .iter()
+                    .find(|tab| tab.path.as_ref() == Some(&path))
+                {
+                    for diagnostic in &mut diagnostics {
+                        if let Some(line) = tab.context.line(diagnostic.line) {
+                            diagnostic.columns = lsp::char_column(&line, diagnostic.columns.start)
+                                ..lsp::char_column(&line, diagnostic.columns.end);
+                        }
+                    }
+                }
+
+                self.lsp.set_diagnostics(path, diagnostics);
+                Command::none()
+            }
+            Message::Lsp(lsp::Event::Completions(items)) => {
+                if !items.is_empty() {
+                    self.completion = Some(completion::Completion::new(self.active_tab(), items));
+                }
+                Command::none()
+            }
+            Message::Lsp(lsp::Event::Hover(hover)) => {
+                self.hover = Some((self.active_tab().id, hover));
+                Command::none()
+            }
+            Message::Lsp(lsp::Event::Disconnected(workspace, reason)) => {
+                self.lsp.disconnected(workspace);
+
+                // 没有安装语言服务器时不提示。
+                if let Some(reason) = reason {
+                    self.notifications
+                        .error(Error::LanguageServerFailed(reason));
+                }
+                Command::none()
+            }
+            Message::LspTick => {
+                if self.lsp_is_stale() && self.last_edit.elapsed() >= LSP_DELAY {
+                    self.sync_document(self.active);
+                }
+                Command::none()
+            }
+            Message::TriggerCompletion => {
+                if let Some((path, position)) = self.cursor_position() {
+                    self.sync_document(self.active);
+                    self.lsp.request_completion(&path, position);
+                }
+                Command::none()
+            }
+            Message::CompletionAccepted(item) => {
+                self.completion = None;
+                self.last_edit = Instant::now();
+                completion::accept(self.active_tab_mut(), &item);
+                Command::none()
+            }
+            Message::ShowHover => {
+                if let Some((path, position)) = self.cursor_position() {
+                    self.sync_document(self.active);
+                    self.lsp.request_hover(&path, position);
+                }
+                Command::none()
+            }
+            Message::LineEndingSelected(line_ending) => {
+                let tab = self.active_tab_mut();
+
+                if tab.line_ending != line_ending {
+                    tab.line_ending = line_ending;
+                    tab.is_dirty = true;
+                }
+                Command::none()
+            }
+        }
+    }
+    // 创建订阅来监听键盘、窗口事件以及自动保存的定时器。
+    fn subscription(&self) -> Subscription<Self::Message> {
+        // 按键交给快捷键映射在 update 中解析。
+        let keys = keyboard::on_key_press(|key_code, modifiers| {
+            Some(Message::KeyPressed(key_code, modifiers))
+        });
+
+        let events = subscription::events_with(|event, _status| match event {
+            Event::Window(window::Event::CloseRequested) => Some(Message::CloseRequested),
+            Event::Window(window::Event::FileDropped(path)) => Some(Message::FileDropped(path)),
+            Event::Keyboard(keyboard::Event::ModifiersChanged(modifiers)) => {
+                Some(Message::ModifiersChanged(modifiers))
+            }
+            // 复制和剪切由获得焦点的控件处理，这里只记录到剪贴板历史中。
+            Event::Keyboard(keyboard::Event::KeyPressed {
+                key_code: key_code @ (keyboard::KeyCode::C | keyboard::KeyCode::X),
+                modifiers,
+            }) if modifiers.command() => {
+                Some(Message::ClipboardCopied(key_code == keyboard::KeyCode::X))
+            }
+            _ => None,
+        });
+
+        let autosave = if self.preferences.autosave {
+            time::every(Duration::from_secs(u64::from(
+                self.preferences.autosave_interval,
+            )))
+            .map(|_| Message::Autosave)
+        } else {
+            Subscription::none()
+        };
+
+        let preview = if self.preview_is_stale() {
+            time::every(Duration::from_millis(100)).map(|_| Message::PreviewTick)
+        } else {
+            Subscription::none()
+        };
+
+        let watcher = watcher::watch(
+            self.tabs
+                .iter()
+                .filter_map(|tab| tab.path.clone())
+                .collect(),
+            Message::FileChanged,
+        );
+
+        // 其他窗口修改了偏好设置后随之更新主题和设置。
+        let preferences = watcher::watch(
+            config_file("preferences.toml").into_iter().collect(),
+            |_| Message::PreferencesChanged,
+        );
+
+        let changes = if self.changes_are_stale() {
+            time::every(Duration::from_millis(100)).map(|_| Message::ChangesTick)
+        } else {
+            Subscription::none()
+        };
+
+        let loading = Subscription::batch(self.loading.iter().map(loader::load));
+
+        // 跟随系统外观时定时检查系统的设置，切换深色模式后随之切换主题。
+        let appearance = if self.preferences.appearance == Appearance::System {
+            time::every(APPEARANCE_INTERVAL).map(|_| Message::AppearanceTick)
+        } else {
+            Subscription::none()
+        };
+
+        // 等待确认是否恢复时不写入日志，以免覆盖上一次留下的修改。新窗口也不写入，以免覆盖主窗口的日志。
+        let journal = if !self.is_secondary
+            && self.recovery.is_empty()
+            && self.unsaved_revisions() != self.journaled
+        {
+            time::every(JOURNAL_INTERVAL).map(|_| Message::JournalTick)
+        } else {
+            Subscription::none()
+        };
+
+        let lsp_tick = if self.lsp_is_stale() {
+            time::every(Duration::from_millis(100)).map(|_| Message::LspTick)
+        } else {
+            Subscription::none()
+        };
+
+        let toasts = if self.notifications.has_toasts() {
+            time::every(Duration::from_millis(500)).map(|_| Message::NotificationTick)
+        } else {
+            Subscription::none()
+        };
+
+        // 隐藏终端面板不会结束正在运行的命令。
+        let terminal = self.terminal.subscription();
+
+        Subscription::batch(vec![
+            keys,
+            events,
+            autosave,
+            preview,
+            changes,
+            watcher,
+            preferences,
+            loading,
+            appearance,
+            journal,
+            terminal,
+            lsp_tick,
+            toasts,
+            self.lsp.subscription(),
+        ])
+    }
+    // 创建应用程序的 UI。
+    fn view(&self) -> Element<'_, Message> {
+        let tab = self.active_tab();
+
+        let controls = row![
+            action(
+                symbol('☰'),
+                tr("Toggle Sidebar"),
+                Some(Message::ToggleSidebar)
+            ),
+            action(new_icon(), tr("New File"), Some(Message::New)),
+            action(open_icon(), tr("Open File"), Some(Message::Open)),
+            if tab.is_read_only {
+                action(save_icon(), tr("Save a Copy..."), Some(Message::SaveAs))
+            } else {
+                action(
+                    save_icon(),
+                    tr("Save File"),
+                    tab.is_dirty.then_some(Message::Save),
+                )
+            },
+            action(
+                symbol('↶'),
+                tr("Undo"),
+                tab.history.can_undo().then_some(Message::Undo)
+            ),
+            action(
+                symbol('↷'),
+                tr("Redo"),
+                tab.history.can_redo().then_some(Message::Redo)
+            ),
+            if self.panes.len() > 1 {
+                action(symbol('▣'), tr("Close Split"), Some(Message::CloseSplit))
+            } else {
+                action(
+                    symbol('◫'),
+                    tr("Split Editor"),
+                    Some(Message::Split(pane_grid::Axis::Vertical)),
+                )
+            },
+            action(
+                symbol('↵'),
+                tr("Toggle Word Wrap"),
+                Some(Message::ToggleWordWrap)
+            ),
+            action(
+                symbol('±'),
+                tr("Show Changes"),
+                tab.path.is_some().then_some(Message::ShowChanges)
+            ),
+            action(
+                symbol('⟲'),
+                tr("Local History"),
+                tab.path.is_some().then_some(Message::ToggleLocalHistory)
+            ),
+            action(symbol('⎙'), tr("Print to PDF"), Some(Message::Print)),
+            action(
+                symbol('✎'),
+                tr("Scratchpad"),
+                Some(Message::ToggleScratchpad)
+            ),
+            action(
+                symbol('▶'),
+                tr("Run File"),
+                tab.path
+                    .as_deref()
+                    .is_some_and(runner::can_run)
+                    .then_some(Message::RunFile)
+            ),
+            horizontal_space(Length::Fill),
+            pick_list(
+                syntax::Theme::all(),
+                Some(self.theme),
+                Message::ThemeSelected
+            ),
+            action(symbol('⚙'), tr("Settings"), Some(Message::ToggleSettings)),
+        ]
+        .spacing(10);
+
+        let tabs = tab_bar::tab_bar(
+            self.tabs
+                .iter()
+                .enumerate()
+                .map(|(index, tab)| tab_button(tab, index == self.active, index))
+                .collect(),
+            5.0,
+            Message::TabMoved,
+            Message::TabTornOff,
+        );
+
+        let input: Element<_> = if self.panes.len() > 1 {
+            pane_grid(&self.panes, |_pane, view, _is_maximized| {
+                pane_grid::Content::new(match view {
+                    Pane::Primary => self.editor(&tab.context, Message::Edit),
+                    Pane::Secondary => self.editor(
+                        tab.split.as_ref().unwrap_or(&tab.context),
+                        Message::SplitEdit,
+                    ),
+                })
+            })
+            .on_resize(10, Message::PaneResized)
+            .spacing(10)
+            .into()
+        } else {
+            self.editor(&tab.context, Message::Edit)
+        };
+
+        let status_bar = {
+            let status = match (tab.path.as_deref().and_then(Path::to_str), &tab.url) {
+                (Some(path), _) => text(path).size(14),
+                (None, Some(url)) => text(url).size(14),
+                (None, None) => match &tab.remote {
+                    Some(location) => text(location).size(14),
+                    None => text(tr("New File")),
+                },
+            };
+
+            // 显示通知历史中的通知数，点击时打开或关闭通知历史。
+            let bell = button(text(format!("🔔 {}", self.notifications.len())).size(14))
+                .on_press(Message::ToggleNotifications)
+                .padding([0, 5])
+                .style(theme::Button::Text);
+
+            // 显示第一个正在加载的文件的进度，尚未打开文件时只显示文件名。
+            let loading: Element<_> = match self.loading.first() {
+                Some(loading) => {
+                    let name = loading
+                        .path
+                        .file_name()
+                        .and_then(|name| name.to_str())
+                        .unwrap_or_default();
+
+                    let progress: Element<_> = match loading.progress() {
+                        Some(progress) => progress_bar(0.0..=1.0, progress)
+                            .width(150)
+                            .height(14)
+                            .into(),
+                        None => text(tr("Opening...")).size(14).into(),
+                    };
+
+                    let others = match self.loading.len() - 1 {
+                        0 => String::new(),
+                        count => format!("(+{count})"),
+                    };
+
+                    row![
+                        text(trf("Loading {name}", &[("name", &name)])).size(14),
+                        progress,
+                        text(others).size(14),
+                        button(text(tr("Cancel")).size(14))
+                            .on_press(Message::CancelLoading(loading.id))
+                            .padding([0, 5])
+                    ]
+                    .spacing(5)
+                    .into()
+                }
+                None => Space::with_width(0).into(),
+            };
+
+            // 显示远程连接的状态，连接后可以断开。
+            let connection: Element<_> = match &self.connection {
+                Some(status @ remote::Status::Connected(_)) => row![
+                    text(status).size(14),
+                    button(text(tr("Disconnect")).size(14))
+                        .on_press(Message::Disconnect)
+                        .padding([0, 5])
+                ]
+                .spacing(5)
+                .into(),
+                Some(status) => text(status).size(14).into(),
+                None => Space::with_width(0).into(),
+            };
+
+            let mode = if let Some(prefix) = self.prefix {
+                text(format!("{prefix} -")).size(14)
+            } else if self.preferences.vim_mode {
+                text(self.vim.status()).size(14)
+            } else {
+                text("")
+            };
+
+            // CSV 和 TSV 文件可以在表格和文本之间切换。
+            let table: Element<_> = if table::delimiter(&tab.extension()).is_some() {
+                let label = if self.table.as_ref().is_some_and(|table| table.tab == tab.id) {
+                    tr("Text View")
+                } else {
+                    tr("Table View")
+                };
+
+                button(text(label).size(14))
+                    .on_press(Message::ToggleTable)
+                    .padding([0, 5])
+                    .into()
+            } else {
+                Space::with_width(0).into()
+            };
+
+            let macro_recording = if self.macros.is_recording() {
+                text(tr("● Recording macro"))
+                    .size(14)
+                    .style(diagnostics::Severity::Error.color())
+            } else {
+                text("")
+            };
+
+            let valid_json = if self.valid_json == Some((tab.id, tab.revision)) {
+                text(tr("✓ Valid JSON")).size(14)
+            } else {
+                text("")
+            };
+
+            let lock = if tab.is_read_only {
+                text(tr("🔒 Read-only")).size(14)
+            } else {
+                text("")
+            };
+
+            // 显示光标所在行最严重的一条诊断。
+            let diagnostic = {
+                let (line, _) = tab.context.cursor_position();
+
+                match self
+                    .diagnostics()
+                    .into_iter()
+                    .filter(|diagnostic| diagnostic.line == line)
+                    .min_by_key(|diagnostic| diagnostic.severity)
+                {
+                    Some(diagnostic) => text(diagnostic.message.lines().next().unwrap_or_default())
+                        .size(14)
+                        .style(diagnostic.severity.color()),
+                    None => text(""),
+                }
+            };
+
+            let position = {
+                let (line, column) = tab.context.cursor_position();
+
+                match self
+                    .cursors
+                    .as_ref()
+                    .filter(|cursors| cursors.tab == tab.id)
+                {
+                    Some(cursors) if !cursors.is_empty() => text(trf(
+                        "{line}:{column} ({count} cursors)",
+                        &[
+                            ("line", &(line + 1)),
+                            ("column", &(column + 1)),
+                            ("count", &cursors.count()),
+                        ],
+                    )),
+                    _ => text(format!("{}:{}", line + 1, column + 1)),
+                }
+            };
+
+            let statistics = {
+                let selected = tab
+                    .context
+                    .selection()
+                    .map(|selection| {
+                        trf(
+                            " ({count} selected)",
+                            &[("count", &selection.chars().count())],
+                        )
+                    })
+                    .unwrap_or_default();
+
+                text(trf(
+                    "{words} words, {chars} chars{selected}",
+                    &[
+                        ("words", &tab.statistics.words()),
+                        ("chars", &tab.statistics.characters()),
+                        ("selected", &selected),
+                    ],
+                ))
+                .size(14)
+            };
+
+            let zoom = text(format!("{}%", self.preferences.zoom)).size(14);
+
+            let encoding = pick_list(
+                Encoding::all(),
+                Some(tab.encoding),
+                Message::EncodingSelected,
+            )
+            .text_size(14);
+
+            let line_ending = pick_list(
+                LineEnding::ALL,
+                Some(tab.line_ending),
+                Message::LineEndingSelected,
+            )
+            .text_size(14);
+
+            // 显示高亮器使用的语言，没有扩展名的文件可以手动选择，选择后可以恢复自动推断。
+            let language: Element<_> = {
+                let picker = pick_list(
+                    syntax::languages(),
+                    Some(syntax::language(&tab.extension())),
+                    Message::LanguageOverridden,
+                )
+                .text_size(14);
+
+                if tab.language.is_some() {
+                    row![
+                        picker,
+                        button(text(tr("Auto")).size(14))
+                            .on_press(Message::LanguageOverrideCleared)
+                            .padding([0, 5])
+                    ]
+                    .spacing(5)
+                    .into()
+                } else {
+                    picker.into()
+                }
+            };
+
+            // 编码检测结果不确定时提示用户手动选择。
+            let encoding_hint = if tab.encoding_is_uncertain {
+                text(tr("Encoding uncertain, pick one:")).size(14)
+            } else {
+                text("")
+            };
+
+            row![
+                mode,
+                macro_recording,
+                lock,
+                valid_json,
+                diagnostic,
+                status,
+                horizontal_space(Length::Fill),
+                loading,
+                connection,
+                table,
+                encoding_hint,
+                language,
+                encoding,
+                line_ending,
+                statistics,
+                zoom,
+                position,
+                bell
+            ]
+            .spacing(10)
+        };
+
+        // 存在等待确认的操作时，用确认对话框替换编辑区域。
+        // 正在查看当前文档的修改时，用差异替换编辑器。
+        // 当前文档正在与另一个文件比较时，用两者的差异替换编辑器。
+        // 正在查看当前文档的本地历史时，用历史版本的列表和差异替换编辑器。
+        let diff = self.diff.as_ref().filter(|(id, _)| *id == tab.id);
+        let comparison = self
+            .comparison
+            .as_ref()
+            .filter(|comparison| comparison.involves(tab.id));
+        let history = self
+            .local_history
+            .as_ref()
+            .filter(|history| history.tab == tab.id);
+
+        let grid = self
+            .table
+            .as_ref()
+            .filter(|table| table.tab == tab.id)
+            .zip(table::delimiter(&tab.extension()));
+
+        let body = match (self.pending, self.screen, diff, comparison, history) {
+            (Some(pending), _, _, _, _) => self.confirmation(pending),
+            (None, Screen::Settings, _, _, _) => container(scrollable(
+                column![
+                    preferences::view(
+                        &self.preferences,
+                        self.theme,
+                        tab.extension(),
+                        tab.rules,
+                        &self.spelling.available,
+                        self.clipboard_history.is_empty()
+                    ),
+                    custom_theme::view(
+                        &self.preferences.custom_themes,
+                        self.preferences.custom_theme.as_deref(),
+                        self.theme_editor.as_ref()
+                    ),
+                    plugins::view(&self.plugins, &self.preferences.disabled_plugins),
+                    self.keymap.view(self.recording)
+                ]
+                .spacing(20)
+                .padding(20),
+            ))
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .into(),
+            (None, Screen::Editor, Some((_, diff)), _, _) => self.changes(diff),
+            (None, Screen::Editor, None, Some(comparison), _) => self.comparison(comparison),
+            (None, Screen::Editor, None, None, Some(history)) => {
+                history.view(tab.title(), self.diff_layout)
+            }
+            // 二进制文件用十六进制编辑器代替文本编辑器，CSV 和 TSV 文件可以显示为表格。
+            (None, Screen::Editor, None, None, None) => match (&tab.hex, grid) {
+                (Some(hex), _) => hex.view(tab.is_read_only),
+                (None, Some((grid, delimiter))) => grid.view(tab, delimiter),
+                (None, None) => {
+                    // 文件在 git 仓库中时，在编辑器左侧显示相对于 HEAD 的修改标记。
+                    let input = if tab.head.is_some() {
+                        row![git::gutter(&tab.changes, tab.context.line_count()), input]
+                            .spacing(2)
+                            .into()
+                    } else {
+                        input
+                    };
+
+                    match &self.preview {
+                        Some(preview) if tab.is_markdown() && preview.tab == tab.id => {
+                            row![input, preview.view()].spacing(10).into()
+                        }
+                        _ => input,
+                    }
+                }
+            },
+        };
+
+        let mut editor = column![tabs].spacing(10);
+
+        if self.pending.is_none() && self.screen == Screen::Editor {
+            editor = editor.push(self.breadcrumbs(tab));
+        }
+
+        if let Some(palette) = &self.palette {
+            editor = editor.push(palette.view());
+        }
+
+        if let Some(quick_open) = &self.quick_open {
+            editor = editor.push(quick_open.view());
+        }
+
+        if let Some(goto_line) = &self.goto_line {
+            editor = editor.push(goto_line.view(tab.context.line_count()));
+        }
+
+        if let Some(open_url) = &self.open_url {
+            editor = editor.push(open_url.view());
+        }
+
+        if let Some(play_macro) = &self.play_macro {
+            editor = editor.push(play_macro.view(self.macros.actions().len()));
+        }
+
+        if let Some(open_remote) = &self.open_remote {
+            editor = editor.push(open_remote.view());
+        }
+
+        if let Some(replace) = &self.replace {
+            editor = editor.push(replace.view(tab, self.replace_scope()));
+        }
+
+        if let Some(completion) = self
+            .completion
+            .as_ref()
+            .filter(|completion| completion.tab == tab.id)
+        {
+            // 编辑器不提供光标在屏幕上的位置，弹窗显示在编辑器上方，并按单词所在的列水平对齐。
+            if let Some(popup) = completion.view(&completion::word_before_cursor(tab)) {
+                let column = tab.context.line(completion.line).map_or(0, |line| {
+                    line.get(..completion.column)
+                        .unwrap_or_default()
+                        .chars()
+                        .count()
+                });
+
+                editor = editor.push(row![
+                    horizontal_space(column as f32 * CHARACTER_WIDTH + EDITOR_PADDING),
+                    popup
+                ]);
+            }
+        }
+
+        if let Some((_, hover)) = self.hover.as_ref().filter(|(id, _)| *id == tab.id) {
+            editor = editor.push(
+                container(text(hover).size(14))
+                    .width(Length::Fill)
+                    .padding(10)
+                    .style(theme::Container::Box),
+            );
+        }
+
+        let editor = editor.push(body);
+
+        let mut main = row![].spacing(10);
+
+        if self.show_sidebar {
+            main = main.push(match &self.file_tree {
+                Some(file_tree) => file_tree.view(),
+                None => container(
+                    column![
+                        button(text(tr("Open Folder"))).on_press(Message::OpenFolder),
+                        button(text(tr("Open Workspace"))).on_press(Message::OpenWorkspace)
+                    ]
+                    .spacing(10),
+                )
+                .width(220)
+                .height(Length::Fill)
+                .center_x()
+                .style(theme::Container::Box)
+                .into(),
+            });
+        }
+
+        if let Some(search) = &self.search {
+            main = main.push(search.view());
+        }
+
+        if let Some(source_control) = &self.source_control {
+            main = main.push(source_control.view(tab.path.as_deref()));
+        }
+
+        if self.show_problems {
+            main = main.push(self.problems.view());
+        }
+
+        let mut main = main.push(editor);
+
+        if self.show_outline {
+            let (line, _) = tab.context.cursor_position();
+            main = main.push(symbols::view(&tab.outline(), line));
+        }
+
+        let mut content = column![controls, main].spacing(10);
+
+        if self.show_terminal {
+            content = content.push(self.terminal.view());
+        }
+
+        if self.show_console {
+            content = content.push(self.console.view());
+        }
+
+        // 打开对话框时按键交给对话框中的输入框，不拦截快捷键。
+        let content = shortcuts::shortcuts(
+            container(content.push(status_bar)).padding(10),
+            (!self.has_dialog()).then_some(&self.keymap),
+            self.prefix,
+        );
+
+        notifications::toasts(content, self.notifications.view())
+    }
+    // 返回界面的缩放比例。
+    //
+    // 这个版本的文本编辑器不能单独设置字号，因此缩放整个窗口的内容。
+    fn scale_factor(&self) -> f64 {
+        f64::from(self.preferences.zoom) / 100.0
+    }
+
+    // 返回当前应用程序的主题：编辑自定义主题时实时预览正在编辑的颜色，其次是选择的自定义主题，
+    // 都没有时由界面外观决定，与高亮主题无关。
+    fn theme(&self) -> Theme {
+        let custom = self
+            .theme_editor
+            .as_ref()
+            .and_then(|editor| editor.draft.theme())
+            .or_else(|| self.preferences.custom_theme().and_then(CustomTheme::theme));
+
+        match custom {
+            Some(theme) => theme,
+            None if self.is_dark() => Theme::Dark,
+            None => Theme::Light,
+        }
+    }
+}
+
+impl Editor {
+    // 判断界面是否使用深色外观，使用自定义主题时按主题的背景颜色判断。
+    fn is_dark(&self) -> bool {
+        match self
+            .preferences
+            .custom_theme()
+            .and_then(CustomTheme::palette)
+        {
+            Some(palette) => custom_theme::is_dark(palette.background),
+            None => self.preferences.appearance.is_dark(self.system_is_dark),
+        }
+    }
+
+    // 使用新的偏好设置，更新界面语言、主题、缩进和拼写检查的词典。
+    fn apply_preferences(&mut self, preferences: Preferences) -> Command<Message> {
+        self.preferences = preferences;
+        i18n::set_language(self.preferences.language);
+        self.apply_theme();
+        self.apply_indentation_to_all();
+        self.clipboard_history
+            .truncate(self.preferences.clipboard_history);
+        self.load_dictionary()
+    }
+
+    // 按偏好设置选择高亮主题，没有选择或找不到时使用与界面外观相配的默认主题。
+    fn apply_theme(&mut self) {
+        self.theme = self
+            .preferences
+            .theme
+            .as_deref()
+            .and_then(syntax::Theme::find)
+            .unwrap_or_else(|| syntax::Theme::default_theme(self.is_dark()));
+    }
+
+    // 返回指定标识的已启用插件。
+    fn plugin(&self, id: &str) -> Option<plugins::Plugin> {
+        self.plugins
+            .iter()
+            .find(|plugin| plugin.id == id && !self.preferences.disabled_plugins.contains(id))
+            .cloned()
+    }
+
+    // 在后台线程中对当前文档运行脚本，command 为控制台中显示的命令。
+    fn run_script(&self, source: String, command: String) -> Command<Message> {
+        let tab = self.active_tab();
+        let (id, revision, selection) = (tab.id, tab.revision, tab.context.selection());
+
+        Command::perform(
+            scripting::run(source, tab.context.text(), selection.clone()),
+            move |outcome| Message::ScriptFinished(id, revision, selection, command, outcome),
+        )
+    }
+
+    // 显示脚本的输出和通知，revision 和 selection 为开始运行时文档的版本号和选中的文本。
+    //
+    // 脚本修改了整个文档时替换文档的内容，否则用脚本的结果替换选中的文本，两者都作为一次可撤销的编辑。
+    // 脚本运行期间文档或选区变化时丢弃脚本的修改，以免覆盖新的输入。
+    fn finish_script(
+        &mut self,
+        id: usize,
+        revision: usize,
+        selection: Option<String>,
+        command: String,
+        outcome: scripting::Outcome,
+    ) -> Command<Message> {
+        self.console.report(command, &outcome);
+
+        if let Some(tab) = self.tabs.iter_mut().find(|tab| tab.id == id) {
+            let changes = outcome.text.is_some() || outcome.selection.is_some();
+
+            if changes && (tab.revision != revision || tab.context.selection() != selection) {
+                self.notifications
+                    .error(Error::ScriptFailed(String::from(tr(
+                        "The document changed while the script was running",
+                    ))));
+            } else if !tab.is_read_only {
+                if let Some(text) = outcome.text {
+                    tab.replace_text(text);
+                    self.last_edit = Instant::now();
+                } else if let Some(selection) = outcome.selection {
+                    let _ = tab.replace_selection(|_| Ok(selection));
+                    self.last_edit = Instant::now();
+                }
+            }
+        }
+
+        self.notify(outcome.notifications);
+        scrollable::snap_to(
+            scripting::Console::output_id(),
+            scrollable::RelativeOffset::END,
+        )
+    }
+
+    // 在状态栏中显示插件或脚本的通知，有多条通知时显示最后一条。
+    fn notify(&mut self, notifications: Vec<String>) {
+        for notification in notifications {
+            self.notifications.info(notification);
+        }
+    }
+
+    // 返回下一个临时缓冲区的编号，比打开的临时缓冲区中最大的编号大一。
+    fn next_scratch_number(&self) -> usize {
+        self.tabs
+            .iter()
+            .filter_map(|tab| tab.scratch)
+            .max()
+            .map_or(1, |number| number + 1)
+    }
+
+    // 返回当前激活的标签页。
+    fn active_tab(&self) -> &Tab {
+        &self.tabs[self.active]
+    }
+
+    // 返回当前激活的标签页的可变引用。
+    fn active_tab_mut(&mut self) -> &mut Tab {
+        &mut self.tabs[self.active]
+    }
+
+    // 分配新的标识并打开一个标签页，随后切换到该标签页。
+    fn open_tab(&mut self, create: impl FnOnce(usize) -> Tab) {
+        let id = self.next_tab_id;
+        self.next_tab_id += 1;
+
+        self.tabs.push(create(id));
+        self.active = self.tabs.len() - 1;
+        self.apply_indentation(self.active);
+    }
+
+    // 根据偏好设置更新标签页的缩进规则，.editorconfig 中的属性优先于偏好设置。
+    fn apply_indentation(&mut self, index: usize) {
+        let tab = &mut self.tabs[index];
+
+        tab.rules = indent::Rules::with_preferences(
+            &tab.extension(),
+            self.preferences.tab_width,
+            &self.preferences.indentation,
+        );
+        tab.rules.apply(tab.editorconfig.indentation());
+    }
+
+    // 偏好设置变化后更新所有标签页的缩进规则。
+    fn apply_indentation_to_all(&mut self) {
+        for index in 0..self.tabs.len() {
+            self.apply_indentation(index);
+        }
+    }
+
+    // 按偏好设置返回当前文档用于高亮的语法树，使用 syntect 高亮时返回 None。
+    fn syntax_tree(&self) -> Option<syntax_tree::Snapshot> {
+        let tab = self.active_tab();
+
+        match self.preferences.highlighter(&tab.extension()) {
+            syntax::Backend::TreeSitter => tab.syntax_tree(),
+            syntax::Backend::Syntect => None,
+        }
+    }
+
+    // 创建显示当前文档的文本编辑器。
+    fn editor<'a>(
+        &'a self,
+        document: &'a Document,
+        on_edit: fn(text_editor::Action) -> Message,
+    ) -> Element<'a, Message> {
+        let content: &text_editor::Content = document;
+
+        let editor = move || -> Element<'a, Message> {
+            text_editor(content)
+                .on_edit(on_edit)
+                .font(self.preferences.font())
+                .highlight::<cursors::Highlighter>(
+                    cursors::Settings {
+                        diagnostics: diagnostics::Settings {
+                            brackets: brackets::Settings {
+                                syntax: syntax::Settings {
+                                    theme: self.theme,
+                                    extension: self.active_tab().extension(),
+                                    tree: self.syntax_tree(),
+                                },
+                                brackets: if self.preferences.match_brackets {
+                                    brackets::find_match(content)
+                                } else {
+                                    None
+                                },
+                            },
+                            diagnostics: self.diagnostics(),
+                        },
+                        marks: self.cursor_marks(),
+                    },
+                    cursors::format,
+                )
+                .into()
+        };
+
+        // 编辑器总是在可用的宽度内换行，关闭自动换行时把它放进足够宽的横向滚动区域中。
+        let width = self.active_tab().statistics.longest_line() as f32 * CHARACTER_WIDTH
+            + EDITOR_PADDING * 2.0;
+
+        responsive(move |size| {
+            let folds: Vec<_> = document.folds().collect();
+            let line_numbers = gutter::LineNumbers {
+                line_count: document.line_count()
+                    + folds.iter().map(|(_, count)| count).sum::<usize>(),
+                current: document.cursor_position().0,
+                folds: folds.clone(),
+            };
+            let bookmarks = gutter::Bookmarks {
+                lines: self
+                    .active_tab()
+                    .bookmarks
+                    .iter()
+                    .map(|line| document.display_line(*line))
+                    .collect(),
+            };
+            let fold_markers = gutter::Folds {
+                regions: folding::regions(document.rope(), &self.active_tab().extension())
+                    .into_iter()
+                    .map(|region| region.start)
+                    .collect(),
+                folded: folds.into_iter().map(|(line, _)| line).collect(),
+            };
+            let gutter_width = if self.preferences.line_numbers {
+                bookmarks.width() + line_numbers.width() + fold_markers.width()
+            } else {
+                bookmarks.width() + fold_markers.width()
+            };
+            let minimap_width = if self.preferences.minimap {
+                minimap::WIDTH
+            } else {
+                0.0
+            };
+
+            // 根据可用的空间估算编辑器的尺寸，用于跟踪编辑器的滚动位置。
+            let available = size.width - gutter_width - minimap_width;
+            let metrics = gutter::Metrics {
+                rows: ((size.height - EDITOR_PADDING * 2.0) / LINE_HEIGHT).max(1.0) as usize,
+                columns: self.preferences.word_wrap.then(|| {
+                    ((available - EDITOR_PADDING * 2.0) / CHARACTER_WIDTH).max(1.0) as usize
+                }),
+            };
+            document.set_metrics(metrics);
+
+            let lines = document
+                .viewport()
+                .lines(document.rope(), metrics, size.height);
+
+            // 在编辑器的文字之下绘制当前行的背景、缩进参考线、空白字符和列标尺。
+            let current_line = lines
+                .iter()
+                .find(|line| line.number == line_numbers.current)
+                .filter(|_| self.preferences.highlight_current_line)
+                .map(|line| (line.y, line.height));
+            let guides = self
+                .preferences
+                .indent_guides
+                .then(|| self.indent_guides(document, &lines));
+            let whitespace = self.whitespace_marks(document, &lines, metrics);
+            let misspellings = if self.preferences.spell_check {
+                self.misspellings(document, &lines, metrics)
+            } else {
+                Vec::new()
+            };
+            let decorated = decorations::decorations(
+                editor(),
+                current_line,
+                guides,
+                whitespace,
+                self.preferences.ruler,
+                misspellings.iter().map(|(bounds, _)| *bounds).collect(),
+            );
+
+            // 右键点击拼写错误的单词时，菜单中显示拼写建议。
+            let targets = misspellings
+                .into_iter()
+                .map(|(bounds, misspelling)| {
+                    (bounds, context_menu::Target::Misspelling(misspelling))
+                })
+                .collect();
+            let menu = self
+                .context_menu
+                .as_ref()
+                .filter(|menu| menu.tab == self.active_tab().id);
+            let decorated =
+                context_menu::context_menu(decorated, targets, menu, Message::ContextMenu);
+
+            let editor = if self.preferences.word_wrap {
+                decorated
+            } else {
+                scrollable(container(decorated).width(width.max(available)))
+                    .direction(scrollable::Direction::Horizontal(
+                        scrollable::Properties::default(),
+                    ))
+                    .height(Length::Fill)
+                    .into()
+            };
+
+            let mut view = row![];
+
+            // 书签栏和折叠标记总是显示，行号显示在两者之间。
+            let mut lanes: Vec<Box<dyn Lane>> = vec![Box::new(bookmarks)];
+
+            if self.preferences.line_numbers {
+                lanes.push(Box::new(line_numbers));
+            }
+
+            lanes.push(Box::new(fold_markers));
+
+            view = view.push(gutter::view(lines, lanes));
+
+            view = view.push(editor);
+
+            if self.preferences.minimap {
+                view = view.push(minimap::view(
+                    document.rope(),
+                    self.active_tab().revision,
+                    document.viewport(),
+                    metrics,
+                    on_edit,
+                ));
+            }
+
+            view.into()
+        })
+        .into()
+    }
+
+    // 返回当前标签页中列选择或多个光标选中的文本，每个选区一行。
+    fn column_selection(&self) -> Option<String> {
+        let tab = self.active_tab();
+
+        self.cursors
+            .as_ref()
+            .filter(|cursors| cursors.tab == tab.id)
+            .and_then(|cursors| cursors.selections(tab))
+    }
+
+    // 把列选择的文本写入剪贴板和剪贴板历史，之后粘贴同样的文本时按列粘贴。
+    fn copy_block(&mut self, block: String) -> Command<Message> {
+        self.clipboard_history
+            .push(block.clone(), self.preferences.clipboard_history);
+        self.block = Some(block.clone());
+
+        clipboard::write(block)
+    }
+
+    // 返回可见的各行的缩进参考线，颜色取自当前的高亮主题。
+    //
+    // 空白行使用上下两侧最近的非空行中较小的缩进，使参考线在代码块中的空行处不中断。
+    fn indent_guides(&self, document: &Document, lines: &[gutter::Line]) -> decorations::Guides {
+        let rope = document.rope();
+        let rules = &self.active_tab().rules;
+
+        let width = |number: usize| {
+            let line: Cow<str> = rope.line(number).into();
+            rules.leading_width(&line)
+        };
+
+        let nearest = |numbers: &mut dyn Iterator<Item = usize>| {
+            numbers
+                .take(GUIDE_SEARCH_LINES)
+                .find_map(&width)
+                .unwrap_or(0)
+        };
+
+        decorations::Guides {
+            lines: lines
+                .iter()
+                .filter(|line| line.number < rope.len_lines())
+                .map(|line| {
+                    let width = width(line.number).unwrap_or_else(|| {
+                        let above = nearest(&mut (0..line.number).rev());
+                        let below = nearest(&mut (line.number + 1..rope.len_lines()));
+                        above.min(below)
+                    });
+
+                    (line.y, line.height, width)
+                })
+                .collect(),
+            step: rules.indentation.tab_width,
+            color: self.theme.foreground(),
+        }
+    }
+
+    // 按偏好设置返回可见的各行中需要显示的空白字符，颜色取自当前的高亮主题。
+    fn whitespace_marks(
+        &self,
+        document: &Document,
+        lines: &[gutter::Line],
+        metrics: gutter::Metrics,
+    ) -> Option<whitespace::Marks> {
+        let tab = self.active_tab();
+        let range = match self.preferences.render_whitespace {
+            whitespace::Mode::Off => return None,
+            whitespace::Mode::Selection => Some(tab.selection_range()?),
+            whitespace::Mode::All => None,
+        };
+
+        Some(whitespace::Marks {
+            marks: whitespace::marks(
+                document.rope(),
+                lines,
+                metrics,
+                tab.rules.indentation.tab_width,
+                range,
+                self.preferences.line_ending_marks,
+            ),
+            color: self.theme.foreground(),
+        })
+    }
+
+    // 返回可见的各行中拼写错误的单词及其在编辑器中的区域。
+    //
+    // 与行号相同，位置按估算的字符宽度和行高计算；自动换行时按字符数估算单词所在的显示行。
+    fn misspellings(
+        &self,
+        document: &Document,
+        lines: &[gutter::Line],
+        metrics: gutter::Metrics,
+    ) -> Vec<(Rectangle, spelling::Misspelling)> {
+        let (Some(first), Some(last)) = (lines.first(), lines.last()) else {
+            return Vec::new();
+        };
+
+        let extension = self.active_tab().extension();
+        let misspellings =
+            self.spelling
+                .misspellings(document.rope(), &extension, first.number..last.number + 1);
+
+        misspellings
+            .into_iter()
+            .filter_map(|misspelling| {
+                let line = lines.iter().find(|line| line.number == misspelling.line)?;
+
+                let (row, column) = match metrics.columns.filter(|columns| *columns > 0) {
+                    Some(columns) => (
+                        misspelling.columns.start / columns,
+                        misspelling.columns.start % columns,
+                    ),
+                    None => (0, misspelling.columns.start),
+                };
+
+                let bounds = Rectangle {
+                    x: EDITOR_PADDING + column as f32 * CHARACTER_WIDTH,
+                    y: line.y + row as f32 * LINE_HEIGHT,
+                    width: misspelling.columns.len() as f32 * CHARACTER_WIDTH,
+                    height: LINE_HEIGHT,
+                };
+
+                Some((bounds, misspelling))
+            })
+            .collect()
+    }
+
+    // 返回右键菜单中的各项：点击位置的内容提供的操作在前，编辑操作在后。
+    fn context_menu_items(&self, target: Option<context_menu::Target>) -> Vec<context_menu::Item> {
+        use context_menu::Item;
+
+        let mut items = Vec::new();
+
+        match target {
+            Some(context_menu::Target::Misspelling(misspelling)) => {
+                let suggestions = self.spelling.suggestions(&misspelling.word);
+
+                if suggestions.is_empty() {
+                    items.push(Item::Action(String::from(tr("No Suggestions")), None));
+                }
+
+                for suggestion in suggestions {
+                    items.push(Item::Action(
+                        suggestion.clone(),
+                        Some(Message::SuggestionAccepted(misspelling.clone(), suggestion)),
+                    ));
+                }
+
+                items.push(Item::Action(
+                    trf(
+                        "Add \"{word}\" to Dictionary",
+                        &[("word", &misspelling.word)],
+                    ),
+                    Some(Message::AddToDictionary(misspelling.word)),
+                ));
+                items.push(Item::Separator);
+            }
+            None => {}
+        }
+
+        let tab = self.active_tab();
+        let has_selection = tab.context.selection().is_some();
+        let can_edit = !tab.is_read_only;
+
+        items.extend([
+            Item::Action(
+                String::from(tr("Cut")),
+                (has_selection && can_edit).then_some(Message::Cut),
+            ),
+            Item::Action(
+                String::from(tr("Copy")),
+                has_selection.then_some(Message::Copy),
+            ),
+            Item::Action(
+                String::from(tr("Paste")),
+                can_edit.then_some(Message::Paste),
+            ),
+            Item::Separator,
+            Item::Action(String::from(tr("Select All")), Some(Message::SelectAll)),
+        ]);
+
+        // 已经保存到磁盘的文件可以在文件管理器中显示，或者复制路径和文件名。
+        if tab.path.is_some() {
+            items.extend([
+                Item::Separator,
+                Item::Action(
+                    String::from(tr("Reveal in File Manager")),
+                    Some(Message::RevealFile),
+                ),
+                Item::Action(String::from(tr("Copy Path")), Some(Message::CopyPath)),
+                Item::Action(
+                    String::from(tr("Copy File Name")),
+                    Some(Message::CopyFileName),
+                ),
+            ]);
+        }
+
+        // 变换选中的文本，没有选区时作用于光标所在的行。
+        if can_edit {
+            items.push(Item::Separator);
+            items.extend(transform::Transform::ALL.iter().map(|transform| {
+                Item::Action(transform.to_string(), Some(Message::Transform(*transform)))
+            }));
+        }
+
+        items
+    }
+
+    // 启用拼写检查时在后台加载偏好设置中选择的词典。
+    fn load_dictionary(&self) -> Command<Message> {
+        if !self.preferences.spell_check {
+            return Command::none();
+        }
+
+        Command::perform(
+            spelling::load(self.preferences.dictionary.clone()),
+            Message::DictionaryLoaded,
+        )
+    }
+
+    // 在后台分块加载并打开指定路径的文件，加载过程中在状态栏中显示进度并可以取消。
+    fn open_path(&mut self, path: PathBuf) -> Command<Message> {
+        if !self.loading.iter().any(|loading| loading.path == path) {
+            self.loading
+                .push(loader::Loading::new(self.next_load_id, path));
+            self.next_load_id += 1;
+        }
+
+        Command::none()
+    }
+
+    // 在标签页中打开已加载的文件：文件已经打开时切换到对应的标签页，
+    // 当前标签页为空白文档时直接复用，否则新建标签页。
+    //
+    // 新打开的文件会在后台读取 git HEAD 中的内容，用于显示修改标记。
+    fn open_file(&mut self, path: PathBuf, decoded: &Decoded) -> Command<Message> {
+        let remembered = if self.workspaces.remember(&path) {
+            self.save_workspaces()
+        } else {
+            Command::none()
+        };
+
+        if let Some(index) = self
+            .tabs
+            .iter()
+            .position(|tab| tab.path.as_ref() == Some(&path))
+        {
+            self.active = index;
+            return remembered;
+        }
+
+        // 恢复这个文件上一次关闭时的书签。
+        let bookmarks = self.bookmarks.remove(&path).unwrap_or_default();
+
+        if self.active_tab().is_blank() {
+            let id = self.active_tab().id;
+            *self.active_tab_mut() = Tab::with_file(id, path, decoded);
+            self.apply_indentation(self.active);
+        } else {
+            self.open_tab(|id| Tab::with_file(id, path, decoded));
+        }
+
+        self.active_tab_mut().bookmarks = bookmarks.into_iter().collect();
+        self.sync_document(self.active);
+        Command::batch([load_file_settings(self.active_tab()), remembered])
+    }
+
+    // 返回所有文件中的书签，打开的文件使用标签页中的书签，没有书签的文件不会保存。
+    fn all_bookmarks(&self) -> BTreeMap<PathBuf, Vec<usize>> {
+        let mut bookmarks = self.bookmarks.clone();
+
+        for tab in &self.tabs {
+            if let Some(path) = &tab.path {
+                bookmarks.insert(path.clone(), tab.bookmarks.iter().copied().collect());
+            }
+        }
+
+        bookmarks.retain(|_, lines| !lines.is_empty());
+        bookmarks
+    }
+
+    // 保存会话并关闭窗口。
+    fn exit(&self) -> Command<Message> {
+        // 新窗口不保存会话，也不清除主窗口的恢复日志。
+        if self.is_secondary {
+            return window::close();
+        }
+
+        let session = session::Session {
+            files: self
+                .tabs
+                .iter()
+                .filter_map(|tab| {
+                    Some(session::File {
+                        path: tab.path.clone()?,
+                        cursor: tab.context.cursor(),
+                        scroll: tab.context.first_visible_line(),
+                    })
+                })
+                .collect(),
+            active: self.active_tab().path.clone(),
+            clipboard: self.clipboard_history.entries(),
+            bookmarks: self.all_bookmarks(),
+            scratch: self
+                .tabs
+                .iter()
+                .filter_map(|tab| {
+                    Some(session::Scratch {
+                        number: tab.scratch?,
+                        text: tab.context.text(),
+                        cursor: tab.context.cursor(),
+                        scroll: tab.context.first_visible_line(),
+                    })
+                })
+                .collect(),
+        };
+
+        // 退出时无法再显示错误，保存会话失败时直接忽略。
+        let _ = session::save(&session);
+
+        // 正常退出时不再需要恢复日志，尚未确认是否恢复的日志除外。
+        if self.recovery.is_empty() {
+            recovery::clear();
+        }
+
+        window::close()
+    }
+
+    // 创建编辑器上方的路径导航栏：文件所在的各级目录、文件名以及光标所在的各层符号。
+    //
+    // 点击目录时以该目录为根打开文件树。文件在文件树中时只显示从根目录开始的部分。
+    fn breadcrumbs<'a>(&'a self, tab: &'a Tab) -> Element<'a, Message> {
+        let separator = || text("›").size(14);
+        let mut crumbs = row![].spacing(5);
+
+        match &tab.path {
+            Some(path) => {
+                let root = self.file_tree.as_ref().and_then(|file_tree| {
+                    file_tree
+                        .roots()
+                        .into_iter()
+                        .find(|root| path.starts_with(root))
+                });
+
+                let directories: Vec<&Path> = path
+                    .ancestors()
+                    .skip(1)
+                    .take_while(|directory| directory.file_name().is_some())
+                    .take_while(|directory| {
+                        root.as_deref()
+                            .map_or(true, |root| directory.starts_with(root))
+                    })
+                    .collect();
+
+                for directory in directories.into_iter().rev() {
+                    let name = directory
+                        .file_name()
+                        .and_then(|name| name.to_str())
+                        .unwrap_or_default();
+
+                    crumbs = crumbs
+                        .push(
+                            button(text(name).size(14))
+                                .on_press(Message::BreadcrumbClicked(directory.to_path_buf()))
+                                .padding(0)
+                                .style(theme::Button::Text),
+                        )
+                        .push(separator());
+                }
+
+                let name = path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .unwrap_or_default();
+
+                crumbs = crumbs.push(text(name).size(14));
+            }
+            None => crumbs = crumbs.push(text(tr("New File")).size(14)),
+        }
+
+        let (line, _) = tab.context.cursor_position();
+
+        for symbol in symbols::enclosing(&tab.outline(), line) {
+            crumbs = crumbs
+                .push(separator())
+                .push(text(format!("{} {}", symbol.kind, symbol.name)).size(14));
+        }
+
+        crumbs.into()
+    }
+
+    // 使用指定目录创建文件树，并加载根目录的内容。
+    fn open_folder(&mut self, folder: PathBuf) -> Command<Message> {
+        self.file_tree = Some(FileTree::new(folder.clone()));
+
+        Command::perform(file_tree::list_directory(folder), Message::DirectoryLoaded)
+    }
+
+    // 使用当前工作区的所有根目录创建文件树，打开的搜索面板也改为在这些目录中搜索。
+    fn open_workspace(&mut self) -> Command<Message> {
+        let Some(roots) = self
+            .workspaces
+            .active()
+            .map(|workspace| workspace.roots.clone())
+            .filter(|roots| !roots.is_empty())
+        else {
+            return Command::none();
+        };
+
+        if let Some(search) = self.search.as_mut() {
+            search.folders = roots.clone();
+            search.results = None;
+        }
+
+        self.file_tree = Some(FileTree::with_roots(roots.clone()));
+
+        Command::batch(roots.into_iter().map(|root| {
+            Command::perform(file_tree::list_directory(root), Message::DirectoryLoaded)
+        }))
+    }
+
+    // 将工作区清单写入配置文件。
+    fn save_workspaces(&self) -> Command<Message> {
+        Command::perform(
+            write_config(config_file("workspaces.toml"), self.workspaces.to_toml()),
+            Message::WorkspacesSaved,
+        )
+    }
+
+    // 判断当前 Markdown 文件的预览是否需要刷新。
+    fn preview_is_stale(&self) -> bool {
+        let tab = self.active_tab();
+
+        tab.is_markdown()
+            && !self
+                .preview
+                .as_ref()
+                .is_some_and(|preview| preview.tab == tab.id && preview.revision == tab.revision)
+    }
+
+    // 在终端面板中运行标签页对应的文件，正在运行的命令会先被结束。
+    fn run_file(&mut self, id: usize) -> Command<Message> {
+        let Some((command, directory)) = self
+            .tabs
+            .iter()
+            .find(|tab| tab.id == id)
+            .and_then(|tab| tab.path.as_deref())
+            .and_then(runner::command)
+        else {
+            return Command::none();
+        };
+
+        self.show_terminal = true;
+        self.terminal.kill();
+        self.terminal.run(command, directory);
+        Command::none()
+    }
+
+    // 返回运行命令时使用的工作目录：文件树的根目录、当前文件所在的目录或者程序的当前目录。
+    fn working_directory(&self) -> PathBuf {
+        match &self.file_tree {
+            Some(file_tree) => file_tree.root().to_path_buf(),
+            None => self
+                .active_tab()
+                .path
+                .as_deref()
+                .and_then(Path::parent)
+                .map(Path::to_path_buf)
+                .or_else(|| std::env::current_dir().ok())
+                .unwrap_or_default(),
+        }
+    }
+
+    // 在后台重新读取源代码管理面板中的仓库状态，面板没有打开时不做任何事。
+    //
+    // 使用当前文件所在的仓库，没有打开文件时使用文件树根目录所在的仓库。
+    fn refresh_source_control(&mut self) -> Command<Message> {
+        let directory = match self.active_tab().path.as_deref().and_then(Path::parent) {
+            Some(directory) => Some(directory.to_path_buf()),
+            None => self
+                .file_tree
+                .as_ref()
+                .map(|file_tree| file_tree.root().to_path_buf()),
+        };
+
+        let (Some(source_control), Some(directory)) = (self.source_control.as_mut(), directory)
+        else {
+            return Command::none();
+        };
+
+        source_control.is_busy = true;
+        Command::perform(git::status(directory), Message::GitStatusLoaded)
+    }
+
+    // 将标签页的最新内容同步给语言服务器，第一次同步时打开文档。
+    fn sync_document(&mut self, index: usize) {
+        let tab = &self.tabs[index];
+
+        if let Some(path) = &tab.path {
+            self.lsp.open(path, || tab.context.text(), tab.revision);
+        }
+    }
+
+    // 判断当前标签页是否有尚未同步给语言服务器的修改。
+    fn lsp_is_stale(&self) -> bool {
+        let tab = self.active_tab();
+
+        tab.path
+            .as_deref()
+            .is_some_and(|path| self.lsp.is_stale(path, tab.revision))
+    }
+
+    // 返回当前标签页的文件路径和语言服务器使用的光标位置。
+    fn cursor_position(&self) -> Option<(PathBuf, lsp::Position)> {
+        let tab = self.active_tab();
+        let path = tab.path.clone()?;
+        let (line, column) = tab.context.cursor_position();
+        let text = tab.context.line(line)?;
+
+        Some((path, lsp::Position::new(line, &text, column)))
+    }
+
+    // 返回当前标签页的诊断，包括语言服务器和 cargo check 报告的问题。
+    fn diagnostics(&self) -> Vec<diagnostics::Diagnostic> {
+        match &self.active_tab().path {
+            Some(path) => self
+                .lsp
+                .diagnostics(path)
+                .iter()
+                .chain(self.problems.diagnostics(path))
+                .cloned()
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    // 返回当前标签页中其他光标需要标记的位置。
+    fn cursor_marks(&self) -> Vec<(usize, std::ops::Range<usize>)> {
+        let tab = self.active_tab();
+
+        self.cursors
+            .as_ref()
+            .filter(|cursors| cursors.tab == tab.id)
+            .map(|cursors| cursors.marks(tab))
+            .unwrap_or_default()
+    }
+
+    // 在后台对 Cargo 项目运行 cargo check；已经在检查时等待检查结束后再重新检查。
+    fn check_project(&mut self, project: PathBuf) -> Command<Message> {
+        if self.problems.is_checking {
+            self.problems.is_stale = true;
+            return Command::none();
+        }
+
+        self.problems.is_checking = true;
+        self.problems.is_stale = false;
+        Command::perform(problems::check(project), Message::ProjectChecked)
+    }
+
+    // 判断当前标签页的 git 修改标记是否需要重新比较。
+    fn changes_are_stale(&self) -> bool {
+        let tab = self.active_tab();
+
+        tab.head.is_some() && tab.changes_revision != tab.revision
+    }
+
+    // 在后台比较标签页的内容与 HEAD 中的内容。
+    fn compare_with_head(&mut self, index: usize) -> Command<Message> {
+        let tab = &mut self.tabs[index];
+
+        let Some(head) = tab.head.clone() else {
+            return Command::none();
+        };
+
+        // 记录开始比较时的版本号，比较完成前不会重复发起。
+        let id = tab.id;
+        let revision = tab.revision;
+        tab.changes_revision = revision;
+
+        Command::perform(git::diff(head, tab.context.text()), move |changes| {
+            Message::ChangesComputed(id, revision, changes)
+        })
+    }
+
+    // 设置缩放比例并保存到偏好设置中。
+    fn zoom(&mut self, zoom: u32) -> Command<Message> {
+        self.preferences.zoom = zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+        self.save_preferences()
+    }
+
+    // 将偏好设置写入配置文件。
+    fn save_preferences(&self) -> Command<Message> {
+        Command::perform(
+            write_config(config_file("preferences.toml"), self.preferences.to_toml()),
+            Message::PreferencesSaved,
+        )
+    }
+
+    // 将快捷键映射写入配置文件。
+    fn save_keymap(&self) -> Command<Message> {
+        Command::perform(
+            write_config(config_file("keymap.toml"), self.keymap.to_toml()),
+            Message::KeymapSaved,
+        )
+    }
+
+    // 返回存在未保存修改的标签页的标识及其版本号，用于判断是否需要更新恢复日志。
+    fn unsaved_revisions(&self) -> Vec<(usize, usize)> {
+        self.tabs
+            .iter()
+            .filter(|tab| tab.has_unsaved_changes())
+            .map(|tab| (tab.id, tab.revision))
+            .collect()
+    }
+
+    // 返回查找和替换的范围（包含两端的行），没有限制在选区中时为整个文档。
+    fn replace_scope(&self) -> (usize, usize) {
+        let last = self.active_tab().context.line_count().saturating_sub(1);
+
+        self.replace
+            .as_ref()
+            .and_then(|replace| replace.scope)
+            .unwrap_or((0, last))
+    }
+
+    // 替换查找范围内的匹配，作为一次可撤销的编辑，only 指定只替换第几处匹配。
+    fn replace_matches(&mut self, only: Option<usize>) -> Command<Message> {
+        let (first, last) = self.replace_scope();
+
+        let Some(replace) = self.replace.as_mut() else {
+            return Command::none();
+        };
+
+        let tab = &mut self.tabs[self.active];
+        let text = tab.lines(first, last).join("\n");
+
+        match replace.replace(&text, only) {
+            Ok(replaced) if replaced != text => {
+                // 替换的文本可能包含换行符，范围随之扩大或缩小。
+                if let Some(scope) = replace.scope.as_mut() {
+                    scope.1 = first + replaced.matches('\n').count();
+                }
+
+                tab.replace_lines(first, last, replaced);
+                self.last_edit = Instant::now();
+            }
+            Ok(_) => {}
+            Err(error) => self.notifications.error(Error::TransformFailed(error)),
+        }
+        Command::none()
+    }
+
+    // 提示当前文档不是 JSON 文件。
+    fn not_json(&mut self) -> Command<Message> {
+        self.notifications.error(Error::InvalidJson(trf(
+            "Not a JSON file: .{extension}",
+            &[("extension", &self.active_tab().extension())],
+        )));
+        Command::none()
+    }
+
+    // 显示 JSON 的检查结果：通过时在状态栏中标记，失败时把光标移动到出错的位置并显示原因。
+    fn report_json(&mut self, result: Result<(), json::Invalid>) {
+        match result {
+            Ok(()) => {
+                let tab = self.active_tab();
+                self.valid_json = Some((tab.id, tab.revision));
+                self.notifications.dismiss_errors();
+            }
+            Err(invalid) => {
+                self.active_tab_mut()
+                    .move_cursor_to(invalid.line, invalid.column);
+                self.notifications
+                    .error(Error::InvalidJson(invalid.message));
+            }
+        }
+    }
+
+    // 判断是否有任何标签页存在未保存的修改。
+    fn has_unsaved_changes(&self) -> bool {
+        self.tabs.iter().any(Tab::has_unsaved_changes)
+    }
+
+    // 在当前标签页的十六进制编辑器中处理按键，不是十六进制编辑器或按键没有被处理时返回 false。
+    fn hex_key_pressed(&mut self, key_code: keyboard::KeyCode) -> bool {
+        let tab = &mut self.tabs[self.active];
+        let is_read_only = tab.is_read_only;
+
+        let Some(hex) = tab.hex.as_mut() else {
+            return false;
+        };
+
+        let row = hex::BYTES_PER_ROW as isize;
+
+        match (key_code, hex_digit(key_code)) {
+            (keyboard::KeyCode::Left, _) => hex.move_cursor(-1),
+            (keyboard::KeyCode::Right, _) => hex.move_cursor(1),
+            (keyboard::KeyCode::Up, _) => hex.move_cursor(-row),
+            (keyboard::KeyCode::Down, _) => hex.move_cursor(row),
+            (keyboard::KeyCode::PageUp, _) => hex.move_page(-1),
+            (keyboard::KeyCode::PageDown, _) => hex.move_page(1),
+            (_, Some(digit)) if !is_read_only => tab.is_dirty |= hex.input(digit),
+            _ => return false,
+        }
+
+        true
+    }
+
+    // 判断是否有对话框、面板或其他界面遮挡着编辑区域，此时按键交给获得焦点的输入框处理。
+    fn has_dialog(&self) -> bool {
+        self.pending.is_some()
+            || self.screen != Screen::Editor
+            || self.goto_line.is_some()
+            || self.play_macro.is_some()
+            || self.palette.is_some()
+            || self.quick_open.is_some()
+            || self.open_url.is_some()
+            || self.open_remote.is_some()
+            || self.replace.is_some()
+    }
+
+    // 使用当前的编码从磁盘重新读取指定标识的标签页，没有路径的文档不做任何事。
+    fn reload_tab(&self, id: usize) -> Command<Message> {
+        let Some(tab) = self.tabs.iter().find(|tab| tab.id == id) else {
+            return Command::none();
+        };
+
+        match tab.path.clone() {
+            Some(path) => Command::perform(load_file_with(path, tab.encoding), move |result| {
+                Message::FileReloaded(id, result)
+            }),
+            None => Command::none(),
+        }
+    }
+
+    // 保存指定下标的标签页。
+    //
+    // 写入成功后才把标签页标记为已保存，写入失败或取消保存对话框时保持未保存的状态。
+    fn save_tab(&mut self, index: usize, mode: SaveMode) -> Command<Message> {
+        let tab = &mut self.tabs[index];
+
+        // 自动保存时只写入清理后的内容，不修改文档，以免移动光标或删掉刚刚输入的空格。
+        // 其他时候先清理文档本身，写入时不需要再清理一次。
+        let text = if mode == SaveMode::Autosave {
+            tab.text_to_save(Some(&self.preferences))
+        } else {
+            tab.clean_up(&self.preferences);
+            tab.text_to_save(None)
+        };
+
+        // 所选编码无法表示全部内容时不保存，以免把字符替换掉；自动保存时跳过，手动保存时询问是否改用 UTF-8。
+        let contents = match tab.contents(&text) {
+            Ok(contents) => contents,
+            Err(character) => {
+                if mode != SaveMode::Autosave && self.pending.is_none() {
+                    self.active = index;
+                    self.pending = Some(Pending::Unmappable(tab.id, character));
+                }
+                return Command::none();
+            }
+        };
 
-// 主函数，程序的入口点。
-fn main() -> iced::Result {
-    // 运行 Editor 应用程序。
-    Editor::run(Settings {
-        // 嵌入字体文件，用于文本编辑器中的字体图标。
-        fonts: vec![include_bytes!("../fonts/editor-icons.ttf")
-            .as_slice()
-            .into()],
+        // 二进制文件写入的不是文本，按照写入的字节计算指纹，与检查磁盘上的文件时的方式相同。
+        let disk = match tab.hex {
+            Some(_) => fingerprint_contents(&contents, tab.encoding),
+            None => tab::fingerprint(text.chunks()),
+        };
 
-        window: window::Settings {
-            ..Default::default()
-        },
-        default_font: Font::MONOSPACE,
-        ..Default::default()
-    })
-}
+        let saved = Saved {
+            id: tab.id,
+            revision: tab.revision,
+            text,
+            disk,
+        };
 
-// 定义文本编辑器应用程序的状态。
-struct Editor {
-    path: Option<PathBuf>,         // 打开文件的路径。
-    context: text_editor::Content, // 文本编辑器的内容。
-    error: Option<Error>,          // 错误信息。
-    theme: highlighter::Theme,     // 代码高亮主题。
-    is_dirty: bool,                // 文件是否被修改过。
-}
+        self.saving.push(saved.id);
 
-// 定义应用程序可能接收的消息类型。
-#[derive(Debug, Clone)]
-enum Message {
-    Edit(text_editor::Action),                         // 文本编辑器的动作。
-    New,                                               // 新建文件。
-    Open,                                              // 打开文件。
-    FileOpened(Result<(PathBuf, Arc<String>), Error>), // 文件打开结果。
-    Save,                                              // 保存文件。
-    FileSaved(Result<PathBuf, Error>),                 // 文件保存结果。
-    ThemeSelected(highlighter::Theme),                 // 选择的高亮主题。
-}
+        // 远程文件通过 SFTP 写回服务器。
+        if let (Some(location), false, SaveMode::Save | SaveMode::Autosave) =
+            (&tab.remote, tab.is_read_only, mode)
+        {
+            return Command::perform(remote::save(location.clone(), contents), move |result| {
+                Message::RemoteSaved(saved, result)
+            });
+        }
 
-// 为 Editor 结构体实现 iced 的 Application trait。
-impl Application for Editor {
-    type Message = Message;
-    type Executor = executor::Default;
-    type Theme = Theme;
-    type Flags = ();
-    // 创建一个新的 Editor 实例。
-    fn new(_flags: Self::Flags) -> (Self, Command<Message>) {
-        (
-            Self {
-                path: None,
-                context: text_editor::Content::new(),
-                error: None,
-                theme: highlighter::Theme::SolarizedDark,
-                is_dirty: true,
-            },
-            Command::perform(load_file(default_file()), Message::FileOpened),
+        // 只读文件不能覆盖，总是弹出保存对话框另存一份副本。
+        let path = match mode {
+            SaveMode::Save | SaveMode::Autosave if !tab.is_read_only => tab.path.clone(),
+            _ => None,
+        };
+
+        Command::perform(
+            save_file(path, contents, self.preferences.backup_on_save),
+            move |result| Message::FileSaved(saved, result),
         )
     }
-    // 返回应用程序的标题。
-    fn title(&self) -> String {
-        String::from("A cool Editor !")
-    }
-    // 根据接收到的消息更新应用程序的状态。
-    fn update(&mut self, message: Self::Message) -> Command<Message> {
-        match message {
-            Message::Edit(action) => {
-                self.is_dirty = self.is_dirty || action.is_edit();
-                self.error = None;
-                self.context.edit(action);
-                Command::none()
-            }
-            Message::New => {
-                self.path = None;
-                self.context = text_editor::Content::new();
-                self.is_dirty = true;
-                Command::none()
-            }
-            Message::Open => Command::perform(pick_file(), Message::FileOpened),
-            Message::FileOpened(Ok((path, content))) => {
-                self.path = Some(path);
-                self.context = text_editor::Content::with(&content);
-                self.is_dirty = false;
 
-                Command::none()
-            }
-            Message::Save => {
-                let text = self.context.text();
-                self.is_dirty = false;
-                Command::perform(save_file(self.path.clone(), text), Message::FileSaved)
-            }
-            Message::FileSaved(Ok(path)) => {
-                self.path = Some(path);
-                Command::none()
-            }
-            Message::FileOpened(Err(error)) => {
-                self.error = Some(error);
-                Command::none()
+    // 保存指定下标的标签页，先检查文件是否在读取之后被其他程序修改，以免悄悄覆盖其他程序的修改。
+    //
+    // 检查期间标签页也算作正在保存，退出时会等待它。
+    fn check_and_save(&mut self, index: usize, mode: SaveMode) -> Command<Message> {
+        let tab = &self.tabs[index];
+
+        match (
+            &tab.path,
+            tab.disk,
+            tab.is_read_only || tab.remote.is_some(),
+        ) {
+            (Some(path), Some(fingerprint), false) => {
+                let id = tab.id;
+                let check = changed_on_disk(path.clone(), tab.encoding, fingerprint);
+
+                self.saving.push(id);
+                Command::perform(check, move |changed| {
+                    Message::SaveChecked(id, mode, changed)
+                })
             }
-            Message::FileSaved(Err(error)) => {
-                self.error = Some(error);
-                Command::none()
+            _ => self.save_tab(index, mode),
+        }
+    }
+
+    // 记录一次检查或保存已经结束，无论成功与否。
+    fn finish_save(&mut self, id: usize) {
+        if let Some(position) = self.saving.iter().position(|&saving| saving == id) {
+            let _ = self.saving.remove(position);
+        }
+    }
+
+    // 保存成功后继续执行等待中的关闭或退出，没有需要继续的操作时返回 None。
+    //
+    // 退出前等待所有的保存都完成；保存期间又修改过的标签页重新询问，而不是丢掉新的修改。
+    fn continue_after_save(&mut self, id: usize) -> Option<Command<Message>> {
+        match self.after_save {
+            Some(Pending::CloseTab(pending)) if pending == id => {
+                self.after_save = None;
+
+                if let Some(index) = self.tabs.iter().position(|tab| tab.id == id) {
+                    self.request_close_tab(index);
+                }
+                Some(Command::none())
             }
-            Message::ThemeSelected(theme) => {
-                self.theme = theme;
-                Command::none()
+            Some(Pending::Exit) if self.saving.is_empty() && self.pending.is_none() => {
+                self.after_save = None;
+
+                if self.has_unsaved_changes() {
+                    self.pending = Some(Pending::Exit);
+                    Some(Command::none())
+                } else {
+                    Some(self.exit())
+                }
             }
+            Some(Pending::Exit) => Some(Command::none()),
+            _ => None,
         }
     }
-    // 创建一个订阅来监听键盘事件。
-    fn subscription(&self) -> Subscription<Self::Message> {
-        keyboard::on_key_press(|key_code, modifiers| match key_code {
-            keyboard::KeyCode::S if modifiers.command() => Some(Message::Save),
-            _ => None,
-        })
+
+    // 请求关闭标签页，存在未保存的修改时先弹出确认对话框。
+    fn request_close_tab(&mut self, index: usize) {
+        let Some(tab) = self.tabs.get(index) else {
+            return;
+        };
+
+        if tab.has_unsaved_changes() {
+            self.active = index;
+            self.pending = Some(Pending::CloseTab(tab.id));
+        } else {
+            self.close_tab(index);
+        }
     }
-    // 创建应用程序的 UI。
-    fn view(&self) -> Element<'_, Message> {
-        let controls = row![
-            action(new_icon(), "New File", Some(Message::New)),
-            action(open_icon(), "Open File", Some(Message::Open)),
-            action(
-                save_icon(),
-                "Save File",
-                self.is_dirty.then_some(Message::Save)
-            ),
+
+    // 将比较中的一处修改复制到 side 一侧的文档中，作为一次可撤销的编辑。
+    //
+    // 修改的前后总是带有上下文或者替换的行，因此两侧覆盖的行都不为空，
+    // 直接用一侧的这些行替换另一侧的这些行即可。
+    fn copy_hunk(&mut self, index: usize, side: diff::Side) {
+        let Some(comparison) = &mut self.comparison else {
+            return;
+        };
+
+        let Some(hunk) = comparison.diff.hunks.get(index) else {
+            return;
+        };
+
+        let (from, to, source, target) = match side {
+            diff::Side::Left => (comparison.right, comparison.left, &hunk.new, &hunk.old),
+            diff::Side::Right => (comparison.left, comparison.right, &hunk.old, &hunk.new),
+        };
+
+        let position = |id| self.tabs.iter().position(|tab| tab.id == id);
+
+        let (Some(from), Some(to)) = (position(from), position(to)) else {
+            return;
+        };
+
+        if source.is_empty() || target.is_empty() {
+            return;
+        }
+
+        let lines = self.tabs[from]
+            .lines(source.start, source.end - 1)
+            .join("\n");
+        let (first, last) = (target.start, target.end - 1);
+
+        comparison.current = index;
+        self.last_edit = Instant::now();
+        self.tabs[to].replace_lines(first, last, lines);
+    }
+
+    // 正在比较的文档被修改后重新比较，其中一个标签页已经关闭时结束比较。
+    fn refresh_comparison(&mut self) {
+        let Some(comparison) = &mut self.comparison else {
+            return;
+        };
+
+        let left = self.tabs.iter().find(|tab| tab.id == comparison.left);
+        let right = self.tabs.iter().find(|tab| tab.id == comparison.right);
+
+        let (Some(left), Some(right)) = (left, right) else {
+            self.comparison = None;
+            return;
+        };
+
+        let revisions = (left.revision, right.revision);
+
+        if comparison.revisions != revisions {
+            comparison.update(revisions, &left.context.text(), &right.context.text());
+        }
+    }
+
+    // 关闭指定标识的标签页。
+    fn close_tab_by_id(&mut self, id: usize) {
+        if let Some(index) = self.tabs.iter().position(|tab| tab.id == id) {
+            self.close_tab(index);
+        }
+    }
+
+    // 创建显示当前文档与磁盘上的文件之间差异的视图，左侧为磁盘上的内容，右侧为当前的内容。
+    fn changes<'a>(&'a self, diff: &'a diff::Diff) -> Element<'a, Message> {
+        let (added, removed) = diff.statistics();
+
+        let header = row![
+            text(tr("Changes since last save")).size(18),
+            text(format!("+{added} −{removed}")).size(14),
             horizontal_space(Length::Fill),
             pick_list(
-                highlighter::Theme::ALL,
-                Some(self.theme),
-                Message::ThemeSelected
+                diff::Layout::ALL,
+                Some(self.diff_layout),
+                Message::DiffLayoutSelected
             )
+            .text_size(14),
+            button(text("×"))
+                .on_press(Message::ShowChanges)
+                .style(theme::Button::Text)
         ]
         .spacing(10);
-        let input = text_editor(&self.context)
-            .on_edit(Message::Edit)
-            .highlight::<Highlighter>(
-                highlighter::Settings {
-                    theme: self.theme,
-                    extension: self
-                        .path
-                        .as_ref()
-                        .and_then(|path| path.extension()?.to_str())
-                        .unwrap_or("rs")
-                        .to_string(),
-                },
-                |highlighter, _theme| highlighter.to_format(),
-            );
 
-        let status_bar = {
-            let status = if let Some(Error::IOFailed(error)) = self.error.as_ref() {
-                text(error.to_string())
-            } else {
-                match self.path.as_deref().and_then(Path::to_str) {
-                    Some(path) => text(path).size(14),
-                    None => text("New File"),
-                }
-            };
+        column![header, diff.view(self.diff_layout)]
+            .spacing(10)
+            .into()
+    }
 
-            let position = {
-                let (line, column) = self.context.cursor_position();
-                text(format!("{}:{}", line + 1, column + 1))
-            };
+    // 创建比较两个文件的视图，标题栏中显示两侧的文件名和切换修改的按钮。
+    fn comparison<'a>(&'a self, comparison: &'a diff::Comparison) -> Element<'a, Message> {
+        let title = |id| {
+            self.tabs
+                .iter()
+                .find(|tab| tab.id == id)
+                .map_or_else(String::new, Tab::title)
+        };
+
+        let (added, removed) = comparison.diff.statistics();
+        let count = comparison.diff.hunks.len();
+        let position = if count == 0 {
+            String::from(tr("No changes"))
+        } else {
+            trf(
+                "Change {current} of {count}",
+                &[("current", &(comparison.current + 1)), ("count", &count)],
+            )
+        };
+
+        let header = row![
+            text(format!(
+                "{} ↔ {}",
+                title(comparison.left),
+                title(comparison.right)
+            ))
+            .size(18),
+            text(format!("+{added} −{removed}")).size(14),
+            horizontal_space(Length::Fill),
+            text(position).size(14),
+            button(text("↑"))
+                .on_press_maybe((count > 0).then_some(Message::PreviousHunk))
+                .style(theme::Button::Text),
+            button(text("↓"))
+                .on_press_maybe((count > 0).then_some(Message::NextHunk))
+                .style(theme::Button::Text),
+            button(text("×"))
+                .on_press(Message::CloseComparison)
+                .style(theme::Button::Text)
+        ]
+        .spacing(10);
+
+        column![header, comparison.view()].spacing(10).into()
+    }
+
+    // 创建确认对话框，提供保存、放弃和取消三个选项。
+    fn confirmation(&self, pending: Pending) -> Element<'_, Message> {
+        let question = match pending {
+            Pending::CloseTab(id) => {
+                let title = self
+                    .tabs
+                    .iter()
+                    .find(|tab| tab.id == id)
+                    .map(Tab::title)
+                    .unwrap_or_default();
+
+                trf(
+                    "Save changes to \"{title}\" before closing?",
+                    &[("title", &title)],
+                )
+            }
+            Pending::Exit => {
+                let count = self
+                    .tabs
+                    .iter()
+                    .filter(|tab| tab.has_unsaved_changes())
+                    .count();
+
+                trf(
+                    "{count} file(s) have unsaved changes. Save before exiting?",
+                    &[("count", &count)],
+                )
+            }
+            Pending::Reload(id) => {
+                let title = self
+                    .tabs
+                    .iter()
+                    .find(|tab| tab.id == id)
+                    .map(Tab::title)
+                    .unwrap_or_default();
+
+                trf(
+                    "\"{title}\" was changed by another program. Reload it from disk?",
+                    &[("title", &title)],
+                )
+            }
+            Pending::Revert(id) => {
+                let title = self
+                    .tabs
+                    .iter()
+                    .find(|tab| tab.id == id)
+                    .map(Tab::title)
+                    .unwrap_or_default();
+
+                trf(
+                    "Discard unsaved changes to \"{title}\" and reload it from disk?",
+                    &[("title", &title)],
+                )
+            }
+            Pending::Overwrite(id) => {
+                let title = self
+                    .tabs
+                    .iter()
+                    .find(|tab| tab.id == id)
+                    .map(Tab::title)
+                    .unwrap_or_default();
+
+                trf(
+                    "\"{title}\" was changed by another program after it was opened. Overwrite it?",
+                    &[("title", &title)],
+                )
+            }
+            Pending::Unmappable(id, character) => {
+                let (title, encoding) = self
+                    .tabs
+                    .iter()
+                    .find(|tab| tab.id == id)
+                    .map(|tab| (tab.title(), tab.encoding.to_string()))
+                    .unwrap_or_default();
 
-            row![status, horizontal_space(Length::Fill), position]
+                trf(
+                    "\"{title}\" contains characters that {encoding} can't represent, such as \"{character}\". Save it as UTF-8?",
+                    &[
+                        ("title", &title),
+                        ("encoding", &encoding),
+                        ("character", &character),
+                    ],
+                )
+            }
+            Pending::Recover => trf(
+                "The editor did not exit cleanly. Restore unsaved changes to {count} file(s)?",
+                &[("count", &self.recovery.len())],
+            ),
         };
 
-        container(column![controls, input, status_bar].spacing(10))
-            .padding(10)
+        let choices = match pending {
+            Pending::Reload(_) => row![
+                button(text(tr("Reload")))
+                    .on_press(Message::Confirm(Choice::Reload))
+                    .style(theme::Button::Primary),
+                button(text(tr("Keep My Version")))
+                    .on_press(Message::Confirm(Choice::Cancel))
+                    .style(theme::Button::Secondary),
+            ],
+            Pending::Overwrite(_) => row![
+                button(text(tr("Overwrite")))
+                    .on_press(Message::Confirm(Choice::Overwrite))
+                    .style(theme::Button::Destructive),
+                button(text(tr("Show Changes")))
+                    .on_press(Message::Confirm(Choice::Compare))
+                    .style(theme::Button::Primary),
+                button(text(tr("Save a Copy...")))
+                    .on_press(Message::Confirm(Choice::SaveAs))
+                    .style(theme::Button::Secondary),
+                button(text(tr("Cancel")))
+                    .on_press(Message::Confirm(Choice::Cancel))
+                    .style(theme::Button::Secondary),
+            ],
+            Pending::Unmappable(..) => row![
+                button(text(tr("Save as UTF-8")))
+                    .on_press(Message::Confirm(Choice::Utf8))
+                    .style(theme::Button::Primary),
+                button(text(tr("Cancel")))
+                    .on_press(Message::Confirm(Choice::Cancel))
+                    .style(theme::Button::Secondary),
+            ],
+            Pending::Revert(_) => row![
+                button(text(tr("Revert")))
+                    .on_press(Message::Confirm(Choice::Reload))
+                    .style(theme::Button::Destructive),
+                button(text(tr("Cancel")))
+                    .on_press(Message::Confirm(Choice::Cancel))
+                    .style(theme::Button::Secondary),
+            ],
+            Pending::Recover => row![
+                button(text(tr("Restore")))
+                    .on_press(Message::Confirm(Choice::Restore))
+                    .style(theme::Button::Primary),
+                button(text(tr("Discard")))
+                    .on_press(Message::Confirm(Choice::Discard))
+                    .style(theme::Button::Destructive),
+            ],
+            Pending::CloseTab(_) | Pending::Exit => row![
+                button(text(tr("Save")))
+                    .on_press(Message::Confirm(Choice::Save))
+                    .style(theme::Button::Primary),
+                button(text(tr("Discard")))
+                    .on_press(Message::Confirm(Choice::Discard))
+                    .style(theme::Button::Destructive),
+                button(text(tr("Cancel")))
+                    .on_press(Message::Confirm(Choice::Cancel))
+                    .style(theme::Button::Secondary),
+            ],
+        }
+        .spacing(10);
+
+        let dialog = container(column![text(question), choices].spacing(20))
+            .padding(20)
+            .style(theme::Container::Box);
+
+        container(dialog)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .center_x()
+            .center_y()
             .into()
     }
-    // 返回当前应用程序的主题。
-    fn theme(&self) -> Theme {
-        if self.theme.is_dark() {
-            Theme::Dark
-        } else {
-            Theme::Light
+
+    // 关闭指定的标签页，始终保留至少一个标签页。
+    fn close_tab(&mut self, index: usize) {
+        if index >= self.tabs.len() {
+            return;
+        }
+
+        let tab = self.tabs.remove(index);
+
+        if let Some(path) = tab.path {
+            self.lsp.close(&path);
+            self.bookmarks
+                .insert(path, tab.bookmarks.into_iter().collect());
+        }
+
+        if self.tabs.is_empty() {
+            self.open_tab(Tab::new);
+        } else if self.active > index || self.active >= self.tabs.len() {
+            self.active = self.active.saturating_sub(1);
         }
+
+        self.refresh_comparison();
     }
 }
 
+// 停止输入后刷新 Markdown 预览的延迟。
+const PREVIEW_DELAY: Duration = Duration::from_millis(300);
+
+// 停止输入多久之后重新比较 git 修改。
+const CHANGES_DELAY: Duration = Duration::from_millis(500);
+
+// 停止输入多久之后将修改同步给语言服务器。
+const LSP_DELAY: Duration = Duration::from_millis(300);
+
+// 每隔多久将未保存的修改写入恢复日志。
+const JOURNAL_INTERVAL: Duration = Duration::from_secs(5);
+
+// 跟随系统外观时检查系统深色模式设置的间隔。
+const APPEARANCE_INTERVAL: Duration = Duration::from_secs(5);
+
+// 估算的编辑器中每个字符的宽度，用于关闭自动换行时计算内容的宽度。
+const CHARACTER_WIDTH: f32 = 9.6;
+
+// 估算的编辑器中每个显示行的高度，用于将标记栏与编辑器中的行对齐。
+const LINE_HEIGHT: f32 = 20.8;
+
+// 文本编辑器的内边距。
+const EDITOR_PADDING: f32 = 5.0;
+
+// 为空白行计算缩进参考线时向上和向下最多查找的行数。
+const GUIDE_SEARCH_LINES: usize = 100;
+
+// 缩放比例的范围和每次调整的幅度，单位为百分比。
+const MIN_ZOOM: u32 = 50;
+const MAX_ZOOM: u32 = 300;
+const ZOOM_STEP: u32 = 10;
+
+// 定义一个函数来创建标签栏中的标签按钮，包含标题和关闭按钮。
+fn tab_button<'a>(tab: &Tab, is_active: bool, index: usize) -> Element<'a, Message> {
+    let close = button(text("×").size(14))
+        .on_press(Message::TabClosed(index))
+        .padding([0, 5])
+        .style(theme::Button::Text);
+
+    button(row![text(tab.title()).size(14), close].spacing(5))
+        .on_press(Message::TabSelected(index))
+        .padding([5, 10])
+        .style(if is_active {
+            theme::Button::Primary
+        } else {
+            theme::Button::Secondary
+        })
+        .into()
+}
+
 // 定义一个函数来创建一个带有图标和标签的按钮，该按钮在被点击时可能会触发一个消息。
 fn action<'a>(
     content: Element<'a, Message>, // 按钮中显示的元素，通常是图标。
@@ -233,55 +6130,347 @@ fn icon<'a>(codepoint: char) -> Element<'a, Message> {
     text(codepoint).font(ICON_FONT).into() // 创建文本元素并应用图标字体。
 }
 
-// 定义一个函数来获取默认文件的路径。
-fn default_file() -> PathBuf {
-    PathBuf::from(format!("{}/src/main.rs", env!("CARGO_MANIFEST_DIR"))) // 使用宏获取默认文件路径。
+// 定义一个函数来创建使用默认字体绘制的符号图标，用于图标字体中没有的图标。
+fn symbol<'a>(symbol: char) -> Element<'a, Message> {
+    text(symbol).into()
+}
+
+// 在后台读取标签页中的文件在 git HEAD 中的内容。
+fn load_head(tab: &Tab) -> Command<Message> {
+    let Some(path) = tab.path.clone() else {
+        return Command::none();
+    };
+
+    let id = tab.id;
+    Command::perform(git::head(path), move |head| Message::HeadLoaded(id, head))
+}
+
+// 在后台查找适用于标签页中文件的 .editorconfig 属性。
+fn load_editorconfig(tab: &Tab) -> Command<Message> {
+    let Some(path) = tab.path.clone() else {
+        return Command::none();
+    };
+
+    let id = tab.id;
+    Command::perform(editorconfig::load(path), move |properties| {
+        Message::EditorConfigLoaded(id, properties)
+    })
+}
+
+// 打开或保存文件后，重新读取 git HEAD 中的内容和 .editorconfig 属性。
+fn load_file_settings(tab: &Tab) -> Command<Message> {
+    Command::batch([load_head(tab), load_editorconfig(tab)])
+}
+
+// 将按键转换为十六进制数字，不是 0-9 或 A-F 时返回 None。
+fn hex_digit(key_code: keyboard::KeyCode) -> Option<u8> {
+    use keyboard::KeyCode;
+
+    let digit = match key_code {
+        KeyCode::Key0 | KeyCode::Numpad0 => 0,
+        KeyCode::Key1 | KeyCode::Numpad1 => 1,
+        KeyCode::Key2 | KeyCode::Numpad2 => 2,
+        KeyCode::Key3 | KeyCode::Numpad3 => 3,
+        KeyCode::Key4 | KeyCode::Numpad4 => 4,
+        KeyCode::Key5 | KeyCode::Numpad5 => 5,
+        KeyCode::Key6 | KeyCode::Numpad6 => 6,
+        KeyCode::Key7 | KeyCode::Numpad7 => 7,
+        KeyCode::Key8 | KeyCode::Numpad8 => 8,
+        KeyCode::Key9 | KeyCode::Numpad9 => 9,
+        KeyCode::A => 0xa,
+        KeyCode::B => 0xb,
+        KeyCode::C => 0xc,
+        KeyCode::D => 0xd,
+        KeyCode::E => 0xe,
+        KeyCode::F => 0xf,
+        _ => return None,
+    };
+
+    Some(digit)
 }
 
 // 定义一个异步函数来打开文件选择对话框并选择文件。
-async fn pick_file() -> Result<(PathBuf, Arc<String>), Error> {
-    let handle = rfd::AsyncFileDialog::new()
-        .set_title("Choose a text file")
+async fn pick_file() -> Result<PathBuf, Error> {
+    rfd::AsyncFileDialog::new()
+        .set_title(tr("Choose a text file"))
         .pick_file()
         .await
-        .ok_or(Error::DialogClosed)?; // 显示文件选择对话框并处理取消操作。
+        .map(|handle| handle.path().to_owned())
+        .ok_or(Error::DialogClosed) // 显示文件选择对话框并处理取消操作。
+}
+
+// 定义一个异步函数来加载文件内容，并自动检测文件的字符编码。
+async fn load_file(path: PathBuf) -> Result<(PathBuf, Decoded), Error> {
+    // 读取文件内容并处理可能的错误。
+    let bytes = tokio::fs::read(&path)
+        .await
+        .map_err(|error| Error::FileFailed(FileError::new(Operation::Open, &path, &error)))?;
+
+    let mut decoded = Encoding::detect(&bytes);
+    decoded.is_read_only = is_read_only(&path).await;
+
+    Ok((path, decoded)) // 返回文件路径和解码后的内容。
+}
+
+// 定义一个异步函数，使用指定的字符编码加载文件内容。
+async fn load_file_with(path: PathBuf, encoding: Encoding) -> Result<(PathBuf, Decoded), Error> {
+    // 读取文件内容并处理可能的错误。
+    let bytes = tokio::fs::read(&path)
+        .await
+        .map_err(|error| Error::FileFailed(FileError::new(Operation::Open, &path, &error)))?;
+
+    let mut decoded = encoding.decode(&bytes);
+    decoded.is_read_only = is_read_only(&path).await;
 
-    load_file(handle.path().to_owned()).await // 加载选择的文件。
+    Ok((path, decoded)) // 返回文件路径和解码后的内容。
 }
 
-// 定义一个异步函数来加载文件内容。
-async fn load_file(path: PathBuf) -> Result<(PathBuf, Arc<String>), Error> {
-    let contexts = tokio::fs::read_to_string(&path)
+// 定义一个异步函数，判断磁盘上的文件是否与指纹对应的内容不同。
+//
+// 文件无法读取（例如已被删除）时按没有变化处理，保存时会重新创建文件或报告错误。
+async fn changed_on_disk(path: PathBuf, encoding: Encoding, fingerprint: u64) -> bool {
+    disk_fingerprint(path, encoding)
         .await
-        .map(Arc::new)
-        .map_err(|error| error.kind())
-        .map_err(Error::IOFailed)?; // 读取文件内容并处理可能的错误。
+        .is_some_and(|disk| disk != fingerprint)
+}
+
+// 定义一个异步函数，计算磁盘上文件内容的指纹，文件无法读取时返回 None。
+async fn disk_fingerprint(path: PathBuf, encoding: Encoding) -> Option<u64> {
+    let bytes = tokio::fs::read(&path).await.ok()?;
+
+    Some(fingerprint_contents(&bytes, encoding))
+}
+
+// 计算文件内容的指纹：按照标签页的编码解码并统一换行符，与读取文件时创建文档的方式相同。
+fn fingerprint_contents(bytes: &[u8], encoding: Encoding) -> u64 {
+    tab::fingerprint([LineEnding::normalize(&encoding.decode(bytes).text).as_str()])
+}
 
-    Ok((path, contexts)) // 返回文件路径和内容。
+// 定义一个异步函数来检查文件是否只读：以写入方式打开文件（不会修改内容），被拒绝时认为只读。
+async fn is_read_only(path: &Path) -> bool {
+    tokio::fs::OpenOptions::new()
+        .write(true)
+        .open(path)
+        .await
+        .is_err_and(|error| error.kind() == io::ErrorKind::PermissionDenied)
 }
 // 定义一个异步函数来保存文件内容。
-async fn save_file(path: Option<PathBuf>, text: String) -> Result<PathBuf, Error> {
+async fn save_file(
+    path: Option<PathBuf>,
+    contents: Vec<u8>,
+    backup: bool,
+) -> Result<PathBuf, Error> {
     let path = if let Some(path) = path {
         path
     } else {
         rfd::AsyncFileDialog::new()
-            .set_title("Choose a file name...")
+            .set_title(tr("Choose a file name..."))
             .save_file()
             .await
             .ok_or(Error::DialogClosed)
             .map(|handle| handle.path().to_owned())? // 显示保存文件对话框并处理取消操作。
     };
 
-    tokio::fs::write(&path, text)
-        .await
-        .map_err(|error| Error::IOFailed(error.kind()))?; // 写入文件内容并处理可能的错误。
+    write_atomically(&path, &contents, backup).await?; // 写入文件内容并处理可能的错误。
 
     Ok(path) // 返回文件路径。
 }
 
+// 已经创建的临时文件的数量，用于生成不重复的临时文件名。
+static TEMPORARY_FILES: AtomicUsize = AtomicUsize::new(0);
+
+// 定义一个异步函数，先把内容写入同一目录中的临时文件，再用它替换原文件。
+//
+// 写入中途失败时原文件保持不变。新文件沿用原文件的权限，backup 为 true 时把原文件复制为同名的 .bak 文件。
+// 路径为符号链接时替换链接指向的文件，链接本身保持不变。
+async fn write_atomically(path: &Path, contents: &[u8], backup: bool) -> Result<(), Error> {
+    let path = tokio::fs::canonicalize(path)
+        .await
+        .unwrap_or_else(|_| path.to_path_buf());
+
+    let name = path
+        .file_name()
+        .ok_or_else(|| {
+            let error = io::Error::from(io::ErrorKind::InvalidInput);
+            Error::FileFailed(FileError::new(Operation::Save, &path, &error))
+        })?
+        .to_string_lossy()
+        .into_owned();
+
+    // 每次保存使用不同的临时文件，同时保存同一个文件时不会写入同一个临时文件。
+    let temporary = path.with_file_name(format!(
+        ".{name}.{}-{}.tmp",
+        std::process::id(),
+        TEMPORARY_FILES.fetch_add(1, Ordering::Relaxed)
+    ));
+    let permissions = tokio::fs::metadata(&path)
+        .await
+        .ok()
+        .map(|metadata| metadata.permissions());
+
+    let written = async {
+        let mut file = tokio::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&temporary)
+            .await?;
+        file.write_all(contents).await?;
+        file.sync_all().await?;
+
+        if let Some(permissions) = permissions.clone() {
+            tokio::fs::set_permissions(&temporary, permissions).await?;
+        }
+
+        if backup && permissions.is_some() {
+            tokio::fs::copy(&path, path.with_file_name(format!("{name}.bak"))).await?;
+        }
+
+        tokio::fs::rename(&temporary, &path).await
+    }
+    .await;
+
+    // 失败时删除写了一半的临时文件。
+    if let Err(error) = written {
+        let _ = tokio::fs::remove_file(&temporary).await;
+        return Err(Error::FileFailed(FileError::new(
+            Operation::Save,
+            &path,
+            &error,
+        )));
+    }
+
+    Ok(())
+}
+
+// 定义一个函数来获取配置文件的路径，配置文件保存在系统的配置目录中。
+fn config_file(name: &str) -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("iced-editor").join(name))
+}
+
+// 定义一个异步函数来写入配置文件，必要时创建配置目录。
+async fn write_config(path: Option<PathBuf>, contents: String) -> Result<(), Error> {
+    let Some(path) = path else {
+        return Ok(());
+    };
+
+    // 创建配置目录。
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await.map_err(|error| {
+            Error::FileFailed(FileError::new(Operation::CreateFolder, parent, &error))
+        })?;
+    }
+
+    // 写入配置文件并处理可能的错误。
+    tokio::fs::write(&path, contents)
+        .await
+        .map_err(|error| Error::FileFailed(FileError::new(Operation::Write, &path, &error)))
+}
+
 // 定义错误类型枚举。
 #[derive(Debug, Clone)]
 enum Error {
-    DialogClosed,            // 表示对话框被关闭。
-    IOFailed(io::ErrorKind), // 表示输入/输出操作失败。
+    DialogClosed,                 // 表示对话框被关闭。
+    FileFailed(FileError),        // 表示读写文件或运行程序失败，附带路径、操作和完整的错误信息。
+    InvalidConfig(String),        // 表示配置文件的内容无效。
+    InvalidJson(String),          // 表示 JSON 文档无法解析，附带出错的位置和原因。
+    TransformFailed(String),      // 表示无法变换选中的文本，附带原因。
+    GitFailed(String),            // 表示 git 命令执行失败，附带 git 输出的错误信息。
+    LanguageServerFailed(String), // 表示语言服务器意外退出或无法启动。
+    CheckFailed(String),          // 表示 cargo check 无法运行，附带 cargo 输出的错误信息。
+    FormatFailed(String),         // 表示格式化工具无法运行或报告了错误。
+    ExportFailed(String),         // 表示无法生成导出的文件。
+    RecoveryFailed(String),       // 表示无法恢复未保存的修改，附带原因。
+    DownloadFailed(String),       // 表示无法下载网址的内容，附带原因。
+    RemoteFailed(String),         // 表示无法连接远程服务器或读写远程文件，附带原因。
+    UnknownHost(remote::HostKey), // 表示服务器的主机密钥不在 known_hosts 中，需要用户确认。
+    PluginFailed(String),         // 表示插件无法加载或运行时出错，附带插件名称和原因。
+    ScriptFailed(String),         // 表示无法应用脚本的结果，附带原因。
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::DialogClosed => f.write_str(tr("Dialog closed")),
+            Self::FileFailed(error) => write!(f, "{error}"),
+            Self::UnknownHost(key) => f.write_str(&trf(
+                "The authenticity of {server} can't be established. Its key fingerprint is {fingerprint}.",
+                &[("server", &key.server), ("fingerprint", &key.fingerprint)],
+            )),
+            Self::InvalidConfig(error)
+            | Self::InvalidJson(error)
+            | Self::TransformFailed(error)
+            | Self::GitFailed(error)
+            | Self::LanguageServerFailed(error)
+            | Self::CheckFailed(error)
+            | Self::FormatFailed(error)
+            | Self::ExportFailed(error)
+            | Self::RecoveryFailed(error)
+            | Self::DownloadFailed(error)
+            | Self::RemoteFailed(error)
+            | Self::PluginFailed(error)
+            | Self::ScriptFailed(error) => f.write_str(error),
+        }
+    }
+}
+
+// 定义读写文件的操作，用于说明出错时正在做什么。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operation {
+    Open,         // 打开文件。
+    Save,         // 保存文件。
+    Read,         // 读取配置等其他文件。
+    Write,        // 写入配置、导出等其他文件。
+    Delete,       // 删除文件。
+    CreateFolder, // 创建目录。
+    ListFolder,   // 列出目录中的条目。
+    Run,          // 运行外部程序，路径为程序名称。
+}
+
+// 定义读写文件或运行外部程序时发生的错误。
+//
+// io::Error 不能克隆，因此只保留错误的类型和完整的文字。
+#[derive(Debug, Clone)]
+struct FileError {
+    operation: Operation, // 出错的操作。
+    path: PathBuf,        // 出错的文件。
+    kind: io::ErrorKind,  // 错误的类型，用于选择更容易理解的说明。
+    message: String,      // 操作系统给出的完整错误信息。
+}
+
+impl FileError {
+    fn new(operation: Operation, path: &Path, error: &io::Error) -> Self {
+        Self {
+            operation,
+            path: path.to_path_buf(),
+            kind: error.kind(),
+            message: error.to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for FileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // 常见的错误用简短的说明代替操作系统的原文。
+        let reason = match self.kind {
+            io::ErrorKind::NotFound => tr("the file does not exist"),
+            io::ErrorKind::PermissionDenied => tr("permission denied"),
+            _ => self.message.as_str(),
+        };
+
+        let message = match self.operation {
+            Operation::Open => "Could not open {path}: {reason}",
+            Operation::Save => "Could not save {path}: {reason}",
+            Operation::Read => "Could not read {path}: {reason}",
+            Operation::Write => "Could not write {path}: {reason}",
+            Operation::Delete => "Could not delete {path}: {reason}",
+            Operation::CreateFolder => "Could not create the folder {path}: {reason}",
+            Operation::ListFolder => "Could not read the folder {path}: {reason}",
+            Operation::Run => "Could not run {path}: {reason}",
+        };
+
+        f.write_str(&trf(
+            message,
+            &[("path", &self.path.display()), ("reason", &reason)],
+        ))
+    }
 }
@@ -0,0 +1,121 @@
+use crate::Message;
+use iced::widget::{column, container, horizontal_rule, row, scrollable, text, Space};
+use iced::{theme, Element, Font, Length};
+use pulldown_cmark::{Event, Parser, Tag};
+
+// 定义预览中渲染的 Markdown 块。
+#[derive(Debug, Clone)]
+pub enum Block {
+    Heading(usize, String),  // 标题，附带标题级别。
+    Paragraph(String),       // 段落。
+    ListItem(usize, String), // 列表项，附带嵌套深度。
+    Quote(String),           // 引用。
+    Code(String),            // 代码块。
+    Rule,                    // 分隔线。
+}
+
+// 定义某个标签页在某个版本下的 Markdown 预览。
+#[derive(Debug)]
+pub struct Preview {
+    pub tab: usize,      // 预览对应的标签页标识。
+    pub revision: usize, // 预览对应的文档版本。
+    blocks: Vec<Block>,  // 解析得到的 Markdown 块。
+}
+
+impl Preview {
+    // 解析 Markdown 文本，生成预览。
+    pub fn new(tab: usize, revision: usize, markdown: &str) -> Self {
+        Self {
+            tab,
+            revision,
+            blocks: parse(markdown),
+        }
+    }
+
+    // 创建预览的视图。
+    pub fn view(&self) -> Element<'_, Message> {
+        let blocks = self.blocks.iter().map(|block| -> Element<'_, Message> {
+            match block {
+                Block::Heading(level, content) => {
+                    text(content).size(32 - 3 * (*level).min(6) as u16).into()
+                }
+                Block::Paragraph(content) => text(content).into(),
+                Block::ListItem(depth, content) => row![
+                    Space::with_width(16 * (*depth).max(1) as u16),
+                    text("•"),
+                    text(content)
+                ]
+                .spacing(5)
+                .into(),
+                Block::Quote(content) => container(text(content))
+                    .padding([5, 10])
+                    .style(theme::Container::Box)
+                    .into(),
+                Block::Code(content) => container(text(content).font(Font::MONOSPACE))
+                    .width(Length::Fill)
+                    .padding(10)
+                    .style(theme::Container::Box)
+                    .into(),
+                Block::Rule => horizontal_rule(1).into(),
+            }
+        });
+
+        container(scrollable(column(blocks.collect()).spacing(10).padding(10)))
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .into()
+    }
+}
+
+// 将 Markdown 文本解析为预览块，行内样式以纯文本显示。
+fn parse(markdown: &str) -> Vec<Block> {
+    let mut blocks = Vec::new();
+    let mut content = String::new();
+    let mut list_depth = 0;
+    let mut quote_depth = 0;
+
+    for event in Parser::new(markdown) {
+        match event {
+            Event::Start(Tag::List(_)) => {
+                // 嵌套列表开始之前，先输出父列表项的内容。
+                if list_depth > 0 && !content.trim().is_empty() {
+                    blocks.push(Block::ListItem(list_depth, take(&mut content)));
+                }
+                list_depth += 1;
+            }
+            Event::End(Tag::List(_)) => list_depth -= 1,
+            Event::Start(Tag::BlockQuote) => quote_depth += 1,
+            Event::End(Tag::BlockQuote) => quote_depth -= 1,
+            Event::End(Tag::Heading(level, _, _)) => {
+                blocks.push(Block::Heading(level as usize, take(&mut content)));
+            }
+            Event::End(Tag::Paragraph) if list_depth > 0 => content.push('\n'),
+            Event::End(Tag::Paragraph) if quote_depth > 0 => {
+                blocks.push(Block::Quote(take(&mut content)));
+            }
+            Event::End(Tag::Paragraph) => blocks.push(Block::Paragraph(take(&mut content))),
+            Event::End(Tag::Item) => {
+                if !content.trim().is_empty() {
+                    blocks.push(Block::ListItem(list_depth, take(&mut content)));
+                }
+            }
+            Event::End(Tag::CodeBlock(_)) => blocks.push(Block::Code(take(&mut content))),
+            Event::Text(fragment) | Event::Code(fragment) | Event::Html(fragment) => {
+                content.push_str(&fragment);
+            }
+            Event::SoftBreak => content.push(' '),
+            Event::HardBreak => content.push('\n'),
+            Event::Rule => blocks.push(Block::Rule),
+            _ => {}
+        }
+    }
+
+    blocks
+}
+
+// 取出累积的文本并去掉首尾空白。
+fn take(content: &mut String) -> String {
+    let taken = content.trim().to_string();
+    content.clear();
+    taken
+}
@@ -0,0 +1,254 @@
+use crate::gutter::{Metrics, Viewport};
+use crate::Message;
+use iced::mouse;
+use iced::widget::canvas::{self, Cache, Canvas, Frame, Geometry};
+use iced::widget::text_editor::Action;
+use iced::{event, Color, Element, Length, Point, Rectangle, Renderer, Size, Theme};
+use ropey::Rope;
+use std::cell::Cell;
+
+// 缩略图的宽度。
+pub const WIDTH: f32 = 100.0;
+
+// 缩略图中每一行的高度和每个字符的宽度。
+const LINE_HEIGHT: f32 = 2.0;
+const CHARACTER_WIDTH: f32 = 1.0;
+
+// 缩略图中文字块的高度，比行高略小，使相邻的行之间留出空隙。
+const BLOCK_HEIGHT: f32 = 1.5;
+
+// 缩略图中制表符占据的字符数。
+const TAB_WIDTH: usize = 4;
+
+// 创建显示文档缩略图的画布，放在编辑器右侧。
+//
+// revision 为文档内容的版本号，内容和显示范围不变时直接使用缓存的图形，
+// 每次只绘制缩略图中能容纳的行，因此大文件也不会拖慢界面。
+pub fn view<'a>(
+    rope: &'a Rope,
+    revision: usize,
+    viewport: Viewport,
+    metrics: Metrics,
+    on_edit: fn(Action) -> Message,
+) -> Element<'a, Message> {
+    Canvas::new(Minimap {
+        rope,
+        revision,
+        viewport,
+        metrics,
+        on_edit,
+    })
+    .width(WIDTH)
+    .height(Length::Fill)
+    .into()
+}
+
+// 绘制缩略图的画布。
+struct Minimap<'a> {
+    rope: &'a Rope,                 // 文档的内容。
+    revision: usize,                // 文档内容的版本号。
+    viewport: Viewport,             // 编辑器的滚动位置。
+    metrics: Metrics,               // 编辑器的尺寸。
+    on_edit: fn(Action) -> Message, // 滚动编辑器时发送的消息。
+}
+
+// 定义缩略图的状态。
+#[derive(Default)]
+struct State {
+    cache: Cache,                            // 缓存的文档内容的图形。
+    drawn: Cell<Option<Key>>,                // 缓存中的图形对应的内容和显示范围。
+    is_dragging: bool,                       // 是否正在拖动。
+    requested: Option<(Viewport, Viewport)>, // 最近一次请求滚动时编辑器的位置和目标位置。
+}
+
+// 定义决定缩略图内容的参数，任何一项变化时都需要重新绘制。
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Key {
+    revision: usize, // 文档内容的版本号。
+    length: usize,   // 文档的字符数，重新加载文件后版本号可能相同。
+    first: usize,    // 缩略图中的第一行。
+    size: Size,      // 缩略图的尺寸。
+    text: Color,     // 文字的颜色，随主题变化。
+}
+
+impl Minimap<'_> {
+    // 返回缩略图中能容纳的行数。
+    fn capacity(&self, bounds: Rectangle) -> usize {
+        (bounds.height / LINE_HEIGHT) as usize
+    }
+
+    // 返回缩略图中的第一行。
+    //
+    // 文档能完整显示时从第一行开始，否则随着编辑器的滚动按比例移动，
+    // 编辑器滚动到底部时缩略图也正好显示到最后一行。
+    fn first_line(&self, bounds: Rectangle) -> usize {
+        let line_count = self.rope.len_lines();
+        let capacity = self.capacity(bounds);
+
+        if line_count <= capacity {
+            return 0;
+        }
+
+        let scrollable = line_count.saturating_sub(self.metrics.rows).max(1);
+        let progress = (self.viewport.line as f32 / scrollable as f32).min(1.0);
+
+        ((line_count - capacity) as f32 * progress) as usize
+    }
+
+    // 返回点击缩略图中的某个位置时编辑器应该滚动到的位置，使点击的行位于可见区域的中间。
+    fn target(&self, bounds: Rectangle, y: f32) -> Viewport {
+        let line = self.first_line(bounds) + (y.max(0.0) / LINE_HEIGHT) as usize;
+        let line = line.min(self.rope.len_lines().saturating_sub(1));
+
+        Viewport {
+            line: line.saturating_sub(self.metrics.rows / 2),
+            row: 0,
+        }
+    }
+}
+
+impl canvas::Program<Message> for Minimap<'_> {
+    type State = State;
+
+    fn update(
+        &self,
+        state: &mut Self::State,
+        event: canvas::Event,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> (event::Status, Option<Message>) {
+        let canvas::Event::Mouse(event) = event else {
+            return (event::Status::Ignored, None);
+        };
+
+        match event {
+            mouse::Event::ButtonPressed(mouse::Button::Left) if cursor.is_over(bounds) => {
+                state.is_dragging = true;
+            }
+            mouse::Event::ButtonReleased(mouse::Button::Left) if state.is_dragging => {
+                state.is_dragging = false;
+                state.requested = None;
+                return (event::Status::Captured, None);
+            }
+            mouse::Event::CursorMoved { .. } if state.is_dragging => {}
+            _ => return (event::Status::Ignored, None),
+        }
+
+        let Some(position) = cursor.position() else {
+            return (event::Status::Captured, None);
+        };
+
+        let target = self.target(bounds, position.y - bounds.y);
+
+        // 编辑器还没有按照上一次的请求刷新时，从上一次请求的位置开始计算，避免重复滚动。
+        let current = match state.requested {
+            Some((viewport, requested)) if viewport == self.viewport => requested,
+            _ => self.viewport,
+        };
+
+        if target == current {
+            return (event::Status::Captured, None);
+        }
+
+        state.requested = Some((self.viewport, target));
+
+        let lines = current.rows_to(self.rope, self.metrics, target);
+        let message = (self.on_edit)(Action::Scroll { lines });
+
+        (event::Status::Captured, Some(message))
+    }
+
+    fn draw(
+        &self,
+        state: &Self::State,
+        renderer: &Renderer,
+        theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<Geometry> {
+        let text = theme.palette().text;
+        let first = self.first_line(bounds);
+
+        let key = Key {
+            revision: self.revision,
+            length: self.rope.len_chars(),
+            first,
+            size: bounds.size(),
+            text,
+        };
+
+        if state.drawn.get() != Some(key) {
+            state.cache.clear();
+            state.drawn.set(Some(key));
+        }
+
+        let content = state.cache.draw(renderer, bounds.size(), |frame| {
+            let color = Color { a: 0.5, ..text };
+            let last = (first + self.capacity(bounds)).min(self.rope.len_lines());
+
+            for (row, line) in (first..last).enumerate() {
+                draw_line(frame, self.rope.line(line).chars(), row, color);
+            }
+        });
+
+        // 可见区域的位置随滚动变化，单独绘制，不影响缓存。
+        let mut frame = Frame::new(renderer, bounds.size());
+        let top = self.viewport.line.saturating_sub(first) as f32 * LINE_HEIGHT;
+
+        frame.fill_rectangle(
+            Point::new(0.0, top),
+            Size::new(bounds.width, self.metrics.rows as f32 * LINE_HEIGHT),
+            Color { a: 0.1, ..text },
+        );
+
+        vec![content, frame.into_geometry()]
+    }
+
+    fn mouse_interaction(
+        &self,
+        state: &Self::State,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> mouse::Interaction {
+        if state.is_dragging || cursor.is_over(bounds) {
+            mouse::Interaction::Pointer
+        } else {
+            mouse::Interaction::default()
+        }
+    }
+}
+
+// 在缩略图的第 row 行中，将每一段连续的非空白字符画成一个色块。
+fn draw_line(frame: &mut Frame, chars: impl Iterator<Item = char>, row: usize, color: Color) {
+    let y = row as f32 * LINE_HEIGHT;
+    let columns = (frame.width() / CHARACTER_WIDTH) as usize;
+
+    let mut column = 0;
+    let mut start = None;
+
+    let mut flush = |start: &mut Option<usize>, end: usize| {
+        if let Some(start) = start.take() {
+            frame.fill_rectangle(
+                Point::new(start as f32 * CHARACTER_WIDTH, y),
+                Size::new((end - start) as f32 * CHARACTER_WIDTH, BLOCK_HEIGHT),
+                color,
+            );
+        }
+    };
+
+    for c in chars {
+        if column >= columns || c == '\n' {
+            break;
+        }
+
+        if c.is_whitespace() {
+            flush(&mut start, column);
+            column += if c == '\t' { TAB_WIDTH } else { 1 };
+        } else {
+            start.get_or_insert(column);
+            column += 1;
+        }
+    }
+
+    flush(&mut start, column.min(columns));
+}
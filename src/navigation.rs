@@ -0,0 +1,240 @@
+use crate::completion;
+use crate::tab::Tab;
+use iced::keyboard::Modifiers;
+use iced::widget::text_editor::{Action, Edit, Motion};
+use ropey::Rope;
+use std::ops::Range;
+
+// 定义按单词移动时区分的字符种类，连续的同一种字符组成一个单词。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Class {
+    Whitespace,  // 空白字符。
+    Word,        // 字母、数字和下划线。
+    Punctuation, // 其他字符，例如运算符和括号。
+}
+
+// 返回字符的种类。
+fn class(c: char) -> Class {
+    if c.is_whitespace() {
+        Class::Whitespace
+    } else if completion::is_word_char(c) {
+        Class::Word
+    } else {
+        Class::Punctuation
+    }
+}
+
+// 在编辑器处理按键产生的动作之前处理行首和按单词的移动、删除与选择，返回是否已经处理。
+//
+// Home 在第一个非空白字符和第 0 列之间切换；按住 Ctrl（macOS 上为 Option）时，
+// 左右方向键跳过一个单词，退格键和删除键删除光标前后的一个单词。按住 Shift 时扩展选区。
+// 双击时按同样的规则选中单词。
+pub fn handle(tab: &mut Tab, action: &Action, modifiers: Modifiers) -> bool {
+    let jump = modifiers.jump();
+
+    match *action {
+        Action::Move(Motion::Home) => smart_home(tab, false),
+        Action::Select(Motion::Home) => smart_home(tab, true),
+        Action::Move(Motion::WordLeft) => word_left(tab, false),
+        Action::Select(Motion::WordLeft) => word_left(tab, true),
+        Action::Move(Motion::WordRight) => word_right(tab, false),
+        Action::Select(Motion::WordRight) => word_right(tab, true),
+        Action::Move(Motion::Left) if jump => word_left(tab, false),
+        Action::Select(Motion::Left) if jump => word_left(tab, true),
+        Action::Move(Motion::Right) if jump => word_right(tab, false),
+        Action::Select(Motion::Right) if jump => word_right(tab, true),
+        Action::Edit(Edit::Backspace) if jump => delete_word(tab, Motion::Left),
+        Action::Edit(Edit::Delete) if jump => delete_word(tab, Motion::Right),
+        Action::SelectWord => {
+            select_word(tab);
+            true
+        }
+        _ => false,
+    }
+}
+
+// 选中光标处的单词。
+pub fn select_word(tab: &mut Tab) {
+    let rope = tab.context.rope();
+    let (line, column) = tab.context.cursor_position();
+    let before = tab.context.line(line).map_or(0, |text| {
+        text.get(..column).unwrap_or(&text).chars().count()
+    });
+    let line_start = rope.line_to_char(line);
+    let word = word_at(rope, line_start + before);
+
+    tab.move_cursor_to(line, word.start - line_start);
+
+    for _ in word {
+        tab.edit(Action::Select(Motion::Right));
+    }
+}
+
+// 返回文档中 offset 处的单词的字符范围，单词不会跨越行尾。
+//
+// 优先使用光标后的字符的种类；光标紧跟在单词之后时（例如 foo|.）选中前面的单词。
+pub fn word_at(rope: &Rope, offset: usize) -> Range<usize> {
+    let is_line_char = |c: &char| *c != '\n' && *c != '\r';
+    let after = rope.get_char(offset).filter(is_line_char).map(class);
+    let before = offset
+        .checked_sub(1)
+        .and_then(|index| rope.get_char(index))
+        .filter(is_line_char)
+        .map(class);
+
+    let kind = match (before, after) {
+        (Some(Class::Word), Some(after)) if after != Class::Word => Class::Word,
+        (_, Some(after)) => after,
+        (Some(before), None) => before,
+        (None, None) => return offset..offset,
+    };
+    let is_part = |c: &char| is_line_char(c) && class(*c) == kind;
+
+    let start = offset - rope.chars_at(offset).reversed().take_while(is_part).count();
+    let end = offset + rope.chars_at(offset).take_while(is_part).count();
+
+    start..end
+}
+
+// 把光标移到第一个非空白字符，已经在那里时移到第 0 列。
+fn smart_home(tab: &mut Tab, select: bool) -> bool {
+    let (before, after) = split_line(tab);
+    let column = before.chars().count();
+    let indentation = before
+        .chars()
+        .chain(after.chars())
+        .take_while(|c| c.is_whitespace())
+        .count();
+
+    let target = if column == indentation {
+        0
+    } else {
+        indentation
+    };
+
+    move_to(tab, column, target, select);
+    true
+}
+
+// 把光标移到前一个单词的开头，已经在行首时移到上一行的行尾。
+fn word_left(tab: &mut Tab, select: bool) -> bool {
+    let (before, _) = split_line(tab);
+    let column = before.chars().count();
+
+    if column == 0 {
+        tab.edit(motion(Motion::Left, select));
+    } else {
+        move_to(tab, column, column - word_before(&before), select);
+    }
+    true
+}
+
+// 把光标移到后一个单词的结尾，已经在行尾时移到下一行的行首。
+fn word_right(tab: &mut Tab, select: bool) -> bool {
+    let (before, after) = split_line(tab);
+    let column = before.chars().count();
+
+    if after.is_empty() {
+        tab.edit(motion(Motion::Right, select));
+    } else {
+        move_to(tab, column, column + word_after(&after), select);
+    }
+    true
+}
+
+// 删除光标前（Left）或光标后（Right）的一个单词。
+//
+// 有选区时、在行首退格或在行尾删除时交给编辑器处理，删除选区或者合并两行。
+fn delete_word(tab: &mut Tab, direction: Motion) -> bool {
+    if tab.is_read_only || tab.context.selection().is_some() {
+        return false;
+    }
+
+    let (before, after) = split_line(tab);
+    let (count, edit) = match direction {
+        Motion::Left => (word_before(&before), Edit::Backspace),
+        _ => (word_after(&after), Edit::Delete),
+    };
+
+    if count == 0 {
+        return false;
+    }
+
+    for _ in 0..count {
+        tab.edit(Action::Select(direction));
+    }
+    tab.edit(Action::Edit(edit));
+    true
+}
+
+// 返回光标所在行中光标前和光标后的文本，不包括换行符。
+fn split_line(tab: &Tab) -> (String, String) {
+    let (line, column) = tab.context.cursor_position();
+    let text = tab
+        .context
+        .line(line)
+        .map(|text| text.to_string())
+        .unwrap_or_default();
+    let text = text.trim_end_matches(['\n', '\r']);
+    let column = column.min(text.len());
+
+    (text[..column].to_string(), text[column..].to_string())
+}
+
+// 返回光标前一个单词（包括它之后的空白）的字符数。
+fn word_before(before: &str) -> usize {
+    word_length(before.chars().rev())
+}
+
+// 返回光标后一个单词（包括它之前的空白）的字符数。
+fn word_after(after: &str) -> usize {
+    word_length(after.chars())
+}
+
+// 返回从开头跳过空白和之后一串同一种字符时经过的字符数。
+fn word_length(chars: impl Iterator<Item = char> + Clone) -> usize {
+    let spaces = chars
+        .clone()
+        .take_while(|c| class(*c) == Class::Whitespace)
+        .count();
+    let mut rest = chars.skip(spaces).peekable();
+
+    let Some(first) = rest.peek().copied().map(class) else {
+        return spaces;
+    };
+
+    spaces + rest.take_while(|c| class(*c) == first).count()
+}
+
+// 把光标从第 column 个字符移到第 target 个字符。
+//
+// 不扩展选区时先取消已有的选区：从行首开始移动，否则编辑器会先把光标移到选区的一端。
+fn move_to(tab: &mut Tab, column: usize, target: usize, select: bool) {
+    if !select && tab.context.selection().is_some() {
+        tab.edit(Action::Move(Motion::Home));
+
+        for _ in 0..target {
+            tab.edit(Action::Move(Motion::Right));
+        }
+        return;
+    }
+
+    let (direction, count) = if target < column {
+        (Motion::Left, column - target)
+    } else {
+        (Motion::Right, target - column)
+    };
+
+    for _ in 0..count {
+        tab.edit(motion(direction, select));
+    }
+}
+
+// 返回移动光标或扩展选区的动作。
+fn motion(motion: Motion, select: bool) -> Action {
+    if select {
+        Action::Select(motion)
+    } else {
+        Action::Move(motion)
+    }
+}
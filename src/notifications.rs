@@ -0,0 +1,476 @@
+use crate::diagnostics::Severity;
+use crate::i18n::tr;
+use crate::{Error, Message};
+use iced::advanced::layout::{self, Layout};
+use iced::advanced::renderer;
+use iced::advanced::widget::{Operation, Tree, Widget};
+use iced::advanced::{overlay, Clipboard, Shell};
+use iced::event::{self, Event};
+use iced::mouse;
+use iced::widget::{button, column, container, horizontal_space, row, scrollable, text, Space};
+use iced::{theme, Alignment, Element, Length, Point, Rectangle, Renderer, Size, Theme};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+// 普通通知显示的时长。
+const INFO_DURATION: Duration = Duration::from_secs(3);
+
+// 错误显示的时长，比普通通知长，留出阅读的时间。
+const ERROR_DURATION: Duration = Duration::from_secs(8);
+
+// 历史中最多保留的通知数，超过时丢弃最早的通知。
+const MAX_HISTORY: usize = 50;
+
+// 通知和历史面板的宽度。
+const WIDTH: f32 = 320.0;
+
+// 通知与窗口右边和下边的距离，下边留出状态栏的位置。
+const MARGIN: Size = Size::new(20.0, 45.0);
+
+// 定义通知的级别。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    Info,  // 操作完成等普通消息。
+    Error, // 操作失败的原因。
+}
+
+// 定义一条通知。
+#[derive(Debug, Clone)]
+struct Notification {
+    id: usize,                // 通知的标识，用于关闭或删除。
+    level: Level,             // 通知的级别。
+    message: String,          // 通知的内容。
+    action: Option<Message>,  // 点击“重试”时发送的消息，执行一次后清除。
+    expires: Option<Instant>, // 通知消失的时刻，已经消失或被关闭时为 None，但仍保留在历史中。
+}
+
+// 定义通知中心：新的通知先短暂显示在窗口右下角，之后保留在历史中，可以从状态栏打开查看。
+#[derive(Debug, Default)]
+pub struct Notifications {
+    history: VecDeque<Notification>, // 最近的通知，最新的在最后。
+    next_id: usize,                  // 下一条通知的标识。
+    pub show_history: bool,          // 是否显示通知历史。
+}
+
+impl Notifications {
+    // 显示一条普通通知。
+    pub fn info(&mut self, message: impl Into<String>) {
+        self.push(Level::Info, message.into(), None);
+    }
+
+    // 显示一条错误。关闭对话框不算错误，不显示通知。
+    pub fn error(&mut self, error: Error) {
+        if !matches!(error, Error::DialogClosed) {
+            self.push(Level::Error, error.to_string(), None);
+        }
+    }
+
+    // 显示一条可以重试的错误，点击“重试”时发送 retry 消息。
+    pub fn retry(&mut self, error: Error, retry: Message) {
+        if !matches!(error, Error::DialogClosed) {
+            self.push(Level::Error, error.to_string(), Some(retry));
+        }
+    }
+
+    // 添加通知。与正在显示的通知相同时只延长显示的时间，以免重复出现的错误堆满屏幕。
+    fn push(&mut self, level: Level, message: String, action: Option<Message>) {
+        let expires = Instant::now()
+            + match level {
+                Level::Info => INFO_DURATION,
+                Level::Error => ERROR_DURATION,
+            };
+
+        if let Some(notification) = self.history.iter_mut().find(|notification| {
+            notification.expires.is_some()
+                && notification.level == level
+                && notification.message == message
+        }) {
+            notification.expires = Some(expires);
+            notification.action = action;
+            return;
+        }
+
+        if self.history.len() >= MAX_HISTORY {
+            let _ = self.history.pop_front();
+        }
+
+        self.history.push_back(Notification {
+            id: self.next_id,
+            level,
+            message,
+            action,
+            expires: Some(expires),
+        });
+        self.next_id += 1;
+    }
+
+    // 隐藏到期的通知，它们仍然保留在历史中。
+    pub fn tick(&mut self, now: Instant) {
+        for notification in &mut self.history {
+            if notification.expires.is_some_and(|expires| expires <= now) {
+                notification.expires = None;
+            }
+        }
+    }
+
+    // 判断是否有正在显示的通知，有时需要定时检查通知是否到期。
+    pub fn has_toasts(&self) -> bool {
+        self.history
+            .iter()
+            .any(|notification| notification.expires.is_some())
+    }
+
+    // 提前关闭正在显示的通知。
+    pub fn dismiss(&mut self, id: usize) {
+        if let Some(notification) = self.history.iter_mut().find(|n| n.id == id) {
+            notification.expires = None;
+        }
+    }
+
+    // 关闭所有正在显示的错误，用于重试的操作成功之后。
+    pub fn dismiss_errors(&mut self) {
+        for notification in &mut self.history {
+            if notification.level == Level::Error {
+                notification.expires = None;
+            }
+        }
+    }
+
+    // 取出通知中的操作并关闭通知，操作只能执行一次。
+    pub fn take_action(&mut self, id: usize) -> Option<Message> {
+        let notification = self.history.iter_mut().find(|n| n.id == id)?;
+        notification.expires = None;
+        notification.action.take()
+    }
+
+    // 从历史中删除一条通知。
+    pub fn remove(&mut self, id: usize) {
+        self.history.retain(|notification| notification.id != id);
+    }
+
+    // 清空历史。
+    pub fn clear(&mut self) {
+        self.history.clear();
+    }
+
+    // 返回历史中的通知数。
+    pub fn len(&self) -> usize {
+        self.history.len()
+    }
+
+    // 创建浮层的内容：打开历史时显示历史，否则显示正在显示的通知；都没有时返回 None。
+    pub fn view(&self) -> Option<Element<'_, Message>> {
+        if self.show_history {
+            return Some(self.history());
+        }
+
+        let toasts: Vec<_> = self
+            .history
+            .iter()
+            .filter(|notification| notification.expires.is_some())
+            .map(|notification| {
+                container(item(notification, Message::DismissNotification))
+                    .width(WIDTH)
+                    .padding(10)
+                    .style(theme::Container::Box)
+                    .into()
+            })
+            .collect();
+
+        (!toasts.is_empty()).then(|| column(toasts).spacing(10).into())
+    }
+
+    // 创建通知历史的视图，最新的通知在最上面。
+    fn history(&self) -> Element<'_, Message> {
+        let header = row![
+            text(tr("Notifications")).size(16),
+            horizontal_space(Length::Fill),
+            button(text(tr("Clear")).size(14))
+                .on_press_maybe((!self.history.is_empty()).then_some(Message::ClearNotifications))
+                .padding([2, 5])
+                .style(theme::Button::Secondary),
+            button(text("×"))
+                .on_press(Message::ToggleNotifications)
+                .style(theme::Button::Text)
+        ]
+        .spacing(5)
+        .align_items(Alignment::Center);
+
+        let list: Element<_> = if self.history.is_empty() {
+            text(tr("No notifications")).size(14).into()
+        } else {
+            scrollable(
+                column(
+                    self.history
+                        .iter()
+                        .rev()
+                        .map(|notification| item(notification, Message::RemoveNotification))
+                        .collect(),
+                )
+                .spacing(5),
+            )
+            .height(Length::Shrink)
+            .into()
+        };
+
+        container(column![header, list].spacing(10))
+            .width(WIDTH)
+            .max_height(400.0)
+            .padding(10)
+            .style(theme::Container::Box)
+            .into()
+    }
+}
+
+// 创建一条通知的视图，点击 × 时发送 on_close 消息。
+fn item(notification: &Notification, on_close: fn(usize) -> Message) -> Element<'_, Message> {
+    let message = text(&notification.message).size(14).width(Length::Fill);
+
+    let message = match notification.level {
+        Level::Info => message,
+        Level::Error => message.style(Severity::Error.color()),
+    };
+
+    let retry: Element<_> = match notification.action {
+        Some(_) => button(text(tr("Retry")).size(14))
+            .on_press(Message::NotificationAction(notification.id))
+            .padding([0, 5])
+            .into(),
+        None => Space::with_width(0).into(),
+    };
+
+    row![
+        message,
+        retry,
+        button(text("×").size(14))
+            .on_press(on_close(notification.id))
+            .padding([0, 5])
+            .style(theme::Button::Text)
+    ]
+    .spacing(5)
+    .into()
+}
+
+// 在内容之上显示通知的浮层，通知固定在窗口的右下角，不占用内容的空间。
+pub fn toasts<'a>(
+    content: impl Into<Element<'a, Message>>,
+    toasts: Option<Element<'a, Message>>,
+) -> Element<'a, Message> {
+    Element::new(Toasts {
+        content: content.into(),
+        toasts,
+    })
+}
+
+// 显示通知浮层的控件，其余的行为都交给内部的内容处理。
+struct Toasts<'a> {
+    content: Element<'a, Message>,        // 窗口的内容。
+    toasts: Option<Element<'a, Message>>, // 正在显示的通知或通知历史。
+}
+
+impl Widget<Message, Renderer> for Toasts<'_> {
+    fn children(&self) -> Vec<Tree> {
+        let mut children = vec![Tree::new(&self.content)];
+        children.extend(self.toasts.as_ref().map(Tree::new));
+        children
+    }
+
+    fn diff(&self, tree: &mut Tree) {
+        match &self.toasts {
+            Some(toasts) => tree.diff_children(&[&self.content, toasts]),
+            None => tree.diff_children(std::slice::from_ref(&self.content)),
+        }
+    }
+
+    fn width(&self) -> Length {
+        self.content.as_widget().width()
+    }
+
+    fn height(&self) -> Length {
+        self.content.as_widget().height()
+    }
+
+    fn layout(
+        &self,
+        tree: &mut Tree,
+        renderer: &Renderer,
+        limits: &layout::Limits,
+    ) -> layout::Node {
+        self.content
+            .as_widget()
+            .layout(&mut tree.children[0], renderer, limits)
+    }
+
+    fn operate(
+        &self,
+        tree: &mut Tree,
+        layout: Layout<'_>,
+        renderer: &Renderer,
+        operation: &mut dyn Operation<Message>,
+    ) {
+        self.content
+            .as_widget()
+            .operate(&mut tree.children[0], layout, renderer, operation);
+    }
+
+    fn on_event(
+        &mut self,
+        tree: &mut Tree,
+        event: Event,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        renderer: &Renderer,
+        clipboard: &mut dyn Clipboard,
+        shell: &mut Shell<'_, Message>,
+        viewport: &Rectangle,
+    ) -> event::Status {
+        self.content.as_widget_mut().on_event(
+            &mut tree.children[0],
+            event,
+            layout,
+            cursor,
+            renderer,
+            clipboard,
+            shell,
+            viewport,
+        )
+    }
+
+    fn mouse_interaction(
+        &self,
+        tree: &Tree,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        viewport: &Rectangle,
+        renderer: &Renderer,
+    ) -> mouse::Interaction {
+        self.content.as_widget().mouse_interaction(
+            &tree.children[0],
+            layout,
+            cursor,
+            viewport,
+            renderer,
+        )
+    }
+
+    fn draw(
+        &self,
+        tree: &Tree,
+        renderer: &mut Renderer,
+        theme: &Theme,
+        style: &renderer::Style,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        viewport: &Rectangle,
+    ) {
+        self.content.as_widget().draw(
+            &tree.children[0],
+            renderer,
+            theme,
+            style,
+            layout,
+            cursor,
+            viewport,
+        );
+    }
+
+    // 内容自己的浮层（例如右键菜单和下拉列表）与通知同时显示。
+    fn overlay<'b>(
+        &'b mut self,
+        tree: &'b mut Tree,
+        layout: Layout<'_>,
+        renderer: &Renderer,
+    ) -> Option<overlay::Element<'b, Message, Renderer>> {
+        let (content_tree, toasts_tree) = tree.children.split_at_mut(1);
+
+        let content = self
+            .content
+            .as_widget_mut()
+            .overlay(&mut content_tree[0], layout, renderer);
+
+        let Some(toasts) = &mut self.toasts else {
+            return content;
+        };
+
+        let toasts = overlay::Element::new(
+            layout.position(),
+            Box::new(Overlay {
+                toasts,
+                tree: &mut toasts_tree[0],
+            }),
+        );
+
+        Some(overlay::Group::with_children(content.into_iter().chain([toasts]).collect()).overlay())
+    }
+}
+
+// 显示在窗口右下角的通知浮层。
+struct Overlay<'a, 'b> {
+    toasts: &'b mut Element<'a, Message>, // 通知的内容。
+    tree: &'b mut Tree,                   // 通知的状态。
+}
+
+impl overlay::Overlay<Message, Renderer> for Overlay<'_, '_> {
+    fn layout(&mut self, renderer: &Renderer, bounds: Size, _position: Point) -> layout::Node {
+        let limits = layout::Limits::new(Size::ZERO, bounds);
+        let node = self.toasts.as_widget().layout(self.tree, renderer, &limits);
+        let size = node.size();
+
+        node.move_to(Point::new(
+            (bounds.width - size.width - MARGIN.width).max(0.0),
+            (bounds.height - size.height - MARGIN.height).max(0.0),
+        ))
+    }
+
+    fn on_event(
+        &mut self,
+        event: Event,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        renderer: &Renderer,
+        clipboard: &mut dyn Clipboard,
+        shell: &mut Shell<'_, Message>,
+    ) -> event::Status {
+        self.toasts.as_widget_mut().on_event(
+            self.tree,
+            event,
+            layout,
+            cursor,
+            renderer,
+            clipboard,
+            shell,
+            &layout.bounds(),
+        )
+    }
+
+    fn mouse_interaction(
+        &self,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        viewport: &Rectangle,
+        renderer: &Renderer,
+    ) -> mouse::Interaction {
+        self.toasts
+            .as_widget()
+            .mouse_interaction(self.tree, layout, cursor, viewport, renderer)
+    }
+
+    fn draw(
+        &self,
+        renderer: &mut Renderer,
+        theme: &Theme,
+        style: &renderer::Style,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+    ) {
+        self.toasts.as_widget().draw(
+            self.tree,
+            renderer,
+            theme,
+            style,
+            layout,
+            cursor,
+            &layout.bounds(),
+        );
+    }
+}
@@ -0,0 +1,108 @@
+use crate::encoding::{Decoded, Encoding};
+use crate::i18n::{tr, trf};
+use crate::{Error, Message};
+use iced::widget::{checkbox, container, row, text, text_input};
+use iced::{theme, Element};
+use std::time::Duration;
+
+// 下载的超时时间，包括连接和读取全部内容。
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+// 最多下载的字节数，超过时中止下载，以免把过大的文件读入内存。
+const MAX_SIZE: usize = 32 * 1024 * 1024;
+
+// 定义“打开网址”对话框的状态。
+#[derive(Debug, Default)]
+pub struct OpenUrl {
+    pub input: String,   // 用户输入的网址。
+    pub read_only: bool, // 是否以只读方式打开下载的内容。
+}
+
+impl OpenUrl {
+    // 返回输入框的标识，用于在打开对话框时获取焦点。
+    pub fn id() -> text_input::Id {
+        text_input::Id::new("open-url")
+    }
+
+    // 返回输入的网址，只接受 http 和 https 网址。
+    pub fn url(&self) -> Option<String> {
+        let url = self.input.trim();
+
+        (url.starts_with("http://") || url.starts_with("https://")).then(|| url.to_string())
+    }
+
+    // 创建对话框的视图。
+    pub fn view(&self) -> Element<'_, Message> {
+        let input = text_input("https://raw.githubusercontent.com/...", &self.input)
+            .id(Self::id())
+            .on_input(Message::OpenUrlChanged)
+            .on_submit(Message::OpenUrlSubmitted)
+            .padding(5);
+
+        let read_only = checkbox(
+            tr("Read-only"),
+            self.read_only,
+            Message::OpenUrlReadOnlyToggled,
+        );
+
+        container(row![text(tr("Open URL:")), input, read_only].spacing(10))
+            .padding(10)
+            .style(theme::Container::Box)
+            .into()
+    }
+}
+
+// 定义一个异步函数来下载网址的内容，并自动检测字符编码。
+//
+// 服务器返回错误状态时也视为下载失败，避免把错误页面当作文件内容打开。
+// 超过 TIMEOUT 或内容超过 MAX_SIZE 时中止下载。
+pub async fn fetch(url: String, read_only: bool) -> Result<(String, Decoded), Error> {
+    let failed = |error: reqwest::Error| {
+        Error::DownloadFailed(if error.is_timeout() {
+            trf(
+                "The download took longer than {seconds} seconds",
+                &[("seconds", &TIMEOUT.as_secs())],
+            )
+        } else {
+            error.to_string()
+        })
+    };
+    let too_large = || {
+        Error::DownloadFailed(trf(
+            "The file is larger than {size} MiB",
+            &[("size", &(MAX_SIZE / 1024 / 1024))],
+        ))
+    };
+
+    let mut response = reqwest::Client::builder()
+        .timeout(TIMEOUT)
+        .build()
+        .map_err(failed)?
+        .get(&url)
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .map_err(failed)?;
+
+    // 服务器提供了长度时提前拒绝，否则在读取的过程中检查。
+    if response
+        .content_length()
+        .is_some_and(|length| length > MAX_SIZE as u64)
+    {
+        return Err(too_large());
+    }
+
+    let mut bytes = Vec::new();
+
+    while let Some(chunk) = response.chunk().await.map_err(failed)? {
+        if bytes.len() + chunk.len() > MAX_SIZE {
+            return Err(too_large());
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+
+    let mut decoded = Encoding::detect(&bytes);
+    decoded.is_read_only = read_only;
+
+    Ok((url, decoded))
+}
@@ -0,0 +1,203 @@
+use crate::i18n::{tr, trf};
+use crate::keymap::{self, Keymap};
+use crate::syntax;
+use crate::transform::Transform;
+use crate::Message;
+use iced::widget::{button, column, container, horizontal_space, row, text, text_input};
+use iced::{theme, Element, Length};
+
+// 列表中最多显示的命令数量。
+const MAX_RESULTS: usize = 12;
+
+// 定义命令面板中的一条命令。
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub label: String,            // 显示的名称。
+    pub shortcut: Option<String>, // 绑定的快捷键。
+    pub message: Message,         // 执行命令时发送的消息。
+}
+
+// 收集命令面板中可以执行的所有命令：快捷键映射中的所有操作、打开目录和工作区、变换选中的文本以及切换高亮主题。
+pub fn commands(keymap: &Keymap) -> Vec<Entry> {
+    let actions = keymap::Action::ALL
+        .iter()
+        .copied()
+        .filter(|action| {
+            !matches!(
+                action,
+                keymap::Action::Dismiss | keymap::Action::CommandPalette
+            )
+        })
+        .map(|action| Entry {
+            label: action.to_string(),
+            shortcut: keymap.binding(action).map(|key| key.to_string()),
+            message: action.message(),
+        });
+
+    let folders = [
+        (tr("Open Folder"), Message::OpenFolder),
+        (tr("Open Workspace"), Message::OpenWorkspace),
+        (tr("Add Folder to Workspace"), Message::AddFolderToWorkspace),
+        (tr("Close Workspace"), Message::CloseWorkspace),
+    ]
+    .into_iter()
+    .map(|(label, message)| Entry {
+        label: String::from(label),
+        shortcut: None,
+        message,
+    });
+
+    let transforms = Transform::ALL.iter().map(|transform| Entry {
+        label: transform.to_string(),
+        shortcut: None,
+        message: Message::Transform(*transform),
+    });
+
+    let themes = syntax::Theme::all().iter().map(|theme| Entry {
+        label: trf("Theme: {theme}", &[("theme", theme)]),
+        shortcut: None,
+        message: Message::ThemeSelected(*theme),
+    });
+
+    actions
+        .chain(folders)
+        .chain(transforms)
+        .chain(themes)
+        .collect()
+}
+
+// 定义命令面板的状态。
+#[derive(Debug)]
+pub struct Palette {
+    pub query: String,         // 用户输入的搜索文本。
+    pub selected: usize,       // 选中的命令在匹配结果中的位置。
+    commands: Vec<Entry>,      // 打开面板时收集的所有命令。
+    placeholder: &'static str, // 输入框为空时显示的提示。
+}
+
+impl Palette {
+    // 使用可以执行的命令创建命令面板。
+    pub fn new(commands: Vec<Entry>) -> Self {
+        Self::with_placeholder(commands, tr("Type a command"))
+    }
+
+    // 创建从其他列表中选择一项的面板，例如剪贴板历史，placeholder 为输入框的提示。
+    pub fn with_placeholder(commands: Vec<Entry>, placeholder: &'static str) -> Self {
+        Self {
+            query: String::new(),
+            selected: 0,
+            commands,
+            placeholder,
+        }
+    }
+
+    // 返回输入框的标识，用于在打开面板时获取焦点。
+    pub fn id() -> text_input::Id {
+        text_input::Id::new("command-palette")
+    }
+
+    // 返回与搜索文本匹配的命令，按匹配程度排序。
+    pub fn matches(&self) -> Vec<&Entry> {
+        let mut matches: Vec<_> = self
+            .commands
+            .iter()
+            .filter_map(|entry| Some((score(&entry.label, &self.query)?, entry)))
+            .collect();
+
+        // 稳定排序，得分相同的命令保持原有的顺序。
+        matches.sort_by(|(a, _), (b, _)| b.cmp(a));
+        matches.into_iter().map(|(_, entry)| entry).collect()
+    }
+
+    // 返回当前选中的命令。
+    pub fn selection(&self) -> Option<&Entry> {
+        self.matches().get(self.selected).copied()
+    }
+
+    // 上下移动选中的命令，到达两端时循环。
+    pub fn move_selection(&mut self, offset: isize) {
+        let count = self.matches().len().min(MAX_RESULTS);
+
+        if count > 0 {
+            self.selected = (self.selected as isize + offset).rem_euclid(count as isize) as usize;
+        }
+    }
+
+    // 创建命令面板的视图。
+    pub fn view(&self) -> Element<'_, Message> {
+        let input = text_input(self.placeholder, &self.query)
+            .id(Self::id())
+            .on_input(Message::PaletteChanged)
+            .on_submit(Message::PaletteSubmitted)
+            .padding(5);
+
+        let entries = self
+            .matches()
+            .into_iter()
+            .take(MAX_RESULTS)
+            .enumerate()
+            .map(|(index, entry)| {
+                let style = if index == self.selected {
+                    theme::Button::Primary
+                } else {
+                    theme::Button::Text
+                };
+
+                button(row![
+                    text(&entry.label),
+                    horizontal_space(Length::Fill),
+                    text(entry.shortcut.as_deref().unwrap_or_default()).size(14)
+                ])
+                .on_press(Message::RunCommand(Box::new(entry.message.clone())))
+                .width(Length::Fill)
+                .padding([2, 5])
+                .style(style)
+                .into()
+            })
+            .collect();
+
+        container(column![input, column(entries)].spacing(5))
+            .padding(10)
+            .style(theme::Container::Box)
+            .into()
+    }
+}
+
+// 模糊匹配：搜索文本中的字符需要按顺序出现在名称中，不区分大小写。
+//
+// 连续匹配和在单词开头匹配的字符得分更高，不匹配时返回 None。
+pub fn score(label: &str, query: &str) -> Option<i32> {
+    if query.trim().is_empty() {
+        return Some(0);
+    }
+
+    let mut score = 0;
+    let mut previous_end = None;
+    let mut chars = label.char_indices();
+
+    for wanted in query.chars().filter(|c| !c.is_whitespace()) {
+        let wanted = wanted.to_lowercase().next()?;
+
+        let (index, c) = chars.find(|(_, c)| c.to_lowercase().next() == Some(wanted))?;
+
+        let is_word_start = !matches!(
+            label[..index].chars().next_back(),
+            Some(before) if before.is_alphanumeric()
+        );
+
+        score += 1;
+
+        if is_word_start {
+            score += 5;
+        }
+
+        if previous_end == Some(index) {
+            score += 3;
+        }
+
+        previous_end = Some(index + c.len_utf8());
+    }
+
+    // 名称越短越靠前。
+    Some(score - label.len() as i32 / 10)
+}
@@ -0,0 +1,441 @@
+use crate::i18n::{tr, trf};
+use crate::palette::Entry;
+use crate::{Error, FileError, Message, Operation};
+use iced::widget::{button, checkbox, column, row, text};
+use iced::{Element, Length};
+use serde::Deserialize;
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
+use wasmi::core::Trap;
+use wasmi::{
+    Caller, Config, Engine, Extern, Instance, Linker, Memory, Module, Store, StoreLimits,
+    StoreLimitsBuilder,
+};
+
+// 插件调用宿主函数时使用的模块名称。
+const HOST_MODULE: &str = "editor";
+
+// 每次调用插件时最多执行的指令数，用完时中止插件，以免死循环的插件卡住界面。
+const FUEL: u64 = 100_000_000;
+
+// 每个插件实例最多可以使用的线性内存，超过时插件的内存分配失败。
+const MEMORY_LIMIT: usize = 256 * 1024 * 1024;
+
+// 没有安装插件时显示的提示。
+const EMPTY_HINT: &str =
+    "Put .wasm plugins and their .toml manifests in the plugins folder of the configuration directory.";
+
+// 定义插件清单，与插件的 .wasm 文件同名，扩展名为 .toml。
+#[derive(Debug, Clone, Deserialize)]
+pub struct Manifest {
+    pub name: String, // 插件的名称。
+    #[serde(default)]
+    pub description: String, // 插件的简要说明。
+    #[serde(default)]
+    pub commands: Vec<Export>, // 插件提供的命令，通过宿主函数读写文档。
+    #[serde(default)]
+    pub transforms: Vec<Export>, // 插件提供的文本变换，作用于选中的文本。
+}
+
+// 定义插件导出的一个函数。
+#[derive(Debug, Clone, Deserialize)]
+pub struct Export {
+    pub title: String,    // 在命令面板中显示的名称。
+    pub function: String, // 插件中导出的函数名称。
+}
+
+// 定义加载的插件。
+#[derive(Debug, Clone)]
+pub struct Plugin {
+    pub id: String,         // 插件的标识，即 .wasm 文件的文件名（不含扩展名）。
+    pub manifest: Manifest, // 插件清单。
+    module: Arc<Module>,    // 加载时编译好的 WebAssembly 模块，每次调用时重新实例化。
+}
+
+// 定义插件运行时可以访问的宿主状态。
+struct Host {
+    text: String,               // 文档的内容，插件可以读取和替换其中的范围。
+    notifications: Vec<String>, // 插件要求显示的通知。
+    limits: StoreLimits,        // 插件实例可以使用的资源。
+}
+
+// 定义插件运行的结果。
+#[derive(Debug, Clone)]
+pub struct Outcome {
+    pub text: String,               // 运行后文档的内容，变换时为变换后的文本。
+    pub notifications: Vec<String>, // 插件要求显示的通知。
+}
+
+impl Plugin {
+    // 运行插件提供的命令。命令没有参数，通过宿主函数读取和修改文档 text。
+    pub fn run_command(&self, function: &str, text: String) -> Result<Outcome, String> {
+        let (mut store, instance) = self.instantiate(text)?;
+
+        instance
+            .get_typed_func::<(), ()>(&store, function)
+            .map_err(|error| error.to_string())?
+            .call(&mut store, ())
+            .map_err(|error| error.to_string())?;
+
+        let host = store.into_data();
+
+        Ok(Outcome {
+            text: host.text,
+            notifications: host.notifications,
+        })
+    }
+
+    // 运行插件提供的文本变换。
+    //
+    // 宿主先调用插件导出的 alloc(len) 分配内存并写入 input，再调用 function(ptr, len)，
+    // 返回值的高 32 位为结果的地址，低 32 位为结果的长度。
+    pub fn run_transform(&self, function: &str, input: &str) -> Result<Outcome, String> {
+        let (mut store, instance) = self.instantiate(String::new())?;
+        let memory = exported_memory(&instance, &store)?;
+        let len =
+            i32::try_from(input.len()).map_err(|_| String::from(tr("The text is too long")))?;
+
+        let ptr = instance
+            .get_typed_func::<i32, i32>(&store, "alloc")
+            .map_err(|error| error.to_string())?
+            .call(&mut store, len)
+            .map_err(|error| error.to_string())?;
+
+        memory
+            .write(&mut store, ptr as usize, input.as_bytes())
+            .map_err(|error| error.to_string())?;
+
+        let result = instance
+            .get_typed_func::<(i32, i32), i64>(&store, function)
+            .map_err(|error| error.to_string())?
+            .call(&mut store, (ptr, len))
+            .map_err(|error| error.to_string())?;
+
+        // 先检查结果是否在插件的内存范围内，再分配缓冲区，以免插件让宿主分配过多内存。
+        let (ptr, len) = (
+            (result >> 32) as u32 as usize,
+            (result & 0xffff_ffff) as usize,
+        );
+        if ptr
+            .checked_add(len)
+            .map_or(true, |end| end > memory.data(&store).len())
+        {
+            return Err(String::from(tr(
+                "The plugin returned a range outside its memory",
+            )));
+        }
+
+        let mut output = vec![0; len];
+        memory
+            .read(&store, ptr, &mut output)
+            .map_err(|error| error.to_string())?;
+
+        Ok(Outcome {
+            text: String::from_utf8(output)
+                .map_err(|_| String::from(tr("The plugin returned invalid UTF-8 text")))?,
+            notifications: store.into_data().notifications,
+        })
+    }
+
+    // 创建插件的实例，链接宿主函数，并限制可以执行的指令数和使用的内存。
+    fn instantiate(&self, text: String) -> Result<(Store<Host>, Instance), String> {
+        let mut store = Store::new(
+            engine(),
+            Host {
+                text,
+                notifications: Vec::new(),
+                limits: StoreLimitsBuilder::new().memory_size(MEMORY_LIMIT).build(),
+            },
+        );
+        store.limiter(|host| &mut host.limits);
+        store.add_fuel(FUEL).map_err(|error| error.to_string())?;
+
+        let mut linker = Linker::<Host>::new(engine());
+        link_host(&mut linker).map_err(|error| error.to_string())?;
+
+        let instance = linker
+            .instantiate(&mut store, &self.module)
+            .and_then(|instance| instance.start(&mut store))
+            .map_err(|error| error.to_string())?;
+
+        Ok((store, instance))
+    }
+}
+
+// 返回编译和运行插件的引擎，所有插件共用，以便加载时编译的模块可以在之后实例化。
+fn engine() -> &'static Engine {
+    static ENGINE: OnceLock<Engine> = OnceLock::new();
+
+    ENGINE.get_or_init(|| {
+        let mut config = Config::default();
+        config.consume_fuel(true);
+        Engine::new(&config)
+    })
+}
+
+// 定义一个异步函数，在后台线程中运行插件提供的命令，以免插件运行期间界面停止响应。
+pub async fn run_command(plugin: Plugin, function: String, text: String) -> Result<Outcome, Error> {
+    tokio::task::spawn_blocking(move || {
+        plugin
+            .run_command(&function, text)
+            .map_err(|error| Error::PluginFailed(format!("{}: {error}", plugin.manifest.name)))
+    })
+    .await
+    .unwrap_or_else(|error| Err(Error::PluginFailed(error.to_string())))
+}
+
+// 在后台线程中运行插件提供的文本变换，以免插件运行期间界面停止响应。
+pub async fn run_transform(
+    plugin: Plugin,
+    function: String,
+    input: String,
+) -> Result<Outcome, Error> {
+    tokio::task::spawn_blocking(move || {
+        plugin
+            .run_transform(&function, &input)
+            .map_err(|error| Error::PluginFailed(format!("{}: {error}", plugin.manifest.name)))
+    })
+    .await
+    .unwrap_or_else(|error| Err(Error::PluginFailed(error.to_string())))
+}
+
+// 链接插件可以调用的宿主函数，文本的位置和长度都以 UTF-8 字节计算：
+//
+// buffer_len() -> i32：返回文档的长度。
+// read_buffer(ptr, len) -> i32：把文档的前 len 个字节复制到插件内存的 ptr 处，返回复制的字节数。
+// replace_range(start, end, ptr, len)：把文档中 start..end 的范围替换为插件内存中的文本。
+// notify(ptr, len)：显示插件内存中的一条通知。
+fn link_host(linker: &mut Linker<Host>) -> Result<(), wasmi::Error> {
+    linker.func_wrap(HOST_MODULE, "buffer_len", |caller: Caller<'_, Host>| {
+        caller.data().text.len() as i32
+    })?;
+
+    linker.func_wrap(
+        HOST_MODULE,
+        "read_buffer",
+        |mut caller: Caller<'_, Host>, ptr: i32, len: i32| -> Result<i32, Trap> {
+            let memory = caller_memory(&caller)?;
+            let text = caller.data().text.as_bytes();
+            let bytes = text[..text.len().min(len.max(0) as usize)].to_vec();
+
+            memory
+                .write(&mut caller, ptr as usize, &bytes)
+                .map_err(|error| Trap::new(error.to_string()))?;
+
+            Ok(bytes.len() as i32)
+        },
+    )?;
+
+    linker.func_wrap(
+        HOST_MODULE,
+        "replace_range",
+        |mut caller: Caller<'_, Host>,
+         start: i32,
+         end: i32,
+         ptr: i32,
+         len: i32|
+         -> Result<(), Trap> {
+            let replacement = read_string(&caller, ptr, len)?;
+            let text = &mut caller.data_mut().text;
+            let (start, end) = (start as usize, end as usize);
+
+            if start > end
+                || end > text.len()
+                || !text.is_char_boundary(start)
+                || !text.is_char_boundary(end)
+            {
+                return Err(Trap::new(trf(
+                    "Invalid range {start}..{end}",
+                    &[("start", &start), ("end", &end)],
+                )));
+            }
+
+            text.replace_range(start..end, &replacement);
+            Ok(())
+        },
+    )?;
+
+    linker.func_wrap(
+        HOST_MODULE,
+        "notify",
+        |mut caller: Caller<'_, Host>, ptr: i32, len: i32| -> Result<(), Trap> {
+            let message = read_string(&caller, ptr, len)?;
+            caller.data_mut().notifications.push(message);
+            Ok(())
+        },
+    )?;
+
+    Ok(())
+}
+
+// 返回插件实例导出的内存。
+fn exported_memory(instance: &Instance, store: &Store<Host>) -> Result<Memory, String> {
+    instance
+        .get_memory(store, "memory")
+        .ok_or_else(|| String::from(tr("The plugin does not export its memory")))
+}
+
+// 返回调用宿主函数的插件导出的内存。
+fn caller_memory(caller: &Caller<'_, Host>) -> Result<Memory, Trap> {
+    caller
+        .get_export("memory")
+        .and_then(Extern::into_memory)
+        .ok_or_else(|| Trap::new(tr("The plugin does not export its memory")))
+}
+
+// 从插件内存中读取一段 UTF-8 文本。
+fn read_string(caller: &Caller<'_, Host>, ptr: i32, len: i32) -> Result<String, Trap> {
+    let memory = caller_memory(caller)?;
+    let mut bytes = vec![0; len.max(0) as usize];
+
+    memory
+        .read(caller, ptr as usize, &mut bytes)
+        .map_err(|error| Trap::new(error.to_string()))?;
+
+    String::from_utf8(bytes).map_err(|_| Trap::new(tr("Invalid UTF-8 text")))
+}
+
+// 返回已启用的插件提供的命令和文本变换，用于命令面板。
+pub fn commands(plugins: &[Plugin], disabled: &BTreeSet<String>) -> Vec<Entry> {
+    plugins
+        .iter()
+        .filter(|plugin| !disabled.contains(&plugin.id))
+        .flat_map(|plugin| {
+            let commands = plugin.manifest.commands.iter().map(|export| Entry {
+                label: format!("{}: {}", plugin.manifest.name, export.title),
+                shortcut: None,
+                message: Message::RunPluginCommand(plugin.id.clone(), export.function.clone()),
+            });
+
+            let transforms = plugin.manifest.transforms.iter().map(|export| Entry {
+                label: format!("{}: {}", plugin.manifest.name, export.title),
+                shortcut: None,
+                message: Message::RunPluginTransform(plugin.id.clone(), export.function.clone()),
+            });
+
+            commands.chain(transforms)
+        })
+        .collect()
+}
+
+// 创建设置界面中的插件管理部分，可以启用或停用每个插件。
+pub fn view<'a>(plugins: &'a [Plugin], disabled: &'a BTreeSet<String>) -> Element<'a, Message> {
+    let header = row![
+        text(tr("Plugins")).size(20).width(Length::Fill),
+        button(text(tr("Reload"))).on_press(Message::ReloadPlugins)
+    ]
+    .spacing(10);
+
+    if plugins.is_empty() {
+        return column![header, text(tr(EMPTY_HINT)).size(14)]
+            .spacing(10)
+            .into();
+    }
+
+    let list = column(
+        plugins
+            .iter()
+            .map(|plugin| {
+                let id = plugin.id.clone();
+                let exports = plugin.manifest.commands.len() + plugin.manifest.transforms.len();
+
+                column![
+                    checkbox(
+                        trf(
+                            "{name} ({count} commands)",
+                            &[("name", &plugin.manifest.name), ("count", &exports)],
+                        ),
+                        !disabled.contains(&plugin.id),
+                        move |enabled| Message::PluginToggled(id.clone(), enabled)
+                    ),
+                    text(&plugin.manifest.description).size(14)
+                ]
+                .spacing(2)
+                .into()
+            })
+            .collect(),
+    )
+    .spacing(10);
+
+    column![header, list].spacing(10).into()
+}
+
+// 定义一个异步函数来加载插件目录中的所有插件，目录不存在时没有插件。
+//
+// 每个插件由 .wasm 模块和同名的 .toml 清单组成，缺少清单的模块被跳过。
+// 一个插件无法加载时不影响其他插件，返回加载的插件和各个插件的错误。
+pub async fn load(folder: Option<PathBuf>) -> (Vec<Plugin>, Vec<Error>) {
+    let Some(folder) = folder else {
+        return (Vec::new(), Vec::new());
+    };
+
+    let mut entries = match tokio::fs::read_dir(&folder).await {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+            return (Vec::new(), Vec::new())
+        }
+        Err(error) => {
+            return (
+                Vec::new(),
+                vec![Error::FileFailed(FileError::new(
+                    Operation::ListFolder,
+                    &folder,
+                    &error,
+                ))],
+            )
+        }
+    };
+
+    let mut plugins = Vec::new();
+    let mut errors = Vec::new();
+
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let path = entry.path();
+
+        if path.extension().and_then(|extension| extension.to_str()) != Some("wasm") {
+            continue;
+        }
+
+        let Some(id) = path.file_stem().and_then(|stem| stem.to_str()) else {
+            continue;
+        };
+
+        let Ok(contents) = tokio::fs::read_to_string(path.with_extension("toml")).await else {
+            continue;
+        };
+
+        let manifest: Manifest = match toml::from_str(&contents) {
+            Ok(manifest) => manifest,
+            Err(error) => {
+                errors.push(Error::PluginFailed(format!("{id}: {}", error.message())));
+                continue;
+            }
+        };
+
+        let wasm = match tokio::fs::read(&path).await {
+            Ok(wasm) => wasm,
+            Err(error) => {
+                errors.push(Error::FileFailed(FileError::new(
+                    Operation::Read,
+                    &path,
+                    &error,
+                )));
+                continue;
+            }
+        };
+
+        // 加载时编译一次模块，无效的模块在这里报告，之后每次调用只需要实例化。
+        match Module::new(engine(), &wasm[..]) {
+            Ok(module) => plugins.push(Plugin {
+                id: id.to_string(),
+                manifest,
+                module: Arc::new(module),
+            }),
+            Err(error) => errors.push(Error::PluginFailed(format!("{id}: {error}"))),
+        }
+    }
+
+    plugins.sort_by(|a, b| a.id.cmp(&b.id));
+    (plugins, errors)
+}
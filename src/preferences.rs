@@ -0,0 +1,497 @@
+use crate::appearance::Appearance;
+use crate::custom_theme::CustomTheme;
+use crate::i18n::{tr, trf, Language};
+use crate::indent;
+use crate::syntax;
+use crate::syntax_tree;
+use crate::whitespace;
+use crate::{Error, FileError, Message, Operation};
+use iced::widget::{button, checkbox, column, pick_list, row, slider, text};
+use iced::{Element, Font};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+
+// 定义用户可以在设置界面中调整的偏好设置，保存在配置目录的 preferences.toml 中。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Preferences {
+    pub autosave: bool,                                  // 是否启用自动保存。
+    pub autosave_interval: u32,                          // 自动保存的间隔，单位为秒。
+    pub auto_close_brackets: bool,                       // 输入开括号或引号时是否自动插入闭合字符。
+    pub match_brackets: bool,                            // 是否高亮光标处括号的匹配括号。
+    pub word_wrap: bool,                                 // 是否自动换行显示过长的行。
+    pub line_numbers: bool,                              // 是否在编辑器左侧显示行号。
+    pub minimap: bool,                                   // 是否在编辑器右侧显示文档的缩略图。
+    pub highlight_current_line: bool,                    // 是否高亮光标所在行的背景。
+    pub indent_guides: bool,                             // 是否按制表符宽度显示缩进参考线。
+    pub render_whitespace: whitespace::Mode,             // 显示空白字符的范围。
+    pub line_ending_marks: bool,                         // 显示空白字符时是否也显示行尾的换行符。
+    pub ruler: Option<usize>,                            // 列标尺所在的列，None 表示不显示。
+    pub zoom: u32,                                       // 界面的缩放比例，单位为百分比。
+    pub trim_trailing_whitespace: bool,                  // 保存时是否去掉行尾的空白。
+    pub normalize_indentation: bool, // 保存时是否按语言的缩进单位统一制表符和空格。
+    pub single_final_newline: bool,  // 保存时是否去掉末尾多余的空行，只保留一个换行。
+    pub backup_on_save: bool,        // 保存时是否把原文件保留为同名的 .bak 文件。
+    pub print_line_numbers: bool,    // 打印为 PDF 时是否显示行号。
+    pub spell_check: bool,           // 是否检查文本文件和代码注释中的拼写。
+    pub dictionary: String,          // 拼写检查使用的 hunspell 词典名称。
+    pub clipboard_history: usize,    // 剪贴板历史最多记录的项数。
+    pub vim_mode: bool,              // 是否启用 Vim 按键模式。
+    pub language: Language,          // 界面使用的语言。
+    pub appearance: Appearance,      // 界面的外观，跟随系统或固定为浅色、深色。
+    pub theme: Option<String>,       // 代码高亮主题的名称，未选择时使用与外观相配的默认主题。
+    pub font: String,                // 编辑器使用的字体。
+    pub tab_width: usize,            // 制表符的宽度，单位为空格数。
+    pub indentation: BTreeMap<String, indent::Override>, // 按文件扩展名覆盖的缩进设置。
+    pub highlighters: BTreeMap<String, syntax::Backend>, // 按文件扩展名选择的代码高亮方式。
+    pub custom_theme: Option<String>, // 使用的自定义应用程序主题的名称，None 表示按外观使用内置主题。
+    pub custom_themes: Vec<CustomTheme>, // 用户保存的自定义应用程序主题。
+    pub disabled_plugins: BTreeSet<String>, // 停用的插件的标识。
+}
+
+impl Default for Preferences {
+    fn default() -> Self {
+        Self {
+            autosave: false,
+            autosave_interval: 30,
+            auto_close_brackets: true,
+            match_brackets: true,
+            word_wrap: true,
+            line_numbers: true,
+            minimap: true,
+            highlight_current_line: true,
+            indent_guides: true,
+            render_whitespace: whitespace::Mode::Off,
+            line_ending_marks: false,
+            ruler: None,
+            zoom: 100,
+            trim_trailing_whitespace: false,
+            normalize_indentation: false,
+            single_final_newline: false,
+            backup_on_save: false,
+            print_line_numbers: true,
+            spell_check: true,
+            dictionary: String::from(DEFAULT_DICTIONARY),
+            clipboard_history: 20,
+            vim_mode: false,
+            language: Language::English,
+            appearance: Appearance::System,
+            theme: None,
+            font: String::from(DEFAULT_FONT),
+            tab_width: 4,
+            indentation: BTreeMap::new(),
+            highlighters: BTreeMap::new(),
+            custom_theme: None,
+            custom_themes: Vec::new(),
+            disabled_plugins: BTreeSet::new(),
+        }
+    }
+}
+
+impl Preferences {
+    // 返回编辑器使用的字体，不在可选列表中的字体使用默认的等宽字体。
+    pub fn font(&self) -> Font {
+        match FONTS.iter().find(|font| **font == self.font) {
+            Some(&name) if name != DEFAULT_FONT => Font::with_name(name),
+            _ => Font::MONOSPACE,
+        }
+    }
+
+    // 返回指定扩展名的文件使用的代码高亮方式，tree-sitter 不支持的语言总是使用 syntect。
+    pub fn highlighter(&self, extension: &str) -> syntax::Backend {
+        match self.highlighters.get(extension) {
+            Some(&backend) if syntax_tree::supports(extension) => backend,
+            _ => syntax::Backend::Syntect,
+        }
+    }
+
+    // 返回选择的自定义应用程序主题，没有选择或已经删除时返回 None。
+    pub fn custom_theme(&self) -> Option<&CustomTheme> {
+        let name = self.custom_theme.as_deref()?;
+        self.custom_themes.iter().find(|theme| theme.name == name)
+    }
+
+    // 将偏好设置序列化为 TOML 配置文件的内容。
+    pub fn to_toml(&self) -> String {
+        toml::to_string(self).unwrap_or_default()
+    }
+}
+
+// 默认字体的名称，表示系统的等宽字体。
+const DEFAULT_FONT: &str = "Monospace";
+
+// 默认使用的拼写检查词典。
+const DEFAULT_DICTIONARY: &str = "en_US";
+
+// 启用列标尺时默认所在的列。
+const DEFAULT_RULER: usize = 80;
+
+// 可以选择的编辑器字体，需要系统中已经安装。
+const FONTS: &[&str] = &[
+    DEFAULT_FONT,
+    "Cascadia Code",
+    "Consolas",
+    "DejaVu Sans Mono",
+    "Fira Code",
+    "JetBrains Mono",
+    "Menlo",
+    "Source Code Pro",
+];
+
+// 创建设置界面中的偏好设置部分。
+//
+// extension 和 rules 为当前文件的扩展名和缩进规则，用于调整这种语言的缩进和高亮方式；
+// dictionaries 为系统中可用的拼写检查词典，clipboard_is_empty 表示剪贴板历史是否为空。
+pub fn view<'a>(
+    preferences: &'a Preferences,
+    theme: syntax::Theme,
+    extension: String,
+    rules: indent::Rules,
+    dictionaries: &'a [String],
+    clipboard_is_empty: bool,
+) -> Element<'a, Message> {
+    let language = row![
+        text(tr("Language")),
+        pick_list(
+            Language::ALL,
+            Some(preferences.language),
+            Message::LanguageSelected
+        )
+    ]
+    .spacing(10);
+
+    let appearance = row![
+        text(tr("Appearance")),
+        pick_list(
+            Appearance::ALL,
+            Some(preferences.appearance),
+            Message::AppearanceSelected
+        )
+    ]
+    .spacing(10);
+
+    let theme = row![
+        text(tr("Theme")),
+        pick_list(syntax::Theme::all(), Some(theme), Message::ThemeSelected),
+        button(text(tr("Use Default"))).on_press_maybe(
+            preferences
+                .theme
+                .is_some()
+                .then_some(Message::DefaultThemeSelected)
+        )
+    ]
+    .spacing(10);
+
+    let font = row![
+        text(tr("Font")),
+        pick_list(
+            FONTS
+                .iter()
+                .map(|font| font.to_string())
+                .collect::<Vec<_>>(),
+            Some(preferences.font.clone()),
+            Message::FontSelected
+        )
+    ]
+    .spacing(10);
+
+    let tab_width = row![
+        text(trf(
+            "Tab width: {width}",
+            &[("width", &preferences.tab_width)]
+        )),
+        slider(1..=8, preferences.tab_width as u32, |width| {
+            Message::TabWidthChanged(width as usize)
+        })
+    ]
+    .spacing(10);
+
+    let highlighter = if syntax_tree::supports(&extension) {
+        let label = text(trf(
+            "Highlighting for .{extension} files",
+            &[("extension", &extension)],
+        ));
+        let extension = extension.clone();
+
+        row![
+            label,
+            pick_list(
+                syntax::Backend::ALL,
+                Some(preferences.highlighter(&extension)),
+                move |backend| Message::HighlighterSelected(extension.clone(), backend)
+            )
+        ]
+        .spacing(10)
+    } else {
+        row![text(trf(
+            "Highlighting for .{extension} files: Syntect (tree-sitter is not available)",
+            &[("extension", &extension)],
+        ))]
+    };
+
+    let indentation = {
+        let use_tabs = {
+            let extension = extension.clone();
+
+            checkbox(
+                tr("Use tabs"),
+                rules.indentation.use_tabs,
+                move |use_tabs| {
+                    Message::IndentationChanged(
+                        extension.clone(),
+                        indent::Override {
+                            use_tabs: Some(use_tabs),
+                            ..indent::Override::default()
+                        },
+                    )
+                },
+            )
+        };
+
+        let size = rules.indentation.size;
+
+        row![
+            text(trf(
+                "Indentation for .{extension} files",
+                &[("extension", &extension)],
+            )),
+            use_tabs,
+            text(trf("Indent size: {size}", &[("size", &size)])),
+            slider(1..=8, size as u32, move |size| {
+                Message::IndentationChanged(
+                    extension.clone(),
+                    indent::Override {
+                        size: Some(size as usize),
+                        ..indent::Override::default()
+                    },
+                )
+            })
+        ]
+        .spacing(10)
+    };
+
+    let autosave = checkbox(
+        tr("Autosave"),
+        preferences.autosave,
+        Message::AutosaveToggled,
+    );
+
+    let interval = row![
+        text(trf(
+            "Autosave every {seconds} seconds",
+            &[("seconds", &preferences.autosave_interval)],
+        )),
+        slider(
+            5..=300,
+            preferences.autosave_interval,
+            Message::AutosaveIntervalChanged
+        )
+        .step(5u32)
+    ]
+    .spacing(10);
+
+    let auto_close = checkbox(
+        tr("Auto-close brackets and quotes"),
+        preferences.auto_close_brackets,
+        Message::AutoCloseBracketsToggled,
+    );
+
+    let match_brackets = checkbox(
+        tr("Highlight matching brackets"),
+        preferences.match_brackets,
+        Message::MatchBracketsToggled,
+    );
+
+    let word_wrap = checkbox(tr("Wrap long lines"), preferences.word_wrap, |_| {
+        Message::ToggleWordWrap
+    });
+
+    let line_numbers = checkbox(tr("Show line numbers"), preferences.line_numbers, |_| {
+        Message::ToggleLineNumbers
+    });
+
+    let minimap = checkbox(tr("Show minimap"), preferences.minimap, |_| {
+        Message::ToggleMinimap
+    });
+
+    let current_line = checkbox(
+        tr("Highlight the current line"),
+        preferences.highlight_current_line,
+        Message::CurrentLineHighlightToggled,
+    );
+
+    let indent_guides = checkbox(
+        tr("Show indent guides"),
+        preferences.indent_guides,
+        Message::IndentGuidesToggled,
+    );
+
+    let whitespace = {
+        let mode = row![
+            text(tr("Render whitespace")),
+            pick_list(
+                whitespace::Mode::ALL,
+                Some(preferences.render_whitespace),
+                Message::WhitespaceModeSelected
+            )
+        ]
+        .spacing(10);
+
+        if preferences.render_whitespace == whitespace::Mode::Off {
+            mode
+        } else {
+            mode.push(checkbox(
+                tr("Show line endings"),
+                preferences.line_ending_marks,
+                Message::LineEndingMarksToggled,
+            ))
+        }
+    };
+
+    let ruler = {
+        let column = preferences.ruler.unwrap_or(DEFAULT_RULER);
+
+        let toggle = checkbox(
+            tr("Show a column ruler"),
+            preferences.ruler.is_some(),
+            move |show| Message::RulerChanged(show.then_some(column)),
+        );
+
+        match preferences.ruler {
+            Some(column) => row![
+                toggle,
+                text(trf("At column {column}", &[("column", &column)])),
+                slider(40..=160, column as u32, |column| {
+                    Message::RulerChanged(Some(column as usize))
+                })
+                .step(4u32)
+            ]
+            .spacing(10),
+            None => row![toggle],
+        }
+    };
+
+    let trim_whitespace = checkbox(
+        tr("Trim trailing whitespace on save"),
+        preferences.trim_trailing_whitespace,
+        Message::TrimWhitespaceToggled,
+    );
+
+    let normalize_indentation = checkbox(
+        tr("Convert indentation to the language's tabs or spaces on save"),
+        preferences.normalize_indentation,
+        Message::NormalizeIndentationToggled,
+    );
+
+    let final_newline = checkbox(
+        tr("Remove extra blank lines at the end on save"),
+        preferences.single_final_newline,
+        Message::FinalNewlineToggled,
+    );
+
+    let spell_check = {
+        let toggle = checkbox(
+            tr("Check spelling in text files and comments"),
+            preferences.spell_check,
+            Message::SpellCheckToggled,
+        );
+
+        if preferences.spell_check {
+            row![
+                toggle,
+                text(tr("Dictionary")),
+                pick_list(
+                    dictionaries,
+                    Some(preferences.dictionary.clone()),
+                    Message::DictionarySelected
+                )
+            ]
+            .spacing(10)
+        } else {
+            row![toggle]
+        }
+    };
+
+    let clipboard_history = row![
+        text(trf(
+            "Remember the last {count} copied snippets",
+            &[("count", &preferences.clipboard_history)],
+        )),
+        slider(5..=100, preferences.clipboard_history as u32, |size| {
+            Message::ClipboardHistorySizeChanged(size as usize)
+        })
+        .step(5u32),
+        button(text(tr("Clear History")))
+            .on_press_maybe((!clipboard_is_empty).then_some(Message::ClearClipboardHistory))
+    ]
+    .spacing(10);
+
+    let vim_mode = checkbox(
+        tr("Vim key bindings"),
+        preferences.vim_mode,
+        Message::VimModeToggled,
+    );
+
+    let backup = checkbox(
+        tr("Keep the previous version as a .bak file on save"),
+        preferences.backup_on_save,
+        Message::BackupToggled,
+    );
+
+    let print_line_numbers = checkbox(
+        tr("Print line numbers"),
+        preferences.print_line_numbers,
+        Message::PrintLineNumbersToggled,
+    );
+
+    column![
+        text(tr("Settings")).size(24),
+        language,
+        appearance,
+        theme,
+        font,
+        tab_width,
+        indentation,
+        highlighter,
+        word_wrap,
+        line_numbers,
+        minimap,
+        current_line,
+        indent_guides,
+        whitespace,
+        ruler,
+        autosave,
+        interval,
+        auto_close,
+        match_brackets,
+        trim_whitespace,
+        normalize_indentation,
+        final_newline,
+        backup,
+        print_line_numbers,
+        spell_check,
+        clipboard_history,
+        vim_mode
+    ]
+    .spacing(20)
+    .into()
+}
+
+// 定义一个异步函数来加载偏好设置，配置文件不存在时使用默认设置。
+pub async fn load(path: Option<PathBuf>) -> Result<Preferences, Error> {
+    let Some(path) = path else {
+        return Ok(Preferences::default());
+    };
+
+    match tokio::fs::read_to_string(&path).await {
+        Ok(contents) => toml::from_str(&contents)
+            .map_err(|error| Error::InvalidConfig(error.message().to_string())),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(Preferences::default()),
+        Err(error) => Err(Error::FileFailed(FileError::new(
+            Operation::Read,
+            &path,
+            &error,
+        ))),
+    }
+}
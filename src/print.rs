@@ -0,0 +1,203 @@
+use crate::i18n::tr;
+use crate::syntax::{self, Theme};
+use crate::{Error, FileError, Operation};
+use iced::Color;
+use printpdf::{BuiltinFont, IndirectFontRef, Mm, PdfDocument, PdfLayerReference, Rgb};
+use std::path::{Path, PathBuf};
+
+// A4 纸张的宽度和高度，单位为毫米。
+const PAGE_WIDTH: f32 = 210.0;
+const PAGE_HEIGHT: f32 = 297.0;
+
+// 页边距和页眉的高度，单位为毫米。
+const MARGIN: f32 = 15.0;
+const HEADER_HEIGHT: f32 = 10.0;
+
+// 正文的字号，单位为磅。
+const FONT_SIZE: f32 = 9.0;
+
+// 每磅对应的毫米数。
+const MM_PER_POINT: f32 = 0.3528;
+
+// 等宽字体 Courier 的字符宽度为字号的 0.6 倍，行高为字号的 1.3 倍。
+const CHARACTER_WIDTH: f32 = FONT_SIZE * 0.6 * MM_PER_POINT;
+const LINE_HEIGHT: f32 = FONT_SIZE * 1.3 * MM_PER_POINT;
+
+// 行号和页眉的颜色。
+const GRAY: Color = Color::from_rgb(0.5, 0.5, 0.5);
+
+// 定义打印的一行：原文的行号（折行的后续部分为 None）和这一行中的带颜色的文本。
+struct Row {
+    number: Option<usize>,
+    spans: syntax::Line,
+}
+
+// 定义一个异步函数，将文档渲染为带有代码高亮的分页 PDF，并保存到用户选择的位置。
+//
+// 纸张是白色的，深色主题的浅色文字在纸上难以阅读，因此深色主题改用内置的浅色主题。
+pub async fn export(
+    title: String,
+    text: String,
+    theme: Theme,
+    extension: String,
+    line_numbers: bool,
+    tab_width: usize,
+) -> Result<PathBuf, Error> {
+    let name = Path::new(&title)
+        .file_stem()
+        .map_or_else(|| title.clone(), |stem| stem.to_string_lossy().into_owned());
+
+    let path = rfd::AsyncFileDialog::new()
+        .set_title(tr("Print to PDF"))
+        .set_file_name(format!("{name}.pdf"))
+        .add_filter("PDF", &["pdf"])
+        .save_file()
+        .await
+        .ok_or(Error::DialogClosed)?
+        .path()
+        .to_owned();
+
+    let theme = if theme.is_dark() {
+        Theme::find("InspiredGitHub").unwrap_or(theme)
+    } else {
+        theme
+    };
+
+    let lines = syntax::highlight_document(&text, theme, &extension);
+    let bytes = render(&title, lines, line_numbers, tab_width)
+        .map_err(|error| Error::ExportFailed(error.to_string()))?;
+
+    tokio::fs::write(&path, bytes)
+        .await
+        .map_err(|error| Error::FileFailed(FileError::new(Operation::Write, &path, &error)))?;
+
+    Ok(path)
+}
+
+// 将高亮后的各行排版到 A4 页面中，过长的行折到下一行，每页顶部显示标题和页码。
+fn render(
+    title: &str,
+    lines: Vec<syntax::Line>,
+    line_numbers: bool,
+    tab_width: usize,
+) -> Result<Vec<u8>, printpdf::Error> {
+    // 行号占用的宽度，包括与正文之间的一个空格。
+    let gutter = if line_numbers {
+        lines.len().max(1).to_string().len() + 1
+    } else {
+        0
+    };
+
+    let columns = ((PAGE_WIDTH - MARGIN * 2.0) / CHARACTER_WIDTH) as usize - gutter;
+    let rows = wrap(lines, columns.max(1), tab_width);
+
+    let rows_per_page = ((PAGE_HEIGHT - MARGIN * 2.0 - HEADER_HEIGHT) / LINE_HEIGHT) as usize;
+    let pages: Vec<&[Row]> = if rows.is_empty() {
+        vec![&[]]
+    } else {
+        rows.chunks(rows_per_page).collect()
+    };
+
+    let (document, page, layer) = PdfDocument::new(title, Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Text");
+    let font = document.add_builtin_font(BuiltinFont::Courier)?;
+
+    let mut current = Some((page, layer));
+
+    for (index, rows) in pages.iter().enumerate() {
+        let (page, layer) = current
+            .take()
+            .unwrap_or_else(|| document.add_page(Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Text"));
+        let layer = document.get_page(page).get_layer(layer);
+
+        // 页眉：左侧为标题，右侧为页码。
+        let top = PAGE_HEIGHT - MARGIN;
+        let number = format!("{} / {}", index + 1, pages.len());
+        let right = PAGE_WIDTH - MARGIN - number.len() as f32 * CHARACTER_WIDTH;
+
+        write(&layer, &font, MARGIN, top, &[(GRAY, title.to_string())]);
+        write(&layer, &font, right, top, &[(GRAY, number)]);
+
+        for (row_index, row) in rows.iter().enumerate() {
+            let y = top - HEADER_HEIGHT - row_index as f32 * LINE_HEIGHT;
+
+            if let Some(number) = row.number.filter(|_| line_numbers) {
+                let label = format!("{:>width$}", number + 1, width = gutter - 1);
+                write(&layer, &font, MARGIN, y, &[(GRAY, label)]);
+            }
+
+            let x = MARGIN + gutter as f32 * CHARACTER_WIDTH;
+            write(&layer, &font, x, y, &row.spans);
+        }
+    }
+
+    document.save_to_bytes()
+}
+
+// 在页面的指定位置依次写出带颜色的文本段。
+fn write(
+    layer: &PdfLayerReference,
+    font: &IndirectFontRef,
+    x: f32,
+    y: f32,
+    spans: &[(Color, String)],
+) {
+    layer.begin_text_section();
+    layer.set_font(font, FONT_SIZE);
+    layer.set_text_cursor(Mm(x), Mm(y));
+
+    for (color, text) in spans {
+        layer.set_fill_color(printpdf::Color::Rgb(Rgb::new(
+            color.r, color.g, color.b, None,
+        )));
+        layer.write_text(printable(text), font);
+    }
+
+    layer.end_text_section();
+}
+
+// 将每一行按指定的列数折行，制表符按制表符宽度展开为空格。
+fn wrap(lines: Vec<syntax::Line>, columns: usize, tab_width: usize) -> Vec<Row> {
+    let tab = " ".repeat(tab_width);
+
+    let mut rows = Vec::new();
+
+    for (number, spans) in lines.into_iter().enumerate() {
+        let mut row = Row {
+            number: Some(number),
+            spans: Vec::new(),
+        };
+        let mut width = 0;
+
+        for (color, text) in spans {
+            for c in text.replace('\t', &tab).chars() {
+                if width == columns {
+                    rows.push(std::mem::replace(
+                        &mut row,
+                        Row {
+                            number: None,
+                            spans: Vec::new(),
+                        },
+                    ));
+                    width = 0;
+                }
+
+                match row.spans.last_mut() {
+                    Some((last, text)) if *last == color => text.push(c),
+                    _ => row.spans.push((color, c.to_string())),
+                }
+                width += 1;
+            }
+        }
+
+        rows.push(row);
+    }
+
+    rows
+}
+
+// PDF 内置字体只能显示 Latin-1 范围内的字符，其他字符用问号代替。
+fn printable(text: &str) -> String {
+    text.chars()
+        .map(|c| if u32::from(c) < 0x100 { c } else { '?' })
+        .collect()
+}
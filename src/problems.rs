@@ -0,0 +1,276 @@
+use crate::diagnostics::{Diagnostic, Severity};
+use crate::i18n::{tr, trf};
+use crate::{Error, FileError, Message, Operation};
+use iced::widget::{button, column, container, horizontal_space, row, scrollable, text};
+use iced::{theme, Element, Length};
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+
+// 定义 cargo check 报告的一个问题。
+#[derive(Debug, Clone, PartialEq)]
+pub struct Problem {
+    pub path: PathBuf,          // 问题所在文件的完整路径。
+    pub diagnostic: Diagnostic, // 问题的位置和信息。
+}
+
+// 定义问题面板的状态。
+#[derive(Debug, Default)]
+pub struct Problems {
+    pub root: Option<PathBuf>,  // 最近一次检查的 Cargo 工作空间根目录。
+    pub problems: Vec<Problem>, // 最近一次检查发现的问题。
+    pub is_checking: bool,      // 是否正在运行 cargo check。
+    pub is_stale: bool,         // 检查期间是否又保存了文件，需要在结束后重新检查。
+}
+
+impl Problems {
+    // 返回指定文件中的问题，用于在编辑器中标记。
+    pub fn diagnostics(&self, path: &Path) -> impl Iterator<Item = &Diagnostic> {
+        let path = path.to_path_buf();
+
+        self.problems
+            .iter()
+            .filter(move |problem| problem.path == path)
+            .map(|problem| &problem.diagnostic)
+    }
+
+    // 统计指定严重程度的问题数量。
+    fn count(&self, severity: Severity) -> usize {
+        self.problems
+            .iter()
+            .filter(|problem| problem.diagnostic.severity == severity)
+            .count()
+    }
+
+    // 创建问题面板的视图。
+    pub fn view(&self) -> Element<'_, Message> {
+        let header = row![
+            text(tr("Problems")).size(18),
+            horizontal_space(Length::Fill),
+            button(text("↻"))
+                .on_press_maybe((!self.is_checking).then_some(Message::CheckProject))
+                .style(theme::Button::Text),
+            button(text("×"))
+                .on_press(Message::ToggleProblems)
+                .style(theme::Button::Text)
+        ];
+
+        let summary = if self.is_checking {
+            String::from(tr("Checking..."))
+        } else if self.root.is_none() {
+            String::from(tr("Save a Rust file in a Cargo project to check it"))
+        } else {
+            trf(
+                "{errors} errors, {warnings} warnings",
+                &[
+                    ("errors", &self.count(Severity::Error)),
+                    ("warnings", &self.count(Severity::Warning)),
+                ],
+            )
+        };
+
+        let mut rows = Vec::new();
+        let mut current: Option<&Path> = None;
+
+        // 问题已经按文件排序，每个文件前显示一次文件名。
+        for problem in &self.problems {
+            if current != Some(&problem.path) {
+                let name = self.root.as_deref().map_or(problem.path.as_path(), |root| {
+                    problem.path.strip_prefix(root).unwrap_or(&problem.path)
+                });
+
+                rows.push(text(name.display()).size(14).into());
+                current = Some(&problem.path);
+            }
+
+            let diagnostic = &problem.diagnostic;
+            let message = diagnostic.message.lines().next().unwrap_or_default();
+
+            rows.push(
+                button(
+                    text(format!("{}: {message}", diagnostic.line + 1))
+                        .size(13)
+                        .style(diagnostic.severity.color()),
+                )
+                .on_press(Message::SearchResultClicked(
+                    problem.path.clone(),
+                    diagnostic.line,
+                    diagnostic.columns.start,
+                ))
+                .padding([2, 10])
+                .style(theme::Button::Text)
+                .into(),
+            );
+        }
+
+        container(
+            column![
+                header,
+                text(summary).size(14),
+                scrollable(column(rows).spacing(2))
+            ]
+            .spacing(10),
+        )
+        .width(300)
+        .height(Length::Fill)
+        .padding(5)
+        .style(theme::Container::Box)
+        .into()
+    }
+}
+
+// 返回文件所属的 Cargo 项目目录，不在 Cargo 项目中的文件或者不是 Rust 文件时返回 None。
+pub fn project(path: &Path) -> Option<PathBuf> {
+    let is_rust = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("rs"));
+
+    if !is_rust {
+        return None;
+    }
+
+    path.ancestors()
+        .skip(1)
+        .find(|ancestor| ancestor.join("Cargo.toml").is_file())
+        .map(Path::to_path_buf)
+}
+
+// 定义一个异步函数，在项目所属的工作空间中运行 cargo check，返回工作空间的根目录和发现的问题。
+//
+// 编译器报告的文件路径相对于工作空间的根目录，因此先找到根目录再在其中运行检查。
+pub async fn check(project: PathBuf) -> Result<(PathBuf, Vec<Problem>), Error> {
+    let manifest = cargo(
+        &project,
+        &["locate-project", "--workspace", "--message-format", "plain"],
+    )
+    .await?;
+    let root = Path::new(manifest.trim())
+        .parent()
+        .map_or(project, Path::to_path_buf);
+
+    let output = Command::new("cargo")
+        .args([
+            "check",
+            "--workspace",
+            "--all-targets",
+            "--message-format=json",
+        ])
+        .current_dir(&root)
+        .kill_on_drop(true)
+        .output()
+        .await
+        .map_err(|error| {
+            Error::FileFailed(FileError::new(Operation::Run, Path::new("cargo"), &error))
+        })?;
+
+    let mut problems = Vec::new();
+
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        for problem in parse(&root, line) {
+            if !problems.contains(&problem) {
+                problems.push(problem);
+            }
+        }
+    }
+
+    // 没有报告任何问题却失败了，通常是清单文件有误，此时显示 cargo 输出的错误。
+    if problems.is_empty() && !output.status.success() {
+        return Err(Error::CheckFailed(last_error(&output.stderr)));
+    }
+
+    problems.sort_by(|a, b| {
+        (&a.path, a.diagnostic.line, a.diagnostic.severity).cmp(&(
+            &b.path,
+            b.diagnostic.line,
+            b.diagnostic.severity,
+        ))
+    });
+
+    Ok((root, problems))
+}
+
+// 在指定目录中运行 cargo 命令，返回标准输出。
+async fn cargo(directory: &Path, arguments: &[&str]) -> Result<String, Error> {
+    let output = Command::new("cargo")
+        .args(arguments)
+        .current_dir(directory)
+        .output()
+        .await
+        .map_err(|error| {
+            Error::FileFailed(FileError::new(Operation::Run, Path::new("cargo"), &error))
+        })?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else {
+        Err(Error::CheckFailed(last_error(&output.stderr)))
+    }
+}
+
+// 返回 cargo 标准错误输出中的最后一行错误信息。
+fn last_error(stderr: &[u8]) -> String {
+    String::from_utf8_lossy(stderr)
+        .lines()
+        .rev()
+        .find(|line| line.starts_with("error"))
+        .unwrap_or(tr("cargo check failed"))
+        .to_string()
+}
+
+// 解析 cargo 输出的一行 JSON 消息，返回编译器消息的主要位置上的问题。
+//
+// 编译器的行号和列号从 1 开始，列号按字符计算；跨越多行的范围只标记到第一行的行尾。
+fn parse(root: &Path, line: &str) -> Vec<Problem> {
+    let Ok(value) = serde_json::from_str::<serde_json::Value>(line) else {
+        return Vec::new();
+    };
+
+    if value["reason"] != "compiler-message" {
+        return Vec::new();
+    }
+
+    let message = &value["message"];
+
+    let severity = match message["level"].as_str().unwrap_or_default() {
+        level if level.starts_with("error") => Severity::Error,
+        "warning" => Severity::Warning,
+        _ => Severity::Information,
+    };
+
+    let text = message["message"].as_str().unwrap_or_default();
+
+    message["spans"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|span| span["is_primary"] == true)
+        .filter_map(|span| {
+            let file = span["file_name"].as_str()?;
+            let line_start = span["line_start"].as_u64()? as usize;
+            let line_end = span["line_end"].as_u64()? as usize;
+            let column_start = span["column_start"].as_u64()? as usize;
+            let column_end = span["column_end"].as_u64()? as usize;
+
+            let end = if line_end == line_start {
+                column_end.saturating_sub(1)
+            } else {
+                usize::MAX
+            };
+
+            // 附带的标签说明了具体的原因，追加在信息后面。
+            let message = match span["label"].as_str() {
+                Some(label) if !label.is_empty() => format!("{text}: {label}"),
+                _ => text.to_string(),
+            };
+
+            Some(Problem {
+                path: root.join(file),
+                diagnostic: Diagnostic {
+                    line: line_start.saturating_sub(1),
+                    columns: column_start.saturating_sub(1)..end,
+                    severity,
+                    message,
+                },
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,167 @@
+use crate::i18n::tr;
+use crate::palette;
+use crate::search;
+use crate::{Error, Message};
+use iced::widget::{button, column, container, text, text_input};
+use iced::{theme, Element, Length};
+use std::path::{Path, PathBuf};
+
+// 列表中最多显示的文件数量。
+const MAX_RESULTS: usize = 12;
+
+// 定义快速打开文件的面板，按文件路径模糊匹配工作区或目录中的文件。
+#[derive(Debug)]
+pub struct QuickOpen {
+    pub query: String,           // 用户输入的搜索文本。
+    pub selected: usize,         // 选中的文件在匹配结果中的位置。
+    roots: Vec<PathBuf>,         // 查找文件的根目录。
+    files: Option<Vec<PathBuf>>, // 根目录中的所有文件，尚未索引完成时为 None。
+    matches: Vec<usize>,         // 与搜索文本匹配的文件的下标，按匹配程度排序。
+}
+
+impl QuickOpen {
+    // 在指定的根目录中创建面板，文件需要通过 index 在后台索引。
+    pub fn new(roots: Vec<PathBuf>) -> Self {
+        Self {
+            query: String::new(),
+            selected: 0,
+            roots,
+            files: None,
+            matches: Vec::new(),
+        }
+    }
+
+    // 返回输入框的标识，用于在打开面板时获取焦点。
+    pub fn id() -> text_input::Id {
+        text_input::Id::new("quick-open")
+    }
+
+    // 返回查找文件的根目录。
+    pub fn roots(&self) -> &[PathBuf] {
+        &self.roots
+    }
+
+    // 使用索引完成的文件列表，并按已经输入的文本重新匹配。
+    pub fn set_files(&mut self, files: Vec<PathBuf>) {
+        self.files = Some(files);
+        self.refilter(None);
+    }
+
+    // 修改搜索文本。
+    //
+    // 新的文本是在原来的文本后面继续输入时，只需要在上一次的匹配结果中继续筛选。
+    pub fn set_query(&mut self, query: String) {
+        let candidates = query
+            .starts_with(&self.query)
+            .then(|| std::mem::take(&mut self.matches));
+
+        self.query = query;
+        self.selected = 0;
+        self.refilter(candidates);
+    }
+
+    // 在候选的文件中重新匹配搜索文本，没有候选时匹配所有文件。
+    fn refilter(&mut self, candidates: Option<Vec<usize>>) {
+        let Some(files) = &self.files else {
+            return;
+        };
+
+        let candidates = candidates.unwrap_or_else(|| (0..files.len()).collect());
+
+        let mut matches: Vec<_> = candidates
+            .into_iter()
+            .filter_map(|index| {
+                let label = self.label(&files[index]);
+                Some((palette::score(&label, &self.query)?, index))
+            })
+            .collect();
+
+        // 稳定排序，得分相同的文件保持按路径排列的顺序。
+        matches.sort_by(|(a, _), (b, _)| b.cmp(a));
+        self.matches = matches.into_iter().map(|(_, index)| index).collect();
+    }
+
+    // 返回文件相对于根目录的路径；有多个根目录时以根目录的名称开头，便于区分同名的文件。
+    fn label(&self, path: &Path) -> String {
+        let Some(root) = self.roots.iter().find(|root| path.starts_with(root)) else {
+            return path.display().to_string();
+        };
+
+        let relative = path.strip_prefix(root).unwrap_or(path);
+
+        match root.file_name().filter(|_| self.roots.len() > 1) {
+            Some(name) => Path::new(name).join(relative).display().to_string(),
+            None => relative.display().to_string(),
+        }
+    }
+
+    // 返回当前选中的文件。
+    pub fn selection(&self) -> Option<&Path> {
+        let index = *self.matches.get(self.selected)?;
+        Some(&self.files.as_ref()?[index])
+    }
+
+    // 上下移动选中的文件，到达两端时循环。
+    pub fn move_selection(&mut self, offset: isize) {
+        let count = self.matches.len().min(MAX_RESULTS);
+
+        if count > 0 {
+            self.selected = (self.selected as isize + offset).rem_euclid(count as isize) as usize;
+        }
+    }
+
+    // 创建面板的视图。
+    pub fn view(&self) -> Element<'_, Message> {
+        let input = text_input(tr("Go to file"), &self.query)
+            .id(Self::id())
+            .on_input(Message::QuickOpenChanged)
+            .on_submit(Message::QuickOpenSubmitted)
+            .padding(5);
+
+        let Some(files) = &self.files else {
+            return container(column![input, text(tr("Indexing files...")).size(14)].spacing(5))
+                .padding(10)
+                .style(theme::Container::Box)
+                .into();
+        };
+
+        let entries = self
+            .matches
+            .iter()
+            .take(MAX_RESULTS)
+            .enumerate()
+            .map(|(index, file)| {
+                let style = if index == self.selected {
+                    theme::Button::Primary
+                } else {
+                    theme::Button::Text
+                };
+
+                let path = &files[*file];
+
+                button(text(self.label(path)))
+                    .on_press(Message::QuickOpenSelected(path.clone()))
+                    .width(Length::Fill)
+                    .padding([2, 5])
+                    .style(style)
+                    .into()
+            })
+            .collect();
+
+        container(column![input, column(entries)].spacing(5))
+            .padding(10)
+            .style(theme::Container::Box)
+            .into()
+    }
+}
+
+// 定义一个异步函数，在后台索引根目录中的所有文件。返回根目录，用于丢弃过期的结果。
+pub async fn index(roots: Vec<PathBuf>) -> Result<(Vec<PathBuf>, Vec<PathBuf>), Error> {
+    let files = search::walk(roots.clone())
+        .await?
+        .into_iter()
+        .map(|(path, _)| path)
+        .collect();
+
+    Ok((roots, files))
+}
@@ -0,0 +1,216 @@
+use crate::encoding::{Decoded, Encoding};
+use crate::i18n::tr;
+use crate::line_ending::LineEnding;
+use crate::{Error, FileError, Operation};
+use ropey::Rope;
+use serde::{Deserialize, Serialize};
+use similar::{DiffOp, TextDiff};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
+
+// 定义恢复日志中的一处修改：把原来从 start 行开始的 removed 行替换为 text。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Change {
+    start: usize,   // 被替换的第一行，从 0 开始。
+    removed: usize, // 被替换的行数。
+    text: String,   // 替换后的文本，包含各行末尾的换行符。
+}
+
+// 定义恢复日志中一个标签页未保存的修改。
+//
+// 只记录相对于磁盘上文件的修改，新建的文档相对于空白的文本，类似于 Vim 的交换文件。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Entry {
+    pub path: Option<PathBuf>, // 文件路径，新建的文档为 None。
+    base: u64,                 // 记录时磁盘上文件内容的哈希值，用于确认恢复时文件没有变化。
+    changes: Vec<Change>,      // 按位置排列的修改。
+}
+
+// 定义需要写入恢复日志的标签页。
+#[derive(Debug, Clone)]
+pub struct Buffer {
+    pub path: Option<PathBuf>, // 文件路径，新建的文档为 None。
+    pub encoding: Encoding,    // 读取磁盘上的文件时使用的编码。
+    pub text: Rope,            // 编辑器中未保存的内容。
+}
+
+// 定义恢复完成的标签页。
+#[derive(Debug, Clone)]
+pub struct Recovered {
+    pub path: Option<PathBuf>,    // 文件路径，新建的文档为 None。
+    pub decoded: Option<Decoded>, // 磁盘上的文件内容，用于创建标签页。
+    pub text: String,             // 恢复的未保存内容。
+}
+
+// 返回恢复日志的路径，与会话一起保存在系统的数据目录中。
+fn path() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("iced-editor").join("recovery.json"))
+}
+
+// 删除恢复日志。正常退出时调用，因此直接同步删除。
+pub fn clear() {
+    if let Some(path) = path() {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+// 定义一个异步函数来读取上一次留下的恢复日志，没有日志或无法解析时返回空列表。
+pub async fn load() -> Vec<Entry> {
+    let Some(path) = path() else {
+        return Vec::new();
+    };
+
+    match tokio::fs::read_to_string(&path).await {
+        Ok(contents) => serde_json::from_str(&contents).unwrap_or_default(),
+        Err(_) => Vec::new(),
+    }
+}
+
+// 定义一个异步函数，把各个标签页相对于磁盘上文件的修改写入恢复日志，没有标签页时删除日志。
+pub async fn write(buffers: Vec<Buffer>) -> Result<(), Error> {
+    let Some(path) = path() else {
+        return Ok(());
+    };
+
+    if buffers.is_empty() {
+        return match tokio::fs::remove_file(&path).await {
+            Err(error) if error.kind() != std::io::ErrorKind::NotFound => Err(Error::FileFailed(
+                FileError::new(Operation::Delete, &path, &error),
+            )),
+            _ => Ok(()),
+        };
+    }
+
+    let mut entries = Vec::new();
+
+    for buffer in buffers {
+        let base = match &buffer.path {
+            Some(path) => match tokio::fs::read(path).await {
+                Ok(bytes) => LineEnding::normalize(&buffer.encoding.decode(&bytes).text),
+                Err(_) => String::new(),
+            },
+            None => String::new(),
+        };
+
+        entries.push(Entry {
+            path: buffer.path,
+            base: hash(&base),
+            changes: diff(&base, &buffer.text.to_string()),
+        });
+    }
+
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await.map_err(|error| {
+            Error::FileFailed(FileError::new(Operation::CreateFolder, parent, &error))
+        })?;
+    }
+
+    let contents = serde_json::to_vec(&entries).unwrap_or_default();
+
+    crate::write_atomically(&path, &contents, false).await
+}
+
+// 定义一个异步函数，把恢复日志中的修改应用到磁盘上的文件。
+//
+// 记录之后文件在磁盘上又被修改过时，修改无法准确地应用，这个文件会被跳过。
+pub async fn restore(entries: Vec<Entry>) -> Vec<Result<Recovered, Error>> {
+    let mut recovered = Vec::new();
+
+    for entry in entries {
+        let (decoded, base) = match &entry.path {
+            Some(path) => match crate::load_file(path.clone()).await {
+                Ok((_, decoded)) => {
+                    let base = LineEnding::normalize(&decoded.text);
+                    (Some(decoded), base)
+                }
+                Err(_) => (None, String::new()),
+            },
+            None => (None, String::new()),
+        };
+
+        if hash(&base) != entry.base {
+            let name = entry.path.as_ref().map_or_else(
+                || String::from(tr("New File")),
+                |path| path.display().to_string(),
+            );
+
+            recovered.push(Err(Error::RecoveryFailed(format!(
+                "{name} changed on disk since the unsaved changes were recorded"
+            ))));
+            continue;
+        }
+
+        recovered.push(Ok(Recovered {
+            text: apply(&base, &entry.changes),
+            path: entry.path,
+            decoded,
+        }));
+    }
+
+    recovered
+}
+
+// 计算文本的哈希值。
+pub fn hash(text: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    text.hash(&mut hasher);
+    hasher.finish()
+}
+
+// 按行比较原文和修改后的文本，返回需要替换的各处修改。
+fn diff(base: &str, text: &str) -> Vec<Change> {
+    let diff = TextDiff::from_lines(base, text);
+    let lines: Vec<_> = text.split_inclusive('\n').collect();
+
+    diff.ops()
+        .iter()
+        .filter_map(|op| match *op {
+            DiffOp::Equal { .. } => None,
+            DiffOp::Delete {
+                old_index, old_len, ..
+            } => Some(Change {
+                start: old_index,
+                removed: old_len,
+                text: String::new(),
+            }),
+            DiffOp::Insert {
+                old_index,
+                new_index,
+                new_len,
+            } => Some(Change {
+                start: old_index,
+                removed: 0,
+                text: lines[new_index..new_index + new_len].concat(),
+            }),
+            DiffOp::Replace {
+                old_index,
+                old_len,
+                new_index,
+                new_len,
+            } => Some(Change {
+                start: old_index,
+                removed: old_len,
+                text: lines[new_index..new_index + new_len].concat(),
+            }),
+        })
+        .collect()
+}
+
+// 把修改依次应用到原文上。
+fn apply(base: &str, changes: &[Change]) -> String {
+    let lines: Vec<_> = base.split_inclusive('\n').collect();
+    let mut text = String::with_capacity(base.len());
+    let mut next = 0;
+
+    for change in changes {
+        let start = change.start.clamp(next, lines.len());
+
+        text.push_str(&lines[next..start].concat());
+        text.push_str(&change.text);
+        next = (start + change.removed).min(lines.len());
+    }
+
+    text.push_str(&lines[next..].concat());
+    text
+}
@@ -0,0 +1,438 @@
+use crate::encoding::{Decoded, Encoding};
+use crate::i18n::{tr, trf};
+use crate::{Error, Message};
+use base64::engine::general_purpose::STANDARD_NO_PAD;
+use base64::Engine;
+use iced::widget::{button, checkbox, column, container, row, text, text_input};
+use iced::{theme, Element};
+use ssh2::{CheckResult, HostKeyType, KnownHostFileKind, OpenFlags, OpenType, RenameFlags};
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+// 在系统钥匙串中保存密码时使用的服务名称。
+const KEYRING_SERVICE: &str = "iced-editor";
+
+// 未指定端口时使用的 SSH 端口。
+const DEFAULT_PORT: u16 = 22;
+
+// 定义远程服务器上的一个文件，对应 sftp://user@host[:port]/path 形式的网址。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Location {
+    pub user: String, // 登录的用户名。
+    pub host: String, // 服务器地址。
+    pub port: u16,    // SSH 端口。
+    pub path: String, // 服务器上文件的绝对路径。
+}
+
+impl Location {
+    // 解析 sftp://user@host[:port]/path 形式的网址。
+    pub fn parse(input: &str) -> Option<Self> {
+        let rest = input.trim().strip_prefix("sftp://")?;
+        let (authority, path) = rest.split_at(rest.find('/')?);
+        let (user, server) = authority.split_once('@')?;
+
+        let (host, port) = match server.rsplit_once(':') {
+            Some((host, port)) => (host, port.parse().ok()?),
+            None => (server, DEFAULT_PORT),
+        };
+
+        if user.is_empty() || host.is_empty() || path.len() < 2 {
+            return None;
+        }
+
+        Some(Self {
+            user: user.to_string(),
+            host: host.to_string(),
+            port,
+            path: path.to_string(),
+        })
+    }
+
+    // 返回 user@host[:port]，用于标识连接和钥匙串中的密码。
+    pub fn server(&self) -> String {
+        if self.port == DEFAULT_PORT {
+            format!("{}@{}", self.user, self.host)
+        } else {
+            format!("{}@{}:{}", self.user, self.host, self.port)
+        }
+    }
+
+    // 返回文件名。
+    pub fn name(&self) -> Option<&str> {
+        Path::new(&self.path).file_name()?.to_str()
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "sftp://{}{}", self.server(), self.path)
+    }
+}
+
+// 定义服务器的主机密钥，不在 known_hosts 中时由用户确认是否信任。
+#[derive(Debug, Clone)]
+pub struct HostKey {
+    pub server: String,      // user@host[:port]，用于显示。
+    host: String,            // 服务器地址。
+    port: u16,               // SSH 端口。
+    key: Vec<u8>,            // 密钥的内容。
+    kind: HostKeyType,       // 密钥的类型。
+    pub fingerprint: String, // 与 ssh 命令显示的格式相同的 SHA256 指纹。
+}
+
+// 定义与远程服务器的连接状态，显示在状态栏中。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Status {
+    Connecting(String),   // 正在连接服务器。
+    Connected(String),    // 已经连接到服务器。
+    Disconnected(String), // 连接失败或已经断开。
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Connecting(server) => {
+                f.write_str(&trf("Connecting to {server}...", &[("server", server)]))
+            }
+            Self::Connected(server) => write!(f, "● {server}"),
+            Self::Disconnected(server) => write!(f, "○ {server}"),
+        }
+    }
+}
+
+// 定义“打开远程文件”对话框的状态。
+#[derive(Debug, Default)]
+pub struct OpenRemote {
+    pub input: String,             // 用户输入的 sftp:// 网址。
+    pub password: String,          // 用户输入的密码，留空时使用钥匙串中保存的密码或 SSH agent。
+    pub remember: bool,            // 是否把密码保存到系统钥匙串中。
+    pub host_key: Option<HostKey>, // 等待用户确认是否信任的主机密钥。
+}
+
+impl OpenRemote {
+    // 返回输入框的标识，用于在打开对话框时获取焦点。
+    pub fn id() -> text_input::Id {
+        text_input::Id::new("open-remote")
+    }
+
+    // 创建对话框的视图。
+    pub fn view(&self) -> Element<'_, Message> {
+        let input = text_input("sftp://user@host/path/to/file", &self.input)
+            .id(Self::id())
+            .on_input(Message::OpenRemoteChanged)
+            .on_submit(Message::OpenRemoteSubmitted)
+            .padding(5);
+
+        let password = text_input(tr("Password (optional)"), &self.password)
+            .on_input(Message::RemotePasswordChanged)
+            .on_submit(Message::OpenRemoteSubmitted)
+            .password()
+            .padding(5)
+            .width(180);
+
+        let remember = checkbox(
+            tr("Remember"),
+            self.remember,
+            Message::RemoteRememberToggled,
+        );
+
+        let mut content =
+            column![row![text(tr("Open remote file:")), input, password, remember].spacing(10)]
+                .spacing(10);
+
+        // 第一次连接的服务器的主机密钥由用户核对指纹后确认。
+        if let Some(host_key) = &self.host_key {
+            content = content.push(
+                row![
+                    text(trf(
+                        "The authenticity of {server} can't be established. Its key fingerprint is {fingerprint}.",
+                        &[
+                            ("server", &host_key.server),
+                            ("fingerprint", &host_key.fingerprint),
+                        ],
+                    )),
+                    button(text(tr("Trust and Connect")))
+                        .on_press(Message::TrustHostKey)
+                        .style(theme::Button::Destructive),
+                ]
+                .spacing(10),
+            );
+        }
+
+        container(content)
+            .padding(10)
+            .style(theme::Container::Box)
+            .into()
+    }
+}
+
+// 已经建立的 SSH 会话，按 user@host[:port] 复用，避免每次读写都重新登录。
+fn sessions() -> &'static Mutex<HashMap<String, ssh2::Session>> {
+    static SESSIONS: OnceLock<Mutex<HashMap<String, ssh2::Session>>> = OnceLock::new();
+
+    SESSIONS.get_or_init(Mutex::default)
+}
+
+// 返回服务器的会话，没有可用的会话时重新连接。
+//
+// 检查主机密钥后，依次尝试输入的密码、钥匙串中保存的密码和 SSH agent 进行认证。
+// 连接和认证期间不锁定会话表，连接较慢的服务器不会阻塞其他服务器上的读写。
+fn connect(location: &Location, password: Option<&str>) -> Result<ssh2::Session, Error> {
+    let server = location.server();
+
+    if let Some(session) = sessions()
+        .lock()
+        .unwrap_or_else(|error| error.into_inner())
+        .get(&server)
+        .filter(|session| session.authenticated())
+    {
+        return Ok(session.clone());
+    }
+
+    let failed = |error: &dyn fmt::Display| Error::RemoteFailed(format!("{server}: {error}"));
+
+    let tcp = TcpStream::connect((location.host.as_str(), location.port))
+        .map_err(|error| failed(&error))?;
+    let mut session = ssh2::Session::new().map_err(|error| failed(&error))?;
+    session.set_tcp_stream(tcp);
+    session.handshake().map_err(|error| failed(&error))?;
+
+    verify(&session, location)?;
+
+    let saved = keyring::Entry::new(KEYRING_SERVICE, &server)
+        .and_then(|entry| entry.get_password())
+        .ok();
+
+    match password.map(str::to_string).or(saved) {
+        Some(password) => session.userauth_password(&location.user, &password),
+        None => session.userauth_agent(&location.user),
+    }
+    .map_err(|error| failed(&error))?;
+
+    sessions()
+        .lock()
+        .unwrap_or_else(|error| error.into_inner())
+        .insert(server, session.clone());
+
+    Ok(session)
+}
+
+// 检查服务器的主机密钥是否与 ~/.ssh/known_hosts 中记录的一致。
+//
+// 密钥不一致时拒绝连接；没有记录时返回 Error::UnknownHost，由用户核对指纹后调用 trust 记录。
+fn verify(session: &ssh2::Session, location: &Location) -> Result<(), Error> {
+    let server = location.server();
+    let failed = |error: &dyn fmt::Display| Error::RemoteFailed(format!("{server}: {error}"));
+
+    let (key, kind) = session
+        .host_key()
+        .ok_or_else(|| failed(&tr("the server did not send a host key")))?;
+
+    let mut known_hosts = session.known_hosts().map_err(|error| failed(&error))?;
+    if let Some(file) = known_hosts_file() {
+        // 文件不存在时所有服务器都是第一次连接。
+        let _ = known_hosts.read_file(&file, KnownHostFileKind::OpenSSH);
+    }
+
+    match known_hosts.check_port(&location.host, location.port, key) {
+        CheckResult::Match => Ok(()),
+        CheckResult::Mismatch => Err(failed(&tr(
+            "the host key doesn't match the one in known_hosts. The server may have been replaced, or someone may be intercepting the connection",
+        ))),
+        CheckResult::Failure => Err(failed(&tr("couldn't check the host key against known_hosts"))),
+        CheckResult::NotFound => {
+            let hash = session
+                .host_key_hash(ssh2::HashType::Sha256)
+                .unwrap_or_default();
+
+            Err(Error::UnknownHost(HostKey {
+                server,
+                host: location.host.clone(),
+                port: location.port,
+                key: key.to_vec(),
+                kind,
+                fingerprint: format!("SHA256:{}", STANDARD_NO_PAD.encode(hash)),
+            }))
+        }
+    }
+}
+
+// 返回 OpenSSH 记录已知主机密钥的文件。
+fn known_hosts_file() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".ssh").join("known_hosts"))
+}
+
+// 定义一个异步函数，把用户确认信任的主机密钥添加到 ~/.ssh/known_hosts 中。
+pub async fn trust(host_key: HostKey) -> Result<(), Error> {
+    tokio::task::spawn_blocking(move || {
+        let failed =
+            |error: &dyn fmt::Display| Error::RemoteFailed(format!("{}: {error}", host_key.server));
+
+        let file = known_hosts_file().ok_or_else(|| failed(&tr("no home folder")))?;
+        let session = ssh2::Session::new().map_err(|error| failed(&error))?;
+        let mut known_hosts = session.known_hosts().map_err(|error| failed(&error))?;
+        let _ = known_hosts.read_file(&file, KnownHostFileKind::OpenSSH);
+
+        // 非默认端口的主机按照 OpenSSH 的格式记录为 [host]:port。
+        let host = if host_key.port == DEFAULT_PORT {
+            host_key.host.clone()
+        } else {
+            format!("[{}]:{}", host_key.host, host_key.port)
+        };
+
+        known_hosts
+            .add(&host, &host_key.key, "", host_key.kind.into())
+            .map_err(|error| failed(&error))?;
+
+        if let Some(folder) = file.parent() {
+            std::fs::create_dir_all(folder).map_err(|error| failed(&error))?;
+        }
+
+        known_hosts
+            .write_file(&file, KnownHostFileKind::OpenSSH)
+            .map_err(|error| failed(&error))
+    })
+    .await
+    .unwrap_or_else(|error| Err(Error::RemoteFailed(error.to_string())))
+}
+
+// 断开所有远程连接。
+pub fn disconnect() {
+    if let Ok(mut sessions) = sessions().lock() {
+        for (_, session) in sessions.drain() {
+            let _ = session.disconnect(None, "Closed by the editor", None);
+        }
+    }
+}
+
+// 定义一个异步函数，连接服务器并读取远程文件，自动检测文件的字符编码。
+//
+// 认证成功后按需把输入的密码保存到系统钥匙串中，之后打开同一台服务器上的文件时不需要再次输入。
+pub async fn open(
+    location: Location,
+    password: Option<String>,
+    remember: bool,
+) -> Result<(Location, Decoded), Error> {
+    tokio::task::spawn_blocking(move || {
+        let session = connect(&location, password.as_deref())?;
+        let failed = |error: &dyn fmt::Display| Error::RemoteFailed(format!("{location}: {error}"));
+
+        if let (Some(password), true) = (&password, remember) {
+            keyring::Entry::new(KEYRING_SERVICE, &location.server())
+                .and_then(|entry| entry.set_password(password))
+                .map_err(|error| failed(&error))?;
+        }
+
+        let sftp = session.sftp().map_err(|error| failed(&error))?;
+        let mut file = sftp
+            .open(Path::new(&location.path))
+            .map_err(|error| failed(&error))?;
+
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)
+            .map_err(|error| failed(&error))?;
+
+        Ok((location, Encoding::detect(&bytes)))
+    })
+    .await
+    .unwrap_or_else(|error| Err(Error::RemoteFailed(error.to_string())))
+}
+
+// 定义一个异步函数，把内容写回远程文件。
+//
+// 先写入同一目录中的临时文件，再用它替换原文件，写入中途断开时原文件保持不变。
+// 新文件沿用原文件的权限；路径为符号链接时替换链接指向的文件。
+pub async fn save(location: Location, contents: Vec<u8>) -> Result<(), Error> {
+    tokio::task::spawn_blocking(move || {
+        let session = connect(&location, None)?;
+        let failed = |error: &dyn fmt::Display| Error::RemoteFailed(format!("{location}: {error}"));
+
+        let sftp = session.sftp().map_err(|error| failed(&error))?;
+        let path = sftp
+            .realpath(Path::new(&location.path))
+            .unwrap_or_else(|_| PathBuf::from(&location.path));
+        let name = path
+            .file_name()
+            .ok_or_else(|| failed(&tr("invalid path")))?
+            .to_string_lossy()
+            .into_owned();
+        let temporary = path.with_file_name(format!(".{name}.tmp"));
+        let permissions = sftp.stat(&path).ok().and_then(|stat| stat.perm);
+
+        // 原文件无法恢复时保留写有新内容的临时文件，不删除它。
+        let mut keep_temporary = false;
+
+        let mut written = || {
+            let mut file = sftp
+                .open_mode(
+                    &temporary,
+                    OpenFlags::WRITE | OpenFlags::CREATE | OpenFlags::TRUNCATE,
+                    permissions.unwrap_or(0o644) as i32,
+                    OpenType::File,
+                )
+                .map_err(|error| failed(&error))?;
+            file.write_all(&contents).map_err(|error| failed(&error))?;
+            drop(file);
+
+            // 创建文件时的权限受服务器上 umask 的影响，写入后再设置一次。
+            if let Some(perm) = permissions {
+                sftp.setstat(
+                    &temporary,
+                    ssh2::FileStat {
+                        perm: Some(perm),
+                        size: None,
+                        uid: None,
+                        gid: None,
+                        atime: None,
+                        mtime: None,
+                    },
+                )
+                .map_err(|error| failed(&error))?;
+            }
+
+            // SFTP 第 3 版的重命名不能覆盖已有的文件，失败时先把原文件改名为备份再重命名，
+            // 新内容就位后删除备份，仍然失败时把备份改回原来的名字。
+            let flags = Some(RenameFlags::OVERWRITE | RenameFlags::ATOMIC | RenameFlags::NATIVE);
+            if sftp.rename(&temporary, &path, flags).is_err() {
+                let backup = path.with_file_name(format!(".{name}.bak"));
+                let has_backup = sftp.rename(&path, &backup, None).is_ok();
+
+                if let Err(error) = sftp.rename(&temporary, &path, None) {
+                    if has_backup && sftp.rename(&backup, &path, None).is_err() {
+                        keep_temporary = true;
+                        return Err(failed(&trf(
+                            "{error}. The original file was moved to {backup} and the new contents are in {temporary}",
+                            &[
+                                ("error", &error),
+                                ("backup", &backup.display()),
+                                ("temporary", &temporary.display()),
+                            ],
+                        )));
+                    }
+
+                    return Err(failed(&error));
+                }
+
+                if has_backup {
+                    let _ = sftp.unlink(&backup);
+                }
+            }
+
+            Ok(())
+        };
+
+        let written = written();
+
+        if written.is_err() && !keep_temporary {
+            let _ = sftp.unlink(&temporary);
+        }
+
+        written
+    })
+    .await
+    .unwrap_or_else(|error| Err(Error::RemoteFailed(error.to_string())))
+}
@@ -0,0 +1,356 @@
+use crate::i18n::{tr, trf};
+use crate::tab::Tab;
+use crate::Message;
+use iced::widget::{
+    button, checkbox, column, container, horizontal_space, row, scrollable, text, text_input,
+};
+use iced::{theme, Element, Length};
+use regex::{Captures, Regex};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+// 预览中最多显示的替换数量。
+const MAX_PREVIEWS: usize = 100;
+
+// 预览列表的高度。
+const PREVIEW_HEIGHT: f32 = 160.0;
+
+// 定义文档中的一处匹配及其替换结果。
+#[derive(Debug, Clone)]
+pub struct Found {
+    pub line: usize,    // 匹配开始的行，从 0 开始。
+    pub column: usize,  // 匹配在行中的字符位置，从 0 开始。
+    pub before: String, // 匹配的文本。
+    pub after: String,  // 替换后的文本。
+}
+
+// 定义查找范围内的匹配及其替换结果，模式无效时为错误信息。
+type Matches = Rc<Result<Vec<Found>, String>>;
+
+// 定义“查找和替换”面板的状态。
+#[derive(Debug, Default)]
+pub struct Replace {
+    find: String,                             // 查找的文本或正则表达式。
+    replacement: String,                      // 替换的文本，使用正则表达式时可以用 $1 引用捕获组。
+    is_regex: bool,                           // 是否把查找的文本作为正则表达式。
+    pub scope: Option<(usize, usize)>,        // 只替换选区覆盖的行（包含两端），None 表示整个文档。
+    pattern: Option<Result<Regex, String>>,   // 编译好的查找模式，查找的文本为空时为 None。
+    matches: RefCell<Option<(Key, Matches)>>, // 缓存的匹配及其对应的文档状态。
+}
+
+// 定义决定匹配列表的文档状态，任何一项变化时都需要重新查找。
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Key {
+    tab: usize,      // 标签页的标识。
+    revision: usize, // 文档内容的版本号。
+    length: usize,   // 文档的字符数，重新加载文件后版本号可能相同。
+    first: usize,    // 查找范围的第一行。
+    last: usize,     // 查找范围的最后一行（包含）。
+}
+
+impl Replace {
+    // 返回查找输入框的标识，用于在打开面板时获取焦点。
+    pub fn id() -> text_input::Id {
+        text_input::Id::new("find-replace")
+    }
+
+    // 修改查找的文本。
+    pub fn set_find(&mut self, find: String) {
+        self.find = find;
+        self.compile();
+    }
+
+    // 修改替换的文本。
+    pub fn set_replacement(&mut self, replacement: String) {
+        self.replacement = replacement;
+        self.matches.take();
+    }
+
+    // 设置是否把查找的文本作为正则表达式。
+    pub fn set_regex(&mut self, is_regex: bool) {
+        self.is_regex = is_regex;
+        self.compile();
+    }
+
+    // 重新编译查找的模式，不使用正则表达式时按字面匹配，并丢弃缓存的匹配。
+    fn compile(&mut self) {
+        let pattern = if self.is_regex {
+            self.find.clone()
+        } else {
+            regex::escape(&self.find)
+        };
+
+        self.pattern = (!self.find.is_empty())
+            .then(|| Regex::new(&pattern).map_err(|error| error.to_string()));
+        self.matches.take();
+    }
+
+    // 把一处匹配的替换结果追加到 output 中，使用正则表达式时展开 $1 等捕获组。
+    fn expand(&self, captures: &Captures<'_>, output: &mut String) {
+        if self.is_regex {
+            captures.expand(&self.replacement, output);
+        } else {
+            output.push_str(&self.replacement);
+        }
+    }
+
+    // 返回标签页中 first 到 last 行的匹配，文档和查找条件都没有变化时使用缓存的结果。
+    fn matches_in(&self, tab: &Tab, first: usize, last: usize) -> Matches {
+        let key = Key {
+            tab: tab.id,
+            revision: tab.revision,
+            length: tab.context.rope().len_chars(),
+            first,
+            last,
+        };
+
+        let mut entry = self.matches.borrow_mut();
+
+        match entry.as_ref() {
+            Some((cached, matches)) if *cached == key => matches.clone(),
+            _ => {
+                let text = tab.lines(first, last).join("\n");
+                let matches = Rc::new(self.matches(&text, tab.context.full_line(first)));
+                *entry = Some((key, matches.clone()));
+                matches
+            }
+        }
+    }
+
+    // 返回查找范围内的各处匹配及其替换结果，text 为范围内的文本，从 first 行开始。
+    pub fn matches(&self, text: &str, first: usize) -> Result<Vec<Found>, String> {
+        let pattern = match &self.pattern {
+            Some(pattern) => pattern.as_ref().map_err(Clone::clone)?,
+            None => return Ok(Vec::new()),
+        };
+
+        // 从上一处匹配继续统计换行符，而不是每次都从范围的开头数起。
+        let (mut line, mut line_start, mut counted) = (first, 0, 0);
+
+        Ok(pattern
+            .captures_iter(text)
+            .filter_map(|captures| {
+                let found = captures.get(0)?;
+
+                for (index, _) in text[counted..found.start()].match_indices('\n') {
+                    line += 1;
+                    line_start = counted + index + 1;
+                }
+                counted = found.start();
+
+                let mut after = String::new();
+                self.expand(&captures, &mut after);
+
+                Some(Found {
+                    line,
+                    column: text[line_start..found.start()].chars().count(),
+                    before: found.as_str().to_string(),
+                    after,
+                })
+            })
+            .collect())
+    }
+
+    // 替换查找范围内的文本，only 指定只替换第几处匹配，None 表示全部替换。
+    pub fn replace(&self, text: &str, only: Option<usize>) -> Result<String, String> {
+        let pattern = match &self.pattern {
+            Some(pattern) => pattern.as_ref().map_err(Clone::clone)?,
+            None => return Ok(text.to_string()),
+        };
+        let mut output = String::with_capacity(text.len());
+        let mut last = 0;
+
+        for (index, captures) in pattern.captures_iter(text).enumerate() {
+            if only.is_some_and(|only| only != index) {
+                continue;
+            }
+
+            let Some(found) = captures.get(0) else {
+                continue;
+            };
+
+            output.push_str(&text[last..found.start()]);
+            self.expand(&captures, &mut output);
+            last = found.end();
+        }
+
+        output.push_str(&text[last..]);
+        Ok(output)
+    }
+
+    // 创建面板的视图：查找和替换的输入框、选项以及每一处替换的预览。
+    pub fn view(&self, tab: &Tab, (first, last): (usize, usize)) -> Element<'_, Message> {
+        let find = text_input(tr("Find"), &self.find)
+            .id(Self::id())
+            .on_input(Message::FindChanged)
+            .padding(5);
+
+        let replacement = text_input(
+            if self.is_regex {
+                tr("Replace ($1 for capture groups)")
+            } else {
+                tr("Replace")
+            },
+            &self.replacement,
+        )
+        .on_input(Message::ReplacementChanged)
+        .on_submit(Message::ReplaceAll)
+        .padding(5);
+
+        let matches = self.matches_in(tab, first, last);
+
+        let options = row![
+            checkbox(
+                tr("Regular expression"),
+                self.is_regex,
+                Message::RegexToggled
+            )
+            .size(16),
+            checkbox(
+                tr("In selection only"),
+                self.scope.is_some(),
+                Message::ReplaceInSelectionToggled
+            )
+            .size(16),
+            horizontal_space(Length::Fill),
+            text(match &*matches {
+                Ok(matches) if matches.len() == 1 => String::from(tr("1 match")),
+                Ok(matches) => trf("{count} matches", &[("count", &matches.len())]),
+                Err(_) => String::from(tr("Invalid pattern")),
+            })
+            .size(14),
+            button(text(tr("Replace All")).size(14))
+                .on_press_maybe(
+                    matches
+                        .as_ref()
+                        .is_ok_and(|matches| !matches.is_empty())
+                        .then_some(Message::ReplaceAll)
+                )
+                .padding([2, 5]),
+            button(text("×"))
+                .on_press(Message::Dismiss)
+                .style(theme::Button::Text)
+        ]
+        .spacing(10);
+
+        let preview: Element<_> = match &*matches {
+            Ok(matches) if !matches.is_empty() => scrollable(column(
+                matches
+                    .iter()
+                    .take(MAX_PREVIEWS)
+                    .enumerate()
+                    .map(|(index, found)| {
+                        row![
+                            button(
+                                text(format!(
+                                    "{}:{}  {} → {}",
+                                    found.line + 1,
+                                    found.column + 1,
+                                    found.before,
+                                    found.after
+                                ))
+                                .size(14)
+                            )
+                            .on_press(Message::FoundClicked(found.line, found.column))
+                            .width(Length::Fill)
+                            .padding([2, 5])
+                            .style(theme::Button::Text),
+                            button(text(tr("Replace")).size(14))
+                                .on_press(Message::ReplaceOne(index))
+                                .padding([2, 5])
+                        ]
+                        .spacing(5)
+                        .into()
+                    })
+                    .collect(),
+            ))
+            .height(PREVIEW_HEIGHT)
+            .into(),
+            Ok(_) => column![].into(),
+            Err(error) => text(error.clone()).size(14).into(),
+        };
+
+        container(column![row![find, replacement].spacing(10), options, preview].spacing(5))
+            .padding(10)
+            .style(theme::Container::Box)
+            .into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn replace(find: &str, replacement: &str, is_regex: bool) -> Replace {
+        let mut replace = Replace::default();
+        replace.set_find(find.to_string());
+        replace.set_replacement(replacement.to_string());
+        replace.set_regex(is_regex);
+        replace
+    }
+
+    #[test]
+    fn expands_capture_groups() {
+        let replace = replace(r"(\w+)@(\w+)", "$2 at ${1}_x", true);
+
+        assert_eq!(
+            replace.replace("mail: alice@home, bob@work", None).unwrap(),
+            "mail: home at alice_x, work at bob_x"
+        );
+    }
+
+    #[test]
+    fn replaces_literally_without_regex() {
+        let replace = replace("a.b", "$1", false);
+
+        assert_eq!(replace.replace("a.b axb a.b", None).unwrap(), "$1 axb $1");
+    }
+
+    #[test]
+    fn replaces_only_the_chosen_match() {
+        let replace = replace("o", "0", false);
+
+        assert_eq!(replace.replace("foo boo", Some(2)).unwrap(), "foo b0o");
+    }
+
+    #[test]
+    fn reports_match_positions_from_the_first_line() {
+        let replace = replace("(é)x", "[$1]", true);
+        let found = replace.matches("aa\nbéx éx", 10).unwrap();
+
+        assert_eq!(found.len(), 2);
+        assert_eq!((found[0].line, found[0].column), (11, 1));
+        assert_eq!((found[1].line, found[1].column), (11, 4));
+        assert_eq!(found[0].before, "éx");
+        assert_eq!(found[0].after, "[é]");
+    }
+
+    #[test]
+    fn counts_lines_between_matches() {
+        let replace = replace("x", "y", false);
+        let found = replace.matches("x\n\nax\nb\n\n中x x", 0).unwrap();
+
+        let positions: Vec<_> = found
+            .iter()
+            .map(|found| (found.line, found.column))
+            .collect();
+        assert_eq!(positions, [(0, 0), (2, 1), (5, 1), (5, 3)]);
+    }
+
+    #[test]
+    fn keeps_the_text_without_a_pattern() {
+        assert_eq!(
+            replace("", "x", false).replace("text", None).unwrap(),
+            "text"
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_patterns() {
+        assert!(replace("(", "", true).matches("text", 0).is_err());
+        assert!(replace("", "x", true)
+            .matches("text", 0)
+            .unwrap()
+            .is_empty());
+    }
+}
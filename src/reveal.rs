@@ -0,0 +1,85 @@
+use crate::{Error, FileError, Operation};
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+
+// 定义一个异步函数，在系统的文件管理器中显示文件并选中它。
+#[cfg(target_os = "macos")]
+pub async fn reveal(path: PathBuf) -> Result<(), Error> {
+    run(Command::new("open").arg("-R").arg(&path)).await
+}
+
+// 定义一个异步函数，在系统的文件管理器中显示文件并选中它。
+//
+// 资源管理器即使成功打开也会返回非零的退出码，因此只检查能否启动。
+#[cfg(target_os = "windows")]
+pub async fn reveal(path: PathBuf) -> Result<(), Error> {
+    let mut select = std::ffi::OsString::from("/select,");
+    select.push(&path);
+
+    Command::new("explorer")
+        .arg(select)
+        .spawn()
+        .map(|_| ())
+        .map_err(|error| {
+            Error::FileFailed(FileError::new(
+                Operation::Run,
+                Path::new("explorer"),
+                &error,
+            ))
+        })
+}
+
+// 定义一个异步函数，在系统的文件管理器中显示文件并选中它。
+//
+// 先通过 D-Bus 请求支持 FileManager1 接口的文件管理器选中文件，不支持时用 xdg-open 打开所在的目录。
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+pub async fn reveal(path: PathBuf) -> Result<(), Error> {
+    let uri = format!("file://{}", absolute(&path).await.display());
+
+    let selected = run(Command::new("dbus-send")
+        .arg("--session")
+        .arg("--dest=org.freedesktop.FileManager1")
+        .arg("--type=method_call")
+        .arg("/org/freedesktop/FileManager1")
+        .arg("org.freedesktop.FileManager1.ShowItems")
+        .arg(format!("array:string:{uri}"))
+        .arg("string:"))
+    .await;
+
+    match (selected, path.parent()) {
+        (Ok(()), _) => Ok(()),
+        (Err(_), Some(folder)) => run(Command::new("xdg-open").arg(folder)).await,
+        (Err(error), None) => Err(error),
+    }
+}
+
+// 运行命令并等待它结束，退出码不为零时返回错误。
+#[cfg(not(target_os = "windows"))]
+async fn run(command: &mut Command) -> Result<(), Error> {
+    let program = PathBuf::from(command.as_std().get_program());
+    let output = command
+        .output()
+        .await
+        .map_err(|error| Error::FileFailed(FileError::new(Operation::Run, &program, &error)))?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        let error = std::io::Error::new(std::io::ErrorKind::Other, output.status.to_string());
+        Err(Error::FileFailed(FileError::new(
+            Operation::Run,
+            &program,
+            &error,
+        )))
+    }
+}
+
+// 定义一个异步函数来返回文件的绝对路径，文件已经不存在时相对于当前目录拼接。
+pub async fn absolute(path: &Path) -> PathBuf {
+    match tokio::fs::canonicalize(path).await {
+        Ok(path) => path,
+        Err(_) => std::env::current_dir()
+            .map(|directory| directory.join(path))
+            .unwrap_or_else(|_| path.to_path_buf()),
+    }
+}
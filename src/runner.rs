@@ -0,0 +1,74 @@
+use std::path::{Path, PathBuf};
+
+// 定义运行文件的命令：解释型语言直接交给解释器，编译型语言先编译再运行。
+//
+// {file} 替换为文件名，{name} 替换为去掉扩展名的文件名，命令在文件所在的目录中执行。
+const RUNNERS: &[(&str, &str)] = &[
+    ("py", "python3 {file}"),
+    ("js", "node {file}"),
+    ("mjs", "node {file}"),
+    ("ts", "deno run {file}"),
+    ("rb", "ruby {file}"),
+    ("pl", "perl {file}"),
+    ("php", "php {file}"),
+    ("lua", "lua {file}"),
+    ("sh", "sh {file}"),
+    ("bash", "bash {file}"),
+    ("go", "go run {file}"),
+    ("rs", "rustc {file} -o {name} && ./{name}"),
+    ("c", "cc {file} -o {name} && ./{name}"),
+    ("cpp", "c++ {file} -o {name} && ./{name}"),
+    ("java", "java {file}"),
+];
+
+// 判断是否知道如何运行指定的文件，只检查扩展名，不访问文件系统。
+pub fn can_run(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| {
+            RUNNERS
+                .iter()
+                .any(|(known, _)| extension.eq_ignore_ascii_case(known))
+        })
+}
+
+// 返回运行文件的 shell 命令和工作目录，不支持的文件返回 None。
+//
+// Cargo 项目中的 Rust 文件使用 cargo run 在项目根目录中运行。
+pub fn command(path: &Path) -> Option<(String, PathBuf)> {
+    let extension = path.extension()?.to_str()?.to_lowercase();
+    let directory = path.parent()?;
+
+    if extension == "rs" {
+        if let Some(project) = directory
+            .ancestors()
+            .find(|ancestor| ancestor.join("Cargo.toml").is_file())
+        {
+            return Some((String::from("cargo run"), project.to_path_buf()));
+        }
+    }
+
+    let (_, template) = RUNNERS.iter().find(|(known, _)| *known == extension)?;
+
+    let file = quote(path.file_name()?.to_str()?);
+    let name = quote(path.file_stem()?.to_str()?);
+
+    let command = template.replace("{file}", &file).replace("{name}", &name);
+
+    Some((command, directory.to_path_buf()))
+}
+
+// 为 shell 转义参数，文件名中包含空格或特殊字符时也能正确传递。
+fn quote(argument: &str) -> String {
+    let is_plain = argument
+        .chars()
+        .all(|c| c.is_alphanumeric() || matches!(c, '.' | '_' | '-'));
+
+    if is_plain {
+        argument.to_string()
+    } else if cfg!(windows) {
+        format!("\"{argument}\"")
+    } else {
+        format!("'{}'", argument.replace('\'', r"'\''"))
+    }
+}
@@ -0,0 +1,247 @@
+use crate::appearance;
+use crate::i18n::{self, tr};
+use crate::launch;
+use crate::preferences::{self, Preferences};
+use crate::watcher;
+use crate::{config_file, Error, FileError, Operation};
+use iced::futures::channel::mpsc;
+use iced::futures::{SinkExt, StreamExt};
+use iced::widget::{container, text_editor};
+use iced::{executor, subscription, time, window, Event, Font, Subscription};
+use iced::{Application, Command, Element, Length, Settings, Theme};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+// 停止输入多久之后把便签写入文件。
+const SAVE_DELAY: Duration = Duration::from_millis(500);
+
+// 便签窗口的宽度和高度。
+const WIDTH: u32 = 320;
+const HEIGHT: u32 = 400;
+
+// 返回便签文件的路径，与会话一样保存在系统的数据目录中。
+fn path() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("iced-editor").join("scratchpad.md"))
+}
+
+// 运行便签窗口：一个总在其他窗口之上的小窗口，只包含一个编辑器，内容自动保存到便签文件中。
+pub fn run() -> iced::Result {
+    Scratchpad::run(Settings {
+        window: window::Settings {
+            size: (WIDTH, HEIGHT),
+            level: window::Level::AlwaysOnTop,
+            ..Default::default()
+        },
+        default_font: Font::MONOSPACE,
+        // 关闭窗口时先保存还没有写入的内容。
+        exit_on_close_request: false,
+        ..Default::default()
+    })
+}
+
+// 定义便签窗口的状态。
+struct Scratchpad {
+    content: text_editor::Content, // 便签的内容。
+    is_loaded: bool,               // 是否已经读取了便签文件，读取之前不接受输入。
+    is_dark: bool,                 // 是否使用深色外观，按编辑器的偏好设置选择。
+    edited: Option<Instant>,       // 最近一次还没有保存的编辑的时间。
+}
+
+// 定义便签窗口的消息。
+#[derive(Debug, Clone)]
+enum Message {
+    Loaded(Option<String>),                        // 读取到的便签内容。
+    PreferencesLoaded(Result<Preferences, Error>), // 偏好设置加载结果。
+    PreferencesChanged,                            // 偏好设置文件在磁盘上被修改。
+    Edit(text_editor::Action),                     // 编辑器的动作。
+    SaveTick,                                      // 检查是否需要保存。
+    Saved(Result<(), Error>),                      // 便签的保存结果。
+    CloseRequested,                                // 用户或编辑器请求关闭窗口。
+}
+
+impl Scratchpad {
+    // 把便签写入文件，必要时创建数据目录。
+    fn save(&mut self) -> Command<Message> {
+        self.edited = None;
+        Command::perform(write(path(), self.content.text()), Message::Saved)
+    }
+}
+
+impl Application for Scratchpad {
+    type Message = Message;
+    type Executor = executor::Default;
+    type Theme = Theme;
+    type Flags = ();
+
+    fn new(_flags: ()) -> (Self, Command<Message>) {
+        (
+            Self {
+                content: text_editor::Content::new(),
+                is_loaded: false,
+                is_dark: appearance::system_is_dark(),
+                edited: None,
+            },
+            Command::batch([
+                Command::perform(read(path()), Message::Loaded),
+                Command::perform(
+                    preferences::load(config_file("preferences.toml")),
+                    Message::PreferencesLoaded,
+                ),
+            ]),
+        )
+    }
+
+    fn title(&self) -> String {
+        tr("Scratchpad").to_string()
+    }
+
+    fn update(&mut self, message: Message) -> Command<Message> {
+        match message {
+            Message::Loaded(text) => {
+                if let Some(text) = text {
+                    self.content = text_editor::Content::with(&text);
+                }
+
+                self.is_loaded = true;
+                Command::none()
+            }
+            Message::PreferencesLoaded(Ok(preferences)) => {
+                i18n::set_language(preferences.language);
+                self.is_dark = preferences.appearance.is_dark(self.is_dark);
+                Command::none()
+            }
+            Message::PreferencesLoaded(Err(_)) => Command::none(),
+            Message::PreferencesChanged => Command::perform(
+                preferences::load(config_file("preferences.toml")),
+                Message::PreferencesLoaded,
+            ),
+            Message::Edit(action) => {
+                // 读取完成之前的输入会被文件的内容覆盖，直接忽略。
+                if action.is_edit() && !self.is_loaded {
+                    return Command::none();
+                }
+
+                if action.is_edit() {
+                    self.edited = Some(Instant::now());
+                }
+
+                self.content.edit(action);
+                Command::none()
+            }
+            Message::SaveTick => match self.edited {
+                Some(edited) if edited.elapsed() >= SAVE_DELAY => self.save(),
+                _ => Command::none(),
+            },
+            // 便签窗口没有显示错误的地方，保存失败时在下一次编辑后重试。
+            Message::Saved(_) => Command::none(),
+            Message::CloseRequested => {
+                // 关闭时同步写入，窗口关闭后异步任务不会再执行。
+                if let (Some(_), Some(path)) = (self.edited, path()) {
+                    if let Some(parent) = path.parent() {
+                        let _ = std::fs::create_dir_all(parent);
+                    }
+
+                    let _ = std::fs::write(&path, self.content.text());
+                }
+
+                window::close()
+            }
+        }
+    }
+
+    fn subscription(&self) -> Subscription<Message> {
+        let events = subscription::events_with(|event, _status| match event {
+            Event::Window(window::Event::CloseRequested) => Some(Message::CloseRequested),
+            _ => None,
+        });
+
+        let save = if self.edited.is_some() {
+            time::every(SAVE_DELAY).map(|_| Message::SaveTick)
+        } else {
+            Subscription::none()
+        };
+
+        // 在编辑器中修改外观或语言后随之更新。
+        let preferences = watcher::watch(
+            config_file("preferences.toml").into_iter().collect(),
+            |_| Message::PreferencesChanged,
+        );
+
+        Subscription::batch([events, save, preferences, close_requests()])
+    }
+
+    fn view(&self) -> Element<'_, Message> {
+        container(
+            text_editor(&self.content)
+                .on_edit(Message::Edit)
+                .height(Length::Fill),
+        )
+        .padding(5)
+        .into()
+    }
+
+    fn theme(&self) -> Theme {
+        if self.is_dark {
+            Theme::Dark
+        } else {
+            Theme::Light
+        }
+    }
+}
+
+// 创建一个订阅，在编辑器通过标准输入要求关闭时请求关闭窗口。
+//
+// 读取标准输入会阻塞，放在单独的线程中进行。编辑器退出后标准输入结束，便签窗口保持打开。
+fn close_requests() -> Subscription<Message> {
+    struct CloseRequests;
+
+    subscription::channel(
+        std::any::TypeId::of::<CloseRequests>(),
+        1,
+        |mut output| async move {
+            let (sender, mut receiver) = mpsc::unbounded();
+
+            std::thread::spawn(move || {
+                for line in std::io::stdin().lines() {
+                    let Ok(line) = line else {
+                        break;
+                    };
+
+                    if line.trim() == launch::CLOSE_SCRATCHPAD {
+                        let _ = sender.unbounded_send(());
+                    }
+                }
+            });
+
+            loop {
+                if receiver.next().await.is_some() {
+                    let _ = output.send(Message::CloseRequested).await;
+                } else {
+                    std::future::pending::<()>().await;
+                }
+            }
+        },
+    )
+}
+
+// 定义一个异步函数来读取便签文件，文件不存在或无法读取时返回 None。
+async fn read(path: Option<PathBuf>) -> Option<String> {
+    tokio::fs::read_to_string(path?).await.ok()
+}
+
+// 定义一个异步函数来写入便签文件，必要时创建数据目录。
+async fn write(path: Option<PathBuf>, text: String) -> Result<(), Error> {
+    let Some(path) = path else {
+        return Ok(());
+    };
+
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await.map_err(|error| {
+            Error::FileFailed(FileError::new(Operation::CreateFolder, parent, &error))
+        })?;
+    }
+
+    tokio::fs::write(&path, text)
+        .await
+        .map_err(|error| Error::FileFailed(FileError::new(Operation::Write, &path, &error)))
+}
@@ -0,0 +1,392 @@
+use crate::i18n::tr;
+use crate::{Error, FileError, Message, Operation};
+use iced::widget::{
+    button, column, container, horizontal_space, pick_list, row, scrollable, text, text_input,
+};
+use iced::{theme, Color, Element, Length};
+use regex::Regex;
+use rhai::{Dynamic, Engine, EvalAltResult};
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+// 最多保留的输出行数，超过时丢弃最早的输出。
+const MAX_LINES: usize = 1000;
+
+// 控制台面板的高度。
+const HEIGHT: f32 = 220.0;
+
+// 每次运行脚本时最多执行的操作数，用完时中止脚本，以免死循环一直占用后台线程。
+const MAX_OPERATIONS: u64 = 10_000_000;
+
+// 脚本中字符串的最大字节数、数组和对象的最大元素数，超过时中止脚本，以免耗尽内存。
+const MAX_STRING_SIZE: usize = 64 * 1024 * 1024;
+const MAX_ARRAY_SIZE: usize = 1_000_000;
+const MAX_MAP_SIZE: usize = 1_000_000;
+
+// 错误输出的颜色。
+const ERROR_COLOR: Color = Color::from_rgb(0.85, 0.35, 0.3);
+
+// 定义脚本目录中保存的脚本。
+#[derive(Debug, Clone)]
+pub struct Script {
+    pub name: String,   // 脚本的名称，即文件名（不含扩展名）。
+    pub source: String, // 脚本的源代码。
+}
+
+// 定义脚本运行时可以读写的文档状态。
+#[derive(Debug, Default)]
+struct Buffer {
+    text: String,                  // 文档的内容。
+    selection: String,             // 选中的文本，没有选区时为空。
+    text_changed: bool,            // 脚本是否修改了文档的内容。
+    new_selection: Option<String>, // 脚本替换选中的文本后的内容。
+    output: Vec<String>,           // 脚本打印的内容。
+    notifications: Vec<String>,    // 脚本要求显示的通知。
+}
+
+// 定义脚本运行的结果。
+#[derive(Debug, Clone)]
+pub struct Outcome {
+    pub result: Result<Option<String>, String>, // 脚本的返回值，没有返回值时为 None；出错时为错误信息。
+    pub text: Option<String>,                   // 脚本修改后的文档内容。
+    pub selection: Option<String>,              // 脚本替换选中的文本后的内容。
+    pub output: Vec<String>,                    // 脚本打印的内容。
+    pub notifications: Vec<String>,             // 脚本要求显示的通知。
+}
+
+// 运行 Rhai 脚本，text 和 selection 为文档的内容和选中的文本。
+//
+// 脚本可以调用以下函数：
+// text()、set_text(s)：读取和替换整个文档；
+// selection()、replace_selection(s)：读取和替换选中的文本，没有选区时替换光标所在的行；
+// line_count()、line(n)：返回行数和第 n 行（从 1 开始）；
+// rename(old, new)：把文档中作为完整单词出现的 old 替换为 new，返回替换的数量；
+// replace_regex(s, pattern, replacement)：按正则表达式替换，replacement 中可以用 $1 引用捕获组；
+// notify(s)：在状态栏中显示通知；print(s)：输出到控制台。
+//
+// 脚本在后台线程中运行，以免脚本运行期间界面停止响应。
+pub async fn run(source: String, text: String, selection: Option<String>) -> Outcome {
+    tokio::task::spawn_blocking(move || evaluate(&source, text, selection))
+        .await
+        .unwrap_or_else(|error| Outcome {
+            result: Err(error.to_string()),
+            text: None,
+            selection: None,
+            output: Vec::new(),
+            notifications: Vec::new(),
+        })
+}
+
+// 在当前线程中运行脚本，参数与 run 相同。
+fn evaluate(source: &str, text: String, selection: Option<String>) -> Outcome {
+    let buffer = Rc::new(RefCell::new(Buffer {
+        text,
+        selection: selection.unwrap_or_default(),
+        ..Buffer::default()
+    }));
+
+    let mut engine = Engine::new();
+    engine.set_max_operations(MAX_OPERATIONS);
+    engine.set_max_string_size(MAX_STRING_SIZE);
+    engine.set_max_array_size(MAX_ARRAY_SIZE);
+    engine.set_max_map_size(MAX_MAP_SIZE);
+
+    let state = buffer.clone();
+    engine.on_print(move |line| state.borrow_mut().output.push(line.to_string()));
+
+    let state = buffer.clone();
+    engine.on_debug(move |line, _, _| state.borrow_mut().output.push(line.to_string()));
+
+    let state = buffer.clone();
+    engine.register_fn("text", move || state.borrow().text.clone());
+
+    let state = buffer.clone();
+    engine.register_fn("set_text", move |text: &str| {
+        let mut buffer = state.borrow_mut();
+        buffer.text = text.to_string();
+        buffer.text_changed = true;
+    });
+
+    let state = buffer.clone();
+    engine.register_fn("selection", move || state.borrow().selection.clone());
+
+    let state = buffer.clone();
+    engine.register_fn("replace_selection", move |text: &str| {
+        let mut buffer = state.borrow_mut();
+        buffer.selection = text.to_string();
+        buffer.new_selection = Some(text.to_string());
+    });
+
+    let state = buffer.clone();
+    engine.register_fn("line_count", move || {
+        state.borrow().text.split('\n').count() as i64
+    });
+
+    let state = buffer.clone();
+    engine.register_fn("line", move |number: i64| {
+        let buffer = state.borrow();
+
+        usize::try_from(number - 1)
+            .ok()
+            .and_then(|index| buffer.text.split('\n').nth(index))
+            .unwrap_or_default()
+            .to_string()
+    });
+
+    let state = buffer.clone();
+    engine.register_fn(
+        "rename",
+        move |old: &str, new: &str| -> Result<i64, Box<EvalAltResult>> {
+            let pattern = Regex::new(&format!(r"\b{}\b", regex::escape(old)))
+                .map_err(|error| error.to_string())?;
+
+            let mut buffer = state.borrow_mut();
+            let count = pattern.find_iter(&buffer.text).count();
+
+            if count > 0 {
+                buffer.text = pattern
+                    .replace_all(&buffer.text, regex::NoExpand(new))
+                    .into_owned();
+                buffer.text_changed = true;
+            }
+
+            Ok(count as i64)
+        },
+    );
+
+    engine.register_fn(
+        "replace_regex",
+        |text: &str, pattern: &str, replacement: &str| -> Result<String, Box<EvalAltResult>> {
+            let pattern = Regex::new(pattern).map_err(|error| error.to_string())?;
+            Ok(pattern.replace_all(text, replacement).into_owned())
+        },
+    );
+
+    let state = buffer.clone();
+    engine.register_fn("notify", move |message: &str| {
+        state.borrow_mut().notifications.push(message.to_string());
+    });
+
+    let result = engine
+        .eval::<Dynamic>(source)
+        .map(|value| (!value.is_unit()).then(|| value.to_string()))
+        .map_err(|error| error.to_string());
+
+    // 释放引擎中注册的函数持有的引用，之后才能取出文档状态。
+    drop(engine);
+
+    let buffer = Rc::try_unwrap(buffer)
+        .map(RefCell::into_inner)
+        .unwrap_or_default();
+
+    Outcome {
+        result,
+        text: buffer.text_changed.then_some(buffer.text),
+        selection: buffer.new_selection,
+        output: buffer.output,
+        notifications: buffer.notifications,
+    }
+}
+
+// 定义控制台中的一行输出。
+#[derive(Debug, Clone)]
+struct Line {
+    text: String,   // 这一行的文本。
+    is_error: bool, // 是否为错误信息。
+}
+
+// 定义脚本控制台面板的状态。
+#[derive(Debug, Default)]
+pub struct Console {
+    pub input: String,        // 输入框中的脚本。
+    pub scripts: Vec<Script>, // 脚本目录中的脚本。
+    lines: VecDeque<Line>,    // 已经输出的内容。
+}
+
+impl Console {
+    // 返回输入框的标识，用于在打开面板时获取焦点。
+    pub fn input_id() -> text_input::Id {
+        text_input::Id::new("console-input")
+    }
+
+    // 返回输出区域的标识，用于在输出新内容时滚动到底部。
+    pub fn output_id() -> scrollable::Id {
+        scrollable::Id::new("console-output")
+    }
+
+    // 根据名称查找脚本目录中的脚本。
+    pub fn script(&self, name: &str) -> Option<&Script> {
+        self.scripts.iter().find(|script| script.name == name)
+    }
+
+    // 在控制台中显示运行的脚本以及脚本的输出和结果。
+    pub fn report(&mut self, command: String, outcome: &Outcome) {
+        self.push(format!("> {command}"), false);
+
+        for line in &outcome.output {
+            self.push(line.clone(), false);
+        }
+
+        match &outcome.result {
+            Ok(Some(value)) => self.push(value.clone(), false),
+            Ok(None) => {}
+            Err(error) => self.push(error.clone(), true),
+        }
+    }
+
+    // 清空输出。
+    pub fn clear(&mut self) {
+        self.lines.clear();
+    }
+
+    // 追加一行输出，超过上限时丢弃最早的一行。
+    fn push(&mut self, text: String, is_error: bool) {
+        if self.lines.len() >= MAX_LINES {
+            let _ = self.lines.pop_front();
+        }
+
+        self.lines.push_back(Line { text, is_error });
+    }
+
+    // 创建控制台面板的视图。
+    pub fn view(&self) -> Element<'_, Message> {
+        let output = column(
+            self.lines
+                .iter()
+                .map(|line| {
+                    let label = text(&line.text).size(13);
+
+                    if line.is_error {
+                        label.style(ERROR_COLOR).into()
+                    } else {
+                        label.into()
+                    }
+                })
+                .collect(),
+        );
+
+        let input = text_input(
+            tr("Run a Rhai script, e.g. rename(\"old\", \"new\")"),
+            &self.input,
+        )
+        .id(Self::input_id())
+        .on_input(Message::ConsoleInputChanged)
+        .on_submit(Message::ConsoleSubmitted)
+        .size(14)
+        .padding(5);
+
+        let scripts: Vec<String> = self
+            .scripts
+            .iter()
+            .map(|script| script.name.clone())
+            .collect();
+
+        let controls = row![
+            input,
+            button(text(tr("Run")).size(14))
+                .on_press(Message::ConsoleSubmitted)
+                .padding([5, 10]),
+            pick_list(scripts, None, Message::RunScript)
+                .placeholder(tr("Run a saved script"))
+                .text_size(14),
+            button(text(tr("Clear")).size(14))
+                .on_press(Message::ConsoleClear)
+                .padding([5, 10])
+                .style(theme::Button::Secondary),
+            horizontal_space(5),
+            button(text("×"))
+                .on_press(Message::ToggleConsole)
+                .style(theme::Button::Text)
+        ]
+        .spacing(5);
+
+        container(
+            column![
+                scrollable(output.width(Length::Fill))
+                    .id(Self::output_id())
+                    .height(Length::Fill),
+                controls
+            ]
+            .spacing(5),
+        )
+        .width(Length::Fill)
+        .height(HEIGHT)
+        .padding(5)
+        .style(theme::Container::Box)
+        .into()
+    }
+}
+
+// 定义一个异步函数来加载脚本目录中的 .rhai 脚本，目录不存在时没有脚本。
+pub async fn load(folder: Option<PathBuf>) -> Result<Vec<Script>, Error> {
+    let Some(folder) = folder else {
+        return Ok(Vec::new());
+    };
+
+    let mut entries = match tokio::fs::read_dir(&folder).await {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => {
+            return Err(Error::FileFailed(FileError::new(
+                Operation::ListFolder,
+                &folder,
+                &error,
+            )))
+        }
+    };
+
+    let mut scripts = Vec::new();
+
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let path = entry.path();
+
+        if path.extension().and_then(|extension| extension.to_str()) != Some("rhai") {
+            continue;
+        }
+
+        let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) else {
+            continue;
+        };
+
+        let source = tokio::fs::read_to_string(&path)
+            .await
+            .map_err(|error| Error::FileFailed(FileError::new(Operation::Read, &path, &error)))?;
+
+        scripts.push(Script {
+            name: name.to_string(),
+            source,
+        });
+    }
+
+    scripts.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(scripts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stops_scripts_that_grow_without_limit() {
+        let outcome = evaluate(r#"let s = "x"; loop { s += s; }"#, String::new(), None);
+        assert!(outcome.result.is_err());
+
+        let outcome = evaluate("let a = []; loop { a.push(1); }", String::new(), None);
+        assert!(outcome.result.is_err());
+    }
+
+    #[test]
+    fn replaces_the_selection() {
+        let outcome = evaluate(
+            "replace_selection(selection().to_upper())",
+            String::from("a b"),
+            Some(String::from("b")),
+        );
+
+        assert_eq!(outcome.result, Ok(None));
+        assert_eq!(outcome.selection.as_deref(), Some("B"));
+        assert_eq!(outcome.text, None);
+    }
+}
@@ -0,0 +1,245 @@
+use crate::i18n::{tr, trf};
+use crate::{Error, FileError, Message, Operation};
+use iced::widget::{
+    button, column, container, horizontal_space, row, scrollable, text, text_input,
+};
+use iced::{theme, Element, Length};
+use std::path::PathBuf;
+
+// 跳过的文件大小上限，超过的文件通常不是需要搜索的源代码。
+const MAX_FILE_SIZE: u64 = 4 * 1024 * 1024;
+
+// 最多返回的匹配数量，避免常见的搜索词产生过多结果。
+const MAX_MATCHES: usize = 1000;
+
+// 遍历目录时跳过的目录名称，隐藏的文件和目录也会被跳过。
+const IGNORED: &[&str] = &["target", "node_modules"];
+
+// 定义文件中的一处匹配。
+#[derive(Debug, Clone)]
+pub struct Match {
+    pub line: usize,     // 匹配所在的行，从 0 开始。
+    pub column: usize,   // 匹配在行中的字符位置，从 0 开始。
+    pub preview: String, // 匹配所在行的文本。
+}
+
+// 定义一个文件中的所有匹配。
+#[derive(Debug, Clone)]
+pub struct FileMatches {
+    pub path: PathBuf,       // 文件的完整路径。
+    pub matches: Vec<Match>, // 文件中的匹配。
+}
+
+// 定义搜索面板的状态。
+#[derive(Debug, Default)]
+pub struct Search {
+    pub folders: Vec<PathBuf>, // 搜索的目录，工作区有多个根目录时全部搜索。
+    pub query: String,         // 搜索的文本。
+    pub results: Option<Vec<FileMatches>>, // 最近一次搜索的结果。
+    pub is_searching: bool,    // 是否正在搜索。
+}
+
+impl Search {
+    // 在指定的目录中创建搜索面板。
+    pub fn new(folders: Vec<PathBuf>) -> Self {
+        Self {
+            folders,
+            ..Self::default()
+        }
+    }
+
+    // 返回输入框的标识，用于在打开面板时获取焦点。
+    pub fn id() -> text_input::Id {
+        text_input::Id::new("search-in-files")
+    }
+
+    // 创建搜索面板的视图。
+    pub fn view(&self) -> Element<'_, Message> {
+        let names: Vec<_> = self
+            .folders
+            .iter()
+            .filter_map(|folder| folder.file_name()?.to_str())
+            .collect();
+
+        let folder = if names.is_empty() {
+            String::from(tr("No folder"))
+        } else {
+            names.join(", ")
+        };
+
+        let header = row![
+            text(tr("Search")).size(18),
+            horizontal_space(Length::Fill),
+            button(text("×"))
+                .on_press(Message::CloseSearch)
+                .style(theme::Button::Text)
+        ];
+
+        let input = text_input(tr("Search in files"), &self.query)
+            .id(Self::id())
+            .on_input(Message::SearchQueryChanged)
+            .on_submit(Message::SearchSubmitted)
+            .padding(5);
+
+        let scope = row![
+            text(folder).size(14),
+            horizontal_space(Length::Fill),
+            button(text(tr("Change")).size(14))
+                .on_press(Message::ChooseSearchFolder)
+                .padding([2, 5])
+        ];
+
+        let status = if self.is_searching {
+            Some(text(tr("Searching...")).size(14))
+        } else {
+            self.results.as_ref().map(|results| {
+                let count: usize = results.iter().map(|file| file.matches.len()).sum();
+                text(trf(
+                    "{count} matches in {files} files",
+                    &[("count", &count), ("files", &results.len())],
+                ))
+                .size(14)
+            })
+        };
+
+        let mut rows = Vec::new();
+
+        for file in self.results.iter().flatten() {
+            let name = self
+                .folders
+                .iter()
+                .find_map(|folder| file.path.strip_prefix(folder).ok())
+                .unwrap_or(&file.path);
+
+            rows.push(text(name.display()).size(14).into());
+
+            for found in &file.matches {
+                rows.push(
+                    button(text(format!("{}: {}", found.line + 1, found.preview.trim())).size(13))
+                        .on_press(Message::SearchResultClicked(
+                            file.path.clone(),
+                            found.line,
+                            found.column,
+                        ))
+                        .padding([2, 10])
+                        .style(theme::Button::Text)
+                        .into(),
+                );
+            }
+        }
+
+        let mut content = column![header, input, scope].spacing(10);
+
+        if let Some(status) = status {
+            content = content.push(status);
+        }
+
+        container(content.push(scrollable(column(rows).spacing(2))))
+            .width(300)
+            .height(Length::Fill)
+            .padding(5)
+            .style(theme::Container::Box)
+            .into()
+    }
+}
+
+// 定义一个异步函数，列出各个目录及其子目录中的所有文件及其大小。
+//
+// 隐藏的文件和目录以及构建目录会被跳过，嵌套的根目录中的文件只列出一次。
+pub async fn walk(folders: Vec<PathBuf>) -> Result<Vec<(PathBuf, u64)>, Error> {
+    let mut pending = folders;
+    let mut files = Vec::new();
+
+    while let Some(directory) = pending.pop() {
+        let mut entries = tokio::fs::read_dir(&directory).await.map_err(|error| {
+            Error::FileFailed(FileError::new(Operation::ListFolder, &directory, &error))
+        })?;
+
+        while let Some(entry) = entries.next_entry().await.map_err(|error| {
+            Error::FileFailed(FileError::new(Operation::ListFolder, &directory, &error))
+        })? {
+            let path = entry.path();
+            let name = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .unwrap_or_default();
+            let is_skipped = name.starts_with('.') || IGNORED.contains(&name);
+
+            if is_skipped {
+                continue;
+            }
+
+            let Ok(metadata) = entry.metadata().await else {
+                continue;
+            };
+
+            if metadata.is_dir() {
+                pending.push(path);
+            } else {
+                files.push((path, metadata.len()));
+            }
+        }
+    }
+
+    files.sort();
+    files.dedup();
+
+    Ok(files)
+}
+
+// 定义一个异步函数，在各个目录及其子目录的所有文本文件中搜索指定的文本。
+//
+// 二进制文件和过大的文件会被跳过。返回搜索的文本，用于丢弃过期的结果。
+pub async fn search(
+    folders: Vec<PathBuf>,
+    query: String,
+) -> Result<(String, Vec<FileMatches>), Error> {
+    let files = walk(folders)
+        .await?
+        .into_iter()
+        .filter(|(_, size)| *size <= MAX_FILE_SIZE)
+        .map(|(path, _)| path);
+
+    let mut results = Vec::new();
+    let mut total = 0;
+
+    for path in files {
+        if total >= MAX_MATCHES {
+            break;
+        }
+
+        // 无法读取或不是 UTF-8 文本的文件直接跳过。
+        let Ok(bytes) = tokio::fs::read(&path).await else {
+            continue;
+        };
+
+        let Ok(contents) = String::from_utf8(bytes) else {
+            continue;
+        };
+
+        let matches: Vec<_> = find(&contents, &query).take(MAX_MATCHES - total).collect();
+
+        if !matches.is_empty() {
+            total += matches.len();
+            results.push(FileMatches { path, matches });
+        }
+    }
+
+    Ok((query, results))
+}
+
+// 查找文本中每一行的第一处匹配。
+fn find<'a>(contents: &'a str, query: &'a str) -> impl Iterator<Item = Match> + 'a {
+    contents
+        .lines()
+        .enumerate()
+        .filter_map(move |(line, text)| {
+            let index = text.find(query)?;
+
+            Some(Match {
+                line,
+                column: text[..index].chars().count(),
+                preview: text.to_string(),
+            })
+        })
+}
@@ -0,0 +1,175 @@
+use crate::cursors;
+use crate::folding;
+use crate::navigation;
+use crate::syntax_tree;
+use crate::tab::Tab;
+use ropey::Rope;
+use std::ops::Range;
+
+// 查找包围选区的括号时向前和向后最多扫描的字符数，避免在大文件中卡顿。
+const SCAN_LIMIT: usize = 100_000;
+
+// 选中光标所在的行（包括换行符）；已经选中整行时再多选中下一行。
+pub fn select_line(tab: &mut Tab) {
+    let rope = tab.context.rope();
+    let range = cursors::selected_range(tab);
+    let (first, last) = lines(rope, &range);
+
+    let is_whole_lines = !range.is_empty()
+        && range.start == rope.line_to_char(first)
+        && range.end == line_start(rope, last + 1);
+
+    let last = if is_whole_lines { last + 1 } else { last };
+    let range = rope.line_to_char(first)..line_start(rope, last + 1);
+
+    cursors::select_range(tab, range);
+}
+
+// 把选区扩大到包含它的下一级范围：单词、行的内容、整行、括号内外、语法节点和代码块，最后是整个文档。
+//
+// tree 为当前文档的语法树，有语法树时按语法节点扩大，extension 用于按语言划分代码块。
+pub fn expand(tab: &mut Tab, tree: Option<syntax_tree::Snapshot>, extension: &str) {
+    let rope = tab.context.rope();
+    let range = cursors::selected_range(tab);
+
+    let next = candidates(rope, &range, tree, extension)
+        .into_iter()
+        .filter(|candidate| {
+            candidate.start <= range.start
+                && candidate.end >= range.end
+                && candidate.len() > range.len()
+        })
+        .min_by_key(|candidate| candidate.len());
+
+    if let Some(next) = next {
+        cursors::select_range(tab, next);
+    }
+}
+
+// 返回可能包含选区的各种范围，不一定按大小排列。
+fn candidates(
+    rope: &Rope,
+    range: &Range<usize>,
+    tree: Option<syntax_tree::Snapshot>,
+    extension: &str,
+) -> Vec<Range<usize>> {
+    let (first, last) = lines(rope, range);
+    let mut candidates = vec![
+        navigation::word_at(rope, range.start),
+        line_content(rope, first, last),
+        rope.line_to_char(first)..line_start(rope, last + 1),
+        0..rope.len_chars(),
+    ];
+
+    if let Some((open, close)) = enclosing_brackets(rope, range) {
+        candidates.push(open + 1..close);
+        candidates.push(open..close + 1);
+    }
+
+    if let Some(tree) = tree {
+        candidates.extend(tree.enclosing(rope, range.clone()));
+    }
+
+    // 代码块从开始行的行首到结束行的行尾，按缩进划分代码块的语言没有括号。
+    candidates.extend(
+        folding::regions(rope, extension)
+            .into_iter()
+            .filter(|region| region.start <= first && last <= region.end)
+            .map(|region| rope.line_to_char(region.start)..line_end(rope, region.end)),
+    );
+
+    candidates
+}
+
+// 返回选区覆盖的第一行和最后一行，结束于行首的选区不包括结束的那一行。
+fn lines(rope: &Rope, range: &Range<usize>) -> (usize, usize) {
+    let first = rope.char_to_line(range.start);
+    let last = rope.char_to_line(range.end);
+
+    if last > first && range.end == rope.line_to_char(last) {
+        (first, last - 1)
+    } else {
+        (first, last)
+    }
+}
+
+// 返回一行的行首位置，超出最后一行时返回文档的末尾。
+fn line_start(rope: &Rope, line: usize) -> usize {
+    if line < rope.len_lines() {
+        rope.line_to_char(line)
+    } else {
+        rope.len_chars()
+    }
+}
+
+// 返回一行的行尾位置，不包括换行符。
+fn line_end(rope: &Rope, line: usize) -> usize {
+    let text = rope.line(line);
+    let newline = text
+        .chars()
+        .rev()
+        .take_while(|c| *c == '\n' || *c == '\r')
+        .count();
+
+    rope.line_to_char(line) + text.len_chars() - newline
+}
+
+// 返回从第一行到最后一行的内容，不包括开头的缩进和结尾的空白。
+fn line_content(rope: &Rope, first: usize, last: usize) -> Range<usize> {
+    let start = rope.line_to_char(first);
+    let end = line_end(rope, last);
+    let text = rope.slice(start..end);
+
+    let leading = text.chars().take_while(|c| c.is_whitespace()).count();
+    let trailing = text
+        .chars()
+        .rev()
+        .take(end - start - leading)
+        .take_while(|c| c.is_whitespace())
+        .count();
+
+    start + leading..end - trailing
+}
+
+// 查找包围选区的最近一对括号，返回开括号和闭括号的位置。不区分字符串和注释中的括号。
+fn enclosing_brackets(rope: &Rope, range: &Range<usize>) -> Option<(usize, usize)> {
+    let mut depth = 0;
+    let mut open = None;
+
+    for (index, c) in rope
+        .chars_at(range.start)
+        .reversed()
+        .take(SCAN_LIMIT)
+        .enumerate()
+    {
+        match c {
+            ')' | ']' | '}' => depth += 1,
+            '(' | '[' | '{' if depth == 0 => {
+                open = Some((range.start - index - 1, c));
+                break;
+            }
+            '(' | '[' | '{' => depth -= 1,
+            _ => {}
+        }
+    }
+
+    let (open, opener) = open?;
+    let closer = match opener {
+        '(' => ')',
+        '[' => ']',
+        _ => '}',
+    };
+
+    let mut depth = 0;
+
+    for (index, c) in rope.chars_at(range.end).take(SCAN_LIMIT).enumerate() {
+        match c {
+            '(' | '[' | '{' => depth += 1,
+            c if c == closer && depth == 0 => return Some((open, range.end + index)),
+            ')' | ']' | '}' => depth -= 1,
+            _ => {}
+        }
+    }
+
+    None
+}
@@ -0,0 +1,115 @@
+use crate::encoding::Decoded;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+// 定义退出时保存、启动时恢复的会话状态。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Session {
+    pub files: Vec<File>,        // 打开的文件。
+    pub active: Option<PathBuf>, // 当前激活的文件。
+    #[serde(default)]
+    pub clipboard: Vec<String>, // 剪贴板历史，最近的在前。
+    #[serde(default)]
+    pub bookmarks: BTreeMap<PathBuf, Vec<usize>>, // 各个文件中添加了书签的行，包括已经关闭的文件。
+    #[serde(default)]
+    pub scratch: Vec<Scratch>, // 临时缓冲区及其内容。
+}
+
+// 定义会话中打开的文件。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct File {
+    pub path: PathBuf,          // 文件路径。
+    pub cursor: (usize, usize), // 光标在完整文本中的行和按字符计的列。
+    #[serde(default)]
+    pub scroll: usize, // 第一个可见的行在完整文本中的行号。
+}
+
+// 定义会话中的临时缓冲区，内容直接保存在会话中。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Scratch {
+    pub number: usize,          // 缓冲区的编号，显示为 untitled-编号。
+    pub text: String,           // 缓冲区的内容。
+    pub cursor: (usize, usize), // 光标在完整文本中的行和按字符计的列。
+    #[serde(default)]
+    pub scroll: usize, // 第一个可见的行在完整文本中的行号。
+}
+
+// 定义恢复完成的会话，包含已经重新加载的文件内容。
+#[derive(Debug, Clone, Default)]
+pub struct Restored {
+    pub files: Vec<(File, Decoded)>, // 成功加载的文件及其内容。
+    pub active: Option<PathBuf>,     // 当前激活的文件。
+    pub clipboard: Vec<String>,      // 剪贴板历史，最近的在前。
+    pub bookmarks: BTreeMap<PathBuf, Vec<usize>>, // 各个文件中添加了书签的行。
+    pub scratch: Vec<Scratch>,       // 临时缓冲区及其内容。
+}
+
+// 返回会话文件的路径，会话保存在系统的数据目录中。
+fn path() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("iced-editor").join("session.toml"))
+}
+
+// 定义一个异步函数来恢复上一次的会话，已经不存在或无法读取的文件会被跳过。
+pub async fn restore() -> Restored {
+    let Some(path) = path() else {
+        return Restored::default();
+    };
+
+    let session: Session = match tokio::fs::read_to_string(&path).await {
+        Ok(contents) => toml::from_str(&contents).unwrap_or_default(),
+        Err(_) => return Restored::default(),
+    };
+
+    let mut files = Vec::new();
+
+    for file in session.files {
+        if let Ok((_, decoded)) = crate::load_file(file.path.clone()).await {
+            files.push((file, decoded));
+        }
+    }
+
+    Restored {
+        files,
+        active: session.active,
+        clipboard: session.clipboard,
+        bookmarks: session.bookmarks,
+        scratch: session.scratch,
+    }
+}
+
+// 保存会话。退出时调用，因此直接同步写入文件。
+pub fn save(session: &Session) -> std::io::Result<()> {
+    let Some(path) = path() else {
+        return Ok(());
+    };
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let contents = toml::to_string(session)
+        .map_err(|error| std::io::Error::new(std::io::ErrorKind::InvalidData, error))?;
+
+    std::fs::write(path, contents)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_sessions_without_scroll_positions() {
+        let session: Session = toml::from_str(
+            r#"
+            [[files]]
+            path = "notes.md"
+            cursor = [3, 2]
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(session.files[0].cursor, (3, 2));
+        assert_eq!(session.files[0].scroll, 0);
+    }
+}
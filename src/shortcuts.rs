@@ -0,0 +1,211 @@
+use crate::keymap::{Key, Keymap};
+use crate::Message;
+use iced::advanced::layout::{self, Layout};
+use iced::advanced::renderer;
+use iced::advanced::widget::{Operation, Tree, Widget};
+use iced::advanced::{overlay, Clipboard, Shell};
+use iced::event::{self, Event};
+use iced::{keyboard, mouse};
+use iced::{Element, Length, Rectangle, Renderer, Theme};
+
+// 在内部的控件之前处理快捷键。
+//
+// 获得焦点的文本编辑器会先处理 Ctrl+A、Ctrl+X 等按键，处理过的按键不会再交给
+// keyboard::on_key_press 的订阅，因此 Emacs 风格的快捷键不会生效。这里在按键到达编辑器之前
+// 查找快捷键映射，拦截需要由快捷键处理的组合。keymap 为 None 时不拦截任何按键。
+pub fn shortcuts<'a>(
+    content: impl Into<Element<'a, Message>>,
+    keymap: Option<&'a Keymap>,
+    prefix: Option<Key>,
+) -> Element<'a, Message> {
+    Element::new(Shortcuts {
+        content: content.into(),
+        keymap,
+        prefix,
+    })
+}
+
+// 拦截快捷键的控件，其余的行为都交给内部的内容处理。
+struct Shortcuts<'a> {
+    content: Element<'a, Message>, // 窗口的内容。
+    keymap: Option<&'a Keymap>,    // 快捷键映射。
+    prefix: Option<Key>,           // 已经按下的快捷键前缀。
+}
+
+impl Widget<Message, Renderer> for Shortcuts<'_> {
+    fn children(&self) -> Vec<Tree> {
+        vec![Tree::new(&self.content)]
+    }
+
+    fn diff(&self, tree: &mut Tree) {
+        tree.diff_children(std::slice::from_ref(&self.content));
+    }
+
+    fn width(&self) -> Length {
+        self.content.as_widget().width()
+    }
+
+    fn height(&self) -> Length {
+        self.content.as_widget().height()
+    }
+
+    fn layout(
+        &self,
+        tree: &mut Tree,
+        renderer: &Renderer,
+        limits: &layout::Limits,
+    ) -> layout::Node {
+        self.content
+            .as_widget()
+            .layout(&mut tree.children[0], renderer, limits)
+    }
+
+    fn operate(
+        &self,
+        tree: &mut Tree,
+        layout: Layout<'_>,
+        renderer: &Renderer,
+        operation: &mut dyn Operation<Message>,
+    ) {
+        self.content
+            .as_widget()
+            .operate(&mut tree.children[0], layout, renderer, operation);
+    }
+
+    fn on_event(
+        &mut self,
+        tree: &mut Tree,
+        event: Event,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        renderer: &Renderer,
+        clipboard: &mut dyn Clipboard,
+        shell: &mut Shell<'_, Message>,
+        viewport: &Rectangle,
+    ) -> event::Status {
+        if let Some(message) = self
+            .keymap
+            .and_then(|keymap| intercept(keymap, self.prefix, &event))
+        {
+            shell.publish(message);
+            return event::Status::Captured;
+        }
+
+        self.content.as_widget_mut().on_event(
+            &mut tree.children[0],
+            event,
+            layout,
+            cursor,
+            renderer,
+            clipboard,
+            shell,
+            viewport,
+        )
+    }
+
+    fn mouse_interaction(
+        &self,
+        tree: &Tree,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        viewport: &Rectangle,
+        renderer: &Renderer,
+    ) -> mouse::Interaction {
+        self.content.as_widget().mouse_interaction(
+            &tree.children[0],
+            layout,
+            cursor,
+            viewport,
+            renderer,
+        )
+    }
+
+    fn draw(
+        &self,
+        tree: &Tree,
+        renderer: &mut Renderer,
+        theme: &Theme,
+        style: &renderer::Style,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        viewport: &Rectangle,
+    ) {
+        self.content.as_widget().draw(
+            &tree.children[0],
+            renderer,
+            theme,
+            style,
+            layout,
+            cursor,
+            viewport,
+        );
+    }
+
+    fn overlay<'b>(
+        &'b mut self,
+        tree: &'b mut Tree,
+        layout: Layout<'_>,
+        renderer: &Renderer,
+    ) -> Option<overlay::Element<'b, Message, Renderer>> {
+        self.content
+            .as_widget_mut()
+            .overlay(&mut tree.children[0], layout, renderer)
+    }
+}
+
+// 返回需要拦截的按键对应的消息，交给 update 中与其他按键相同的快捷键处理。
+fn intercept(keymap: &Keymap, prefix: Option<Key>, event: &Event) -> Option<Message> {
+    let Event::Keyboard(keyboard::Event::KeyPressed {
+        key_code,
+        modifiers,
+    }) = *event
+    else {
+        return None;
+    };
+
+    keymap
+        .intercepts(prefix, Key::new(key_code, modifiers))
+        .then_some(Message::KeyPressed(key_code, modifiers))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keymap::{Action, Preset};
+    use iced::keyboard::{KeyCode, Modifiers};
+
+    fn press(key_code: KeyCode) -> Event {
+        Event::Keyboard(keyboard::Event::KeyPressed {
+            key_code,
+            modifiers: Modifiers::CTRL,
+        })
+    }
+
+    #[test]
+    fn intercepts_emacs_chords_before_the_editor() {
+        let keymap = Preset::Emacs.keymap();
+        let ctrl = |code| Key::new(code, Modifiers::CTRL);
+
+        for code in [KeyCode::A, KeyCode::E, KeyCode::K, KeyCode::Y, KeyCode::X] {
+            assert!(matches!(
+                intercept(&keymap, None, &press(code)),
+                Some(Message::KeyPressed(pressed, modifiers))
+                    if pressed == code && modifiers == Modifiers::CTRL
+            ));
+        }
+
+        // 按下 Ctrl+X 后，第二个组合同样在编辑器之前处理。
+        let prefix = Some(ctrl(KeyCode::X));
+        assert!(intercept(&keymap, prefix, &press(KeyCode::S)).is_some());
+        assert_eq!(keymap.resolve(prefix, ctrl(KeyCode::S)), Some(Action::Save));
+    }
+
+    #[test]
+    fn leaves_unbound_keys_to_the_editor() {
+        let keymap = Keymap::default();
+
+        assert!(intercept(&keymap, None, &press(KeyCode::A)).is_none());
+        assert!(intercept(&keymap, None, &press(KeyCode::X)).is_none());
+        assert!(intercept(&keymap, None, &press(KeyCode::C)).is_none());
+    }
+}
@@ -0,0 +1,326 @@
+use crate::tab::Tab;
+use crate::{Error, FileError, Operation};
+use iced::widget::text_editor::{Action, Edit, Motion};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+// 适用于所有语言的代码片段使用的名称。
+const ANY_LANGUAGE: &str = "*";
+
+// 定义用户的代码片段，按文件扩展名分组，每组中将触发词映射到模板。
+//
+// 配置文件 snippets.toml 的格式如下，模板中 $1、${2:默认值} 表示占位符，$0 表示最后的光标位置：
+//
+// [rs]
+// fn = "fn ${1:name}(${2}) {\n    $0\n}"
+#[derive(Debug, Clone)]
+pub struct Snippets {
+    languages: BTreeMap<String, BTreeMap<String, String>>, // 扩展名到代码片段的映射。
+}
+
+impl Default for Snippets {
+    fn default() -> Self {
+        let defaults: &[(&str, &str, &str)] = &[
+            ("rs", "fn", "fn ${1:name}(${2}) {\n    $0\n}"),
+            ("rs", "test", "#[test]\nfn ${1:name}() {\n    $0\n}"),
+            ("py", "def", "def ${1:name}(${2}):\n    ${0:pass}"),
+            (
+                "py",
+                "main",
+                "if __name__ == \"__main__\":\n    ${0:main()}",
+            ),
+            ("js", "fn", "function ${1:name}(${2}) {\n    $0\n}"),
+            ("js", "log", "console.log($0);"),
+        ];
+
+        let mut languages: BTreeMap<String, BTreeMap<String, String>> = BTreeMap::new();
+
+        for (extension, trigger, body) in defaults {
+            languages
+                .entry(extension.to_string())
+                .or_default()
+                .insert(trigger.to_string(), body.to_string());
+        }
+
+        Self { languages }
+    }
+}
+
+impl Snippets {
+    // 解析 TOML 配置文件，配置文件中的代码片段会替换同名的默认代码片段。
+    pub fn from_toml(contents: &str) -> Result<Self, Error> {
+        let languages: BTreeMap<String, BTreeMap<String, String>> = toml::from_str(contents)
+            .map_err(|error| Error::InvalidConfig(error.message().to_string()))?;
+
+        let mut snippets = Self::default();
+
+        for (extension, triggers) in languages {
+            snippets
+                .languages
+                .entry(extension)
+                .or_default()
+                .extend(triggers);
+        }
+
+        Ok(snippets)
+    }
+
+    // 查找扩展名对应的语言中的代码片段，找不到时查找适用于所有语言的代码片段。
+    fn find(&self, extension: &str, trigger: &str) -> Option<&str> {
+        [extension, ANY_LANGUAGE]
+            .into_iter()
+            .find_map(|language| self.languages.get(language)?.get(trigger))
+            .map(String::as_str)
+    }
+
+    // 如果光标前的单词是代码片段的触发词，就将它展开为模板，并选中第一个占位符。
+    pub fn expand(&self, tab: &mut Tab) -> Option<Expansion> {
+        let trigger = crate::completion::word_before_cursor(tab);
+
+        if trigger.is_empty() {
+            return None;
+        }
+
+        let template = parse(self.find(&tab.extension(), &trigger)?);
+
+        for _ in trigger.chars() {
+            tab.edit(Action::Edit(Edit::Backspace));
+        }
+
+        let (line, column) = tab.context.cursor_position();
+        let before = tab
+            .context
+            .line(line)
+            .map(|text| text.get(..column).unwrap_or_default().to_string())
+            .unwrap_or_default();
+
+        // 模板的后续行使用与当前行相同的缩进。
+        let indentation: String = before
+            .chars()
+            .take_while(|c| *c == ' ' || *c == '\t')
+            .collect();
+        let start = before.chars().count();
+
+        let text = template.text.replace('\n', &format!("\n{indentation}"));
+        tab.edit(Action::Edit(Edit::Paste(Arc::new(text))));
+
+        let stops = template
+            .stops
+            .iter()
+            .map(|(_, range)| {
+                let (lines, offset) = position(&template.text, range.start);
+
+                Stop {
+                    line: line + lines,
+                    column: if lines == 0 {
+                        start + offset
+                    } else {
+                        indentation.chars().count() + offset
+                    },
+                    length: range.len(),
+                }
+            })
+            .collect();
+
+        let mut expansion = Expansion {
+            tab: tab.id,
+            stops,
+            current: 0,
+        };
+        expansion.select(tab);
+
+        Some(expansion)
+    }
+}
+
+// 定义展开后的模板中的一个占位符，位置按字符计算。
+#[derive(Debug, Clone, Copy)]
+struct Stop {
+    line: usize,   // 所在的行。
+    column: usize, // 开始的列。
+    length: usize, // 默认值的长度。
+}
+
+// 定义正在进行的代码片段展开，按 Tab 依次跳到下一个占位符。
+#[derive(Debug)]
+pub struct Expansion {
+    pub tab: usize,   // 展开代码片段的标签页标识。
+    stops: Vec<Stop>, // 按访问顺序排列的占位符，最后一个是最终的光标位置。
+    current: usize,   // 当前所在的占位符。
+}
+
+impl Expansion {
+    // 选中当前的占位符的默认值。
+    fn select(&self, tab: &mut Tab) {
+        let stop = self.stops[self.current];
+
+        tab.move_cursor_to(stop.line, stop.column);
+        for _ in 0..stop.length {
+            tab.edit(Action::Select(Motion::Right));
+        }
+    }
+
+    // 跳到下一个占位符。
+    pub fn next(&mut self, tab: &mut Tab) {
+        self.current = (self.current + 1).min(self.stops.len() - 1);
+        self.select(tab);
+    }
+
+    // 判断是否已经到达最后的光标位置，此时展开结束。
+    pub fn is_finished(&self) -> bool {
+        self.current + 1 >= self.stops.len()
+    }
+
+    // 在当前的占位符中编辑后更新其他占位符的位置，返回展开是否仍然有效。
+    //
+    // length 和 line_count 分别为编辑前当前占位符所在行的字符数和文档的行数。
+    // 编辑增加或删除了行时，同一行中后面的占位符无法再准确定位，此时结束展开。
+    pub fn edited(&mut self, tab: &Tab, length: usize, line_count: usize) -> bool {
+        let current = self.stops[self.current];
+        let line_delta = tab.context.line_count() as isize - line_count as isize;
+
+        if line_delta != 0 {
+            let same_line = self.stops[self.current + 1..]
+                .iter()
+                .any(|stop| stop.line == current.line);
+
+            if same_line {
+                return false;
+            }
+
+            for stop in &mut self.stops[self.current + 1..] {
+                if stop.line > current.line {
+                    stop.line = stop.line.saturating_add_signed(line_delta);
+                }
+            }
+
+            return true;
+        }
+
+        let delta = line_length(tab, current.line) as isize - length as isize;
+
+        for stop in &mut self.stops[self.current + 1..] {
+            if stop.line == current.line && stop.column > current.column {
+                stop.column = stop.column.saturating_add_signed(delta);
+            }
+        }
+
+        true
+    }
+
+    // 返回当前的占位符所在行，用于在编辑前记录这一行的长度。
+    pub fn line(&self) -> usize {
+        self.stops[self.current].line
+    }
+}
+
+// 返回文档中指定行的字符数。
+pub fn line_length(tab: &Tab, line: usize) -> usize {
+    tab.context
+        .line(line)
+        .map_or(0, |text| text.chars().count())
+}
+
+// 定义解析后的模板。
+#[derive(Debug)]
+struct Template {
+    text: String,                                // 去掉占位符标记之后的文本。
+    stops: Vec<(usize, std::ops::Range<usize>)>, // 占位符的编号和默认值在文本中的字符范围。
+}
+
+// 解析模板中的占位符：$1、${1} 和 ${1:默认值}，\$ 表示美元符号本身。
+//
+// 占位符按编号排列，$0 排在最后；没有 $0 时在模板末尾添加一个。
+fn parse(body: &str) -> Template {
+    let mut text = String::new();
+    let mut length = 0;
+    let mut stops = Vec::new();
+    let mut chars = body.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek() == Some(&'$') => {
+                chars.next();
+                text.push('$');
+                length += 1;
+            }
+            '$' if chars.peek().is_some_and(char::is_ascii_digit) => {
+                let mut number = String::new();
+                while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+                    number.push(digit);
+                }
+
+                stops.push((number.parse().unwrap_or(0), length..length));
+            }
+            '$' if chars.peek() == Some(&'{') => {
+                chars.next();
+
+                let mut number = String::new();
+                while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+                    number.push(digit);
+                }
+
+                let start = length;
+
+                if chars.next_if_eq(&':').is_some() {
+                    for c in chars.by_ref().take_while(|c| *c != '}') {
+                        text.push(c);
+                        length += 1;
+                    }
+                } else {
+                    chars.next_if_eq(&'}');
+                }
+
+                stops.push((number.parse().unwrap_or(0), start..length));
+            }
+            _ => {
+                text.push(c);
+                length += 1;
+            }
+        }
+    }
+
+    if !stops.iter().any(|(index, _)| *index == 0) {
+        stops.push((0, length..length));
+    }
+
+    // 编号相同的占位符保持在模板中出现的顺序。
+    stops.sort_by_key(|(index, _)| if *index == 0 { usize::MAX } else { *index });
+
+    Template { text, stops }
+}
+
+// 返回文本中字符位置之前的换行数，以及该位置在所在行中的字符位置。
+fn position(text: &str, offset: usize) -> (usize, usize) {
+    let mut lines = 0;
+    let mut column = 0;
+
+    for c in text.chars().take(offset) {
+        if c == '\n' {
+            lines += 1;
+            column = 0;
+        } else {
+            column += 1;
+        }
+    }
+
+    (lines, column)
+}
+
+// 定义一个异步函数来加载代码片段，配置文件不存在时使用默认的代码片段。
+pub async fn load(path: Option<PathBuf>) -> Result<Snippets, Error> {
+    let Some(path) = path else {
+        return Ok(Snippets::default());
+    };
+
+    match tokio::fs::read_to_string(&path).await {
+        Ok(contents) => Snippets::from_toml(&contents),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(Snippets::default()),
+        Err(error) => Err(Error::FileFailed(FileError::new(
+            Operation::Read,
+            &path,
+            &error,
+        ))),
+    }
+}
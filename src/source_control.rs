@@ -0,0 +1,126 @@
+use crate::git::FileStatus;
+use crate::i18n::{tr, trf};
+use crate::Message;
+use iced::widget::{
+    button, column, container, horizontal_space, row, scrollable, text, text_input,
+};
+use iced::{theme, Element, Length};
+use std::path::{Path, PathBuf};
+
+// 定义源代码管理面板的状态。
+#[derive(Debug, Default)]
+pub struct SourceControl {
+    pub root: Option<PathBuf>,  // 仓库的根目录，尚未加载或不在仓库中时为 None。
+    pub files: Vec<FileStatus>, // 仓库中修改过的文件。
+    pub message: String,        // 输入的提交信息。
+    pub is_busy: bool,          // 是否正在执行 git 命令。
+}
+
+impl SourceControl {
+    // 返回提交信息输入框的标识，用于在打开面板时获取焦点。
+    pub fn id() -> text_input::Id {
+        text_input::Id::new("commit-message")
+    }
+
+    // 判断仓库中是否有已经暂存的修改。
+    fn has_staged(&self) -> bool {
+        self.files
+            .iter()
+            .any(|file| !matches!(file.staged, ' ' | '?'))
+    }
+
+    // 创建源代码管理面板的视图，current 为当前标签页的文件路径。
+    pub fn view(&self, current: Option<&Path>) -> Element<'_, Message> {
+        let header = row![
+            text(tr("Source Control")).size(18),
+            horizontal_space(Length::Fill),
+            button(text("↻"))
+                .on_press_maybe((!self.is_busy).then_some(Message::RefreshSourceControl))
+                .style(theme::Button::Text),
+            button(text("×"))
+                .on_press(Message::ToggleSourceControl)
+                .style(theme::Button::Text)
+        ];
+
+        let Some(root) = self.root.as_deref() else {
+            let status = if self.is_busy {
+                tr("Loading...")
+            } else {
+                tr("Not in a git repository")
+            };
+
+            return panel(column![header, text(status).size(14)].spacing(10));
+        };
+
+        let name = root
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or_default();
+
+        let files = self.files.iter().map(|file| {
+            let relative = file.path.strip_prefix(root).unwrap_or(&file.path);
+
+            button(
+                row![
+                    text(format!("{}{}", file.staged, file.unstaged)).size(13),
+                    text(relative.display()).size(13)
+                ]
+                .spacing(10),
+            )
+            .on_press(Message::FileClicked(file.path.clone()))
+            .padding([2, 5])
+            .style(theme::Button::Text)
+            .into()
+        });
+
+        let summary = if self.files.is_empty() {
+            String::from(tr("No changes"))
+        } else {
+            trf("{count} changed files", &[("count", &self.files.len())])
+        };
+
+        // 只有当前文件有修改时才能暂存，只有存在暂存的修改且填写了提交信息时才能提交。
+        let can_stage = !self.is_busy
+            && current.is_some_and(|current| self.files.iter().any(|file| file.path == current));
+        let can_commit = !self.is_busy && self.has_staged() && !self.message.trim().is_empty();
+
+        let input = text_input(tr("Commit message"), &self.message)
+            .id(Self::id())
+            .on_input(Message::CommitMessageChanged)
+            .on_submit(Message::Commit)
+            .padding(5);
+
+        let actions = row![
+            button(text(tr("Stage Current File")).size(14))
+                .on_press_maybe(can_stage.then_some(Message::StageFile))
+                .padding([2, 5]),
+            button(text(tr("Commit")).size(14))
+                .on_press_maybe(can_commit.then_some(Message::Commit))
+                .padding([2, 5])
+                .style(theme::Button::Primary)
+        ]
+        .spacing(5);
+
+        panel(
+            column![
+                header,
+                text(name).size(14),
+                input,
+                actions,
+                text(summary).size(14),
+                scrollable(column(files.collect()).spacing(2))
+            ]
+            .spacing(10),
+        )
+    }
+}
+
+// 将面板的内容放入固定宽度的侧边容器中。
+fn panel<'a>(content: impl Into<Element<'a, Message>>) -> Element<'a, Message> {
+    container(content)
+        .width(300)
+        .height(Length::Fill)
+        .padding(5)
+        .style(theme::Container::Box)
+        .into()
+}
@@ -0,0 +1,362 @@
+use crate::comment;
+use crate::{Error, FileError, Operation};
+use ropey::Rope;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::ops::Range;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+// 右键菜单中最多显示的拼写建议数量。
+const MAX_SUGGESTIONS: usize = 8;
+
+// 定义加载的 hunspell 词典，可以在消息之间共享。
+#[derive(Clone)]
+pub struct Dictionary(Arc<spellbook::Dictionary>);
+
+impl fmt::Debug for Dictionary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Dictionary")
+    }
+}
+
+// 定义拼写检查的状态。
+#[derive(Debug, Default)]
+pub struct Spelling {
+    pub dictionary: Option<Dictionary>, // 加载的词典，找不到词典时为 None。
+    pub available: Vec<String>,         // 系统和配置目录中可用的词典名称。
+    pub words: BTreeSet<String>,        // 用户添加到个人词典中的单词。
+}
+
+// 定义文档中一个拼写错误的单词。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Misspelling {
+    pub line: usize,           // 所在的行，从 0 开始。
+    pub columns: Range<usize>, // 在行中的字符范围。
+    pub word: String,          // 拼写错误的单词。
+}
+
+// 定义文件中需要检查拼写的部分。
+#[derive(Debug, Clone, Copy)]
+enum Scope {
+    Prose { is_markdown: bool }, // 整个文件都是文字，Markdown 中的代码除外。
+    Comments(comment::Syntax),   // 只检查代码中的注释。
+}
+
+impl Scope {
+    // 根据文件扩展名返回需要检查的部分，不支持的文件不检查拼写。
+    fn for_extension(extension: &str) -> Option<Self> {
+        match extension {
+            "md" | "markdown" => Some(Self::Prose { is_markdown: true }),
+            "txt" => Some(Self::Prose { is_markdown: false }),
+            _ => comment::Syntax::for_extension(extension).map(Self::Comments),
+        }
+    }
+}
+
+impl Spelling {
+    // 判断单词的拼写是否正确，个人词典中的单词总是正确的。
+    pub fn is_correct(&self, word: &str) -> bool {
+        self.words.contains(word)
+            || self
+                .dictionary
+                .as_ref()
+                .map_or(true, |dictionary| dictionary.0.check(word))
+    }
+
+    // 返回指定范围内的各行中拼写错误的单词。
+    //
+    // 只检查编辑器中可见的行，块注释和 Markdown 代码块的状态从文档开头开始跟踪。
+    pub fn misspellings(
+        &self,
+        rope: &Rope,
+        extension: &str,
+        lines: Range<usize>,
+    ) -> Vec<Misspelling> {
+        let Some(scope) = Scope::for_extension(extension) else {
+            return Vec::new();
+        };
+
+        if self.dictionary.is_none() {
+            return Vec::new();
+        }
+
+        let mut misspellings = Vec::new();
+        let mut in_block = false;
+
+        for number in 0..lines.end.min(rope.len_lines()) {
+            let line = rope.line(number);
+
+            // 可见区域之前的行只需要更新代码块或块注释的状态。
+            if number < lines.start {
+                match scope {
+                    Scope::Prose { is_markdown: true } => in_block ^= is_fence(line.chars()),
+                    Scope::Comments(comment::Syntax::Block(open, close)) => {
+                        comments(&line.to_string(), open, close, &mut in_block);
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+
+            let text = line.to_string();
+            let text = text.trim_end_matches(['\n', '\r']);
+
+            let segments = match scope {
+                Scope::Prose { is_markdown: false } => vec![0..text.chars().count()],
+                Scope::Prose { is_markdown: true } => {
+                    if is_fence(text.chars()) {
+                        in_block = !in_block;
+                        continue;
+                    }
+
+                    if in_block {
+                        continue;
+                    }
+
+                    outside_code_spans(text)
+                }
+                Scope::Comments(comment::Syntax::Line(token)) => text
+                    .find(token)
+                    .map(|start| {
+                        let start = text[..start + token.len()].chars().count();
+                        vec![start..text.chars().count()]
+                    })
+                    .unwrap_or_default(),
+                Scope::Comments(comment::Syntax::Block(open, close)) => {
+                    comments(text, open, close, &mut in_block)
+                }
+            };
+
+            let chars: Vec<char> = text.chars().collect();
+
+            for segment in segments {
+                for columns in words(&chars, segment) {
+                    let word: String = chars[columns.clone()].iter().collect();
+
+                    if !self.is_correct(&word) {
+                        misspellings.push(Misspelling {
+                            line: number,
+                            columns,
+                            word,
+                        });
+                    }
+                }
+            }
+        }
+
+        misspellings
+    }
+
+    // 返回拼写错误的单词的建议拼写。
+    pub fn suggestions(&self, word: &str) -> Vec<String> {
+        let mut suggestions = Vec::new();
+
+        if let Some(dictionary) = &self.dictionary {
+            dictionary.0.suggest(word, &mut suggestions);
+        }
+
+        suggestions.truncate(MAX_SUGGESTIONS);
+        suggestions
+    }
+}
+
+// 返回查找 hunspell 词典的目录：配置目录中的 dictionaries，以及系统中常见的安装位置。
+fn directories() -> Vec<PathBuf> {
+    let mut directories: Vec<PathBuf> = crate::config_file("dictionaries").into_iter().collect();
+
+    directories.extend(
+        [
+            "/usr/share/hunspell",
+            "/usr/share/myspell",
+            "/usr/share/myspell/dicts",
+            "/usr/local/share/hunspell",
+            "/Library/Spelling",
+        ]
+        .map(PathBuf::from),
+    );
+
+    if let Some(home) = dirs::home_dir() {
+        directories.push(home.join("Library/Spelling"));
+    }
+
+    directories
+}
+
+// 定义一个异步函数，列出所有同时具有 .aff 和 .dic 文件的词典名称，例如 en_US。
+pub async fn available() -> Vec<String> {
+    let mut names = BTreeSet::new();
+
+    for directory in directories() {
+        let Ok(mut entries) = tokio::fs::read_dir(&directory).await else {
+            continue;
+        };
+
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            let path = entry.path();
+
+            if path.extension().is_some_and(|extension| extension == "dic")
+                && path.with_extension("aff").exists()
+            {
+                if let Some(name) = path.file_stem().and_then(|name| name.to_str()) {
+                    names.insert(name.to_string());
+                }
+            }
+        }
+    }
+
+    names.into_iter().collect()
+}
+
+// 定义一个异步函数，加载指定语言的词典，找不到时返回 None。
+pub async fn load(language: String) -> Result<Option<Dictionary>, Error> {
+    for directory in directories() {
+        let aff = directory.join(format!("{language}.aff"));
+        let dic = directory.join(format!("{language}.dic"));
+
+        let (Ok(aff), Ok(dic)) = (
+            tokio::fs::read_to_string(&aff).await,
+            tokio::fs::read_to_string(&dic).await,
+        ) else {
+            continue;
+        };
+
+        // 解析大型词典需要一些时间，放到阻塞线程中执行。
+        let dictionary =
+            tokio::task::spawn_blocking(move || spellbook::Dictionary::new(&aff, &dic))
+                .await
+                .map_err(|error| Error::InvalidConfig(error.to_string()))?
+                .map_err(|error| Error::InvalidConfig(format!("{language}: {error}")))?;
+
+        return Ok(Some(Dictionary(Arc::new(dictionary))));
+    }
+
+    Ok(None)
+}
+
+// 定义一个异步函数来加载个人词典，每行一个单词，文件不存在时为空。
+pub async fn load_words(path: Option<PathBuf>) -> Result<BTreeSet<String>, Error> {
+    let Some(path) = path else {
+        return Ok(BTreeSet::new());
+    };
+
+    match tokio::fs::read_to_string(&path).await {
+        Ok(contents) => Ok(contents
+            .lines()
+            .map(str::trim)
+            .filter(|word| !word.is_empty())
+            .map(str::to_string)
+            .collect()),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(BTreeSet::new()),
+        Err(error) => Err(Error::FileFailed(FileError::new(
+            Operation::Read,
+            &path,
+            &error,
+        ))),
+    }
+}
+
+// 判断一行是否是 Markdown 代码块的开始或结束。
+fn is_fence(chars: impl Iterator<Item = char>) -> bool {
+    let start: String = chars.skip_while(|c| *c == ' ').take(3).collect();
+
+    start == "```" || start == "~~~"
+}
+
+// 返回 Markdown 的一行中不在 `行内代码` 中的字符范围。
+fn outside_code_spans(line: &str) -> Vec<Range<usize>> {
+    let mut segments = Vec::new();
+    let mut start = Some(0);
+
+    for (column, c) in line.chars().enumerate() {
+        if c == '`' {
+            match start.take() {
+                Some(start) => segments.push(start..column),
+                None => start = Some(column + 1),
+            }
+        }
+    }
+
+    if let Some(start) = start {
+        segments.push(start..line.chars().count());
+    }
+
+    segments
+}
+
+// 返回一行中位于块注释内的字符范围，in_block 表示这一行是否从块注释中开始，并更新为下一行的状态。
+fn comments(line: &str, open: &str, close: &str, in_block: &mut bool) -> Vec<Range<usize>> {
+    let mut segments = Vec::new();
+    let mut rest = 0;
+
+    loop {
+        let column = |index: usize| line[..index].chars().count();
+
+        if *in_block {
+            match line[rest..].find(close) {
+                Some(end) => {
+                    segments.push(column(rest)..column(rest + end));
+                    rest += end + close.len();
+                    *in_block = false;
+                }
+                None => {
+                    segments.push(column(rest)..column(line.len()));
+                    return segments;
+                }
+            }
+        } else {
+            match line[rest..].find(open) {
+                Some(start) => {
+                    rest += start + open.len();
+                    *in_block = true;
+                }
+                None => return segments,
+            }
+        }
+    }
+}
+
+// 返回字符范围内需要检查的单词。
+//
+// 含有数字或下划线的是标识符，首字母之后含有大写字母的是驼峰命名或缩写，都不检查。
+fn words(chars: &[char], segment: Range<usize>) -> Vec<Range<usize>> {
+    let is_token_char = |c: char| c.is_alphanumeric() || c == '_' || c == '\'';
+
+    let mut words = Vec::new();
+    let mut index = segment.start;
+
+    while index < segment.end {
+        if !is_token_char(chars[index]) {
+            index += 1;
+            continue;
+        }
+
+        let start = index;
+
+        while index < segment.end && is_token_char(chars[index]) {
+            index += 1;
+        }
+
+        // 去掉两端的撇号，保留 don't 中间的撇号。
+        let mut range = start..index;
+
+        while range.start < range.end && chars[range.start] == '\'' {
+            range.start += 1;
+        }
+
+        while range.end > range.start && chars[range.end - 1] == '\'' {
+            range.end -= 1;
+        }
+
+        let word = &chars[range.clone()];
+
+        let is_plain = word.iter().all(|c| c.is_alphabetic() || *c == '\'')
+            && !word.iter().skip(1).any(|c| c.is_uppercase());
+
+        if word.len() > 1 && is_plain {
+            words.push(range);
+        }
+    }
+
+    words
+}
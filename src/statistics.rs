@@ -0,0 +1,71 @@
+use crate::document::Document;
+
+// 定义文档的字数统计，按行缓存以便编辑时只重新统计受影响的行。
+//
+// 被折叠的行不在编辑器的内容中，单独统计；折叠或展开区域时需要调用 new 重新统计。
+#[derive(Debug)]
+pub struct Statistics {
+    lines: Vec<(usize, usize)>, // 内容中每一行的单词数和字符数。
+    words: usize,               // 文档的单词总数，包括被折叠的行。
+    characters: usize,          // 文档的字符总数，包括被折叠的行，不包括换行符。
+}
+
+impl Statistics {
+    // 完整统计文档内容。
+    pub fn new(document: &Document) -> Self {
+        let lines: Vec<_> = document.lines().map(|line| count(&line)).collect();
+        let hidden: Vec<_> = document
+            .folds()
+            .filter_map(|(line, _)| document.hidden(line))
+            .flat_map(|hidden| hidden.split('\n'))
+            .map(count)
+            .collect();
+
+        Self {
+            words: lines.iter().chain(&hidden).map(|(words, _)| words).sum(),
+            characters: lines
+                .iter()
+                .chain(&hidden)
+                .map(|(_, characters)| characters)
+                .sum(),
+            lines,
+        }
+    }
+
+    // 重新统计指定的一行，行数发生变化时需要调用 new 重新统计。
+    pub fn update_line(&mut self, document: &Document, index: usize) {
+        let (Some(cached), Some(line)) = (self.lines.get_mut(index), document.line(index)) else {
+            return;
+        };
+
+        let (words, characters) = count(&line);
+
+        self.words = self.words - cached.0 + words;
+        self.characters = self.characters - cached.1 + characters;
+        *cached = (words, characters);
+    }
+
+    // 返回单词总数。
+    pub fn words(&self) -> usize {
+        self.words
+    }
+
+    // 返回字符总数。
+    pub fn characters(&self) -> usize {
+        self.characters
+    }
+
+    // 返回最长一行的字符数。
+    pub fn longest_line(&self) -> usize {
+        self.lines
+            .iter()
+            .map(|(_, characters)| *characters)
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+// 统计一行文本中的单词数和字符数。
+fn count(line: &str) -> (usize, usize) {
+    (line.split_whitespace().count(), line.chars().count())
+}
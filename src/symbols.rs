@@ -0,0 +1,551 @@
+use crate::i18n::tr;
+use crate::Message;
+use iced::widget::{button, column, container, horizontal_space, row, scrollable, text, Space};
+use iced::{theme, Element, Length};
+use ropey::Rope;
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+
+// 定义符号的种类。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Function,    // 函数或方法。
+    Struct,      // 结构体或类型定义。
+    Enum,        // 枚举。
+    Trait,       // trait 或接口。
+    Impl,        // impl 块。
+    Module,      // 模块。
+    Class,       // 类。
+    Heading(u8), // Markdown 标题及其级别。
+}
+
+// 定义文档中的一个符号。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Symbol {
+    pub kind: Kind,   // 符号的种类。
+    pub name: String, // 符号的名称，impl 块为实现的类型。
+    pub line: usize,  // 符号开始的行，从 0 开始。
+    pub end: usize,   // 符号结束的行（包含），用于判断光标是否在符号内。
+    pub depth: usize, // 嵌套的层数，最外层为 0。
+}
+
+// 定义按文档版本缓存的符号，避免每次绘制界面时都重新解析。
+#[derive(Default)]
+pub struct Cache {
+    entry: RefCell<Option<(Key, Rc<[Symbol]>)>>, // 缓存的符号及其对应的文档状态。
+}
+
+// 定义决定符号列表的文档状态，任何一项变化时都需要重新解析。
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Key {
+    revision: usize,   // 文档内容的版本号。
+    length: usize,     // 文档的字符数，重新加载文件后版本号可能相同。
+    extension: String, // 文件扩展名，另存为后语言可能变化。
+}
+
+// 定义解析符号的语法规则。
+#[derive(Debug, Clone, Copy)]
+enum Language {
+    Rust,     // 使用大括号划分代码块的 Rust。
+    Braces,   // 使用大括号划分代码块的 JavaScript、TypeScript、Go 等语言。
+    Python,   // 使用缩进划分代码块的 Python。
+    Markdown, // 以标题划分章节的 Markdown。
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Function => f.write_str("fn"),
+            Self::Struct => f.write_str("struct"),
+            Self::Enum => f.write_str("enum"),
+            Self::Trait => f.write_str("trait"),
+            Self::Impl => f.write_str("impl"),
+            Self::Module => f.write_str("mod"),
+            Self::Class => f.write_str("class"),
+            Self::Heading(level) => f.write_str(&"#".repeat(*level as usize)),
+        }
+    }
+}
+
+impl Language {
+    // 根据文件扩展名返回语法规则，不支持的语言没有符号。
+    fn for_extension(extension: &str) -> Option<Self> {
+        match extension {
+            "rs" => Some(Self::Rust),
+            "js" | "mjs" | "jsx" | "ts" | "tsx" | "go" | "java" | "kt" | "swift" => {
+                Some(Self::Braces)
+            }
+            "py" | "pyw" => Some(Self::Python),
+            "md" | "markdown" => Some(Self::Markdown),
+            _ => None,
+        }
+    }
+}
+
+impl Cache {
+    // 返回文档中的符号，文档没有变化时直接使用缓存的结果。
+    pub fn get(&self, rope: &Rope, revision: usize, extension: &str) -> Rc<[Symbol]> {
+        let key = Key {
+            revision,
+            length: rope.len_chars(),
+            extension: extension.to_string(),
+        };
+
+        let mut entry = self.entry.borrow_mut();
+
+        match entry.as_ref() {
+            Some((cached, symbols)) if *cached == key => symbols.clone(),
+            _ => {
+                let symbols: Rc<[Symbol]> = parse(rope, extension).into();
+                *entry = Some((key, symbols.clone()));
+                symbols
+            }
+        }
+    }
+}
+
+// 使用每种语言的简单规则解析文档中的符号，按出现的顺序返回。
+//
+// 这里不做完整的语法分析：字符串和块注释中的大括号可能导致符号的结束行不准确，
+// 但符号本身的位置总是正确的。
+pub fn parse(rope: &Rope, extension: &str) -> Vec<Symbol> {
+    let Some(language) = Language::for_extension(extension) else {
+        return Vec::new();
+    };
+
+    let lines = rope.lines().map(|line| {
+        let line = line.to_string();
+        line.trim_end_matches(['\n', '\r']).to_string()
+    });
+
+    match language {
+        Language::Rust | Language::Braces => braces(lines, language),
+        Language::Python => indented(lines),
+        Language::Markdown => headings(lines),
+    }
+}
+
+// 返回包含指定行的各层符号，从最外层到最内层。
+pub fn enclosing(symbols: &[Symbol], line: usize) -> Vec<&Symbol> {
+    symbols
+        .iter()
+        .filter(|symbol| symbol.line <= line && line <= symbol.end)
+        .collect()
+}
+
+// 创建大纲面板的视图，按嵌套的层数缩进，点击符号时跳转到符号所在的行。
+//
+// 光标所在的最内层符号高亮显示。
+pub fn view<'a>(symbols: &[Symbol], line: usize) -> Element<'a, Message> {
+    let current = enclosing(symbols, line).last().map(|symbol| symbol.line);
+
+    let header = row![
+        text(tr("Outline")).size(18),
+        horizontal_space(Length::Fill),
+        button(text("×"))
+            .on_press(Message::ToggleOutline)
+            .style(theme::Button::Text)
+    ];
+
+    let rows: Vec<Element<_>> = if symbols.is_empty() {
+        vec![text(tr("No symbols")).size(14).into()]
+    } else {
+        symbols
+            .iter()
+            .map(|symbol| {
+                let style = if current == Some(symbol.line) {
+                    theme::Button::Primary
+                } else {
+                    theme::Button::Text
+                };
+
+                row![
+                    Space::with_width(symbol.depth as u16 * 12),
+                    button(text(format!("{} {}", symbol.kind, symbol.name)).size(14))
+                        .on_press(Message::SymbolClicked(symbol.line))
+                        .padding([2, 5])
+                        .style(style)
+                ]
+                .into()
+            })
+            .collect()
+    };
+
+    container(column![header, scrollable(column(rows))].spacing(10))
+        .width(220)
+        .height(Length::Fill)
+        .padding(5)
+        .style(theme::Container::Box)
+        .into()
+}
+
+// 解析使用大括号划分代码块的语言。
+//
+// 符号从声明所在的行开始，到声明之后第一个大括号对应的右括号结束；
+// 在遇到大括号之前以分号结束的声明（例如 struct Unit;）只占一行。
+fn braces(lines: impl Iterator<Item = String>, language: Language) -> Vec<Symbol> {
+    let mut symbols: Vec<Symbol> = Vec::new();
+    let mut open: Vec<(usize, usize)> = Vec::new(); // 尚未结束的符号及其代码块的大括号层数。
+    let mut pending = None; // 已经声明但还没有遇到大括号的符号。
+    let mut depth = 0;
+    let mut last = 0;
+
+    for (number, line) in lines.enumerate() {
+        last = number;
+
+        let declaration = match language {
+            Language::Rust => rust_declaration(&line),
+            _ => brace_declaration(&line),
+        };
+
+        if let Some((kind, name)) = declaration {
+            symbols.push(Symbol {
+                kind,
+                name,
+                line: number,
+                end: number,
+                depth: open.len(),
+            });
+            pending = Some(symbols.len() - 1);
+        }
+
+        for c in code(&line).chars() {
+            match c {
+                '{' => {
+                    depth += 1;
+
+                    if let Some(index) = pending.take() {
+                        open.push((index, depth));
+                    }
+                }
+                '}' => {
+                    if let Some(&(index, opened)) = open.last() {
+                        if opened == depth {
+                            symbols[index].end = number;
+                            open.pop();
+                        }
+                    }
+
+                    depth = depth.saturating_sub(1);
+                }
+                ';' => {
+                    if let Some(index) = pending.take() {
+                        symbols[index].end = number;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    // 文档末尾仍未结束的符号延伸到最后一行。
+    for (index, _) in open {
+        symbols[index].end = last;
+    }
+
+    symbols
+}
+
+// 解析使用缩进划分代码块的 Python：符号到下一个缩进不大于它的非空行之前结束。
+fn indented(lines: impl Iterator<Item = String>) -> Vec<Symbol> {
+    let mut symbols: Vec<Symbol> = Vec::new();
+    let mut open: Vec<(usize, usize)> = Vec::new(); // 尚未结束的符号及其缩进。
+    let mut last_code = 0;
+
+    for (number, line) in lines.enumerate() {
+        let trimmed = line.trim_start();
+
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+
+        let indent = line.len() - trimmed.len();
+
+        while let Some(&(index, opened)) = open.last() {
+            if indent > opened {
+                break;
+            }
+
+            symbols[index].end = last_code;
+            open.pop();
+        }
+
+        let trimmed = trimmed.strip_prefix("async ").unwrap_or(trimmed);
+
+        let declaration = [("def ", Kind::Function), ("class ", Kind::Class)]
+            .into_iter()
+            .find_map(|(keyword, kind)| Some((kind, identifier(trimmed.strip_prefix(keyword)?)?)));
+
+        if let Some((kind, name)) = declaration {
+            symbols.push(Symbol {
+                kind,
+                name,
+                line: number,
+                end: number,
+                depth: open.len(),
+            });
+            open.push((symbols.len() - 1, indent));
+        }
+
+        last_code = number;
+    }
+
+    for (index, _) in open {
+        symbols[index].end = last_code;
+    }
+
+    symbols
+}
+
+// 解析 Markdown 的标题：每个标题的章节到下一个级别不低于它的标题之前结束，代码块中的 # 被忽略。
+fn headings(lines: impl Iterator<Item = String>) -> Vec<Symbol> {
+    let mut symbols: Vec<Symbol> = Vec::new();
+    let mut open: Vec<(usize, u8)> = Vec::new(); // 尚未结束的标题及其级别。
+    let mut in_code = false;
+    let mut last = 0;
+
+    for (number, line) in lines.enumerate() {
+        last = number;
+        let trimmed = line.trim_start();
+
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_code = !in_code;
+            continue;
+        }
+
+        if in_code {
+            continue;
+        }
+
+        let level = trimmed.chars().take_while(|c| *c == '#').count();
+        let title = trimmed[level..].trim();
+
+        if !(1..=6).contains(&level) || !trimmed[level..].starts_with(' ') || title.is_empty() {
+            continue;
+        }
+
+        let level = level as u8;
+
+        while let Some(&(index, opened)) = open.last() {
+            if level > opened {
+                break;
+            }
+
+            symbols[index].end = number.saturating_sub(1);
+            open.pop();
+        }
+
+        symbols.push(Symbol {
+            kind: Kind::Heading(level),
+            name: title.trim_end_matches('#').trim_end().to_string(),
+            line: number,
+            end: number,
+            depth: open.len(),
+        });
+        open.push((symbols.len() - 1, level));
+    }
+
+    for (index, _) in open {
+        symbols[index].end = last;
+    }
+
+    symbols
+}
+
+// 识别 Rust 中的声明，跳过可见性和修饰符。
+fn rust_declaration(line: &str) -> Option<(Kind, String)> {
+    let mut rest = line.trim_start();
+
+    if rest.starts_with("//") {
+        return None;
+    }
+
+    // 跳过 pub、pub(crate) 以及函数的各种修饰符。
+    loop {
+        let stripped = [
+            "pub ",
+            "async ",
+            "const ",
+            "unsafe ",
+            "default ",
+            "extern \"C\" ",
+        ]
+        .into_iter()
+        .find_map(|modifier| rest.strip_prefix(modifier))
+        .or_else(|| {
+            let visibility = rest.strip_prefix("pub(")?;
+            Some(visibility[visibility.find(')')? + 1..].trim_start())
+        });
+
+        match stripped {
+            Some(stripped) => rest = stripped,
+            None => break,
+        }
+    }
+
+    if let Some(target) = rest.strip_prefix("impl") {
+        if !target.starts_with([' ', '<']) {
+            return None;
+        }
+
+        // impl 块的名称为实现的类型，trait 的实现显示为 Trait for Type。
+        let target = skip_generics(target.trim_start());
+        let end = target.find(" where").or_else(|| target.find('{'));
+        let name = target[..end.unwrap_or(target.len())].trim();
+
+        return (!name.is_empty()).then(|| (Kind::Impl, name.to_string()));
+    }
+
+    [
+        ("fn ", Kind::Function),
+        ("struct ", Kind::Struct),
+        ("enum ", Kind::Enum),
+        ("union ", Kind::Struct),
+        ("type ", Kind::Struct),
+        ("trait ", Kind::Trait),
+        ("mod ", Kind::Module),
+    ]
+    .into_iter()
+    .find_map(|(keyword, kind)| Some((kind, identifier(rest.strip_prefix(keyword)?)?)))
+}
+
+// 识别 JavaScript、TypeScript、Go 等语言中的函数、类和类型声明。
+fn brace_declaration(line: &str) -> Option<(Kind, String)> {
+    let mut rest = line.trim_start();
+
+    if rest.starts_with("//") {
+        return None;
+    }
+
+    loop {
+        let stripped = [
+            "export ",
+            "default ",
+            "async ",
+            "public ",
+            "private ",
+            "abstract ",
+        ]
+        .into_iter()
+        .find_map(|modifier| rest.strip_prefix(modifier));
+
+        match stripped {
+            Some(stripped) => rest = stripped,
+            None => break,
+        }
+    }
+
+    // Go 的方法在函数名之前声明接收者：func (s *Server) Run()。
+    if let Some(function) = rest.strip_prefix("func ") {
+        let function = match function.strip_prefix('(') {
+            Some(receiver) => receiver[receiver.find(')')? + 1..].trim_start(),
+            None => function,
+        };
+
+        return Some((Kind::Function, identifier(function)?));
+    }
+
+    // Go 的类型声明：type Server struct。
+    if let Some(declaration) = rest.strip_prefix("type ") {
+        let name = identifier(declaration)?;
+        let kind = if declaration.contains(" interface") {
+            Kind::Trait
+        } else {
+            Kind::Struct
+        };
+
+        return Some((kind, name));
+    }
+
+    [
+        ("function ", Kind::Function),
+        ("function* ", Kind::Function),
+        ("class ", Kind::Class),
+        ("interface ", Kind::Trait),
+        ("enum ", Kind::Enum),
+    ]
+    .into_iter()
+    .find_map(|(keyword, kind)| Some((kind, identifier(rest.strip_prefix(keyword)?)?)))
+}
+
+// 返回文本开头的标识符。
+fn identifier(text: &str) -> Option<String> {
+    let name: String = text
+        .trim_start()
+        .chars()
+        .take_while(|c| c.is_alphanumeric() || *c == '_' || *c == '$')
+        .collect();
+
+    (!name.is_empty()).then_some(name)
+}
+
+// 跳过 impl 之后的泛型参数列表，例如 impl<'a, T: Clone> 中的 <'a, T: Clone>。
+fn skip_generics(text: &str) -> &str {
+    if !text.starts_with('<') {
+        return text;
+    }
+
+    let mut depth = 0;
+
+    for (index, c) in text.char_indices() {
+        match c {
+            '<' => depth += 1,
+            '>' => {
+                depth -= 1;
+
+                if depth == 0 {
+                    return text[index + 1..].trim_start();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    text
+}
+
+// 返回一行中的代码部分：去掉行注释，并把字符串和字符字面量替换为空格，
+// 使其中的大括号和分号不影响代码块的划分。
+fn code(line: &str) -> String {
+    let mut code = String::with_capacity(line.len());
+    let mut chars = line.chars().peekable();
+    let mut quote = None;
+
+    while let Some(c) = chars.next() {
+        match quote {
+            Some(open) => {
+                if c == '\\' {
+                    chars.next();
+                } else if c == open {
+                    quote = None;
+                }
+                code.push(' ');
+            }
+            None => match c {
+                '/' if chars.peek() == Some(&'/') => break,
+                '"' | '`' => {
+                    quote = Some(c);
+                    code.push(' ');
+                }
+                // 字符字面量，例如 '{' 和 '\''；生命周期 'a 后面没有单引号，保持不变。
+                '\'' => {
+                    let mut ahead = chars.clone();
+                    let length = match (ahead.next(), ahead.next(), ahead.next()) {
+                        (Some('\\'), Some(_), Some('\'')) => 3,
+                        (Some(_), Some('\''), _) => 2,
+                        _ => 0,
+                    };
+
+                    for _ in 0..length {
+                        chars.next();
+                    }
+                    code.push(' ');
+                }
+                _ => code.push(c),
+            },
+        }
+    }
+
+    code
+}
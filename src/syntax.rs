@@ -0,0 +1,410 @@
+use crate::syntax_tree;
+use iced::advanced::text::highlighter::{self, Format};
+use iced::{Color, Font};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::ops::Range;
+use std::path::Path;
+use std::sync::OnceLock;
+use syntect::highlighting::{self, ThemeSet};
+use syntect::parsing::{ParseState, ScopeStack, ScopeStackOp, SyntaxReference, SyntaxSet};
+
+// 内置的语法定义。
+fn syntaxes() -> &'static SyntaxSet {
+    static SYNTAXES: OnceLock<SyntaxSet> = OnceLock::new();
+
+    SYNTAXES.get_or_init(SyntaxSet::load_defaults_nonewlines)
+}
+
+// 定义一种可以手动选择的语言，对应一个内置的语法定义。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Language {
+    pub name: &'static str,      // 语法定义的名称。
+    pub extension: &'static str, // 语法定义的第一个扩展名，选择语言后代替文件的扩展名。
+}
+
+impl fmt::Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name)
+    }
+}
+
+// 返回所有可以选择的语言，按名称排序。没有扩展名的语法定义无法通过扩展名找到，不能选择。
+pub fn languages() -> Vec<Language> {
+    let mut languages: Vec<_> = syntaxes()
+        .syntaxes()
+        .iter()
+        .filter_map(|syntax| {
+            Some(Language {
+                name: &syntax.name,
+                extension: syntax.file_extensions.first()?,
+            })
+        })
+        .collect();
+
+    languages.sort_by_key(|language| language.name.to_lowercase());
+    languages
+}
+
+// 返回高亮器按扩展名选择的语言，找不到对应的语法时为纯文本。
+pub fn language(extension: &str) -> Language {
+    let syntax = find_syntax(extension);
+
+    Language {
+        name: &syntax.name,
+        extension: syntax.file_extensions.first().map_or("txt", String::as_str),
+    }
+}
+
+// 返回名称或扩展名对应的语法定义所使用的扩展名，没有对应的语法时返回 None。
+pub fn find_extension(token: &str) -> Option<&'static str> {
+    syntaxes()
+        .find_syntax_by_token(token)?
+        .file_extensions
+        .first()
+        .map(String::as_str)
+}
+
+// 定义代码高亮主题：内置主题或用户主题目录中的 .tmTheme 主题。
+#[derive(Debug, Clone, Copy)]
+pub struct Theme {
+    name: &'static str,                  // 显示的名称，也用于保存到偏好设置中。
+    theme: &'static highlighting::Theme, // 主题的颜色定义。
+}
+
+impl Theme {
+    // 默认的高亮主题，按界面是否使用深色外观选择 Solarized 的深色或浅色版本。
+    pub fn default_theme(is_dark: bool) -> Self {
+        let name = if is_dark {
+            "Solarized (dark)"
+        } else {
+            "Solarized (light)"
+        };
+
+        Self::all()
+            .iter()
+            .copied()
+            .find(|theme| theme.name == name)
+            .unwrap_or(Self::all()[0])
+    }
+
+    // 返回所有可用的主题，内置主题在前，用户主题按文件名排在后面。
+    //
+    // 主题只在第一次使用时加载一次，并在整个程序运行期间保留。
+    pub fn all() -> &'static [Self] {
+        static THEMES: OnceLock<Vec<Theme>> = OnceLock::new();
+
+        THEMES.get_or_init(|| {
+            let builtin = ThemeSet::load_defaults();
+            let mut themes = leak(builtin);
+
+            if let Some(folder) = crate::config_file("themes") {
+                themes.extend(leak(load_folder(&folder)));
+            }
+
+            themes
+        })
+    }
+
+    // 根据名称查找主题。
+    pub fn find(name: &str) -> Option<Self> {
+        Self::all().iter().copied().find(|theme| theme.name == name)
+    }
+
+    // 返回主题的默认文字颜色，主题没有定义时使用黑色。
+    pub fn foreground(&self) -> Color {
+        self.theme.settings.foreground.map_or(Color::BLACK, color)
+    }
+
+    // 返回主题的背景颜色，主题没有定义时使用白色。
+    pub fn background(&self) -> Color {
+        self.theme.settings.background.map_or(Color::WHITE, color)
+    }
+
+    // 判断主题的背景是否为深色。
+    pub fn is_dark(&self) -> bool {
+        let Some(color) = self.theme.settings.background else {
+            return true;
+        };
+
+        let luminance =
+            0.299 * f32::from(color.r) + 0.587 * f32::from(color.g) + 0.114 * f32::from(color.b);
+
+        luminance < 128.0
+    }
+}
+
+impl PartialEq for Theme {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
+impl Eq for Theme {}
+
+impl fmt::Display for Theme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name)
+    }
+}
+
+// 读取主题目录中的所有 .tmTheme 文件，无法解析的文件会被跳过。
+fn load_folder(folder: &Path) -> ThemeSet {
+    let mut themes = ThemeSet::new();
+
+    let Ok(entries) = std::fs::read_dir(folder) else {
+        return themes;
+    };
+
+    for path in entries.flatten().map(|entry| entry.path()) {
+        let is_theme = path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("tmTheme"));
+
+        let name = path.file_stem().and_then(|name| name.to_str());
+
+        if let (true, Some(name)) = (is_theme, name) {
+            if let Ok(theme) = ThemeSet::get_theme(&path) {
+                let _ = themes.themes.insert(name.to_string(), theme);
+            }
+        }
+    }
+
+    themes
+}
+
+// 将主题集合转换为静态的主题列表，主题在程序运行期间不会被释放。
+fn leak(themes: ThemeSet) -> Vec<Theme> {
+    themes
+        .themes
+        .into_iter()
+        .map(|(name, theme)| Theme {
+            name: Box::leak(name.into_boxed_str()),
+            theme: Box::leak(Box::new(theme)),
+        })
+        .collect()
+}
+
+// 定义代码高亮的实现方式，可以按语言分别选择。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Backend {
+    #[default]
+    Syntect, // 使用 TextMate 语法定义逐行解析，支持所有语言。
+    TreeSitter, // 使用 tree-sitter 解析完整的语法树，只支持部分语言。
+}
+
+impl Backend {
+    // 所有可选的高亮方式，用于设置界面中的选择列表。
+    pub const ALL: &[Self] = &[Self::Syntect, Self::TreeSitter];
+}
+
+impl fmt::Display for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Syntect => f.write_str("Syntect"),
+            Self::TreeSitter => f.write_str("Tree-sitter"),
+        }
+    }
+}
+
+// 定义高亮器的设置。
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settings {
+    pub theme: Theme,                        // 使用的高亮主题。
+    pub extension: String,                   // 用于选择语法的文件扩展名。
+    pub tree: Option<syntax_tree::Snapshot>, // 使用 tree-sitter 高亮时的语法树，None 表示使用 syntect。
+}
+
+// 定义一段文本的高亮样式。
+pub struct Highlight(highlighting::StyleModifier);
+
+impl Highlight {
+    // 将高亮转换为编辑器使用的文字格式。
+    pub fn to_format(&self) -> Format<Font> {
+        Format {
+            color: self.0.foreground.map(color),
+            font: None,
+        }
+    }
+}
+
+// 定义一行的解析结果。
+//
+// 解析一行需要上一行结束时的状态，因此只有修改的行及其之后的行需要重新解析；
+// 只更换主题时，所有的行都可以直接使用缓存的作用域操作重新着色。
+struct Line {
+    ops: Vec<(usize, ScopeStackOp)>, // 解析这一行得到的作用域操作。
+    state: ParseState,               // 这一行结束时的解析状态。
+    stack: ScopeStack,               // 这一行结束时的作用域。
+}
+
+// 基于 syntect 的代码高亮器，与 iced 内置的高亮器相同，但可以使用任意的主题。
+//
+// 设置中带有语法树时改为按语法树高亮，颜色同样来自 syntect 的主题。
+pub struct Highlighter {
+    syntax: &'static SyntaxReference,
+    highlighter: highlighting::Highlighter<'static>,
+    lines: Vec<Line>, // 已经解析的各行，修改某一行后丢弃它及之后的结果。
+    tree: Option<syntax_tree::Snapshot>, // 使用 tree-sitter 高亮时的语法树。
+    current_line: usize, // 下一次高亮的行。
+    extension: String, // 当前语法对应的文件扩展名。
+    theme: Theme,     // 当前的高亮主题。
+}
+
+impl highlighter::Highlighter for Highlighter {
+    type Settings = Settings;
+    type Highlight = Highlight;
+    type Iterator<'a> = Box<dyn Iterator<Item = (Range<usize>, Highlight)> + 'a>;
+
+    fn new(settings: &Self::Settings) -> Self {
+        let syntax = find_syntax(&settings.extension);
+
+        Self {
+            syntax,
+            highlighter: highlighting::Highlighter::new(settings.theme.theme),
+            lines: Vec::new(),
+            tree: settings.tree.clone(),
+            current_line: 0,
+            extension: settings.extension.clone(),
+            theme: settings.theme,
+        }
+    }
+
+    fn update(&mut self, new_settings: &Self::Settings) {
+        // 只有语法树变化时，从高亮可能变化的第一行开始重新高亮。
+        if let (Some(_), Some(tree)) = (&self.tree, &new_settings.tree) {
+            if self.extension == new_settings.extension && self.theme == new_settings.theme {
+                let line = tree.changed_line();
+                self.tree = Some(tree.clone());
+                self.current_line = self.current_line.min(line);
+                return;
+            }
+        }
+
+        // 语法没有变化时解析的结果仍然有效，只需要从头重新着色。
+        if self.extension != new_settings.extension || self.tree != new_settings.tree {
+            self.syntax = find_syntax(&new_settings.extension);
+            self.lines.clear();
+        }
+
+        self.tree = new_settings.tree.clone();
+        self.extension = new_settings.extension.clone();
+        self.theme = new_settings.theme;
+        self.highlighter = highlighting::Highlighter::new(new_settings.theme.theme);
+        self.current_line = 0;
+    }
+
+    fn change_line(&mut self, line: usize) {
+        // 修改的行及其之后的解析结果都不再有效，即使这些行还没有重新高亮。
+        self.lines.truncate(line);
+        self.current_line = self.current_line.min(line);
+    }
+
+    fn highlight_line(&mut self, line: &str) -> Self::Iterator<'_> {
+        if let Some(tree) = &self.tree {
+            let spans = tree.highlight_line(self.current_line);
+            let highlighter = &self.highlighter;
+            self.current_line += 1;
+
+            return Box::new(spans.into_iter().map(move |(range, scope)| {
+                let scopes = scope.as_ref().map_or(&[][..], std::slice::from_ref);
+                (range, Highlight(highlighter.style_mod_for_stack(scopes)))
+            }));
+        }
+
+        let index = self.current_line;
+        self.current_line += 1;
+
+        // 总是按顺序高亮，没有缓存的行一定紧接在已经解析的行之后。
+        if index >= self.lines.len() {
+            let (mut state, mut stack) = match self.lines.last() {
+                Some(previous) => (previous.state.clone(), previous.stack.clone()),
+                None => (ParseState::new(self.syntax), ScopeStack::new()),
+            };
+
+            let ops = state.parse_line(line, syntaxes()).unwrap_or_default();
+
+            for (_, op) in &ops {
+                let _ = stack.apply(op);
+            }
+
+            self.lines.push(Line { ops, state, stack });
+        }
+
+        let mut stack = match index.checked_sub(1) {
+            Some(previous) => self.lines[previous].stack.clone(),
+            None => ScopeStack::new(),
+        };
+        let ops = self.lines[index].ops.clone();
+        let highlighter = &self.highlighter;
+
+        Box::new(
+            scope_ranges(ops, line.len()).filter_map(move |(range, op)| {
+                let _ = stack.apply(&op);
+
+                if range.is_empty() {
+                    None
+                } else {
+                    Some((
+                        range,
+                        Highlight(highlighter.style_mod_for_stack(&stack.scopes)),
+                    ))
+                }
+            }),
+        )
+    }
+
+    fn current_line(&self) -> usize {
+        self.current_line
+    }
+}
+
+// 定义导出时使用的一行高亮结果：每一段文本及其颜色。
+pub type Line = Vec<(Color, String)>;
+
+// 高亮整个文档，用于打印和导出。与编辑器中的高亮不同，这里一次处理所有行。
+pub fn highlight_document(text: &str, theme: Theme, extension: &str) -> Vec<Line> {
+    let mut highlighter = syntect::easy::HighlightLines::new(find_syntax(extension), theme.theme);
+    let foreground = theme.foreground();
+
+    text.lines()
+        .map(|line| match highlighter.highlight_line(line, syntaxes()) {
+            Ok(spans) => spans
+                .into_iter()
+                .map(|(style, text)| (color(style.foreground), text.to_string()))
+                .collect(),
+            Err(_) => vec![(foreground, line.to_string())],
+        })
+        .collect()
+}
+
+// 将 syntect 的颜色转换为 iced 的颜色。
+fn color(color: highlighting::Color) -> Color {
+    Color::from_rgba8(color.r, color.g, color.b, f32::from(color.a) / 255.0)
+}
+
+// 根据文件扩展名查找语法定义，找不到时按纯文本处理。
+fn find_syntax(extension: &str) -> &'static SyntaxReference {
+    let syntaxes = syntaxes();
+
+    syntaxes
+        .find_syntax_by_token(extension)
+        .unwrap_or_else(|| syntaxes.find_syntax_plain_text())
+}
+
+// 将解析得到的作用域操作转换为文本范围，每个范围使用应用对应操作后的作用域。
+fn scope_ranges(
+    ops: Vec<(usize, ScopeStackOp)>,
+    length: usize,
+) -> impl Iterator<Item = (Range<usize>, ScopeStackOp)> {
+    let ends: Vec<_> = ops
+        .iter()
+        .map(|(index, _)| *index)
+        .chain(std::iter::once(length))
+        .collect();
+
+    std::iter::once((0, ScopeStackOp::Noop))
+        .chain(ops)
+        .zip(ends)
+        .map(|((start, op), end)| (start..end, op))
+}
@@ -0,0 +1,351 @@
+use ropey::Rope;
+use std::cell::RefCell;
+use std::fmt;
+use std::ops::Range;
+use std::sync::{Arc, OnceLock};
+use syntect::parsing::Scope;
+use tree_sitter::{InputEdit, Node, Parser, Point, Query, QueryCursor, Tree};
+
+// 定义 tree-sitter 支持的一种语言：语法、高亮查询以及各个捕获对应的 syntect 作用域。
+struct Grammar {
+    extensions: &'static [&'static str], // 使用这种语法的文件扩展名。
+    language: tree_sitter::Language,     // 语法定义。
+    query: Query,                        // 高亮查询。
+    scopes: Vec<Option<Scope>>,          // 按捕获的下标排列的作用域，用于从高亮主题中查找颜色。
+}
+
+// 内置的语法，只在第一次使用时加载一次。
+fn grammars() -> &'static [Grammar] {
+    static GRAMMARS: OnceLock<Vec<Grammar>> = OnceLock::new();
+
+    GRAMMARS.get_or_init(|| {
+        [
+            (
+                &["rs"][..],
+                tree_sitter_rust::language(),
+                tree_sitter_rust::HIGHLIGHT_QUERY,
+            ),
+            (
+                &["py", "pyw"][..],
+                tree_sitter_python::language(),
+                tree_sitter_python::HIGHLIGHT_QUERY,
+            ),
+            (
+                &["js", "mjs", "cjs", "jsx"][..],
+                tree_sitter_javascript::language(),
+                tree_sitter_javascript::HIGHLIGHT_QUERY,
+            ),
+        ]
+        .into_iter()
+        .filter_map(|(extensions, language, source)| {
+            let query = Query::new(language, source).ok()?;
+            let scopes = query
+                .capture_names()
+                .iter()
+                .map(|name| Scope::new(scope(name)).ok())
+                .collect();
+
+            Some(Grammar {
+                extensions,
+                language,
+                query,
+                scopes,
+            })
+        })
+        .collect()
+    })
+}
+
+// 根据文件扩展名查找语法。
+fn find_grammar(extension: &str) -> Option<&'static Grammar> {
+    grammars()
+        .iter()
+        .find(|grammar| grammar.extensions.contains(&extension))
+}
+
+// 判断 tree-sitter 是否支持指定扩展名的文件。
+pub fn supports(extension: &str) -> bool {
+    find_grammar(extension).is_some()
+}
+
+// 将高亮查询中的捕获名称转换为 TextMate 作用域，使两种高亮方式使用同一套主题。
+fn scope(capture: &str) -> &'static str {
+    match capture {
+        "variable.builtin" => "variable.language",
+        "variable.parameter" => "variable.parameter",
+        "constant.builtin" => "constant.language",
+        "type.builtin" => "storage.type",
+        "function.macro" => "support.function.macro",
+        _ => match capture.split('.').next().unwrap_or_default() {
+            "keyword" => "keyword.control",
+            "function" | "constructor" => "entity.name.function",
+            "type" => "entity.name.type",
+            "string" => "string.quoted",
+            "escape" => "constant.character.escape",
+            "comment" => "comment.line",
+            "number" => "constant.numeric",
+            "constant" => "constant.other",
+            "property" => "variable.other.member",
+            "attribute" => "entity.other.attribute-name",
+            "label" => "entity.name.label",
+            "operator" => "keyword.operator",
+            "punctuation" => "punctuation",
+            "tag" => "entity.name.tag",
+            _ => "source",
+        },
+    }
+}
+
+// 定义某个版本的文档及其语法树，由编辑器的高亮器逐行读取。
+#[derive(Clone)]
+pub struct Snapshot(Arc<Parsed>);
+
+struct Parsed {
+    grammar: &'static Grammar, // 使用的语法。
+    rope: Rope,                // 解析的文本。
+    tree: Tree,                // 解析得到的语法树。
+    changed: usize,            // 相对于上一个版本，高亮可能变化的第一行。
+}
+
+impl Snapshot {
+    // 返回高亮可能变化的第一行，之前的行可以沿用上一个版本的高亮结果。
+    pub fn changed_line(&self) -> usize {
+        self.0.changed
+    }
+
+    // 返回一行中各段文本的作用域，范围相对于行首、互不重叠并覆盖整行，没有被捕获的文本没有作用域。
+    pub fn highlight_line(&self, line: usize) -> Vec<(Range<usize>, Option<Scope>)> {
+        let Parsed {
+            grammar,
+            rope,
+            tree,
+            ..
+        } = &*self.0;
+
+        if line >= rope.len_lines() {
+            return Vec::new();
+        }
+
+        let start = rope.line_to_byte(line);
+        let end = if line + 1 < rope.len_lines() {
+            rope.line_to_byte(line + 1) - 1
+        } else {
+            rope.len_bytes()
+        };
+
+        let mut cursor = QueryCursor::new();
+        cursor.set_byte_range(start..end);
+
+        let text = |node: Node| {
+            rope.byte_slice(node.byte_range())
+                .chunks()
+                .map(str::as_bytes)
+        };
+
+        // 内层的节点在外层之后出现，覆盖外层节点的作用域。
+        let mut owners = vec![None; end - start];
+        let mut previous = None;
+
+        for (found, index) in cursor.captures(&grammar.query, tree.root_node(), text) {
+            let capture = found.captures[index];
+            let range = capture.node.byte_range();
+
+            // 同一个节点被多个模式捕获时，以查询中靠前的模式为准。
+            if previous.as_ref() == Some(&range) {
+                continue;
+            }
+
+            let from = range.start.clamp(start, end) - start;
+            let to = range.end.clamp(start, end) - start;
+            owners[from..to].fill(Some(capture.index as usize));
+            previous = Some(range);
+        }
+
+        let mut spans: Vec<(Range<usize>, Option<usize>)> = Vec::new();
+
+        for (offset, owner) in owners.into_iter().enumerate() {
+            match spans.last_mut() {
+                Some((range, last)) if *last == owner => range.end = offset + 1,
+                _ => spans.push((offset..offset + 1, owner)),
+            }
+        }
+
+        spans
+            .into_iter()
+            .map(|(range, owner)| (range, owner.and_then(|index| grammar.scopes[index])))
+            .collect()
+    }
+
+    // 返回包含指定字符范围的各个具名语法节点的字符范围，从内到外排列。
+    //
+    // 语法树对应的文本与 rope 不同时（例如还没有重新解析）返回空的列表。
+    pub fn enclosing(&self, rope: &Rope, range: Range<usize>) -> Vec<Range<usize>> {
+        let Parsed {
+            rope: parsed, tree, ..
+        } = &*self.0;
+
+        if parsed != rope || range.end > rope.len_chars() {
+            return Vec::new();
+        }
+
+        let start = rope.char_to_byte(range.start);
+        let end = rope.char_to_byte(range.end);
+        let mut node = tree.root_node().descendant_for_byte_range(start, end);
+        let mut ranges = Vec::new();
+
+        while let Some(current) = node {
+            if current.is_named() {
+                let bytes = current.byte_range();
+                ranges.push(rope.byte_to_char(bytes.start)..rope.byte_to_char(bytes.end));
+            }
+
+            node = current.parent();
+        }
+
+        ranges
+    }
+}
+
+impl PartialEq for Snapshot {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl fmt::Debug for Snapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Snapshot")
+            .field("lines", &self.0.rope.len_lines())
+            .field("changed", &self.0.changed)
+            .finish()
+    }
+}
+
+// 定义标签页的语法树，文档修改后只增量地重新解析变化的部分。
+#[derive(Default)]
+pub struct Cache {
+    state: RefCell<Option<State>>, // 解析器和最近一次解析的结果。
+}
+
+struct State {
+    revision: usize,    // 解析时文档内容的版本号。
+    parser: Parser,     // 设置好语法的解析器，重新解析时复用。
+    snapshot: Snapshot, // 最近一次解析的结果。
+}
+
+impl Cache {
+    // 返回文档的语法树，不支持的语言返回 None。
+    //
+    // 文档变化后，先按修改前后的文本找出被替换的范围并编辑旧的语法树，
+    // tree-sitter 据此只重新解析受影响的节点。
+    pub fn get(&self, rope: &Rope, revision: usize, extension: &str) -> Option<Snapshot> {
+        let grammar = find_grammar(extension)?;
+        let mut state = self.state.borrow_mut();
+
+        match state.as_mut() {
+            Some(state)
+                if std::ptr::eq(state.snapshot.0.grammar, grammar)
+                    && state.revision == revision
+                    && state.snapshot.0.rope.len_chars() == rope.len_chars() =>
+            {
+                Some(state.snapshot.clone())
+            }
+            Some(state) if std::ptr::eq(state.snapshot.0.grammar, grammar) => {
+                let previous = &state.snapshot.0;
+                let edit = edit(&previous.rope, rope);
+
+                let mut tree = previous.tree.clone();
+                tree.edit(&edit);
+
+                let parsed = parse(&mut state.parser, rope, Some(&tree))?;
+
+                let changed = tree
+                    .changed_ranges(&parsed)
+                    .map(|range| range.start_point.row)
+                    .chain(std::iter::once(edit.start_position.row))
+                    .min()
+                    .unwrap_or_default();
+
+                state.revision = revision;
+                state.snapshot = Snapshot(Arc::new(Parsed {
+                    grammar,
+                    rope: rope.clone(),
+                    tree: parsed,
+                    changed,
+                }));
+
+                Some(state.snapshot.clone())
+            }
+            _ => {
+                let mut parser = Parser::new();
+                parser.set_language(grammar.language).ok()?;
+
+                let tree = parse(&mut parser, rope, None)?;
+                let snapshot = Snapshot(Arc::new(Parsed {
+                    grammar,
+                    rope: rope.clone(),
+                    tree,
+                    changed: 0,
+                }));
+
+                *state = Some(State {
+                    revision,
+                    parser,
+                    snapshot: snapshot.clone(),
+                });
+
+                Some(snapshot)
+            }
+        }
+    }
+}
+
+// 按块读取 rope 中的文本进行解析，不需要把整个文档复制成字符串。
+fn parse(parser: &mut Parser, rope: &Rope, old: Option<&Tree>) -> Option<Tree> {
+    parser.parse_with(
+        &mut |byte: usize, _: Point| {
+            if byte < rope.len_bytes() {
+                let (chunk, start, _, _) = rope.chunk_at_byte(byte);
+                &chunk.as_bytes()[byte - start..]
+            } else {
+                &b""[..]
+            }
+        },
+        old,
+    )
+}
+
+// 比较修改前后的文本，按行找出被替换的范围：两者开头和结尾相同的行都不算修改。
+fn edit(old: &Rope, new: &Rope) -> InputEdit {
+    let lines = old.len_lines().min(new.len_lines());
+
+    let first = (0..lines)
+        .find(|&line| old.line(line) != new.line(line))
+        .unwrap_or(lines);
+
+    let same = (0..lines - first)
+        .take_while(|&offset| {
+            old.line(old.len_lines() - 1 - offset) == new.line(new.len_lines() - 1 - offset)
+        })
+        .count();
+
+    let start_byte = old.line_to_byte(first);
+    let old_end_byte = old.line_to_byte(old.len_lines() - same);
+    let new_end_byte = new.line_to_byte(new.len_lines() - same);
+
+    InputEdit {
+        start_byte,
+        old_end_byte,
+        new_end_byte,
+        start_position: point(old, start_byte),
+        old_end_position: point(old, old_end_byte),
+        new_end_position: point(new, new_end_byte),
+    }
+}
+
+// 将字节位置转换为 tree-sitter 使用的行和列，列同样以字节计算。
+fn point(rope: &Rope, byte: usize) -> Point {
+    let row = rope.byte_to_line(byte);
+
+    Point::new(row, byte - rope.line_to_byte(row))
+}
@@ -0,0 +1,1065 @@
+use crate::cleanup;
+use crate::comment;
+use crate::document::Document;
+use crate::editorconfig::Properties;
+use crate::encoding::{Decoded, Encoding};
+use crate::filetype;
+use crate::folding::{self, Region};
+use crate::git;
+use crate::hex::HexEditor;
+use crate::history::{History, Snapshot};
+use crate::i18n::tr;
+use crate::indent;
+use crate::line_ending::LineEnding;
+use crate::preferences::Preferences;
+use crate::remote;
+use crate::statistics::Statistics;
+use crate::symbols::{self, Symbol};
+use crate::syntax_tree;
+use crate::transform::Transform;
+use iced::widget::text_editor::{Action, Edit, Motion};
+use ropey::Rope;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeSet;
+use std::hash::Hasher;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::Arc;
+
+// 定义单个标签页（文档）的状态。
+pub struct Tab {
+    pub id: usize,                        // 标签页的唯一标识，用于匹配异步任务的结果。
+    pub path: Option<PathBuf>,            // 打开文件的路径。
+    pub context: Document,                // 文本编辑器的内容。
+    pub is_dirty: bool,                   // 文件是否被修改过。
+    pub history: History,                 // 撤销/重做历史记录。
+    pub line_ending: LineEnding,          // 保存文件时使用的换行符。
+    pub encoding: Encoding,               // 保存文件时使用的字符编码。
+    pub encoding_is_uncertain: bool,      // 检测到的编码是否可能不准确。
+    pub has_bom: bool,                    // 保存时是否写入 BOM，保留原文件开头的 BOM。
+    pub statistics: Statistics,           // 字数统计。
+    pub revision: usize,                  // 文档内容的版本号，每次修改内容时递增。
+    pub split: Option<Document>,          // 分屏中第二个视图的内容，拥有独立的光标。
+    pub is_read_only: bool,               // 文件是否只读，只读的文件不能编辑。
+    pub head: Option<Arc<String>>,        // 文件在 git HEAD 中的内容，不在仓库中时为 None。
+    pub changes: Vec<git::Change>,        // 相对于 HEAD 修改过的行。
+    pub changes_revision: usize,          // 最近一次比较修改时文档内容的版本号。
+    pub rules: indent::Rules,             // 缩进规则，由编辑器根据偏好设置更新。
+    pub editorconfig: Properties,         // 文件所在目录的 .editorconfig 中适用于该文件的属性。
+    pub symbols: symbols::Cache,          // 按文档版本缓存的符号。
+    pub tree: syntax_tree::Cache,         // 使用 tree-sitter 高亮时增量更新的语法树。
+    pub url: Option<String>,              // 从网址打开的文档的来源。
+    pub remote: Option<remote::Location>, // 通过 SFTP 打开的远程文件，保存时写回服务器。
+    pub hex: Option<HexEditor>,           // 二进制文件的十六进制编辑器，代替文本编辑器显示。
+    pub bookmarks: BTreeSet<usize>,       // 添加了书签的行。
+    pub language: Option<String>,         // 手动选择的语言，以扩展名表示，代替由文件名推断的语言。
+    pub disk: Option<u64>,                // 最近一次读取或保存时磁盘上文件内容的指纹。
+    pub scratch: Option<usize>,           // 临时缓冲区的编号，关闭时不提示保存。
+}
+
+impl Tab {
+    // 创建一个空白的标签页。
+    pub fn new(id: usize) -> Self {
+        let context = Document::new();
+
+        Self {
+            id,
+            path: None,
+            statistics: Statistics::new(&context),
+            context,
+            is_dirty: true,
+            history: History::default(),
+            line_ending: LineEnding::Lf,
+            encoding: Encoding::UTF_8,
+            encoding_is_uncertain: false,
+            has_bom: false,
+            revision: 0,
+            split: None,
+            is_read_only: false,
+            head: None,
+            changes: Vec::new(),
+            changes_revision: 0,
+            rules: indent::Rules::for_extension("rs"),
+            editorconfig: Properties::default(),
+            symbols: symbols::Cache::default(),
+            tree: syntax_tree::Cache::default(),
+            url: None,
+            remote: None,
+            hex: None,
+            bookmarks: BTreeSet::new(),
+            language: None,
+            disk: None,
+            scratch: None,
+        }
+    }
+
+    // 使用已加载的文件内容创建一个标签页，检测原有的换行符并在编辑器中统一为 \n。
+    pub fn with_file(id: usize, path: PathBuf, decoded: &Decoded) -> Self {
+        let text = LineEnding::normalize(&decoded.text);
+        let context = Document::with_text(&text);
+        let rules = indent::Rules::for_extension(
+            path.extension()
+                .and_then(|extension| extension.to_str())
+                .unwrap_or("rs"),
+        );
+
+        Self {
+            id,
+            path: Some(path),
+            statistics: Statistics::new(&context),
+            context,
+            is_dirty: false,
+            history: History::default(),
+            line_ending: LineEnding::detect(&decoded.text),
+            encoding: decoded.encoding,
+            encoding_is_uncertain: decoded.is_uncertain,
+            has_bom: decoded.has_bom,
+            revision: 0,
+            split: None,
+            is_read_only: decoded.is_read_only,
+            head: None,
+            changes: Vec::new(),
+            changes_revision: 0,
+            rules,
+            editorconfig: Properties::default(),
+            symbols: symbols::Cache::default(),
+            tree: syntax_tree::Cache::default(),
+            url: None,
+            remote: None,
+            hex: None,
+            bookmarks: BTreeSet::new(),
+            language: None,
+            disk: Some(fingerprint([text.as_str()])),
+            scratch: None,
+        }
+    }
+
+    // 创建一个包含指定文本的新文档，例如启动时读取的剪贴板内容。文档没有路径，保存时需要选择路径。
+    pub fn with_text(id: usize, text: &str) -> Self {
+        let context = Document::with_text(&LineEnding::normalize(text));
+
+        Self {
+            statistics: Statistics::new(&context),
+            context,
+            ..Self::new(id)
+        }
+    }
+
+    // 创建一个临时缓冲区，显示为 untitled-编号。
+    //
+    // 临时缓冲区的内容保存在会话中，关闭和退出时不提示保存，只有明确保存时才选择路径。
+    pub fn with_scratch(id: usize, number: usize, text: &str) -> Self {
+        Self {
+            scratch: Some(number),
+            ..Self::with_text(id, text)
+        }
+    }
+
+    // 使用从网址下载的内容创建一个标签页。文档没有本地路径，保存时需要另存为本地文件。
+    pub fn with_download(id: usize, url: String, decoded: &Decoded) -> Self {
+        let mut tab = Self::with_file(id, PathBuf::new(), decoded);
+        tab.path = None;
+        tab.url = Some(url);
+        tab.rules = indent::Rules::for_extension(&tab.extension());
+        tab
+    }
+
+    // 使用从服务器读取的远程文件创建一个标签页，保存时通过 SFTP 写回服务器。
+    pub fn with_remote(id: usize, location: remote::Location, decoded: &Decoded) -> Self {
+        let mut tab = Self::with_file(id, PathBuf::new(), decoded);
+        tab.path = None;
+        tab.remote = Some(location);
+        tab.rules = indent::Rules::for_extension(&tab.extension());
+        tab
+    }
+
+    // 使用二进制文件的内容创建一个标签页，以十六进制编辑器的方式显示。
+    pub fn with_binary(id: usize, path: PathBuf, bytes: Vec<u8>, is_read_only: bool) -> Self {
+        let mut tab = Self::new(id);
+        tab.path = Some(path);
+        tab.is_read_only = is_read_only;
+        tab.hex = Some(HexEditor::new(bytes));
+        tab
+    }
+
+    // 返回保存时写入的完整文本，以换行符结尾。
+    //
+    // preferences 为 Some 时按照偏好设置清理，只计算清理的结果而不修改文档，
+    // 自动保存时不会移动光标或删掉正在输入的空格。只读文件不清理。
+    pub fn text_to_save(&self, preferences: Option<&Preferences>) -> Rope {
+        let mut text = self.context.full_rope();
+
+        if let (Some(preferences), false) = (preferences, self.is_read_only) {
+            if let Some(cleaned) = self.cleaned(&text, preferences) {
+                text = Rope::from(cleaned);
+            }
+        }
+
+        let length = text.len_chars();
+        if length == 0 || text.char(length - 1) != '\n' {
+            text.insert_char(length, '\n');
+        }
+
+        text
+    }
+
+    // 把文本转换为写入磁盘的字节内容，使用标签页选择的换行符和字符编码。二进制文件直接返回原始字节。
+    //
+    // 逐段转换 rope 中的文本，不复制整个文档。文本中有所选编码无法表示的字符时返回第一个这样的字符。
+    pub fn contents(&self, text: &Rope) -> Result<Vec<u8>, char> {
+        if let Some(hex) = &self.hex {
+            return Ok(hex.bytes.clone());
+        }
+
+        self.encoding.encode(
+            text.chunks().map(|chunk| self.line_ending.apply(chunk)),
+            self.has_bom,
+        )
+    }
+
+    // 执行一个编辑器动作，并在修改内容前记录撤销历史。只读文件会忽略修改内容的动作。
+    pub fn edit(&mut self, action: Action) {
+        if !action.is_edit() {
+            self.context.edit(action);
+            return;
+        }
+
+        if self.is_read_only {
+            return;
+        }
+
+        let last = self.unfold_edited(&action);
+
+        // 快照保存完整的文本，撤销时所有折叠的区域都会展开。
+        let context = &self.context;
+        self.history.record(&action, || Snapshot {
+            text: context.full_rope(),
+            cursor: context.cursor(),
+        });
+        self.is_dirty = true;
+        self.revision += 1;
+
+        // 只有在单行内编辑时才增量更新字数统计，其他情况重新统计整个文档。
+        let line_count = self.context.line_count();
+        let (start, _) = self.context.cursor_position();
+        let has_selection = self.context.selection().is_some();
+        let is_single_line = !has_selection && !matches!(action, Action::Edit(Edit::Paste(_)));
+
+        // 根据语言的缩进规则，换行时自动缩进，在空行中输入闭括号时回退一级缩进。
+        let rules = self.rules;
+        let before = self.line_before_cursor();
+
+        // 使用空格缩进时，Tab 键插入空格补齐到下一个缩进位置，在缩进中退格时删除一级缩进。
+        let action = match action {
+            Action::Edit(Edit::Insert('\t')) if !has_selection => rules
+                .tab(&before)
+                .map_or(action, |spaces| Action::Edit(Edit::Paste(Arc::new(spaces)))),
+            _ => action,
+        };
+
+        let indentation = match action {
+            Action::Edit(Edit::Enter) => rules.indentation_after(&before),
+            _ => String::new(),
+        };
+
+        let dedent = match action {
+            Action::Edit(Edit::Insert(c)) if !has_selection => rules.dedent(&before, c),
+            Action::Edit(Edit::Backspace) if !has_selection => rules.backspace(&before) - 1,
+            _ => 0,
+        };
+
+        for _ in 0..dedent {
+            self.context.edit(Action::Edit(Edit::Backspace));
+        }
+
+        self.context.edit(action);
+
+        if !indentation.is_empty() {
+            self.context
+                .edit(Action::Edit(Edit::Paste(Arc::new(indentation))));
+        }
+
+        if is_single_line && self.context.line_count() == line_count {
+            let (line, _) = self.context.cursor_position();
+            self.statistics.update_line(&self.context, line);
+        } else {
+            self.statistics = Statistics::new(&self.context);
+        }
+
+        // 编辑影响的最后一行及之后的折叠区域随编辑移动。
+        let delta = self.context.line_count() as isize - line_count as isize;
+        self.context.shift_folds(last, delta);
+
+        let (end, _) = self.context.cursor_position();
+        self.shift_bookmarks(self.context.full_line(start.min(end)), line_count);
+
+        self.sync_split();
+    }
+
+    // 编辑会影响被折叠的行时先展开相应的区域，返回编辑影响的最后一行。
+    //
+    // 选区跨过折叠的区域，或者结束于折叠区域开始行的末尾时，被折叠的行也属于选区，展开后重新选中；
+    // 在开始行换行、粘贴多行，或者把下一行合并到开始行时，无法确定被折叠的行应该放在哪里，也先展开。
+    fn unfold_edited(&mut self, action: &Action) -> usize {
+        let (line, column) = self.context.cursor_position();
+
+        if !self.context.has_folds() {
+            return line;
+        }
+
+        if let Some(((first, start), (last, end))) = self.selection_range() {
+            let at_end = end >= self.line_length(last);
+            let folded: Vec<usize> = self
+                .context
+                .folds()
+                .map(|(line, _)| line)
+                .filter(|line| (first..last).contains(line) || (*line == last && at_end))
+                .collect();
+
+            if folded.is_empty() {
+                return last;
+            }
+
+            let count: usize = folded
+                .into_iter()
+                .rev()
+                .map(|line| self.context.unfold(line))
+                .sum();
+            let last = last + count;
+            let end = if at_end { self.line_length(last) } else { end };
+
+            self.context.move_to(first, start);
+            self.context.select_to(last, end);
+
+            self.refolded();
+            return last;
+        }
+
+        let is_at_end = self
+            .context
+            .line(line)
+            .is_some_and(|text| column >= text.len());
+        let joined = match action {
+            Action::Edit(Edit::Enter) => Some(line),
+            Action::Edit(Edit::Paste(text)) if text.contains('\n') => Some(line),
+            Action::Edit(Edit::Backspace) if column == 0 => line.checked_sub(1),
+            Action::Edit(Edit::Delete) if is_at_end => Some(line),
+            _ => None,
+        };
+
+        let Some(folded) = joined.filter(|folded| self.context.is_folded(*folded)) else {
+            return line;
+        };
+
+        let column = self.line_before_cursor().chars().count();
+        let count = self.context.unfold(folded);
+        let line = if line > folded { line + count } else { line };
+
+        self.context.move_to(line, column);
+        self.refolded();
+        line
+    }
+
+    // 返回选区的开始和结束位置（行和字符列），没有选区时返回 None。
+    //
+    // 与 selected_lines 相同，先假设选区在光标之前，文本对不上时再认为选区在光标之后。
+    pub fn selection_range(&self) -> Option<((usize, usize), (usize, usize))> {
+        let selection = self.context.selection()?;
+        let (line, _) = self.context.cursor_position();
+        let column = self.line_before_cursor().chars().count();
+        let (first, last) = self.selected_lines();
+
+        let first_length = selection
+            .split('\n')
+            .next()
+            .unwrap_or_default()
+            .chars()
+            .count();
+        let last_length = selection
+            .rsplit('\n')
+            .next()
+            .unwrap_or_default()
+            .chars()
+            .count();
+
+        let is_before = if first == last {
+            self.line_before_cursor().ends_with(&selection)
+        } else {
+            last == line
+        };
+
+        Some(match (is_before, first == last) {
+            (true, true) => ((line, column.saturating_sub(first_length)), (line, column)),
+            (true, false) => (
+                (first, self.line_length(first).saturating_sub(first_length)),
+                (line, column),
+            ),
+            (false, true) => ((line, column), (line, column + last_length)),
+            (false, false) => ((line, column), (last, last_length)),
+        })
+    }
+
+    // 返回内容中一行的字符数。
+    fn line_length(&self, line: usize) -> usize {
+        self.context
+            .line(line)
+            .map_or(0, |text| text.chars().count())
+    }
+
+    // 返回可以折叠的区域。
+    pub fn fold_regions(&self) -> Vec<Region> {
+        folding::regions(self.context.rope(), &self.extension())
+    }
+
+    // 折叠从指定的行开始的区域，这一行不是区域的开始时折叠包含它的最内层区域。
+    pub fn fold(&mut self, line: usize) {
+        let regions = self.fold_regions();
+        let region = regions
+            .iter()
+            .find(|region| region.start == line)
+            .or_else(|| {
+                regions
+                    .iter()
+                    .filter(|region| region.contains(line))
+                    .max_by_key(|region| region.start)
+            });
+
+        let Some(&Region { start, end }) = region else {
+            return;
+        };
+
+        let (cursor, _) = self.context.cursor_position();
+        let column = self.line_before_cursor().chars().count();
+        let count = self.context.fold(start, end);
+
+        // 光标位于被折叠的行中时移到区域开始行的末尾。
+        if cursor > start && cursor <= end {
+            self.context.move_to(start, usize::MAX);
+        } else if cursor > end {
+            self.context.move_to(cursor - count, column);
+        } else {
+            self.context.move_to(cursor, column);
+        }
+
+        self.refolded();
+    }
+
+    // 展开从指定的行开始的区域。
+    pub fn unfold(&mut self, line: usize) {
+        let (cursor, _) = self.context.cursor_position();
+        let column = self.line_before_cursor().chars().count();
+        let count = self.context.unfold(line);
+
+        if count == 0 {
+            return;
+        }
+
+        let cursor = if cursor > line {
+            cursor + count
+        } else {
+            cursor
+        };
+        self.context.move_to(cursor, column);
+
+        self.refolded();
+    }
+
+    // 展开所有折叠的区域，光标保持在原来的位置。
+    pub fn unfold_all(&mut self) {
+        let folded: Vec<usize> = self.context.folds().map(|(line, _)| line).collect();
+
+        for line in folded.into_iter().rev() {
+            self.unfold(line);
+        }
+    }
+
+    // 折叠或展开从指定的行开始的区域。
+    pub fn toggle_fold(&mut self, line: usize) {
+        if self.context.is_folded(line) {
+            self.unfold(line);
+        } else {
+            self.fold(line);
+        }
+    }
+
+    // 折叠或展开区域后，内容中的行发生了变化，需要重新统计并让按版本号缓存的符号和语法树失效。
+    fn refolded(&mut self) {
+        self.statistics = Statistics::new(&self.context);
+        self.revision += 1;
+    }
+
+    // 编辑改变了行数后移动书签：start 行之后插入行时，后面的书签随之下移；
+    // 删除行时，被删除的行上的书签被移除，后面的书签随之上移。
+    fn shift_bookmarks(&mut self, start: usize, line_count: usize) {
+        let new_count = self.context.line_count();
+
+        if new_count == line_count || self.bookmarks.is_empty() {
+            return;
+        }
+
+        self.bookmarks = std::mem::take(&mut self.bookmarks)
+            .into_iter()
+            .filter_map(|line| {
+                if line <= start {
+                    Some(line)
+                } else if new_count > line_count {
+                    Some(line + (new_count - line_count))
+                } else {
+                    let removed = line_count - new_count;
+                    (line > start + removed).then(|| line - removed)
+                }
+            })
+            .collect();
+    }
+
+    // 在内容中指定的行上添加书签，已经有书签时移除。书签使用完整文本中的行号。
+    pub fn toggle_bookmark(&mut self, line: usize) {
+        let line = self.context.full_line(line);
+
+        if !self.bookmarks.remove(&line) {
+            self.bookmarks.insert(line);
+        }
+    }
+
+    // 返回光标之后（forward 为 true）或之前的下一个书签在内容中的行，到达文档的一端时从另一端继续查找。
+    pub fn next_bookmark(&self, forward: bool) -> Option<usize> {
+        let (line, _) = self.context.cursor_position();
+        let line = self.context.full_line(line);
+
+        let next = if forward {
+            self.bookmarks
+                .range(line + 1..)
+                .next()
+                .or_else(|| self.bookmarks.first())
+                .copied()
+        } else {
+            self.bookmarks
+                .range(..line)
+                .next_back()
+                .or_else(|| self.bookmarks.last())
+                .copied()
+        };
+
+        next.map(|line| self.context.display_line(line))
+    }
+
+    // 在分屏的第二个视图上执行编辑操作。
+    //
+    // 暂时交换两个视图，复用主视图的编辑逻辑，编辑后主视图会从第二个视图同步。
+    pub fn with_split(&mut self, f: impl FnOnce(&mut Self)) {
+        let split = self
+            .split
+            .get_or_insert_with(|| Document::with_rope(self.context.full_rope()));
+
+        std::mem::swap(&mut self.context, split);
+        f(self);
+
+        if let Some(split) = self.split.as_mut() {
+            std::mem::swap(&mut self.context, split);
+        }
+    }
+
+    // 把主视图的修改同步到分屏的第二个视图，它的光标、选区和滚动位置保持不变。
+    fn sync_split(&mut self) {
+        if let Some(split) = self.split.as_mut() {
+            split.sync(&self.context);
+        }
+    }
+
+    // 注释或取消注释光标所在的行或选中的行。
+    pub fn toggle_comment(&mut self) {
+        let Some(syntax) = comment::Syntax::for_extension(&self.extension()) else {
+            return;
+        };
+
+        let (first, last) = self.selected_lines();
+        let replacement = comment::toggle(&self.lines(first, last), syntax);
+
+        self.replace_lines(first, last, replacement);
+    }
+
+    // 按照偏好设置清理文档，作为一次可撤销的编辑，光标尽量保持在原来的位置。
+    //
+    // .editorconfig 中的 trim_trailing_whitespace 优先于偏好设置。
+    pub fn clean_up(&mut self, preferences: &Preferences) {
+        if self.is_read_only {
+            return;
+        }
+
+        let text = self.context.full_rope();
+
+        match self.cleaned(&text, preferences) {
+            Some(cleaned) if text != cleaned.as_str() => self.replace_text(cleaned),
+            _ => {}
+        }
+    }
+
+    // 返回按照偏好设置清理后的文本，不修改文档。没有启用任何清理时返回 None，不复制文档。
+    fn cleaned(&self, text: &Rope, preferences: &Preferences) -> Option<String> {
+        let mut preferences = preferences.clone();
+        if let Some(trim) = self.editorconfig.trim_trailing_whitespace {
+            preferences.trim_trailing_whitespace = trim;
+        }
+
+        cleanup::is_enabled(&preferences)
+            .then(|| cleanup::apply(&text.to_string(), &preferences, self.rules))
+    }
+
+    // 用新的文本替换整个文档，作为一次可撤销的编辑，光标尽量保持在原来的位置。
+    pub fn replace_text(&mut self, text: String) {
+        self.unfold_all();
+
+        let (line, _) = self.context.cursor_position();
+        let column = self.line_before_cursor().chars().count();
+
+        let last = self.context.line_count().saturating_sub(1);
+        self.replace_lines(0, last, text);
+
+        self.context.move_to(line, column);
+    }
+
+    // 返回选区覆盖的第一行和最后一行，没有选区时返回光标所在的行。
+    //
+    // 编辑器只提供选中的文本，光标可能位于选区的任意一端，
+    // 因此先假设选区在光标之上，如果文本对不上再认为选区在光标之下。
+    pub fn selected_lines(&self) -> (usize, usize) {
+        let (line, _) = self.context.cursor_position();
+
+        let Some(selection) = self.context.selection() else {
+            return (line, line);
+        };
+
+        let span = selection.matches('\n').count();
+        let above = line.saturating_sub(span);
+        let text: Vec<_> = (above..=line)
+            .filter_map(|index| self.context.line(index).map(|line| line.to_string()))
+            .collect();
+
+        if text.join("\n").contains(&selection) {
+            (above, line)
+        } else {
+            let last = (line + span).min(self.context.line_count().saturating_sub(1));
+            (line, last)
+        }
+    }
+
+    // 返回指定范围内（包含两端）每一行的文本，折叠的区域中被折叠的行也包括在内。
+    //
+    // 用 replace_lines 替换同一范围时，这些被折叠的行会先展开，因此替换后不会丢失。
+    pub fn lines(&self, first: usize, last: usize) -> Vec<String> {
+        (first..=last)
+            .filter_map(|index| {
+                self.context
+                    .line(index)
+                    .map(|line| (index, line.to_string()))
+            })
+            .flat_map(|(index, line)| {
+                let hidden = self.context.hidden(index).unwrap_or_default();
+
+                std::iter::once(line).chain(hidden.split('\n').skip(1).map(str::to_string))
+            })
+            .collect()
+    }
+
+    // 用新的文本替换指定范围内（包含两端）的行，作为一次可撤销的编辑。
+    pub fn replace_lines(&mut self, first: usize, last: usize, replacement: String) {
+        self.select_lines(first, last);
+        self.edit(Action::Edit(Edit::Paste(Arc::new(replacement))));
+    }
+
+    // 用新的文本替换一行中指定的字符范围，作为一次可撤销的编辑。
+    pub fn replace_columns(&mut self, line: usize, columns: Range<usize>, replacement: String) {
+        self.context.move_to(line, columns.start);
+        for _ in columns {
+            self.context.edit(Action::Select(Motion::Right));
+        }
+        self.edit(Action::Edit(Edit::Paste(Arc::new(replacement))));
+    }
+
+    // 按列粘贴复制的列选择：从光标所在的列开始，每一行文本粘贴到依次向下的一行中，作为一次可撤销的编辑。
+    //
+    // 比光标所在的列短的行先用空格补齐，超出文档末尾时添加新的行。
+    pub fn paste_block(&mut self, block: &str) {
+        let block: Vec<&str> = block.lines().collect();
+
+        if self.is_read_only || block.is_empty() {
+            return;
+        }
+
+        if self.context.selection().is_some() {
+            self.edit(Action::Edit(Edit::Delete));
+        }
+
+        let (line, _) = self.context.cursor_position();
+        let column = self.line_before_cursor().chars().count();
+        let last = (line + block.len())
+            .min(self.context.line_count())
+            .saturating_sub(1);
+
+        let existing = self.lines(line, last);
+        let mut lines: Vec<String> = block
+            .iter()
+            .enumerate()
+            .map(|(index, text)| {
+                let mut line = existing.get(index).cloned().unwrap_or_default();
+                let length = line.chars().count();
+
+                if length < column {
+                    line.push_str(&" ".repeat(column - length));
+                }
+
+                let at = line
+                    .char_indices()
+                    .nth(column)
+                    .map_or(line.len(), |(index, _)| index);
+                line.insert_str(at, text);
+                line
+            })
+            .collect();
+        lines.extend(existing.into_iter().skip(block.len()));
+
+        self.replace_lines(line, last, lines.join("\n"));
+
+        let end = block.last().map_or(0, |text| text.chars().count());
+        self.move_cursor_to(line + block.len().saturating_sub(1), column + end);
+    }
+
+    // 选中整个文档。
+    pub fn select_all(&mut self) {
+        self.context.edit(Action::Move(Motion::DocumentStart));
+        self.context.edit(Action::Select(Motion::DocumentEnd));
+    }
+
+    // 选中指定范围内（包含两端）的行，不包括最后一行的换行符。
+    pub fn select_lines(&mut self, first: usize, last: usize) {
+        self.context.move_to(first, 0);
+        self.context.select_to(last, usize::MAX);
+    }
+
+    // 对选中的文本应用变换，作为一次可撤销的编辑。
+    //
+    // 以行为单位的变换作用于选区覆盖的所有行，变换后这些行保持选中；
+    // 其余变换作用于选中的文本，没有选区时作用于光标所在的行。
+    pub fn transform(&mut self, transform: Transform) -> Result<(), String> {
+        if self.is_read_only {
+            return Ok(());
+        }
+
+        if transform.is_line_based() {
+            let (first, last) = self.selected_lines();
+            let lines = transform.apply_lines(self.lines(first, last));
+            let count = lines.len();
+
+            self.replace_lines(first, last, lines.join("\n"));
+            self.select_lines(first, first + count.saturating_sub(1));
+            return Ok(());
+        }
+
+        self.replace_selection(|text| transform.apply(text))
+    }
+
+    // 返回选中的文本，没有选区时返回光标所在的行，即 replace_selection 要替换的文本。
+    pub fn selection_or_line(&self) -> String {
+        match self.context.selection() {
+            Some(selection) => selection,
+            None => {
+                let (line, _) = self.context.cursor_position();
+                self.lines(line, line).concat()
+            }
+        }
+    }
+
+    // 用 replace 的结果替换选中的文本，没有选区时替换光标所在的行。
+    pub fn replace_selection(
+        &mut self,
+        replace: impl FnOnce(&str) -> Result<String, String>,
+    ) -> Result<(), String> {
+        if self.is_read_only {
+            return Ok(());
+        }
+
+        match self.context.selection() {
+            Some(selection) => {
+                let replacement = replace(&selection)?;
+                self.edit(Action::Edit(Edit::Paste(Arc::new(replacement))));
+            }
+            None => {
+                let (line, _) = self.context.cursor_position();
+                let replacement = replace(&self.lines(line, line).concat())?;
+                self.replace_lines(line, line, replacement);
+            }
+        }
+
+        Ok(())
+    }
+
+    // 在光标所在的行或选中的行下方插入一份副本，光标或选区随之移到副本中。
+    pub fn duplicate_lines(&mut self) {
+        let (first, last) = self.selected_lines();
+        let has_selection = self.context.selection().is_some();
+        let (line, _) = self.context.cursor_position();
+        let column = self.line_before_cursor().chars().count();
+
+        let text = self.lines(first, last).join("\n");
+        self.replace_lines(first, last, format!("{text}\n{text}"));
+
+        let count = last - first + 1;
+
+        if has_selection {
+            self.select_lines(first + count, last + count);
+        } else {
+            self.context.move_to(line + count, column);
+        }
+    }
+
+    // 剪切从光标到行尾的文本，光标已经在行尾时剪切换行符，返回剪切的文本。
+    pub fn kill_line(&mut self) -> Option<String> {
+        if self.is_read_only {
+            return None;
+        }
+
+        let (line, _) = self.context.cursor_position();
+        let column = self.line_before_cursor().chars().count();
+        let length = self.lines(line, line).concat().chars().count();
+
+        // 忽略原有的选区，从光标处开始选择。
+        self.context.move_to(line, column);
+
+        if column < length {
+            self.context.select_to(line, length);
+        } else if line + 1 < self.context.line_count() {
+            self.context.edit(Action::Select(Motion::Right));
+        } else {
+            return None;
+        }
+
+        let text = self.context.selection()?;
+        self.edit(Action::Edit(Edit::Delete));
+
+        Some(text)
+    }
+
+    // 将光标所在的行或选中的行与上方或下方的一行交换位置，到达文档的开头或末尾时不做任何事。
+    pub fn move_lines(&mut self, up: bool) {
+        let (first, last) = self.selected_lines();
+        let has_selection = self.context.selection().is_some();
+        let (line, _) = self.context.cursor_position();
+        let column = self.line_before_cursor().chars().count();
+
+        let (start, end) = if up {
+            if first == 0 {
+                return;
+            }
+            (first - 1, last)
+        } else {
+            if last + 1 >= self.context.line_count() {
+                return;
+            }
+            (first, last + 1)
+        };
+
+        let mut lines = self.lines(start, end);
+        if up {
+            lines.rotate_left(1);
+        } else {
+            lines.rotate_right(1);
+        }
+        self.replace_lines(start, end, lines.join("\n"));
+
+        let moved = |line: usize| if up { line - 1 } else { line + 1 };
+
+        if has_selection {
+            self.select_lines(moved(first), moved(last));
+        } else {
+            self.context.move_to(moved(line), column);
+        }
+    }
+
+    // 返回光标所在行中光标之前的文本。
+    fn line_before_cursor(&self) -> String {
+        let (line, column) = self.context.cursor_position();
+
+        self.context
+            .line(line)
+            .map(|text| text.get(..column).unwrap_or(&text).to_string())
+            .unwrap_or_default()
+    }
+
+    // 撤销最近一次编辑。
+    pub fn undo(&mut self) {
+        if let Some(snapshot) = self.history.undo(self.snapshot()) {
+            self.restore(snapshot);
+        }
+    }
+
+    // 重做最近一次撤销的编辑。
+    pub fn redo(&mut self) {
+        if let Some(snapshot) = self.history.redo(self.snapshot()) {
+            self.restore(snapshot);
+        }
+    }
+
+    // 记录当前内容和光标位置的快照。
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            text: self.context.full_rope(),
+            cursor: self.context.cursor(),
+        }
+    }
+
+    // 使用快照替换当前内容，并恢复光标位置。
+    fn restore(&mut self, snapshot: Snapshot) {
+        let (line, column) = snapshot.cursor;
+
+        self.context = Document::with_rope(snapshot.text);
+        self.statistics = Statistics::new(&self.context);
+        self.move_cursor_to(line, column);
+        self.is_dirty = true;
+        self.revision += 1;
+        self.sync_split();
+    }
+
+    // 将光标移动到指定的行和列，超出范围时停在文档或行的末尾。
+    pub fn move_cursor_to(&mut self, line: usize, column: usize) {
+        self.context.move_to(line, column);
+    }
+
+    // 将光标移动到完整文本中指定的行和列，这一行被折叠时先展开所在的区域。
+    pub fn reveal(&mut self, line: usize, column: usize) {
+        let mut display = self.context.display_line(line);
+
+        while self.context.full_line(display) != line && self.context.is_folded(display) {
+            self.unfold(display);
+            display = self.context.display_line(line);
+        }
+
+        self.context.move_to(display, column);
+    }
+
+    // 判断标签页是否为尚未使用过的空白文档，打开文件时可以直接复用。
+    pub fn is_blank(&self) -> bool {
+        self.path.is_none()
+            && self.url.is_none()
+            && self.remote.is_none()
+            && self.scratch.is_none()
+            && self.context.text().trim().is_empty()
+    }
+
+    // 判断标签页是否有需要保存的修改，空白的新文档和临时缓冲区不算在内。
+    pub fn has_unsaved_changes(&self) -> bool {
+        self.is_dirty && !self.is_blank() && self.scratch.is_none()
+    }
+
+    // 返回标签栏中显示的标题，未保存的修改用圆点标记。
+    pub fn title(&self) -> String {
+        let scratch = self.scratch_name();
+        let name = self.name().or(scratch.as_deref()).unwrap_or(tr("New File"));
+
+        if self.is_dirty && self.path.is_some() {
+            format!("{name} •")
+        } else {
+            name.to_string()
+        }
+    }
+
+    // 返回临时缓冲区的名称，例如 untitled-1，不是临时缓冲区时返回 None。
+    pub fn scratch_name(&self) -> Option<String> {
+        self.scratch.map(|number| format!("untitled-{number}"))
+    }
+
+    // 返回文件的完整路径，未保存的文件返回标签页的标题。用于打印和导出时的页眉和标题。
+    pub fn full_title(&self) -> String {
+        self.path
+            .as_deref()
+            .map_or_else(|| self.title(), |path| path.display().to_string())
+    }
+
+    // 判断是否为 Markdown 文件。
+    pub fn is_markdown(&self) -> bool {
+        matches!(self.extension().as_str(), "md" | "markdown")
+    }
+
+    // 返回文档中的函数、类型和标题等符号。
+    pub fn outline(&self) -> Rc<[Symbol]> {
+        self.symbols
+            .get(self.context.rope(), self.revision, &self.extension())
+    }
+
+    // 返回 tree-sitter 解析得到的语法树，不支持的语言返回 None。
+    pub fn syntax_tree(&self) -> Option<syntax_tree::Snapshot> {
+        self.tree
+            .get(self.context.rope(), self.revision, &self.extension())
+    }
+
+    // 返回文件名，从网址打开的文档使用网址中的最后一段。
+    fn name(&self) -> Option<&str> {
+        match (&self.path, &self.remote, &self.url) {
+            (Some(path), _, _) => path.file_name()?.to_str(),
+            (None, Some(location), _) => location.name(),
+            (None, None, Some(url)) => url
+                .split(['?', '#'])
+                .next()?
+                .rsplit('/')
+                .next()
+                .filter(|name| !name.is_empty()),
+            (None, None, None) => None,
+        }
+    }
+
+    // 返回代码高亮使用的文件扩展名，手动选择了语言时使用该语言的扩展名。
+    //
+    // 文件名没有扩展名时根据内容推断，例如脚本第一行的 #!，仍然无法确定时按 Rust 处理。
+    pub fn extension(&self) -> String {
+        if let Some(language) = &self.language {
+            return language.clone();
+        }
+
+        self.name()
+            .and_then(|name| Path::new(name).extension()?.to_str())
+            .or_else(|| filetype::detect(self.context.rope()))
+            .unwrap_or("rs")
+            .to_string()
+    }
+}
+
+// 计算文档内容的指纹，用于判断磁盘上的文件是否在读取或保存之后被其他程序修改。
+//
+// 依次传入文本的各个片段，例如 rope 的各个块，结果与传入整个文本相同。只在末尾添加或删除空白也算作修改。
+pub fn fingerprint<'a>(chunks: impl IntoIterator<Item = &'a str>) -> u64 {
+    let mut hasher = DefaultHasher::new();
+
+    for chunk in chunks {
+        hasher.write(chunk.as_bytes());
+    }
+
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn restores_the_cursor_after_non_ascii_text() {
+        let text = "第一行\n中文 text";
+        let mut tab = Tab::with_text(0, text);
+        tab.move_cursor_to(1, 4);
+
+        let (line, column) = tab.context.cursor();
+        let mut reloaded = Tab::with_text(0, text);
+        reloaded.move_cursor_to(line, column);
+
+        assert_eq!(reloaded.context.cursor(), (1, 4));
+    }
+
+    #[test]
+    fn undo_restores_the_cursor_in_characters() {
+        let mut tab = Tab::with_text(0, "中文\n");
+        tab.move_cursor_to(0, 2);
+        tab.edit(Action::Edit(Edit::Insert('!')));
+        tab.undo();
+
+        assert_eq!(tab.context.cursor(), (0, 2));
+    }
+}
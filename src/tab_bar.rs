@@ -0,0 +1,299 @@
+use crate::Message;
+use iced::advanced::layout::{self, Layout};
+use iced::advanced::renderer::{self, Renderer as _};
+use iced::advanced::widget::{tree, Operation, Tree, Widget};
+use iced::advanced::{overlay, Clipboard, Shell};
+use iced::event::{self, Event};
+use iced::mouse;
+use iced::widget::row;
+use iced::{Color, Element, Length, Point, Rectangle, Renderer, Theme};
+
+// 按下后移动超过这个距离才开始拖动标签，避免点击时轻微的移动被当作拖动。
+const DRAG_THRESHOLD: f32 = 5.0;
+
+// 拖到标签栏上下超过这个距离后松开时，把标签移到新窗口中。
+const TEAR_OFF_DISTANCE: f32 = 40.0;
+
+// 拖动时显示插入位置的竖线的宽度。
+const MARKER_WIDTH: f32 = 2.0;
+
+// 创建可以拖动标签的标签栏，tabs 为各个标签按钮，按顺序排成一行。
+//
+// 在标签栏中拖动标签并松开时发送 on_move 消息，附带标签原来的和新的位置；
+// 拖出标签栏一段距离后松开时发送 on_tear_off 消息，附带标签的位置。
+// 点击和关闭按钮照常交给标签按钮处理。
+pub fn tab_bar<'a>(
+    tabs: Vec<Element<'a, Message>>,
+    spacing: f32,
+    on_move: fn(usize, usize) -> Message,
+    on_tear_off: fn(usize) -> Message,
+) -> Element<'a, Message> {
+    Element::new(TabBar {
+        content: row(tabs).spacing(spacing).into(),
+        spacing,
+        on_move,
+        on_tear_off,
+    })
+}
+
+// 处理标签拖动的控件，其余的行为都交给内部的标签按钮处理。
+struct TabBar<'a> {
+    content: Element<'a, Message>,        // 排成一行的标签按钮。
+    spacing: f32,                         // 标签之间的间距。
+    on_move: fn(usize, usize) -> Message, // 在标签栏中移动标签时发送的消息。
+    on_tear_off: fn(usize) -> Message,    // 把标签拖出标签栏时发送的消息。
+}
+
+// 定义标签栏的拖动状态。
+#[derive(Debug, Default)]
+struct State {
+    pressed: Option<(usize, Point)>, // 按下的标签的位置和按下鼠标的位置。
+    is_dragging: bool,               // 是否已经开始拖动。
+}
+
+// 定义松开正在拖动的标签时的结果。
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Drop {
+    Insert(usize), // 插入到第几个标签之前，等于标签数时放在最后。
+    TearOff,       // 拖出了标签栏，移到新窗口中。
+}
+
+impl TabBar<'_> {
+    // 返回在标签栏中松开鼠标时的结果：按照各个标签的中点决定插入的位置。
+    fn drop(&self, layout: Layout<'_>, position: Point) -> Drop {
+        let bounds = layout.bounds();
+
+        if position.y < bounds.y - TEAR_OFF_DISTANCE
+            || position.y > bounds.y + bounds.height + TEAR_OFF_DISTANCE
+        {
+            return Drop::TearOff;
+        }
+
+        Drop::Insert(
+            layout
+                .children()
+                .filter(|tab| tab.bounds().center_x() < position.x)
+                .count(),
+        )
+    }
+}
+
+impl Widget<Message, Renderer> for TabBar<'_> {
+    fn tag(&self) -> tree::Tag {
+        tree::Tag::of::<State>()
+    }
+
+    fn state(&self) -> tree::State {
+        tree::State::new(State::default())
+    }
+
+    fn children(&self) -> Vec<Tree> {
+        vec![Tree::new(&self.content)]
+    }
+
+    fn diff(&self, tree: &mut Tree) {
+        tree.diff_children(std::slice::from_ref(&self.content));
+    }
+
+    fn width(&self) -> Length {
+        self.content.as_widget().width()
+    }
+
+    fn height(&self) -> Length {
+        self.content.as_widget().height()
+    }
+
+    fn layout(
+        &self,
+        tree: &mut Tree,
+        renderer: &Renderer,
+        limits: &layout::Limits,
+    ) -> layout::Node {
+        self.content
+            .as_widget()
+            .layout(&mut tree.children[0], renderer, limits)
+    }
+
+    fn operate(
+        &self,
+        tree: &mut Tree,
+        layout: Layout<'_>,
+        renderer: &Renderer,
+        operation: &mut dyn Operation<Message>,
+    ) {
+        self.content
+            .as_widget()
+            .operate(&mut tree.children[0], layout, renderer, operation);
+    }
+
+    fn on_event(
+        &mut self,
+        tree: &mut Tree,
+        event: Event,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        renderer: &Renderer,
+        clipboard: &mut dyn Clipboard,
+        shell: &mut Shell<'_, Message>,
+        viewport: &Rectangle,
+    ) -> event::Status {
+        let state = tree.state.downcast_mut::<State>();
+
+        match event {
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => {
+                state.is_dragging = false;
+                state.pressed = cursor.position().and_then(|position| {
+                    layout
+                        .children()
+                        .position(|tab| tab.bounds().contains(position))
+                        .map(|index| (index, position))
+                });
+            }
+            Event::Mouse(mouse::Event::CursorMoved { position }) => {
+                if let Some((_, start)) = state.pressed {
+                    state.is_dragging |= start.distance(position) > DRAG_THRESHOLD;
+                }
+            }
+            _ => {}
+        }
+
+        let is_released = matches!(
+            event,
+            Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left))
+        );
+
+        // 松开时先交给标签按钮，让按下的按钮恢复原状；拖到别处松开时按钮不会发送消息。
+        let status = self.content.as_widget_mut().on_event(
+            &mut tree.children[0],
+            event,
+            layout,
+            cursor,
+            renderer,
+            clipboard,
+            shell,
+            viewport,
+        );
+
+        if !is_released {
+            return status;
+        }
+
+        let state = tree.state.downcast_mut::<State>();
+        let pressed = state.pressed.take();
+        let is_dragging = std::mem::take(&mut state.is_dragging);
+
+        if !is_dragging {
+            return status;
+        }
+
+        let (Some((from, _)), Some(position)) = (pressed, cursor.position()) else {
+            return status;
+        };
+
+        match self.drop(layout, position) {
+            Drop::TearOff => shell.publish((self.on_tear_off)(from)),
+            Drop::Insert(index) => {
+                let to = if index > from { index - 1 } else { index };
+
+                if to != from {
+                    shell.publish((self.on_move)(from, to));
+                }
+            }
+        }
+
+        event::Status::Captured
+    }
+
+    fn mouse_interaction(
+        &self,
+        tree: &Tree,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        viewport: &Rectangle,
+        renderer: &Renderer,
+    ) -> mouse::Interaction {
+        if tree.state.downcast_ref::<State>().is_dragging {
+            return mouse::Interaction::Grabbing;
+        }
+
+        self.content.as_widget().mouse_interaction(
+            &tree.children[0],
+            layout,
+            cursor,
+            viewport,
+            renderer,
+        )
+    }
+
+    fn draw(
+        &self,
+        tree: &Tree,
+        renderer: &mut Renderer,
+        theme: &Theme,
+        style: &renderer::Style,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        viewport: &Rectangle,
+    ) {
+        self.content.as_widget().draw(
+            &tree.children[0],
+            renderer,
+            theme,
+            style,
+            layout,
+            cursor,
+            viewport,
+        );
+
+        // 拖动时在插入的位置画一条竖线，拖出标签栏时不显示。
+        if !tree.state.downcast_ref::<State>().is_dragging {
+            return;
+        }
+
+        let Some(position) = cursor.position() else {
+            return;
+        };
+
+        let Drop::Insert(index) = self.drop(layout, position) else {
+            return;
+        };
+
+        let bounds = layout.bounds();
+        let x = match layout.children().nth(index) {
+            Some(tab) => tab.bounds().x - self.spacing / 2.0,
+            None => {
+                layout
+                    .children()
+                    .last()
+                    .map_or(bounds.x, |tab| tab.bounds().x + tab.bounds().width)
+                    + self.spacing / 2.0
+            }
+        };
+
+        renderer.fill_quad(
+            renderer::Quad {
+                bounds: Rectangle {
+                    x: x - MARKER_WIDTH / 2.0,
+                    y: bounds.y,
+                    width: MARKER_WIDTH,
+                    height: bounds.height,
+                },
+                border_radius: 0.0.into(),
+                border_width: 0.0,
+                border_color: Color::TRANSPARENT,
+            },
+            theme.palette().primary,
+        );
+    }
+
+    fn overlay<'b>(
+        &'b mut self,
+        tree: &'b mut Tree,
+        layout: Layout<'_>,
+        renderer: &Renderer,
+    ) -> Option<overlay::Element<'b, Message, Renderer>> {
+        self.content
+            .as_widget_mut()
+            .overlay(&mut tree.children[0], layout, renderer)
+    }
+}
@@ -0,0 +1,301 @@
+use crate::i18n::{tr, trf};
+use crate::tab::Tab;
+use crate::Message;
+use iced::widget::{button, column, container, row, scrollable, text, text_input};
+use iced::{theme, Element, Length};
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::rc::Rc;
+
+// 表格中最多显示的行数，行数更多时提示切换回文本编辑。
+const MAX_ROWS: usize = 500;
+
+// 每一列的宽度。
+const COLUMN_WIDTH: f32 = 160.0;
+
+// 返回指定扩展名的文件使用的分隔符，不是 CSV 或 TSV 文件时返回 None。
+pub fn delimiter(extension: &str) -> Option<char> {
+    match extension {
+        "csv" => Some(','),
+        "tsv" => Some('\t'),
+        _ => None,
+    }
+}
+
+// 定义文件中的一条记录。带引号的字段可以包含换行符，因此一条记录可能占据多行。
+#[derive(Debug, Clone)]
+pub struct Record {
+    pub line: usize,         // 记录开始的行，从 0 开始。
+    pub lines: usize,        // 记录占据的行数。
+    pub fields: Vec<String>, // 各个字段的值，已经去掉引号。
+}
+
+// 按 RFC 4180 的规则解析 CSV 或 TSV 文本，忽略空行。
+pub fn parse(text: &str, delimiter: char) -> Vec<Record> {
+    let mut records = Vec::new();
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut line = 0;
+    let mut start = 0;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' if quoted => quoted = false,
+            '"' if field.is_empty() => quoted = true,
+            '\n' if quoted => {
+                field.push('\n');
+                line += 1;
+            }
+            '\n' => {
+                fields.push(std::mem::take(&mut field));
+
+                if fields.len() > 1 || !fields[0].is_empty() {
+                    records.push(Record {
+                        line: start,
+                        lines: line + 1 - start,
+                        fields: std::mem::take(&mut fields),
+                    });
+                }
+
+                fields.clear();
+                line += 1;
+                start = line;
+            }
+            '\r' if !quoted && chars.peek() == Some(&'\n') => {}
+            c if c == delimiter && !quoted => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+
+    if !field.is_empty() || !fields.is_empty() {
+        fields.push(field);
+        records.push(Record {
+            line: start,
+            lines: line + 1 - start,
+            fields,
+        });
+    }
+
+    records
+}
+
+// 将一条记录转换回文本，只给包含分隔符、引号或换行符的字段加上引号。
+pub fn serialize(fields: &[String], delimiter: char) -> String {
+    fields
+        .iter()
+        .map(|field| {
+            if field.contains([delimiter, '"', '\n', '\r']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field.clone()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(&delimiter.to_string())
+}
+
+// 定义排序的方向。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Order {
+    Ascending,
+    Descending,
+}
+
+// 定义 CSV 或 TSV 文件的表格视图。
+//
+// 表格从文档的内容生成，修改单元格时直接修改文档，因此两种视图总是同步的。
+// 解析和排序的结果按文档版本缓存，只在文档或排序变化后重新生成，不必每次绘制界面时都解析。
+#[derive(Debug)]
+pub struct Table {
+    pub tab: usize,                  // 显示为表格的标签页标识。
+    sort: Option<(usize, Order)>,    // 排序的列及方向，只影响显示的顺序，不修改文档。
+    rows: RefCell<Option<Rc<Rows>>>, // 缓存的记录及显示顺序。
+}
+
+// 定义解析并排序后的表格内容。
+#[derive(Debug)]
+struct Rows {
+    revision: usize,              // 解析时文档内容的版本号。
+    length: usize,                // 解析时文档的字符数，重新加载文件后版本号可能相同。
+    sort: Option<(usize, Order)>, // 生成显示顺序时使用的排序。
+    records: Rc<[Record]>,        // 文件中的所有记录，第一条为列标题。
+    order: Vec<usize>,            // 按排序显示的记录序号，不包含列标题。
+}
+
+impl Table {
+    // 创建标签页的表格视图，按文件中的顺序显示。
+    pub fn new(tab: usize) -> Self {
+        Self {
+            tab,
+            sort: None,
+            rows: RefCell::default(),
+        }
+    }
+
+    // 返回记录及显示顺序，文档和排序都没有变化时使用缓存的结果，只改变排序时不重新解析。
+    fn rows(&self, tab: &Tab, delimiter: char) -> Rc<Rows> {
+        let revision = tab.revision;
+        let length = tab.context.rope().len_chars();
+        let mut entry = self.rows.borrow_mut();
+
+        let records = match entry.as_ref() {
+            Some(rows) if rows.revision == revision && rows.length == length => {
+                if rows.sort == self.sort {
+                    return rows.clone();
+                }
+                rows.records.clone()
+            }
+            _ => parse(&tab.context.text(), delimiter).into(),
+        };
+
+        let rows = Rc::new(Rows {
+            revision,
+            length,
+            sort: self.sort,
+            order: order(&records, self.sort),
+            records,
+        });
+        *entry = Some(rows.clone());
+        rows
+    }
+
+    // 返回指定序号的记录，用于修改单元格时找到记录所在的行。
+    pub fn record(&self, tab: &Tab, delimiter: char, index: usize) -> Option<Record> {
+        self.rows(tab, delimiter).records.get(index).cloned()
+    }
+
+    // 点击列标题时按这一列排序，再次点击时反转方向，第三次点击时恢复文件中的顺序。
+    pub fn toggle_sort(&mut self, column: usize) {
+        self.sort = match self.sort {
+            Some((sorted, Order::Ascending)) if sorted == column => {
+                Some((column, Order::Descending))
+            }
+            Some((sorted, Order::Descending)) if sorted == column => None,
+            _ => Some((column, Order::Ascending)),
+        };
+    }
+
+    // 创建表格的视图：第一条记录作为列标题，其余记录按排序显示，每个单元格都可以编辑。
+    pub fn view(&self, tab: &Tab, delimiter: char) -> Element<'_, Message> {
+        let rows = self.rows(tab, delimiter);
+        let (records, is_read_only) = (&rows.records, tab.is_read_only);
+
+        let Some((header, body)) = records.split_first() else {
+            return container(text(tr("The file has no rows")))
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .center_x()
+                .center_y()
+                .into();
+        };
+
+        let columns = records
+            .iter()
+            .map(|record| record.fields.len())
+            .max()
+            .unwrap_or_default();
+
+        let cell = |index: usize, column: usize| {
+            let value = records[index]
+                .fields
+                .get(column)
+                .map(String::as_str)
+                .unwrap_or_default();
+
+            let input = text_input("", value).width(COLUMN_WIDTH).size(14);
+
+            if is_read_only {
+                input
+            } else {
+                input.on_input(move |value| Message::CellEdited(index, column, value))
+            }
+        };
+
+        let headers = row((0..columns)
+            .map(|column| {
+                let arrow = match self.sort {
+                    Some((sorted, Order::Ascending)) if sorted == column => " ▲",
+                    Some((sorted, Order::Descending)) if sorted == column => " ▼",
+                    _ => "",
+                };
+
+                let label = header
+                    .fields
+                    .get(column)
+                    .map(String::as_str)
+                    .unwrap_or_default();
+
+                button(text(format!("{label}{arrow}")).size(14))
+                    .on_press(Message::TableSorted(column))
+                    .width(COLUMN_WIDTH)
+                    .style(theme::Button::Secondary)
+                    .into()
+            })
+            .collect());
+
+        let cells = rows.order.iter().take(MAX_ROWS).map(|&index| {
+            row((0..columns)
+                .map(|column| cell(index, column).into())
+                .collect())
+            .into()
+        });
+
+        let mut table = column![headers].push(column(cells.collect()));
+
+        if body.len() > MAX_ROWS {
+            table = table.push(
+                text(trf(
+                    "Showing the first {shown} of {count} rows, switch to the text view to see the rest",
+                    &[("shown", &MAX_ROWS), ("count", &body.len())],
+                ))
+                .size(14),
+            );
+        }
+
+        scrollable(table.spacing(5))
+            .direction(scrollable::Direction::Both {
+                vertical: scrollable::Properties::default(),
+                horizontal: scrollable::Properties::default(),
+            })
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .into()
+    }
+}
+
+// 返回除列标题外的记录按 sort 排序后的序号，没有排序时按文件中的顺序。
+fn order(records: &[Record], sort: Option<(usize, Order)>) -> Vec<usize> {
+    let mut order: Vec<usize> = (1..records.len()).collect();
+
+    if let Some((column, direction)) = sort {
+        order.sort_by(|&a, &b| {
+            let ordering = compare(records[a].fields.get(column), records[b].fields.get(column));
+
+            match direction {
+                Order::Ascending => ordering,
+                Order::Descending => ordering.reverse(),
+            }
+        });
+    }
+
+    order
+}
+
+// 比较两个单元格：都是数字时按数值比较，否则按文本比较，空的单元格排在最后。
+fn compare(a: Option<&String>, b: Option<&String>) -> Ordering {
+    match (a.filter(|a| !a.is_empty()), b.filter(|b| !b.is_empty())) {
+        (Some(a), Some(b)) => match (a.trim().parse::<f64>(), b.trim().parse::<f64>()) {
+            (Ok(a), Ok(b)) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+            _ => a.cmp(b),
+        },
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
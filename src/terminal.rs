@@ -0,0 +1,285 @@
+use crate::i18n::tr;
+use crate::{Error, FileError, Message, Operation};
+use iced::futures::channel::mpsc;
+use iced::futures::SinkExt;
+use iced::widget::{
+    button, column, container, horizontal_space, row, scrollable, text, text_input,
+};
+use iced::{subscription, theme, Color, Element, Length, Subscription};
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::process::Command;
+
+// 最多保留的输出行数，超过时丢弃最早的输出。
+const MAX_LINES: usize = 5000;
+
+// 终端面板的高度。
+const HEIGHT: f32 = 220.0;
+
+// 标准错误输出的颜色。
+const ERROR_COLOR: Color = Color::from_rgb(0.85, 0.35, 0.3);
+
+// 定义运行中的命令发送的事件。
+#[derive(Debug, Clone)]
+pub enum Event {
+    Output(String, bool),               // 输出了一行，附带是否来自标准错误输出。
+    Exited(Result<Option<i32>, Error>), // 命令结束，附带退出码；无法启动命令时返回错误。
+}
+
+// 定义终端中的一行输出。
+#[derive(Debug, Clone)]
+struct Line {
+    text: String,   // 这一行的文本。
+    is_error: bool, // 是否来自标准错误输出。
+}
+
+// 定义正在运行的命令。
+#[derive(Debug, Clone)]
+pub struct Process {
+    pub id: usize,      // 命令的唯一标识，用于丢弃已经结束的命令的输出。
+    command: String,    // 交给 shell 执行的命令。
+    directory: PathBuf, // 命令的工作目录。
+}
+
+// 定义终端面板的状态。
+#[derive(Debug, Default)]
+pub struct Terminal {
+    pub input: String,            // 输入框中的命令。
+    pub process: Option<Process>, // 正在运行的命令。
+    lines: VecDeque<Line>,        // 已经输出的内容。
+    next_id: usize,               // 下一个命令的唯一标识。
+}
+
+impl Terminal {
+    // 返回输入框的标识，用于在打开面板时获取焦点。
+    pub fn input_id() -> text_input::Id {
+        text_input::Id::new("terminal-input")
+    }
+
+    // 返回输出区域的标识，用于在输出新内容时滚动到底部。
+    pub fn output_id() -> scrollable::Id {
+        scrollable::Id::new("terminal-output")
+    }
+
+    // 在指定目录中运行输入框中的命令，已经有命令在运行时不做任何事。
+    pub fn submit(&mut self, directory: PathBuf) {
+        if self.process.is_none() && !self.input.trim().is_empty() {
+            let command = std::mem::take(&mut self.input);
+            self.run(command, directory);
+        }
+    }
+
+    // 在指定目录中运行命令，已经有命令在运行时不做任何事。
+    pub fn run(&mut self, command: String, directory: PathBuf) {
+        if self.process.is_some() {
+            return;
+        }
+
+        self.push(format!("$ {command}"), false);
+        self.process = Some(Process {
+            id: self.next_id,
+            command,
+            directory,
+        });
+        self.next_id += 1;
+    }
+
+    // 结束正在运行的命令。移除订阅后子进程会被终止。
+    pub fn kill(&mut self) {
+        if self.process.take().is_some() {
+            self.push(String::from("[killed]"), true);
+        }
+    }
+
+    // 清空输出。
+    pub fn clear(&mut self) {
+        self.lines.clear();
+    }
+
+    // 处理命令发送的事件，忽略已经结束或被终止的命令的事件。
+    pub fn update(&mut self, id: usize, event: Event) {
+        if self.process.as_ref().map(|process| process.id) != Some(id) {
+            return;
+        }
+
+        match event {
+            Event::Output(line, is_error) => self.push(line, is_error),
+            Event::Exited(result) => {
+                self.process = None;
+
+                match result {
+                    Ok(Some(code)) => self.push(format!("[exited with code {code}]"), code != 0),
+                    Ok(None) => self.push(String::from("[terminated by signal]"), true),
+                    Err(error) => self.push(format!("[failed to start: {error:?}]"), true),
+                }
+            }
+        }
+    }
+
+    // 追加一行输出，超过上限时丢弃最早的一行。
+    fn push(&mut self, text: String, is_error: bool) {
+        if self.lines.len() >= MAX_LINES {
+            let _ = self.lines.pop_front();
+        }
+
+        self.lines.push_back(Line { text, is_error });
+    }
+
+    // 创建运行当前命令的订阅。
+    pub fn subscription(&self) -> Subscription<Message> {
+        match &self.process {
+            Some(process) => run(process.clone()),
+            None => Subscription::none(),
+        }
+    }
+
+    // 创建终端面板的视图。
+    pub fn view(&self) -> Element<'_, Message> {
+        let output = column(
+            self.lines
+                .iter()
+                .map(|line| {
+                    let label = text(&line.text).size(13);
+
+                    if line.is_error {
+                        label.style(ERROR_COLOR).into()
+                    } else {
+                        label.into()
+                    }
+                })
+                .collect(),
+        );
+
+        let input = text_input(tr("Run a shell command"), &self.input)
+            .id(Self::input_id())
+            .on_input(Message::TerminalInputChanged)
+            .on_submit(Message::TerminalSubmitted)
+            .size(14)
+            .padding(5);
+
+        let run_or_kill = if self.process.is_some() {
+            button(text(tr("Kill")).size(14))
+                .on_press(Message::TerminalKill)
+                .style(theme::Button::Destructive)
+        } else {
+            button(text(tr("Run")).size(14)).on_press(Message::TerminalSubmitted)
+        };
+
+        let controls = row![
+            input,
+            run_or_kill.padding([5, 10]),
+            button(text(tr("Clear")).size(14))
+                .on_press(Message::TerminalClear)
+                .padding([5, 10])
+                .style(theme::Button::Secondary),
+            horizontal_space(5),
+            button(text("×"))
+                .on_press(Message::ToggleTerminal)
+                .style(theme::Button::Text)
+        ]
+        .spacing(5);
+
+        container(
+            column![
+                scrollable(output.width(Length::Fill))
+                    .id(Self::output_id())
+                    .height(Length::Fill),
+                controls
+            ]
+            .spacing(5),
+        )
+        .width(Length::Fill)
+        .height(HEIGHT)
+        .padding(5)
+        .style(theme::Container::Box)
+        .into()
+    }
+}
+
+// 创建一个订阅，在 shell 中运行命令，逐行发送标准输出和标准错误输出，结束时发送退出码。
+//
+// 订阅被移除时子进程也会被终止。
+fn run(process: Process) -> Subscription<Message> {
+    let id = process.id;
+
+    subscription::channel(id, 100, move |mut output| async move {
+        let result = execute(&process, &output).await;
+
+        let _ = output
+            .send(Message::TerminalOutput(id, Event::Exited(result)))
+            .await;
+
+        // 命令结束后订阅会被移除，在此之前保持空闲。
+        loop {
+            std::future::pending::<()>().await;
+        }
+    })
+}
+
+// 启动子进程，转发它的输出，并等待它结束。
+async fn execute(process: &Process, output: &mpsc::Sender<Message>) -> Result<Option<i32>, Error> {
+    let (shell, flag) = if cfg!(windows) {
+        ("cmd", "/C")
+    } else {
+        ("sh", "-c")
+    };
+
+    let mut child = Command::new(shell)
+        .arg(flag)
+        .arg(&process.command)
+        .current_dir(&process.directory)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|error| {
+            Error::FileFailed(FileError::new(Operation::Run, Path::new(shell), &error))
+        })?;
+
+    let stdout = child
+        .stdout
+        .take()
+        .map(|stdout| forward(process.id, stdout, false, output.clone()));
+    let stderr = child
+        .stderr
+        .take()
+        .map(|stderr| forward(process.id, stderr, true, output.clone()));
+
+    let status = child.wait().await.map_err(|error| {
+        Error::FileFailed(FileError::new(Operation::Run, Path::new(shell), &error))
+    })?;
+
+    // 等待剩余的输出发送完毕后再报告退出码。
+    for task in stdout.into_iter().chain(stderr) {
+        let _ = task.await;
+    }
+
+    Ok(status.code())
+}
+
+// 在后台逐行读取子进程的输出并发送给应用程序。
+fn forward(
+    id: usize,
+    stream: impl AsyncRead + Unpin + Send + 'static,
+    is_error: bool,
+    mut output: mpsc::Sender<Message>,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut lines = BufReader::new(stream).lines();
+
+        while let Ok(Some(line)) = lines.next_line().await {
+            let event = Event::Output(line, is_error);
+
+            if output
+                .send(Message::TerminalOutput(id, event))
+                .await
+                .is_err()
+            {
+                break;
+            }
+        }
+    })
+}
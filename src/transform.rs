@@ -0,0 +1,166 @@
+use crate::i18n::{tr, trf};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use std::collections::HashSet;
+use std::fmt;
+
+// 定义作用于选中文本的变换。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transform {
+    SortLines,    // 按字母顺序排列各行。
+    ReverseLines, // 反转各行的顺序。
+    UniqueLines,  // 删除重复的行，保留第一次出现的行。
+    Uppercase,    // 转换为大写。
+    Lowercase,    // 转换为小写。
+    TitleCase,    // 每个单词首字母大写，其余字母小写。
+    Base64Encode, // 编码为 Base64。
+    Base64Decode, // 从 Base64 解码。
+}
+
+impl Transform {
+    pub const ALL: &'static [Self] = &[
+        Self::SortLines,
+        Self::ReverseLines,
+        Self::UniqueLines,
+        Self::Uppercase,
+        Self::Lowercase,
+        Self::TitleCase,
+        Self::Base64Encode,
+        Self::Base64Decode,
+    ];
+
+    // 判断变换是否以整行为单位：这类变换作用于选区覆盖的所有行，其余变换只作用于选中的文本。
+    pub fn is_line_based(self) -> bool {
+        matches!(
+            self,
+            Self::SortLines | Self::ReverseLines | Self::UniqueLines
+        )
+    }
+
+    // 变换一组完整的行。
+    pub fn apply_lines(self, mut lines: Vec<String>) -> Vec<String> {
+        match self {
+            Self::SortLines => lines.sort(),
+            Self::ReverseLines => lines.reverse(),
+            Self::UniqueLines => {
+                let mut seen = HashSet::new();
+                lines.retain(|line| seen.insert(line.clone()));
+            }
+            _ => {}
+        }
+
+        lines
+    }
+
+    // 变换一段文本，Base64 解码的结果不是有效的 UTF-8 文本时返回错误。
+    pub fn apply(self, text: &str) -> Result<String, String> {
+        match self {
+            Self::Uppercase => Ok(text.to_uppercase()),
+            Self::Lowercase => Ok(text.to_lowercase()),
+            Self::TitleCase => Ok(title_case(text)),
+            Self::Base64Encode => Ok(STANDARD.encode(text)),
+            Self::Base64Decode => {
+                let bytes = STANDARD
+                    .decode(text.trim())
+                    .map_err(|error| trf("Invalid Base64: {error}", &[("error", &error)]))?;
+
+                String::from_utf8(bytes)
+                    .map_err(|_| String::from(tr("The decoded Base64 is not valid UTF-8 text")))
+            }
+            _ => Ok(self
+                .apply_lines(text.split('\n').map(str::to_string).collect())
+                .join("\n")),
+        }
+    }
+}
+
+impl fmt::Display for Transform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(tr(match self {
+            Self::SortLines => "Sort Lines",
+            Self::ReverseLines => "Reverse Lines",
+            Self::UniqueLines => "Remove Duplicate Lines",
+            Self::Uppercase => "Transform to Uppercase",
+            Self::Lowercase => "Transform to Lowercase",
+            Self::TitleCase => "Transform to Title Case",
+            Self::Base64Encode => "Base64 Encode",
+            Self::Base64Decode => "Base64 Decode",
+        }))
+    }
+}
+
+// 把每个单词的首字母转换为大写，其余字母转换为小写。
+fn title_case(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut at_word_start = true;
+
+    for c in text.chars() {
+        if c.is_alphanumeric() {
+            if at_word_start {
+                result.extend(c.to_uppercase());
+            } else {
+                result.extend(c.to_lowercase());
+            }
+            at_word_start = false;
+        } else {
+            result.push(c);
+            at_word_start = c.is_whitespace() || c == '-' || c == '_';
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transforms_lines() {
+        let text = "pear\napple\npear\nfig";
+
+        assert_eq!(
+            Transform::SortLines.apply(text).unwrap(),
+            "apple\nfig\npear\npear"
+        );
+        assert_eq!(
+            Transform::ReverseLines.apply(text).unwrap(),
+            "fig\npear\napple\npear"
+        );
+        assert_eq!(
+            Transform::UniqueLines.apply(text).unwrap(),
+            "pear\napple\nfig"
+        );
+    }
+
+    #[test]
+    fn changes_case() {
+        assert_eq!(Transform::Uppercase.apply("Straße").unwrap(), "STRASSE");
+        assert_eq!(Transform::Lowercase.apply("ÀB").unwrap(), "àb");
+        assert_eq!(
+            Transform::TitleCase
+                .apply("hello wORLD it's snake_case-text")
+                .unwrap(),
+            "Hello World It's Snake_Case-Text"
+        );
+    }
+
+    #[test]
+    fn round_trips_base64() {
+        let encoded = Transform::Base64Encode.apply("编辑器").unwrap();
+
+        assert_eq!(encoded, "57yW6L6R5Zmo");
+        assert_eq!(
+            Transform::Base64Decode
+                .apply(&format!(" {encoded}\n"))
+                .unwrap(),
+            "编辑器"
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_base64() {
+        assert!(Transform::Base64Decode.apply("not base64!").is_err());
+        assert!(Transform::Base64Decode.apply("//79").is_err());
+    }
+}
@@ -0,0 +1,569 @@
+use crate::tab::Tab;
+use iced::widget::text_editor::{Action, Edit, Motion};
+use std::fmt;
+use std::sync::Arc;
+
+// 定义 Vim 模式下的编辑模式。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Mode {
+    #[default]
+    Normal, // 普通模式，按键作为命令。
+    Insert,     // 插入模式，按键直接输入文本。
+    Visual,     // 可视模式，移动光标时扩展选区。
+    VisualLine, // 可视行模式，按整行选择。
+    Command,    // 命令行模式，输入以冒号开头的命令。
+}
+
+impl fmt::Display for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Normal => "NORMAL",
+            Self::Insert => "INSERT",
+            Self::Visual => "VISUAL",
+            Self::VisualLine => "VISUAL LINE",
+            Self::Command => "COMMAND",
+        })
+    }
+}
+
+// 定义命令行中需要由编辑器完成的命令。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Request {
+    Write,     // :w 保存文件。
+    Quit,      // :q 关闭标签页，有未保存的修改时询问。
+    ForceQuit, // :q! 放弃修改并关闭标签页。
+    WriteQuit, // :wq 或 :x 保存后关闭标签页。
+}
+
+// 定义按键的处理结果。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Ignored,          // 不是 Vim 命令，交给编辑器照常处理。
+    Handled,          // 已经作为 Vim 命令处理。
+    Request(Request), // 需要编辑器执行的命令。
+}
+
+// 定义复制或删除的文本，按行复制的文本粘贴时作为独立的行。
+#[derive(Debug, Clone, Default)]
+struct Register {
+    text: String,   // 复制的文本，按行复制时不包括最后的换行符。
+    linewise: bool, // 是否按行复制。
+}
+
+// 定义 Vim 按键层的状态，位于键盘输入和编辑器动作之间。
+//
+// 文本编辑器把按键转换为动作后才交给应用程序，因此普通模式下输入的字符以
+// Insert 动作的形式到达这里，回车和退格分别为 Enter 和 Backspace 动作。
+// Esc 由快捷键中的 Dismiss 处理，用于回到普通模式。
+#[derive(Debug, Default)]
+pub struct Vim {
+    pub mode: Mode,         // 当前的编辑模式。
+    pub command: String,    // 命令行模式中输入的命令，不包括冒号。
+    count: String,          // 命令前输入的重复次数。
+    operator: Option<char>, // 等待动作的操作符 d、y、c，或者前缀 g。
+    register: Register,     // 最近一次复制或删除的文本。
+    anchor: usize,          // 可视行模式开始时所在的行。
+    head: usize,            // 可视行模式中光标所在的行。
+}
+
+impl Vim {
+    // 处理一个编辑器动作。插入模式和鼠标、滚动等不是按键的动作不做处理。
+    pub fn handle(&mut self, tab: &mut Tab, action: Action) -> Outcome {
+        if self.mode == Mode::Insert {
+            return Outcome::Ignored;
+        }
+
+        let key = match action {
+            Action::Edit(Edit::Insert(c)) => c,
+            Action::Edit(Edit::Enter) => '\n',
+            Action::Edit(Edit::Backspace) => '\u{8}',
+            Action::Edit(Edit::Delete) => '\u{7f}',
+            // 普通模式下忽略粘贴等其他修改。
+            Action::Edit(_) => return Outcome::Handled,
+            _ => return Outcome::Ignored,
+        };
+
+        match self.mode {
+            Mode::Command => self.command_line(tab, key),
+            Mode::Visual | Mode::VisualLine => {
+                self.visual(tab, key);
+                Outcome::Handled
+            }
+            _ => {
+                self.normal(tab, key);
+                Outcome::Handled
+            }
+        }
+    }
+
+    // 按下 Esc 时回到普通模式，取消输入到一半的命令和选区。
+    pub fn escape(&mut self, tab: &mut Tab) {
+        let (line, column) = position(tab);
+
+        match self.mode {
+            // 与 Vim 一样，离开插入模式时光标左移一个字符。
+            Mode::Insert => tab.move_cursor_to(line, column.saturating_sub(1)),
+            Mode::Visual | Mode::VisualLine => tab.move_cursor_to(line, column),
+            _ => {}
+        }
+
+        self.mode = Mode::Normal;
+        self.command.clear();
+        self.count.clear();
+        self.operator = None;
+    }
+
+    // 返回状态栏中显示的模式和输入到一半的命令。
+    pub fn status(&self) -> String {
+        match self.mode {
+            Mode::Command => format!(":{}", self.command),
+            Mode::Normal => {
+                let pending: String = self.count.chars().chain(self.operator).collect();
+
+                if pending.is_empty() {
+                    format!("-- {} --", self.mode)
+                } else {
+                    format!("-- {} -- {pending}", self.mode)
+                }
+            }
+            mode => format!("-- {mode} --"),
+        }
+    }
+
+    // 取出命令前输入的重复次数。
+    fn take_count(&mut self) -> Option<usize> {
+        std::mem::take(&mut self.count).parse().ok()
+    }
+
+    // 处理普通模式下的按键。
+    fn normal(&mut self, tab: &mut Tab, key: char) {
+        // 0 在没有输入次数时表示移到行首。
+        if key.is_ascii_digit() && (key != '0' || !self.count.is_empty()) {
+            self.count.push(key);
+            return;
+        }
+
+        let count = self.take_count();
+        let times = count.unwrap_or(1);
+
+        if let Some(operator) = self.operator.take() {
+            self.operate(tab, operator, key, count);
+            return;
+        }
+
+        let (line, column) = position(tab);
+
+        match key {
+            'i' => self.mode = Mode::Insert,
+            'a' => {
+                if column < line_length(tab, line) {
+                    tab.edit(Action::Move(Motion::Right));
+                }
+                self.mode = Mode::Insert;
+            }
+            'I' => {
+                tab.move_cursor_to(line, indentation(tab, line).chars().count());
+                self.mode = Mode::Insert;
+            }
+            'A' => {
+                tab.edit(Action::Move(Motion::End));
+                self.mode = Mode::Insert;
+            }
+            'o' => {
+                tab.edit(Action::Move(Motion::End));
+                tab.edit(Action::Edit(Edit::Enter));
+                self.mode = Mode::Insert;
+            }
+            'O' => {
+                let indentation = indentation(tab, line);
+
+                tab.move_cursor_to(line, 0);
+                tab.edit(Action::Edit(Edit::Paste(Arc::new(format!(
+                    "{indentation}\n"
+                )))));
+                tab.move_cursor_to(line, indentation.chars().count());
+                self.mode = Mode::Insert;
+            }
+            'v' => self.mode = Mode::Visual,
+            'V' => {
+                self.mode = Mode::VisualLine;
+                self.anchor = line;
+                self.head = line;
+                tab.select_lines(line, line);
+            }
+            'x' | '\u{7f}' => {
+                let length = line_length(tab, line);
+
+                for _ in 0..times.min(length - column.min(length)) {
+                    tab.edit(Action::Select(Motion::Right));
+                }
+                self.cut(tab);
+            }
+            'p' => self.paste(tab, true, times),
+            'P' => self.paste(tab, false, times),
+            'u' => {
+                for _ in 0..times {
+                    tab.undo();
+                }
+            }
+            'd' | 'y' | 'c' | 'g' => {
+                // 保留操作符前的次数，例如 3dd。
+                self.count = count.map(|count| count.to_string()).unwrap_or_default();
+                self.operator = Some(key);
+            }
+            ':' => {
+                self.mode = Mode::Command;
+                self.command.clear();
+            }
+            'G' => go_to_line(tab, count),
+            _ => {
+                if let Some(motion) = motion(key) {
+                    for _ in 0..times {
+                        tab.edit(Action::Move(motion));
+                    }
+                }
+            }
+        }
+    }
+
+    // 执行操作符与动作的组合：dd、yy、cc 作用于整行，dj、dk 作用于多行，其他动作作用于移动经过的文本。
+    fn operate(&mut self, tab: &mut Tab, operator: char, key: char, count: Option<usize>) {
+        let times = count.unwrap_or(1);
+        let (line, column) = position(tab);
+        let last_line = tab.context.line_count().saturating_sub(1);
+
+        if operator == 'g' {
+            if key == 'g' {
+                match count {
+                    Some(_) => go_to_line(tab, count),
+                    None => tab.edit(Action::Move(Motion::DocumentStart)),
+                }
+            }
+            return;
+        }
+
+        let lines = match key {
+            _ if key == operator => Some((line, (line + times - 1).min(last_line))),
+            'j' | '\n' => Some((line, (line + times).min(last_line))),
+            'k' => Some((line.saturating_sub(times), line)),
+            _ => None,
+        };
+
+        if let Some((first, last)) = lines {
+            self.register = Register {
+                text: tab.lines(first, last).join("\n"),
+                linewise: true,
+            };
+
+            match operator {
+                'd' => delete_lines(tab, first, last),
+                'c' => {
+                    let indentation = indentation(tab, first);
+
+                    tab.replace_lines(first, last, indentation);
+                    self.mode = Mode::Insert;
+                }
+                _ => tab.move_cursor_to(first, column.min(line_length(tab, first))),
+            }
+            return;
+        }
+
+        let Some(motion) = motion(key) else {
+            return;
+        };
+
+        for _ in 0..times {
+            tab.edit(Action::Select(motion));
+        }
+
+        match operator {
+            'd' => self.cut(tab),
+            'c' => {
+                self.cut(tab);
+                self.mode = Mode::Insert;
+            }
+            _ => {
+                self.yank(tab, false);
+                tab.move_cursor_to(line, column);
+            }
+        }
+    }
+
+    // 处理可视模式下的按键，移动光标时扩展选区，操作符作用于选中的文本。
+    fn visual(&mut self, tab: &mut Tab, key: char) {
+        if key.is_ascii_digit() && (key != '0' || !self.count.is_empty()) {
+            self.count.push(key);
+            return;
+        }
+
+        let count = self.take_count();
+        let times = count.unwrap_or(1);
+        let is_linewise = self.mode == Mode::VisualLine;
+        let (line, column) = position(tab);
+
+        match key {
+            'd' | 'x' | '\u{7f}' => {
+                self.cut_selection(tab, is_linewise);
+                self.mode = Mode::Normal;
+            }
+            'c' => {
+                if is_linewise {
+                    let first = self.anchor.min(self.head);
+                    self.yank(tab, true);
+
+                    let indentation = indentation(tab, first);
+                    tab.replace_lines(first, self.anchor.max(self.head), indentation);
+                } else {
+                    self.cut(tab);
+                }
+                self.mode = Mode::Insert;
+            }
+            'y' => {
+                self.yank(tab, is_linewise);
+
+                let first = self.anchor.min(self.head);
+                if is_linewise {
+                    tab.move_cursor_to(first, 0);
+                } else {
+                    tab.move_cursor_to(line, column);
+                }
+                self.mode = Mode::Normal;
+            }
+            'p' | 'P' => {
+                let register = self.register.clone();
+
+                if is_linewise {
+                    let (first, last) = (self.anchor.min(self.head), self.anchor.max(self.head));
+                    tab.replace_lines(first, last, register.text);
+                } else {
+                    tab.edit(Action::Edit(Edit::Paste(Arc::new(register.text))));
+                }
+                self.mode = Mode::Normal;
+            }
+            'v' | 'V' => {
+                let mode = if key == 'v' {
+                    Mode::Visual
+                } else {
+                    Mode::VisualLine
+                };
+
+                tab.move_cursor_to(line, column);
+
+                if mode == self.mode {
+                    self.mode = Mode::Normal;
+                } else if mode == Mode::VisualLine {
+                    self.mode = mode;
+                    self.anchor = line;
+                    self.head = line;
+                    tab.select_lines(line, line);
+                } else {
+                    self.mode = mode;
+                }
+            }
+            ':' => {
+                tab.move_cursor_to(line, column);
+                self.mode = Mode::Command;
+                self.command.clear();
+            }
+            _ if is_linewise => {
+                let last_line = tab.context.line_count().saturating_sub(1);
+
+                self.head = match key {
+                    'j' | '\n' => (self.head + times).min(last_line),
+                    'k' => self.head.saturating_sub(times),
+                    'G' => count.map_or(last_line, |line| line.saturating_sub(1).min(last_line)),
+                    _ => return,
+                };
+
+                tab.select_lines(self.anchor.min(self.head), self.anchor.max(self.head));
+            }
+            _ => {
+                if let Some(motion) = motion(key) {
+                    for _ in 0..times {
+                        tab.edit(Action::Select(motion));
+                    }
+                }
+            }
+        }
+    }
+
+    // 处理命令行模式下的按键，回车执行命令，删除冒号时回到普通模式。
+    fn command_line(&mut self, tab: &mut Tab, key: char) -> Outcome {
+        match key {
+            '\n' => {
+                self.mode = Mode::Normal;
+
+                let command = std::mem::take(&mut self.command);
+
+                match command.trim() {
+                    "w" => Outcome::Request(Request::Write),
+                    "q" => Outcome::Request(Request::Quit),
+                    "q!" => Outcome::Request(Request::ForceQuit),
+                    "wq" | "x" => Outcome::Request(Request::WriteQuit),
+                    command => {
+                        // :数字 跳转到指定的行。
+                        if let Ok(line) = command.parse() {
+                            go_to_line(tab, Some(line));
+                        }
+                        Outcome::Handled
+                    }
+                }
+            }
+            '\u{8}' => {
+                if self.command.pop().is_none() {
+                    self.mode = Mode::Normal;
+                }
+                Outcome::Handled
+            }
+            '\u{7f}' | '\t' => Outcome::Handled,
+            c => {
+                self.command.push(c);
+                Outcome::Handled
+            }
+        }
+    }
+
+    // 复制选中的文本。
+    fn yank(&mut self, tab: &Tab, linewise: bool) {
+        if linewise {
+            let (first, last) = (self.anchor.min(self.head), self.anchor.max(self.head));
+
+            self.register = Register {
+                text: tab.lines(first, last).join("\n"),
+                linewise: true,
+            };
+        } else if let Some(text) = tab.context.selection() {
+            self.register = Register {
+                text,
+                linewise: false,
+            };
+        }
+    }
+
+    // 剪切选中的文本。
+    fn cut(&mut self, tab: &mut Tab) {
+        if tab.context.selection().is_some() {
+            self.yank(tab, false);
+            tab.edit(Action::Edit(Edit::Delete));
+        }
+    }
+
+    // 剪切可视模式中选中的文本，可视行模式下删除整行。
+    fn cut_selection(&mut self, tab: &mut Tab, linewise: bool) {
+        if linewise {
+            self.yank(tab, true);
+            delete_lines(tab, self.anchor.min(self.head), self.anchor.max(self.head));
+        } else {
+            self.cut(tab);
+        }
+    }
+
+    // 在光标之后或之前粘贴复制的文本，按行复制的文本粘贴到下一行或上一行。
+    fn paste(&mut self, tab: &mut Tab, after: bool, times: usize) {
+        let Register { text, linewise } = &self.register;
+
+        if text.is_empty() {
+            return;
+        }
+
+        let (line, column) = position(tab);
+
+        if *linewise {
+            let text = vec![text.as_str(); times].join("\n");
+
+            if after {
+                tab.move_cursor_to(line, line_length(tab, line));
+                tab.edit(Action::Edit(Edit::Paste(Arc::new(format!("\n{text}")))));
+                tab.move_cursor_to(line + 1, 0);
+            } else {
+                tab.move_cursor_to(line, 0);
+                tab.edit(Action::Edit(Edit::Paste(Arc::new(format!("{text}\n")))));
+                tab.move_cursor_to(line, 0);
+            }
+        } else {
+            if after && column < line_length(tab, line) {
+                tab.edit(Action::Move(Motion::Right));
+            }
+
+            tab.edit(Action::Edit(Edit::Paste(Arc::new(text.repeat(times)))));
+        }
+    }
+}
+
+// 将按键转换为光标移动。w 和 b 使用编辑器自身的按单词移动。
+fn motion(key: char) -> Option<Motion> {
+    match key {
+        'h' | '\u{8}' => Some(Motion::Left),
+        'l' | ' ' => Some(Motion::Right),
+        'j' | '\n' => Some(Motion::Down),
+        'k' => Some(Motion::Up),
+        'w' | 'e' => Some(Motion::WordRight),
+        'b' => Some(Motion::WordLeft),
+        '0' | '^' => Some(Motion::Home),
+        '$' => Some(Motion::End),
+        'G' => Some(Motion::DocumentEnd),
+        _ => None,
+    }
+}
+
+// 跳转到指定的行（从 1 开始），没有指定时跳转到最后一行。
+fn go_to_line(tab: &mut Tab, line: Option<usize>) {
+    match line {
+        Some(line) => {
+            let last = tab.context.line_count().saturating_sub(1);
+            let line = line.saturating_sub(1).min(last);
+
+            tab.move_cursor_to(line, indentation(tab, line).chars().count());
+        }
+        None => tab.edit(Action::Move(Motion::DocumentEnd)),
+    }
+}
+
+// 删除指定范围内（包含两端）的行，包括行尾的换行符。
+fn delete_lines(tab: &mut Tab, first: usize, last: usize) {
+    let line_count = tab.context.line_count();
+
+    if last + 1 < line_count {
+        let next = tab.lines(last + 1, last + 1).concat();
+
+        tab.replace_lines(first, last + 1, next);
+        tab.move_cursor_to(first, indentation(tab, first).chars().count());
+    } else if first > 0 {
+        let previous = tab.lines(first - 1, first - 1).concat();
+
+        tab.replace_lines(first - 1, last, previous);
+        tab.move_cursor_to(first - 1, indentation(tab, first - 1).chars().count());
+    } else {
+        tab.replace_lines(first, last, String::new());
+    }
+}
+
+// 返回编辑器的光标所在的行和字符列。
+fn position(tab: &Tab) -> (usize, usize) {
+    let (line, column) = tab.context.cursor_position();
+
+    let column = tab.context.line(line).map_or(0, |text| {
+        text.get(..column).unwrap_or(&text).chars().count()
+    });
+
+    (line, column)
+}
+
+// 返回指定行的字符数。
+fn line_length(tab: &Tab, line: usize) -> usize {
+    tab.context
+        .line(line)
+        .map_or(0, |text| text.chars().count())
+}
+
+// 返回指定行开头的缩进。
+fn indentation(tab: &Tab, line: usize) -> String {
+    tab.context
+        .line(line)
+        .map(|text| {
+            text.chars()
+                .take_while(|c| *c == ' ' || *c == '\t')
+                .collect()
+        })
+        .unwrap_or_default()
+}
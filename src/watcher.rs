@@ -0,0 +1,76 @@
+use iced::futures::channel::mpsc;
+use iced::futures::{SinkExt, StreamExt};
+use iced::{subscription, Subscription};
+use notify::{EventKind, RecursiveMode, Watcher};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+// 创建一个订阅来监听文件在磁盘上的变化，文件变化时用 on_change 生成消息。
+//
+// 为了同时捕获“写入临时文件再重命名”的保存方式，监听的是文件所在的目录，
+// 然后只转发属于这些文件的事件。监听的文件发生变化时订阅会重新创建。
+pub fn watch<Message: Send + 'static>(
+    paths: Vec<PathBuf>,
+    on_change: fn(PathBuf) -> Message,
+) -> Subscription<Message> {
+    if paths.is_empty() {
+        return Subscription::none();
+    }
+
+    let id = (paths.clone(), on_change);
+
+    subscription::channel(id, 100, move |mut output| async move {
+        let (sender, mut receiver) = mpsc::unbounded();
+
+        let watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            if let Ok(event) = event {
+                let _ = sender.unbounded_send(event);
+            }
+        });
+
+        let Ok(mut watcher) = watcher else {
+            // 当前平台无法监听文件时保持订阅空闲。
+            loop {
+                std::future::pending::<()>().await;
+            }
+        };
+
+        // 通知中的路径是绝对路径，比较前先规范化监听的文件的路径。
+        let watched: Vec<(PathBuf, PathBuf)> = paths
+            .iter()
+            .map(|path| {
+                (
+                    path.canonicalize().unwrap_or_else(|_| path.clone()),
+                    path.clone(),
+                )
+            })
+            .collect();
+
+        let directories: BTreeSet<&Path> = watched
+            .iter()
+            .filter_map(|(canonical, _)| canonical.parent())
+            .collect();
+
+        for directory in directories {
+            let _ = watcher.watch(directory, RecursiveMode::NonRecursive);
+        }
+
+        loop {
+            let Some(event) = receiver.next().await else {
+                std::future::pending::<()>().await;
+                continue;
+            };
+
+            if !matches!(event.kind, EventKind::Modify(_) | EventKind::Create(_)) {
+                continue;
+            }
+
+            for changed in event.paths {
+                if let Some((_, path)) = watched.iter().find(|(canonical, _)| *canonical == changed)
+                {
+                    let _ = output.send(on_change(path.clone())).await;
+                }
+            }
+        }
+    })
+}
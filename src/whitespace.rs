@@ -0,0 +1,133 @@
+use crate::gutter::{self, Metrics};
+use crate::i18n::tr;
+use crate::{CHARACTER_WIDTH, EDITOR_PADDING, LINE_HEIGHT};
+use iced::{Color, Rectangle};
+use ropey::Rope;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// 定义显示空白字符的范围。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Mode {
+    #[default]
+    Off, // 不显示空白字符。
+    Selection, // 只显示选中的文本中的空白字符。
+    All,       // 显示所有的空白字符。
+}
+
+impl Mode {
+    // 所有可选的范围，用于设置界面中的选择列表。
+    pub const ALL: &'static [Self] = &[Self::Off, Self::Selection, Self::All];
+
+    // 返回切换显示时的下一种范围，依次为全部、仅选区和不显示。
+    pub fn next(self) -> Self {
+        match self {
+            Self::Off => Self::All,
+            Self::All => Self::Selection,
+            Self::Selection => Self::Off,
+        }
+    }
+}
+
+impl fmt::Display for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(tr(match self {
+            Self::Off => "Off",
+            Self::Selection => "Selection Only",
+            Self::All => "All",
+        }))
+    }
+}
+
+// 定义空白字符的种类。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Space,      // 空格，显示为一个点。
+    Tab,        // 制表符，显示为一个箭头。
+    LineEnding, // 行尾的换行符，显示为一个回车符号。
+}
+
+// 定义一个需要显示的空白字符。
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mark {
+    pub kind: Kind,        // 空白字符的种类。
+    pub bounds: Rectangle, // 空白字符相对于编辑器占据的区域。
+}
+
+// 定义可见的各行中需要显示的空白字符。
+#[derive(Debug, Clone, Default)]
+pub struct Marks {
+    pub marks: Vec<Mark>, // 需要显示的空白字符。
+    pub color: Color,     // 绘制空白字符的颜色。
+}
+
+// 返回可见的各行中需要显示的空白字符及其在编辑器中的区域。
+//
+// 与拼写检查的下划线相同，位置按估算的字符宽度和行高计算，制表符占据 tab_width 列。
+// range 为需要显示的范围（开始和结束的行和字符列），None 表示显示所有的空白字符。
+pub fn marks(
+    rope: &Rope,
+    lines: &[gutter::Line],
+    metrics: Metrics,
+    tab_width: usize,
+    range: Option<((usize, usize), (usize, usize))>,
+    line_endings: bool,
+) -> Vec<Mark> {
+    let columns = metrics.columns.filter(|columns| *columns > 0);
+    let is_included = |position: (usize, usize)| {
+        range.map_or(true, |(start, end)| start <= position && position < end)
+    };
+
+    let mut marks = Vec::new();
+
+    for line in lines.iter().filter(|line| line.number < rope.len_lines()) {
+        let text = rope.line(line.number);
+        let mut column = 0;
+        let mut index = 0;
+
+        // 按显示的列计算位置，自动换行时按列数估算所在的显示行。
+        let bounds = |column: usize, width: usize| {
+            let (row, column) = match columns {
+                Some(columns) => (column / columns, column % columns),
+                None => (0, column),
+            };
+
+            Rectangle {
+                x: EDITOR_PADDING + column as f32 * CHARACTER_WIDTH,
+                y: line.y + row as f32 * LINE_HEIGHT,
+                width: width as f32 * CHARACTER_WIDTH,
+                height: LINE_HEIGHT,
+            }
+        };
+
+        for c in text.chars() {
+            let (kind, width) = match c {
+                ' ' => (Some(Kind::Space), 1),
+                '\t' => (Some(Kind::Tab), tab_width.max(1)),
+                '\n' | '\r' => break,
+                _ => (None, 1),
+            };
+
+            if let Some(kind) = kind.filter(|_| is_included((line.number, index))) {
+                marks.push(Mark {
+                    kind,
+                    bounds: bounds(column, width),
+                });
+            }
+
+            column += width;
+            index += 1;
+        }
+
+        let has_line_ending = line.number + 1 < rope.len_lines();
+
+        if line_endings && has_line_ending && is_included((line.number, index)) {
+            marks.push(Mark {
+                kind: Kind::LineEnding,
+                bounds: bounds(column, 1),
+            });
+        }
+    }
+
+    marks
+}
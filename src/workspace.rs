@@ -0,0 +1,220 @@
+use crate::i18n::trf;
+use crate::palette;
+use crate::{Error, FileError, Message, Operation};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+// 每个工作区最多记住的最近打开的文件数量。
+const MAX_RECENT: usize = 20;
+
+// 定义工作区：一组根目录，以及在这些目录中最近打开的文件。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Workspace {
+    pub name: String,        // 工作区的名称，默认为第一个根目录的名称。
+    pub roots: Vec<PathBuf>, // 工作区包含的根目录。
+    #[serde(default)]
+    pub recent: Vec<PathBuf>, // 最近打开的文件，最近的在前。
+}
+
+// 定义记住的所有工作区。
+//
+// 保存在清单文件 workspaces.toml 中，格式如下：
+//
+// active = "iced-demo"
+//
+// [[workspace]]
+// name = "iced-demo"
+// roots = ["/home/me/iced-demo", "/home/me/iced"]
+// recent = ["/home/me/iced-demo/src/main.rs"]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Workspaces {
+    #[serde(default)]
+    active: Option<String>, // 当前打开的工作区名称。
+    #[serde(default, rename = "workspace")]
+    workspaces: Vec<Workspace>, // 所有工作区。
+}
+
+impl Workspace {
+    // 判断路径是否位于工作区的某个根目录中。
+    fn contains(&self, path: &Path) -> bool {
+        self.roots.iter().any(|root| path.starts_with(root))
+    }
+}
+
+impl Workspaces {
+    // 解析 TOML 格式的清单文件。
+    pub fn from_toml(contents: &str) -> Result<Self, Error> {
+        toml::from_str(contents).map_err(|error| Error::InvalidConfig(error.message().to_string()))
+    }
+
+    // 将所有工作区序列化为 TOML 格式的字符串。
+    pub fn to_toml(&self) -> String {
+        toml::to_string(self).unwrap_or_default()
+    }
+
+    // 返回当前打开的工作区。
+    pub fn active(&self) -> Option<&Workspace> {
+        let name = self.active.as_deref()?;
+
+        self.workspaces
+            .iter()
+            .find(|workspace| workspace.name == name)
+    }
+
+    fn active_mut(&mut self) -> Option<&mut Workspace> {
+        let name = self.active.as_deref()?;
+
+        self.workspaces
+            .iter_mut()
+            .find(|workspace| workspace.name == name)
+    }
+
+    // 将目录作为工作区打开。
+    //
+    // 目录已经是某个工作区的根目录时切换到该工作区，否则以目录名称创建新的工作区。
+    pub fn open(&mut self, folder: PathBuf) {
+        let index = match self
+            .workspaces
+            .iter()
+            .position(|workspace| workspace.roots.contains(&folder))
+        {
+            Some(index) => index,
+            None => {
+                let name = self.unique_name(&folder);
+
+                self.workspaces.push(Workspace {
+                    name,
+                    roots: vec![folder],
+                    recent: Vec::new(),
+                });
+                self.workspaces.len() - 1
+            }
+        };
+
+        self.active = Some(self.workspaces[index].name.clone());
+    }
+
+    // 为新的工作区返回不与已有工作区重复的名称。
+    fn unique_name(&self, folder: &Path) -> String {
+        let base = folder
+            .file_name()
+            .and_then(|name| name.to_str())
+            .map_or_else(|| folder.display().to_string(), str::to_string);
+
+        let is_taken = |name: &str| {
+            self.workspaces
+                .iter()
+                .any(|workspace| workspace.name == name)
+        };
+
+        if !is_taken(&base) {
+            return base;
+        }
+
+        (2..)
+            .map(|number| format!("{base} ({number})"))
+            .find(|name| !is_taken(name))
+            .unwrap_or(base)
+    }
+
+    // 将目录添加到当前的工作区，没有打开工作区时将目录作为新的工作区打开。
+    pub fn add_root(&mut self, folder: PathBuf) {
+        match self.active_mut() {
+            Some(workspace) => {
+                if !workspace.roots.contains(&folder) {
+                    workspace.roots.push(folder);
+                }
+            }
+            None => self.open(folder),
+        }
+    }
+
+    // 切换到指定名称的工作区，找不到时返回 false。
+    pub fn switch(&mut self, name: &str) -> bool {
+        let exists = self
+            .workspaces
+            .iter()
+            .any(|workspace| workspace.name == name);
+
+        if exists {
+            self.active = Some(name.to_string());
+        }
+
+        exists
+    }
+
+    // 关闭当前的工作区，工作区本身仍然被记住。
+    pub fn close(&mut self) {
+        self.active = None;
+    }
+
+    // 将文件记录为当前工作区中最近打开的文件，不在工作区中的文件被忽略。
+    pub fn remember(&mut self, path: &Path) -> bool {
+        let Some(workspace) = self
+            .active_mut()
+            .filter(|workspace| workspace.contains(path))
+        else {
+            return false;
+        };
+
+        workspace.recent.retain(|recent| recent != path);
+        workspace.recent.insert(0, path.to_path_buf());
+        workspace.recent.truncate(MAX_RECENT);
+        true
+    }
+
+    // 返回在命令面板中切换工作区的各条命令，当前的工作区除外。
+    pub fn commands(&self) -> Vec<palette::Entry> {
+        self.workspaces
+            .iter()
+            .filter(|workspace| Some(&workspace.name) != self.active.as_ref())
+            .map(|workspace| palette::Entry {
+                label: trf("Workspace: {name}", &[("name", &workspace.name)]),
+                shortcut: None,
+                message: Message::WorkspaceSelected(workspace.name.clone()),
+            })
+            .collect()
+    }
+
+    // 返回在命令面板中打开当前工作区最近文件的各条命令，以相对于根目录的路径显示。
+    pub fn recent_commands(&self) -> Vec<palette::Entry> {
+        let Some(workspace) = self.active() else {
+            return Vec::new();
+        };
+
+        workspace
+            .recent
+            .iter()
+            .map(|path| {
+                let label = workspace
+                    .roots
+                    .iter()
+                    .find_map(|root| path.strip_prefix(root).ok())
+                    .unwrap_or(path);
+
+                palette::Entry {
+                    label: label.display().to_string(),
+                    shortcut: None,
+                    message: Message::FileClicked(path.clone()),
+                }
+            })
+            .collect()
+    }
+}
+
+// 定义一个异步函数来加载工作区清单，清单文件不存在时没有任何工作区。
+pub async fn load(path: Option<PathBuf>) -> Result<Workspaces, Error> {
+    let Some(path) = path else {
+        return Ok(Workspaces::default());
+    };
+
+    match tokio::fs::read_to_string(&path).await {
+        Ok(contents) => Workspaces::from_toml(&contents),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(Workspaces::default()),
+        Err(error) => Err(Error::FileFailed(FileError::new(
+            Operation::Read,
+            &path,
+            &error,
+        ))),
+    }
+}